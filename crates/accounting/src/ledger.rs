@@ -1,3 +1,5 @@
+use std::collections::{HashMap, HashSet};
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -5,7 +7,7 @@ use forgeerp_core::{Aggregate, AggregateRoot, AggregateId, DomainError, TenantId
 use forgeerp_events::Event;
 
 /// High-level account kind (determines normal balance side).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum AccountKind {
     Asset,
@@ -33,15 +35,44 @@ pub struct JournalEntryLine {
     pub is_debit: bool,
 }
 
+impl JournalEntryLine {
+    /// Sort lines into canonical order: account code, debits before credits, then amount
+    /// (account name and kind break any remaining tie). Posted entries store lines in this
+    /// order, so the same logical entry always serializes to the same bytes.
+    pub fn canonicalize(lines: &mut [JournalEntryLine]) {
+        lines.sort_by(|a, b| {
+            a.account
+                .code
+                .cmp(&b.account.code)
+                .then(b.is_debit.cmp(&a.is_debit))
+                .then(a.amount.cmp(&b.amount))
+                .then_with(|| a.account.name.cmp(&b.account.name))
+                .then(a.account.kind.cmp(&b.account.kind))
+        });
+    }
+}
+
 /// Ledger identifier (aggregate id).
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct LedgerId(pub AggregateId);
 
+/// Namespace of the default ledger ids derived from tenant ids.
+const DEFAULT_LEDGER_NAMESPACE: uuid::Uuid = uuid::uuid!("5c0f6b0e-2a3d-4f0b-9d6e-6b1f4c2a8e71");
+
 impl LedgerId {
     pub fn new(id: AggregateId) -> Self {
         Self(id)
     }
+
+    /// The tenant's default ledger: the same id on every start (UUIDv5 of the tenant id),
+    /// so postings keep landing in one stream and its entry-id deduplication holds.
+    pub fn default_for(tenant_id: TenantId) -> Self {
+        Self(AggregateId::from_uuid(uuid::Uuid::new_v5(
+            &DEFAULT_LEDGER_NAMESPACE,
+            tenant_id.as_uuid().as_bytes(),
+        )))
+    }
 }
 
 impl core::fmt::Display for LedgerId {
@@ -52,14 +83,23 @@ impl core::fmt::Display for LedgerId {
 
 /// Aggregate root: Ledger (double-entry journal).
 ///
-/// Note: Ledger does NOT hold balances; it only tracks identity + tenant.
-/// Balances are derived from projections over `JournalEntryPosted` events.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Note: Ledger does NOT hold balances; it tracks identity, tenant, currency, the lines of
+/// posted entries (so they can be reversed) and closed accounting periods. Balances are
+/// derived from projections over `JournalEntryPosted` / `JournalEntryReversed` events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Ledger {
     id: LedgerId,
     tenant_id: Option<TenantId>,
     version: u64,
     created: bool,
+    /// Currency of the books, fixed by the first entry that names one.
+    currency: Option<String>,
+    /// Lines of every entry already posted, by entry id (re-posting the same entry is a no-op).
+    posted_entries: HashMap<uuid::Uuid, Vec<JournalEntryLine>>,
+    /// Entry ids that have been reversed.
+    reversed_entries: HashSet<uuid::Uuid>,
+    /// End dates of closed periods, oldest first (reopening pops the latest).
+    closed_periods: Vec<DateTime<Utc>>,
 }
 
 impl Ledger {
@@ -70,6 +110,10 @@ impl Ledger {
             tenant_id: None,
             version: 0,
             created: false,
+            currency: None,
+            posted_entries: HashMap::new(),
+            reversed_entries: HashSet::new(),
+            closed_periods: Vec::new(),
         }
     }
 
@@ -80,6 +124,16 @@ impl Ledger {
     pub fn tenant_id(&self) -> Option<TenantId> {
         self.tenant_id
     }
+
+    /// ISO code every entry is kept in (`None` until an entry names one).
+    pub fn currency(&self) -> Option<&str> {
+        self.currency.as_deref()
+    }
+
+    /// End of the latest closed period; entries dated on or before it are rejected.
+    pub fn last_closed_period(&self) -> Option<DateTime<Utc>> {
+        self.closed_periods.last().copied()
+    }
 }
 
 impl AggregateRoot for Ledger {
@@ -101,13 +155,57 @@ pub struct PostJournalEntry {
     pub ledger_id: LedgerId,
     pub entry_id: uuid::Uuid,
     pub lines: Vec<JournalEntryLine>,
+    /// ISO code of the line amounts; must match the ledger's once it has one.
+    #[serde(default)]
+    pub currency: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+    pub description: Option<String>,
+}
+
+/// Command: ReverseJournalEntry.
+///
+/// Posts a new entry (`reversal_entry_id`) with the debit/credit sides of the original
+/// entry's lines swapped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReverseJournalEntry {
+    pub tenant_id: TenantId,
+    pub ledger_id: LedgerId,
+    /// Entry being reversed.
+    pub entry_id: uuid::Uuid,
+    pub reversal_entry_id: uuid::Uuid,
     pub occurred_at: DateTime<Utc>,
     pub description: Option<String>,
 }
 
+/// Command: ClosePeriod.
+///
+/// Locks the ledger against entries dated on or before `period_end`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClosePeriod {
+    pub tenant_id: TenantId,
+    pub ledger_id: LedgerId,
+    pub period_end: DateTime<Utc>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Command: ReopenPeriod.
+///
+/// Reopens the latest closed period (`period_end` must match it); the lock falls back
+/// to the prior close, if any.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReopenPeriod {
+    pub tenant_id: TenantId,
+    pub ledger_id: LedgerId,
+    pub period_end: DateTime<Utc>,
+    pub occurred_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum JournalCommand {
     PostJournalEntry(PostJournalEntry),
+    ReverseJournalEntry(ReverseJournalEntry),
+    ClosePeriod(ClosePeriod),
+    ReopenPeriod(ReopenPeriod),
 }
 
 /// Event: JournalEntryPosted.
@@ -117,19 +215,84 @@ pub struct JournalEntryPosted {
     pub ledger_id: LedgerId,
     pub entry_id: uuid::Uuid,
     pub lines: Vec<JournalEntryLine>,
+    /// Absent on entries posted before the currency was recorded.
+    #[serde(default)]
+    pub currency: Option<String>,
+    pub description: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Event: JournalEntryReversed.
+///
+/// `lines` are the inverse of the reversed entry's lines, in canonical order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntryReversed {
+    pub tenant_id: TenantId,
+    pub ledger_id: LedgerId,
+    /// Id of the reversing entry.
+    pub entry_id: uuid::Uuid,
+    pub reversed_entry_id: uuid::Uuid,
+    pub lines: Vec<JournalEntryLine>,
+    /// The ledger's currency (absent when it has none recorded).
+    #[serde(default)]
+    pub currency: Option<String>,
     pub description: Option<String>,
     pub occurred_at: DateTime<Utc>,
 }
 
+/// Event: PeriodClosed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeriodClosed {
+    pub tenant_id: TenantId,
+    pub ledger_id: LedgerId,
+    pub period_end: DateTime<Utc>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Event: PeriodReopened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeriodReopened {
+    pub tenant_id: TenantId,
+    pub ledger_id: LedgerId,
+    pub period_end: DateTime<Utc>,
+    pub occurred_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LedgerEvent {
     JournalEntryPosted(JournalEntryPosted),
+    JournalEntryReversed(JournalEntryReversed),
+    PeriodClosed(PeriodClosed),
+    PeriodReopened(PeriodReopened),
+}
+
+impl LedgerEvent {
+    /// Lines posted by this event (the inverse lines for a reversal, none for period events).
+    pub fn lines(&self) -> &[JournalEntryLine] {
+        match self {
+            LedgerEvent::JournalEntryPosted(e) => &e.lines,
+            LedgerEvent::JournalEntryReversed(e) => &e.lines,
+            LedgerEvent::PeriodClosed(_) | LedgerEvent::PeriodReopened(_) => &[],
+        }
+    }
+
+    pub fn tenant_id(&self) -> TenantId {
+        match self {
+            LedgerEvent::JournalEntryPosted(e) => e.tenant_id,
+            LedgerEvent::JournalEntryReversed(e) => e.tenant_id,
+            LedgerEvent::PeriodClosed(e) => e.tenant_id,
+            LedgerEvent::PeriodReopened(e) => e.tenant_id,
+        }
+    }
 }
 
 impl Event for LedgerEvent {
     fn event_type(&self) -> &'static str {
         match self {
             LedgerEvent::JournalEntryPosted(_) => "accounting.ledger.journal_entry_posted",
+            LedgerEvent::JournalEntryReversed(_) => "accounting.ledger.journal_entry_reversed",
+            LedgerEvent::PeriodClosed(_) => "accounting.ledger.period_closed",
+            LedgerEvent::PeriodReopened(_) => "accounting.ledger.period_reopened",
         }
     }
 
@@ -140,6 +303,9 @@ impl Event for LedgerEvent {
     fn occurred_at(&self) -> DateTime<Utc> {
         match self {
             LedgerEvent::JournalEntryPosted(e) => e.occurred_at,
+            LedgerEvent::JournalEntryReversed(e) => e.occurred_at,
+            LedgerEvent::PeriodClosed(e) => e.occurred_at,
+            LedgerEvent::PeriodReopened(e) => e.occurred_at,
         }
     }
 }
@@ -157,6 +323,25 @@ impl Aggregate for Ledger {
                     self.tenant_id = Some(e.tenant_id);
                     self.created = true;
                 }
+                if self.currency.is_none() {
+                    self.currency = e.currency.clone();
+                }
+                self.posted_entries.insert(e.entry_id, e.lines.clone());
+            }
+            LedgerEvent::JournalEntryReversed(e) => {
+                self.posted_entries.insert(e.entry_id, e.lines.clone());
+                self.reversed_entries.insert(e.reversed_entry_id);
+            }
+            LedgerEvent::PeriodClosed(e) => {
+                self.id = e.ledger_id;
+                if self.tenant_id.is_none() {
+                    self.tenant_id = Some(e.tenant_id);
+                    self.created = true;
+                }
+                self.closed_periods.push(e.period_end);
+            }
+            LedgerEvent::PeriodReopened(_) => {
+                self.closed_periods.pop();
             }
         }
 
@@ -166,6 +351,9 @@ impl Aggregate for Ledger {
     fn handle(&self, command: &Self::Command) -> Result<Vec<Self::Event>, Self::Error> {
         match command {
             JournalCommand::PostJournalEntry(cmd) => self.handle_post(cmd),
+            JournalCommand::ReverseJournalEntry(cmd) => self.handle_reverse(cmd),
+            JournalCommand::ClosePeriod(cmd) => self.handle_close_period(cmd),
+            JournalCommand::ReopenPeriod(cmd) => self.handle_reopen_period(cmd),
         }
     }
 }
@@ -181,9 +369,37 @@ impl Ledger {
         Ok(())
     }
 
+    /// Reject entries dated inside a closed period.
+    fn ensure_period_open(&self, occurred_at: DateTime<Utc>) -> Result<(), DomainError> {
+        match self.last_closed_period() {
+            Some(period_end) if occurred_at <= period_end => Err(DomainError::invariant(format!(
+                "posting date {occurred_at} falls in a period closed through {period_end}"
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reject entries in another currency than the books (their amounts cannot be summed).
+    fn ensure_currency(&self, currency: Option<&str>) -> Result<(), DomainError> {
+        match (self.currency.as_deref(), currency) {
+            (Some(books), Some(given)) if books != given => Err(DomainError::validation(format!(
+                "currency {given} does not match the ledger currency {books}"
+            ))),
+            _ => Ok(()),
+        }
+    }
+
     fn handle_post(&self, cmd: &PostJournalEntry) -> Result<Vec<LedgerEvent>, DomainError> {
         self.ensure_tenant(cmd.tenant_id)?;
 
+        // Idempotent by entry id: a redelivered posting decides no events.
+        if self.posted_entries.contains_key(&cmd.entry_id) {
+            return Ok(vec![]);
+        }
+
+        self.ensure_period_open(cmd.occurred_at)?;
+        self.ensure_currency(cmd.currency.as_deref())?;
+
         if cmd.lines.is_empty() {
             return Err(DomainError::validation("journal entry must have lines"));
         }
@@ -206,15 +422,97 @@ impl Ledger {
             return Err(DomainError::invariant("debits must equal credits"));
         }
 
+        let mut lines = cmd.lines.clone();
+        JournalEntryLine::canonicalize(&mut lines);
+
         Ok(vec![LedgerEvent::JournalEntryPosted(JournalEntryPosted {
             tenant_id: cmd.tenant_id,
             ledger_id: cmd.ledger_id,
             entry_id: cmd.entry_id,
-            lines: cmd.lines.clone(),
+            lines,
+            currency: cmd.currency.clone().or_else(|| self.currency.clone()),
             description: cmd.description.clone(),
             occurred_at: cmd.occurred_at,
         })])
     }
+
+    fn handle_reverse(&self, cmd: &ReverseJournalEntry) -> Result<Vec<LedgerEvent>, DomainError> {
+        self.ensure_tenant(cmd.tenant_id)?;
+
+        let original = self
+            .posted_entries
+            .get(&cmd.entry_id)
+            .ok_or_else(DomainError::not_found)?;
+        if self.reversed_entries.contains(&cmd.entry_id) {
+            return Err(DomainError::conflict("journal entry is already reversed"));
+        }
+        if self.posted_entries.contains_key(&cmd.reversal_entry_id) {
+            return Err(DomainError::conflict("reversal entry id is already in use"));
+        }
+        self.ensure_period_open(cmd.occurred_at)?;
+
+        // Swapping sides keeps debits equal to credits.
+        let mut lines: Vec<JournalEntryLine> = original
+            .iter()
+            .map(|l| JournalEntryLine {
+                is_debit: !l.is_debit,
+                ..l.clone()
+            })
+            .collect();
+        JournalEntryLine::canonicalize(&mut lines);
+
+        Ok(vec![LedgerEvent::JournalEntryReversed(JournalEntryReversed {
+            tenant_id: cmd.tenant_id,
+            ledger_id: cmd.ledger_id,
+            entry_id: cmd.reversal_entry_id,
+            reversed_entry_id: cmd.entry_id,
+            lines,
+            currency: self.currency.clone(),
+            description: cmd.description.clone(),
+            occurred_at: cmd.occurred_at,
+        })])
+    }
+
+    fn handle_close_period(&self, cmd: &ClosePeriod) -> Result<Vec<LedgerEvent>, DomainError> {
+        self.ensure_tenant(cmd.tenant_id)?;
+
+        if cmd.period_end >= cmd.occurred_at {
+            return Err(DomainError::validation("cannot close a period that has not ended"));
+        }
+        if let Some(last) = self.last_closed_period()
+            && cmd.period_end <= last
+        {
+            return Err(DomainError::conflict(format!("period is already closed through {last}")));
+        }
+
+        Ok(vec![LedgerEvent::PeriodClosed(PeriodClosed {
+            tenant_id: cmd.tenant_id,
+            ledger_id: cmd.ledger_id,
+            period_end: cmd.period_end,
+            occurred_at: cmd.occurred_at,
+        })])
+    }
+
+    fn handle_reopen_period(&self, cmd: &ReopenPeriod) -> Result<Vec<LedgerEvent>, DomainError> {
+        self.ensure_tenant(cmd.tenant_id)?;
+
+        let Some(last) = self.last_closed_period() else {
+            return Err(DomainError::not_found());
+        };
+        // Only the latest close can be undone; earlier periods stay locked behind it.
+        if cmd.period_end != last {
+            return Err(DomainError::conflict(format!(
+                "only the latest closed period ({last}) can be reopened"
+            )));
+        }
+
+        Ok(vec![LedgerEvent::PeriodReopened(PeriodReopened {
+            tenant_id: cmd.tenant_id,
+            ledger_id: cmd.ledger_id,
+            period_end: cmd.period_end,
+            occurred_at: cmd.occurred_at,
+        })])
+    }
 }
 
 #[cfg(test)]
@@ -267,6 +565,7 @@ mod tests {
             ledger_id,
             entry_id: uuid::Uuid::now_v7(),
             lines: lines.clone(),
+            currency: None,
             occurred_at: test_time(),
             description: Some("Test entry".to_string()),
         };
@@ -281,7 +580,72 @@ mod tests {
                 assert_eq!(e.ledger_id, ledger_id);
                 assert_eq!(e.lines, lines);
             }
+            _ => panic!("Expected JournalEntryPosted event"),
+        }
+    }
+
+    #[test]
+    fn same_entry_with_lines_in_any_order_serializes_identically() {
+        let ledger = Ledger::empty(test_ledger_id());
+        let tenant_id = test_tenant_id();
+        let ledger_id = test_ledger_id();
+        let entry_id = uuid::Uuid::now_v7();
+        let occurred_at = test_time();
+        let line = |code: &str, kind, amount, is_debit| JournalEntryLine {
+            account: test_account(code, kind),
+            amount,
+            is_debit,
+        };
+        let lines = vec![
+            line("4000", AccountKind::Revenue, 70, false),
+            line("1200", AccountKind::Asset, 100, true),
+            line("2100", AccountKind::Liability, 30, false),
+            line("1200", AccountKind::Asset, 5, false),
+            line("6000", AccountKind::Expense, 5, true),
+        ];
+        let post = |lines: Vec<JournalEntryLine>| {
+            let cmd = PostJournalEntry {
+                tenant_id,
+                ledger_id,
+                entry_id,
+                lines,
+                currency: None,
+                occurred_at,
+                description: Some("Mixed entry".to_string()),
+            };
+            let events = ledger.handle(&JournalCommand::PostJournalEntry(cmd)).unwrap();
+            serde_json::to_string(&events[0]).unwrap()
+        };
+
+        let expected = post(lines.clone());
+        let mut reversed = lines.clone();
+        reversed.reverse();
+        assert_eq!(post(reversed), expected);
+        for shift in 1..lines.len() {
+            let mut rotated = lines.clone();
+            rotated.rotate_left(shift);
+            assert_eq!(post(rotated), expected);
         }
+
+        let events = ledger
+            .handle(&JournalCommand::PostJournalEntry(PostJournalEntry {
+                tenant_id,
+                ledger_id,
+                entry_id,
+                lines,
+                currency: None,
+                occurred_at,
+                description: None,
+            }))
+            .unwrap();
+        let LedgerEvent::JournalEntryPosted(posted) = &events[0] else {
+            panic!("Expected JournalEntryPosted event");
+        };
+        let order: Vec<_> = posted.lines.iter().map(|l| (l.account.code.as_str(), l.is_debit)).collect();
+        assert_eq!(
+            order,
+            [("1200", true), ("1200", false), ("2100", false), ("4000", false), ("6000", true)]
+        );
     }
 
     #[test]
@@ -307,6 +671,7 @@ mod tests {
             ledger_id: test_ledger_id(),
             entry_id: uuid::Uuid::now_v7(),
             lines,
+            currency: None,
             occurred_at: test_time(),
             description: None,
         };
@@ -318,6 +683,300 @@ mod tests {
         }
     }
 
+    #[test]
+    fn reposting_same_entry_id_is_a_no_op() {
+        let ledger_id = test_ledger_id();
+        let mut ledger = Ledger::empty(ledger_id);
+        let tenant_id = test_tenant_id();
+
+        let cmd = JournalCommand::PostJournalEntry(PostJournalEntry {
+            tenant_id,
+            ledger_id,
+            entry_id: uuid::Uuid::now_v7(),
+            lines: vec![
+                JournalEntryLine {
+                    account: test_account("1000", AccountKind::Asset),
+                    amount: 100,
+                    is_debit: true,
+                },
+                JournalEntryLine {
+                    account: test_account("2000", AccountKind::Liability),
+                    amount: 100,
+                    is_debit: false,
+                },
+            ],
+            currency: None,
+            occurred_at: test_time(),
+            description: None,
+        });
+
+        let events = ledger.handle(&cmd).unwrap();
+        assert_eq!(events.len(), 1);
+        ledger.apply(&events[0]);
+
+        assert!(ledger.handle(&cmd).unwrap().is_empty());
+    }
+
+    fn post_simple_entry(ledger: &mut Ledger, tenant_id: TenantId, amount: i64) -> uuid::Uuid {
+        let entry_id = uuid::Uuid::now_v7();
+        let cmd = PostJournalEntry {
+            tenant_id,
+            ledger_id: ledger.id_typed(),
+            entry_id,
+            lines: vec![
+                JournalEntryLine {
+                    account: test_account("1000", AccountKind::Asset),
+                    amount,
+                    is_debit: true,
+                },
+                JournalEntryLine {
+                    account: test_account("4000", AccountKind::Revenue),
+                    amount,
+                    is_debit: false,
+                },
+            ],
+            currency: None,
+            occurred_at: test_time(),
+            description: None,
+        };
+        let events = ledger.handle(&JournalCommand::PostJournalEntry(cmd)).unwrap();
+        ledger.apply(&events[0]);
+        entry_id
+    }
+
+    #[test]
+    fn reversal_swaps_sides_and_cannot_be_repeated() {
+        let ledger_id = test_ledger_id();
+        let mut ledger = Ledger::empty(ledger_id);
+        let tenant_id = test_tenant_id();
+        let entry_id = post_simple_entry(&mut ledger, tenant_id, 250);
+
+        let reverse = |reversal_entry_id| {
+            JournalCommand::ReverseJournalEntry(ReverseJournalEntry {
+                tenant_id,
+                ledger_id,
+                entry_id,
+                reversal_entry_id,
+                occurred_at: test_time(),
+                description: Some("Posted in error".to_string()),
+            })
+        };
+
+        let reversal_entry_id = uuid::Uuid::now_v7();
+        let events = ledger.handle(&reverse(reversal_entry_id)).unwrap();
+        let LedgerEvent::JournalEntryReversed(reversed) = &events[0] else {
+            panic!("Expected JournalEntryReversed event");
+        };
+        assert_eq!(reversed.entry_id, reversal_entry_id);
+        assert_eq!(reversed.reversed_entry_id, entry_id);
+        let sides: Vec<_> = reversed
+            .lines
+            .iter()
+            .map(|l| (l.account.code.as_str(), l.amount, l.is_debit))
+            .collect();
+        assert_eq!(sides, [("1000", 250, false), ("4000", 250, true)]);
+        ledger.apply(&events[0]);
+
+        let err = ledger.handle(&reverse(uuid::Uuid::now_v7())).unwrap_err();
+        assert!(matches!(err, DomainError::Conflict(_)));
+    }
+
+    #[test]
+    fn reversing_unknown_entry_is_not_found() {
+        let ledger_id = test_ledger_id();
+        let mut ledger = Ledger::empty(ledger_id);
+        let tenant_id = test_tenant_id();
+        post_simple_entry(&mut ledger, tenant_id, 100);
+
+        let err = ledger
+            .handle(&JournalCommand::ReverseJournalEntry(ReverseJournalEntry {
+                tenant_id,
+                ledger_id,
+                entry_id: uuid::Uuid::now_v7(),
+                reversal_entry_id: uuid::Uuid::now_v7(),
+                occurred_at: test_time(),
+                description: None,
+            }))
+            .unwrap_err();
+        assert!(matches!(err, DomainError::NotFound));
+    }
+
+    fn post_entry_at(
+        ledger: &mut Ledger,
+        tenant_id: TenantId,
+        occurred_at: DateTime<Utc>,
+    ) -> Result<(), DomainError> {
+        let cmd = PostJournalEntry {
+            tenant_id,
+            ledger_id: ledger.id_typed(),
+            entry_id: uuid::Uuid::now_v7(),
+            lines: vec![
+                JournalEntryLine {
+                    account: test_account("1000", AccountKind::Asset),
+                    amount: 100,
+                    is_debit: true,
+                },
+                JournalEntryLine {
+                    account: test_account("4000", AccountKind::Revenue),
+                    amount: 100,
+                    is_debit: false,
+                },
+            ],
+            currency: None,
+            occurred_at,
+            description: None,
+        };
+        for ev in ledger.handle(&JournalCommand::PostJournalEntry(cmd))? {
+            ledger.apply(&ev);
+        }
+        Ok(())
+    }
+
+    fn run_period_command(ledger: &mut Ledger, cmd: JournalCommand) -> Result<(), DomainError> {
+        for ev in ledger.handle(&cmd)? {
+            ledger.apply(&ev);
+        }
+        Ok(())
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn posting_into_a_closed_period_fails_and_after_it_succeeds() {
+        let ledger_id = test_ledger_id();
+        let mut ledger = Ledger::empty(ledger_id);
+        let tenant_id = test_tenant_id();
+        post_entry_at(&mut ledger, tenant_id, at("2026-01-15T10:00:00Z")).unwrap();
+
+        let period_end = at("2026-01-31T23:59:59Z");
+        run_period_command(
+            &mut ledger,
+            JournalCommand::ClosePeriod(ClosePeriod {
+                tenant_id,
+                ledger_id,
+                period_end,
+                occurred_at: at("2026-02-03T09:00:00Z"),
+            }),
+        )
+        .unwrap();
+        assert_eq!(ledger.last_closed_period(), Some(period_end));
+
+        for back_dated in ["2026-01-20T12:00:00Z", "2026-01-31T23:59:59Z"] {
+            let err = post_entry_at(&mut ledger, tenant_id, at(back_dated)).unwrap_err();
+            assert!(matches!(err, DomainError::InvariantViolation(_)));
+        }
+        post_entry_at(&mut ledger, tenant_id, at("2026-02-01T00:00:00Z")).unwrap();
+    }
+
+    #[test]
+    fn reopen_only_undoes_the_latest_close() {
+        let ledger_id = test_ledger_id();
+        let mut ledger = Ledger::empty(ledger_id);
+        let tenant_id = test_tenant_id();
+        let close = |period_end| {
+            JournalCommand::ClosePeriod(ClosePeriod {
+                tenant_id,
+                ledger_id,
+                period_end,
+                occurred_at: at("2026-03-05T09:00:00Z"),
+            })
+        };
+        let reopen = |period_end| {
+            JournalCommand::ReopenPeriod(ReopenPeriod {
+                tenant_id,
+                ledger_id,
+                period_end,
+                occurred_at: at("2026-03-05T10:00:00Z"),
+            })
+        };
+        let january = at("2026-01-31T23:59:59Z");
+        let february = at("2026-02-28T23:59:59Z");
+
+        let err = run_period_command(&mut ledger, reopen(january)).unwrap_err();
+        assert!(matches!(err, DomainError::NotFound));
+
+        run_period_command(&mut ledger, close(january)).unwrap();
+        run_period_command(&mut ledger, close(february)).unwrap();
+        let err = run_period_command(&mut ledger, close(january)).unwrap_err();
+        assert!(matches!(err, DomainError::Conflict(_)));
+
+        // January lies before the prior (February) close, so it cannot be reopened on its own.
+        let err = run_period_command(&mut ledger, reopen(january)).unwrap_err();
+        assert!(matches!(err, DomainError::Conflict(_)));
+
+        run_period_command(&mut ledger, reopen(february)).unwrap();
+        assert_eq!(ledger.last_closed_period(), Some(january));
+        post_entry_at(&mut ledger, tenant_id, at("2026-02-10T12:00:00Z")).unwrap();
+        let err = post_entry_at(&mut ledger, tenant_id, at("2026-01-10T12:00:00Z")).unwrap_err();
+        assert!(matches!(err, DomainError::InvariantViolation(_)));
+    }
+
+    #[test]
+    fn closing_a_period_that_has_not_ended_is_rejected() {
+        let ledger_id = test_ledger_id();
+        let ledger = Ledger::empty(ledger_id);
+        let err = ledger
+            .handle(&JournalCommand::ClosePeriod(ClosePeriod {
+                tenant_id: test_tenant_id(),
+                ledger_id,
+                period_end: at("2026-03-31T23:59:59Z"),
+                occurred_at: at("2026-03-15T09:00:00Z"),
+            }))
+            .unwrap_err();
+        assert!(matches!(err, DomainError::Validation(_)));
+    }
+
+    #[test]
+    fn default_ledger_id_is_stable_per_tenant() {
+        let tenant_id = test_tenant_id();
+        assert_eq!(LedgerId::default_for(tenant_id), LedgerId::default_for(tenant_id));
+        assert_ne!(LedgerId::default_for(tenant_id), LedgerId::default_for(test_tenant_id()));
+    }
+
+    #[test]
+    fn first_entry_fixes_the_ledger_currency() {
+        let ledger_id = test_ledger_id();
+        let mut ledger = Ledger::empty(ledger_id);
+        let tenant_id = test_tenant_id();
+        let post = |currency: Option<&str>| {
+            JournalCommand::PostJournalEntry(PostJournalEntry {
+                tenant_id,
+                ledger_id,
+                entry_id: uuid::Uuid::now_v7(),
+                lines: vec![
+                    JournalEntryLine {
+                        account: test_account("1000", AccountKind::Asset),
+                        amount: 100,
+                        is_debit: true,
+                    },
+                    JournalEntryLine {
+                        account: test_account("4000", AccountKind::Revenue),
+                        amount: 100,
+                        is_debit: false,
+                    },
+                ],
+                currency: currency.map(str::to_string),
+                occurred_at: test_time(),
+                description: None,
+            })
+        };
+
+        for ev in ledger.handle(&post(Some("JPY"))).unwrap() {
+            ledger.apply(&ev);
+        }
+        assert_eq!(ledger.currency(), Some("JPY"));
+
+        let err = ledger.handle(&post(Some("USD"))).unwrap_err();
+        assert!(matches!(err, DomainError::Validation(_)));
+        let events = ledger.handle(&post(None)).unwrap();
+        let LedgerEvent::JournalEntryPosted(posted) = &events[0] else {
+            panic!("Expected JournalEntryPosted event");
+        };
+        assert_eq!(posted.currency.as_deref(), Some("JPY"));
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig {
             cases: 256,
@@ -325,7 +984,8 @@ mod tests {
         })]
 
         /// Property: For any generated sequence of balanced journal entries,
-        /// the sum of debits minus credits across all posted events is zero.
+        /// some of them reversed, the sum of debits minus credits across all
+        /// posted events is zero.
         #[test]
         fn debits_equal_credits_in_posted_events(
             amounts in prop::collection::vec(1i64..1_000_000i64, 1..10)
@@ -336,7 +996,7 @@ mod tests {
 
             let mut all_events: Vec<LedgerEvent> = Vec::new();
 
-            for amount in amounts {
+            for (i, amount) in amounts.into_iter().enumerate() {
                 // Build a balanced entry: one debit, one credit with same amount.
                 let lines = vec![
                     JournalEntryLine {
@@ -356,22 +1016,39 @@ mod tests {
                     ledger_id,
                     entry_id: uuid::Uuid::now_v7(),
                     lines: lines.clone(),
+                    currency: None,
                     occurred_at: test_time(),
                     description: None,
                 };
 
+                let entry_id = cmd.entry_id;
                 let events = ledger.handle(&JournalCommand::PostJournalEntry(cmd)).unwrap();
                 for e in &events {
                     ledger.apply(e);
                 }
                 all_events.extend(events);
+
+                if i % 2 == 0 {
+                    let reverse = ReverseJournalEntry {
+                        tenant_id,
+                        ledger_id,
+                        entry_id,
+                        reversal_entry_id: uuid::Uuid::now_v7(),
+                        occurred_at: test_time(),
+                        description: None,
+                    };
+                    let events = ledger.handle(&JournalCommand::ReverseJournalEntry(reverse)).unwrap();
+                    for e in &events {
+                        ledger.apply(e);
+                    }
+                    all_events.extend(events);
+                }
             }
 
             // Compute sum of debits - credits from all events.
             let mut total: i128 = 0;
             for ev in &all_events {
-                let LedgerEvent::JournalEntryPosted(je) = ev;
-                for line in &je.lines {
+                for line in ev.lines() {
                     if line.is_debit {
                         total += line.amount as i128;
                     } else {
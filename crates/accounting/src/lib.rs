@@ -5,8 +5,9 @@
 pub mod ledger;
 
 pub use ledger::{
-    Account, AccountKind, JournalCommand, JournalEntryLine, JournalEntryPosted, Ledger,
-    LedgerEvent, LedgerId, PostJournalEntry,
+    Account, AccountKind, ClosePeriod, JournalCommand, JournalEntryLine, JournalEntryPosted,
+    JournalEntryReversed, Ledger, LedgerEvent, LedgerId, PeriodClosed, PeriodReopened,
+    PostJournalEntry, ReopenPeriod, ReverseJournalEntry,
 };
 
 
@@ -0,0 +1,135 @@
+//! Pluggable inference backends.
+//!
+//! Jobs describe *what* to infer; an [`AiBackend`] decides *where*: in-process
+//! ([`LocalAiBackend`]) or in an external model service (infra adapters). Requests are
+//! plain JSON so any backend can serve them.
+//!
+//! [`BackendScheduler`] is the async counterpart of [`crate::LocalAiScheduler`]: it
+//! enforces the tenant scope and bounds every call by a timeout, so a slow or failing
+//! backend surfaces as an [`AiError`] and never blocks or breaks the caller. With an
+//! [`AiUsageMeter`] attached it also applies per-tenant rate limits and tallies cost.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use forgeerp_core::TenantId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::inventory_anomaly::InventoryAnomalyJob;
+use crate::job::AiJob;
+use crate::result::{AiError, AiResult};
+use crate::reorder_suggestion::ReorderSuggestionJob;
+use crate::sales_forecast::SalesForecastJob;
+use crate::scheduler::TenantScope;
+use crate::usage::AiUsageMeter;
+
+/// Backend-neutral inference request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AiRequest {
+    pub tenant_id: TenantId,
+    /// Inference kind (e.g. `inventory.anomaly_detection`).
+    pub kind: String,
+    /// Job input (e.g. an `InventorySnapshot`).
+    pub input: JsonValue,
+    /// Job parameters (thresholds, window sizes).
+    #[serde(default)]
+    pub params: JsonValue,
+    /// Estimated cost of serving the request, in backend-neutral units.
+    #[serde(default)]
+    pub estimated_units: u64,
+}
+
+/// Executes inference requests.
+#[async_trait]
+pub trait AiBackend: Send + Sync + 'static {
+    async fn infer(&self, request: AiRequest) -> Result<AiResult, AiError>;
+}
+
+#[async_trait]
+impl<B: AiBackend + ?Sized> AiBackend for Arc<B> {
+    async fn infer(&self, request: AiRequest) -> Result<AiResult, AiError> {
+        (**self).infer(request).await
+    }
+}
+
+/// Runs the built-in jobs in-process.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct LocalAiBackend;
+
+#[async_trait]
+impl AiBackend for LocalAiBackend {
+    async fn infer(&self, request: AiRequest) -> Result<AiResult, AiError> {
+        match request.kind.as_str() {
+            InventoryAnomalyJob::KIND => InventoryAnomalyJob::from_request(&request)?.run(),
+            SalesForecastJob::KIND => SalesForecastJob::from_request(&request)?.run(),
+            ReorderSuggestionJob::KIND => ReorderSuggestionJob::from_request(&request)?.run(),
+            other => Err(AiError::InvalidInput(format!("unsupported inference kind: {other}"))),
+        }
+    }
+}
+
+/// Default upper bound for one backend call.
+pub const DEFAULT_BACKEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tenant-scoped, time-bounded scheduler over an [`AiBackend`].
+#[derive(Debug, Clone)]
+pub struct BackendScheduler<B> {
+    scope: TenantScope,
+    backend: B,
+    timeout: Duration,
+    meter: Option<Arc<AiUsageMeter>>,
+}
+
+impl<B: AiBackend> BackendScheduler<B> {
+    pub fn new(scope: TenantScope, backend: B) -> Self {
+        Self {
+            scope,
+            backend,
+            timeout: DEFAULT_BACKEND_TIMEOUT,
+            meter: None,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Rate-limit runs and record their cost in `meter`.
+    pub fn with_meter(mut self, meter: Arc<AiUsageMeter>) -> Self {
+        self.meter = Some(meter);
+        self
+    }
+
+    pub fn scope(&self) -> TenantScope {
+        self.scope
+    }
+
+    /// Run `request` on the backend (requires a Tokio runtime with the time driver).
+    pub async fn run(&self, request: AiRequest) -> Result<AiResult, AiError> {
+        if !self.scope.allows(request.tenant_id) {
+            return Err(AiError::InvalidInput(
+                "tenant scope violation (job tenant not allowed by scheduler)".to_string(),
+            ));
+        }
+        let tenant_id = request.tenant_id;
+        let units = request.estimated_units;
+        if self.meter.as_ref().is_some_and(|m| !m.try_acquire(tenant_id)) {
+            return Err(AiError::RateLimited(format!("tenant {tenant_id} exceeded its AI run rate")));
+        }
+
+        let result = match tokio::time::timeout(self.timeout, self.backend.infer(request)).await {
+            Ok(result) => result,
+            Err(_) => Err(AiError::InferenceFailed(format!(
+                "backend timed out after {}ms",
+                self.timeout.as_millis()
+            ))),
+        };
+        if let Some(meter) = &self.meter {
+            meter.record(tenant_id, units, result.is_ok());
+        }
+        result
+    }
+}
@@ -3,8 +3,9 @@ use serde_json::json;
 
 use forgeerp_core::TenantId;
 
+use crate::backend::AiRequest;
 use crate::job::AiJob;
-use crate::result::{AiError, AiResult};
+use crate::result::{AiError, AiInsightKind, AiResult};
 use crate::scheduler::{InventoryItemSnapshot, InventorySnapshot};
 
 /// Inventory anomaly detection output (AI insight).
@@ -15,8 +16,24 @@ pub struct AnomalyDetected {
     pub item_id: String,
     pub severity: f64,
     pub explanation: String,
+    /// Detection window: positions `[start, end]` in the item's trend that were evaluated.
+    #[serde(default)]
+    pub window: (usize, usize),
 }
 
+impl AnomalyDetected {
+    /// Stable identity (kind + item + detection window): re-running detection over
+    /// unchanged data yields the same key.
+    pub fn idempotency_key(&self) -> String {
+        format!("{ANOMALY_KIND}:{}:{}-{}", self.item_id, self.window.0, self.window.1)
+    }
+}
+
+/// One anomaly of an inventory anomaly result, as read by [`AiResult::as_inventory_anomalies`].
+pub type AnomalyEntry = AnomalyDetected;
+
+const ANOMALY_KIND: &str = InventoryAnomalyJob::KIND;
+
 /// Deterministic anomaly detection job for inventory stock movements.
 ///
 /// Model:
@@ -31,15 +48,21 @@ pub struct InventoryAnomalyJob {
     window: usize,
     /// Z-score threshold (e.g., 3.0).
     z_threshold: f64,
+    /// Trend points an item needs before it is scored (never fewer than `window + 2`).
+    min_samples: usize,
 }
 
 impl InventoryAnomalyJob {
+    /// Inference kind used in results and [`AiRequest`]s.
+    pub const KIND: &'static str = AiInsightKind::InventoryAnomalyDetection.as_str();
+
     pub fn new(tenant_id: TenantId, input: InventorySnapshot) -> Self {
         Self {
             tenant_id,
             input,
             window: 10,
             z_threshold: 3.0,
+            min_samples: 0,
         }
     }
 
@@ -52,6 +75,44 @@ impl InventoryAnomalyJob {
         self.z_threshold = z_threshold;
         self
     }
+
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples;
+        self
+    }
+
+    /// Backend request for this job.
+    pub fn to_request(&self) -> AiRequest {
+        AiRequest {
+            tenant_id: self.tenant_id,
+            kind: Self::KIND.to_string(),
+            input: serde_json::to_value(&self.input).unwrap_or_default(),
+            params: json!({
+                "window": self.window,
+                "z_threshold": self.z_threshold,
+                "min_samples": self.min_samples,
+            }),
+            // One unit per item scored (at least one per run).
+            estimated_units: self.input.items.len().max(1) as u64,
+        }
+    }
+
+    /// Rebuild a job from a backend request (inverse of [`Self::to_request`]).
+    pub fn from_request(request: &AiRequest) -> Result<Self, AiError> {
+        let input: InventorySnapshot = serde_json::from_value(request.input.clone())
+            .map_err(|e| AiError::InvalidInput(format!("invalid inventory snapshot: {e}")))?;
+        let mut job = Self::new(request.tenant_id, input);
+        if let Some(window) = request.params.get("window").and_then(|v| v.as_u64()) {
+            job.window = window as usize;
+        }
+        if let Some(z) = request.params.get("z_threshold").and_then(|v| v.as_f64()) {
+            job.z_threshold = z;
+        }
+        if let Some(min_samples) = request.params.get("min_samples").and_then(|v| v.as_u64()) {
+            job.min_samples = min_samples as usize;
+        }
+        Ok(job)
+    }
 }
 
 impl AiJob for InventoryAnomalyJob {
@@ -87,7 +148,7 @@ impl AiJob for InventoryAnomalyJob {
         let mut anomalies: Vec<AnomalyDetected> = Vec::new();
 
         for item in &self.input.items {
-            if let Some(a) = detect_item_anomaly(item, self.window, self.z_threshold) {
+            if let Some(a) = detect_item_anomaly(item, self.window, self.z_threshold, self.min_samples) {
                 anomalies.push(a);
             }
         }
@@ -105,23 +166,43 @@ impl AiJob for InventoryAnomalyJob {
                 self.z_threshold
             ))
             .with_metadata(json!({
-                "kind": "inventory.anomaly_detection",
+                "kind": ANOMALY_KIND,
                 "tenant_id": self.tenant_id.to_string(),
                 "window": self.window,
                 "z_threshold": self.z_threshold,
+                "min_samples": self.min_samples,
                 "anomalies": anomalies,
             })))
     }
 }
 
+/// Fan a detection result out into one insight per anomaly, each keyed by
+/// [`AnomalyDetected::idempotency_key`] so repeated runs collapse in the sink.
+pub fn anomaly_insights(result: &AiResult) -> Vec<AiResult> {
+    result
+        .as_inventory_anomalies()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|a| {
+            let mut metadata = result.metadata.clone();
+            metadata["anomalies"] = json!([a]);
+            AiResult::new(a.severity, result.confidence)
+                .with_explanation(a.explanation.clone())
+                .with_metadata(metadata)
+                .with_idempotency_key(a.idempotency_key())
+        })
+        .collect()
+}
+
 fn detect_item_anomaly(
     item: &InventoryItemSnapshot,
     window: usize,
     z_threshold: f64,
+    min_samples: usize,
 ) -> Option<AnomalyDetected> {
     // Need at least 3 points: 2 for deltas, and >=2 baseline deltas to compute stddev.
     // Baseline deltas count = window; total points needed = window + 2.
-    if item.historical_trend.len() < window + 2 {
+    if item.historical_trend.len() < (window + 2).max(min_samples) {
         return None;
     }
 
@@ -155,6 +236,7 @@ fn detect_item_anomaly(
                 item_id: item.item_id.clone(),
                 severity: 1.0,
                 explanation,
+                window: (start, deltas.len()),
             });
         }
         return None;
@@ -178,6 +260,7 @@ fn detect_item_anomaly(
         item_id: item.item_id.clone(),
         severity,
         explanation,
+        window: (start, deltas.len()),
     })
 }
 
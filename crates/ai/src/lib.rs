@@ -7,14 +7,24 @@
 //! - It must not mutate domain state.
 //! - It emits **AI insights/results**, not domain events.
 
+pub mod backend;
 pub mod job;
 pub mod inventory_anomaly;
+pub mod reorder_suggestion;
 pub mod result;
+pub mod sales_forecast;
 pub mod scheduler;
+pub mod usage;
 
+pub use backend::{AiBackend, AiRequest, BackendScheduler, LocalAiBackend, DEFAULT_BACKEND_TIMEOUT};
 pub use job::AiJob;
-pub use inventory_anomaly::{AnomalyDetected, InventoryAnomalyJob};
-pub use result::{AiError, AiResult};
+pub use inventory_anomaly::{anomaly_insights, AnomalyDetected, AnomalyEntry, InventoryAnomalyJob};
+pub use reorder_suggestion::{
+    reorder_insights, ReorderItemSnapshot, ReorderSnapshot, ReorderSuggestion, ReorderSuggestionJob,
+};
+pub use result::{AiError, AiInsightKind, AiResult};
+pub use sales_forecast::{forecast_insights, ProductDemandSnapshot, SalesForecast, SalesForecastJob, SalesSnapshot};
+pub use usage::{AiUsage, AiUsageMeter, RateLimit};
 pub use scheduler::{
     AiScheduler, InventoryItemSnapshot, InventorySnapshot, LocalAiScheduler, ReadModelReader, TenantScope,
 };
@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use forgeerp_core::TenantId;
+
+use crate::backend::AiRequest;
+use crate::job::AiJob;
+use crate::result::{AiError, AiInsightKind, AiResult};
+
+/// Reorder snapshot: per inventory item, the stock on hand and the quantities of the
+/// sales orders that drew on it.
+///
+/// - `item_id` is a string to avoid depending on ERP module types in `forgeerp-ai`.
+/// - `demand_history` is oldest first, one entry per order line the producer counted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReorderSnapshot {
+    pub tenant_id: TenantId,
+    pub items: Vec<ReorderItemSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReorderItemSnapshot {
+    pub item_id: String,
+    pub on_hand: i64,
+    pub demand_history: Vec<i64>,
+}
+
+/// Suggested reorder for one inventory item (AI insight).
+///
+/// This is an AI result payload, not a domain event. `suggested_qty == 0` means the
+/// stock on hand covers the expected demand.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReorderSuggestion {
+    pub item_id: String,
+    pub suggested_qty: i64,
+    pub reason: String,
+    pub on_hand: i64,
+    /// Demand the stock should cover (average order quantity times the cover).
+    pub expected_demand: f64,
+}
+
+impl ReorderSuggestion {
+    /// Stable identity (kind + item): a newer suggestion replaces the previous one.
+    pub fn idempotency_key(&self) -> String {
+        format!("{REORDER_KIND}:{}", self.item_id)
+    }
+}
+
+const REORDER_KIND: &str = ReorderSuggestionJob::KIND;
+
+/// Deterministic reorder suggestions for inventory.
+///
+/// Model: expected demand is the moving average of the item's last `window` order
+/// quantities times `cover` (orders the stock should last); the suggestion tops
+/// on-hand stock up to it.
+#[derive(Debug, Clone)]
+pub struct ReorderSuggestionJob {
+    tenant_id: TenantId,
+    input: ReorderSnapshot,
+    /// Number of most recent orders averaged (must be >= 1).
+    window: usize,
+    /// Number of average orders the stock should cover (must be >= 1).
+    cover: u32,
+}
+
+impl ReorderSuggestionJob {
+    /// Inference kind used in results and [`AiRequest`]s.
+    pub const KIND: &'static str = AiInsightKind::InventoryReorderSuggestion.as_str();
+
+    pub fn new(tenant_id: TenantId, input: ReorderSnapshot) -> Self {
+        Self {
+            tenant_id,
+            input,
+            window: 5,
+            cover: 2,
+        }
+    }
+
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn with_cover(mut self, cover: u32) -> Self {
+        self.cover = cover;
+        self
+    }
+
+    /// Backend request for this job.
+    pub fn to_request(&self) -> AiRequest {
+        AiRequest {
+            tenant_id: self.tenant_id,
+            kind: Self::KIND.to_string(),
+            input: serde_json::to_value(&self.input).unwrap_or_default(),
+            params: json!({ "window": self.window, "cover": self.cover }),
+            // One unit per item suggestion (at least one per run).
+            estimated_units: self.input.items.len().max(1) as u64,
+        }
+    }
+
+    /// Rebuild a job from a backend request (inverse of [`Self::to_request`]).
+    pub fn from_request(request: &AiRequest) -> Result<Self, AiError> {
+        let input: ReorderSnapshot = serde_json::from_value(request.input.clone())
+            .map_err(|e| AiError::InvalidInput(format!("invalid reorder snapshot: {e}")))?;
+        let mut job = Self::new(request.tenant_id, input);
+        if let Some(window) = request.params.get("window").and_then(|v| v.as_u64()) {
+            job.window = window as usize;
+        }
+        if let Some(cover) = request.params.get("cover").and_then(|v| v.as_u64()) {
+            job.cover = cover as u32;
+        }
+        Ok(job)
+    }
+}
+
+impl AiJob for ReorderSuggestionJob {
+    type Input = ReorderSnapshot;
+
+    fn tenant_id(&self) -> TenantId {
+        self.tenant_id
+    }
+
+    fn input(&self) -> &Self::Input {
+        &self.input
+    }
+
+    fn run(&self) -> Result<AiResult, AiError> {
+        if self.input.tenant_id != self.tenant_id {
+            return Err(AiError::InvalidInput(
+                "tenant_id mismatch between job and snapshot".to_string(),
+            ));
+        }
+
+        if self.window == 0 {
+            return Err(AiError::InvalidInput("window must be >= 1".to_string()));
+        }
+        if self.cover == 0 {
+            return Err(AiError::InvalidInput("cover must be >= 1".to_string()));
+        }
+
+        let suggestions: Vec<ReorderSuggestion> = self
+            .input
+            .items
+            .iter()
+            .filter_map(|item| suggest_item(item, self.window, self.cover))
+            .collect();
+        let to_reorder = suggestions.iter().filter(|s| s.suggested_qty > 0).count();
+
+        Ok(AiResult::new(to_reorder as f64, 1.0)
+            .with_explanation(format!(
+                "{} of {} item(s) need reordering to cover {} average order(s)",
+                to_reorder,
+                suggestions.len(),
+                self.cover
+            ))
+            .with_metadata(json!({
+                "kind": REORDER_KIND,
+                "tenant_id": self.tenant_id.to_string(),
+                "window": self.window,
+                "cover": self.cover,
+                "suggestions": suggestions,
+            })))
+    }
+}
+
+/// Fan a reorder result out into one insight per item, each keyed by
+/// [`ReorderSuggestion::idempotency_key`] so the sink keeps only the latest suggestion.
+pub fn reorder_insights(result: &AiResult) -> Vec<AiResult> {
+    result
+        .as_reorder_suggestions()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|s| {
+            let mut metadata = result.metadata.clone();
+            metadata["suggestions"] = json!([s]);
+            AiResult::new(s.suggested_qty as f64, 1.0)
+                .with_explanation(s.reason.clone())
+                .with_metadata(metadata)
+                .with_idempotency_key(s.idempotency_key())
+        })
+        .collect()
+}
+
+fn suggest_item(item: &ReorderItemSnapshot, window: usize, cover: u32) -> Option<ReorderSuggestion> {
+    // Without sales there is no velocity to reorder against.
+    if item.demand_history.is_empty() {
+        return None;
+    }
+    let start = item.demand_history.len().saturating_sub(window);
+    let recent = &item.demand_history[start..];
+    let average = recent.iter().sum::<i64>() as f64 / recent.len() as f64;
+    let expected_demand = average * cover as f64;
+    let suggested_qty = (expected_demand.ceil() as i64 - item.on_hand).max(0);
+
+    let reason = if suggested_qty > 0 {
+        format!(
+            "on hand {} is below the expected demand of {:.2} ({} order(s) averaging {:.2} units)",
+            item.on_hand, expected_demand, cover, average
+        )
+    } else {
+        format!(
+            "on hand {} covers the expected demand of {:.2} ({} order(s) averaging {:.2} units)",
+            item.on_hand, expected_demand, cover, average
+        )
+    };
+
+    Some(ReorderSuggestion {
+        item_id: item.item_id.clone(),
+        suggested_qty,
+        reason,
+        on_hand: item.on_hand,
+        expected_demand,
+    })
+}
@@ -2,6 +2,31 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use thiserror::Error;
 
+use crate::inventory_anomaly::AnomalyEntry;
+use crate::reorder_suggestion::ReorderSuggestion;
+use crate::sales_forecast::SalesForecast;
+
+/// Kind of insight an [`AiResult`] carries (`metadata.kind`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AiInsightKind {
+    #[serde(rename = "inventory.anomaly_detection")]
+    InventoryAnomalyDetection,
+    #[serde(rename = "sales.forecast")]
+    SalesForecast,
+    #[serde(rename = "inventory.reorder_suggestion")]
+    InventoryReorderSuggestion,
+}
+
+impl AiInsightKind {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            AiInsightKind::InventoryAnomalyDetection => "inventory.anomaly_detection",
+            AiInsightKind::SalesForecast => "sales.forecast",
+            AiInsightKind::InventoryReorderSuggestion => "inventory.reorder_suggestion",
+        }
+    }
+}
+
 /// Result of an AI/ML inference.
 ///
 /// This is *not* a domain event. It is an insight that can be persisted or displayed
@@ -19,6 +44,11 @@ pub struct AiResult {
 
     /// Free-form metadata (model name, feature flags, timings, etc).
     pub metadata: JsonValue,
+
+    /// Identity of the insight: sinks keep one result per key, replacing earlier ones.
+    /// `None` results are always appended.
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
 }
 
 impl AiResult {
@@ -28,6 +58,7 @@ impl AiResult {
             confidence,
             explanation: None,
             metadata: JsonValue::Null,
+            idempotency_key: None,
         }
     }
 
@@ -40,6 +71,40 @@ impl AiResult {
         self.metadata = metadata;
         self
     }
+
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Insight kind from `metadata.kind` (`None` if missing or unknown).
+    pub fn kind(&self) -> Option<AiInsightKind> {
+        AiInsightKind::deserialize(self.metadata.get("kind")?).ok()
+    }
+
+    /// Anomalies of an inventory anomaly result (`None` for other kinds or malformed metadata).
+    pub fn as_inventory_anomalies(&self) -> Option<Vec<AnomalyEntry>> {
+        if self.kind()? != AiInsightKind::InventoryAnomalyDetection {
+            return None;
+        }
+        Vec::<AnomalyEntry>::deserialize(self.metadata.get("anomalies")?).ok()
+    }
+
+    /// Forecasts of a sales forecast result (`None` for other kinds or malformed metadata).
+    pub fn as_sales_forecasts(&self) -> Option<Vec<SalesForecast>> {
+        if self.kind()? != AiInsightKind::SalesForecast {
+            return None;
+        }
+        Vec::<SalesForecast>::deserialize(self.metadata.get("forecasts")?).ok()
+    }
+
+    /// Suggestions of a reorder suggestion result (`None` for other kinds or malformed metadata).
+    pub fn as_reorder_suggestions(&self) -> Option<Vec<ReorderSuggestion>> {
+        if self.kind()? != AiInsightKind::InventoryReorderSuggestion {
+            return None;
+        }
+        Vec::<ReorderSuggestion>::deserialize(self.metadata.get("suggestions")?).ok()
+    }
 }
 
 #[derive(Debug, Error)]
@@ -50,6 +115,10 @@ pub enum AiError {
     #[error("inference failed: {0}")]
     InferenceFailed(String),
 
+    /// The tenant's AI rate limit is exhausted; the run was dropped, not queued.
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+
     #[error("internal error: {0}")]
     Internal(String),
 }
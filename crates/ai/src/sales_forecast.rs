@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use forgeerp_core::TenantId;
+
+use crate::backend::AiRequest;
+use crate::job::AiJob;
+use crate::result::{AiError, AiInsightKind, AiResult};
+
+/// Sales demand snapshot: per product, the quantities of its past orders.
+///
+/// - `product_id` is a string to avoid depending on ERP module types in `forgeerp-ai`.
+/// - `demand_history` is oldest first, one entry per order line the producer counted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SalesSnapshot {
+    pub tenant_id: TenantId,
+    pub products: Vec<ProductDemandSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductDemandSnapshot {
+    pub product_id: String,
+    pub demand_history: Vec<i64>,
+}
+
+/// Demand forecast for one product (AI insight).
+///
+/// This is an AI result payload, not a domain event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SalesForecast {
+    pub product_id: String,
+    /// Expected quantity of the product's next order.
+    pub forecast: f64,
+    /// Orders the average was taken over (at most the job's window).
+    pub observations: usize,
+}
+
+impl SalesForecast {
+    /// Stable identity (kind + product): a newer forecast replaces the previous one.
+    pub fn idempotency_key(&self) -> String {
+        format!("{FORECAST_KIND}:{}", self.product_id)
+    }
+}
+
+const FORECAST_KIND: &str = SalesForecastJob::KIND;
+
+/// Deterministic demand forecast for sales.
+///
+/// Model: simple moving average of each product's last `window` order quantities.
+#[derive(Debug, Clone)]
+pub struct SalesForecastJob {
+    tenant_id: TenantId,
+    input: SalesSnapshot,
+    /// Number of most recent orders averaged (must be >= 1).
+    window: usize,
+}
+
+impl SalesForecastJob {
+    /// Inference kind used in results and [`AiRequest`]s.
+    pub const KIND: &'static str = AiInsightKind::SalesForecast.as_str();
+
+    pub fn new(tenant_id: TenantId, input: SalesSnapshot) -> Self {
+        Self {
+            tenant_id,
+            input,
+            window: 5,
+        }
+    }
+
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Backend request for this job.
+    pub fn to_request(&self) -> AiRequest {
+        AiRequest {
+            tenant_id: self.tenant_id,
+            kind: Self::KIND.to_string(),
+            input: serde_json::to_value(&self.input).unwrap_or_default(),
+            params: json!({ "window": self.window }),
+            // One unit per product forecast (at least one per run).
+            estimated_units: self.input.products.len().max(1) as u64,
+        }
+    }
+
+    /// Rebuild a job from a backend request (inverse of [`Self::to_request`]).
+    pub fn from_request(request: &AiRequest) -> Result<Self, AiError> {
+        let input: SalesSnapshot = serde_json::from_value(request.input.clone())
+            .map_err(|e| AiError::InvalidInput(format!("invalid sales snapshot: {e}")))?;
+        let mut job = Self::new(request.tenant_id, input);
+        if let Some(window) = request.params.get("window").and_then(|v| v.as_u64()) {
+            job.window = window as usize;
+        }
+        Ok(job)
+    }
+}
+
+impl AiJob for SalesForecastJob {
+    type Input = SalesSnapshot;
+
+    fn tenant_id(&self) -> TenantId {
+        self.tenant_id
+    }
+
+    fn input(&self) -> &Self::Input {
+        &self.input
+    }
+
+    fn run(&self) -> Result<AiResult, AiError> {
+        if self.input.tenant_id != self.tenant_id {
+            return Err(AiError::InvalidInput(
+                "tenant_id mismatch between job and snapshot".to_string(),
+            ));
+        }
+
+        if self.window == 0 {
+            return Err(AiError::InvalidInput("window must be >= 1".to_string()));
+        }
+
+        let forecasts: Vec<SalesForecast> = self
+            .input
+            .products
+            .iter()
+            .filter_map(|product| forecast_product(product, self.window))
+            .collect();
+
+        // Confidence grows with how full the averaging windows are.
+        let filled: usize = forecasts.iter().map(|f| f.observations).sum();
+        let confidence = if forecasts.is_empty() {
+            1.0
+        } else {
+            filled as f64 / (forecasts.len() * self.window) as f64
+        };
+
+        Ok(AiResult::new(forecasts.len() as f64, confidence)
+            .with_explanation(format!(
+                "forecast demand for {} product(s) using a {}-order moving average",
+                forecasts.len(),
+                self.window
+            ))
+            .with_metadata(json!({
+                "kind": FORECAST_KIND,
+                "tenant_id": self.tenant_id.to_string(),
+                "window": self.window,
+                "forecasts": forecasts,
+            })))
+    }
+}
+
+/// Fan a forecast result out into one insight per product, each keyed by
+/// [`SalesForecast::idempotency_key`] so the sink keeps only the latest forecast.
+pub fn forecast_insights(result: &AiResult) -> Vec<AiResult> {
+    result
+        .as_sales_forecasts()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|f| {
+            let mut metadata = result.metadata.clone();
+            metadata["forecasts"] = json!([f]);
+            let confidence = f.observations as f64 / metadata["window"].as_f64().unwrap_or(1.0).max(1.0);
+            AiResult::new(f.forecast, confidence.min(1.0))
+                .with_explanation(format!(
+                    "product {} is expected to sell {:.2} units per order (average of the last {} orders)",
+                    f.product_id, f.forecast, f.observations
+                ))
+                .with_metadata(metadata)
+                .with_idempotency_key(f.idempotency_key())
+        })
+        .collect()
+}
+
+fn forecast_product(product: &ProductDemandSnapshot, window: usize) -> Option<SalesForecast> {
+    if product.demand_history.is_empty() {
+        return None;
+    }
+    let start = product.demand_history.len().saturating_sub(window);
+    let recent = &product.demand_history[start..];
+    let forecast = recent.iter().sum::<i64>() as f64 / recent.len() as f64;
+
+    Some(SalesForecast {
+        product_id: product.product_id.clone(),
+        forecast,
+        observations: recent.len(),
+    })
+}
@@ -0,0 +1,127 @@
+//! Per-tenant AI rate limiting and cost accounting.
+//!
+//! External model calls cost money, and a runaway trigger loop (e.g. a projection
+//! that keeps re-firing) could otherwise generate unbounded spend. [`AiUsageMeter`]
+//! gives every tenant a token bucket for AI runs: a run that finds the bucket empty is
+//! dropped (never queued) and counted. Completed runs add their estimated cost units.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use forgeerp_core::TenantId;
+use serde::{Deserialize, Serialize};
+
+/// Token bucket parameters (per tenant).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Maximum burst of runs.
+    pub burst: u32,
+    /// Runs replenished per minute.
+    pub per_minute: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            burst: 10,
+            per_minute: 30,
+        }
+    }
+}
+
+/// AI usage counters for one tenant.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AiUsage {
+    /// Runs admitted and completed successfully.
+    pub runs: u64,
+    /// Runs admitted that failed (backend error or timeout).
+    pub failed: u64,
+    /// Runs dropped because the tenant's rate limit was exhausted.
+    pub dropped: u64,
+    /// Estimated cost units of admitted runs.
+    pub cost_units: u64,
+}
+
+#[derive(Debug)]
+struct TenantMeter {
+    tokens: f64,
+    refilled_at: Instant,
+    usage: AiUsage,
+}
+
+/// Per-tenant token buckets + usage counters.
+#[derive(Debug)]
+pub struct AiUsageMeter {
+    limit: RateLimit,
+    tenants: Mutex<HashMap<TenantId, TenantMeter>>,
+}
+
+impl AiUsageMeter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tenants: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Take one run from the tenant's bucket; `false` (and a dropped run) when empty.
+    pub fn try_acquire(&self, tenant_id: TenantId) -> bool {
+        self.try_acquire_at(tenant_id, Instant::now())
+    }
+
+    /// [`Self::try_acquire`] at an explicit instant (deterministic tests).
+    pub fn try_acquire_at(&self, tenant_id: TenantId, now: Instant) -> bool {
+        let mut tenants = self.tenants.lock().unwrap();
+        let meter = tenants.entry(tenant_id).or_insert_with(|| TenantMeter {
+            tokens: self.limit.burst as f64,
+            refilled_at: now,
+            usage: AiUsage::default(),
+        });
+
+        let elapsed = now.saturating_duration_since(meter.refilled_at).as_secs_f64();
+        meter.tokens = (meter.tokens + elapsed * self.limit.per_minute as f64 / 60.0).min(self.limit.burst as f64);
+        meter.refilled_at = now;
+
+        if meter.tokens >= 1.0 {
+            meter.tokens -= 1.0;
+            true
+        } else {
+            meter.usage.dropped += 1;
+            false
+        }
+    }
+
+    /// Record the outcome of an admitted run.
+    pub fn record(&self, tenant_id: TenantId, cost_units: u64, succeeded: bool) {
+        let mut tenants = self.tenants.lock().unwrap();
+        let Some(meter) = tenants.get_mut(&tenant_id) else {
+            return;
+        };
+        meter.usage.cost_units += cost_units;
+        if succeeded {
+            meter.usage.runs += 1;
+        } else {
+            meter.usage.failed += 1;
+        }
+    }
+
+    pub fn usage(&self, tenant_id: TenantId) -> AiUsage {
+        self.tenants
+            .lock()
+            .unwrap()
+            .get(&tenant_id)
+            .map(|m| m.usage.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for AiUsageMeter {
+    fn default() -> Self {
+        Self::new(RateLimit::default())
+    }
+}
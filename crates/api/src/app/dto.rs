@@ -1,6 +1,10 @@
 use serde::Deserialize;
 
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+
 use forgeerp_accounting::{AccountKind, Account, JournalEntryLine};
+use forgeerp_core::CurrencyConvention;
 use forgeerp_infra::projections::{
     accounting::AccountBalance,
     invoices::InvoiceReadModel,
@@ -8,8 +12,11 @@ use forgeerp_infra::projections::{
     products::ProductReadModel,
     purchasing::PurchaseOrderReadModel,
     sales_orders::SalesOrderReadModel,
+    trial_balance::TrialBalance,
 };
+use forgeerp_infra::tenant_settings::TenantSettings;
 use forgeerp_parties::PartyKind;
+use forgeerp_purchasing::AllocationBasis;
 
 use crate::app::errors;
 
@@ -17,21 +24,77 @@ use crate::app::errors;
 // Request DTOs
 // -------------------------
 
+/// Money amount in a request body.
+///
+/// Integers are minor units (as stored); decimal strings (e.g. `"12.345"`) are major
+/// units, rounded with the tenant's currency convention.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum AmountInput {
+    Minor(i64),
+    Decimal(String),
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateItemRequest {
     pub name: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RenameItemRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetReorderPointRequest {
+    /// `null` clears the reorder point.
+    pub reorder_point: Option<i64>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct AdjustStockRequest {
     pub delta: i64,
+    /// Defaults to the item's default location.
+    #[serde(default)]
+    pub location_id: Option<String>,
+}
+
+/// One entry of a bulk stock adjustment.
+#[derive(Debug, Deserialize)]
+pub struct BulkAdjustStockEntry {
+    pub item_id: String,
+    pub delta: i64,
+    /// Defaults to the item's default location.
+    #[serde(default)]
+    pub location_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransferStockRequest {
+    pub from_location: String,
+    pub to_location: String,
+    pub quantity: i64,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateProductRequest {
     pub sku: String,
     pub name: String,
-    pub pricing: Option<forgeerp_products::PricingMetadata>,
+    pub pricing: Option<PricingRequest>,
+    /// Inventory item holding the product's stock (reserved when sales orders are confirmed).
+    pub inventory_item_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteProductRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PricingRequest {
+    pub base_price: Option<AmountInput>,
+    /// Defaults to the tenant's currency when omitted.
+    pub currency: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,10 +103,114 @@ pub struct RegisterPartyRequest {
     pub contact: Option<forgeerp_parties::ContactInfo>,
 }
 
+/// One field of a JSON merge patch (RFC 7396).
+///
+/// Absent keeps the current value, `null` clears it, anything else replaces it. Fields of
+/// this type need `#[serde(default)]` so that a missing key deserializes as `Absent`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Patch<T> {
+    #[default]
+    Absent,
+    Null,
+    Value(T),
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Option::<T>::deserialize(deserializer).map(|v| v.map_or(Patch::Null, Patch::Value))
+    }
+}
+
+impl<T> Patch<T> {
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Patch::Absent)
+    }
+
+    /// The patched value of a field currently holding `current`.
+    pub fn apply(self, current: Option<T>) -> Option<T> {
+        match self {
+            Patch::Absent => current,
+            Patch::Null => None,
+            Patch::Value(v) => Some(v),
+        }
+    }
+}
+
+/// Contact fields of a party merge patch.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ContactPatch {
+    #[serde(default)]
+    pub email: Patch<String>,
+    #[serde(default)]
+    pub phone: Patch<String>,
+    #[serde(default)]
+    pub address: Patch<String>,
+}
+
+impl ContactPatch {
+    pub fn apply(self, current: forgeerp_parties::ContactInfo) -> forgeerp_parties::ContactInfo {
+        forgeerp_parties::ContactInfo {
+            email: self.email.apply(current.email),
+            phone: self.phone.apply(current.phone),
+            address: self.address.apply(current.address),
+        }
+    }
+}
+
+/// `PATCH` body for a party, with JSON merge patch semantics.
 #[derive(Debug, Deserialize)]
 pub struct UpdatePartyRequest {
+    #[serde(default)]
+    pub name: Patch<String>,
+    /// `null` clears every contact field; an object patches the fields it mentions.
+    #[serde(default)]
+    pub contact: Patch<ContactPatch>,
+    /// Party `version` the edit is based on; a stale edit fails with 412.
+    pub expected_version: Option<u64>,
+}
+
+/// `UpdateDetails` fields of a resolved party patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartyUpdate {
     pub name: Option<String>,
     pub contact: Option<forgeerp_parties::ContactInfo>,
+    pub expected_version: Option<u64>,
+}
+
+impl UpdatePartyRequest {
+    /// Whether resolving needs the party's current contact (a field-level contact patch).
+    pub fn patches_contact_fields(&self) -> bool {
+        matches!(self.contact, Patch::Value(_))
+    }
+
+    /// Resolve against the party's current read model.
+    ///
+    /// Contact fields are merged into `current`; the edit is then pinned to the version
+    /// that was merged against (unless the client sent its own), so a concurrent change
+    /// fails with 412 instead of being overwritten.
+    pub fn resolve(self, current: Option<&PartyReadModel>) -> Result<PartyUpdate, &'static str> {
+        let name = match self.name {
+            Patch::Absent => None,
+            Patch::Null => return Err("name cannot be cleared"),
+            Patch::Value(name) => Some(name),
+        };
+        let (contact, expected_version) = match self.contact {
+            Patch::Absent => (None, self.expected_version),
+            Patch::Null => (Some(forgeerp_parties::ContactInfo::default()), self.expected_version),
+            Patch::Value(patch) => {
+                let current = current.ok_or("party not found")?;
+                (
+                    Some(patch.apply(current.contact())),
+                    self.expected_version.or(Some(current.version)),
+                )
+            }
+        };
+        Ok(PartyUpdate {
+            name,
+            contact,
+            expected_version,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -51,23 +218,33 @@ pub struct SuspendPartyRequest {
     pub reason: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CancelSalesOrderRequest {
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateSalesOrderLineRequest {
     pub product_id: String,
     pub quantity: i64,
-    pub unit_price: u64,
+    pub unit_price: AmountInput,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct IssueInvoiceRequest {
     pub sales_order_id: String,
-    pub due_date: String, // RFC3339
+    /// Days after issue until payment is due; defaults to 30.
+    pub payment_terms_days: Option<u32>,
+    /// Defaults to the tenant's currency when omitted.
+    pub currency: Option<String>,
     pub lines: Vec<CreateSalesOrderLineRequest>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct RegisterPaymentRequest {
-    pub amount: u64,
+    pub amount: AmountInput,
+    /// Defaults to the invoice's currency when omitted; any other is rejected.
+    pub currency: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -75,33 +252,98 @@ pub struct VoidInvoiceRequest {
     pub reason: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ReversePaymentRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreditNoteLineRequest {
+    /// Line number on the original invoice.
+    pub line_no: u32,
+    pub quantity: i64,
+    /// Defaults to the invoiced unit price when omitted.
+    pub unit_price: Option<AmountInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IssueCreditNoteRequest {
+    pub lines: Vec<CreditNoteLineRequest>,
+    /// Defaults to the invoice's currency when omitted; any other is rejected.
+    pub currency: Option<String>,
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PurchaseOrderLineRequest {
     pub product_id: String,
     pub quantity: i64,
+    /// Agreed price per unit (counts toward supplier spend).
+    pub unit_price: Option<AmountInput>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreatePurchaseOrderRequest {
     pub supplier_id: String,
+    /// Promised delivery date (RFC3339); receipts after it count as late.
+    pub expected_at: Option<String>,
+    /// Currency of the line prices; defaults to the tenant's currency when omitted.
+    pub currency: Option<String>,
+    /// Order-level freight, allocated across lines into their landed cost.
+    pub freight: Option<AmountInput>,
+    /// Order-level discount, allocated across lines into their landed cost.
+    pub discount: Option<AmountInput>,
+    /// `value` (default) or `quantity`: how freight and discount are spread.
+    #[serde(default)]
+    pub allocation_basis: AllocationBasis,
     pub lines: Vec<PurchaseOrderLineRequest>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SupplierInvoiceLineRequest {
+    pub product_id: String,
+    pub quantity: i64,
+    pub unit_price: AmountInput,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PurchaseMatchRequest {
+    /// Supplier invoice lines to match against the order and its receipts.
+    pub lines: Vec<SupplierInvoiceLineRequest>,
+    /// Defaults to the tenant's currency when omitted.
+    pub currency: Option<String>,
+    /// Defaults to `MatchTolerances::default()`.
+    pub tolerances: Option<forgeerp_infra::three_way_match::MatchTolerances>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateLedgerLineRequest {
     pub account_code: String,
     pub account_name: String,
     pub kind: String,
-    pub amount: i64,
+    pub amount: AmountInput,
     pub is_debit: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct PostJournalEntryRequest {
     pub description: Option<String>,
+    /// Defaults to the tenant's currency when omitted.
+    pub currency: Option<String>,
     pub lines: Vec<CreateLedgerLineRequest>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ReverseJournalEntryRequest {
+    pub description: Option<String>,
+}
+
+/// Body of `POST /ledger/periods/close` and `POST /ledger/periods/reopen`.
+#[derive(Debug, Deserialize)]
+pub struct LedgerPeriodRequest {
+    pub period_end: chrono::DateTime<chrono::Utc>,
+}
+
 // -------------------------
 // JSON mapping helpers
 // -------------------------
@@ -111,9 +353,30 @@ pub fn inventory_to_json(rm: forgeerp_infra::projections::inventory_stock::Inven
         "id": rm.item_id.0.to_string(),
         "name": rm.name,
         "quantity": rm.quantity,
+        "last_sequence": rm.last_sequence,
+        "updated_at": rm.updated_at,
     })
 }
 
+pub fn inventory_movement_to_json(m: forgeerp_infra::projections::InventoryMovement) -> serde_json::Value {
+    serde_json::json!({
+        "sequence": m.sequence,
+        "delta": m.delta,
+        "balance": m.balance,
+        "occurred_at": m.occurred_at,
+        "reason": m.reason,
+    })
+}
+
+/// Value of an item movement sort field, as in [`inventory_movement_to_json`].
+pub fn inventory_movement_list_field(m: &forgeerp_infra::projections::InventoryMovement, field: &str) -> serde_json::Value {
+    match field {
+        "sequence" => m.sequence.into(),
+        "occurred_at" => serde_json::to_value(m.occurred_at).unwrap_or_default(),
+        _ => serde_json::Value::Null,
+    }
+}
+
 pub fn product_to_json(rm: ProductReadModel) -> serde_json::Value {
     serde_json::json!({
         "id": rm.product_id.0.to_string(),
@@ -123,7 +386,31 @@ pub fn product_to_json(rm: ProductReadModel) -> serde_json::Value {
         "pricing": {
             "base_price": rm.pricing.base_price,
             "currency": rm.pricing.currency,
-        }
+        },
+        "inventory_item_id": rm.inventory_item_id.map(|id| id.to_string()),
+    })
+}
+
+/// Value of a `GET /products` sort or filter field, as in [`product_to_json`].
+pub fn product_list_field(rm: &ProductReadModel, field: &str) -> serde_json::Value {
+    match field {
+        "id" => rm.product_id.0.to_string().into(),
+        "sku" => rm.sku.clone().into(),
+        "name" => rm.name.clone().into(),
+        "status" => format!("{:?}", rm.status).to_lowercase().into(),
+        _ => serde_json::Value::Null,
+    }
+}
+
+/// Admin view of a deleted product.
+pub fn product_tombstone_to_json(rm: ProductReadModel) -> serde_json::Value {
+    let tombstone = rm.tombstone.as_ref();
+    serde_json::json!({
+        "id": rm.product_id.0.to_string(),
+        "sku": rm.sku,
+        "name": rm.name,
+        "reason": tombstone.and_then(|t| t.reason.clone()),
+        "deleted_at": tombstone.map(|t| t.deleted_at),
     })
 }
 
@@ -134,10 +421,23 @@ pub fn party_to_json(rm: PartyReadModel) -> serde_json::Value {
         "name": rm.name,
         "email": rm.email,
         "phone": rm.phone,
+        "address": rm.address,
         "status": format!("{:?}", rm.status).to_lowercase(),
+        "version": rm.version,
     })
 }
 
+/// Value of a customer or supplier list sort or filter field, as in [`party_to_json`].
+pub fn party_list_field(rm: &PartyReadModel, field: &str) -> serde_json::Value {
+    match field {
+        "id" => rm.party_id.0.to_string().into(),
+        "name" => rm.name.clone().into(),
+        "email" => rm.email.clone().into(),
+        "status" => format!("{:?}", rm.status).to_lowercase().into(),
+        _ => serde_json::Value::Null,
+    }
+}
+
 pub fn sales_order_to_json(rm: SalesOrderReadModel) -> serde_json::Value {
     serde_json::json!({
         "id": rm.order_id.0.to_string(),
@@ -147,19 +447,34 @@ pub fn sales_order_to_json(rm: SalesOrderReadModel) -> serde_json::Value {
             "product_id": l.product_id.0.to_string(),
             "quantity": l.quantity,
             "unit_price": l.unit_price,
-        })).collect::<Vec<_>>()
+        })).collect::<Vec<_>>(),
+        "backordered_lines": rm.backordered_lines,
+        "flag_reason": rm.flag_reason,
+        "total_amount": rm.total_amount,
     })
 }
 
+/// Value of a `GET /sales/orders` sort or filter field, as in [`sales_order_to_json`].
+pub fn sales_order_list_field(rm: &SalesOrderReadModel, field: &str) -> serde_json::Value {
+    match field {
+        "id" => rm.order_id.0.to_string().into(),
+        "status" => format!("{:?}", rm.status).to_lowercase().into(),
+        _ => serde_json::Value::Null,
+    }
+}
+
 pub fn invoice_to_json(rm: InvoiceReadModel) -> serde_json::Value {
     serde_json::json!({
         "id": rm.invoice_id.0.to_string(),
         "sales_order_id": rm.sales_order_id.0.to_string(),
         "status": format!("{:?}", rm.status).to_lowercase(),
         "due_date": rm.due_date.map(|d| d.to_rfc3339()),
+        "currency": rm.currency,
         "total_amount": rm.total_amount,
+        "credited_total": rm.credited_total,
+        "net_total": rm.net_total(),
         "total_paid": rm.total_paid,
-        "outstanding_amount": rm.total_amount.saturating_sub(rm.total_paid),
+        "outstanding_amount": rm.outstanding_amount(),
         "lines": rm.lines.into_iter().map(|l| serde_json::json!({
             "line_no": l.line_no,
             "product_id": l.product_id.0.to_string(),
@@ -169,16 +484,64 @@ pub fn invoice_to_json(rm: InvoiceReadModel) -> serde_json::Value {
     })
 }
 
+/// Value of a `GET /invoices` sort or filter field, as in [`invoice_to_json`].
+pub fn invoice_list_field(rm: &InvoiceReadModel, field: &str) -> serde_json::Value {
+    match field {
+        "id" => rm.invoice_id.0.to_string().into(),
+        "sales_order_id" => rm.sales_order_id.0.to_string().into(),
+        "status" => format!("{:?}", rm.status).to_lowercase().into(),
+        "due_date" => rm.due_date.map(|d| d.to_rfc3339()).into(),
+        "total_amount" => rm.total_amount.into(),
+        "outstanding_amount" => rm.outstanding_amount().into(),
+        _ => serde_json::Value::Null,
+    }
+}
+
 pub fn purchase_order_to_json(rm: PurchaseOrderReadModel) -> serde_json::Value {
     serde_json::json!({
         "id": rm.order_id.0.to_string(),
         "supplier_id": rm.supplier_id.0.to_string(),
         "status": format!("{:?}", rm.status).to_lowercase(),
-        "lines": rm.lines.into_iter().map(|l| serde_json::json!({
-            "line_no": l.line_no,
-            "product_id": l.product_id.0.to_string(),
-            "quantity": l.quantity,
-        })).collect::<Vec<_>>()
+        "freight": rm.freight,
+        "discount": rm.discount,
+        "allocation_basis": rm.allocation_basis,
+        "lines": rm.lines.iter().map(|l| {
+            let landed = rm.landed_cost(l.line_no);
+            serde_json::json!({
+                "line_no": l.line_no,
+                "product_id": l.product_id.0.to_string(),
+                "quantity": l.quantity,
+                "unit_cost": rm.unit_costs.get(&l.line_no),
+                "allocated_freight": landed.map(|c| c.freight),
+                "allocated_discount": landed.map(|c| c.discount),
+                "landed_cost": landed.map(|c| c.landed_cost),
+                "landed_unit_cost": landed.and_then(|c| c.unit_landed_cost()),
+            })
+        }).collect::<Vec<_>>()
+    })
+}
+
+/// Value of a `GET /purchases/orders` sort or filter field, as in [`purchase_order_to_json`].
+pub fn purchase_order_list_field(rm: &PurchaseOrderReadModel, field: &str) -> serde_json::Value {
+    match field {
+        "id" => rm.order_id.0.to_string().into(),
+        "supplier_id" => rm.supplier_id.0.to_string().into(),
+        "status" => format!("{:?}", rm.status).to_lowercase().into(),
+        _ => serde_json::Value::Null,
+    }
+}
+
+pub fn supplier_performance_to_json(p: forgeerp_infra::projections::SupplierPerformance) -> serde_json::Value {
+    serde_json::json!({
+        "supplier_id": p.supplier_id.0.to_string(),
+        "from": p.window.from,
+        "to": p.window.to,
+        "orders_received": p.orders_received,
+        "orders_with_due_date": p.orders_with_due_date,
+        "orders_on_time": p.orders_on_time,
+        "on_time_rate": p.on_time_rate,
+        "average_delay_hours": p.average_delay.map(|d| d.num_seconds() as f64 / 3600.0),
+        "total_spend": p.total_spend,
     })
 }
 
@@ -191,8 +554,108 @@ pub fn ledger_balance_to_json(b: AccountBalance) -> serde_json::Value {
     })
 }
 
+pub fn trial_balance_to_json(tb: TrialBalance) -> serde_json::Value {
+    let kinds = tb
+        .kinds
+        .iter()
+        .map(|k| {
+            serde_json::json!({
+                "kind": format!("{:?}", k.kind).to_lowercase(),
+                "debit_total": k.debit_total.to_string(),
+                "credit_total": k.credit_total.to_string(),
+                "balance": k.balance().to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::json!({
+        "kinds": kinds,
+        "total_debits": tb.total_debits.to_string(),
+        "total_credits": tb.total_credits.to_string(),
+        "balanced": tb.balanced,
+    })
+}
+
+/// Rejected currency or amount in a request; answered as 400 `code`.
+#[derive(Debug)]
+pub struct MoneyInputError {
+    code: &'static str,
+    message: String,
+}
+
+impl MoneyInputError {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for MoneyInputError {
+    fn into_response(self) -> axum::response::Response {
+        errors::json_error(StatusCode::BAD_REQUEST, self.code, self.message)
+    }
+}
+
+impl From<MoneyInputError> for axum::response::Response {
+    fn from(err: MoneyInputError) -> Self {
+        err.into_response()
+    }
+}
+
+/// Tenant currency convention for a request (`currency` falls back to the tenant default).
+pub fn currency_convention(
+    settings: &TenantSettings,
+    currency: Option<&str>,
+) -> Result<CurrencyConvention, MoneyInputError> {
+    settings
+        .convention(currency)
+        .map_err(|e| MoneyInputError::new("invalid_currency", e.to_string()))
+}
+
+/// Amount in minor units, rounded with `convention`.
+pub fn to_minor_units(
+    amount: &AmountInput,
+    convention: &CurrencyConvention,
+) -> Result<i64, MoneyInputError> {
+    match amount {
+        AmountInput::Minor(v) => Ok(*v),
+        AmountInput::Decimal(s) => convention
+            .to_minor_units(s)
+            .map_err(|e| MoneyInputError::new("invalid_amount", e.to_string())),
+    }
+}
+
+/// Non-negative amount in minor units (prices, payments).
+pub fn to_unsigned_minor_units(
+    amount: &AmountInput,
+    convention: &CurrencyConvention,
+) -> Result<u64, MoneyInputError> {
+    let minor = to_minor_units(amount, convention)?;
+    u64::try_from(minor).map_err(|_| MoneyInputError::new("invalid_amount", "amount must not be negative"))
+}
+
+pub fn to_pricing(
+    pricing: Option<PricingRequest>,
+    settings: &TenantSettings,
+) -> Result<Option<forgeerp_products::PricingMetadata>, MoneyInputError> {
+    let Some(p) = pricing else {
+        return Ok(None);
+    };
+    let convention = currency_convention(settings, p.currency.as_deref())?;
+    let base_price = match &p.base_price {
+        Some(amount) => Some(to_unsigned_minor_units(amount, &convention)?),
+        None => None,
+    };
+    Ok(Some(forgeerp_products::PricingMetadata {
+        base_price,
+        currency: Some(convention.code),
+    }))
+}
+
 pub fn to_journal_lines(
     req_lines: Vec<CreateLedgerLineRequest>,
+    convention: &CurrencyConvention,
 ) -> Result<Vec<JournalEntryLine>, axum::response::Response> {
     let mut lines = Vec::with_capacity(req_lines.len());
     for l in req_lines {
@@ -206,7 +669,7 @@ pub fn to_journal_lines(
                 name: l.account_name,
                 kind,
             },
-            amount: l.amount,
+            amount: to_minor_units(&l.amount, convention)?,
             is_debit: l.is_debit,
         });
     }
@@ -214,3 +677,68 @@ pub fn to_journal_lines(
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forgeerp_core::AggregateId;
+    use forgeerp_parties::{ContactInfo, PartyId, PartyStatus};
+
+    fn party() -> PartyReadModel {
+        PartyReadModel {
+            party_id: PartyId::new(AggregateId::new()),
+            kind: PartyKind::Customer,
+            name: "Acme".to_string(),
+            email: Some("ops@acme.test".to_string()),
+            phone: Some("555-0100".to_string()),
+            address: Some("1 Main St".to_string()),
+            status: PartyStatus::Active,
+            version: 3,
+        }
+    }
+
+    fn resolve(body: serde_json::Value) -> PartyUpdate {
+        let request: UpdatePartyRequest = serde_json::from_value(body).unwrap();
+        request.resolve(Some(&party())).unwrap()
+    }
+
+    #[test]
+    fn absent_fields_are_kept() {
+        let update = resolve(serde_json::json!({}));
+        assert_eq!((update.name, update.contact, update.expected_version), (None, None, None));
+
+        let update = resolve(serde_json::json!({"contact": {"email": "billing@acme.test"}}));
+        let contact = update.contact.unwrap();
+        assert_eq!(contact.email.as_deref(), Some("billing@acme.test"));
+        assert_eq!(contact.phone.as_deref(), Some("555-0100"));
+        assert_eq!(contact.address.as_deref(), Some("1 Main St"));
+        // Merged against version 3, so a concurrent edit is caught.
+        assert_eq!(update.expected_version, Some(3));
+    }
+
+    #[test]
+    fn null_fields_are_cleared() {
+        let update = resolve(serde_json::json!({"contact": {"phone": null}, "expected_version": 2}));
+        assert_eq!(
+            update.contact,
+            Some(ContactInfo {
+                email: Some("ops@acme.test".to_string()),
+                phone: None,
+                address: Some("1 Main St".to_string()),
+            })
+        );
+        assert_eq!(update.expected_version, Some(2));
+
+        assert_eq!(resolve(serde_json::json!({"contact": null})).contact, Some(ContactInfo::default()));
+
+        let request: UpdatePartyRequest = serde_json::from_value(serde_json::json!({"name": null})).unwrap();
+        assert!(request.resolve(Some(&party())).is_err());
+    }
+
+    #[test]
+    fn values_replace_fields() {
+        let update = resolve(serde_json::json!({"name": "Acme Ltd", "contact": {"address": "2 High St"}}));
+        assert_eq!(update.name.as_deref(), Some("Acme Ltd"));
+        assert_eq!(update.contact.unwrap().address.as_deref(), Some("2 High St"));
+    }
+}
@@ -3,30 +3,81 @@ use axum::response::IntoResponse;
 use serde_json::json;
 
 use forgeerp_accounting::AccountKind;
+use forgeerp_infra::ai::AiInsightStoreError;
+use forgeerp_infra::command_bus::CommandBusError;
 use forgeerp_infra::command_dispatcher::DispatchError;
 
+use crate::authz::CommandDenied;
+
+/// Map a dispatch failure to a JSON error.
+///
+/// When the request dispatched through the retrying dispatcher, the body also carries a
+/// `retry` object (`attempts`, `max_attempts`, `exhausted`).
 pub fn dispatch_error_to_response(err: DispatchError) -> axum::response::Response {
+    let (status, code, message) = dispatch_error_parts(err);
+
+    match crate::middleware::current_retry() {
+        Some(retry) => (
+            status,
+            axum::Json(json!({
+                "error": code,
+                "message": message,
+                "retry": retry,
+            })),
+        )
+            .into_response(),
+        None => json_error(status, code, message),
+    }
+}
+
+/// Status, error code and message a dispatch failure maps to.
+pub fn dispatch_error_parts(err: DispatchError) -> (StatusCode, &'static str, String) {
+    match err {
+        DispatchError::Concurrency(msg) => (StatusCode::CONFLICT, "conflict", msg),
+        DispatchError::Validation(msg) => (StatusCode::BAD_REQUEST, "validation_error", msg),
+        DispatchError::InvariantViolation(msg) => (StatusCode::UNPROCESSABLE_ENTITY, "invariant_violation", msg),
+        DispatchError::Unauthorized => (StatusCode::FORBIDDEN, "unauthorized", "unauthorized".to_string()),
+        DispatchError::NotFound => (StatusCode::NOT_FOUND, "not_found", "not found".to_string()),
+        DispatchError::Deserialize(msg) => (StatusCode::INTERNAL_SERVER_ERROR, "deserialize_error", msg),
+        DispatchError::Store(e) => (StatusCode::INTERNAL_SERVER_ERROR, "store_error", format!("{e:?}")),
+        DispatchError::Publish { reason, .. } => (StatusCode::BAD_GATEWAY, "publish_error", reason),
+        DispatchError::TenantIsolation(msg) => (StatusCode::FORBIDDEN, "tenant_isolation", msg),
+        DispatchError::StreamLimit(msg) => (StatusCode::TOO_MANY_REQUESTS, "stream_limit", msg),
+        DispatchError::PreconditionFailed(msg) => (StatusCode::PRECONDITION_FAILED, "precondition_failed", msg),
+        DispatchError::Overloaded(msg) => (StatusCode::TOO_MANY_REQUESTS, "overloaded", msg),
+        DispatchError::Rejected { error, .. } => dispatch_error_parts(*error),
+    }
+}
+
+/// 403 for a refused command, with the denial kind and the permissions the principal lacked.
+pub fn command_denied_to_response(denied: CommandDenied) -> axum::response::Response {
+    (
+        StatusCode::FORBIDDEN,
+        axum::Json(json!({
+            "error": "forbidden",
+            "message": denied.to_string(),
+            "denial": denied,
+        })),
+    )
+        .into_response()
+}
+
+pub fn command_bus_error_to_response(err: CommandBusError) -> axum::response::Response {
     match err {
-        DispatchError::Concurrency(msg) => json_error(StatusCode::CONFLICT, "conflict", msg),
-        DispatchError::Validation(msg) => json_error(StatusCode::BAD_REQUEST, "validation_error", msg),
-        DispatchError::InvariantViolation(msg) => {
-            json_error(StatusCode::UNPROCESSABLE_ENTITY, "invariant_violation", msg)
-        }
-        DispatchError::Unauthorized => json_error(StatusCode::FORBIDDEN, "unauthorized", "unauthorized"),
-        DispatchError::NotFound => json_error(StatusCode::NOT_FOUND, "not_found", "not found"),
-        DispatchError::Deserialize(msg) => {
-            json_error(StatusCode::INTERNAL_SERVER_ERROR, "deserialize_error", msg)
-        }
-        DispatchError::Store(e) => json_error(
+        CommandBusError::Unauthorized(e) => json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string()),
+        CommandBusError::NoHandler(ty) => json_error(
             StatusCode::INTERNAL_SERVER_ERROR,
-            "store_error",
-            format!("{e:?}"),
+            "no_handler",
+            format!("no handler registered for {ty}"),
         ),
-        DispatchError::Publish(msg) => json_error(StatusCode::BAD_GATEWAY, "publish_error", msg),
-        DispatchError::TenantIsolation(msg) => json_error(StatusCode::FORBIDDEN, "tenant_isolation", msg),
+        CommandBusError::Dispatch(e) => dispatch_error_to_response(e),
     }
 }
 
+pub fn ai_insight_store_error_to_response(err: AiInsightStoreError) -> axum::response::Response {
+    json_error(StatusCode::INTERNAL_SERVER_ERROR, "ai_insight_store_error", err.to_string())
+}
+
 pub fn json_error(
     status: StatusCode,
     code: &'static str,
@@ -5,8 +5,11 @@
 //! - `routes/`: HTTP routes + handlers (one file per domain area)
 //! - `dto.rs`: request/response DTOs and JSON mapping helpers
 //! - `errors.rs`: consistent error responses
+//! - `query.rs`: shared, validated query parameters for list endpoints
+//! - `shutdown.rs`: graceful shutdown (signals, draining the server)
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{routing::get, Extension, Router};
 use tower::ServiceBuilder;
@@ -15,30 +18,135 @@ use crate::middleware;
 
 pub mod dto;
 pub mod errors;
+pub mod query;
 pub mod routes;
 pub mod services;
+pub mod shutdown;
 
-/// Build the full HTTP router (public entrypoint used by `main.rs`).
+/// Build the full HTTP router (used by the black-box tests).
 pub async fn build_app(jwt_secret: String) -> Router {
-    let jwt = Arc::new(forgeerp_auth::Hs256JwtValidator::new(jwt_secret.into_bytes()));
-    let auth_state = middleware::AuthState { jwt };
+    build_app_with_services(jwt_secret).await.0
+}
+
+/// Build the full HTTP router and the services behind it (public entrypoint used by
+/// `main.rs`, which stops their background workers on shutdown).
+pub async fn build_app_with_services(jwt_secret: String) -> (Router, Arc<services::AppServices>) {
+    let list = |name: &str| -> Vec<String> {
+        std::env::var(name)
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect()
+    };
+    // Tokens from an external IdP (RS256, keys from its JWKS) or signed with the shared secret.
+    let jwt: Arc<dyn forgeerp_auth::JwtValidator> = match std::env::var("JWT_JWKS_URL") {
+        Ok(url) if !url.trim().is_empty() => Arc::new(
+            load_jwks(url.trim())
+                .await
+                .with_audiences(list("JWT_AUDIENCES"))
+                .with_issuers(list("JWT_ISSUERS")),
+        ),
+        _ => Arc::new(
+            forgeerp_auth::Hs256JwtValidator::new(jwt_secret.into_bytes())
+                .with_audiences(list("JWT_AUDIENCES"))
+                .with_issuers(list("JWT_ISSUERS")),
+        ),
+    };
+    let mut auth_state =
+        middleware::AuthState::new(jwt).with_api_keys(Arc::new(service_api_keys(&list("SERVICE_API_KEYS"))));
+    // Revoked token ids are enforced with JWT_REVOCATION=true (tokens must then carry a `jti`).
+    // Suspension cutoffs recorded in the store are enforced either way (see below).
+    let revocations: Arc<dyn forgeerp_auth::RevocationStore> = Arc::new(forgeerp_auth::InMemoryRevocationStore::new());
+    let revocation_enabled = std::env::var("JWT_REVOCATION")
+        .ok()
+        .and_then(|v| v.trim().parse::<bool>().ok())
+        .unwrap_or(false);
+    if revocation_enabled {
+        auth_state = auth_state.with_revocations(revocations.clone());
+    }
 
     let services = Arc::new(services::build_services().await);
+    let memberships = services.clone();
+    let suspensions = services.clone();
+    let cutoffs = revocations.clone();
+    // Tokens issued before a user's suspension in a tenant are refused there. The cutoff is
+    // rebuilt from events; the store has it as soon as the suspension is accepted.
+    let auth_state = auth_state
+        .with_memberships(Arc::new(move |principal_id, tenant_id| {
+            memberships.users_membership(tenant_id, principal_id)
+        }))
+        .with_suspensions(Arc::new(move |principal_id, tenant_id| {
+            suspensions
+                .users_suspended_at(tenant_id, principal_id)
+                .max(cutoffs.revoked_before(tenant_id, principal_id))
+        }));
+    let tasks = services.tasks().clone();
+    let publish_breaker = services.publish_breaker().clone();
+    let dependencies = services.clone();
+    let background = services.clone();
     let replay_jobs = routes::replay::ReplayJobStore::new();
 
     // Protected routes: require auth + tenant context.
     let protected = routes::router()
         .layer(Extension(services))
         .layer(Extension(replay_jobs))
+        .layer(Extension(revocations))
+        .layer(axum::middleware::from_fn(middleware::retry_middleware))
+        .layer(axum::middleware::from_fn_with_state(
+            Arc::new(middleware::IdempotencyCache::default()),
+            middleware::idempotency_middleware,
+        ))
         .layer(axum::middleware::from_fn_with_state(
             auth_state,
             middleware::auth_middleware,
         ));
 
-    Router::new()
+    let router = Router::new()
+        .route("/livez", get(routes::system::livez))
         .route("/health", get(routes::system::health))
+        .route("/health/ready", get(routes::system::ready))
+        .route("/metrics", get(routes::system::metrics))
+        .layer(Extension(tasks))
+        .layer(Extension(publish_breaker))
+        .layer(Extension(dependencies))
         .merge(protected)
-        .layer(ServiceBuilder::new())
+        .layer(ServiceBuilder::new());
+    (router, background)
 }
 
+/// Longest pause between attempts to load `JWT_JWKS_URL` at startup.
+const MAX_JWKS_RETRY_PAUSE: Duration = Duration::from_secs(30);
 
+/// RS256 validator for the JWKS at `url`, retried with backoff until the identity
+/// provider answers (startup waits for it instead of failing).
+async fn load_jwks(url: &str) -> forgeerp_auth::Rs256JwtValidator {
+    let mut pause = Duration::from_secs(1);
+    loop {
+        match forgeerp_auth::Rs256JwtValidator::from_jwks_url(url).await {
+            Ok(validator) => return validator,
+            Err(e) => {
+                tracing::warn!("failed to load JWT_JWKS_URL, retrying in {}s: {e}", pause.as_secs());
+                tokio::time::sleep(pause).await;
+                pause = (pause * 2).min(MAX_JWKS_RETRY_PAUSE);
+            }
+        }
+    }
+}
+
+/// Service accounts from `SERVICE_API_KEYS` entries of the form
+/// `<sha256 hex of the key>:<tenant_id>:<principal_id>:<role>+<role>`.
+fn service_api_keys(entries: &[String]) -> forgeerp_auth::ApiKeyValidator {
+    entries.iter().fold(forgeerp_auth::ApiKeyValidator::new(), |keys, entry| {
+        let invalid = || panic!("invalid SERVICE_API_KEYS entry (expected hash:tenant_id:principal_id:roles)");
+        let [hash, tenant_id, principal_id, roles] = entry.split(':').collect::<Vec<_>>()[..] else {
+            invalid()
+        };
+        let account = forgeerp_auth::ServiceAccount {
+            principal_id: principal_id.parse().unwrap_or_else(|_| invalid()),
+            tenant_id: tenant_id.parse().unwrap_or_else(|_| invalid()),
+            roles: roles.split('+').filter(|r| !r.is_empty()).map(|r| forgeerp_auth::Role::new(r.to_string())).collect(),
+        };
+        keys.with_hashed_key(hash, account).unwrap_or_else(|_| invalid())
+    })
+}
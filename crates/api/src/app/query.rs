@@ -0,0 +1,637 @@
+//! Typed query-string handling for list endpoints.
+//!
+//! Every list endpoint accepts the same paging/sorting parameters:
+//! - `limit`: page size (default: 50, max: 500; larger values are rejected)
+//! - `cursor`: opaque continuation token returned as `next_cursor` by the previous page
+//! - `offset`: numeric alternative to `cursor` (cannot be combined with it)
+//! - `sort`: field name, prefixed with `-` for descending order (e.g. `sort=-name`)
+//!
+//! Cursors are keyset positions (base64 of the sort key of the last item served), so
+//! items inserted or removed while a client pages through a list never shift others
+//! between pages. Offset paging is kept for compatibility; new clients should follow
+//! `next_cursor`. Results are always ordered by the requested sort, then by the
+//! endpoint's unique [`ListSpec::KEY`].
+//!
+//! On top of that, each endpoint declares the sort fields and filters it supports
+//! through a [`ListSpec`]. Anything else in the query string is rejected with a
+//! structured 400 instead of being silently ignored.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Query},
+    http::{request::Parts, StatusCode},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use forgeerp_infra::read_model::{ListPage, PageStart};
+
+use crate::app::errors;
+
+pub const DEFAULT_LIMIT: u32 = 50;
+pub const MAX_LIMIT: u32 = 500;
+
+/// Per-endpoint description of accepted sort fields and filters.
+pub trait ListSpec: Send + Sync + 'static {
+    /// Fields accepted by `sort`.
+    const SORT_FIELDS: &'static [&'static str];
+    /// Additional query parameters accepted as filters.
+    const FILTERS: &'static [&'static str];
+    /// Unique field breaking sort ties, so cursors identify a single position.
+    const KEY: &'static str = "id";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sort {
+    pub field: &'static str,
+    pub direction: SortDirection,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ListQueryError {
+    #[error("limit must be a positive integer, got `{0}`")]
+    InvalidLimit(String),
+
+    #[error("limit must not exceed {MAX_LIMIT}, got {0}")]
+    LimitTooLarge(u64),
+
+    #[error("invalid cursor `{0}`")]
+    InvalidCursor(String),
+
+    #[error("cursor and offset cannot be combined")]
+    CursorAndOffset,
+
+    #[error("cannot sort by `{field}`; allowed: {allowed}")]
+    InvalidSort { field: String, allowed: String },
+
+    #[error("unknown query parameter `{0}`")]
+    UnknownParameter(String),
+}
+
+impl ListQueryError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ListQueryError::InvalidLimit(_) | ListQueryError::LimitTooLarge(_) => "invalid_limit",
+            ListQueryError::InvalidCursor(_) | ListQueryError::CursorAndOffset => "invalid_cursor",
+            ListQueryError::InvalidSort { .. } => "invalid_sort",
+            ListQueryError::UnknownParameter(_) => "unknown_parameter",
+        }
+    }
+
+    pub fn into_response(self) -> axum::response::Response {
+        errors::json_error(StatusCode::BAD_REQUEST, self.code(), self.to_string())
+    }
+}
+
+/// Validated list parameters for the endpoint described by `S`.
+#[derive(Debug, Clone)]
+pub struct ListQuery<S> {
+    pub limit: u32,
+    pub offset: u32,
+    pub sort: Option<Sort>,
+    cursor: Option<String>,
+    offset_requested: bool,
+    filters: BTreeMap<&'static str, String>,
+    _spec: PhantomData<S>,
+}
+
+/// Keyset position in a read-model list: the sort value and key of the last item served.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ListCursor {
+    sort: Option<String>,
+    value: Value,
+    key: Value,
+}
+
+impl<S: ListSpec> ListQuery<S> {
+    /// Validate raw query parameters against `S`.
+    pub fn parse(raw: &HashMap<String, String>) -> Result<Self, ListQueryError> {
+        let mut limit = DEFAULT_LIMIT;
+        let mut offset = 0;
+        let mut cursor = None;
+        let mut offset_requested = false;
+        let mut sort = None;
+        let mut filters = BTreeMap::new();
+
+        if let Some(raw_limit) = raw.get("limit") {
+            let parsed: u64 = raw_limit
+                .trim()
+                .parse()
+                .map_err(|_| ListQueryError::InvalidLimit(raw_limit.clone()))?;
+            if parsed == 0 {
+                return Err(ListQueryError::InvalidLimit(raw_limit.clone()));
+            }
+            if parsed > MAX_LIMIT as u64 {
+                return Err(ListQueryError::LimitTooLarge(parsed));
+            }
+            limit = parsed as u32;
+        }
+
+        match (raw.get("cursor"), raw.get("offset")) {
+            (Some(_), Some(_)) => return Err(ListQueryError::CursorAndOffset),
+            (Some(token), None) => {
+                decode_cursor::<Value>(token)?;
+                cursor = Some(token.clone());
+            }
+            (None, Some(raw_offset)) => {
+                offset = raw_offset
+                    .trim()
+                    .parse()
+                    .map_err(|_| ListQueryError::InvalidCursor(raw_offset.clone()))?;
+                offset_requested = true;
+            }
+            (None, None) => {}
+        }
+
+        if let Some(raw_sort) = raw.get("sort") {
+            let (direction, name) = match raw_sort.strip_prefix('-') {
+                Some(rest) => (SortDirection::Desc, rest),
+                None => (SortDirection::Asc, raw_sort.as_str()),
+            };
+            let field = S::SORT_FIELDS
+                .iter()
+                .copied()
+                .find(|f| *f == name)
+                .ok_or_else(|| ListQueryError::InvalidSort {
+                    field: name.to_string(),
+                    allowed: S::SORT_FIELDS.join(", "),
+                })?;
+            sort = Some(Sort { field, direction });
+        }
+
+        for (key, value) in raw {
+            if matches!(key.as_str(), "limit" | "cursor" | "offset" | "sort") {
+                continue;
+            }
+            let name = S::FILTERS
+                .iter()
+                .copied()
+                .find(|f| *f == key)
+                .ok_or_else(|| ListQueryError::UnknownParameter(key.clone()))?;
+            filters.insert(name, value.clone());
+        }
+
+        Ok(Self {
+            limit,
+            offset,
+            offset_requested,
+            sort,
+            cursor,
+            filters,
+            _spec: PhantomData,
+        })
+    }
+
+    /// Value of a declared filter, if present in the query string.
+    pub fn filter(&self, name: &str) -> Option<&str> {
+        self.filters.get(name).map(String::as_str)
+    }
+
+    /// Whether the client asked for offset paging with `offset`.
+    pub fn uses_offset(&self) -> bool {
+        self.offset_requested
+    }
+
+    /// The decoded keyset cursor, if one was supplied.
+    pub fn cursor<T: DeserializeOwned>(&self) -> Result<Option<T>, ListQueryError> {
+        self.cursor.as_deref().map(decode_cursor).transpose()
+    }
+
+    /// Page a read-model store through `list`.
+    ///
+    /// The filters, the sort (then [`ListSpec::KEY`]) and the cursor position go to the
+    /// store as predicates over its rows, read through `field` (a row's value of a list
+    /// field as it appears in the row's JSON). The store copies out only the page, and
+    /// `next_cursor` is the position of the page's last row.
+    pub fn page_from<V>(
+        &self,
+        field: impl Fn(&V, &str) -> Value,
+        list: impl FnOnce(&dyn Fn(&V) -> bool, &dyn Fn(&V, &V) -> Ordering, PageStart<'_, V>, usize) -> ListPage<V>,
+        to_json: impl Fn(V) -> Value,
+    ) -> Result<Value, ListQueryError> {
+        let position = |row: &V| (self.sort.map(|sort| field(row, sort.field)), field(row, S::KEY));
+        let keep = |row: &V| {
+            self.filters
+                .iter()
+                .all(|(name, expected)| field_matches(Some(&field(row, name)), expected))
+        };
+        let order = |a: &V, b: &V| {
+            let (a, b) = (position(a), position(b));
+            self.compare((a.0.as_ref(), Some(&a.1)), (b.0.as_ref(), Some(&b.1)))
+        };
+
+        let cursor = self.resume_position()?;
+        let after = |row: &V| match &cursor {
+            Some(cursor) => {
+                let row = position(row);
+                self.compare((row.0.as_ref(), Some(&row.1)), (Some(&cursor.value), Some(&cursor.key)))
+                    == Ordering::Greater
+            }
+            None => true,
+        };
+        let start = match cursor {
+            Some(_) => PageStart::After(&after),
+            None => PageStart::Offset(self.offset as usize),
+        };
+
+        let page = list(&keep, &order, start, self.limit as usize);
+        let next_cursor = page
+            .has_more()
+            .then(|| page.items.last().map(position))
+            .flatten()
+            .map(|(value, key)| self.encode_position(value.as_ref(), Some(&key)));
+        Ok(serde_json::json!({
+            "items": page.items.into_iter().map(to_json).collect::<Vec<_>>(),
+            "limit": self.limit,
+            "offset": page.offset,
+            "total": page.total,
+            "next_cursor": next_cursor,
+        }))
+    }
+
+    /// Filter (by exact field match), sort and page already-serialized list items.
+    ///
+    /// Returns the `{ items, limit, offset, total, next_cursor }` response body: `total`
+    /// counts the items left after filtering, `offset` is the position of the first
+    /// item served (for cursor pages too; for offset pages, the requested offset).
+    pub fn page(&self, mut items: Vec<Value>) -> Result<Value, ListQueryError> {
+        items.retain(|item| {
+            self.filters
+                .iter()
+                .all(|(field, expected)| field_matches(item.get(*field), expected))
+        });
+
+        items.sort_by(|a, b| self.compare(self.sort_key(a), self.sort_key(b)));
+        let total = items.len();
+
+        let (start, offset) = match self.resume_position()? {
+            Some(cursor) => {
+                let position = (Some(&cursor.value), Some(&cursor.key));
+                let start =
+                    items.partition_point(|item| self.compare(self.sort_key(item), position) != Ordering::Greater);
+                (start, start)
+            }
+            None => ((self.offset as usize).min(items.len()), self.offset as usize),
+        };
+        let end = start.saturating_add(self.limit as usize).min(items.len());
+        let next_cursor = (end < items.len()).then(|| self.cursor_after(&items[end - 1]));
+        let page: Vec<Value> = items.drain(start..end).collect();
+
+        Ok(serde_json::json!({
+            "items": page,
+            "limit": self.limit,
+            "offset": offset,
+            "total": total,
+            "next_cursor": next_cursor,
+        }))
+    }
+
+    /// The supplied cursor, rejected if it was issued for another sort.
+    fn resume_position(&self) -> Result<Option<ListCursor>, ListQueryError> {
+        let cursor = self.cursor::<ListCursor>()?;
+        if cursor.as_ref().is_some_and(|cursor| cursor.sort != self.sort_param()) {
+            return Err(ListQueryError::InvalidCursor(self.cursor.clone().unwrap_or_default()));
+        }
+        Ok(cursor)
+    }
+
+    /// Cursor resuming after `item`.
+    fn cursor_after(&self, item: &Value) -> String {
+        let (value, key) = self.sort_key(item);
+        self.encode_position(value, key)
+    }
+
+    fn encode_position(&self, value: Option<&Value>, key: Option<&Value>) -> String {
+        encode_cursor(&ListCursor {
+            sort: self.sort_param(),
+            value: value.cloned().unwrap_or(Value::Null),
+            key: key.cloned().unwrap_or(Value::Null),
+        })
+    }
+
+    /// The `sort` parameter as given (`-field` for descending).
+    fn sort_param(&self) -> Option<String> {
+        self.sort.map(|sort| match sort.direction {
+            SortDirection::Asc => sort.field.to_string(),
+            SortDirection::Desc => format!("-{}", sort.field),
+        })
+    }
+
+    fn sort_key<'a>(&self, item: &'a Value) -> (Option<&'a Value>, Option<&'a Value>) {
+        (self.sort.and_then(|sort| item.get(sort.field)), item.get(S::KEY))
+    }
+
+    fn compare(&self, a: (Option<&Value>, Option<&Value>), b: (Option<&Value>, Option<&Value>)) -> Ordering {
+        let by_sort = match self.sort {
+            Some(sort) => {
+                let ord = compare_values(a.0, b.0);
+                match sort.direction {
+                    SortDirection::Asc => ord,
+                    SortDirection::Desc => ord.reverse(),
+                }
+            }
+            None => Ordering::Equal,
+        };
+        by_sort.then_with(|| compare_values(a.1, b.1))
+    }
+}
+
+#[async_trait]
+impl<S, St> FromRequestParts<St> for ListQuery<S>
+where
+    S: ListSpec,
+    St: Send + Sync,
+{
+    type Rejection = axum::response::Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &St) -> Result<Self, Self::Rejection> {
+        let Query(raw) = Query::<HashMap<String, String>>::try_from_uri(&parts.uri).map_err(|e| {
+            errors::json_error(StatusCode::BAD_REQUEST, "invalid_query", e.body_text())
+        })?;
+        Self::parse(&raw).map_err(ListQueryError::into_response)
+    }
+}
+
+/// Opaque cursor token: URL-safe base64 of the JSON position.
+pub fn encode_cursor<T: Serialize>(position: &T) -> String {
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(position).expect("cursor serializes"))
+}
+
+fn decode_cursor<T: DeserializeOwned>(cursor: &str) -> Result<T, ListQueryError> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| ListQueryError::InvalidCursor(cursor.to_string()))
+}
+
+fn field_matches(value: Option<&Value>, expected: &str) -> bool {
+    match value {
+        Some(Value::String(s)) => s.eq_ignore_ascii_case(expected),
+        Some(Value::Number(n)) => n.to_string() == expected,
+        Some(Value::Bool(b)) => b.to_string() == expected,
+        _ => false,
+    }
+}
+
+fn compare_values(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    match (a, b) {
+        (Some(Value::Number(x)), Some(Value::Number(y))) => x
+            .as_f64()
+            .unwrap_or_default()
+            .total_cmp(&y.as_f64().unwrap_or_default()),
+        (Some(Value::String(x)), Some(Value::String(y))) => x.cmp(y),
+        (Some(Value::Null) | None, Some(Value::Null) | None) => Ordering::Equal,
+        (Some(Value::Null) | None, _) => Ordering::Less,
+        (_, Some(Value::Null) | None) => Ordering::Greater,
+        (Some(x), Some(y)) => x.to_string().cmp(&y.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct TestSpec;
+
+    impl ListSpec for TestSpec {
+        const SORT_FIELDS: &'static [&'static str] = &["name", "total"];
+        const FILTERS: &'static [&'static str] = &["status"];
+    }
+
+    fn raw(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn defaults_apply_when_params_absent() {
+        let q = ListQuery::<TestSpec>::parse(&HashMap::new()).unwrap();
+        assert_eq!(q.limit, DEFAULT_LIMIT);
+        assert_eq!(q.offset, 0);
+        assert!(q.sort.is_none());
+        assert!(q.filter("status").is_none());
+    }
+
+    #[test]
+    fn over_cap_limit_is_rejected() {
+        let err = ListQuery::<TestSpec>::parse(&raw(&[("limit", "100000")])).unwrap_err();
+        assert_eq!(err, ListQueryError::LimitTooLarge(100_000));
+        assert_eq!(err.code(), "invalid_limit");
+
+        let q = ListQuery::<TestSpec>::parse(&raw(&[("limit", "500")])).unwrap();
+        assert_eq!(q.limit, MAX_LIMIT);
+        assert!(ListQuery::<TestSpec>::parse(&raw(&[("limit", "501")])).is_err());
+    }
+
+    #[test]
+    fn non_numeric_or_zero_limit_is_rejected() {
+        assert!(matches!(
+            ListQuery::<TestSpec>::parse(&raw(&[("limit", "abc")])),
+            Err(ListQueryError::InvalidLimit(_))
+        ));
+        assert!(matches!(
+            ListQuery::<TestSpec>::parse(&raw(&[("limit", "0")])),
+            Err(ListQueryError::InvalidLimit(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_sort_field_is_rejected() {
+        let err = ListQuery::<TestSpec>::parse(&raw(&[("sort", "-password")])).unwrap_err();
+        assert_eq!(err.code(), "invalid_sort");
+
+        let q = ListQuery::<TestSpec>::parse(&raw(&[("sort", "-total")])).unwrap();
+        assert_eq!(
+            q.sort,
+            Some(Sort {
+                field: "total",
+                direction: SortDirection::Desc
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_parameters_and_bad_cursors_are_rejected() {
+        assert!(matches!(
+            ListQuery::<TestSpec>::parse(&raw(&[("colour", "red")])),
+            Err(ListQueryError::UnknownParameter(_))
+        ));
+        assert!(matches!(
+            ListQuery::<TestSpec>::parse(&raw(&[("cursor", "xyz")])),
+            Err(ListQueryError::InvalidCursor(_))
+        ));
+        assert_eq!(
+            ListQuery::<TestSpec>::parse(&raw(&[("cursor", "o1"), ("offset", "1")])).unwrap_err(),
+            ListQueryError::CursorAndOffset
+        );
+    }
+
+    #[test]
+    fn page_filters_sorts_and_returns_next_cursor() {
+        let items = vec![
+            serde_json::json!({ "name": "b", "total": 2, "status": "open" }),
+            serde_json::json!({ "name": "a", "total": 3, "status": "open" }),
+            serde_json::json!({ "name": "c", "total": 1, "status": "void" }),
+        ];
+
+        let q = ListQuery::<TestSpec>::parse(&raw(&[
+            ("status", "open"),
+            ("sort", "-total"),
+            ("limit", "1"),
+        ]))
+        .unwrap();
+        let body = q.page(items.clone()).unwrap();
+        assert_eq!(body["items"].as_array().unwrap().len(), 1);
+        assert_eq!(body["items"][0]["name"], "a");
+        let cursor = body["next_cursor"].as_str().unwrap().to_string();
+
+        let q = ListQuery::<TestSpec>::parse(&raw(&[
+            ("status", "open"),
+            ("sort", "-total"),
+            ("limit", "1"),
+            ("cursor", &cursor),
+        ]))
+        .unwrap();
+        let body = q.page(items).unwrap();
+        assert_eq!(body["items"][0]["name"], "b");
+        assert!(body["next_cursor"].is_null());
+    }
+
+    fn order(id: &str, total: i64) -> Value {
+        serde_json::json!({ "id": id, "name": id, "total": total, "status": "open" })
+    }
+
+    fn ids(body: &Value) -> Vec<String> {
+        body["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn cursor_pages_do_not_skip_or_repeat_when_items_arrive_mid_pagination() {
+        let mut items = vec![order("a", 5), order("b", 4), order("c", 4), order("d", 2), order("e", 1)];
+        let params = [("sort", "-total"), ("limit", "2")];
+
+        let first = ListQuery::<TestSpec>::parse(&raw(&params)).unwrap().page(items.clone()).unwrap();
+        assert_eq!(ids(&first), ["a", "b"]);
+
+        // New items land before the cursor and in the middle of the remaining range.
+        items.push(order("z", 9));
+        items.push(order("bb", 4));
+
+        let mut seen = ids(&first);
+        let mut cursor = first["next_cursor"].as_str().map(str::to_string);
+        while let Some(token) = cursor {
+            let q = ListQuery::<TestSpec>::parse(&raw(&[params[0], params[1], ("cursor", &token)])).unwrap();
+            assert!(!q.uses_offset());
+            let body = q.page(items.clone()).unwrap();
+            seen.extend(ids(&body));
+            cursor = body["next_cursor"].as_str().map(str::to_string);
+        }
+        assert_eq!(seen, ["a", "b", "bb", "c", "d", "e"]);
+    }
+
+    #[test]
+    fn cursor_from_another_sort_is_rejected() {
+        let items = vec![order("a", 1), order("b", 2), order("c", 3)];
+        let body = ListQuery::<TestSpec>::parse(&raw(&[("sort", "total"), ("limit", "1")]))
+            .unwrap()
+            .page(items.clone())
+            .unwrap();
+        let token = body["next_cursor"].as_str().unwrap();
+
+        let q = ListQuery::<TestSpec>::parse(&raw(&[("sort", "name"), ("cursor", token)])).unwrap();
+        assert_eq!(q.page(items).unwrap_err().code(), "invalid_cursor");
+    }
+
+    #[test]
+    fn offset_pages_are_ordered_by_key_and_report_the_total() {
+        let items = vec![order("c", 1), order("a", 1), order("e", 1), order("b", 1), order("d", 1)];
+
+        let pages: Vec<Value> = ["0", "2", "4"]
+            .iter()
+            .map(|offset| {
+                ListQuery::<TestSpec>::parse(&raw(&[("limit", "2"), ("offset", offset)]))
+                    .unwrap()
+                    .page(items.clone())
+                    .unwrap()
+            })
+            .collect();
+
+        let seen: Vec<String> = pages.iter().flat_map(ids).collect();
+        assert_eq!(seen, ["a", "b", "c", "d", "e"]);
+        assert_eq!(pages[1]["offset"], 2);
+        assert!(pages.iter().all(|page| page["total"] == 5 && page["limit"] == 2));
+
+        // Filters narrow the total; an offset past the end is an empty page.
+        let body = ListQuery::<TestSpec>::parse(&raw(&[("status", "void"), ("offset", "9")]))
+            .unwrap()
+            .page(items)
+            .unwrap();
+        assert_eq!((body["total"].as_u64(), body["offset"].as_u64()), (Some(0), Some(9)));
+    }
+
+    #[test]
+    fn store_pages_match_in_memory_pages() {
+        use forgeerp_infra::read_model::{InMemoryTenantStore, TenantStore};
+
+        let mut items = vec![order("a", 5), order("b", 4), order("c", 4), order("d", 2), order("e", 1)];
+        items[3]["status"] = Value::from("void");
+        let tenant_id = forgeerp_core::TenantId::new();
+        let store = InMemoryTenantStore::new();
+        for item in &items {
+            store.upsert(tenant_id, item["id"].as_str().unwrap().to_string(), item.clone());
+        }
+        let field = |row: &Value, name: &str| row.get(name).cloned().unwrap_or(Value::Null);
+        let from_store = |q: &ListQuery<TestSpec>| {
+            q.page_from(field, |keep, order, start, limit| store.list_page(tenant_id, keep, order, start, limit), |v| v)
+        };
+
+        for params in [
+            vec![("limit", "2")],
+            vec![("sort", "-total"), ("limit", "2")],
+            vec![("sort", "name"), ("status", "open"), ("limit", "3")],
+            vec![("offset", "3"), ("limit", "1")],
+            vec![("offset", "9")],
+        ] {
+            let mut q = ListQuery::<TestSpec>::parse(&raw(&params)).unwrap();
+            loop {
+                let expected = q.page(items.clone()).unwrap();
+                assert_eq!(from_store(&q).unwrap(), expected, "{params:?}");
+                let Some(token) = expected["next_cursor"].as_str() else { break };
+                let mut next = params.clone();
+                next.retain(|(k, _)| *k != "offset");
+                next.push(("cursor", token));
+                q = ListQuery::<TestSpec>::parse(&raw(&next)).unwrap();
+            }
+        }
+
+        let token = from_store(&ListQuery::parse(&raw(&[("sort", "total"), ("limit", "1")])).unwrap()).unwrap()
+            ["next_cursor"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let q = ListQuery::<TestSpec>::parse(&raw(&[("sort", "name"), ("cursor", &token)])).unwrap();
+        assert_eq!(from_store(&q).unwrap_err().code(), "invalid_cursor");
+    }
+}
@@ -6,24 +6,34 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Extension, Path},
-    http::StatusCode,
+    extract::{Extension, Path, Query},
+    http::{header, StatusCode},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
 use forgeerp_auth::{
-    admin, ActivateUser, AssignRole, CreateUser, Permission, RevokeRole, Role, SuspendUser,
-    User, UserCommand, UserId,
+    admin, ActivateUser, AssignRole, CreateUser, PrincipalId, RevocationStore, RevokeRole, Role,
+    SuspendUser, User, UserCommand, UserId,
+};
+use forgeerp_core::{AggregateId, RoundingMode, TenantId};
+use forgeerp_infra::admin_audit::{operation as audit_operation, AdminAuditFilter};
+use forgeerp_infra::ai::AnomalyConfig;
+use forgeerp_infra::jobs::JobStore;
+use forgeerp_infra::projections::{
+    default_role_permissions, ProjectionDeadLetterError, ProjectionDeadLetterStore, ProjectionVersionError, UserReadModel,
+};
+use forgeerp_infra::rejected_commands::RejectedCommandFilter;
+use forgeerp_infra::tenant_settings::{
+    keys as settings_keys, LedgerAccounts, SetSetting, SetSettings, ShortStockPolicy, TenantSettings,
+    TenantSettingsCommand,
 };
-use forgeerp_core::AggregateId;
-use forgeerp_infra::projections::{default_role_permissions, UserReadModel};
 
-use crate::app::{errors, services::AppServices};
-use crate::app::routes::common::CmdAuth;
+use crate::app::{dto, errors, services::AppServices};
+use crate::app::routes::common::{record_admin_action, CmdAuth};
 use crate::context::{PrincipalContext, TenantContext};
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -52,6 +62,59 @@ pub struct SuspendUserRequest {
     pub reason: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UpdateSettingsRequest {
+    pub default_currency: String,
+    pub rounding: Option<RoundingMode>,
+    /// Accounts for automatic invoice/payment postings (kept when omitted).
+    pub ledger_accounts: Option<LedgerAccounts>,
+    /// Confirmed orders without enough stock: fail or backorder (kept when omitted).
+    pub short_stock: Option<ShortStockPolicy>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SettingsHistoryQuery {
+    /// Only changes of this setting.
+    pub key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminAuditQuery {
+    pub operation: Option<String>,
+    /// Principal id of the actor.
+    pub actor: Option<String>,
+    pub target: Option<String>,
+    /// RFC 3339; only entries recorded at or after it.
+    pub since: Option<String>,
+}
+
+const DEFAULT_REJECTED_COMMANDS_LIMIT: usize = 100;
+const MAX_REJECTED_COMMANDS_LIMIT: usize = 1_000;
+
+#[derive(Debug, Deserialize)]
+pub struct RejectedCommandsQuery {
+    /// Command variant name, e.g. `AdjustStock`.
+    pub command_type: Option<String>,
+    /// Principal id of the sender.
+    pub principal: Option<String>,
+    /// RFC 3339; only commands rejected at or after it.
+    pub since: Option<String>,
+    /// Newest entries to return (default 100, max 1000).
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ActivateProjectionVersionRequest {
+    /// Label of the version that should serve reads.
+    pub version: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PurgeDeadLettersQuery {
+    /// RFC 3339 cutoff; required so a purge never clears the whole queue by accident.
+    pub older_than: Option<String>,
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Router
 // ─────────────────────────────────────────────────────────────────────────────
@@ -65,6 +128,24 @@ pub fn router() -> Router {
         .route("/users/:id/suspend", post(suspend_user))
         .route("/users/:id/activate", post(activate_user))
         .route("/users/:id/permissions", get(inspect_permissions))
+        .route("/settings", get(get_settings).put(update_settings))
+        .route("/settings/history", get(settings_history))
+        .route("/settings/:key", axum::routing::put(set_setting))
+        .route("/tenants/:id/stats", get(tenant_stats))
+        .route("/tombstones/products", get(product_tombstones))
+        .route("/ai/usage", get(ai_usage))
+        .route("/ai/inventory-anomaly/config", axum::routing::put(set_anomaly_config))
+        .route("/jobs/dead-letters", axum::routing::delete(purge_dead_letters))
+        .route("/jobs/dead-letters/export", get(export_dead_letters))
+        .route("/audit", get(list_admin_audit))
+        .route("/audit/export", get(export_admin_audit))
+        .route("/rejected-commands", get(list_rejected_commands))
+        .route("/projections/versions", get(list_projection_versions))
+        .route("/projections/versions/:name/compare", get(compare_projection_versions))
+        .route("/projections/versions/:name/activate", post(activate_projection_version))
+        .route("/projections/dead-letters", get(list_projection_dead_letters))
+        .route("/projections/dead-letters/replay", post(replay_projection_dead_letters))
+        .route("/projections/dead-letters/:id/replay", post(replay_projection_dead_letter))
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -119,7 +200,7 @@ pub async fn create_user(
     };
 
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<User>(
@@ -155,7 +236,7 @@ pub async fn list_users(
         required: vec![admin::USER_LIST.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let users = services.users_list(tenant.tenant_id());
@@ -177,7 +258,7 @@ pub async fn get_user(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let user_id: UserId = match id.parse::<uuid::Uuid>() {
@@ -221,7 +302,7 @@ pub async fn assign_role(
     };
 
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<User>(
@@ -271,7 +352,7 @@ pub async fn revoke_role(
     };
 
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<User>(
@@ -295,9 +376,10 @@ pub async fn revoke_role(
         .into_response()
 }
 
-/// POST /admin/users/:id/suspend - Suspend a user
+/// POST /admin/users/:id/suspend - Suspend a user and revoke the tokens they hold
 pub async fn suspend_user(
     Extension(services): Extension<Arc<AppServices>>,
+    Extension(revocations): Extension<Arc<dyn RevocationStore>>,
     Extension(tenant): Extension<TenantContext>,
     Extension(principal): Extension<PrincipalContext>,
     Path(id): Path<String>,
@@ -309,11 +391,12 @@ pub async fn suspend_user(
     };
     let agg = AggregateId::from_uuid(*user_id.as_uuid());
 
+    let suspended_at = Utc::now();
     let cmd = UserCommand::Suspend(SuspendUser {
         tenant_id: tenant.tenant_id(),
         user_id,
         reason: body.reason.unwrap_or_else(|| "No reason provided".to_string()),
-        occurred_at: Utc::now(),
+        occurred_at: suspended_at,
     });
 
     let cmd_auth = CmdAuth {
@@ -322,7 +405,7 @@ pub async fn suspend_user(
     };
 
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<User>(
@@ -335,6 +418,9 @@ pub async fn suspend_user(
         Ok(c) => c,
         Err(e) => return errors::dispatch_error_to_response(e),
     };
+    // The users projection keeps the cutoff too; the store also has it before the projection
+    // catches up.
+    revocations.revoke_principal(tenant.tenant_id(), PrincipalId::from_uuid(*user_id.as_uuid()), suspended_at);
 
     (
         StatusCode::OK,
@@ -371,7 +457,7 @@ pub async fn activate_user(
     };
 
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<User>(
@@ -408,7 +494,7 @@ pub async fn inspect_permissions(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let user_id: UserId = match id.parse::<uuid::Uuid>() {
@@ -436,6 +522,144 @@ pub async fn inspect_permissions(
     }
 }
 
+/// GET /admin/settings - Tenant money settings
+pub async fn get_settings(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::SETTINGS_READ.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let settings = services.tenant_settings().get(tenant.tenant_id());
+    (StatusCode::OK, Json(settings)).into_response()
+}
+
+/// PUT /admin/settings - Set default currency, rounding convention and posting accounts
+pub async fn update_settings(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+    Json(body): Json<UpdateSettingsRequest>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::SETTINGS_WRITE.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    if let Err(e) = TenantSettings::new(&body.default_currency, body.rounding.unwrap_or_default()) {
+        return errors::json_error(StatusCode::BAD_REQUEST, "invalid_currency", e.to_string());
+    }
+    let mut values = vec![
+        (
+            settings_keys::ROUNDING.to_string(),
+            serde_json::to_value(body.rounding.unwrap_or_default()).unwrap_or_default(),
+        ),
+        (settings_keys::DEFAULT_CURRENCY.to_string(), serde_json::Value::from(body.default_currency)),
+    ];
+    if let Some(accounts) = body.ledger_accounts {
+        values.push((settings_keys::LEDGER_ACCOUNTS.to_string(), serde_json::to_value(accounts).unwrap_or_default()));
+    }
+    if let Some(policy) = body.short_stock {
+        values.push((settings_keys::SHORT_STOCK.to_string(), serde_json::to_value(policy).unwrap_or_default()));
+    }
+    let cmd = TenantSettingsCommand::SetSettings(SetSettings {
+        tenant_id: tenant.tenant_id(),
+        values,
+        occurred_at: Utc::now(),
+    });
+
+    match services.change_settings(cmd) {
+        Ok(settings) => (StatusCode::OK, Json(settings)).into_response(),
+        Err(e) => errors::dispatch_error_to_response(e),
+    }
+}
+
+/// PUT /admin/settings/:key - Set one setting (body: its JSON value)
+pub async fn set_setting(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+    Path(key): Path<String>,
+    Json(value): Json<serde_json::Value>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::SETTINGS_WRITE.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let cmd = TenantSettingsCommand::SetSetting(SetSetting {
+        tenant_id: tenant.tenant_id(),
+        key,
+        value,
+        occurred_at: Utc::now(),
+    });
+    match services.change_settings(cmd) {
+        Ok(settings) => (StatusCode::OK, Json(settings)).into_response(),
+        Err(e) => errors::dispatch_error_to_response(e),
+    }
+}
+
+/// GET /admin/settings/history - Setting changes, oldest first (`?key=` for one setting)
+pub async fn settings_history(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+    Query(query): Query<SettingsHistoryQuery>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::SETTINGS_READ.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let history = services.tenant_settings().history(tenant.tenant_id(), query.key.as_deref());
+    (StatusCode::OK, Json(history)).into_response()
+}
+
+/// GET /admin/tenants/:id/stats - Event store usage for the tenant
+pub async fn tenant_stats(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::TENANT_STATS.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let tenant_id: TenantId = match id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid tenant id"),
+    };
+    // Stats are tenant-scoped like every other admin read.
+    if tenant_id != tenant.tenant_id() {
+        return errors::json_error(StatusCode::FORBIDDEN, "tenant_isolation", "cannot read another tenant's stats");
+    }
+
+    match services.tenant_stats(tenant_id).await {
+        Ok(stats) => (StatusCode::OK, Json(stats)).into_response(),
+        Err(e) => errors::json_error(StatusCode::INTERNAL_SERVER_ERROR, "stats_failed", e.to_string()),
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // JSON Helpers
 // ─────────────────────────────────────────────────────────────────────────────
@@ -453,3 +677,432 @@ fn user_to_json(user: UserReadModel) -> serde_json::Value {
     })
 }
 
+/// GET /admin/tombstones/products - Deleted products kept for audit
+pub async fn product_tombstones(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::TOMBSTONES_READ.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let items = services
+        .products_tombstones(tenant.tenant_id())
+        .into_iter()
+        .map(dto::product_tombstone_to_json)
+        .collect::<Vec<_>>();
+    (StatusCode::OK, Json(serde_json::json!({ "items": items }))).into_response()
+}
+
+/// GET /admin/ai/usage - AI run counters and estimated cost for the current tenant
+pub async fn ai_usage(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::AI_USAGE_READ.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let meter = services.ai_usage();
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "usage": meter.usage(tenant.tenant_id()),
+            "limit": meter.limit(),
+        })),
+    )
+        .into_response()
+}
+
+/// PUT /admin/ai/inventory-anomaly/config - Set the tenant's anomaly detection settings
+/// (used from the next detection run; tenants that never set one use the defaults)
+pub async fn set_anomaly_config(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+    Json(config): Json<AnomalyConfig>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::AI_CONFIG_WRITE.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    if let Err(e) = services.anomaly_configs().set(tenant.tenant_id(), config) {
+        return errors::json_error(StatusCode::BAD_REQUEST, "validation_error", e.to_string());
+    }
+    record_admin_action(
+        services.admin_audit(),
+        &tenant,
+        &principal,
+        audit_operation::AI_ANOMALY_CONFIG_UPDATE,
+        "ai.inventory_anomaly",
+        serde_json::to_value(config).unwrap_or_default(),
+    );
+    (StatusCode::OK, Json(config)).into_response()
+}
+
+/// GET /admin/jobs/dead-letters/export - Dead-lettered jobs as NDJSON (one entry per line)
+pub async fn export_dead_letters(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::DEAD_LETTERS_EXPORT.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let mut body = Vec::new();
+    match services.job_store().export_dead_letters(tenant.tenant_id(), &mut body) {
+        Ok(_) => (StatusCode::OK, [(header::CONTENT_TYPE, "application/x-ndjson")], body).into_response(),
+        Err(e) => errors::json_error(StatusCode::INTERNAL_SERVER_ERROR, "job_store_error", e.to_string()),
+    }
+}
+
+/// DELETE /admin/jobs/dead-letters?older_than=<RFC 3339> - Purge dead-lettered jobs older than the cutoff
+pub async fn purge_dead_letters(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+    Query(query): Query<PurgeDeadLettersQuery>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::DEAD_LETTERS_PURGE.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let Some(raw) = query.older_than else {
+        return errors::json_error(StatusCode::BAD_REQUEST, "validation_error", "older_than is required");
+    };
+    let older_than = match DateTime::parse_from_rfc3339(&raw) {
+        Ok(t) => t.with_timezone(&Utc),
+        Err(_) => {
+            return errors::json_error(
+                StatusCode::BAD_REQUEST,
+                "validation_error",
+                format!("older_than must be an RFC 3339 timestamp, got `{raw}`"),
+            )
+        }
+    };
+    if older_than > Utc::now() {
+        return errors::json_error(StatusCode::BAD_REQUEST, "validation_error", "older_than must not be in the future");
+    }
+
+    match services.job_store().purge_dead_letters(tenant.tenant_id(), older_than) {
+        Ok(purged) => {
+            record_admin_action(
+                services.admin_audit(),
+                &tenant,
+                &principal,
+                audit_operation::DEAD_LETTERS_PURGE,
+                "dead_letters",
+                serde_json::json!({ "purged": purged, "older_than": older_than.to_rfc3339() }),
+            );
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({ "purged": purged, "older_than": older_than.to_rfc3339() })),
+            )
+                .into_response()
+        }
+        Err(e) => errors::json_error(StatusCode::INTERNAL_SERVER_ERROR, "job_store_error", e.to_string()),
+    }
+}
+
+/// GET /admin/audit?operation=&actor=&target=&since= - Admin operations of the tenant, oldest first
+pub async fn list_admin_audit(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+    Query(query): Query<AdminAuditQuery>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::AUDIT_READ.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let actor = match query.actor.as_deref().map(str::parse::<uuid::Uuid>).transpose() {
+        Ok(actor) => actor.map(forgeerp_auth::PrincipalId::from_uuid),
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "validation_error", "actor must be a principal id"),
+    };
+    let since = match query.since.as_deref().map(DateTime::parse_from_rfc3339).transpose() {
+        Ok(since) => since.map(|t| t.with_timezone(&Utc)),
+        Err(_) => {
+            return errors::json_error(StatusCode::BAD_REQUEST, "validation_error", "since must be an RFC 3339 timestamp")
+        }
+    };
+    let filter = AdminAuditFilter {
+        operation: query.operation,
+        actor,
+        target: query.target,
+        since,
+    };
+    let entries = services.admin_audit().list(tenant.tenant_id(), &filter);
+    (StatusCode::OK, Json(serde_json::json!({ "entries": entries }))).into_response()
+}
+
+/// GET /admin/audit/export - The tenant's admin audit log as NDJSON
+pub async fn export_admin_audit(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::AUDIT_READ.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let mut body = Vec::new();
+    match services.admin_audit().export(tenant.tenant_id(), &mut body) {
+        Ok(_) => (StatusCode::OK, [(header::CONTENT_TYPE, "application/x-ndjson")], body).into_response(),
+        Err(e) => errors::json_error(StatusCode::INTERNAL_SERVER_ERROR, "audit_export_failed", e.to_string()),
+    }
+}
+
+/// GET /admin/rejected-commands?command_type=&principal=&since=&limit= - Rejected commands, newest first
+pub async fn list_rejected_commands(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+    Query(query): Query<RejectedCommandsQuery>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::REJECTED_COMMANDS_READ.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let Some(log) = services.rejected_commands() else {
+        return errors::json_error(
+            StatusCode::NOT_FOUND,
+            "rejected_command_log_disabled",
+            "rejected commands are not recorded (set REJECTED_COMMAND_LOG_CAPACITY)",
+        );
+    };
+    let principal_filter = match query.principal.as_deref().map(str::parse::<uuid::Uuid>).transpose() {
+        Ok(p) => p.map(forgeerp_auth::PrincipalId::from_uuid),
+        Err(_) => {
+            return errors::json_error(StatusCode::BAD_REQUEST, "validation_error", "principal must be a principal id")
+        }
+    };
+    let since = match query.since.as_deref().map(DateTime::parse_from_rfc3339).transpose() {
+        Ok(since) => since.map(|t| t.with_timezone(&Utc)),
+        Err(_) => {
+            return errors::json_error(StatusCode::BAD_REQUEST, "validation_error", "since must be an RFC 3339 timestamp")
+        }
+    };
+    let filter = RejectedCommandFilter {
+        command_type: query.command_type,
+        principal: principal_filter,
+        since,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_REJECTED_COMMANDS_LIMIT).min(MAX_REJECTED_COMMANDS_LIMIT);
+    let entries = log.list(tenant.tenant_id(), &filter, limit);
+    (StatusCode::OK, Json(serde_json::json!({ "entries": entries }))).into_response()
+}
+
+fn projection_version_error(e: ProjectionVersionError) -> axum::response::Response {
+    match e {
+        ProjectionVersionError::UnknownProjection(_) => errors::json_error(StatusCode::NOT_FOUND, "not_found", e.to_string()),
+        _ => errors::json_error(StatusCode::BAD_REQUEST, "validation_error", e.to_string()),
+    }
+}
+
+/// GET /admin/projections/versions - Projections running a candidate version, with the active one
+pub async fn list_projection_versions(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::PROJECTION_VERSIONS_MANAGE.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let projections = services.projection_versions().list();
+    (StatusCode::OK, Json(serde_json::json!({ "projections": projections }))).into_response()
+}
+
+/// GET /admin/projections/versions/:name/compare - Entries of the tenant where the two versions diverge
+pub async fn compare_projection_versions(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+    Path(name): Path<String>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::PROJECTION_VERSIONS_MANAGE.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    match services.projection_versions().get(&name) {
+        Ok(versions) => (StatusCode::OK, Json(versions.compare(tenant.tenant_id()))).into_response(),
+        Err(e) => projection_version_error(e),
+    }
+}
+
+/// POST /admin/projections/versions/:name/activate - Serve reads from another version
+pub async fn activate_projection_version(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+    Path(name): Path<String>,
+    Json(body): Json<ActivateProjectionVersionRequest>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::PROJECTION_VERSIONS_MANAGE.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let versions = match services.projection_versions().get(&name) {
+        Ok(v) => v,
+        Err(e) => return projection_version_error(e),
+    };
+    let previous = versions.active_label();
+    if let Err(e) = versions.activate(&body.version) {
+        return projection_version_error(e);
+    }
+    record_admin_action(
+        services.admin_audit(),
+        &tenant,
+        &principal,
+        audit_operation::PROJECTION_VERSION_ACTIVATE,
+        name,
+        serde_json::json!({ "from": previous, "to": body.version }),
+    );
+    (StatusCode::OK, Json(versions.info())).into_response()
+}
+
+fn projection_dead_letter_error(e: ProjectionDeadLetterError) -> axum::response::Response {
+    match e {
+        ProjectionDeadLetterError::NotFound(_) => errors::json_error(StatusCode::NOT_FOUND, "not_found", e.to_string()),
+        ProjectionDeadLetterError::ReplayFailed { .. } => {
+            errors::json_error(StatusCode::UNPROCESSABLE_ENTITY, "projection_apply_failed", e.to_string())
+        }
+        ProjectionDeadLetterError::Storage(_) => {
+            errors::json_error(StatusCode::INTERNAL_SERVER_ERROR, "dead_letter_store_failed", e.to_string())
+        }
+    }
+}
+
+/// GET /admin/projections/dead-letters - Events the projections failed to apply, oldest first
+pub async fn list_projection_dead_letters(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::PROJECTION_DEAD_LETTERS_MANAGE.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    match services.projection_dead_letters().list(tenant.tenant_id()) {
+        Ok(entries) => (StatusCode::OK, Json(serde_json::json!({ "entries": entries }))).into_response(),
+        Err(e) => projection_dead_letter_error(e),
+    }
+}
+
+/// POST /admin/projections/dead-letters/:id/replay - Apply one dead letter again (removed once it applies)
+pub async fn replay_projection_dead_letter(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+    Path(id): Path<uuid::Uuid>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::PROJECTION_DEAD_LETTERS_MANAGE.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let result = services.replay_projection_dead_letter(tenant.tenant_id(), id);
+    record_admin_action(
+        services.admin_audit(),
+        &tenant,
+        &principal,
+        audit_operation::PROJECTION_DEAD_LETTER_REPLAY,
+        id.to_string(),
+        serde_json::json!({ "replayed": result.is_ok() }),
+    );
+    match result {
+        Ok(letter) => (StatusCode::OK, Json(letter)).into_response(),
+        Err(e) => projection_dead_letter_error(e),
+    }
+}
+
+/// POST /admin/projections/dead-letters/replay - Apply all of the tenant's dead letters again, oldest first
+pub async fn replay_projection_dead_letters(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::PROJECTION_DEAD_LETTERS_MANAGE.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let letters = match services.projection_dead_letters().list(tenant.tenant_id()) {
+        Ok(letters) => letters,
+        Err(e) => return projection_dead_letter_error(e),
+    };
+    let mut replayed = Vec::new();
+    let mut failed = Vec::new();
+    for letter in letters {
+        match services.replay_projection_dead_letter(tenant.tenant_id(), letter.dead_letter_id) {
+            Ok(letter) => replayed.push(letter.dead_letter_id),
+            Err(e) => failed.push(serde_json::json!({ "dead_letter_id": letter.dead_letter_id, "error": e.to_string() })),
+        }
+    }
+    record_admin_action(
+        services.admin_audit(),
+        &tenant,
+        &principal,
+        audit_operation::PROJECTION_DEAD_LETTER_REPLAY,
+        "all",
+        serde_json::json!({ "replayed": replayed.len(), "failed": failed.len() }),
+    );
+    (StatusCode::OK, Json(serde_json::json!({ "replayed": replayed, "failed": failed }))).into_response()
+}
@@ -12,6 +12,7 @@ pub async fn get_ar_aging(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
 ) -> axum::response::Response {
+    let now = chrono::Utc::now();
     let items = services
         .ar_aging_list(tenant.tenant_id())
         .into_iter()
@@ -21,6 +22,8 @@ pub async fn get_ar_aging(
             "total_amount": rm.total_amount,
             "outstanding_amount": rm.outstanding_amount,
             "due_date": rm.due_date.map(|d| d.to_rfc3339()),
+            "days_past_due": rm.days_past_due(now),
+            "bucket": rm.bucket(now),
         }))
         .collect::<Vec<_>>();
     (StatusCode::OK, Json(serde_json::json!({ "items": items }))).into_response()
@@ -1,4 +1,24 @@
-use forgeerp_auth::{CommandAuthorization, Permission};
+use std::future::Future;
+
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::Json;
+use forgeerp_auth::{CommandAuthorization, Permission, Principal};
+use forgeerp_infra::admin_audit::AdminAuditLog;
+use forgeerp_infra::command_bus::CommandBusError;
+use forgeerp_infra::command_dispatcher::with_idempotency_key_sync;
+use forgeerp_infra::event_store::{EventStoreError, StoredEvent};
+use forgeerp_infra::idempotency::IdempotencyKey;
+
+use crate::app::errors;
+use crate::app::services::AppServices;
+use crate::context::{PrincipalContext, TenantContext};
+
+/// Seconds a client should wait before re-reading a row whose projection is pending.
+const PROJECTION_RETRY_AFTER_SECS: u64 = 1;
+
+/// Request header carrying a client-chosen idempotency key (a UUID).
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
 
 /// Small helper wrapper to associate required permissions with a command.
 pub struct CmdAuth<C> {
@@ -12,4 +32,277 @@ impl<C> CommandAuthorization for CmdAuth<C> {
     }
 }
 
+/// Record a privileged admin operation by the request's principal in the admin audit log.
+pub fn record_admin_action(
+    audit: &AdminAuditLog,
+    tenant: &TenantContext,
+    principal: &PrincipalContext,
+    operation: &str,
+    target: impl Into<String>,
+    details: serde_json::Value,
+) {
+    let entry = audit.record(
+        tenant.tenant_id(),
+        principal.principal_id(),
+        tenant.is_cross_tenant().then(|| tenant.home_tenant_id()),
+        operation,
+        target,
+        details,
+    );
+    tracing::info!(
+        tenant_id = %entry.tenant_id,
+        actor = %entry.actor,
+        operation = %entry.operation,
+        target = %entry.target,
+        "admin operation"
+    );
+}
+
+/// `Idempotency-Key` header that is not a UUID (`400 invalid_idempotency_key`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidIdempotencyKey;
+
+impl IntoResponse for InvalidIdempotencyKey {
+    fn into_response(self) -> axum::response::Response {
+        errors::json_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_idempotency_key",
+            "Idempotency-Key must be a UUID",
+        )
+    }
+}
+
+/// The request's `Idempotency-Key`, if any, fingerprinted with `request` (the route's
+/// path and body) so that a reused key can be told apart from a retry.
+pub fn idempotency_key(
+    headers: &HeaderMap,
+    request: &impl std::fmt::Debug,
+) -> Result<Option<IdempotencyKey>, InvalidIdempotencyKey> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .ok()
+        .and_then(|v| v.trim().parse::<uuid::Uuid>().ok())
+        .ok_or(InvalidIdempotencyKey)?;
+    Ok(Some(IdempotencyKey::new(key).with_request(request)))
+}
+
+/// Send `command` through the command bus, under `idempotency` when the request has a key:
+/// a retry gets the events committed the first time instead of running the command again,
+/// and the same key with a different request fails with `DispatchError::Validation`.
+pub fn send_idempotent<C: forgeerp_events::Command>(
+    services: &AppServices,
+    principal: &Principal,
+    idempotency: Option<IdempotencyKey>,
+    command: C,
+) -> Result<Vec<StoredEvent>, CommandBusError> {
+    match idempotency {
+        Some(key) => with_idempotency_key_sync(key, || services.send(principal, command)),
+        None => services.send(principal, command),
+    }
+}
+
+/// Respond with a read model row, or explain why it is missing.
+///
+/// Projections apply events in the background, so a GET right after a write can miss a
+/// row whose aggregate is already in the event store. On a miss, `first_event` (the
+/// head of the aggregate's stream) decides between `404 not_found` (never written, or a
+/// stream of another aggregate type) and `202 projection_pending` with `Retry-After`.
+pub async fn read_model_response<T>(
+    found: Option<T>,
+    to_json: impl FnOnce(T) -> serde_json::Value,
+    first_event: impl Future<Output = Result<Option<StoredEvent>, EventStoreError>>,
+    aggregate_type: &str,
+    what: &str,
+) -> axum::response::Response {
+    if let Some(rm) = found {
+        return (StatusCode::OK, Json(to_json(rm))).into_response();
+    }
+    match first_event.await {
+        Ok(Some(event)) if event.aggregate_type == aggregate_type => (
+            StatusCode::ACCEPTED,
+            [(header::RETRY_AFTER, PROJECTION_RETRY_AFTER_SECS.to_string())],
+            Json(serde_json::json!({
+                "error": "projection_pending",
+                "message": format!("{what} was written but is not readable yet; retry shortly"),
+                "retry_after_secs": PROJECTION_RETRY_AFTER_SECS,
+            })),
+        )
+            .into_response(),
+        Ok(_) => errors::json_error(StatusCode::NOT_FOUND, "not_found", format!("{what} not found")),
+        Err(e) => errors::json_error(StatusCode::INTERNAL_SERVER_ERROR, "store_error", format!("{e:?}")),
+    }
+}
+
+/// Weak entity tag for a read model row at stream `version`.
+pub fn etag(version: u64) -> String {
+    format!("W/\"{version}\"")
+}
+
+/// Whether `If-None-Match` lists `tag` (or `*`), compared weakly.
+fn if_none_match(headers: &HeaderMap, tag: &str) -> bool {
+    let opaque = |t: &str| t.trim().trim_start_matches("W/").to_string();
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == opaque(tag))
+}
+
+/// Like [`read_model_response`] for rows that carry their stream version: the row is
+/// served with an `ETag`, and a request whose `If-None-Match` already names that
+/// version answers `304 Not Modified` without a body.
+pub async fn versioned_read_model_response<T>(
+    headers: &HeaderMap,
+    found: Option<T>,
+    version: impl FnOnce(&T) -> u64,
+    to_json: impl FnOnce(T) -> serde_json::Value,
+    first_event: impl Future<Output = Result<Option<StoredEvent>, EventStoreError>>,
+    aggregate_type: &str,
+    what: &str,
+) -> axum::response::Response {
+    let Some(rm) = found else {
+        return read_model_response(None, to_json, first_event, aggregate_type, what).await;
+    };
+    let tag = etag(version(&rm));
+    if if_none_match(headers, &tag) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, tag)]).into_response();
+    }
+    (StatusCode::OK, [(header::ETAG, tag)], Json(to_json(rm))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use forgeerp_core::{AggregateId, ExpectedVersion, TenantId};
+    use forgeerp_infra::event_store::{EventQuery, EventStore, InMemoryEventStore, UncommittedEvent};
+
+    use super::*;
+
+    async fn get(
+        store: &InMemoryEventStore,
+        tenant_id: TenantId,
+        id: AggregateId,
+        found: Option<&str>,
+    ) -> axum::response::Response {
+        let first_event = async {
+            let page = store.get_aggregate_events(tenant_id, id, None).await?;
+            Ok(page.events.into_iter().next())
+        };
+        read_model_response(found, |name| serde_json::json!({ "name": name }), first_event, "inventory.item", "item")
+            .await
+    }
+
+    async fn body(response: axum::response::Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn missing_row_is_404_only_when_the_aggregate_was_never_written() {
+        let store = InMemoryEventStore::new();
+        let tenant_id = TenantId::new();
+        let written = AggregateId::new();
+        store
+            .append(
+                vec![UncommittedEvent {
+                    event_id: uuid::Uuid::now_v7(),
+                    tenant_id,
+                    aggregate_id: written,
+                    aggregate_type: "inventory.item".to_string(),
+                    event_type: "inventory.item.created".to_string(),
+                    event_version: 1,
+                    occurred_at: chrono::Utc::now(),
+                    payload: serde_json::json!({}),
+                    metadata: Default::default(),
+                    correlation_id: None,
+                    causation_id: None,
+                }],
+                ExpectedVersion::Exact(0),
+            )
+            .unwrap();
+
+        // Present: the projection has the row.
+        let present = get(&store, tenant_id, written, Some("Widget")).await;
+        assert_eq!(present.status(), StatusCode::OK);
+        assert_eq!(body(present).await["name"], "Widget");
+
+        // Pending: the stream exists but the projection has not applied it yet.
+        let pending = get(&store, tenant_id, written, None).await;
+        assert_eq!(pending.status(), StatusCode::ACCEPTED);
+        assert_eq!(pending.headers()[header::RETRY_AFTER], "1");
+        assert_eq!(body(pending).await["error"], "projection_pending");
+
+        // Absent: nothing was ever written for this id (or for this tenant).
+        let absent = get(&store, tenant_id, AggregateId::new(), None).await;
+        assert_eq!(absent.status(), StatusCode::NOT_FOUND);
+        let other_tenant = get(&store, TenantId::new(), written, None).await;
+        assert_eq!(other_tenant.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn idempotency_key_header_is_fingerprinted_with_the_request() {
+        let key = uuid::Uuid::now_v7();
+        let mut headers = HeaderMap::new();
+        assert_eq!(idempotency_key(&headers, &"body"), Ok(None));
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, key.to_string().parse().unwrap());
+        let first = idempotency_key(&headers, &("item-1", "delta: 5")).unwrap().unwrap();
+        let retry = idempotency_key(&headers, &("item-1", "delta: 5")).unwrap().unwrap();
+        let other = idempotency_key(&headers, &("item-1", "delta: 7")).unwrap().unwrap();
+        assert_eq!(first.key, key);
+        assert_eq!(first, retry);
+        assert_ne!(first.fingerprint, other.fingerprint);
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "not-a-uuid".parse().unwrap());
+        assert_eq!(idempotency_key(&headers, &"body"), Err(InvalidIdempotencyKey));
+    }
+
+    async fn get_versioned(row: Option<(&str, u64)>, if_none_match: Option<&str>) -> axum::response::Response {
+        let mut headers = HeaderMap::new();
+        if let Some(tag) = if_none_match {
+            headers.insert(header::IF_NONE_MATCH, tag.parse().unwrap());
+        }
+        versioned_read_model_response(
+            &headers,
+            row,
+            |(_, version)| *version,
+            |(name, _)| serde_json::json!({ "name": name }),
+            async { Ok(None) },
+            "inventory.item",
+            "item",
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn unchanged_row_is_not_modified_and_a_changed_one_gets_a_new_etag() {
+        let first = get_versioned(Some(("Widget", 2)), None).await;
+        assert_eq!(first.status(), StatusCode::OK);
+        let tag = first.headers()[header::ETAG].to_str().unwrap().to_string();
+        assert_eq!(tag, etag(2));
+
+        // Unchanged: 304 with the same tag and no body.
+        let unchanged = get_versioned(Some(("Widget", 2)), Some(&tag)).await;
+        assert_eq!(unchanged.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(unchanged.headers()[header::ETAG], tag.as_str());
+        let bytes = axum::body::to_bytes(unchanged.into_body(), usize::MAX).await.unwrap();
+        assert!(bytes.is_empty());
+
+        // Changed (another event applied): the full row with the new tag.
+        let changed = get_versioned(Some(("Gadget", 3)), Some(&tag)).await;
+        assert_eq!(changed.status(), StatusCode::OK);
+        assert_eq!(changed.headers()[header::ETAG], etag(3).as_str());
+        assert_eq!(body(changed).await["name"], "Gadget");
 
+        // Lists, strong forms of the tag and `*` match too.
+        let listed = get_versioned(Some(("Widget", 2)), Some("\"1\", \"2\"")).await;
+        assert_eq!(listed.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(get_versioned(Some(("Widget", 2)), Some("*")).await.status(), StatusCode::NOT_MODIFIED);
+
+        // Missing rows are unaffected by the header.
+        assert_eq!(get_versioned(None, Some(&tag)).await.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use axum::{
     extract::{Extension, Path},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
@@ -13,10 +13,19 @@ use forgeerp_auth::Permission;
 use forgeerp_core::AggregateId;
 use forgeerp_parties::{Party, PartyCommand, PartyId, PartyKind, RegisterParty, SuspendParty, UpdateDetails};
 
+use crate::app::query::{ListQuery, ListSpec};
 use crate::app::{dto, errors};
-use crate::app::routes::common::CmdAuth;
+use crate::app::routes::common::{versioned_read_model_response, CmdAuth};
 use crate::app::services::AppServices;
 
+/// Sort fields and filters accepted by `GET` on the customers list.
+pub struct CustomerListSpec;
+
+impl ListSpec for CustomerListSpec {
+    const SORT_FIELDS: &'static [&'static str] = &["id", "name", "email", "status"];
+    const FILTERS: &'static [&'static str] = &["status", "name", "email"];
+}
+
 pub fn router() -> Router {
     Router::new()
         .route("/", post(register_customer).get(list_customers))
@@ -57,21 +66,26 @@ pub async fn get_customer(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
     Path(id): Path<String>,
+    headers: HeaderMap,
 ) -> axum::response::Response {
-    get_party_by_kind(services, tenant, id, PartyKind::Customer).await
+    get_party_by_kind(services, tenant, id, &headers, PartyKind::Customer).await
 }
 
 pub async fn list_customers(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
+    query: ListQuery<CustomerListSpec>,
 ) -> axum::response::Response {
-    let items = services
-        .parties_list(tenant.tenant_id())
-        .into_iter()
-        .filter(|p| p.kind == PartyKind::Customer)
-        .map(dto::party_to_json)
-        .collect::<Vec<_>>();
-    (StatusCode::OK, Json(serde_json::json!({ "items": items }))).into_response()
+    let tenant_id = tenant.tenant_id();
+    let page = query.page_from(
+        dto::party_list_field,
+        |keep, order, start, limit| services.parties_list_page(tenant_id, PartyKind::Customer, keep, order, start, limit),
+        dto::party_to_json,
+    );
+    match page {
+        Ok(body) => (StatusCode::OK, Json(body)).into_response(),
+        Err(e) => e.into_response(),
+    }
 }
 
 async fn register_party(
@@ -99,7 +113,7 @@ async fn register_party(
         required: vec![Permission::new(perm)],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<Party>(
@@ -140,17 +154,25 @@ async fn update_party(
     let party_id = PartyId::new(agg);
 
     // Guard kind (read model) so /customers can't mutate a supplier.
-    if let Some(rm) = services.parties_get(tenant.tenant_id(), &party_id) {
-        if rm.kind != kind {
-            return errors::json_error(StatusCode::NOT_FOUND, "not_found", "party not found");
-        }
+    let current = services.parties_get(tenant.tenant_id(), &party_id);
+    if current.as_ref().is_some_and(|rm| rm.kind != kind) {
+        return errors::json_error(StatusCode::NOT_FOUND, "not_found", "party not found");
     }
+    // Field-level contact patches merge into the current contact.
+    if body.patches_contact_fields() && current.is_none() {
+        return errors::json_error(StatusCode::NOT_FOUND, "not_found", "party not found");
+    }
+    let update = match body.resolve(current.as_ref()) {
+        Ok(u) => u,
+        Err(msg) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_patch", msg),
+    };
 
     let cmd = PartyCommand::UpdateDetails(UpdateDetails {
         tenant_id: tenant.tenant_id(),
         party_id,
-        name: body.name,
-        contact: body.contact,
+        name: update.name,
+        contact: update.contact,
+        expected_version: update.expected_version,
         occurred_at: Utc::now(),
     });
 
@@ -159,10 +181,10 @@ async fn update_party(
         required: vec![Permission::new(perm)],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
-    let committed = match services.dispatch::<Party>(
+    let committed = match services.dispatch_versioned::<Party>(
         tenant.tenant_id(),
         agg,
         "parties.party",
@@ -209,7 +231,7 @@ async fn suspend_party(
         required: vec![Permission::new(perm)],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<Party>(
@@ -230,6 +252,7 @@ async fn get_party_by_kind(
     services: Arc<AppServices>,
     tenant: crate::context::TenantContext,
     id: String,
+    headers: &HeaderMap,
     kind: PartyKind,
 ) -> axum::response::Response {
     let agg: AggregateId = match id.parse() {
@@ -237,10 +260,22 @@ async fn get_party_by_kind(
         Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid party id"),
     };
     let party_id = PartyId::new(agg);
-    match services.parties_get(tenant.tenant_id(), &party_id) {
-        Some(rm) if rm.kind == kind => (StatusCode::OK, Json(dto::party_to_json(rm))).into_response(),
-        _ => errors::json_error(StatusCode::NOT_FOUND, "not_found", "party not found"),
-    }
+    let found = match services.parties_get(tenant.tenant_id(), &party_id) {
+        Some(rm) if rm.kind != kind => {
+            return errors::json_error(StatusCode::NOT_FOUND, "not_found", "party not found");
+        }
+        found => found,
+    };
+    versioned_read_model_response(
+        headers,
+        found,
+        |rm| rm.version,
+        dto::party_to_json,
+        services.first_event(tenant.tenant_id(), agg),
+        "parties.party",
+        "party",
+    )
+    .await
 }
 
 
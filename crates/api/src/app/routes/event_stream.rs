@@ -7,7 +7,6 @@ use std::time::Duration;
 
 use axum::{
     extract::Extension,
-    http::StatusCode,
     response::{
         sse::{Event as SseEvent, KeepAlive, Sse},
         IntoResponse,
@@ -61,11 +60,7 @@ pub async fn stream_events(
         required: vec![admin::USER_READ.clone()], // Admin-only for event streaming
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return (
-            StatusCode::FORBIDDEN,
-            format!("Forbidden: {}", e),
-        )
-            .into_response();
+        return crate::app::errors::command_denied_to_response(e);
     }
 
     let tenant_id = tenant.tenant_id();
@@ -84,10 +79,10 @@ pub async fn stream_events(
                 event_bus.subscribe()
             }
             #[cfg(feature = "redis")]
-            AppServices::Persistent { event_bus, .. } => {
+            AppServices::Persistent { bus, .. } => {
                 // For Redis Streams, use a unique consumer group for this stream
                 let consumer_name = format!("event-stream-{}", uuid::Uuid::now_v7());
-                event_bus.subscribe_with_group("event.stream.dashboard", &consumer_name, Some(tenant_id))
+                bus.subscribe_with_group("event.stream.dashboard", &consumer_name, Some(tenant_id))
             }
         };
 
@@ -6,19 +6,19 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Extension, Path, Query},
+    extract::{Extension, Path},
     http::StatusCode,
     response::IntoResponse,
     routing::get,
     Json, Router,
 };
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
 
 use forgeerp_auth::admin;
 use forgeerp_core::AggregateId;
-use forgeerp_infra::event_store::{EventFilter, Pagination, StoredEvent};
+use forgeerp_infra::event_store::{EventCursor, EventFilter, Pagination, StoredEvent};
 
+use crate::app::query::{ListQuery, ListSpec};
 use crate::app::{errors, services::AppServices};
 use crate::app::routes::common::CmdAuth;
 use crate::context::{PrincipalContext, TenantContext};
@@ -27,15 +27,27 @@ use crate::context::{PrincipalContext, TenantContext};
 // Query Parameters
 // ─────────────────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Deserialize)]
-pub struct EventListQuery {
-    pub aggregate_id: Option<String>,
-    pub aggregate_type: Option<String>,
-    pub event_type: Option<String>,
-    pub occurred_after: Option<DateTime<Utc>>,
-    pub occurred_before: Option<DateTime<Utc>>,
-    pub limit: Option<u32>,
-    pub offset: Option<u32>,
+/// Filters accepted by `GET /admin/events` (paging via the shared `ListQuery`).
+pub struct EventListSpec;
+
+impl ListSpec for EventListSpec {
+    const SORT_FIELDS: &'static [&'static str] = &[];
+    const FILTERS: &'static [&'static str] = &[
+        "aggregate_id",
+        "aggregate_type",
+        "event_type",
+        "occurred_after",
+        "occurred_before",
+        "business_key",
+    ];
+}
+
+/// Paging only: `GET /admin/events/aggregates/:id` is already scoped to one stream.
+pub struct AggregateEventsSpec;
+
+impl ListSpec for AggregateEventsSpec {
+    const SORT_FIELDS: &'static [&'static str] = &[];
+    const FILTERS: &'static [&'static str] = &[];
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -53,7 +65,7 @@ pub fn router() -> Router {
 // Handlers
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// GET /admin/events?aggregate_id=X&event_type=Y&limit=50&offset=0
+/// GET /admin/events?aggregate_id=X&event_type=Y&limit=50&cursor=...
 /// 
 /// List events with optional filters and pagination.
 /// 
@@ -63,13 +75,17 @@ pub fn router() -> Router {
 /// - `event_type`: Filter by event type (e.g., "inventory.item.created")
 /// - `occurred_after`: Filter events after this timestamp (ISO 8601)
 /// - `occurred_before`: Filter events before this timestamp (ISO 8601)
-/// - `limit`: Maximum number of events to return (default: 50, max: 1000)
-/// - `offset`: Pagination offset (default: 0)
+/// - `business_key`: Events of one business process, across aggregates (set via `X-Business-Key`)
+/// - `limit`: Maximum number of events to return (default: 50, max: 500)
+/// - `cursor`: `next_cursor` of the previous page; stable while new events arrive
+/// - `offset`: legacy offset paging (responds with `total` and `pagination` instead)
+///
+/// Malformed ids, timestamps or cursors are rejected with 400 rather than ignored.
 pub async fn list_events(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<TenantContext>,
     Extension(principal): Extension<PrincipalContext>,
-    Query(query): Query<EventListQuery>,
+    query: ListQuery<EventListSpec>,
 ) -> axum::response::Response {
     // Check permission (admin only for event inspection)
     let cmd_auth = CmdAuth::<()> {
@@ -77,25 +93,42 @@ pub async fn list_events(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
-    // Build filter
-    let aggregate_id = query.aggregate_id.and_then(|s| {
-        s.parse::<uuid::Uuid>()
-            .ok()
-            .map(|uuid| AggregateId::from_uuid(uuid))
-    });
-
-    let filter = EventFilter {
-        aggregate_id,
-        aggregate_type: query.aggregate_type,
-        event_type: query.event_type,
-        occurred_after: query.occurred_after,
-        occurred_before: query.occurred_before,
+    let filter = match event_filter(&query) {
+        Ok(f) => f,
+        Err(message) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_filter", message),
     };
 
-    let pagination = Pagination::new(query.limit, query.offset);
+    if !query.uses_offset() {
+        let after = match query.cursor::<EventCursor>() {
+            Ok(after) => after,
+            Err(e) => return e.into_response(),
+        };
+        return match services
+            .query_events_after(tenant.tenant_id(), filter, after, query.limit)
+            .await
+        {
+            Ok(page) => (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "events": page.events.iter().map(event_to_json).collect::<Vec<_>>(),
+                    "limit": page.limit,
+                    "next_cursor": page.next_cursor.map(|c| c.encode()),
+                    "has_more": page.next_cursor.is_some(),
+                })),
+            )
+                .into_response(),
+            Err(e) => errors::json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "query_failed",
+                format!("Failed to query events: {}", e),
+            ),
+        };
+    }
+
+    let pagination = Pagination::new(Some(query.limit), Some(query.offset));
 
     match services.query_events(tenant.tenant_id(), filter, pagination).await {
         Ok(result) => {
@@ -129,7 +162,7 @@ pub async fn get_aggregate_events(
     Extension(tenant): Extension<TenantContext>,
     Extension(principal): Extension<PrincipalContext>,
     Path(aggregate_id_str): Path<String>,
-    Query(query): Query<EventListQuery>,
+    query: ListQuery<AggregateEventsSpec>,
 ) -> axum::response::Response {
     // Check permission
     let cmd_auth = CmdAuth::<()> {
@@ -137,7 +170,7 @@ pub async fn get_aggregate_events(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let aggregate_id: AggregateId = match aggregate_id_str.parse::<uuid::Uuid>() {
@@ -147,7 +180,16 @@ pub async fn get_aggregate_events(
         }
     };
 
-    let pagination = Pagination::new(query.limit, query.offset);
+    // A single stream is paged by offset; keyset cursors belong to `GET /admin/events`.
+    if !matches!(query.cursor::<serde_json::Value>(), Ok(None)) {
+        return errors::json_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_cursor",
+            "aggregate event streams are paged with `offset`",
+        );
+    }
+
+    let pagination = Pagination::new(Some(query.limit), Some(query.offset));
 
     match services
         .get_aggregate_events(tenant.tenant_id(), aggregate_id, Some(pagination))
@@ -192,7 +234,7 @@ pub async fn get_event(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let event_id = match event_id_str.parse::<uuid::Uuid>() {
@@ -213,11 +255,47 @@ pub async fn get_event(
     }
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Filter Helpers
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Event filter from the query's filters; `Err` is the `invalid_filter` message.
+fn event_filter(query: &ListQuery<EventListSpec>) -> Result<EventFilter, String> {
+    let aggregate_id = match query.filter("aggregate_id") {
+        Some(raw) => match raw.parse::<uuid::Uuid>() {
+            Ok(uuid) => Some(AggregateId::from_uuid(uuid)),
+            Err(_) => return Err(format!("aggregate_id must be a UUID, got `{raw}`")),
+        },
+        None => None,
+    };
+
+    Ok(EventFilter {
+        aggregate_id,
+        aggregate_type: query.filter("aggregate_type").map(str::to_string),
+        event_type: query.filter("event_type").map(str::to_string),
+        occurred_after: parse_timestamp(query, "occurred_after")?,
+        occurred_before: parse_timestamp(query, "occurred_before")?,
+        business_key: query.filter("business_key").map(str::to_string),
+    })
+}
+
+fn parse_timestamp(
+    query: &ListQuery<EventListSpec>,
+    name: &'static str,
+) -> Result<Option<DateTime<Utc>>, String> {
+    match query.filter(name) {
+        Some(raw) => DateTime::parse_from_rfc3339(raw)
+            .map(|t| Some(t.with_timezone(&Utc)))
+            .map_err(|_| format!("{name} must be an RFC 3339 timestamp, got `{raw}`")),
+        None => Ok(None),
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // JSON Helpers
 // ─────────────────────────────────────────────────────────────────────────────
 
-fn event_to_json(event: &StoredEvent) -> serde_json::Value {
+pub fn event_to_json(event: &StoredEvent) -> serde_json::Value {
     serde_json::json!({
         "event_id": event.event_id.to_string(),
         "tenant_id": event.tenant_id.to_string(),
@@ -227,6 +305,9 @@ fn event_to_json(event: &StoredEvent) -> serde_json::Value {
         "event_type": event.event_type,
         "event_version": event.event_version,
         "occurred_at": event.occurred_at.to_rfc3339(),
+        "created_at": event.created_at.to_rfc3339(),
+        "correlation_id": event.correlation_id.map(|id| id.to_string()),
+        "causation_id": event.causation_id.map(|id| id.to_string()),
         "payload": event.payload,
     })
 }
@@ -2,27 +2,53 @@ use std::sync::Arc;
 
 use axum::{
     extract::{Extension, Path},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
 use chrono::Utc;
 
-use forgeerp_auth::Permission;
+use forgeerp_ai::{AiInsightKind, AnomalyEntry, ReorderSuggestion};
 use forgeerp_core::AggregateId;
-use forgeerp_inventory::{AdjustStock, CreateItem, InventoryCommand, InventoryItem, InventoryItemId};
+use forgeerp_infra::event_history::EventHistoryRegistry;
+use forgeerp_infra::event_store::Pagination;
+use forgeerp_infra::read_model::page_records;
+use forgeerp_auth::Permission;
+use forgeerp_inventory::{
+    AdjustStock, CreateItem, DEFAULT_LOCATION, InventoryCommand, InventoryItem, InventoryItemId, RenameItem,
+    SetReorderPoint, TransferStock,
+};
 
+use crate::app::query::{ListQuery, ListSpec};
 use crate::app::{dto, errors};
-use crate::app::routes::common::CmdAuth;
+use crate::app::routes::common::{idempotency_key, send_idempotent, versioned_read_model_response, CmdAuth};
+use crate::app::routes::events::AggregateEventsSpec;
 use crate::app::services::AppServices;
 
+/// Sort fields accepted by `GET` on an item's movements (stream order by default).
+pub struct MovementListSpec;
+
+impl ListSpec for MovementListSpec {
+    const SORT_FIELDS: &'static [&'static str] = &["sequence", "occurred_at"];
+    const FILTERS: &'static [&'static str] = &[];
+    const KEY: &'static str = "sequence";
+}
+
 pub fn router() -> Router {
     Router::new()
         .route("/anomalies", get(get_inventory_anomalies))
+        .route("/reorder-suggestions", get(get_reorder_suggestions))
+        .route("/watermark", get(get_inventory_watermark))
         .route("/:id/insights", get(get_inventory_item_insights))
+        .route("/:id/movements", get(list_item_movements))
+        .route("/:id/history", get(get_item_history))
         .route("/items", post(create_item))
+        .route("/items/:id/rename", post(rename_item))
+        .route("/items/:id/reorder-point", post(set_reorder_point))
+        .route("/items/adjust-bulk", post(adjust_stock_bulk))
         .route("/items/:id/adjust", post(adjust_stock))
+        .route("/items/:id/transfer", post(transfer_stock))
         .route("/items/:id", get(get_item))
 }
 
@@ -30,8 +56,13 @@ pub async fn create_item(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
     Extension(principal): Extension<crate::context::PrincipalContext>,
+    headers: HeaderMap,
     Json(body): Json<dto::CreateItemRequest>,
 ) -> axum::response::Response {
+    let idempotency = match idempotency_key(&headers, &body) {
+        Ok(key) => key,
+        Err(e) => return e.into_response(),
+    };
     let agg = AggregateId::new();
     let item_id = InventoryItemId::new(agg);
 
@@ -42,31 +73,104 @@ pub async fn create_item(
         occurred_at: Utc::now(),
     });
 
-    let cmd_auth = CmdAuth {
-        inner: cmd,
-        required: vec![Permission::new("inventory.items.create")],
+    // Routing + permission checks live in the command bus registration.
+    let principal = crate::authz::principal_for(&tenant, &principal);
+    let committed = match send_idempotent(&services, &principal, idempotency, cmd) {
+        Ok(c) => c,
+        Err(e) => return errors::command_bus_error_to_response(e),
     };
 
-    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
-    }
+    // A replayed create reports the item its first attempt created.
+    let agg = committed.first().map_or(agg, |e| e.aggregate_id);
+    (
+        StatusCode::CREATED,
+        Json(serde_json::json!({
+            "id": agg.to_string(),
+            "events_committed": committed.len(),
+        })),
+    )
+        .into_response()
+}
 
-    let committed = match services.dispatch::<InventoryItem>(
-        tenant.tenant_id(),
-        agg,
-        "inventory.item",
-        cmd_auth.inner,
-        |_tenant_id, aggregate_id| InventoryItem::empty(InventoryItemId::new(aggregate_id)),
-    ) {
+/// POST /inventory/items/:id/rename - Correct an item's name
+pub async fn rename_item(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<dto::RenameItemRequest>,
+) -> axum::response::Response {
+    let agg: AggregateId = match id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid item id"),
+    };
+    let idempotency = match idempotency_key(&headers, &(&id, &body)) {
+        Ok(key) => key,
+        Err(e) => return e.into_response(),
+    };
+
+    let cmd = InventoryCommand::RenameItem(RenameItem {
+        tenant_id: tenant.tenant_id(),
+        item_id: InventoryItemId::new(agg),
+        name: body.name,
+        occurred_at: Utc::now(),
+    });
+
+    let principal = crate::authz::principal_for(&tenant, &principal);
+    let committed = match send_idempotent(&services, &principal, idempotency, cmd) {
         Ok(c) => c,
-        Err(e) => return errors::dispatch_error_to_response(e),
+        Err(e) => return errors::command_bus_error_to_response(e),
     };
 
     (
-        StatusCode::CREATED,
+        StatusCode::OK,
         Json(serde_json::json!({
             "id": agg.to_string(),
             "events_committed": committed.len(),
+            "stream_version": committed.last().map(|e| e.sequence_number).unwrap_or(0),
+        })),
+    )
+        .into_response()
+}
+
+/// POST /inventory/items/:id/reorder-point - Set (or clear) the stock level that triggers reorder alerts
+pub async fn set_reorder_point(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<dto::SetReorderPointRequest>,
+) -> axum::response::Response {
+    let agg: AggregateId = match id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid item id"),
+    };
+    let idempotency = match idempotency_key(&headers, &(&id, &body)) {
+        Ok(key) => key,
+        Err(e) => return e.into_response(),
+    };
+
+    let cmd = InventoryCommand::SetReorderPoint(SetReorderPoint {
+        tenant_id: tenant.tenant_id(),
+        item_id: InventoryItemId::new(agg),
+        reorder_point: body.reorder_point,
+        occurred_at: Utc::now(),
+    });
+
+    let principal = crate::authz::principal_for(&tenant, &principal);
+    let committed = match send_idempotent(&services, &principal, idempotency, cmd) {
+        Ok(c) => c,
+        Err(e) => return errors::command_bus_error_to_response(e),
+    };
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "id": agg.to_string(),
+            "events_committed": committed.len(),
+            "stream_version": committed.last().map(|e| e.sequence_number).unwrap_or(0),
         })),
     )
         .into_response()
@@ -77,40 +181,152 @@ pub async fn adjust_stock(
     Extension(tenant): Extension<crate::context::TenantContext>,
     Extension(principal): Extension<crate::context::PrincipalContext>,
     Path(id): Path<String>,
+    headers: HeaderMap,
     Json(body): Json<dto::AdjustStockRequest>,
 ) -> axum::response::Response {
     let agg: AggregateId = match id.parse() {
         Ok(v) => v,
         Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid item id"),
     };
+    let idempotency = match idempotency_key(&headers, &(&id, &body)) {
+        Ok(key) => key,
+        Err(e) => return e.into_response(),
+    };
 
     let item_id = InventoryItemId::new(agg);
 
     let cmd = InventoryCommand::AdjustStock(AdjustStock {
         tenant_id: tenant.tenant_id(),
         item_id,
+        location_id: body.location_id.unwrap_or_else(|| DEFAULT_LOCATION.to_string()),
         delta: body.delta,
         occurred_at: Utc::now(),
     });
 
-    let cmd_auth = CmdAuth {
-        inner: cmd,
-        required: vec![Permission::new("inventory.items.adjust")],
+    // Routing + permission checks live in the command bus registration.
+    let principal = crate::authz::principal_for(&tenant, &principal);
+    let committed = match send_idempotent(&services, &principal, idempotency, cmd) {
+        Ok(c) => c,
+        Err(e) => return errors::command_bus_error_to_response(e),
     };
 
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "id": agg.to_string(),
+            "events_committed": committed.len(),
+            "stream_version": committed.last().map(|e| e.sequence_number).unwrap_or(0),
+        })),
+    )
+        .into_response()
+}
+
+/// POST /inventory/items/adjust-bulk - Apply a stock adjustment to each item of an array
+///
+/// `inventory.items.adjust` is checked once for the whole request. Each entry is then
+/// dispatched on its own, so a failing entry (bad id, invariant violation, conflict)
+/// is reported in its result and does not stop the others.
+pub async fn adjust_stock_bulk(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
+    Json(body): Json<Vec<dto::BulkAdjustStockEntry>>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![Permission::new("inventory.items.adjust")],
+    };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
-    let committed = match services.dispatch::<InventoryItem>(
-        tenant.tenant_id(),
-        agg,
-        "inventory.item",
-        cmd_auth.inner,
-        |_tenant_id, aggregate_id| InventoryItem::empty(InventoryItemId::new(aggregate_id)),
-    ) {
+    let mut succeeded = 0usize;
+    let results: Vec<serde_json::Value> = body
+        .into_iter()
+        .map(|entry| {
+            let Ok(agg) = entry.item_id.parse::<AggregateId>() else {
+                return serde_json::json!({
+                    "item_id": entry.item_id,
+                    "ok": false,
+                    "error": "invalid_id",
+                    "message": "invalid item id",
+                });
+            };
+
+            let cmd = InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id: tenant.tenant_id(),
+                item_id: InventoryItemId::new(agg),
+                location_id: entry.location_id.unwrap_or_else(|| DEFAULT_LOCATION.to_string()),
+                delta: entry.delta,
+                occurred_at: Utc::now(),
+            });
+            match services.dispatch::<InventoryItem>(tenant.tenant_id(), agg, "inventory.item", cmd, |_t, id| {
+                InventoryItem::empty(InventoryItemId::new(id))
+            }) {
+                Ok(committed) => {
+                    succeeded += 1;
+                    serde_json::json!({
+                        "item_id": entry.item_id,
+                        "ok": true,
+                        "events_committed": committed.len(),
+                        "stream_version": committed.last().map(|e| e.sequence_number).unwrap_or(0),
+                    })
+                }
+                Err(e) => {
+                    let (_, code, message) = errors::dispatch_error_parts(e);
+                    serde_json::json!({
+                        "item_id": entry.item_id,
+                        "ok": false,
+                        "error": code,
+                        "message": message,
+                    })
+                }
+            }
+        })
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "succeeded": succeeded,
+            "failed": results.len() - succeeded,
+            "results": results,
+        })),
+    )
+        .into_response()
+}
+
+/// POST /inventory/items/:id/transfer - Move stock between two locations of an item
+pub async fn transfer_stock(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<dto::TransferStockRequest>,
+) -> axum::response::Response {
+    let agg: AggregateId = match id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid item id"),
+    };
+    let idempotency = match idempotency_key(&headers, &(&id, &body)) {
+        Ok(key) => key,
+        Err(e) => return e.into_response(),
+    };
+
+    let cmd = InventoryCommand::TransferStock(TransferStock {
+        tenant_id: tenant.tenant_id(),
+        item_id: InventoryItemId::new(agg),
+        from_location: body.from_location,
+        to_location: body.to_location,
+        quantity: body.quantity,
+        occurred_at: Utc::now(),
+    });
+
+    let principal = crate::authz::principal_for(&tenant, &principal);
+    let committed = match send_idempotent(&services, &principal, idempotency, cmd) {
         Ok(c) => c,
-        Err(e) => return errors::dispatch_error_to_response(e),
+        Err(e) => return errors::command_bus_error_to_response(e),
     };
 
     (
@@ -124,10 +340,12 @@ pub async fn adjust_stock(
         .into_response()
 }
 
+/// GET /inventory/items/:id - Item read model, with an `ETag` (honours `If-None-Match`)
 pub async fn get_item(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
     Path(id): Path<String>,
+    headers: HeaderMap,
 ) -> axum::response::Response {
     let agg: AggregateId = match id.parse() {
         Ok(v) => v,
@@ -135,33 +353,121 @@ pub async fn get_item(
     };
 
     let item_id = InventoryItemId::new(agg);
-    let rm = services.inventory_get(tenant.tenant_id(), &item_id);
-    match rm {
-        Some(rm) => (StatusCode::OK, Json(dto::inventory_to_json(rm))).into_response(),
-        None => errors::json_error(StatusCode::NOT_FOUND, "not_found", "item not found"),
+    versioned_read_model_response(
+        &headers,
+        services.inventory_get(tenant.tenant_id(), &item_id),
+        |rm| rm.last_sequence,
+        dto::inventory_to_json,
+        services.first_event(tenant.tenant_id(), agg),
+        "inventory.item",
+        "item",
+    )
+    .await
+}
+
+/// GET /inventory/:id/movements - Stock ledger of an item (delta and running balance per adjustment)
+pub async fn list_item_movements(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Path(id): Path<String>,
+    query: ListQuery<MovementListSpec>,
+) -> axum::response::Response {
+    let agg: AggregateId = match id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid item id"),
+    };
+
+    let item_id = InventoryItemId::new(agg);
+    let Some(history) = services.inventory_movements(tenant.tenant_id(), &item_id) else {
+        return errors::json_error(StatusCode::NOT_FOUND, "not_found", "item not found");
+    };
+    let page = query.page_from(
+        dto::inventory_movement_list_field,
+        |keep, order, start, limit| page_records(history.movements, keep, order, start, limit),
+        dto::inventory_movement_to_json,
+    );
+    match page {
+        Ok(body) => (StatusCode::OK, Json(body)).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
-pub async fn get_inventory_anomalies(
+/// GET /inventory/:id/history?limit=50&offset=0 - Readable activity log of one item, oldest first
+pub async fn get_item_history(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
+    Path(id): Path<String>,
+    query: ListQuery<AggregateEventsSpec>,
 ) -> axum::response::Response {
-    let tenant_id = tenant.tenant_id();
-    let all = services.ai_sink().all();
+    let agg: AggregateId = match id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid item id"),
+    };
+    if !matches!(query.cursor::<serde_json::Value>(), Ok(None)) {
+        return errors::json_error(StatusCode::BAD_REQUEST, "invalid_cursor", "item history is paged with `offset`");
+    }
 
-    let mut anomalies: Vec<serde_json::Value> = Vec::new();
-    for (t, r) in all {
-        if t != tenant_id {
-            continue;
-        }
-        if r.metadata.get("kind").and_then(|v| v.as_str()) != Some("inventory.anomaly_detection") {
-            continue;
-        }
-        if let Some(arr) = r.metadata.get("anomalies").and_then(|v| v.as_array()) {
-            anomalies.extend(arr.iter().cloned());
+    let pagination = Pagination::new(Some(query.limit), Some(query.offset));
+    let result = match services.get_aggregate_events(tenant.tenant_id(), agg, Some(pagination)).await {
+        Ok(r) => r,
+        Err(e) => {
+            return errors::json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "query_failed",
+                format!("Failed to load item history: {}", e),
+            );
         }
+    };
+    // Other aggregates share the id space; only inventory items have an item history.
+    if result.total == 0 || result.events.iter().any(|e| e.aggregate_type != "inventory.item") {
+        return errors::json_error(StatusCode::NOT_FOUND, "not_found", "item not found");
     }
 
+    let entries = EventHistoryRegistry::default().render_all(&result.events);
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "item_id": agg.to_string(),
+            "entries": entries,
+            "total": result.total,
+            "pagination": {
+                "limit": result.pagination.limit,
+                "offset": result.pagination.offset,
+            },
+            "has_more": result.has_more,
+        })),
+    )
+        .into_response()
+}
+
+/// GET /inventory/watermark - Latest change to any item (poll this before refetching)
+pub async fn get_inventory_watermark(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+) -> axum::response::Response {
+    let watermark = services.inventory_watermark(tenant.tenant_id());
+    (StatusCode::OK, Json(watermark)).into_response()
+}
+
+pub async fn get_inventory_anomalies(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+) -> axum::response::Response {
+    let insights = match services
+        .ai_sink()
+        .insights(tenant.tenant_id(), AiInsightKind::InventoryAnomalyDetection)
+        .await
+    {
+        Ok(insights) => insights,
+        Err(e) => return errors::ai_insight_store_error_to_response(e),
+    };
+
+    let anomalies: Vec<AnomalyEntry> = insights
+        .iter()
+        .filter_map(|r| r.as_inventory_anomalies())
+        .flatten()
+        .collect();
+
     (
         StatusCode::OK,
         Json(serde_json::json!({
@@ -174,36 +480,64 @@ pub async fn get_inventory_anomalies(
         .into_response()
 }
 
+/// Latest reorder suggestions for the tenant; items whose stock covers demand are left out.
+pub async fn get_reorder_suggestions(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+) -> axum::response::Response {
+    let insights = match services
+        .ai_sink()
+        .insights(tenant.tenant_id(), AiInsightKind::InventoryReorderSuggestion)
+        .await
+    {
+        Ok(insights) => insights,
+        Err(e) => return errors::ai_insight_store_error_to_response(e),
+    };
+
+    let suggestions: Vec<ReorderSuggestion> = insights
+        .iter()
+        .filter_map(|r| r.as_reorder_suggestions())
+        .flatten()
+        .filter(|s| s.suggested_qty > 0)
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "kind": "insights",
+            "insight_type": "inventory.reorder_suggestions",
+            "count": suggestions.len(),
+            "suggestions": suggestions,
+        })),
+    )
+        .into_response()
+}
+
 pub async fn get_inventory_item_insights(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
     Path(id): Path<String>,
 ) -> axum::response::Response {
-    let tenant_id = tenant.tenant_id();
     let agg: AggregateId = match id.parse() {
         Ok(v) => v,
         Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid inventory id"),
     };
     let item_id = agg.to_string();
 
-    let all = services.ai_sink().all();
-    let mut item_anomalies: Vec<serde_json::Value> = Vec::new();
-
-    for (t, r) in all {
-        if t != tenant_id {
-            continue;
-        }
-        if r.metadata.get("kind").and_then(|v| v.as_str()) != Some("inventory.anomaly_detection") {
-            continue;
-        }
-        if let Some(arr) = r.metadata.get("anomalies").and_then(|v| v.as_array()) {
-            for a in arr {
-                if a.get("item_id").and_then(|v| v.as_str()) == Some(item_id.as_str()) {
-                    item_anomalies.push(a.clone());
-                }
-            }
-        }
-    }
+    let insights = match services
+        .ai_sink()
+        .insights(tenant.tenant_id(), AiInsightKind::InventoryAnomalyDetection)
+        .await
+    {
+        Ok(insights) => insights,
+        Err(e) => return errors::ai_insight_store_error_to_response(e),
+    };
+    let item_anomalies: Vec<AnomalyEntry> = insights
+        .iter()
+        .filter_map(|r| r.as_inventory_anomalies())
+        .flatten()
+        .filter(|a| a.item_id == item_id)
+        .collect();
 
     (
         StatusCode::OK,
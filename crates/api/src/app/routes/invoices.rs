@@ -12,21 +12,33 @@ use chrono::Utc;
 use forgeerp_auth::Permission;
 use forgeerp_core::AggregateId;
 use forgeerp_invoicing::{
-    Invoice, InvoiceCommand, InvoiceId, InvoiceLine, IssueInvoice, RegisterPayment, VoidInvoice,
+    DEFAULT_PAYMENT_TERMS_DAYS, Invoice, InvoiceCommand, InvoiceId, InvoiceLine, IssueCreditNote, IssueInvoice, PaymentId,
+    RegisterPayment, ReversePayment, VoidInvoice,
 };
 use forgeerp_products::ProductId;
 use forgeerp_sales::SalesOrderId;
 
+use crate::app::query::{ListQuery, ListSpec};
 use crate::app::{dto, errors};
-use crate::app::routes::common::CmdAuth;
+use crate::app::routes::common::{read_model_response, CmdAuth};
 use crate::app::services::AppServices;
 
+/// Sort fields and filters accepted by `GET` on the invoices list.
+pub struct InvoiceListSpec;
+
+impl ListSpec for InvoiceListSpec {
+    const SORT_FIELDS: &'static [&'static str] = &["id", "status", "due_date", "total_amount", "outstanding_amount"];
+    const FILTERS: &'static [&'static str] = &["status", "sales_order_id"];
+}
+
 pub fn router() -> Router {
     Router::new()
         .route("/", post(issue_invoice).get(list_invoices))
         .route("/:id", get(get_invoice))
         .route("/:id/payments", post(register_invoice_payment))
+        .route("/:id/payments/:payment_id/reverse", post(reverse_invoice_payment))
         .route("/:id/void", post(void_invoice))
+        .route("/:id/credit-notes", post(issue_credit_note))
 }
 
 pub async fn issue_invoice(
@@ -41,9 +53,10 @@ pub async fn issue_invoice(
     };
     let sales_order_id = SalesOrderId::new(sales_order_agg);
 
-    let due_date = match chrono::DateTime::parse_from_rfc3339(&body.due_date) {
-        Ok(dt) => dt.with_timezone(&Utc),
-        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_due_date", "due_date must be RFC3339"),
+    let settings = services.tenant_settings().get(tenant.tenant_id());
+    let convention = match dto::currency_convention(&settings, body.currency.as_deref()) {
+        Ok(c) => c,
+        Err(e) => return e.into_response(),
     };
 
     let invoice_agg = AggregateId::new();
@@ -55,12 +68,16 @@ pub async fn issue_invoice(
             Ok(v) => v,
             Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid product id"),
         };
+        let unit_price = match dto::to_unsigned_minor_units(&l.unit_price, &convention) {
+            Ok(v) => v,
+            Err(e) => return e.into_response(),
+        };
         lines.push(InvoiceLine {
             line_no: (idx as u32) + 1,
             sales_order_id,
             product_id: ProductId::new(prod_agg),
             quantity: l.quantity,
-            unit_price: l.unit_price,
+            unit_price,
         });
     }
 
@@ -69,7 +86,8 @@ pub async fn issue_invoice(
         invoice_id,
         sales_order_id,
         lines,
-        due_date,
+        payment_terms_days: body.payment_terms_days.unwrap_or(DEFAULT_PAYMENT_TERMS_DAYS),
+        currency: Some(convention.code),
         occurred_at: Utc::now(),
     });
 
@@ -78,7 +96,7 @@ pub async fn issue_invoice(
         required: vec![Permission::new("invoices.issue")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<Invoice>(
@@ -108,10 +126,25 @@ pub async fn register_invoice_payment(
     };
     let invoice_id = InvoiceId::new(agg);
 
+    // An omitted currency means the invoice's; the aggregate rejects any other.
+    let invoiced = services.invoices_get(tenant.tenant_id(), &invoice_id).and_then(|rm| rm.currency);
+    let settings = services.tenant_settings().get(tenant.tenant_id());
+    let convention = match dto::currency_convention(&settings, body.currency.as_deref().or(invoiced.as_deref())) {
+        Ok(c) => c,
+        Err(e) => return e.into_response(),
+    };
+    let amount = match dto::to_unsigned_minor_units(&body.amount, &convention) {
+        Ok(v) => v,
+        Err(e) => return e.into_response(),
+    };
+
+    let payment_id = PaymentId::new(AggregateId::new());
     let cmd = InvoiceCommand::RegisterPayment(RegisterPayment {
         tenant_id: tenant.tenant_id(),
         invoice_id,
-        amount: body.amount,
+        payment_id,
+        amount,
+        currency: Some(convention.code),
         occurred_at: Utc::now(),
     });
 
@@ -120,7 +153,62 @@ pub async fn register_invoice_payment(
         required: vec![Permission::new("invoices.pay")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
+    }
+
+    let committed = match services.dispatch::<Invoice>(
+        tenant.tenant_id(),
+        agg,
+        "invoicing.invoice",
+        cmd_auth.inner,
+        |_t, aggregate_id| Invoice::empty(InvoiceId::new(aggregate_id)),
+    ) {
+        Ok(c) => c,
+        Err(e) => return errors::dispatch_error_to_response(e),
+    };
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "id": agg.to_string(),
+            "payment_id": payment_id.to_string(),
+            "events_committed": committed.len(),
+        })),
+    )
+        .into_response()
+}
+
+pub async fn reverse_invoice_payment(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
+    Path((id, payment_id)): Path<(String, String)>,
+    Json(body): Json<dto::ReversePaymentRequest>,
+) -> axum::response::Response {
+    let agg: AggregateId = match id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid invoice id"),
+    };
+    let invoice_id = InvoiceId::new(agg);
+    let payment_id = match payment_id.parse() {
+        Ok(v) => PaymentId::new(v),
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid payment id"),
+    };
+
+    let cmd = InvoiceCommand::ReversePayment(ReversePayment {
+        tenant_id: tenant.tenant_id(),
+        invoice_id,
+        payment_id,
+        reason: body.reason,
+        occurred_at: Utc::now(),
+    });
+
+    let cmd_auth = CmdAuth {
+        inner: cmd,
+        required: vec![Permission::new("invoices.pay")],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<Invoice>(
@@ -162,7 +250,7 @@ pub async fn void_invoice(
         required: vec![Permission::new("invoices.void")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<Invoice>(
@@ -179,32 +267,120 @@ pub async fn void_invoice(
     (StatusCode::OK, Json(serde_json::json!({"id": agg.to_string(), "events_committed": committed.len()}))).into_response()
 }
 
-pub async fn get_invoice(
+pub async fn issue_credit_note(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
     Path(id): Path<String>,
+    Json(body): Json<dto::IssueCreditNoteRequest>,
 ) -> axum::response::Response {
     let agg: AggregateId = match id.parse() {
         Ok(v) => v,
         Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid invoice id"),
     };
     let invoice_id = InvoiceId::new(agg);
-    match services.invoices_get(tenant.tenant_id(), &invoice_id) {
-        Some(rm) => (StatusCode::OK, Json(dto::invoice_to_json(rm))).into_response(),
-        None => errors::json_error(StatusCode::NOT_FOUND, "not_found", "invoice not found"),
+
+    // Credited lines reference the original invoice lines for product and order.
+    let Some(invoice) = services.invoices_get(tenant.tenant_id(), &invoice_id) else {
+        return errors::json_error(StatusCode::NOT_FOUND, "not_found", "invoice not found");
+    };
+
+    let settings = services.tenant_settings().get(tenant.tenant_id());
+    let currency = body.currency.as_deref().or(invoice.currency.as_deref());
+    let convention = match dto::currency_convention(&settings, currency) {
+        Ok(c) => c,
+        Err(e) => return e.into_response(),
+    };
+
+    let mut lines: Vec<InvoiceLine> = Vec::new();
+    for l in body.lines {
+        let Some(original) = invoice.lines.iter().find(|il| il.line_no == l.line_no) else {
+            return errors::json_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_line",
+                format!("invoice has no line {}", l.line_no),
+            );
+        };
+        let unit_price = match &l.unit_price {
+            Some(p) => match dto::to_unsigned_minor_units(p, &convention) {
+                Ok(v) => v,
+                Err(e) => return e.into_response(),
+            },
+            None => original.unit_price,
+        };
+        lines.push(InvoiceLine {
+            quantity: l.quantity,
+            unit_price,
+            ..original.clone()
+        });
+    }
+
+    let cmd = InvoiceCommand::IssueCreditNote(IssueCreditNote {
+        tenant_id: tenant.tenant_id(),
+        invoice_id,
+        lines,
+        currency: Some(convention.code),
+        reason: body.reason,
+        occurred_at: Utc::now(),
+    });
+
+    let cmd_auth = CmdAuth {
+        inner: cmd,
+        required: vec![Permission::new("invoices.credit")],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
     }
+
+    let committed = match services.dispatch::<Invoice>(
+        tenant.tenant_id(),
+        agg,
+        "invoicing.invoice",
+        cmd_auth.inner,
+        |_t, aggregate_id| Invoice::empty(InvoiceId::new(aggregate_id)),
+    ) {
+        Ok(c) => c,
+        Err(e) => return errors::dispatch_error_to_response(e),
+    };
+
+    (StatusCode::OK, Json(serde_json::json!({"id": agg.to_string(), "events_committed": committed.len()}))).into_response()
+}
+
+pub async fn get_invoice(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    let agg: AggregateId = match id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid invoice id"),
+    };
+    let invoice_id = InvoiceId::new(agg);
+    read_model_response(
+        services.invoices_get(tenant.tenant_id(), &invoice_id),
+        dto::invoice_to_json,
+        services.first_event(tenant.tenant_id(), agg),
+        "invoicing.invoice",
+        "invoice",
+    )
+    .await
 }
 
 pub async fn list_invoices(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
+    query: ListQuery<InvoiceListSpec>,
 ) -> axum::response::Response {
-    let items = services
-        .invoices_list(tenant.tenant_id())
-        .into_iter()
-        .map(dto::invoice_to_json)
-        .collect::<Vec<_>>();
-    (StatusCode::OK, Json(serde_json::json!({ "items": items }))).into_response()
+    let tenant_id = tenant.tenant_id();
+    let page = query.page_from(
+        dto::invoice_list_field,
+        |keep, order, start, limit| services.invoices_list_page(tenant_id, keep, order, start, limit),
+        dto::invoice_to_json,
+    );
+    match page {
+        Ok(body) => (StatusCode::OK, Json(body)).into_response(),
+        Err(e) => e.into_response(),
+    }
 }
 
 
@@ -9,7 +9,9 @@ use axum::{
 };
 use chrono::Utc;
 
-use forgeerp_accounting::{JournalCommand, Ledger, LedgerId, PostJournalEntry};
+use forgeerp_accounting::{
+    ClosePeriod, JournalCommand, Ledger, LedgerId, PostJournalEntry, ReopenPeriod, ReverseJournalEntry,
+};
 use forgeerp_auth::Permission;
 
 use crate::app::{dto, errors};
@@ -20,7 +22,11 @@ pub fn router() -> Router {
     Router::new()
         .route("/balances", get(list_ledger_balances))
         .route("/balances/:code", get(get_ledger_balance))
+        .route("/trial-balance", get(get_trial_balance))
         .route("/journal", post(post_journal_entry))
+        .route("/journal/:entry_id/reverse", post(reverse_journal_entry))
+        .route("/periods/close", post(close_period))
+        .route("/periods/reopen", post(reopen_period))
 }
 
 pub async fn list_ledger_balances(
@@ -46,6 +52,14 @@ pub async fn get_ledger_balance(
     }
 }
 
+pub async fn get_trial_balance(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+) -> axum::response::Response {
+    let tb = services.trial_balance(tenant.tenant_id());
+    (StatusCode::OK, Json(dto::trial_balance_to_json(tb))).into_response()
+}
+
 pub async fn post_journal_entry(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
@@ -56,19 +70,26 @@ pub async fn post_journal_entry(
         return errors::json_error(StatusCode::BAD_REQUEST, "validation", "journal entry must have lines");
     }
 
-    let lines = match dto::to_journal_lines(body.lines) {
+    let settings = services.tenant_settings().get(tenant.tenant_id());
+    let convention = match dto::currency_convention(&settings, body.currency.as_deref()) {
+        Ok(c) => c,
+        Err(e) => return e.into_response(),
+    };
+    let lines = match dto::to_journal_lines(body.lines, &convention) {
         Ok(v) => v,
         Err(resp) => return resp,
     };
 
-    let ledger_agg = services.default_ledger_id();
-    let ledger_id = LedgerId::new(ledger_agg);
+    let ledger_id = LedgerId::default_for(tenant.tenant_id());
+    let ledger_agg = ledger_id.0;
 
+    let entry_id = uuid::Uuid::now_v7();
     let cmd = JournalCommand::PostJournalEntry(PostJournalEntry {
         tenant_id: tenant.tenant_id(),
         ledger_id,
-        entry_id: uuid::Uuid::now_v7(),
+        entry_id,
         lines,
+        currency: Some(convention.code),
         occurred_at: Utc::now(),
         description: body.description,
     });
@@ -78,7 +99,7 @@ pub async fn post_journal_entry(
         required: vec![Permission::new("ledger.post")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<Ledger>(
@@ -94,9 +115,134 @@ pub async fn post_journal_entry(
 
     (
         StatusCode::OK,
-        Json(serde_json::json!({"ledger_id": ledger_agg.to_string(), "events_committed": committed.len()})),
+        Json(serde_json::json!({
+            "ledger_id": ledger_agg.to_string(),
+            "entry_id": entry_id.to_string(),
+            "events_committed": committed.len(),
+        })),
     )
         .into_response()
 }
 
+pub async fn reverse_journal_entry(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
+    Path(entry_id): Path<String>,
+    Json(body): Json<dto::ReverseJournalEntryRequest>,
+) -> axum::response::Response {
+    let entry_id: uuid::Uuid = match entry_id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid entry id"),
+    };
+
+    let ledger_id = LedgerId::default_for(tenant.tenant_id());
+    let ledger_agg = ledger_id.0;
 
+    let reversal_entry_id = uuid::Uuid::now_v7();
+    let cmd = JournalCommand::ReverseJournalEntry(ReverseJournalEntry {
+        tenant_id: tenant.tenant_id(),
+        ledger_id,
+        entry_id,
+        reversal_entry_id,
+        occurred_at: Utc::now(),
+        description: body.description,
+    });
+
+    let cmd_auth = CmdAuth {
+        inner: cmd,
+        required: vec![Permission::new("ledger.post")],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let committed = match services.dispatch::<Ledger>(
+        tenant.tenant_id(),
+        ledger_agg,
+        "accounting.ledger",
+        cmd_auth.inner,
+        |_t, aggregate_id| Ledger::empty(LedgerId::new(aggregate_id)),
+    ) {
+        Ok(c) => c,
+        Err(e) => return errors::dispatch_error_to_response(e),
+    };
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "ledger_id": ledger_agg.to_string(),
+            "entry_id": reversal_entry_id.to_string(),
+            "events_committed": committed.len(),
+        })),
+    )
+        .into_response()
+}
+
+pub async fn close_period(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
+    Json(body): Json<dto::LedgerPeriodRequest>,
+) -> axum::response::Response {
+    let cmd = JournalCommand::ClosePeriod(ClosePeriod {
+        tenant_id: tenant.tenant_id(),
+        ledger_id: LedgerId::default_for(tenant.tenant_id()),
+        period_end: body.period_end,
+        occurred_at: Utc::now(),
+    });
+    dispatch_period_command(&services, &tenant, &principal, cmd, body.period_end)
+}
+
+pub async fn reopen_period(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
+    Json(body): Json<dto::LedgerPeriodRequest>,
+) -> axum::response::Response {
+    let cmd = JournalCommand::ReopenPeriod(ReopenPeriod {
+        tenant_id: tenant.tenant_id(),
+        ledger_id: LedgerId::default_for(tenant.tenant_id()),
+        period_end: body.period_end,
+        occurred_at: Utc::now(),
+    });
+    dispatch_period_command(&services, &tenant, &principal, cmd, body.period_end)
+}
+
+fn dispatch_period_command(
+    services: &AppServices,
+    tenant: &crate::context::TenantContext,
+    principal: &crate::context::PrincipalContext,
+    cmd: JournalCommand,
+    period_end: chrono::DateTime<Utc>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth {
+        inner: cmd,
+        required: vec![Permission::new("ledger.close")],
+    };
+    if let Err(e) = crate::authz::authorize_command(tenant, principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let ledger_agg = LedgerId::default_for(tenant.tenant_id()).0;
+    let committed = match services.dispatch::<Ledger>(
+        tenant.tenant_id(),
+        ledger_agg,
+        "accounting.ledger",
+        cmd_auth.inner,
+        |_t, aggregate_id| Ledger::empty(LedgerId::new(aggregate_id)),
+    ) {
+        Ok(c) => c,
+        Err(e) => return errors::dispatch_error_to_response(e),
+    };
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "ledger_id": ledger_agg.to_string(),
+            "period_end": period_end.to_rfc3339(),
+            "events_committed": committed.len(),
+        })),
+    )
+        .into_response()
+}
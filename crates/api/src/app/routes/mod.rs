@@ -1,4 +1,6 @@
-use axum::{routing::get, Router};
+use axum::{middleware::from_fn, routing::get, Router};
+
+use crate::middleware::dry_run_middleware;
 
 pub mod admin;
 pub mod ar;
@@ -18,20 +20,23 @@ pub mod suppliers;
 pub mod system;
 
 /// Router for all authenticated (tenant-scoped) endpoints.
+///
+/// Command routes accept `?dry_run=true` (see [`dry_run_middleware`]); replay jobs
+/// have their own `dry_run` and streaming/inspection routes have nothing to preview.
 pub fn router() -> Router {
     Router::new()
         .route("/whoami", get(system::whoami))
         .route("/stream", get(system::stream))
-        .nest("/inventory", inventory::router())
-        .nest("/products", products::router())
-        .nest("/customers", customers::router())
-        .nest("/suppliers", suppliers::router())
-        .nest("/sales", sales::router())
-        .nest("/invoices", invoices::router())
-        .nest("/purchases", purchases::router())
-        .nest("/ledger", ledger::router())
+        .nest("/inventory", inventory::router().layer(from_fn(dry_run_middleware)))
+        .nest("/products", products::router().layer(from_fn(dry_run_middleware)))
+        .nest("/customers", customers::router().layer(from_fn(dry_run_middleware)))
+        .nest("/suppliers", suppliers::router().layer(from_fn(dry_run_middleware)))
+        .nest("/sales", sales::router().layer(from_fn(dry_run_middleware)))
+        .nest("/invoices", invoices::router().layer(from_fn(dry_run_middleware)))
+        .nest("/purchases", purchases::router().layer(from_fn(dry_run_middleware)))
+        .nest("/ledger", ledger::router().layer(from_fn(dry_run_middleware)))
         .nest("/ar", ar::router())
-        .nest("/admin", admin::router())
+        .nest("/admin", admin::router().layer(from_fn(dry_run_middleware)))
         .nest("/admin/rbac", rbac::router())
         .nest("/admin/events", events::router())
         .nest("/admin/replay", replay::router())
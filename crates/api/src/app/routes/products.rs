@@ -1,28 +1,44 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Extension, Path},
-    http::StatusCode,
+    extract::{Extension, Path, Query},
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
-use chrono::Utc;
-
 use forgeerp_auth::Permission;
-use forgeerp_core::AggregateId;
-use forgeerp_products::{ActivateProduct, ArchiveProduct, CreateProduct, Product, ProductCommand, ProductId};
+use forgeerp_core::{AggregateId, DeletionGuard};
+use forgeerp_infra::command_dispatcher::is_dry_run;
+use forgeerp_infra::product_import::{self, ImportMode, ImportRow};
+use forgeerp_products::{
+    ActivateProduct, ArchiveProduct, CreateProduct, DeleteProduct, Product, ProductCommand, ProductId, UpdatePricing,
+};
 
+use crate::app::query::{ListQuery, ListSpec};
 use crate::app::{dto, errors};
-use crate::app::routes::common::CmdAuth;
+use crate::app::routes::common::{read_model_response, CmdAuth};
 use crate::app::services::AppServices;
 
+// Commands leave `occurred_at` at its default: the dispatcher stamps it when the
+// command is handled (see `stamp_product_command` in `services.rs`).
+
+/// Sort fields and filters accepted by `GET` on the products list.
+pub struct ProductListSpec;
+
+impl ListSpec for ProductListSpec {
+    const SORT_FIELDS: &'static [&'static str] = &["id", "sku", "name", "status"];
+    const FILTERS: &'static [&'static str] = &["status", "sku"];
+}
+
 pub fn router() -> Router {
     Router::new()
         .route("/", post(create_product).get(list_products))
-        .route("/:id", get(get_product))
+        .route("/import", post(import_products))
+        .route("/:id", get(get_product).delete(delete_product))
         .route("/:id/activate", post(activate_product))
         .route("/:id/archive", post(archive_product))
+        .route("/:id/pricing", post(update_pricing))
 }
 
 pub async fn create_product(
@@ -31,16 +47,28 @@ pub async fn create_product(
     Extension(principal): Extension<crate::context::PrincipalContext>,
     Json(body): Json<dto::CreateProductRequest>,
 ) -> axum::response::Response {
+    let settings = services.tenant_settings().get(tenant.tenant_id());
+    let pricing = match dto::to_pricing(body.pricing, &settings) {
+        Ok(p) => p,
+        Err(e) => return e.into_response(),
+    };
+    let inventory_item_id = match body.inventory_item_id.as_deref().map(str::parse::<AggregateId>).transpose() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid inventory item id"),
+    };
+
     let agg = AggregateId::new();
     let product_id = ProductId::new(agg);
+    let sku = body.sku.clone();
 
     let cmd = ProductCommand::CreateProduct(CreateProduct {
         tenant_id: tenant.tenant_id(),
         product_id,
         sku: body.sku,
         name: body.name,
-        pricing: body.pricing,
-        occurred_at: Utc::now(),
+        pricing,
+        inventory_item_id,
+        occurred_at: Default::default(),
     });
 
     let cmd_auth = CmdAuth {
@@ -48,16 +76,19 @@ pub async fn create_product(
         required: vec![Permission::new("products.create")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
-    let committed = match services.dispatch::<Product>(
-        tenant.tenant_id(),
-        agg,
-        "products.product",
-        cmd_auth.inner,
-        |_t, aggregate_id| Product::empty(ProductId::new(aggregate_id)),
-    ) {
+    // The SKU is reserved before dispatch so concurrent creates cannot both succeed.
+    let committed = match services.sku_registry().create_with(tenant.tenant_id(), &sku, product_id, || {
+        services.dispatch::<Product>(
+            tenant.tenant_id(),
+            agg,
+            "products.product",
+            cmd_auth.inner,
+            |_t, aggregate_id| Product::empty(ProductId::new(aggregate_id)),
+        )
+    }) {
         Ok(c) => c,
         Err(e) => return errors::dispatch_error_to_response(e),
     };
@@ -72,6 +103,68 @@ pub async fn create_product(
         .into_response()
 }
 
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct ImportQuery {
+    /// `atomic` (default) or `best-effort`.
+    pub mode: Option<String>,
+}
+
+/// POST /products/import - Create products from a CSV (`text/csv`) or JSON array body.
+///
+/// Every row is validated first. An import that writes nothing because of invalid rows
+/// (always the case for an atomic one) answers 422 with the full report; otherwise the
+/// report lists what was created and which rows failed.
+pub async fn import_products(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
+    Query(query): Query<ImportQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![Permission::new("products.create")],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    // Imports reserve SKUs outside the dispatcher, so they cannot be previewed.
+    if is_dry_run() {
+        return errors::json_error(
+            StatusCode::BAD_REQUEST,
+            "dry_run_unsupported",
+            "product imports cannot be previewed",
+        );
+    }
+
+    let mode = match query.mode.as_deref().map(str::parse::<ImportMode>).transpose() {
+        Ok(mode) => mode.unwrap_or_default(),
+        Err(e) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_mode", e),
+    };
+
+    let is_csv = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/csv"));
+    let rows = if is_csv {
+        product_import::parse_csv(&body)
+    } else {
+        serde_json::from_str::<Vec<ImportRow>>(&body).map_err(|e| e.to_string())
+    };
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_import", e),
+    };
+
+    match services.import_products(tenant.tenant_id(), &rows, mode) {
+        Ok(report) if report.is_rejected() => (StatusCode::UNPROCESSABLE_ENTITY, Json(report)).into_response(),
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => errors::dispatch_error_to_response(e),
+    }
+}
+
 pub async fn activate_product(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
@@ -87,7 +180,7 @@ pub async fn activate_product(
     let cmd = ProductCommand::ActivateProduct(ActivateProduct {
         tenant_id: tenant.tenant_id(),
         product_id,
-        occurred_at: Utc::now(),
+        occurred_at: Default::default(),
     });
 
     let cmd_auth = CmdAuth {
@@ -95,7 +188,55 @@ pub async fn activate_product(
         required: vec![Permission::new("products.activate")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
+    }
+
+    let committed = match services.dispatch::<Product>(
+        tenant.tenant_id(),
+        agg,
+        "products.product",
+        cmd_auth.inner,
+        |_t, aggregate_id| Product::empty(ProductId::new(aggregate_id)),
+    ) {
+        Ok(c) => c,
+        Err(e) => return errors::dispatch_error_to_response(e),
+    };
+
+    (StatusCode::OK, Json(serde_json::json!({"id": agg.to_string(), "events_committed": committed.len()}))).into_response()
+}
+
+/// POST /products/:id/pricing - Replace a product's pricing
+pub async fn update_pricing(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
+    Path(id): Path<String>,
+    Json(body): Json<dto::PricingRequest>,
+) -> axum::response::Response {
+    let agg: AggregateId = match id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid product id"),
+    };
+    let product_id = ProductId::new(agg);
+    let settings = services.tenant_settings().get(tenant.tenant_id());
+    let pricing = match dto::to_pricing(Some(body), &settings) {
+        Ok(p) => p.unwrap_or_default(),
+        Err(e) => return e.into_response(),
+    };
+
+    let cmd = ProductCommand::UpdatePricing(UpdatePricing {
+        tenant_id: tenant.tenant_id(),
+        product_id,
+        pricing,
+        occurred_at: Default::default(),
+    });
+
+    let cmd_auth = CmdAuth {
+        inner: cmd,
+        required: vec![Permission::new("products.pricing.update")],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<Product>(
@@ -127,7 +268,7 @@ pub async fn archive_product(
     let cmd = ProductCommand::ArchiveProduct(ArchiveProduct {
         tenant_id: tenant.tenant_id(),
         product_id,
-        occurred_at: Utc::now(),
+        occurred_at: Default::default(),
     });
 
     let cmd_auth = CmdAuth {
@@ -135,7 +276,7 @@ pub async fn archive_product(
         required: vec![Permission::new("products.archive")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<Product>(
@@ -148,6 +289,59 @@ pub async fn archive_product(
         Ok(c) => c,
         Err(e) => return errors::dispatch_error_to_response(e),
     };
+    // Release the SKU now rather than when the projection worker gets to it.
+    for stored in &committed {
+        let _ = services.sku_registry().apply_envelope(&stored.to_envelope());
+    }
+
+    (StatusCode::OK, Json(serde_json::json!({"id": agg.to_string(), "events_committed": committed.len()}))).into_response()
+}
+
+/// DELETE /products/:id - Remove a product nothing references (kept as a tombstone)
+pub async fn delete_product(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
+    Path(id): Path<String>,
+    body: Option<Json<dto::DeleteProductRequest>>,
+) -> axum::response::Response {
+    let agg: AggregateId = match id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid product id"),
+    };
+    let product_id = ProductId::new(agg);
+
+    let cmd = ProductCommand::DeleteProduct(DeleteProduct {
+        tenant_id: tenant.tenant_id(),
+        product_id,
+        reason: body.and_then(|Json(b)| b.reason),
+        guard: DeletionGuard {
+            references: services.product_references(tenant.tenant_id(), product_id),
+        },
+        occurred_at: Default::default(),
+    });
+
+    let cmd_auth = CmdAuth {
+        inner: cmd,
+        required: vec![Permission::new("products.delete")],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let committed = match services.dispatch::<Product>(
+        tenant.tenant_id(),
+        agg,
+        "products.product",
+        cmd_auth.inner,
+        |_t, aggregate_id| Product::empty(ProductId::new(aggregate_id)),
+    ) {
+        Ok(c) => c,
+        Err(e) => return errors::dispatch_error_to_response(e),
+    };
+    for stored in &committed {
+        let _ = services.sku_registry().apply_envelope(&stored.to_envelope());
+    }
 
     (StatusCode::OK, Json(serde_json::json!({"id": agg.to_string(), "events_committed": committed.len()}))).into_response()
 }
@@ -162,22 +356,41 @@ pub async fn get_product(
         Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid product id"),
     };
     let product_id = ProductId::new(agg);
-    match services.products_get(tenant.tenant_id(), &product_id) {
-        Some(rm) => (StatusCode::OK, Json(dto::product_to_json(rm))).into_response(),
-        None => errors::json_error(StatusCode::NOT_FOUND, "not_found", "product not found"),
+    let found = services.products_get(tenant.tenant_id(), &product_id);
+    // A deleted product's stream still exists; its row is only a tombstone.
+    if found.is_none()
+        && services
+            .products_tombstones(tenant.tenant_id())
+            .iter()
+            .any(|rm| rm.product_id == product_id)
+    {
+        return errors::json_error(StatusCode::NOT_FOUND, "not_found", "product not found");
     }
+    read_model_response(
+        found,
+        dto::product_to_json,
+        services.first_event(tenant.tenant_id(), agg),
+        "products.product",
+        "product",
+    )
+    .await
 }
 
 pub async fn list_products(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
+    query: ListQuery<ProductListSpec>,
 ) -> axum::response::Response {
-    let items = services
-        .products_list(tenant.tenant_id())
-        .into_iter()
-        .map(dto::product_to_json)
-        .collect::<Vec<_>>();
-    (StatusCode::OK, Json(serde_json::json!({ "items": items }))).into_response()
+    let tenant_id = tenant.tenant_id();
+    let page = query.page_from(
+        dto::product_list_field,
+        |keep, order, start, limit| services.products_list_page(tenant_id, keep, order, start, limit),
+        dto::product_to_json,
+    );
+    match page {
+        Ok(body) => (StatusCode::OK, Json(body)).into_response(),
+        Err(e) => e.into_response(),
+    }
 }
 
 
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::{
@@ -11,17 +12,27 @@ use chrono::Utc;
 
 use forgeerp_auth::Permission;
 use forgeerp_core::AggregateId;
+use forgeerp_infra::three_way_match::{SupplierInvoice, SupplierInvoiceLine, ThreeWayMatch};
 use forgeerp_parties::PartyId;
 use forgeerp_products::ProductId;
 use forgeerp_purchasing::{
     AddLine as AddPurchaseLine, Approve, CreatePurchaseOrder, PurchaseOrder, PurchaseOrderCommand,
-    PurchaseOrderId, ReceiveGoods,
+    PurchaseOrderEvent, PurchaseOrderId, ReceiveGoods,
 };
 
+use crate::app::query::{ListQuery, ListSpec};
 use crate::app::{dto, errors};
-use crate::app::routes::common::CmdAuth;
+use crate::app::routes::common::{read_model_response, CmdAuth};
 use crate::app::services::AppServices;
 
+/// Sort fields and filters accepted by `GET` on the purchase orders list.
+pub struct PurchaseOrderListSpec;
+
+impl ListSpec for PurchaseOrderListSpec {
+    const SORT_FIELDS: &'static [&'static str] = &["id", "status", "supplier_id"];
+    const FILTERS: &'static [&'static str] = &["status", "supplier_id"];
+}
+
 pub fn router() -> Router {
     Router::new().nest("/orders", orders_router())
 }
@@ -33,6 +44,7 @@ fn orders_router() -> Router {
         .route("/:id/lines", post(add_purchase_order_line))
         .route("/:id/approve", post(approve_purchase_order))
         .route("/:id/receive", post(receive_purchase_order_goods))
+        .route("/:id/match", post(match_purchase_order))
 }
 
 pub async fn create_purchase_order(
@@ -47,6 +59,34 @@ pub async fn create_purchase_order(
     };
     let supplier_id = PartyId::new(supplier_agg);
 
+    let expected_at = match body.expected_at.as_deref().map(chrono::DateTime::parse_from_rfc3339) {
+        None => None,
+        Some(Ok(dt)) => Some(dt.with_timezone(&Utc)),
+        Some(Err(_)) => {
+            return errors::json_error(StatusCode::BAD_REQUEST, "invalid_expected_at", "expected_at must be RFC3339");
+        }
+    };
+    let settings = services.tenant_settings().get(tenant.tenant_id());
+    let convention = match dto::currency_convention(&settings, body.currency.as_deref()) {
+        Ok(c) => c,
+        Err(e) => return e.into_response(),
+    };
+
+    let freight = match &body.freight {
+        Some(amount) => match dto::to_unsigned_minor_units(amount, &convention) {
+            Ok(v) => v,
+            Err(e) => return e.into_response(),
+        },
+        None => 0,
+    };
+    let discount = match &body.discount {
+        Some(amount) => match dto::to_unsigned_minor_units(amount, &convention) {
+            Ok(v) => v,
+            Err(e) => return e.into_response(),
+        },
+        None => 0,
+    };
+
     let order_agg = AggregateId::new();
     let order_id = PurchaseOrderId::new(order_agg);
 
@@ -55,6 +95,10 @@ pub async fn create_purchase_order(
         tenant_id: tenant.tenant_id(),
         order_id,
         supplier_id,
+        expected_at,
+        freight,
+        discount,
+        allocation_basis: body.allocation_basis,
         occurred_at: Utc::now(),
     });
 
@@ -63,7 +107,7 @@ pub async fn create_purchase_order(
         required: vec![Permission::new("purchases.orders.create")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let mut committed_total = 0usize;
@@ -85,11 +129,19 @@ pub async fn create_purchase_order(
             Ok(v) => v,
             Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid product id"),
         };
+        let unit_cost = match &l.unit_price {
+            Some(price) => match dto::to_unsigned_minor_units(price, &convention) {
+                Ok(v) => Some(v),
+                Err(e) => return e.into_response(),
+            },
+            None => None,
+        };
         let add_cmd = PurchaseOrderCommand::AddLine(AddPurchaseLine {
             tenant_id: tenant.tenant_id(),
             order_id,
             product_id: ProductId::new(prod_agg),
             quantity: l.quantity,
+            unit_cost,
             occurred_at: Utc::now(),
         });
         let add_auth = CmdAuth {
@@ -97,7 +149,7 @@ pub async fn create_purchase_order(
             required: vec![Permission::new("purchases.orders.add_line")],
         };
         if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &add_auth) {
-            return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+            return errors::command_denied_to_response(e);
         }
         let committed = match services.dispatch::<PurchaseOrder>(
             tenant.tenant_id(),
@@ -139,12 +191,25 @@ pub async fn add_purchase_order_line(
         Ok(v) => v,
         Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid product id"),
     };
+    let settings = services.tenant_settings().get(tenant.tenant_id());
+    let convention = match dto::currency_convention(&settings, None) {
+        Ok(c) => c,
+        Err(e) => return e.into_response(),
+    };
+    let unit_cost = match &body.unit_price {
+        Some(price) => match dto::to_unsigned_minor_units(price, &convention) {
+            Ok(v) => Some(v),
+            Err(e) => return e.into_response(),
+        },
+        None => None,
+    };
 
     let cmd = PurchaseOrderCommand::AddLine(AddPurchaseLine {
         tenant_id: tenant.tenant_id(),
         order_id,
         product_id: ProductId::new(prod_agg),
         quantity: body.quantity,
+        unit_cost,
         occurred_at: Utc::now(),
     });
 
@@ -153,7 +218,7 @@ pub async fn add_purchase_order_line(
         required: vec![Permission::new("purchases.orders.add_line")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<PurchaseOrder>(
@@ -191,7 +256,7 @@ pub async fn approve_purchase_order(
         required: vec![Permission::new("purchases.orders.approve")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<PurchaseOrder>(
@@ -229,7 +294,7 @@ pub async fn receive_purchase_order_goods(
         required: vec![Permission::new("purchases.orders.receive")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<PurchaseOrder>(
@@ -255,22 +320,103 @@ pub async fn get_purchase_order(
         Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid purchase order id"),
     };
     let order_id = PurchaseOrderId::new(agg);
-    match services.purchases_get(tenant.tenant_id(), &order_id) {
-        Some(rm) => (StatusCode::OK, Json(dto::purchase_order_to_json(rm))).into_response(),
-        None => errors::json_error(StatusCode::NOT_FOUND, "not_found", "purchase order not found"),
+    read_model_response(
+        services.purchases_get(tenant.tenant_id(), &order_id),
+        dto::purchase_order_to_json,
+        services.first_event(tenant.tenant_id(), agg),
+        "purchasing.order",
+        "purchase order",
+    )
+    .await
+}
+
+/// POST /purchases/orders/:id/match - 3-way match of a supplier invoice (request body) against
+/// the order and its receipts. Read-only: nothing is dispatched.
+pub async fn match_purchase_order(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Path(id): Path<String>,
+    Json(body): Json<dto::PurchaseMatchRequest>,
+) -> axum::response::Response {
+    let agg: AggregateId = match id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid purchase order id"),
+    };
+    let order_id = PurchaseOrderId::new(agg);
+    let Some(order) = services.purchases_get(tenant.tenant_id(), &order_id) else {
+        return errors::json_error(StatusCode::NOT_FOUND, "not_found", "purchase order not found");
+    };
+
+    let settings = services.tenant_settings().get(tenant.tenant_id());
+    let convention = match dto::currency_convention(&settings, body.currency.as_deref()) {
+        Ok(c) => c,
+        Err(e) => return e.into_response(),
+    };
+    let mut invoice = SupplierInvoice::default();
+    for l in &body.lines {
+        let product_agg: AggregateId = match l.product_id.parse() {
+            Ok(v) => v,
+            Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid product id"),
+        };
+        let unit_price = match dto::to_unsigned_minor_units(&l.unit_price, &convention) {
+            Ok(v) => v,
+            Err(e) => return e.into_response(),
+        };
+        invoice.lines.push(SupplierInvoiceLine {
+            product_id: ProductId::new(product_agg),
+            quantity: l.quantity,
+            unit_price,
+        });
     }
+
+    // Received quantities come from all of the order's GoodsReceived events.
+    let events = match services.get_all_aggregate_events(tenant.tenant_id(), agg).await {
+        Ok(events) => events,
+        Err(e) => return errors::json_error(StatusCode::INTERNAL_SERVER_ERROR, "query_failed", e.to_string()),
+    };
+    let received: Vec<_> = events
+        .into_iter()
+        .filter_map(|e| match serde_json::from_value::<PurchaseOrderEvent>(e.payload) {
+            Ok(PurchaseOrderEvent::GoodsReceived(r)) => Some(r.lines),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+
+    // Expected unit prices are the catalog base prices.
+    let expected_prices: HashMap<ProductId, u64> = order
+        .lines
+        .iter()
+        .filter_map(|l| {
+            let price = services.products_get(tenant.tenant_id(), &l.product_id)?.pricing.base_price?;
+            Some((l.product_id, price))
+        })
+        .collect();
+
+    let report = ThreeWayMatch::new(body.tolerances.unwrap_or_default()).check(
+        &order,
+        &received,
+        &invoice,
+        &expected_prices,
+    );
+    (StatusCode::OK, Json(report)).into_response()
 }
 
 pub async fn list_purchase_orders(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
+    query: ListQuery<PurchaseOrderListSpec>,
 ) -> axum::response::Response {
-    let items = services
-        .purchases_list(tenant.tenant_id())
-        .into_iter()
-        .map(dto::purchase_order_to_json)
-        .collect::<Vec<_>>();
-    (StatusCode::OK, Json(serde_json::json!({ "items": items }))).into_response()
+    let tenant_id = tenant.tenant_id();
+    let page = query.page_from(
+        dto::purchase_order_list_field,
+        |keep, order, start, limit| services.purchases_list_page(tenant_id, keep, order, start, limit),
+        dto::purchase_order_to_json,
+    );
+    match page {
+        Ok(body) => (StatusCode::OK, Json(body)).into_response(),
+        Err(e) => e.into_response(),
+    }
 }
 
 
@@ -64,7 +64,7 @@ pub async fn list_roles(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let registry = RbacRegistry::from_role_mapping(default_role_permissions);
@@ -86,7 +86,7 @@ pub async fn get_role(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let registry = RbacRegistry::from_role_mapping(default_role_permissions);
@@ -108,7 +108,7 @@ pub async fn list_permissions(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let registry = RbacRegistry::from_role_mapping(default_role_permissions);
@@ -130,7 +130,7 @@ pub async fn get_permission(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let registry = RbacRegistry::from_role_mapping(default_role_permissions);
@@ -184,7 +184,7 @@ pub async fn explain_user_authorization(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     // Parse user ID
@@ -16,10 +16,14 @@ use uuid::Uuid;
 
 use forgeerp_auth::admin;
 use forgeerp_core::TenantId;
-use forgeerp_infra::projections::replay::{ReplayHandle, ReplayProgress};
+use forgeerp_infra::admin_audit::{operation as audit_operation, AdminAuditLog};
+use forgeerp_infra::event_store::EventQuery;
+use forgeerp_infra::projections::replay::{
+    rebuild_projection, replay_projection, ApplyEnvelopeFn, ClearTenantFn, ReplayError, ReplayHandle, ReplayProgress,
+};
 
 use crate::app::{errors, services::AppServices};
-use crate::app::routes::common::CmdAuth;
+use crate::app::routes::common::{record_admin_action, CmdAuth};
 use crate::context::{PrincipalContext, TenantContext};
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -30,6 +34,8 @@ use crate::context::{PrincipalContext, TenantContext};
 #[derive(Clone, Default)]
 pub struct ReplayJobStore {
     jobs: Arc<RwLock<HashMap<Uuid, ReplayHandle>>>,
+    /// Latest rebuild job per (tenant, projection).
+    rebuilds: Arc<RwLock<HashMap<(TenantId, String), Uuid>>>,
 }
 
 impl ReplayJobStore {
@@ -52,6 +58,29 @@ impl ReplayJobStore {
     pub async fn remove(&self, job_id: &Uuid) {
         self.jobs.write().await.remove(job_id);
     }
+
+    /// Start a rebuild of the tenant's `projection` with `start` and track it as a job,
+    /// unless a rebuild of it is still running (`None`).
+    pub async fn start_rebuild(
+        &self,
+        tenant_id: TenantId,
+        projection: &str,
+        start: impl FnOnce() -> ReplayHandle,
+    ) -> Option<(Uuid, ReplayHandle)> {
+        let key = (tenant_id, projection.to_string());
+        let mut rebuilds = self.rebuilds.write().await;
+        if let Some(running) = rebuilds.get(&key)
+            && let Some(handle) = self.get(running).await
+            && !handle.progress().await.is_complete
+        {
+            return None;
+        }
+        let job_id = Uuid::now_v7();
+        let handle = start();
+        self.insert(job_id, handle.clone()).await;
+        rebuilds.insert(key, job_id);
+        Some((job_id, handle))
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -84,6 +113,7 @@ pub fn router() -> Router {
     Router::new()
         .route("/projections/:projection", post(start_replay))
         .route("/projections", post(start_all_replays))
+        .route("/projection/:projection", post(start_rebuild))
         .route("/jobs/:job_id", get(get_replay_status))
         .route("/jobs/:job_id", axum::routing::delete(cancel_replay))
         .route("/jobs", get(list_replays))
@@ -110,77 +140,41 @@ pub async fn start_replay(
         required: vec![admin::USER_READ.clone()], // Using admin permission for now
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let dry_run = query.dry_run.unwrap_or(false);
     let job_id = Uuid::now_v7();
 
-    // Map projection name to configuration
-    let (aggregate_types, apply_fn, clear_fn) = match projection_name.as_str() {
-        "inventory" => (
-            vec!["inventory.item".to_string()],
-            create_inventory_apply_fn(&services, tenant.tenant_id()),
-            create_inventory_clear_fn(&services, tenant.tenant_id()),
-        ),
-        "products" => (
-            vec!["products.product".to_string()],
-            create_products_apply_fn(&services, tenant.tenant_id()),
-            create_products_clear_fn(&services, tenant.tenant_id()),
-        ),
-        "parties" => (
-            vec!["parties.party".to_string()],
-            create_parties_apply_fn(&services, tenant.tenant_id()),
-            create_parties_clear_fn(&services, tenant.tenant_id()),
-        ),
-        "sales" => (
-            vec!["sales.order".to_string()],
-            create_sales_apply_fn(&services, tenant.tenant_id()),
-            create_sales_clear_fn(&services, tenant.tenant_id()),
-        ),
-        "invoices" => (
-            vec!["invoicing.invoice".to_string()],
-            create_invoices_apply_fn(&services, tenant.tenant_id()),
-            create_invoices_clear_fn(&services, tenant.tenant_id()),
-        ),
-        "purchases" => (
-            vec!["purchasing.order".to_string()],
-            create_purchases_apply_fn(&services, tenant.tenant_id()),
-            create_purchases_clear_fn(&services, tenant.tenant_id()),
-        ),
-        _ => {
-            return errors::json_error(
-                StatusCode::BAD_REQUEST,
-                "invalid_projection",
-                format!("Unknown projection: {}", projection_name),
-            );
-        }
+    let Some((aggregate_types, apply_fn, clear_fn)) =
+        projection_config(&services, &projection_name, tenant.tenant_id())
+    else {
+        return errors::json_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_projection",
+            format!("Unknown projection: {}", projection_name),
+        );
     };
 
+    let replay = ProjectionReplay {
+        projection: &projection_name,
+        aggregate_types,
+        apply_fn,
+        clear_fn,
+        dry_run,
+    };
     // Start replay - match on services type to get the right event store
     let handle_result = match &*services {
         AppServices::InMemory { event_store, .. } => {
-            forgeerp_infra::projections::replay::replay_projection(
-                event_store.clone(),
-                tenant.tenant_id(),
-                aggregate_types,
-                apply_fn,
-                clear_fn,
-                dry_run,
-            )
-            .await
+            replay
+                .start(event_store.clone(), services.admin_audit(), &tenant, &principal)
+                .await
         }
         #[cfg(feature = "redis")]
         AppServices::Persistent { event_store, .. } => {
-            forgeerp_infra::projections::replay::replay_projection(
-                event_store.clone(),
-                tenant.tenant_id(),
-                aggregate_types,
-                apply_fn,
-                clear_fn,
-                dry_run,
-            )
-            .await
+            replay
+                .start(event_store.clone(), services.admin_audit(), &tenant, &principal)
+                .await
         }
     };
 
@@ -208,6 +202,176 @@ pub async fn start_replay(
     }
 }
 
+/// Aggregate types, apply and clear functions of the projection called `projection_name`.
+fn projection_config(
+    services: &AppServices,
+    projection_name: &str,
+    tenant_id: TenantId,
+) -> Option<(Vec<String>, ApplyEnvelopeFn, ClearTenantFn)> {
+    let config = match projection_name {
+        "inventory" => (
+            vec!["inventory.item".to_string()],
+            create_inventory_apply_fn(services, tenant_id),
+            create_inventory_clear_fn(services, tenant_id),
+        ),
+        "products" => (
+            vec!["products.product".to_string()],
+            create_products_apply_fn(services, tenant_id),
+            create_products_clear_fn(services, tenant_id),
+        ),
+        "parties" => (
+            vec!["parties.party".to_string()],
+            create_parties_apply_fn(services, tenant_id),
+            create_parties_clear_fn(services, tenant_id),
+        ),
+        "sales" => (
+            vec!["sales.order".to_string()],
+            create_sales_apply_fn(services, tenant_id),
+            create_sales_clear_fn(services, tenant_id),
+        ),
+        "invoices" => (
+            vec!["invoicing.invoice".to_string()],
+            create_invoices_apply_fn(services, tenant_id),
+            create_invoices_clear_fn(services, tenant_id),
+        ),
+        "purchases" => (
+            vec!["purchasing.order".to_string()],
+            create_purchases_apply_fn(services, tenant_id),
+            create_purchases_clear_fn(services, tenant_id),
+        ),
+        "ledger" => (
+            vec!["accounting.ledger".to_string()],
+            create_ledger_apply_fn(services, tenant_id),
+            create_ledger_clear_fn(services, tenant_id),
+        ),
+        "users" => (
+            vec!["auth.user".to_string()],
+            create_users_apply_fn(services, tenant_id),
+            create_users_clear_fn(services, tenant_id),
+        ),
+        _ => return None,
+    };
+    Some(config)
+
+}
+
+/// POST /admin/replay/projection/:projection
+///
+/// Rebuild one projection for the tenant: clear its read model and cursors, then apply
+/// the tenant's events again in global order (`read_all`). Progress is reported like a
+/// replay (`GET /admin/replay/jobs/:job_id`); a second rebuild of the same projection
+/// while one is running is rejected with 409.
+pub async fn start_rebuild(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(job_store): Extension<ReplayJobStore>,
+    Extension(tenant): Extension<TenantContext>,
+    Extension(principal): Extension<PrincipalContext>,
+    Path(projection_name): Path<String>,
+) -> axum::response::Response {
+    let cmd_auth = CmdAuth::<()> {
+        inner: (),
+        required: vec![admin::USER_READ.clone()],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
+    }
+
+    let tenant_id = tenant.tenant_id();
+    let Some((aggregate_types, apply_fn, clear_fn)) = projection_config(&services, &projection_name, tenant_id)
+    else {
+        return errors::json_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_projection",
+            format!("Unknown projection: {}", projection_name),
+        );
+    };
+
+    let types = aggregate_types.clone();
+    let started = job_store
+        .start_rebuild(tenant_id, &projection_name, || match &*services {
+            AppServices::InMemory { event_store, .. } => {
+                rebuild_projection(event_store.clone(), tenant_id, types, apply_fn, clear_fn)
+            }
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { event_store, .. } => {
+                rebuild_projection(event_store.clone(), tenant_id, types, apply_fn, clear_fn)
+            }
+        })
+        .await;
+    let Some((job_id, _)) = started else {
+        return errors::json_error(
+            StatusCode::CONFLICT,
+            "rebuild_in_progress",
+            format!("A rebuild of projection {} is already running", projection_name),
+        );
+    };
+
+    record_admin_action(
+        services.admin_audit(),
+        &tenant,
+        &principal,
+        audit_operation::PROJECTION_RESET,
+        &projection_name,
+        serde_json::json!({ "aggregate_types": aggregate_types, "job_id": job_id }),
+    );
+    (
+        StatusCode::ACCEPTED,
+        Json(ReplayResponse {
+            job_id: job_id.to_string(),
+            message: format!("Rebuild started for projection: {}", projection_name),
+        }),
+    )
+        .into_response()
+}
+
+/// A projection replay requested by an admin.
+struct ProjectionReplay<'a> {
+    projection: &'a str,
+    aggregate_types: Vec<String>,
+    apply_fn: ApplyEnvelopeFn,
+    clear_fn: ClearTenantFn,
+    dry_run: bool,
+}
+
+impl ProjectionReplay<'_> {
+    /// Start the replay and record it in the admin audit log (a real replay resets the
+    /// projection; a dry run leaves it untouched).
+    async fn start<Q>(
+        self,
+        event_store: Arc<Q>,
+        audit: &AdminAuditLog,
+        tenant: &TenantContext,
+        principal: &PrincipalContext,
+    ) -> Result<ReplayHandle, ReplayError>
+    where
+        Q: EventQuery + Send + Sync + 'static,
+    {
+        let handle = replay_projection(
+            event_store,
+            tenant.tenant_id(),
+            self.aggregate_types.clone(),
+            self.apply_fn,
+            self.clear_fn,
+            self.dry_run,
+        )
+        .await?;
+        let operation = if self.dry_run {
+            audit_operation::PROJECTION_REPLAY_DRY_RUN
+        } else {
+            audit_operation::PROJECTION_RESET
+        };
+        record_admin_action(
+            audit,
+            tenant,
+            principal,
+            operation,
+            self.projection,
+            serde_json::json!({ "aggregate_types": self.aggregate_types }),
+        );
+        Ok(handle)
+    }
+}
+
 /// POST /admin/replay/projections
 /// 
 /// Start replaying all projections for the tenant.
@@ -223,7 +387,7 @@ pub async fn start_all_replays(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     // For now, return not implemented
@@ -250,7 +414,7 @@ pub async fn get_replay_status(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let job_id = match job_id_str.parse::<Uuid>() {
@@ -280,6 +444,7 @@ pub async fn get_replay_status(
 /// 
 /// Cancel a running replay job.
 pub async fn cancel_replay(
+    Extension(services): Extension<Arc<AppServices>>,
     Extension(job_store): Extension<ReplayJobStore>,
     Extension(tenant): Extension<TenantContext>,
     Extension(principal): Extension<PrincipalContext>,
@@ -291,7 +456,7 @@ pub async fn cancel_replay(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let job_id = match job_id_str.parse::<Uuid>() {
@@ -304,6 +469,14 @@ pub async fn cancel_replay(
     match job_store.get(&job_id).await {
         Some(handle) => {
             handle.cancel();
+            record_admin_action(
+                services.admin_audit(),
+                &tenant,
+                &principal,
+                audit_operation::PROJECTION_REPLAY_CANCEL,
+                job_id.to_string(),
+                serde_json::Value::Null,
+            );
             // Optionally remove from store after cancellation
             // job_store.remove(&job_id).await;
             (
@@ -333,7 +506,7 @@ pub async fn list_replays(
         required: vec![admin::USER_READ.clone()],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let job_ids = job_store.list().await;
@@ -623,3 +796,154 @@ fn create_purchases_clear_fn(services: &AppServices, _tenant_id: TenantId) -> fo
     })
 }
 
+// The ledger rebuild covers both read models fed by the ledger stream.
+fn create_ledger_apply_fn(services: &AppServices, _tenant_id: TenantId) -> forgeerp_infra::projections::replay::ApplyEnvelopeFn {
+    let services_clone = services.clone();
+    Arc::new(move |envelope| {
+        match &services_clone {
+            AppServices::InMemory { ledger_projection, trial_balance_projection, .. } => {
+                ledger_projection.apply_envelope(envelope).map_err(|e| format!("{}", e))?;
+                trial_balance_projection.apply_envelope(envelope).map_err(|e| format!("{}", e))
+            }
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { ledger_projection, trial_balance_projection, .. } => {
+                ledger_projection.apply_envelope(envelope).map_err(|e| format!("{}", e))?;
+                trial_balance_projection.apply_envelope(envelope).map_err(|e| format!("{}", e))
+            }
+        }
+    })
+}
+
+fn create_ledger_clear_fn(services: &AppServices, _tenant_id: TenantId) -> forgeerp_infra::projections::replay::ClearTenantFn {
+    let services_clone = services.clone();
+    Arc::new(move |tenant_id| {
+        use forgeerp_core::AggregateId;
+        use forgeerp_events::EventEnvelope;
+        use serde_json::json;
+
+        let dummy_envelope = EventEnvelope::new(
+            uuid::Uuid::now_v7(),
+            tenant_id,
+            AggregateId::from_uuid(uuid::Uuid::now_v7()),
+            "accounting.ledger".to_string(),
+            1,
+            json!({}),
+        );
+
+        match &services_clone {
+            AppServices::InMemory { ledger_projection, trial_balance_projection, .. } => {
+                let _ = ledger_projection.rebuild_from_scratch(std::iter::once(dummy_envelope.clone()));
+                let _ = trial_balance_projection.rebuild_from_scratch(std::iter::once(dummy_envelope));
+            }
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { ledger_projection, trial_balance_projection, .. } => {
+                let _ = ledger_projection.rebuild_from_scratch(std::iter::once(dummy_envelope.clone()));
+                let _ = trial_balance_projection.rebuild_from_scratch(std::iter::once(dummy_envelope));
+            }
+        }
+    })
+}
+
+fn create_users_apply_fn(services: &AppServices, _tenant_id: TenantId) -> forgeerp_infra::projections::replay::ApplyEnvelopeFn {
+    let services_clone = services.clone();
+    Arc::new(move |envelope| {
+        match &services_clone {
+            AppServices::InMemory { users_projection, .. } => {
+                users_projection.apply_envelope(envelope).map_err(|e| format!("{}", e))
+            }
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { users_projection, .. } => {
+                users_projection.apply_envelope(envelope).map_err(|e| format!("{}", e))
+            }
+        }
+    })
+}
+
+// The users projection keeps no cursors; clearing its store is the whole reset.
+fn create_users_clear_fn(services: &AppServices, _tenant_id: TenantId) -> forgeerp_infra::projections::replay::ClearTenantFn {
+    let services_clone = services.clone();
+    Arc::new(move |tenant_id| match &services_clone {
+        AppServices::InMemory { users_projection, .. } => users_projection.clear_tenant(tenant_id),
+        #[cfg(feature = "redis")]
+        AppServices::Persistent { users_projection, .. } => users_projection.clear_tenant(tenant_id),
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use forgeerp_auth::PrincipalId;
+    use forgeerp_infra::admin_audit::{AdminAuditFilter, AdminAuditRetention};
+    use forgeerp_infra::event_store::InMemoryEventStore;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn projection_reset_is_recorded_in_the_admin_audit() {
+        let audit = AdminAuditLog::new(AdminAuditRetention::default());
+        let tenant = TenantContext::new(TenantId::new());
+        let principal = PrincipalContext::new(PrincipalId::new(), vec![forgeerp_auth::Role::new("admin")]);
+        let cleared = Arc::new(AtomicUsize::new(0));
+        let clear_count = cleared.clone();
+
+        let replay = ProjectionReplay {
+            projection: "inventory",
+            aggregate_types: vec!["inventory.item".to_string()],
+            apply_fn: Arc::new(|_| Ok(())),
+            clear_fn: Arc::new(move |_| {
+                clear_count.fetch_add(1, Ordering::SeqCst);
+            }),
+            dry_run: false,
+        };
+        let handle = replay
+            .start(Arc::new(InMemoryEventStore::new()), &audit, &tenant, &principal)
+            .await
+            .unwrap();
+        handle.wait_for_completion().await.unwrap();
+        assert_eq!(cleared.load(Ordering::SeqCst), 1);
+
+        let resets = audit.list(
+            tenant.tenant_id(),
+            &AdminAuditFilter {
+                operation: Some(audit_operation::PROJECTION_RESET.to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(resets.len(), 1);
+        assert_eq!(resets[0].actor, principal.principal_id());
+        assert_eq!(resets[0].target, "inventory");
+        assert_eq!(resets[0].actor_home_tenant_id, None);
+        // Other tenants do not see it.
+        assert!(audit.list(TenantId::new(), &AdminAuditFilter::default()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_running_rebuild_blocks_only_the_same_tenant_and_projection() {
+        let jobs = ReplayJobStore::new();
+        let tenant_id = TenantId::new();
+        let start = |tenant_id: TenantId, clear: ClearTenantFn| {
+            move || {
+                rebuild_projection(Arc::new(InMemoryEventStore::new()), tenant_id, Vec::new(), Arc::new(|_| Ok(())), clear)
+            }
+        };
+        let noop = || -> ClearTenantFn { Arc::new(|_| {}) };
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        let released = std::sync::Mutex::new(released);
+        let blocked: ClearTenantFn = Arc::new(move |_| {
+            let _ = released.lock().unwrap().recv();
+        });
+
+        let (_, running) = jobs.start_rebuild(tenant_id, "inventory", start(tenant_id, blocked)).await.unwrap();
+        assert!(jobs.start_rebuild(tenant_id, "inventory", start(tenant_id, noop())).await.is_none());
+        assert!(jobs.start_rebuild(tenant_id, "products", start(tenant_id, noop())).await.is_some());
+        let other = TenantId::new();
+        assert!(jobs.start_rebuild(other, "inventory", start(other, noop())).await.is_some());
+
+        release.send(()).unwrap();
+        running.wait_for_completion().await.unwrap();
+        assert!(jobs.start_rebuild(tenant_id, "inventory", start(tenant_id, noop())).await.is_some());
+        assert_eq!(jobs.list().await.len(), 4);
+    }
+}
@@ -4,25 +4,37 @@ use axum::{
     extract::{Extension, Path},
     http::StatusCode,
     response::IntoResponse,
-    routing::{get, post},
+    routing::{delete, get, post},
     Json, Router,
 };
 use chrono::Utc;
 
+use forgeerp_ai::{AiInsightKind, SalesForecast};
 use forgeerp_auth::Permission;
 use forgeerp_core::AggregateId;
 use forgeerp_products::ProductId;
 use forgeerp_sales::{
-    AddLine as AddSalesLine, ConfirmOrder, CreateSalesOrder, MarkInvoiced, SalesOrder, SalesOrderCommand,
-    SalesOrderId,
+    AddLine as AddSalesLine, CancelOrder, RemoveLine, ConfirmOrder, CreateSalesOrder, MarkInvoiced, SalesOrder,
+    SalesOrderCommand, SalesOrderId,
 };
 
+use crate::app::query::{ListQuery, ListSpec};
 use crate::app::{dto, errors};
-use crate::app::routes::common::CmdAuth;
+use crate::app::routes::common::{read_model_response, CmdAuth};
 use crate::app::services::AppServices;
 
+/// Sort fields and filters accepted by `GET` on the sales orders list.
+pub struct SalesOrderListSpec;
+
+impl ListSpec for SalesOrderListSpec {
+    const SORT_FIELDS: &'static [&'static str] = &["id", "status"];
+    const FILTERS: &'static [&'static str] = &["status"];
+}
+
 pub fn router() -> Router {
-    Router::new().nest("/orders", orders_router())
+    Router::new()
+        .nest("/orders", orders_router())
+        .route("/forecast", get(get_sales_forecast))
 }
 
 fn orders_router() -> Router {
@@ -30,8 +42,10 @@ fn orders_router() -> Router {
         .route("/", post(create_sales_order).get(list_sales_orders))
         .route("/:id", get(get_sales_order))
         .route("/:id/lines", post(add_sales_order_line))
+        .route("/:id/lines/:line_no", delete(remove_sales_order_line))
         .route("/:id/confirm", post(confirm_sales_order))
         .route("/:id/mark-invoiced", post(mark_sales_order_invoiced))
+        .route("/:id/cancel", post(cancel_sales_order))
 }
 
 pub async fn create_sales_order(
@@ -53,7 +67,7 @@ pub async fn create_sales_order(
         required: vec![Permission::new("sales.orders.create")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<SalesOrder>(
@@ -88,12 +102,20 @@ pub async fn add_sales_order_line(
         Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid product id"),
     };
 
+    let settings = services.tenant_settings().get(tenant.tenant_id());
+    let unit_price = match dto::currency_convention(&settings, None)
+        .and_then(|c| dto::to_unsigned_minor_units(&body.unit_price, &c))
+    {
+        Ok(v) => v,
+        Err(e) => return e.into_response(),
+    };
+
     let cmd = SalesOrderCommand::AddLine(AddSalesLine {
         tenant_id: tenant.tenant_id(),
         order_id,
         product_id: ProductId::new(product_agg),
         quantity: body.quantity,
-        unit_price: body.unit_price,
+        unit_price,
         occurred_at: Utc::now(),
     });
 
@@ -102,7 +124,49 @@ pub async fn add_sales_order_line(
         required: vec![Permission::new("sales.orders.add_line")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
+    }
+
+    let committed = match services.dispatch::<SalesOrder>(
+        tenant.tenant_id(),
+        agg,
+        "sales.order",
+        cmd_auth.inner,
+        |_t, aggregate_id| SalesOrder::empty(SalesOrderId::new(aggregate_id)),
+    ) {
+        Ok(c) => c,
+        Err(e) => return errors::dispatch_error_to_response(e),
+    };
+
+    (StatusCode::OK, Json(serde_json::json!({"id": agg.to_string(), "events_committed": committed.len()}))).into_response()
+}
+
+/// Remove a line from a draft order.
+pub async fn remove_sales_order_line(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
+    Path((id, line_no)): Path<(String, u32)>,
+) -> axum::response::Response {
+    let agg: AggregateId = match id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid order id"),
+    };
+    let order_id = SalesOrderId::new(agg);
+
+    let cmd = SalesOrderCommand::RemoveLine(RemoveLine {
+        tenant_id: tenant.tenant_id(),
+        order_id,
+        line_no,
+        occurred_at: Utc::now(),
+    });
+
+    let cmd_auth = CmdAuth {
+        inner: cmd,
+        required: vec![Permission::new("sales.orders.remove_line")],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<SalesOrder>(
@@ -142,7 +206,7 @@ pub async fn confirm_sales_order(
         required: vec![Permission::new("sales.orders.confirm")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<SalesOrder>(
@@ -182,7 +246,7 @@ pub async fn mark_sales_order_invoiced(
         required: vec![Permission::new("sales.orders.mark_invoiced")],
     };
     if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
-        return errors::json_error(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+        return errors::command_denied_to_response(e);
     }
 
     let committed = match services.dispatch::<SalesOrder>(
@@ -199,32 +263,116 @@ pub async fn mark_sales_order_invoiced(
     (StatusCode::OK, Json(serde_json::json!({"id": agg.to_string(), "events_committed": committed.len()}))).into_response()
 }
 
-pub async fn get_sales_order(
+/// Cancel a draft or confirmed order; stock reserved for it is released.
+pub async fn cancel_sales_order(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
+    Extension(principal): Extension<crate::context::PrincipalContext>,
     Path(id): Path<String>,
+    body: Option<Json<dto::CancelSalesOrderRequest>>,
 ) -> axum::response::Response {
     let agg: AggregateId = match id.parse() {
         Ok(v) => v,
         Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid order id"),
     };
     let order_id = SalesOrderId::new(agg);
-    match services.sales_get(tenant.tenant_id(), &order_id) {
-        Some(rm) => (StatusCode::OK, Json(dto::sales_order_to_json(rm))).into_response(),
-        None => errors::json_error(StatusCode::NOT_FOUND, "not_found", "sales order not found"),
+
+    let cmd = SalesOrderCommand::CancelOrder(CancelOrder {
+        tenant_id: tenant.tenant_id(),
+        order_id,
+        reason: body.and_then(|Json(b)| b.reason),
+        occurred_at: Utc::now(),
+    });
+
+    let cmd_auth = CmdAuth {
+        inner: cmd,
+        required: vec![Permission::new("sales.orders.cancel")],
+    };
+    if let Err(e) = crate::authz::authorize_command(&tenant, &principal, &cmd_auth) {
+        return errors::command_denied_to_response(e);
     }
+
+    let committed = match services.dispatch::<SalesOrder>(
+        tenant.tenant_id(),
+        agg,
+        "sales.order",
+        cmd_auth.inner,
+        |_t, aggregate_id| SalesOrder::empty(SalesOrderId::new(aggregate_id)),
+    ) {
+        Ok(c) => c,
+        Err(e) => return errors::dispatch_error_to_response(e),
+    };
+
+    (StatusCode::OK, Json(serde_json::json!({"id": agg.to_string(), "events_committed": committed.len()}))).into_response()
+}
+
+pub async fn get_sales_order(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    let agg: AggregateId = match id.parse() {
+        Ok(v) => v,
+        Err(_) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_id", "invalid order id"),
+    };
+    let order_id = SalesOrderId::new(agg);
+    read_model_response(
+        services.sales_get(tenant.tenant_id(), &order_id),
+        dto::sales_order_to_json,
+        services.first_event(tenant.tenant_id(), agg),
+        "sales.order",
+        "sales order",
+    )
+    .await
 }
 
 pub async fn list_sales_orders(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
+    query: ListQuery<SalesOrderListSpec>,
 ) -> axum::response::Response {
-    let items = services
-        .sales_list(tenant.tenant_id())
-        .into_iter()
-        .map(dto::sales_order_to_json)
-        .collect::<Vec<_>>();
-    (StatusCode::OK, Json(serde_json::json!({ "items": items }))).into_response()
+    let tenant_id = tenant.tenant_id();
+    let page = query.page_from(
+        dto::sales_order_list_field,
+        |keep, order, start, limit| services.sales_list_page(tenant_id, keep, order, start, limit),
+        dto::sales_order_to_json,
+    );
+    match page {
+        Ok(body) => (StatusCode::OK, Json(body)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// GET /sales/forecast - Latest demand forecast per product (AI insight, read-only)
+pub async fn get_sales_forecast(
+    Extension(services): Extension<Arc<AppServices>>,
+    Extension(tenant): Extension<crate::context::TenantContext>,
+) -> axum::response::Response {
+    let insights = match services
+        .ai_sink()
+        .insights(tenant.tenant_id(), AiInsightKind::SalesForecast)
+        .await
+    {
+        Ok(insights) => insights,
+        Err(e) => return errors::ai_insight_store_error_to_response(e),
+    };
+
+    let forecasts: Vec<SalesForecast> = insights
+        .iter()
+        .filter_map(|r| r.as_sales_forecasts())
+        .flatten()
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "kind": "insights",
+            "insight_type": "sales.forecast",
+            "count": forecasts.len(),
+            "forecasts": forecasts,
+        })),
+    )
+        .into_response()
 }
 
 
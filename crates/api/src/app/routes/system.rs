@@ -1,18 +1,164 @@
+use std::fmt::Write as _;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{
-    extract::Extension,
+    extract::{Extension, Query},
     http::StatusCode,
-    response::{sse::Event as SseEvent, IntoResponse},
+    response::IntoResponse,
     Json,
 };
+use serde::Deserialize;
 
+use forgeerp_infra::event_bus::{BreakerStats, PublishBreaker};
+use forgeerp_infra::workers::{LivenessReport, TaskHealth, TaskRegistry};
+
+use crate::app::errors;
 use crate::app::services::{self, AppServices};
 
-pub async fn health() -> StatusCode {
+/// Liveness: 200 while the process serves requests, whatever the state of its dependencies.
+pub async fn livez() -> StatusCode {
     StatusCode::OK
 }
 
+/// Dependency health: 503 unless the event store and the event bus both answer.
+pub async fn health(Extension(services): Extension<Arc<AppServices>>) -> impl IntoResponse {
+    dependency_report(&services.check_dependencies().await)
+}
+
+fn dependency_report(dependencies: &[services::DependencyStatus]) -> (StatusCode, Json<serde_json::Value>) {
+    let healthy = dependencies.iter().all(|d| d.healthy);
+    let (status, label) = if healthy {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    };
+    let dependencies: serde_json::Map<String, serde_json::Value> = dependencies
+        .iter()
+        .map(|d| {
+            let mut entry = serde_json::json!({ "status": if d.healthy { "up" } else { "down" } });
+            if let Some(error) = &d.error {
+                entry["error"] = serde_json::Value::String(error.clone());
+            }
+            (d.name.to_string(), entry)
+        })
+        .collect();
+    (
+        status,
+        Json(serde_json::json!({
+            "status": label,
+            "dependencies": dependencies,
+        })),
+    )
+}
+
+/// How long a background task may go without a heartbeat before it counts as stalled
+/// (`TASK_STALL_AFTER_SECS`, default 30).
+fn stall_after() -> Duration {
+    let secs = std::env::var("TASK_STALL_AFTER_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(30);
+    Duration::from_secs(secs)
+}
+
+fn liveness(tasks: &TaskRegistry) -> LivenessReport {
+    tasks.report(chrono::Utc::now(), stall_after())
+}
+
+/// Readiness: 503 once any background task stalled or exited unexpectedly.
+pub async fn ready(Extension(tasks): Extension<TaskRegistry>) -> impl IntoResponse {
+    let report = liveness(&tasks);
+    let (status, label) = if report.is_healthy() {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    };
+    (
+        status,
+        Json(serde_json::json!({
+            "status": label,
+            "tasks": report.tasks,
+        })),
+    )
+}
+
+/// Background task liveness and event bus breaker state in the Prometheus text format.
+pub async fn metrics(
+    Extension(tasks): Extension<TaskRegistry>,
+    Extension(breaker): Extension<Arc<PublishBreaker>>,
+) -> impl IntoResponse {
+    let mut body = render_metrics(&liveness(&tasks));
+    body.push_str(&render_breaker_metrics(&breaker.stats()));
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        body,
+    )
+}
+
+fn render_metrics(report: &LivenessReport) -> String {
+    let mut out = String::new();
+    let mut family = |name: &str, kind: &str, help: &str, value: &dyn Fn(&forgeerp_infra::workers::TaskStatus) -> String| {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        for t in &report.tasks {
+            let _ = writeln!(out, "{name}{{task=\"{}\"}} {}", t.name, value(t));
+        }
+    };
+    family("forgeerp_task_up", "gauge", "1 while the task is running and not stalled.", &|t| {
+        u8::from(t.health == TaskHealth::Running).to_string()
+    });
+    family(
+        "forgeerp_task_last_heartbeat_seconds",
+        "gauge",
+        "Unix time of the task's last heartbeat.",
+        &|t| t.last_heartbeat.timestamp().to_string(),
+    );
+    family(
+        "forgeerp_task_last_processed_seconds",
+        "gauge",
+        "Unix time the task last processed a message (0 if never).",
+        &|t| t.last_processed.map_or(0, |at| at.timestamp()).to_string(),
+    );
+    family("forgeerp_task_processed_total", "counter", "Messages processed by the task.", &|t| {
+        t.processed.to_string()
+    });
+    family("forgeerp_task_failures_total", "counter", "Messages the task failed to process.", &|t| {
+        t.failures.to_string()
+    });
+    out
+}
+
+fn render_breaker_metrics(stats: &BreakerStats) -> String {
+    let mut out = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: u64| {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        let _ = writeln!(out, "{name} {value}");
+    };
+    metric(
+        "forgeerp_bus_breaker_state",
+        "gauge",
+        "Event bus publish breaker: 0 closed, 1 open, 2 half-open.",
+        u64::from(stats.state.as_gauge()),
+    );
+    metric(
+        "forgeerp_bus_breaker_consecutive_failures",
+        "gauge",
+        "Bus publishes failed in a row.",
+        u64::from(stats.consecutive_failures),
+    );
+    metric("forgeerp_bus_breaker_trips_total", "counter", "Times the publish breaker opened.", stats.trips);
+    metric("forgeerp_bus_publish_timeouts_total", "counter", "Bus publishes that timed out.", stats.timeouts);
+    metric(
+        "forgeerp_bus_publish_rejected_total",
+        "counter",
+        "Bus publishes refused while the breaker was open.",
+        stats.rejected,
+    );
+    out
+}
+
 pub async fn whoami(
     axum::extract::Extension(tenant): axum::extract::Extension<crate::context::TenantContext>,
     axum::extract::Extension(principal): axum::extract::Extension<crate::context::PrincipalContext>,
@@ -24,11 +170,97 @@ pub async fn whoami(
     }))
 }
 
+/// Longest accepted `coalesce_ms` window.
+const MAX_COALESCE_MS: u64 = 10_000;
+
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    /// Batch updates per topic within this many milliseconds (unset: one message per update).
+    pub coalesce_ms: Option<u64>,
+    /// `latest` (default) sends the most recent payload; `list` sends every payload.
+    pub coalesce: Option<String>,
+}
+
 pub async fn stream(
     Extension(services): Extension<Arc<AppServices>>,
     Extension(tenant): Extension<crate::context::TenantContext>,
-) -> axum::response::Sse<impl tokio_stream::Stream<Item = Result<SseEvent, std::convert::Infallible>>> {
-    services::tenant_sse_stream(services, tenant.tenant_id())
+    Query(query): Query<StreamQuery>,
+) -> axum::response::Response {
+    let mode = match query.coalesce.as_deref() {
+        None | Some("latest") => services::CoalesceMode::Latest,
+        Some("list") => services::CoalesceMode::List,
+        Some(other) => {
+            return errors::json_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_query",
+                format!("coalesce must be `latest` or `list`, got `{other}`"),
+            );
+        }
+    };
+    match query.coalesce_ms {
+        None => services::tenant_sse_stream(services, tenant.tenant_id()).into_response(),
+        Some(ms) if (1..=MAX_COALESCE_MS).contains(&ms) => {
+            let coalescing = services::Coalescing {
+                window: Duration::from_millis(ms),
+                mode,
+            };
+            services::tenant_sse_stream_coalesced(services, tenant.tenant_id(), coalescing).into_response()
+        }
+        Some(_) => errors::json_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_query",
+            format!("coalesce_ms must be between 1 and {MAX_COALESCE_MS}"),
+        ),
+    }
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_mark_exited_task_down() {
+        let tasks = TaskRegistry::new();
+        tasks.register("projections").processed(true);
+        tasks.register("ledger_posting").exited("subscription closed");
+
+        let text = render_metrics(&liveness(&tasks));
+        assert!(text.contains("forgeerp_task_up{task=\"projections\"} 1"));
+        assert!(text.contains("forgeerp_task_up{task=\"ledger_posting\"} 0"));
+        assert!(text.contains("forgeerp_task_processed_total{task=\"projections\"} 1"));
+    }
+
+    #[test]
+    fn health_is_unavailable_when_any_dependency_is_down() {
+        let up = [
+            services::DependencyStatus::healthy("event_store"),
+            services::DependencyStatus::healthy("event_bus"),
+        ];
+        let (status, Json(body)) = dependency_report(&up);
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["dependencies"]["event_bus"]["status"], "up");
+        assert!(body["dependencies"]["event_bus"].get("error").is_none());
+
+        let bus_down = [
+            services::DependencyStatus::healthy("event_store"),
+            services::DependencyStatus::from_result("event_bus", Err("connection refused")),
+        ];
+        let (status, Json(body)) = dependency_report(&bus_down);
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "unavailable");
+        assert_eq!(body["dependencies"]["event_store"]["status"], "up");
+        assert_eq!(body["dependencies"]["event_bus"]["status"], "down");
+        assert_eq!(body["dependencies"]["event_bus"]["error"], "connection refused");
+    }
+
+    #[test]
+    fn metrics_expose_the_publish_breaker() {
+        let breaker = PublishBreaker::new(Default::default());
+        let text = render_breaker_metrics(&breaker.stats());
+        assert!(text.contains("forgeerp_bus_breaker_state 0"));
+        assert!(text.contains("forgeerp_bus_breaker_trips_total 0"));
+    }
+}
@@ -1,45 +1,167 @@
 use std::{
-    collections::HashMap,
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
     convert::Infallible,
     sync::{Arc, Mutex},
     time::Duration,
 };
 
 use axum::response::sse::{Event as SseEvent, KeepAlive, Sse};
-use forgeerp_ai::AiResult;
+use forgeerp_ai::{AiInsightKind, AiResult, AiUsageMeter, RateLimit};
 use forgeerp_core::{AggregateId, DomainError, TenantId};
 use forgeerp_events::{EventBus, EventEnvelope, InMemoryEventBus};
-use forgeerp_auth::UserId;
+use forgeerp_auth::{PrincipalId, TenantMembership, UserId};
 use forgeerp_infra::{
-    ai::{AiInsightSink, InventoryAnomalyRunner, InventoryAnomalyRunnerHandle},
-    command_dispatcher::{CommandDispatcher, DispatchError},
-    event_store::{EventFilter, EventQuery, EventQueryResult, InMemoryEventStore, Pagination, StoredEvent},
+    admin_audit::{AdminAuditLog, AdminAuditRetention},
+    rejected_commands::RejectedCommandLog,
+    idempotency::IdempotencyLog,
+    ai::{
+        upsert_insight, AiBackendConfig, AiInsightSink, AiInsightStoreError, AiRunnerHandle, AnomalyConfigs,
+        InventoryAnomalyRunner,
+        InventoryAnomalyRunnerHandle,
+        InventorySalesReader, ReorderSuggestionRunner, ReorderSuggestionRunnerHandle, SalesForecastRunner,
+        SalesForecastRunnerHandle,
+    },
+    command_bus::{AggregateRoute, CommandBus},
+    command_dispatcher::{
+        with_business_key_of, with_correlation_of, CommandDispatcher, ConcurrencyLimit, DispatchError, RetryPolicy,
+        TimestampPolicy,
+    },
+    enrichment::EnrichContext,
+    event_bus::{GuardedEventBus, PublishBreaker, PublishBreakerConfig},
+    event_store::{
+        verify_all_streams, EventCursor, EventFilter, EventPage, EventQuery, EventQueryResult, EventStore,
+        InMemoryEventStore, IntegritySchedule, Pagination, StoredEvent, TenantStats,
+    },
+    integration_events::{IntegrationEvent, IntegrationEventMapper, IntegrationEventRelay},
+    jobs::InMemoryJobStore,
+    product_import::{self, ImportMode, ImportReport, ImportRow},
+    sku_registry::SkuRegistry,
+    redaction::PayloadRedactor,
+    tenant_settings::{change_settings, TenantSettings, TenantSettingsCommand},
+    workers::{supervise, ShardKey, ShardedProjectionWorker, ShardingConfig, TaskRegistry, WorkerHandle},
     projections::{
         accounting::{AccountBalance, AccountBalancesProjection},
         invoices::{InvoiceReadModel, InvoicesProjection},
+        inventory_movements::{InventoryMovementHistory, InventoryMovementProjection},
         inventory_stock::{InventoryReadModel, InventoryStockProjection},
         invoicing::{InvoiceAgingProjection, InvoiceAgingReadModel},
         parties::{PartyDirectoryProjection, PartyReadModel},
         products::{ProductCatalogProjection, ProductReadModel},
         purchasing::{PurchaseOrderReadModel, PurchaseOrdersProjection},
+        supplier_performance::{PerformanceWindow, SupplierOrderRecord, SupplierPerformance, SupplierPerformanceProjection},
+        trial_balance::{KindTotals, TrialBalance, TrialBalanceProjection},
         sales_orders::{SalesOrderReadModel, SalesOrdersProjection},
-        users::{EffectivePermissions, UserReadModel, UsersProjection},
+        users::{default_role_permissions, EffectivePermissions, UserReadModel, UsersProjection},
+        tenant_settings::TenantSettingsProjection,
+        versioned::ProjectionVersionRegistry,
+        catch_up, replay_dead_letter, InMemoryCursorStore, InMemoryProjectionDeadLetters, ProjectionApplyFn,
+        ProjectionCursorStore, ProjectionDeadLetter, ProjectionDeadLetterError, ProjectionDeadLetterStore,
+        SubscriberPositions,
+    },
+    read_model::{InMemoryTenantStore, ListPage, PageStart, Watermark},
+    saga::{
+        invoice_ledger::InvoiceLedgerPosting, sales_ar::SalesArSaga, sales_reservation::SalesStockReservation,
+        timeout::{SagaTimeouts, SAGA_TIMEOUT_SWEEP_INTERVAL},
+        CommandExecutor as SagaCommandExecutor, SagaRepository,
     },
-    read_model::InMemoryTenantStore,
-    saga::{sales_ar::SalesArSaga, CommandExecutor as SagaCommandExecutor, SagaRepository},
 };
-use tokio::sync::broadcast;
-use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+use tokio::sync::{broadcast, mpsc::unbounded_channel};
+use tokio_stream::{
+    wrappers::{BroadcastStream, UnboundedReceiverStream},
+    StreamExt,
+};
 
 #[cfg(feature = "redis")]
 use forgeerp_infra::{
     event_bus::RedisStreamsEventBus,
-    event_store::{EventFilter, EventQuery, EventQueryResult, Pagination, PostgresEventStore},
+    event_store::PostgresEventStore,
+    ai::{PostgresAiInsightSink, DEFAULT_AI_INSIGHT_RETENTION_DAYS},
+    projections::PostgresCursorStore,
     read_model::PostgresInventoryStore,
 };
 #[cfg(feature = "redis")]
 use sqlx::PgPool;
 
+/// How long `GET /health` waits for each dependency to answer.
+#[cfg(feature = "redis")]
+const DEPENDENCY_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Reachability of one external dependency, as reported by `GET /health`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct DependencyStatus {
+    pub name: &'static str,
+    pub healthy: bool,
+    /// Why the check failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DependencyStatus {
+    pub fn healthy(name: &'static str) -> Self {
+        Self {
+            name,
+            healthy: true,
+            error: None,
+        }
+    }
+
+    pub fn from_result<E: std::fmt::Display>(name: &'static str, result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => Self::healthy(name),
+            Err(e) => Self {
+                name,
+                healthy: false,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// Per-tenant AI runners of one kind, started on first use.
+type AiRunners = Arc<Mutex<HashMap<TenantId, AiRunnerHandle>>>;
+
+/// Background workers stopped by [`AppServices::shutdown`].
+#[derive(Debug)]
+pub struct BackgroundWorkers {
+    projections: Mutex<Option<WorkerHandle>>,
+    ai_runners: Vec<AiRunners>,
+}
+
+impl BackgroundWorkers {
+    fn new(ai_runners: Vec<AiRunners>) -> Self {
+        Self {
+            projections: Mutex::new(None),
+            ai_runners,
+        }
+    }
+
+    fn set_projections(&self, handle: WorkerHandle) {
+        *self.projections.lock().unwrap() = Some(handle);
+    }
+
+    /// Stop the projection worker, then the AI runners it may have triggered.
+    ///
+    /// Blocks until they exit: the projection worker first applies the events already
+    /// delivered to it (storing its cursor after each), runners finish their current run.
+    fn shutdown(&self) {
+        let projections = self.projections.lock().unwrap().take();
+        if let Some(projections) = projections {
+            projections.shutdown();
+        }
+        let runners: Vec<AiRunnerHandle> = self
+            .ai_runners
+            .iter()
+            .flat_map(|runners| runners.lock().unwrap().drain().map(|(_, handle)| handle).collect::<Vec<_>>())
+            .collect();
+        let stopped = runners.len();
+        for runner in runners {
+            runner.shutdown();
+        }
+        tracing::info!(ai_runners = stopped, "background workers stopped");
+    }
+}
+
 /// Realtime message broadcasted via SSE.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct RealtimeMessage {
@@ -48,10 +170,33 @@ pub struct RealtimeMessage {
     pub payload: serde_json::Value,
 }
 
+/// Projection-update notification for SSE.
+///
+/// The event body is included for live views, but only after PII redaction:
+/// SSE streams are an observability channel, not a source of truth.
+fn projection_update_message(env: &EventEnvelope<serde_json::Value>, redactor: &PayloadRedactor) -> RealtimeMessage {
+    let at = env.aggregate_type();
+    RealtimeMessage {
+        tenant_id: env.tenant_id(),
+        topic: format!("{at}.projection_updated"),
+        payload: serde_json::json!({
+            "kind": "projection_update",
+            "aggregate_type": at,
+            "aggregate_id": env.aggregate_id().to_string(),
+            "sequence_number": env.sequence_number(),
+            "event": redactor.redact_envelope(env),
+        }),
+    }
+}
+
 /// API-local AI insight sink that stores results and broadcasts "insight available" notifications.
+///
+/// Results are kept in memory unless a Postgres store is attached (persistent mode).
 #[derive(Debug)]
 pub struct ApiAiInsightSink {
     inner: Mutex<Vec<(TenantId, AiResult)>>,
+    #[cfg(feature = "redis")]
+    store: Option<PostgresAiInsightSink>,
     realtime_tx: broadcast::Sender<RealtimeMessage>,
 }
 
@@ -59,42 +204,76 @@ impl ApiAiInsightSink {
     pub fn new(realtime_tx: broadcast::Sender<RealtimeMessage>) -> Self {
         Self {
             inner: Mutex::new(Vec::new()),
+            #[cfg(feature = "redis")]
+            store: None,
             realtime_tx,
         }
     }
 
-    pub fn all(&self) -> Vec<(TenantId, AiResult)> {
-        self.inner.lock().unwrap().clone()
+    /// Keep insights in Postgres instead of memory (they survive restarts).
+    #[cfg(feature = "redis")]
+    pub fn with_store(mut self, store: PostgresAiInsightSink) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// The tenant's insights of one kind, oldest first.
+    pub async fn insights(&self, tenant_id: TenantId, kind: AiInsightKind) -> Result<Vec<AiResult>, AiInsightStoreError> {
+        #[cfg(feature = "redis")]
+        if let Some(store) = &self.store {
+            return store.query(tenant_id, kind).await;
+        }
+        Ok(self
+            .inner
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(t, r)| *t == tenant_id && r.kind() == Some(kind))
+            .map(|(_, r)| r.clone())
+            .collect())
     }
 }
 
 impl AiInsightSink for ApiAiInsightSink {
     fn emit(&self, tenant_id: TenantId, result: AiResult) {
-        self.inner.lock().unwrap().push((tenant_id, result.clone()));
+        #[cfg(feature = "redis")]
+        if let Some(store) = &self.store {
+            let realtime_tx = self.realtime_tx.clone();
+            let metadata = result.metadata.clone();
+            store.emit_then(tenant_id, result, move || announce_insight(&realtime_tx, tenant_id, metadata));
+            return;
+        }
 
-        // Broadcast that new insights are available (lossy; no backpressure on core).
-        let _ = self.realtime_tx.send(RealtimeMessage {
-            tenant_id,
-            topic: "ai.insight_available".to_string(),
-            payload: serde_json::json!({
-                "kind": "insights",
-                "insight_type": "ai.result",
-                "metadata": result.metadata,
-            }),
-        });
+        // Keyed insights replace earlier copies; only new ones are announced.
+        if !upsert_insight(&mut self.inner.lock().unwrap(), tenant_id, result.clone()) {
+            return;
+        }
+        announce_insight(&self.realtime_tx, tenant_id, result.metadata);
     }
 }
 
+/// Broadcast that new insights are available (lossy; no backpressure on core).
+fn announce_insight(realtime_tx: &broadcast::Sender<RealtimeMessage>, tenant_id: TenantId, metadata: serde_json::Value) {
+    let _ = realtime_tx.send(RealtimeMessage {
+        tenant_id,
+        topic: "ai.insight_available".to_string(),
+        payload: serde_json::json!({
+            "kind": "insights",
+            "insight_type": "ai.result",
+            "metadata": metadata,
+        }),
+    });
+}
+
 // Type-erased dispatcher for in-memory implementations
 type InMemoryDispatcher = CommandDispatcher<
     Arc<InMemoryEventStore>,
-    Arc<InMemoryEventBus<EventEnvelope<serde_json::Value>>>,
+    GuardedEventBus<InMemoryEventBus<EventEnvelope<serde_json::Value>>>,
 >;
 
 /// Minimal command executor implementation for the Sales→AR saga using the in-memory dispatcher.
 struct InMemorySagaExecutor {
     dispatcher: Arc<InMemoryDispatcher>,
-    default_ledger_id: AggregateId,
 }
 
 impl SagaCommandExecutor for InMemorySagaExecutor {
@@ -127,13 +306,25 @@ impl SagaCommandExecutor for InMemorySagaExecutor {
                     serde_json::from_value(payload.clone()).map_err(|e| DispatchError::Validation(e.to_string()))?;
                 let _ = self.dispatcher.dispatch::<forgeerp_accounting::Ledger>(
                     cmd.tenant_id,
-                    self.default_ledger_id,
+                    forgeerp_accounting::LedgerId::default_for(cmd.tenant_id).0,
                     "accounting.ledger",
                     forgeerp_accounting::JournalCommand::PostJournalEntry(cmd),
                     |_, id| forgeerp_accounting::Ledger::empty(forgeerp_accounting::LedgerId::new(id)),
                 )?;
                 Ok(())
             }
+            ("SalesOrder", "FlagOrder") => {
+                let cmd: forgeerp_sales::FlagOrder =
+                    serde_json::from_value(payload.clone()).map_err(|e| DispatchError::Validation(e.to_string()))?;
+                let _ = self.dispatcher.dispatch::<forgeerp_sales::SalesOrder>(
+                    cmd.tenant_id,
+                    cmd.order_id.0,
+                    "sales.order",
+                    forgeerp_sales::SalesOrderCommand::FlagOrder(cmd),
+                    |_, id| forgeerp_sales::SalesOrder::empty(forgeerp_sales::SalesOrderId::new(id)),
+                )?;
+                Ok(())
+            }
             ("Invoice", "VoidInvoice") => {
                 let cmd: forgeerp_invoicing::VoidInvoice =
                     serde_json::from_value(payload.clone()).map_err(|e| DispatchError::Validation(e.to_string()))?;
@@ -156,7 +347,7 @@ impl SagaCommandExecutor for InMemorySagaExecutor {
 
 // Type-erased dispatcher for persistent implementations
 #[cfg(feature = "redis")]
-type PersistentDispatcher = CommandDispatcher<Arc<PostgresEventStore>, Arc<RedisStreamsEventBus>>;
+type PersistentDispatcher = CommandDispatcher<Arc<PostgresEventStore>, GuardedEventBus<RedisStreamsEventBus>>;
 
 #[derive(Clone)]
 pub enum AppServices {
@@ -167,6 +358,11 @@ pub enum AppServices {
         inventory_projection: Arc<
             InventoryStockProjection<Arc<InMemoryTenantStore<forgeerp_inventory::InventoryItemId, InventoryReadModel>>>,
         >,
+        movements_projection: Arc<
+            InventoryMovementProjection<
+                Arc<InMemoryTenantStore<forgeerp_inventory::InventoryItemId, InventoryMovementHistory>>,
+            >,
+        >,
         parties_projection: Arc<
             PartyDirectoryProjection<Arc<InMemoryTenantStore<forgeerp_parties::PartyId, PartyReadModel>>>,
         >,
@@ -185,17 +381,48 @@ pub enum AppServices {
         purchases_projection: Arc<
             PurchaseOrdersProjection<Arc<InMemoryTenantStore<forgeerp_purchasing::PurchaseOrderId, PurchaseOrderReadModel>>>,
         >,
+        supplier_performance_projection: Arc<
+            SupplierPerformanceProjection<
+                Arc<InMemoryTenantStore<forgeerp_purchasing::PurchaseOrderId, SupplierOrderRecord>>,
+            >,
+        >,
         ledger_projection: Arc<AccountBalancesProjection<Arc<InMemoryTenantStore<String, AccountBalance>>>>,
+        trial_balance_projection:
+            Arc<TrialBalanceProjection<Arc<InMemoryTenantStore<forgeerp_accounting::AccountKind, KindTotals>>>>,
         users_projection: Arc<UsersProjection<Arc<InMemoryTenantStore<UserId, UserReadModel>>>>,
-        default_ledger_id: AggregateId,
         ai_sink: Arc<ApiAiInsightSink>,
+        ai_usage: Arc<AiUsageMeter>,
+        anomaly_configs: AnomalyConfigs,
+        background: Arc<BackgroundWorkers>,
+        tasks: TaskRegistry,
         realtime_tx: broadcast::Sender<RealtimeMessage>,
+        integration_bus: Arc<InMemoryEventBus<IntegrationEvent>>,
+        command_bus: Arc<CommandBus>,
+        tenant_settings: Arc<TenantSettingsProjection>,
+        job_store: Arc<InMemoryJobStore>,
+        sku_registry: Arc<SkuRegistry>,
+        admin_audit: Arc<AdminAuditLog>,
+        /// Rejected commands (`None` unless `REJECTED_COMMAND_LOG_CAPACITY` is set).
+        rejected_commands: Option<Arc<RejectedCommandLog>>,
+        /// Projections running a candidate version next to the primary one.
+        projection_versions: Arc<ProjectionVersionRegistry>,
+        /// Envelopes the projection subscriber failed to apply.
+        projection_dead_letters: Arc<InMemoryProjectionDeadLetters>,
+        /// The projection subscriber's apply, for replaying dead letters.
+        projection_apply: ProjectionApplyFn,
+        /// Timeout and circuit breaker on the dispatcher's bus publishes.
+        publish_breaker: Arc<PublishBreaker>,
     },
     #[cfg(feature = "redis")]
     Persistent {
         dispatcher: Arc<PersistentDispatcher>,
         event_store: Arc<PostgresEventStore>,
         inventory_projection: Arc<InventoryStockProjection<Arc<PostgresInventoryStore>>>,
+        movements_projection: Arc<
+            InventoryMovementProjection<
+                Arc<InMemoryTenantStore<forgeerp_inventory::InventoryItemId, InventoryMovementHistory>>,
+            >,
+        >,
         parties_projection: Arc<
             PartyDirectoryProjection<Arc<InMemoryTenantStore<forgeerp_parties::PartyId, PartyReadModel>>>,
         >,
@@ -214,15 +441,313 @@ pub enum AppServices {
         purchases_projection: Arc<
             PurchaseOrdersProjection<Arc<InMemoryTenantStore<forgeerp_purchasing::PurchaseOrderId, PurchaseOrderReadModel>>>,
         >,
+        supplier_performance_projection: Arc<
+            SupplierPerformanceProjection<
+                Arc<InMemoryTenantStore<forgeerp_purchasing::PurchaseOrderId, SupplierOrderRecord>>,
+            >,
+        >,
         ledger_projection: Arc<AccountBalancesProjection<Arc<InMemoryTenantStore<String, AccountBalance>>>>,
+        trial_balance_projection:
+            Arc<TrialBalanceProjection<Arc<InMemoryTenantStore<forgeerp_accounting::AccountKind, KindTotals>>>>,
         users_projection: Arc<UsersProjection<Arc<InMemoryTenantStore<UserId, UserReadModel>>>>,
-        default_ledger_id: AggregateId,
         ai_sink: Arc<ApiAiInsightSink>,
+        ai_usage: Arc<AiUsageMeter>,
+        anomaly_configs: AnomalyConfigs,
+        background: Arc<BackgroundWorkers>,
+        tasks: TaskRegistry,
         realtime_tx: broadcast::Sender<RealtimeMessage>,
+        integration_bus: Arc<InMemoryEventBus<IntegrationEvent>>,
+        command_bus: Arc<CommandBus>,
+        tenant_settings: Arc<TenantSettingsProjection>,
+        job_store: Arc<InMemoryJobStore>,
+        sku_registry: Arc<SkuRegistry>,
+        admin_audit: Arc<AdminAuditLog>,
+        /// Rejected commands (`None` unless `REJECTED_COMMAND_LOG_CAPACITY` is set).
+        rejected_commands: Option<Arc<RejectedCommandLog>>,
+        /// Projections running a candidate version next to the primary one.
+        projection_versions: Arc<ProjectionVersionRegistry>,
+        /// Envelopes the projection subscriber failed to apply.
+        projection_dead_letters: Arc<InMemoryProjectionDeadLetters>,
+        /// The projection subscriber's apply, for replaying dead letters.
+        projection_apply: ProjectionApplyFn,
+        /// Timeout and circuit breaker on the dispatcher's bus publishes.
+        publish_breaker: Arc<PublishBreaker>,
         bus: Arc<RedisStreamsEventBus>,
     },
 }
 
+/// Attempts per command before an append conflict is returned to the client.
+const DISPATCH_MAX_ATTEMPTS: u32 = 3;
+
+/// Pause before the first retry of a conflicted append (doubled for each further retry).
+const DISPATCH_RETRY_BACKOFF: std::time::Duration = std::time::Duration::from_millis(5);
+
+/// Retry append conflicts and report the effort on the current request; bound client
+/// `occurred_at` values with the `COMMAND_TIMESTAMP_*` policy; stamp server-owned fields;
+/// bound concurrent dispatches when `DISPATCH_MAX_IN_FLIGHT` is set; log rejected commands
+/// when `REJECTED_COMMAND_LOG_CAPACITY` is set; honour `Idempotency-Key` headers.
+fn configure_dispatcher<S, B>(
+    dispatcher: CommandDispatcher<S, B>,
+    rejected_commands: Option<Arc<RejectedCommandLog>>,
+) -> CommandDispatcher<S, B> {
+    let mut dispatcher = dispatcher
+        .with_retry_policy(RetryPolicy {
+            max_attempts: DISPATCH_MAX_ATTEMPTS,
+            backoff: DISPATCH_RETRY_BACKOFF,
+        })
+        .with_retry_observer(Arc::new(crate::middleware::record_retry))
+        .with_timestamp_policy(TimestampPolicy::from_env())
+        .with_enricher(stamp_product_command)
+        .with_idempotency_log(Arc::new(IdempotencyLog::default()));
+    if let Some(log) = rejected_commands {
+        dispatcher = dispatcher.with_rejected_command_log(log);
+    }
+    match ConcurrencyLimit::from_env() {
+        Some(limit) => dispatcher.with_concurrency_limit(limit),
+        None => dispatcher,
+    }
+}
+
+/// Sweep every stream for sequence gaps and duplicates every
+/// `SEQUENCE_INTEGRITY_CHECK_INTERVAL_SECS` (off when unset); anomalies are logged.
+fn spawn_integrity_sweep<S>(store: Arc<S>)
+where
+    S: EventStore + 'static,
+{
+    let Some(schedule) = IntegritySchedule::from_env() else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(schedule.interval);
+        ticks.tick().await;
+        loop {
+            ticks.tick().await;
+            let store = store.clone();
+            match tokio::task::spawn_blocking(move || verify_all_streams(&*store)).await {
+                Ok(Ok(anomalies)) => {
+                    for a in &anomalies {
+                        tracing::error!(
+                            tenant_id = %a.tenant_id,
+                            aggregate_id = %a.aggregate_id,
+                            anomaly = %a.anomaly,
+                            "event stream sequence integrity violated"
+                        );
+                    }
+                    tracing::info!(broken_streams = anomalies.len(), "sequence integrity sweep finished");
+                }
+                Ok(Err(e)) => tracing::warn!("sequence integrity sweep failed: {e}"),
+                Err(e) => tracing::warn!("sequence integrity sweep panicked: {e}"),
+            }
+        }
+    });
+}
+
+/// Fire the compensation of sales_ar sagas stuck past their deadline every
+/// `SAGA_TIMEOUT_SWEEP_INTERVAL`.
+fn spawn_saga_timeout_sweep(
+    saga_repo: Arc<SagaRepository<SalesArSaga, Arc<InMemoryEventStore>>>,
+    executor: Arc<InMemorySagaExecutor>,
+    timeouts: Arc<SagaTimeouts<SalesArSaga>>,
+) {
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(SAGA_TIMEOUT_SWEEP_INTERVAL);
+        ticks.tick().await;
+        loop {
+            ticks.tick().await;
+            let (saga_repo, executor, timeouts) = (saga_repo.clone(), executor.clone(), timeouts.clone());
+            match tokio::task::spawn_blocking(move || timeouts.fire_expired(&saga_repo, &*executor, chrono::Utc::now())).await {
+                Ok(fired) if !fired.is_empty() => tracing::info!(sagas = fired.len(), "timed-out sagas compensated"),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("saga timeout sweep panicked: {e}"),
+            }
+        }
+    });
+}
+
+/// Product commands happen when the server handles them; handlers leave `occurred_at` unset.
+fn stamp_product_command(ctx: &EnrichContext<'_>, command: &mut forgeerp_products::ProductCommand) {
+    use forgeerp_products::ProductCommand;
+
+    let occurred_at = match command {
+        ProductCommand::CreateProduct(c) => &mut c.occurred_at,
+        ProductCommand::ActivateProduct(c) => &mut c.occurred_at,
+        ProductCommand::ArchiveProduct(c) => &mut c.occurred_at,
+        ProductCommand::UpdatePricing(c) => &mut c.occurred_at,
+        ProductCommand::DeleteProduct(c) => &mut c.occurred_at,
+    };
+    *occurred_at = ctx.now;
+}
+
+/// Apply `env` to the read models; an envelope that fails is kept in `dead_letters` for
+/// replay instead of being dropped.
+fn apply_or_dead_letter(
+    apply: &ProjectionApplyFn,
+    dead_letters: &dyn ProjectionDeadLetterStore,
+    env: EventEnvelope<serde_json::Value>,
+) -> Result<(), String> {
+    apply(&env).inspect_err(|e| {
+        tracing::warn!(event_id = %env.event_id(), "{e}");
+        if let Err(dead) = dead_letters.record(env.clone(), e.clone()) {
+            tracing::error!(event_id = %env.event_id(), "projection dead-letter failed: {dead}");
+        }
+    })
+}
+
+/// Name under which the bus -> projections subscriber keeps its position.
+const PROJECTIONS_SUBSCRIBER: &str = "projections";
+
+/// Tenants with at least one stream (`None`, logged, when the store cannot list them).
+fn tenants_with_events<S>(store: &S) -> Option<HashSet<TenantId>>
+where
+    S: forgeerp_infra::event_store::EventStore + ?Sized,
+{
+    match store.stream_ids() {
+        Ok(streams) => Some(streams.into_iter().map(|(tenant_id, _)| tenant_id).collect()),
+        Err(e) => {
+            tracing::warn!("projection catch-up skipped: {e}");
+            None
+        }
+    }
+}
+
+/// Apply, for every tenant with events, what was committed after the subscriber's stored
+/// position, before it starts following the bus. Failed envelopes are dead-lettered like
+/// live ones.
+fn catch_up_projections<S>(
+    store: &S,
+    cursors: &dyn ProjectionCursorStore,
+    apply: &ProjectionApplyFn,
+    dead_letters: &dyn ProjectionDeadLetterStore,
+) where
+    S: forgeerp_infra::event_store::EventStore + ?Sized,
+{
+    let Some(tenants) = tenants_with_events(store) else {
+        return;
+    };
+    for tenant_id in tenants {
+        let caught_up = catch_up(store, cursors, PROJECTIONS_SUBSCRIBER, tenant_id, |page| {
+            for env in page {
+                let _ = apply_or_dead_letter(apply, dead_letters, env.clone());
+            }
+            Ok(())
+        });
+        match caught_up {
+            Ok(applied) => tracing::info!(tenant_id = %tenant_id, applied, "projections caught up"),
+            Err(e) => tracing::warn!(tenant_id = %tenant_id, "projection catch-up failed: {e}"),
+        }
+    }
+}
+
+/// Startup catch-up when some read models are durable and the others live in memory.
+///
+/// The in-memory read models start empty, so every tenant's whole history is replayed
+/// through `volatile`. `durable` only gets the events after the subscriber position
+/// stored in `cursors`, which moves forward page by page. Returns the positions replayed
+/// here: the bus subscriber skips envelopes at or below them (the consumer group
+/// redelivers what it had not acknowledged before the restart).
+#[cfg_attr(not(feature = "redis"), allow(dead_code))]
+fn restore_projections<S>(
+    store: &S,
+    cursors: &dyn ProjectionCursorStore,
+    durable: &ProjectionApplyFn,
+    volatile: &ProjectionApplyFn,
+    dead_letters: &dyn ProjectionDeadLetterStore,
+) -> Arc<InMemoryCursorStore>
+where
+    S: forgeerp_infra::event_store::EventStore + ?Sized,
+{
+    let replayed = InMemoryCursorStore::arc();
+    for tenant_id in tenants_with_events(store).unwrap_or_default() {
+        let resume = cursors.get_position(tenant_id, PROJECTIONS_SUBSCRIBER).unwrap_or(0);
+        let caught_up = catch_up(store, replayed.as_ref(), PROJECTIONS_SUBSCRIBER, tenant_id, |page| {
+            for env in page {
+                if env.global_position().is_some_and(|position| position > resume) {
+                    let _ = apply_or_dead_letter(durable, dead_letters, env.clone());
+                }
+                let _ = apply_or_dead_letter(volatile, dead_letters, env.clone());
+            }
+            if let Some(position) = page.last().and_then(|env| env.global_position()) {
+                cursors.update_position(tenant_id, PROJECTIONS_SUBSCRIBER, position);
+            }
+            Ok(())
+        });
+        match caught_up {
+            Ok(applied) => tracing::info!(tenant_id = %tenant_id, applied, resumed_after = resume, "projections restored"),
+            Err(e) => tracing::warn!(tenant_id = %tenant_id, "projection restore failed: {e}"),
+        }
+    }
+    replayed
+}
+
+/// Projection concurrency from `PROJECTION_WORKERS` (default 1, sequential) and
+/// `PROJECTION_SHARD_KEY` (`aggregate` or `tenant`, default `aggregate`).
+fn projection_sharding() -> ShardingConfig {
+    let workers = std::env::var("PROJECTION_WORKERS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(1);
+    let key = match std::env::var("PROJECTION_SHARD_KEY").as_deref() {
+        Ok("tenant") => ShardKey::Tenant,
+        _ => ShardKey::Aggregate,
+    };
+    ShardingConfig { workers, key }
+}
+
+/// Per-tenant AI run budget from `AI_RATE_LIMIT_BURST` and `AI_RATE_LIMIT_PER_MINUTE`.
+fn ai_rate_limit() -> RateLimit {
+    let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u32>().ok());
+    let defaults = RateLimit::default();
+    RateLimit {
+        burst: var("AI_RATE_LIMIT_BURST").unwrap_or(defaults.burst),
+        per_minute: var("AI_RATE_LIMIT_PER_MINUTE").unwrap_or(defaults.per_minute),
+    }
+}
+
+/// Days an AI insight is kept after its last update (`AI_INSIGHT_RETENTION_DAYS`).
+#[cfg(feature = "redis")]
+fn ai_insight_retention() -> chrono::Duration {
+    let days = std::env::var("AI_INSIGHT_RETENTION_DAYS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(DEFAULT_AI_INSIGHT_RETENTION_DAYS);
+    chrono::Duration::days(days)
+}
+
+/// Command routes (aggregate type, factory, required permissions) for `AppServices::send`.
+fn build_command_bus<S, B>(dispatcher: Arc<CommandDispatcher<S, B>>) -> CommandBus
+where
+    S: forgeerp_infra::event_store::EventStore + Send + Sync + 'static,
+    B: EventBus<EventEnvelope<serde_json::Value>> + Send + Sync + 'static,
+{
+    CommandBus::new().register(
+        dispatcher,
+        AggregateRoute::<forgeerp_inventory::InventoryItem> {
+            aggregate_type: "inventory.item",
+            make_aggregate: |_, id| {
+                forgeerp_inventory::InventoryItem::empty(forgeerp_inventory::InventoryItemId::new(id))
+            },
+            permissions: |cmd| match cmd {
+                // Names and reorder points are part of the item master data.
+                forgeerp_inventory::InventoryCommand::CreateItem(_)
+                | forgeerp_inventory::InventoryCommand::RenameItem(_)
+                | forgeerp_inventory::InventoryCommand::SetReorderPoint(_) => {
+                    vec![forgeerp_auth::Permission::new("inventory.items.create")]
+                }
+                forgeerp_inventory::InventoryCommand::AdjustStock(_)
+                | forgeerp_inventory::InventoryCommand::TransferStock(_) => {
+                    vec![forgeerp_auth::Permission::new("inventory.items.adjust")]
+                }
+                forgeerp_inventory::InventoryCommand::ReserveStock(_)
+                | forgeerp_inventory::InventoryCommand::ReleaseReservation(_) => {
+                    vec![forgeerp_auth::Permission::new("inventory.items.reserve")]
+                }
+            },
+        },
+    )
+}
+
 pub async fn build_services() -> AppServices {
     let use_persistent = std::env::var("USE_PERSISTENT_STORES")
         .unwrap_or_else(|_| "false".to_string())
@@ -250,11 +775,14 @@ fn build_in_memory_services() -> AppServices {
     // In-memory infra wiring (dev/test): store + bus + projection.
     let store = Arc::new(InMemoryEventStore::new());
     let bus: Arc<InMemoryEventBus<EventEnvelope<serde_json::Value>>> = Arc::new(InMemoryEventBus::new());
+    spawn_integrity_sweep(store.clone());
 
     let rm_store: Arc<InMemoryTenantStore<forgeerp_inventory::InventoryItemId, InventoryReadModel>> =
         Arc::new(InMemoryTenantStore::new());
     let inventory_projection: Arc<InventoryStockProjection<_>> =
         Arc::new(InventoryStockProjection::new(rm_store));
+    let movements_projection: Arc<InventoryMovementProjection<_>> =
+        Arc::new(InventoryMovementProjection::new(Arc::new(InMemoryTenantStore::new())));
 
     let parties_store: Arc<InMemoryTenantStore<forgeerp_parties::PartyId, PartyReadModel>> =
         Arc::new(InMemoryTenantStore::new());
@@ -265,6 +793,7 @@ fn build_in_memory_services() -> AppServices {
         Arc::new(InMemoryTenantStore::new());
     let products_projection: Arc<ProductCatalogProjection<_>> =
         Arc::new(ProductCatalogProjection::new(products_store));
+    let sku_registry = SkuRegistry::arc();
 
     let sales_store: Arc<InMemoryTenantStore<forgeerp_sales::SalesOrderId, SalesOrderReadModel>> =
         Arc::new(InMemoryTenantStore::new());
@@ -286,173 +815,393 @@ fn build_in_memory_services() -> AppServices {
     > = Arc::new(InMemoryTenantStore::new());
     let purchases_projection: Arc<PurchaseOrdersProjection<_>> =
         Arc::new(PurchaseOrdersProjection::new(purchases_store));
+    let supplier_performance_projection: Arc<SupplierPerformanceProjection<_>> =
+        Arc::new(SupplierPerformanceProjection::new(Arc::new(InMemoryTenantStore::new())));
 
     let ledger_store: Arc<InMemoryTenantStore<String, AccountBalance>> = Arc::new(InMemoryTenantStore::new());
     let ledger_projection: Arc<AccountBalancesProjection<_>> =
         Arc::new(AccountBalancesProjection::new(ledger_store));
+    let trial_balance_projection: Arc<TrialBalanceProjection<_>> =
+        Arc::new(TrialBalanceProjection::new(Arc::new(InMemoryTenantStore::new())));
 
     let users_store: Arc<InMemoryTenantStore<UserId, UserReadModel>> = Arc::new(InMemoryTenantStore::new());
     let users_projection: Arc<UsersProjection<_>> = Arc::new(UsersProjection::new(users_store));
-
-    let default_ledger_id = AggregateId::new();
+    let tenant_settings = TenantSettingsProjection::arc();
+    let projection_versions = ProjectionVersionRegistry::arc();
 
     // Realtime channel (SSE): lossy broadcast, tenant-filtered in handlers.
     let (realtime_tx, _realtime_rx) = broadcast::channel::<RealtimeMessage>(256);
 
+    // Public bus: curated integration events for external consumers.
+    let integration_bus: Arc<InMemoryEventBus<IntegrationEvent>> = Arc::new(InMemoryEventBus::new());
+
+    // Liveness of the background subscribers and runners below (see `/health/ready`).
+    let tasks = TaskRegistry::new();
+
     // AI wiring (dev/test): in-memory insights + per-tenant anomaly runners.
     let ai_sink: Arc<ApiAiInsightSink> = Arc::new(ApiAiInsightSink::new(realtime_tx.clone()));
     let ai_runners: Arc<Mutex<HashMap<TenantId, InventoryAnomalyRunnerHandle>>> =
         Arc::new(Mutex::new(HashMap::new()));
-    let ai_runner_cfg = InventoryAnomalyRunner::default();
-
-    // Background subscriber: bus -> projections
-    {
-        let sub = bus.subscribe();
+    let ai_backend = AiBackendConfig::from_env();
+    let ai_usage = Arc::new(AiUsageMeter::new(ai_rate_limit()));
+    let anomaly_configs = AnomalyConfigs::in_memory();
+    let ai_runner_cfg = InventoryAnomalyRunner {
+        configs: Some(anomaly_configs.clone()),
+        backend_timeout: ai_backend.timeout,
+        meter: Some(ai_usage.clone()),
+        liveness: Some(tasks.clone()),
+        ..Default::default()
+    };
+    let forecast_runners: Arc<Mutex<HashMap<TenantId, SalesForecastRunnerHandle>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    let forecast_runner_cfg = SalesForecastRunner {
+        backend_timeout: ai_backend.timeout,
+        meter: Some(ai_usage.clone()),
+        liveness: Some(tasks.clone()),
+        ..Default::default()
+    };
+    let reorder_runners: Arc<Mutex<HashMap<TenantId, ReorderSuggestionRunnerHandle>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    let reorder_runner_cfg = ReorderSuggestionRunner {
+        backend_timeout: ai_backend.timeout,
+        meter: Some(ai_usage.clone()),
+        liveness: Some(tasks.clone()),
+        ..Default::default()
+    };
+    let background = Arc::new(BackgroundWorkers::new(vec![
+        ai_runners.clone(),
+        forecast_runners.clone(),
+        reorder_runners.clone(),
+    ]));
+    // Reorder suggestions join stock on hand with the sales of the products stocking it.
+    let reorder_reader = {
+        let products_projection = products_projection.clone();
+        let items = move |tenant_id: TenantId, product_id: forgeerp_products::ProductId| {
+            products_projection
+                .get(tenant_id, &product_id)
+                .and_then(|p| p.inventory_item_id)
+                .map(forgeerp_inventory::InventoryItemId::new)
+        };
+        Arc::new(InventorySalesReader::new(
+            inventory_projection.clone(),
+            sales_projection.clone(),
+            Arc::new(items),
+        ))
+    };
+    let ai_backend = ai_backend.build();
+
+    // Background subscriber: bus -> projections (sharded by aggregate when configured)
+    let projection_dead_letters = InMemoryProjectionDeadLetters::arc();
+    let projection_apply: ProjectionApplyFn = {
         let inventory_projection = inventory_projection.clone();
+        let movements_projection = movements_projection.clone();
         let parties_projection = parties_projection.clone();
         let products_projection = products_projection.clone();
+        let sku_registry = sku_registry.clone();
         let sales_projection = sales_projection.clone();
         let invoices_projection = invoices_projection.clone();
         let ar_aging_projection = ar_aging_projection.clone();
         let purchases_projection = purchases_projection.clone();
+        let supplier_performance_projection = supplier_performance_projection.clone();
         let ledger_projection = ledger_projection.clone();
+        let trial_balance_projection = trial_balance_projection.clone();
         let users_projection = users_projection.clone();
+        let tenant_settings = tenant_settings.clone();
         let ai_sink = ai_sink.clone();
         let ai_runners = ai_runners.clone();
+        let forecast_runners = forecast_runners.clone();
+        let reorder_runners = reorder_runners.clone();
         let realtime_tx = realtime_tx.clone();
-        tokio::task::spawn_blocking(move || loop {
-            match sub.recv() {
-                Ok(env) => {
-                    let at = env.aggregate_type();
-
-                    // Apply to the relevant projection(s) only.
-                    let apply_ok = match at {
-                        "inventory.item" => inventory_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                        "parties.party" => parties_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                        "products.product" => products_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                        "sales.order" => sales_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                        "invoicing.invoice" => {
-                            if let Err(e) = invoices_projection.apply_envelope(&env) {
-                                Err(e.to_string())
-                            } else if let Err(e) = ar_aging_projection.apply_envelope(&env) {
-                                Err(e.to_string())
-                            } else {
-                                Ok(())
-                            }
-                        }
-                        "purchasing.order" => purchases_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                        "accounting.ledger" => ledger_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                        "auth.user" => users_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                        _ => Ok(()),
-                    };
-
-                    if let Err(e) = apply_ok {
-                        tracing::warn!("projection apply failed: {e}");
-                        continue;
-                    }
+        let redactor = PayloadRedactor::default();
+        Arc::new(move |env: &EventEnvelope<serde_json::Value>| -> Result<(), String> {
+            let at = env.aggregate_type();
+            tracing::debug!(
+                aggregate_type = at,
+                aggregate_id = %env.aggregate_id(),
+                payload = %redactor.redact_envelope(env),
+                "event received"
+            );
 
-                    // Broadcast projection update (lossy; no backpressure on core).
-                    let _ = realtime_tx.send(RealtimeMessage {
-                        tenant_id: env.tenant_id(),
-                        topic: format!("{at}.projection_updated"),
-                        payload: serde_json::json!({
-                            "kind": "projection_update",
-                            "aggregate_type": at,
-                            "aggregate_id": env.aggregate_id().to_string(),
-                            "sequence_number": env.sequence_number(),
-                        }),
-                    });
-
-                    // Event-triggered AI execution only for inventory updates.
-                    if at == "inventory.item" {
-                        let tenant_id = env.tenant_id();
-                        let mut runners = ai_runners.lock().unwrap();
-                        let handle = runners.entry(tenant_id).or_insert_with(|| {
-                            ai_runner_cfg.spawn_for_tenant(
-                                "ai.inventory_anomaly",
-                                tenant_id,
-                                inventory_projection.clone(),
-                                ai_sink.clone(),
-                            )
-                        });
-                        handle.trigger();
+            // Apply to the relevant projection(s) only.
+            let apply_ok = match at {
+                "inventory.item" => inventory_projection
+                    .apply_envelope(env)
+                    .map_err(|e| e.to_string())
+                    .and_then(|()| movements_projection.apply_envelope(env).map_err(|e| e.to_string())),
+                "parties.party" => parties_projection.apply_envelope(env).map_err(|e| e.to_string()),
+                "products.product" => products_projection
+                    .apply_envelope(env)
+                    .map_err(|e| e.to_string())
+                    .and_then(|()| sku_registry.apply_envelope(env).map_err(|e| format!("{e:?}"))),
+                "sales.order" => sales_projection.apply_envelope(env).map_err(|e| e.to_string()),
+                "invoicing.invoice" => {
+                    if let Err(e) = invoices_projection.apply_envelope(env) {
+                        Err(e.to_string())
+                    } else if let Err(e) = ar_aging_projection.apply_envelope(env) {
+                        Err(e.to_string())
+                    } else {
+                        Ok(())
                     }
                 }
-                Err(_) => break,
+                "purchasing.order" => purchases_projection
+                    .apply_envelope(env)
+                    .map_err(|e| e.to_string())
+                    .and_then(|()| supplier_performance_projection.apply_envelope(env).map_err(|e| e.to_string())),
+                "accounting.ledger" => ledger_projection
+                    .apply_envelope(env)
+                    .map_err(|e| e.to_string())
+                    .and_then(|()| trial_balance_projection.apply_envelope(env).map_err(|e| e.to_string())),
+                "auth.user" => users_projection.apply_envelope(env).map_err(|e| e.to_string()),
+                "tenant.settings" => tenant_settings.apply_envelope(env).map_err(|e| e.to_string()),
+                _ => Ok(()),
+            };
+
+            apply_ok.map_err(|e| format!("projection apply failed: {e}"))?;
+
+            // Broadcast projection update (lossy; no backpressure on core).
+            let _ = realtime_tx.send(projection_update_message(env, &redactor));
+
+            // Event-triggered AI execution: anomalies on inventory updates, forecasts on sales,
+            // reorder suggestions on either.
+            if at == "inventory.item" {
+                let tenant_id = env.tenant_id();
+                let mut runners = ai_runners.lock().unwrap();
+                let handle = runners.entry(tenant_id).or_insert_with(|| {
+                    ai_runner_cfg.spawn_for_tenant(
+                        "ai.inventory_anomaly",
+                        tenant_id,
+                        inventory_projection.clone(),
+                        ai_sink.clone(),
+                        ai_backend.clone(),
+                    )
+                });
+                handle.trigger();
+            }
+            if at == "sales.order" {
+                let tenant_id = env.tenant_id();
+                let mut runners = forecast_runners.lock().unwrap();
+                let handle = runners.entry(tenant_id).or_insert_with(|| {
+                    forecast_runner_cfg.spawn_for_tenant(
+                        "ai.sales_forecast",
+                        tenant_id,
+                        sales_projection.clone(),
+                        ai_sink.clone(),
+                        ai_backend.clone(),
+                    )
+                });
+                handle.trigger();
             }
+            if at == "inventory.item" || at == "sales.order" {
+                let tenant_id = env.tenant_id();
+                let mut runners = reorder_runners.lock().unwrap();
+                let handle = runners.entry(tenant_id).or_insert_with(|| {
+                    reorder_runner_cfg.spawn_for_tenant(
+                        "ai.reorder_suggestion",
+                        tenant_id,
+                        reorder_reader.clone(),
+                        ai_sink.clone(),
+                        ai_backend.clone(),
+                    )
+                });
+                handle.trigger();
+            }
+            Ok(())
+        })
+    };
+    // Subscriber position: resume after the last handled event instead of re-scanning.
+    // With several workers the stored position waits for the slowest shard.
+    let projection_cursors = InMemoryCursorStore::arc();
+    catch_up_projections(
+        store.as_ref(),
+        projection_cursors.as_ref(),
+        &projection_apply,
+        projection_dead_letters.as_ref(),
+    );
+    {
+        let apply = projection_apply.clone();
+        let dead_letters = projection_dead_letters.clone();
+        let positions = Arc::new(SubscriberPositions::new(projection_cursors.clone(), PROJECTIONS_SUBSCRIBER));
+        let routed = positions.clone();
+        // Runs until `AppServices::shutdown`.
+        let beat = tasks.register("projections");
+        let worker = ShardedProjectionWorker::spawn_observed(
+            "projections",
+            bus.clone(),
+            projection_sharding(),
+            beat,
+            move |env: &EventEnvelope<serde_json::Value>| {
+                if let Some(position) = env.global_position() {
+                    routed.routed(env.tenant_id(), position);
+                }
+            },
+            move |env| {
+                let (tenant_id, position) = (env.tenant_id(), env.global_position());
+                let applied = apply_or_dead_letter(&apply, dead_letters.as_ref(), env);
+                // Dead-lettered events count as handled: they are replayed from the dead letters.
+                if let Some(position) = position {
+                    positions.handled(tenant_id, position);
+                }
+                applied
+            },
+        );
+        background.set_projections(worker);
+    }
+
+    // Background subscriber: domain events -> public integration events
+    {
+        let sub = bus.subscribe();
+        let relay = IntegrationEventRelay::new(IntegrationEventMapper::default(), integration_bus.clone());
+        let beat = tasks.register("integration_events");
+        tokio::task::spawn_blocking(move || {
+            supervise(&beat, sub, |env| {
+                relay
+                    .relay(&env)
+                    .map(drop)
+                    .inspect_err(|e| tracing::warn!("integration event publish failed: {e:?}"))
+            })
         });
     }
 
-    let dispatcher: Arc<InMemoryDispatcher> = Arc::new(CommandDispatcher::new(store.clone(), bus.clone()));
+    // Background subscriber: domain events -> both versions of A/B projections
+    {
+        let sub = bus.subscribe();
+        let projection_versions = projection_versions.clone();
+        let beat = tasks.register("projection_versions");
+        tokio::task::spawn_blocking(move || {
+            supervise(&beat, sub, |env| {
+                let failures = projection_versions.apply(&env);
+                for e in &failures {
+                    tracing::warn!(event_id = %env.event_id(), "projection version apply failed: {e}");
+                }
+                failures.into_iter().next().map_or(Ok(()), Err)
+            })
+        });
+    }
+
+    let rejected_commands = RejectedCommandLog::from_env();
+    let publish_breaker = PublishBreaker::arc(PublishBreakerConfig::from_env());
+    let dispatcher: Arc<InMemoryDispatcher> = Arc::new(configure_dispatcher(
+        CommandDispatcher::new(store.clone(), GuardedEventBus::new(bus.clone(), publish_breaker.clone())),
+        rejected_commands.clone(),
+    ));
+    let command_bus = Arc::new(build_command_bus(dispatcher.clone()));
+    // Background subscriber: Invoice/Payment → Ledger postings
+    {
+        let sub = bus.subscribe();
+        let posting = InvoiceLedgerPosting::new(dispatcher.clone(), tenant_settings.clone());
+        let beat = tasks.register("ledger_posting");
+        tokio::task::spawn_blocking(move || {
+            supervise(&beat, sub, |env| {
+                posting
+                    .handle(&env)
+                    .map(drop)
+                    .inspect_err(|e| tracing::warn!(event_id = %env.event_id(), "ledger posting failed: {e:?}"))
+            })
+        });
+    }
+    // Background subscriber: confirmed/cancelled sales orders → stock reservations
+    {
+        let sub = bus.subscribe();
+        let products_projection = products_projection.clone();
+        let items = move |tenant_id: TenantId, product_id: forgeerp_products::ProductId| {
+            products_projection
+                .get(tenant_id, &product_id)
+                .and_then(|p| p.inventory_item_id)
+                .map(forgeerp_inventory::InventoryItemId::new)
+        };
+        let reservation = SalesStockReservation::new(dispatcher.clone(), tenant_settings.clone(), Arc::new(items));
+        let beat = tasks.register("sales_reservation");
+        tokio::task::spawn_blocking(move || {
+            supervise(&beat, sub, |env| {
+                reservation
+                    .handle(&env)
+                    .map(drop)
+                    .inspect_err(|e| tracing::warn!(event_id = %env.event_id(), "stock reservation failed: {e:?}"))
+            })
+        });
+    }
     // Background subscriber: Sales→Invoice→Ledger saga
     {
         let sub = bus.subscribe();
-        let saga_repo = SagaRepository::<SalesArSaga, _>::new(store.clone());
-        let executor = InMemorySagaExecutor {
+        let saga_repo = Arc::new(SagaRepository::<SalesArSaga, _>::new(store.clone()));
+        let executor = Arc::new(InMemorySagaExecutor {
             dispatcher: dispatcher.clone(),
-            default_ledger_id,
-        };
-        // Sales orders projection to build invoice lines
+        });
+        let timeouts = Arc::new(SagaTimeouts::<SalesArSaga>::new());
+        match timeouts.restore(&saga_repo) {
+            Ok(restored) => tracing::info!(restored, "saga deadlines re-armed"),
+            Err(e) => tracing::warn!("saga deadlines not restored: {e}"),
+        }
+        spawn_saga_timeout_sweep(saga_repo.clone(), executor.clone(), timeouts.clone());
+        // Sales orders projection to build invoice lines (priced in the tenant's currency)
         let sales_projection = sales_projection.clone();
-        tokio::task::spawn_blocking(move || loop {
-            match sub.recv() {
-                Ok(env) => {
-                    if let Some(correlation) = <SalesArSaga as forgeerp_events::Saga>::correlate(&env) {
-                        let tenant_id = env.tenant_id();
-                        let saga_id = <SalesArSaga as forgeerp_events::Saga>::saga_id(tenant_id, &correlation);
-                        // Rehydrate saga state
-                        let mut state = <SalesArSaga as forgeerp_events::Saga>::initial_state(tenant_id, &correlation);
-                        if let Ok(history) = saga_repo.load(tenant_id, saga_id) {
-                            for se in history {
-                                if let Ok(saga_evt) = serde_json::from_value::<forgeerp_infra::saga::sales_ar::SalesArSagaEvent>(se.payload.clone()) {
-                                    <SalesArSaga as forgeerp_events::Saga>::apply(&mut state, &saga_evt);
-                                }
+        let tenant_settings = tenant_settings.clone();
+        let beat = tasks.register("sales_ar_saga");
+        tokio::task::spawn_blocking(move || {
+            supervise(&beat, sub, |env| {
+                if let Some(correlation) = <SalesArSaga as forgeerp_events::Saga>::correlate(&env) {
+                    let tenant_id = env.tenant_id();
+                    let saga_id = <SalesArSaga as forgeerp_events::Saga>::saga_id(tenant_id, &correlation);
+                    // Rehydrate saga state
+                    let state = saga_repo
+                        .rehydrate(tenant_id, &correlation)
+                        .unwrap_or_else(|_| <SalesArSaga as forgeerp_events::Saga>::initial_state(tenant_id, &correlation));
+                    // React
+                    let actions = <SalesArSaga as forgeerp_events::Saga>::react(&state, tenant_id, &correlation, &env);
+                    for action in actions {
+                        match action {
+                            forgeerp_events::SagaAction::Emit { event_type, payload } => {
+                                let _ = saga_repo.append_emit(tenant_id, saga_id, &event_type, payload);
                             }
-                        }
-                        // React
-                        let actions = <SalesArSaga as forgeerp_events::Saga>::react(&state, tenant_id, &correlation, &env);
-                        for action in actions {
-                            match action {
-                                forgeerp_events::SagaAction::Emit { event_type, payload } => {
-                                    let _ = saga_repo.append_emit(tenant_id, saga_id, &event_type, payload);
-                                }
-                                forgeerp_events::SagaAction::Command { aggregate_type, command_type, mut payload } => {
-                                    // Fill IssueInvoice payload from sales read model if missing
-                                    if aggregate_type == "Invoice" && command_type == "IssueInvoice" {
-                                        if let Some(order) = sales_projection.get(tenant_id, &correlation) {
-                                            let invoice_id = forgeerp_invoicing::InvoiceId::new(AggregateId::new());
-                                            let lines: Vec<forgeerp_invoicing::InvoiceLine> = order.lines.iter().map(|l| {
-                                                forgeerp_invoicing::InvoiceLine {
-                                                    line_no: l.line_no,
-                                                    sales_order_id: order.order_id,
-                                                    product_id: l.product_id,
-                                                    quantity: l.quantity,
-                                                    unit_price: l.unit_price,
-                                                }
-                                            }).collect();
-                                            let due = chrono::Utc::now() + chrono::Duration::days(30);
-                                            let obj = payload.as_object_mut().unwrap();
-                                            obj.entry("tenant_id").or_insert(serde_json::json!(tenant_id));
-                                            obj.entry("invoice_id").or_insert(serde_json::json!(invoice_id));
-                                            obj.entry("lines").or_insert(serde_json::json!(lines));
-                                            obj.entry("due_date").or_insert(serde_json::json!(due));
-                                            obj.entry("occurred_at").or_insert(serde_json::json!(chrono::Utc::now()));
+                            forgeerp_events::SagaAction::Command { aggregate_type, command_type, mut payload } => {
+                                // Fill IssueInvoice payload from sales read model if missing
+                                if aggregate_type == "Invoice"
+                                    && command_type == "IssueInvoice"
+                                    && let Some(order) = sales_projection.get(tenant_id, &correlation)
+                                {
+                                    let invoice_id = forgeerp_invoicing::InvoiceId::new(AggregateId::new());
+                                    let lines: Vec<forgeerp_invoicing::InvoiceLine> = order.lines.iter().map(|l| {
+                                        forgeerp_invoicing::InvoiceLine {
+                                            line_no: l.line_no,
+                                            sales_order_id: order.order_id,
+                                            product_id: l.product_id,
+                                            quantity: l.quantity,
+                                            unit_price: l.unit_price,
                                         }
-                                    }
-                                    let _ = executor.execute(tenant_id, &aggregate_type, &command_type, &payload);
-                                }
-                                forgeerp_events::SagaAction::Compensate { aggregate_type, command_type, payload } => {
-                                    let _ = executor.execute(tenant_id, &aggregate_type, &command_type, &payload);
-                                }
-                                forgeerp_events::SagaAction::Complete => {
-                                    let _ = saga_repo.append_emit(tenant_id, saga_id, "saga.completed", serde_json::json!({}));
+                                    }).collect();
+                                    let obj = payload.as_object_mut().unwrap();
+                                    obj.entry("tenant_id").or_insert(serde_json::json!(tenant_id));
+                                    obj.entry("invoice_id").or_insert(serde_json::json!(invoice_id));
+                                    obj.entry("lines").or_insert(serde_json::json!(lines));
+                                    obj.entry("currency")
+                                        .or_insert(serde_json::json!(tenant_settings.get(tenant_id).default_currency));
+                                    obj.entry("occurred_at").or_insert(serde_json::json!(chrono::Utc::now()));
                                 }
+                                let _ = with_correlation_of(&env, || {
+                                    with_business_key_of(&env, || {
+                                        executor.execute(tenant_id, &aggregate_type, &command_type, &payload)
+                                    })
+                                });
+                            }
+                            forgeerp_events::SagaAction::Compensate { aggregate_type, command_type, payload } => {
+                                let _ = with_correlation_of(&env, || {
+                                    with_business_key_of(&env, || {
+                                        executor.execute(tenant_id, &aggregate_type, &command_type, &payload)
+                                    })
+                                });
+                            }
+                            forgeerp_events::SagaAction::Complete => {
+                                let _ = saga_repo.append_emit(tenant_id, saga_id, "saga.completed", serde_json::json!({}));
                             }
                         }
                     }
+                    // (Re-)arm or clear the deadline of the state the saga is now in
+                    if let Ok(state) = saga_repo.rehydrate(tenant_id, &correlation) {
+                        timeouts.track(&saga_repo, tenant_id, &correlation, &state, chrono::Utc::now());
+                    }
                 }
-                Err(_) => break,
-            }
+                Ok::<(), ()>(())
+            })
         });
     }
     AppServices::InMemory {
@@ -460,17 +1209,34 @@ fn build_in_memory_services() -> AppServices {
         event_store: store,
         event_bus: bus,
         inventory_projection,
+        movements_projection,
         parties_projection,
         products_projection,
         sales_projection,
         invoices_projection,
         ar_aging_projection,
         purchases_projection,
+        supplier_performance_projection,
         ledger_projection,
+        trial_balance_projection,
         users_projection,
-        default_ledger_id,
         ai_sink,
+        ai_usage,
+        anomaly_configs,
+        background,
+        tasks,
         realtime_tx,
+        integration_bus,
+        command_bus,
+        tenant_settings,
+        job_store: InMemoryJobStore::arc(),
+        sku_registry,
+        admin_audit: AdminAuditLog::arc(AdminAuditRetention::from_env()),
+        rejected_commands,
+        projection_versions,
+        projection_dead_letters,
+        projection_apply,
+        publish_breaker,
     }
 }
 
@@ -485,6 +1251,7 @@ async fn build_persistent_services() -> AppServices {
         .expect("Failed to connect to Postgres");
 
     let store = Arc::new(PostgresEventStore::new(pool.clone()));
+    spawn_integrity_sweep(store.clone());
 
     let bus = Arc::new(
         RedisStreamsEventBus::new(&redis_url, None, None).expect("Failed to create Redis Streams event bus"),
@@ -493,9 +1260,13 @@ async fn build_persistent_services() -> AppServices {
     bus.ensure_consumer_group("inventory.projection")
         .expect("Failed to create consumer group");
 
+    let ai_insight_store = PostgresAiInsightSink::new(pool.clone(), ai_insight_retention());
+    let projection_cursors: Arc<dyn ProjectionCursorStore> = Arc::new(PostgresCursorStore::new(pool.clone()));
     let rm_store = Arc::new(PostgresInventoryStore::new(pool));
     let inventory_projection: Arc<InventoryStockProjection<_>> =
         Arc::new(InventoryStockProjection::new(rm_store));
+    let movements_projection: Arc<InventoryMovementProjection<_>> =
+        Arc::new(InventoryMovementProjection::new(Arc::new(InMemoryTenantStore::new())));
 
     // Other projections currently use in-memory read models (can be swapped to Postgres later).
     let parties_store: Arc<InMemoryTenantStore<forgeerp_parties::PartyId, PartyReadModel>> =
@@ -507,6 +1278,7 @@ async fn build_persistent_services() -> AppServices {
         Arc::new(InMemoryTenantStore::new());
     let products_projection: Arc<ProductCatalogProjection<_>> =
         Arc::new(ProductCatalogProjection::new(products_store));
+    let sku_registry = SkuRegistry::arc();
 
     let sales_store: Arc<InMemoryTenantStore<forgeerp_sales::SalesOrderId, SalesOrderReadModel>> =
         Arc::new(InMemoryTenantStore::new());
@@ -528,120 +1300,362 @@ async fn build_persistent_services() -> AppServices {
     > = Arc::new(InMemoryTenantStore::new());
     let purchases_projection: Arc<PurchaseOrdersProjection<_>> =
         Arc::new(PurchaseOrdersProjection::new(purchases_store));
+    let supplier_performance_projection: Arc<SupplierPerformanceProjection<_>> =
+        Arc::new(SupplierPerformanceProjection::new(Arc::new(InMemoryTenantStore::new())));
 
     let ledger_store: Arc<InMemoryTenantStore<String, AccountBalance>> = Arc::new(InMemoryTenantStore::new());
     let ledger_projection: Arc<AccountBalancesProjection<_>> =
         Arc::new(AccountBalancesProjection::new(ledger_store));
+    let trial_balance_projection: Arc<TrialBalanceProjection<_>> =
+        Arc::new(TrialBalanceProjection::new(Arc::new(InMemoryTenantStore::new())));
 
     let users_store: Arc<InMemoryTenantStore<UserId, UserReadModel>> = Arc::new(InMemoryTenantStore::new());
     let users_projection: Arc<UsersProjection<_>> = Arc::new(UsersProjection::new(users_store));
-
-    let default_ledger_id = AggregateId::new();
+    let tenant_settings = TenantSettingsProjection::arc();
+    let projection_versions = ProjectionVersionRegistry::arc();
 
     let (realtime_tx, _realtime_rx) = broadcast::channel::<RealtimeMessage>(256);
+    let integration_bus: Arc<InMemoryEventBus<IntegrationEvent>> = Arc::new(InMemoryEventBus::new());
+    let tasks = TaskRegistry::new();
 
-    let ai_sink: Arc<ApiAiInsightSink> = Arc::new(ApiAiInsightSink::new(realtime_tx.clone()));
+    let ai_sink: Arc<ApiAiInsightSink> =
+        Arc::new(ApiAiInsightSink::new(realtime_tx.clone()).with_store(ai_insight_store));
     let ai_runners: Arc<Mutex<HashMap<TenantId, InventoryAnomalyRunnerHandle>>> =
         Arc::new(Mutex::new(HashMap::new()));
-    let ai_runner_cfg = InventoryAnomalyRunner::default();
-
-    {
-        let bus = bus.clone();
+    let ai_backend = AiBackendConfig::from_env();
+    let ai_usage = Arc::new(AiUsageMeter::new(ai_rate_limit()));
+    let anomaly_configs = AnomalyConfigs::in_memory();
+    let ai_runner_cfg = InventoryAnomalyRunner {
+        configs: Some(anomaly_configs.clone()),
+        backend_timeout: ai_backend.timeout,
+        meter: Some(ai_usage.clone()),
+        liveness: Some(tasks.clone()),
+        ..Default::default()
+    };
+    let forecast_runners: Arc<Mutex<HashMap<TenantId, SalesForecastRunnerHandle>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    let forecast_runner_cfg = SalesForecastRunner {
+        backend_timeout: ai_backend.timeout,
+        meter: Some(ai_usage.clone()),
+        liveness: Some(tasks.clone()),
+        ..Default::default()
+    };
+    let reorder_runners: Arc<Mutex<HashMap<TenantId, ReorderSuggestionRunnerHandle>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    let reorder_runner_cfg = ReorderSuggestionRunner {
+        backend_timeout: ai_backend.timeout,
+        meter: Some(ai_usage.clone()),
+        liveness: Some(tasks.clone()),
+        ..Default::default()
+    };
+    let background = Arc::new(BackgroundWorkers::new(vec![
+        ai_runners.clone(),
+        forecast_runners.clone(),
+        reorder_runners.clone(),
+    ]));
+    // Reorder suggestions join stock on hand with the sales of the products stocking it.
+    let reorder_reader = {
+        let products_projection = products_projection.clone();
+        let items = move |tenant_id: TenantId, product_id: forgeerp_products::ProductId| {
+            products_projection
+                .get(tenant_id, &product_id)
+                .and_then(|p| p.inventory_item_id)
+                .map(forgeerp_inventory::InventoryItemId::new)
+        };
+        Arc::new(InventorySalesReader::new(
+            inventory_projection.clone(),
+            sales_projection.clone(),
+            Arc::new(items),
+        ))
+    };
+    let ai_backend = ai_backend.build();
+
+    let projection_dead_letters = InMemoryProjectionDeadLetters::arc();
+    // Stock levels live in Postgres and survive a restart; everything else is rebuilt.
+    let durable_apply: ProjectionApplyFn = {
+        let inventory_projection = inventory_projection.clone();
+        Arc::new(move |env: &EventEnvelope<serde_json::Value>| -> Result<(), String> {
+            match env.aggregate_type() {
+                "inventory.item" => inventory_projection
+                    .apply_envelope(env)
+                    .map_err(|e| format!("projection apply failed: {e}")),
+                _ => Ok(()),
+            }
+        })
+    };
+    let volatile_apply: ProjectionApplyFn = {
         let inventory_projection = inventory_projection.clone();
+        let movements_projection = movements_projection.clone();
         let parties_projection = parties_projection.clone();
         let products_projection = products_projection.clone();
+        let sku_registry = sku_registry.clone();
         let sales_projection = sales_projection.clone();
         let invoices_projection = invoices_projection.clone();
         let ar_aging_projection = ar_aging_projection.clone();
         let purchases_projection = purchases_projection.clone();
+        let supplier_performance_projection = supplier_performance_projection.clone();
         let ledger_projection = ledger_projection.clone();
+        let trial_balance_projection = trial_balance_projection.clone();
         let users_projection = users_projection.clone();
+        let tenant_settings = tenant_settings.clone();
         let ai_sink = ai_sink.clone();
         let ai_runners = ai_runners.clone();
+        let forecast_runners = forecast_runners.clone();
+        let reorder_runners = reorder_runners.clone();
         let realtime_tx = realtime_tx.clone();
+        let redactor = PayloadRedactor::default();
+        Arc::new(move |env: &EventEnvelope<serde_json::Value>| -> Result<(), String> {
+            let at = env.aggregate_type();
+            tracing::debug!(
+                aggregate_type = at,
+                aggregate_id = %env.aggregate_id(),
+                payload = %redactor.redact_envelope(env),
+                "event received"
+            );
+
+            let apply_ok = match at {
+                "inventory.item" => movements_projection.apply_envelope(env).map_err(|e| e.to_string()),
+                "parties.party" => parties_projection.apply_envelope(env).map_err(|e| e.to_string()),
+                "products.product" => products_projection
+                    .apply_envelope(env)
+                    .map_err(|e| e.to_string())
+                    .and_then(|()| sku_registry.apply_envelope(env).map_err(|e| format!("{e:?}"))),
+                "sales.order" => sales_projection.apply_envelope(env).map_err(|e| e.to_string()),
+                "invoicing.invoice" => {
+                    if let Err(e) = invoices_projection.apply_envelope(env) {
+                        Err(e.to_string())
+                    } else if let Err(e) = ar_aging_projection.apply_envelope(env) {
+                        Err(e.to_string())
+                    } else {
+                        Ok(())
+                    }
+                }
+                "purchasing.order" => purchases_projection
+                    .apply_envelope(env)
+                    .map_err(|e| e.to_string())
+                    .and_then(|()| supplier_performance_projection.apply_envelope(env).map_err(|e| e.to_string())),
+                "accounting.ledger" => ledger_projection
+                    .apply_envelope(env)
+                    .map_err(|e| e.to_string())
+                    .and_then(|()| trial_balance_projection.apply_envelope(env).map_err(|e| e.to_string())),
+                "auth.user" => users_projection.apply_envelope(env).map_err(|e| e.to_string()),
+                "tenant.settings" => tenant_settings.apply_envelope(env).map_err(|e| e.to_string()),
+                _ => Ok(()),
+            };
+
+            apply_ok.map_err(|e| format!("projection apply failed: {e}"))?;
+
+            let _ = realtime_tx.send(projection_update_message(env, &redactor));
+
+            if at == "inventory.item" {
+                let tenant_id = env.tenant_id();
+                let mut runners = ai_runners.lock().unwrap();
+                let handle = runners.entry(tenant_id).or_insert_with(|| {
+                    ai_runner_cfg.spawn_for_tenant(
+                        "ai.inventory_anomaly",
+                        tenant_id,
+                        inventory_projection.clone(),
+                        ai_sink.clone(),
+                        ai_backend.clone(),
+                    )
+                });
+                handle.trigger();
+            }
+            if at == "sales.order" {
+                let tenant_id = env.tenant_id();
+                let mut runners = forecast_runners.lock().unwrap();
+                let handle = runners.entry(tenant_id).or_insert_with(|| {
+                    forecast_runner_cfg.spawn_for_tenant(
+                        "ai.sales_forecast",
+                        tenant_id,
+                        sales_projection.clone(),
+                        ai_sink.clone(),
+                        ai_backend.clone(),
+                    )
+                });
+                handle.trigger();
+            }
+            if at == "inventory.item" || at == "sales.order" {
+                let tenant_id = env.tenant_id();
+                let mut runners = reorder_runners.lock().unwrap();
+                let handle = runners.entry(tenant_id).or_insert_with(|| {
+                    reorder_runner_cfg.spawn_for_tenant(
+                        "ai.reorder_suggestion",
+                        tenant_id,
+                        reorder_reader.clone(),
+                        ai_sink.clone(),
+                        ai_backend.clone(),
+                    )
+                });
+                handle.trigger();
+            }
+            Ok(())
+        })
+    };
+    let projection_apply: ProjectionApplyFn = {
+        let (durable, volatile) = (durable_apply.clone(), volatile_apply.clone());
+        Arc::new(move |env: &EventEnvelope<serde_json::Value>| -> Result<(), String> {
+            durable(env)?;
+            volatile(env)
+        })
+    };
+    // Catch up from the event store before following the bus (the stores block on the runtime).
+    let replayed = {
+        let store = store.clone();
+        let cursors = projection_cursors.clone();
+        let dead_letters = projection_dead_letters.clone();
+        tokio::task::spawn_blocking(move || {
+            restore_projections(
+                store.as_ref(),
+                cursors.as_ref(),
+                &durable_apply,
+                &volatile_apply,
+                dead_letters.as_ref(),
+            )
+        })
+        .await
+        .expect("projection restore panicked")
+    };
+    {
+        let bus = bus.clone();
+        let apply = projection_apply.clone();
+        let dead_letters = projection_dead_letters.clone();
+        let cursors = projection_cursors.clone();
+        let relay = IntegrationEventRelay::new(IntegrationEventMapper::default(), integration_bus.clone());
+        let beat = tasks.register("projections");
         tokio::task::spawn_blocking(move || {
             let sub = bus.subscribe_with_group(
                 "inventory.projection",
                 &format!("consumer-{}", uuid::Uuid::now_v7()),
                 None,
             );
-            loop {
-                match sub.recv() {
-                    Ok(env) => {
-                        let at = env.aggregate_type();
-
-                        let apply_ok = match at {
-                            "inventory.item" => inventory_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                            "parties.party" => parties_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                            "products.product" => products_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                            "sales.order" => sales_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                            "invoicing.invoice" => {
-                                if let Err(e) = invoices_projection.apply_envelope(&env) {
-                                    Err(e.to_string())
-                                } else if let Err(e) = ar_aging_projection.apply_envelope(&env) {
-                                    Err(e.to_string())
-                                } else {
-                                    Ok(())
-                                }
-                            }
-                            "purchasing.order" => purchases_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                            "accounting.ledger" => ledger_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                            "auth.user" => users_projection.apply_envelope(&env).map_err(|e| e.to_string()),
-                            _ => Ok(()),
-                        };
-
-                        if let Err(e) = apply_ok {
-                            tracing::warn!("projection apply failed: {e}");
-                            continue;
-                        }
+            supervise(&beat, sub, |env| {
+                if let Err(e) = relay.relay(&env) {
+                    tracing::warn!("integration event publish failed: {e:?}");
+                }
+                let (tenant_id, position) = (env.tenant_id(), env.global_position());
+                let restored = replayed.get_position(tenant_id, PROJECTIONS_SUBSCRIBER);
+                if position.is_some_and(|p| restored.is_some_and(|r| p <= r)) {
+                    return Ok(());
+                }
+                let applied = apply_or_dead_letter(&apply, dead_letters.as_ref(), env).map_err(drop);
+                // One sequential consumer: everything up to this position has been handled.
+                if let Some(position) = position {
+                    cursors.update_position(tenant_id, PROJECTIONS_SUBSCRIBER, position);
+                }
+                applied
+            });
+        });
+    }
 
-                        let _ = realtime_tx.send(RealtimeMessage {
-                            tenant_id: env.tenant_id(),
-                            topic: format!("{at}.projection_updated"),
-                            payload: serde_json::json!({
-                                "kind": "projection_update",
-                                "aggregate_type": at,
-                                "aggregate_id": env.aggregate_id().to_string(),
-                                "sequence_number": env.sequence_number(),
-                            }),
-                        });
-
-                        if at == "inventory.item" {
-                            let tenant_id = env.tenant_id();
-                            let mut runners = ai_runners.lock().unwrap();
-                            let handle = runners.entry(tenant_id).or_insert_with(|| {
-                                ai_runner_cfg.spawn_for_tenant(
-                                    "ai.inventory_anomaly",
-                                    tenant_id,
-                                    inventory_projection.clone(),
-                                    ai_sink.clone(),
-                                )
-                            });
-                            handle.trigger();
-                        }
-                    }
-                    Err(_) => break,
+    bus.ensure_consumer_group("projections.versions")
+        .expect("Failed to create consumer group");
+    {
+        let bus = bus.clone();
+        let projection_versions = projection_versions.clone();
+        let beat = tasks.register("projection_versions");
+        tokio::task::spawn_blocking(move || {
+            let sub = bus.subscribe_with_group(
+                "projections.versions",
+                &format!("consumer-{}", uuid::Uuid::now_v7()),
+                None,
+            );
+            supervise(&beat, sub, |env| {
+                let failures = projection_versions.apply(&env);
+                for e in &failures {
+                    tracing::warn!(event_id = %env.event_id(), "projection version apply failed: {e}");
                 }
-            }
+                failures.into_iter().next().map_or(Ok(()), Err)
+            })
         });
     }
 
-    let dispatcher: Arc<PersistentDispatcher> = Arc::new(CommandDispatcher::new(store.clone(), bus.clone()));
+    let rejected_commands = RejectedCommandLog::from_env();
+    let publish_breaker = PublishBreaker::arc(PublishBreakerConfig::from_env());
+    let dispatcher: Arc<PersistentDispatcher> = Arc::new(configure_dispatcher(
+        CommandDispatcher::new(store.clone(), GuardedEventBus::new(bus.clone(), publish_breaker.clone())),
+        rejected_commands.clone(),
+    ));
+    let command_bus = Arc::new(build_command_bus(dispatcher.clone()));
+    bus.ensure_consumer_group("invoice.ledger_posting")
+        .expect("Failed to create consumer group");
+    {
+        let bus = bus.clone();
+        let posting = InvoiceLedgerPosting::new(dispatcher.clone(), tenant_settings.clone());
+        let beat = tasks.register("ledger_posting");
+        tokio::task::spawn_blocking(move || {
+            let sub = bus.subscribe_with_group(
+                "invoice.ledger_posting",
+                &format!("consumer-{}", uuid::Uuid::now_v7()),
+                None,
+            );
+            supervise(&beat, sub, |env| {
+                posting
+                    .handle(&env)
+                    .map(drop)
+                    .inspect_err(|e| tracing::warn!(event_id = %env.event_id(), "ledger posting failed: {e:?}"))
+            })
+        });
+    }
+    bus.ensure_consumer_group("sales.stock_reservation")
+        .expect("Failed to create consumer group");
+    {
+        let bus = bus.clone();
+        let products_projection = products_projection.clone();
+        let items = move |tenant_id: TenantId, product_id: forgeerp_products::ProductId| {
+            products_projection
+                .get(tenant_id, &product_id)
+                .and_then(|p| p.inventory_item_id)
+                .map(forgeerp_inventory::InventoryItemId::new)
+        };
+        let reservation = SalesStockReservation::new(dispatcher.clone(), tenant_settings.clone(), Arc::new(items));
+        let beat = tasks.register("sales_reservation");
+        tokio::task::spawn_blocking(move || {
+            let sub = bus.subscribe_with_group(
+                "sales.stock_reservation",
+                &format!("consumer-{}", uuid::Uuid::now_v7()),
+                None,
+            );
+            supervise(&beat, sub, |env| {
+                reservation
+                    .handle(&env)
+                    .map(drop)
+                    .inspect_err(|e| tracing::warn!(event_id = %env.event_id(), "stock reservation failed: {e:?}"))
+            })
+        });
+    }
     AppServices::Persistent {
         dispatcher,
         event_store: store,
         inventory_projection,
+        movements_projection,
         parties_projection,
         products_projection,
         sales_projection,
         invoices_projection,
         ar_aging_projection,
         purchases_projection,
+        supplier_performance_projection,
         ledger_projection,
+        trial_balance_projection,
         users_projection,
-        default_ledger_id,
         ai_sink,
+        ai_usage,
+        anomaly_configs,
+        background,
+        tasks,
         realtime_tx,
+        integration_bus,
+        command_bus,
+        tenant_settings,
+        job_store: InMemoryJobStore::arc(),
+        sku_registry,
+        admin_audit: AdminAuditLog::arc(AdminAuditRetention::from_env()),
+        rejected_commands,
+        projection_versions,
+        projection_dead_letters,
+        projection_apply,
+        publish_breaker,
         bus,
     }
 }
@@ -655,48 +1669,279 @@ impl AppServices {
         }
     }
 
-    pub fn ai_sink(&self) -> &Arc<ApiAiInsightSink> {
+    /// Public bus carrying curated integration events (see `forgeerp_infra::integration_events`).
+    pub fn integration_bus(&self) -> &Arc<InMemoryEventBus<IntegrationEvent>> {
         match self {
-            AppServices::InMemory { ai_sink, .. } => ai_sink,
+            AppServices::InMemory { integration_bus, .. } => integration_bus,
             #[cfg(feature = "redis")]
-            AppServices::Persistent { ai_sink, .. } => ai_sink,
+            AppServices::Persistent { integration_bus, .. } => integration_bus,
         }
     }
 
-    pub fn default_ledger_id(&self) -> AggregateId {
+    /// Per-tenant settings (default currency, rounding, posting accounts, ...) and their history.
+    pub fn tenant_settings(&self) -> &Arc<TenantSettingsProjection> {
         match self {
-            AppServices::InMemory { default_ledger_id, .. } => *default_ledger_id,
+            AppServices::InMemory { tenant_settings, .. } => tenant_settings,
             #[cfg(feature = "redis")]
-            AppServices::Persistent { default_ledger_id, .. } => *default_ledger_id,
+            AppServices::Persistent { tenant_settings, .. } => tenant_settings,
         }
     }
 
-    pub fn dispatch<A>(
-        &self,
-        tenant_id: TenantId,
-        aggregate_id: AggregateId,
-        aggregate_type: &str,
-        command: A::Command,
-        make_aggregate: impl FnOnce(TenantId, AggregateId) -> A,
-    ) -> Result<Vec<StoredEvent>, DispatchError>
-    where
-        A: forgeerp_core::Aggregate<Error = DomainError>,
-        A::Event: forgeerp_events::Event + serde::Serialize + serde::de::DeserializeOwned,
-    {
+    /// Change tenant settings through their event stream; returns the new settings.
+    pub fn change_settings(&self, command: TenantSettingsCommand) -> Result<TenantSettings, DispatchError> {
         match self {
-            AppServices::InMemory { dispatcher, .. } => dispatcher.dispatch::<A>(
-                tenant_id,
-                aggregate_id,
-                aggregate_type,
-                command,
-                make_aggregate,
-            ),
+            AppServices::InMemory {
+                dispatcher,
+                tenant_settings,
+                ..
+            } => change_settings(dispatcher, tenant_settings, command),
             #[cfg(feature = "redis")]
-            AppServices::Persistent { dispatcher, .. } => dispatcher.dispatch::<A>(
-                tenant_id,
-                aggregate_id,
-                aggregate_type,
-                command,
+            AppServices::Persistent {
+                dispatcher,
+                tenant_settings,
+                ..
+            } => change_settings(dispatcher, tenant_settings, command),
+        }
+    }
+
+    /// Append-only trail of privileged admin operations (replays, purges).
+    pub fn admin_audit(&self) -> &Arc<AdminAuditLog> {
+        match self {
+            AppServices::InMemory { admin_audit, .. } => admin_audit,
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { admin_audit, .. } => admin_audit,
+        }
+    }
+
+    /// Log of commands the domain rejected, when enabled.
+    pub fn rejected_commands(&self) -> Option<&Arc<RejectedCommandLog>> {
+        match self {
+            AppServices::InMemory { rejected_commands, .. } => rejected_commands.as_ref(),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { rejected_commands, .. } => rejected_commands.as_ref(),
+        }
+    }
+
+    /// Projections running a primary and a candidate version side by side.
+    pub fn projection_versions(&self) -> &Arc<ProjectionVersionRegistry> {
+        match self {
+            AppServices::InMemory { projection_versions, .. } => projection_versions,
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { projection_versions, .. } => projection_versions,
+        }
+    }
+
+    /// Envelopes the projection subscriber failed to apply, kept for replay.
+    pub fn projection_dead_letters(&self) -> &Arc<InMemoryProjectionDeadLetters> {
+        match self {
+            AppServices::InMemory { projection_dead_letters, .. } => projection_dead_letters,
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { projection_dead_letters, .. } => projection_dead_letters,
+        }
+    }
+
+    /// Apply a projection dead letter again; it is removed once it applies.
+    pub fn replay_projection_dead_letter(
+        &self,
+        tenant_id: TenantId,
+        id: uuid::Uuid,
+    ) -> Result<ProjectionDeadLetter, ProjectionDeadLetterError> {
+        match self {
+            AppServices::InMemory {
+                projection_dead_letters,
+                projection_apply,
+                ..
+            } => replay_dead_letter(projection_dead_letters.as_ref(), tenant_id, id, projection_apply.as_ref()),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent {
+                projection_dead_letters,
+                projection_apply,
+                ..
+            } => replay_dead_letter(projection_dead_letters.as_ref(), tenant_id, id, projection_apply.as_ref()),
+        }
+    }
+
+    /// Ping the event store and the event bus.
+    ///
+    /// In-memory ones live in this process and are reported healthy without a check.
+    pub async fn check_dependencies(&self) -> Vec<DependencyStatus> {
+        match self {
+            AppServices::InMemory { .. } => {
+                vec![DependencyStatus::healthy("event_store"), DependencyStatus::healthy("event_bus")]
+            }
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { event_store, bus, .. } => {
+                let store = match tokio::time::timeout(DEPENDENCY_CHECK_TIMEOUT, event_store.ping()).await {
+                    Ok(result) => result.map_err(|e| e.to_string()),
+                    Err(_) => Err("timed out".to_string()),
+                };
+                let bus = bus.clone();
+                let bus = match tokio::task::spawn_blocking(move || bus.ping(DEPENDENCY_CHECK_TIMEOUT)).await {
+                    Ok(result) => result.map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                vec![
+                    DependencyStatus::from_result("event_store", store),
+                    DependencyStatus::from_result("event_bus", bus),
+                ]
+            }
+        }
+    }
+
+    /// Stop the background projection worker and AI runners (blocking; see
+    /// [`BackgroundWorkers`]). Call once the server stopped taking requests.
+    pub fn shutdown(&self) {
+        match self {
+            AppServices::InMemory { background, .. } => background.shutdown(),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { background, .. } => background.shutdown(),
+        }
+    }
+
+    /// Circuit breaker on command dispatch's bus publishes.
+    pub fn publish_breaker(&self) -> &Arc<PublishBreaker> {
+        match self {
+            AppServices::InMemory { publish_breaker, .. } => publish_breaker,
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { publish_breaker, .. } => publish_breaker,
+        }
+    }
+
+    /// SKU reservations of live products (tenant-wide SKU uniqueness).
+    pub fn sku_registry(&self) -> &Arc<SkuRegistry> {
+        match self {
+            AppServices::InMemory { sku_registry, .. } => sku_registry,
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { sku_registry, .. } => sku_registry,
+        }
+    }
+
+    /// Validate and create products in bulk (see `forgeerp_infra::product_import`).
+    pub fn import_products(
+        &self,
+        tenant_id: TenantId,
+        rows: &[ImportRow],
+        mode: ImportMode,
+    ) -> Result<ImportReport, DispatchError> {
+        let settings = self.tenant_settings().get(tenant_id);
+        match self {
+            AppServices::InMemory { dispatcher, sku_registry, .. } => {
+                product_import::import_products(dispatcher, sku_registry, &settings, tenant_id, rows, mode)
+            }
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { dispatcher, sku_registry, .. } => {
+                product_import::import_products(dispatcher, sku_registry, &settings, tenant_id, rows, mode)
+            }
+        }
+    }
+
+    /// Background job queue (including its dead-letter queue).
+    pub fn job_store(&self) -> &Arc<InMemoryJobStore> {
+        match self {
+            AppServices::InMemory { job_store, .. } => job_store,
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { job_store, .. } => job_store,
+        }
+    }
+
+    /// Per-tenant AI rate limits and usage counters.
+    pub fn ai_usage(&self) -> &Arc<AiUsageMeter> {
+        match self {
+            AppServices::InMemory { ai_usage, .. } => ai_usage,
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { ai_usage, .. } => ai_usage,
+        }
+    }
+
+    /// Per-tenant inventory anomaly detection settings.
+    pub fn anomaly_configs(&self) -> &AnomalyConfigs {
+        match self {
+            AppServices::InMemory { anomaly_configs, .. } => anomaly_configs,
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { anomaly_configs, .. } => anomaly_configs,
+        }
+    }
+
+    pub fn ai_sink(&self) -> &Arc<ApiAiInsightSink> {
+        match self {
+            AppServices::InMemory { ai_sink, .. } => ai_sink,
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { ai_sink, .. } => ai_sink,
+        }
+    }
+
+    /// Send a command through the typed command bus (authorization + routing).
+    pub fn send<C: forgeerp_events::Command>(
+        &self,
+        principal: &forgeerp_auth::Principal,
+        command: C,
+    ) -> Result<Vec<StoredEvent>, forgeerp_infra::command_bus::CommandBusError> {
+        match self {
+            AppServices::InMemory { command_bus, .. } => command_bus.send(principal, command),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { command_bus, .. } => command_bus.send(principal, command),
+        }
+    }
+
+    pub fn dispatch<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: &str,
+        command: A::Command,
+        make_aggregate: impl Fn(TenantId, AggregateId) -> A,
+    ) -> Result<Vec<StoredEvent>, DispatchError>
+    where
+        A: forgeerp_core::Aggregate<Error = DomainError> + serde::Serialize + serde::de::DeserializeOwned,
+        A::Event: forgeerp_events::Event + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        match self {
+            AppServices::InMemory { dispatcher, .. } => dispatcher.dispatch::<A>(
+                tenant_id,
+                aggregate_id,
+                aggregate_type,
+                command,
+                make_aggregate,
+            ),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { dispatcher, .. } => dispatcher.dispatch::<A>(
+                tenant_id,
+                aggregate_id,
+                aggregate_type,
+                command,
+                make_aggregate,
+            ),
+        }
+    }
+
+    /// Like [`dispatch`](Self::dispatch), rejecting stale edits (see `VersionedCommand`).
+    pub fn dispatch_versioned<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: &str,
+        command: A::Command,
+        make_aggregate: impl Fn(TenantId, AggregateId) -> A,
+    ) -> Result<Vec<StoredEvent>, DispatchError>
+    where
+        A: forgeerp_core::Aggregate<Error = DomainError> + serde::Serialize + serde::de::DeserializeOwned,
+        A::Command: forgeerp_core::VersionedCommand,
+        A::Event: forgeerp_events::Event + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        match self {
+            AppServices::InMemory { dispatcher, .. } => dispatcher.dispatch_versioned::<A>(
+                tenant_id,
+                aggregate_id,
+                aggregate_type,
+                command,
+                make_aggregate,
+            ),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { dispatcher, .. } => dispatcher.dispatch_versioned::<A>(
+                tenant_id,
+                aggregate_id,
+                aggregate_type,
+                command,
                 make_aggregate,
             ),
         }
@@ -714,6 +1959,28 @@ impl AppServices {
         }
     }
 
+    /// Stock movements of an item, oldest first.
+    pub fn inventory_movements(
+        &self,
+        tenant_id: TenantId,
+        item_id: &forgeerp_inventory::InventoryItemId,
+    ) -> Option<InventoryMovementHistory> {
+        match self {
+            AppServices::InMemory { movements_projection, .. } => movements_projection.get(tenant_id, item_id),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { movements_projection, .. } => movements_projection.get(tenant_id, item_id),
+        }
+    }
+
+    /// Highest inventory item watermark for a tenant.
+    pub fn inventory_watermark(&self, tenant_id: TenantId) -> Watermark {
+        match self {
+            AppServices::InMemory { inventory_projection, .. } => inventory_projection.watermark(tenant_id),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { inventory_projection, .. } => inventory_projection.watermark(tenant_id),
+        }
+    }
+
     pub fn products_get(
         &self,
         tenant_id: TenantId,
@@ -734,6 +2001,54 @@ impl AppServices {
         }
     }
 
+    /// One page of the live products that `keep` accepts, ordered by `order`.
+    pub fn products_list_page(
+        &self,
+        tenant_id: TenantId,
+        keep: &dyn Fn(&ProductReadModel) -> bool,
+        order: &dyn Fn(&ProductReadModel, &ProductReadModel) -> Ordering,
+        start: PageStart<'_, ProductReadModel>,
+        limit: usize,
+    ) -> ListPage<ProductReadModel> {
+        match self {
+            AppServices::InMemory { products_projection, .. } => products_projection.list_page(tenant_id, keep, order, start, limit),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { products_projection, .. } => products_projection.list_page(tenant_id, keep, order, start, limit),
+        }
+    }
+
+    /// Deleted products (tombstones).
+    pub fn products_tombstones(&self, tenant_id: TenantId) -> Vec<ProductReadModel> {
+        match self {
+            AppServices::InMemory { products_projection, .. } => products_projection.list_tombstones(tenant_id),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { products_projection, .. } => products_projection.list_tombstones(tenant_id),
+        }
+    }
+
+    /// Sales order, invoice and purchase order lines referencing `product_id`.
+    pub fn product_references(&self, tenant_id: TenantId, product_id: forgeerp_products::ProductId) -> u64 {
+        let sales = self
+            .sales_list(tenant_id)
+            .iter()
+            .flat_map(|o| &o.lines)
+            .filter(|l| l.product_id == product_id)
+            .count();
+        let invoices = self
+            .invoices_list(tenant_id)
+            .iter()
+            .flat_map(|i| &i.lines)
+            .filter(|l| l.product_id == product_id)
+            .count();
+        let purchases = self
+            .purchases_list(tenant_id)
+            .iter()
+            .flat_map(|o| &o.lines)
+            .filter(|l| l.product_id == product_id)
+            .count();
+        (sales + invoices + purchases) as u64
+    }
+
     pub fn parties_get(
         &self,
         tenant_id: TenantId,
@@ -754,6 +2069,27 @@ impl AppServices {
         }
     }
 
+    /// One page of the parties of `kind` that `keep` accepts, ordered by `order`.
+    pub fn parties_list_page(
+        &self,
+        tenant_id: TenantId,
+        kind: forgeerp_parties::PartyKind,
+        keep: &dyn Fn(&PartyReadModel) -> bool,
+        order: &dyn Fn(&PartyReadModel, &PartyReadModel) -> Ordering,
+        start: PageStart<'_, PartyReadModel>,
+        limit: usize,
+    ) -> ListPage<PartyReadModel> {
+        match self {
+            AppServices::InMemory { parties_projection, .. } => {
+                parties_projection.list_page(tenant_id, kind, keep, order, start, limit)
+            }
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { parties_projection, .. } => {
+                parties_projection.list_page(tenant_id, kind, keep, order, start, limit)
+            }
+        }
+    }
+
     pub fn sales_get(
         &self,
         tenant_id: TenantId,
@@ -774,6 +2110,22 @@ impl AppServices {
         }
     }
 
+    /// One page of the sales orders that `keep` accepts, ordered by `order`.
+    pub fn sales_list_page(
+        &self,
+        tenant_id: TenantId,
+        keep: &dyn Fn(&SalesOrderReadModel) -> bool,
+        order: &dyn Fn(&SalesOrderReadModel, &SalesOrderReadModel) -> Ordering,
+        start: PageStart<'_, SalesOrderReadModel>,
+        limit: usize,
+    ) -> ListPage<SalesOrderReadModel> {
+        match self {
+            AppServices::InMemory { sales_projection, .. } => sales_projection.list_page(tenant_id, keep, order, start, limit),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { sales_projection, .. } => sales_projection.list_page(tenant_id, keep, order, start, limit),
+        }
+    }
+
     pub fn invoices_get(
         &self,
         tenant_id: TenantId,
@@ -794,6 +2146,22 @@ impl AppServices {
         }
     }
 
+    /// One page of the invoices that `keep` accepts, ordered by `order`.
+    pub fn invoices_list_page(
+        &self,
+        tenant_id: TenantId,
+        keep: &dyn Fn(&InvoiceReadModel) -> bool,
+        order: &dyn Fn(&InvoiceReadModel, &InvoiceReadModel) -> Ordering,
+        start: PageStart<'_, InvoiceReadModel>,
+        limit: usize,
+    ) -> ListPage<InvoiceReadModel> {
+        match self {
+            AppServices::InMemory { invoices_projection, .. } => invoices_projection.list_page(tenant_id, keep, order, start, limit),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { invoices_projection, .. } => invoices_projection.list_page(tenant_id, keep, order, start, limit),
+        }
+    }
+
     pub fn ar_aging_list(&self, tenant_id: TenantId) -> Vec<InvoiceAgingReadModel> {
         match self {
             AppServices::InMemory { ar_aging_projection, .. } => ar_aging_projection.list(tenant_id),
@@ -822,6 +2190,40 @@ impl AppServices {
         }
     }
 
+    /// One page of the purchase orders that `keep` accepts, ordered by `order`.
+    pub fn purchases_list_page(
+        &self,
+        tenant_id: TenantId,
+        keep: &dyn Fn(&PurchaseOrderReadModel) -> bool,
+        order: &dyn Fn(&PurchaseOrderReadModel, &PurchaseOrderReadModel) -> Ordering,
+        start: PageStart<'_, PurchaseOrderReadModel>,
+        limit: usize,
+    ) -> ListPage<PurchaseOrderReadModel> {
+        match self {
+            AppServices::InMemory { purchases_projection, .. } => purchases_projection.list_page(tenant_id, keep, order, start, limit),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { purchases_projection, .. } => purchases_projection.list_page(tenant_id, keep, order, start, limit),
+        }
+    }
+
+    /// Delivery and spend metrics of a supplier over orders completed in `window`.
+    pub fn supplier_performance(
+        &self,
+        tenant_id: TenantId,
+        supplier_id: forgeerp_parties::PartyId,
+        window: PerformanceWindow,
+    ) -> SupplierPerformance {
+        match self {
+            AppServices::InMemory { supplier_performance_projection, .. } => {
+                supplier_performance_projection.performance(tenant_id, supplier_id, window)
+            }
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { supplier_performance_projection, .. } => {
+                supplier_performance_projection.performance(tenant_id, supplier_id, window)
+            }
+        }
+    }
+
     pub fn ledger_balances_list(&self, tenant_id: TenantId) -> Vec<AccountBalance> {
         match self {
             AppServices::InMemory { ledger_projection, .. } => ledger_projection.list(tenant_id),
@@ -838,6 +2240,17 @@ impl AppServices {
         }
     }
 
+    /// Debit/credit totals per account kind, with the overall balance check.
+    pub fn trial_balance(&self, tenant_id: TenantId) -> TrialBalance {
+        match self {
+            AppServices::InMemory { trial_balance_projection, .. } => trial_balance_projection.trial_balance(tenant_id),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { trial_balance_projection, .. } => {
+                trial_balance_projection.trial_balance(tenant_id)
+            }
+        }
+    }
+
     pub fn users_get(&self, tenant_id: TenantId, user_id: &UserId) -> Option<UserReadModel> {
         match self {
             AppServices::InMemory { users_projection, .. } => users_projection.get(tenant_id, user_id),
@@ -874,6 +2287,30 @@ impl AppServices {
         }
     }
 
+    /// When the principal was last suspended as a user of `tenant_id`.
+    pub fn users_suspended_at(&self, tenant_id: TenantId, principal_id: PrincipalId) -> Option<chrono::DateTime<chrono::Utc>> {
+        let user_id = UserId::from_uuid(*principal_id.as_uuid());
+        match self {
+            AppServices::InMemory { users_projection, .. } => users_projection.suspended_at(tenant_id, &user_id),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { users_projection, .. } => users_projection.suspended_at(tenant_id, &user_id),
+        }
+    }
+
+    /// The principal's membership in `tenant_id` (active users only).
+    pub fn users_membership(&self, tenant_id: TenantId, principal_id: PrincipalId) -> Option<TenantMembership> {
+        let user_id = UserId::from_uuid(*principal_id.as_uuid());
+        match self {
+            AppServices::InMemory { users_projection, .. } => {
+                users_projection.membership(tenant_id, &user_id, default_role_permissions)
+            }
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { users_projection, .. } => {
+                users_projection.membership(tenant_id, &user_id, default_role_permissions)
+            }
+        }
+    }
+
     /// Query events with filters and pagination.
     pub async fn query_events(
         &self,
@@ -892,6 +2329,25 @@ impl AppServices {
         }
     }
 
+    /// Query events with keyset paging (see `EventQuery::query_events_after`).
+    pub async fn query_events_after(
+        &self,
+        tenant_id: TenantId,
+        filter: EventFilter,
+        after: Option<EventCursor>,
+        limit: u32,
+    ) -> Result<EventPage, forgeerp_infra::event_store::EventStoreError> {
+        match self {
+            AppServices::InMemory { event_store, .. } => {
+                event_store.query_events_after(tenant_id, filter, after, limit).await
+            }
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { event_store, .. } => {
+                event_store.query_events_after(tenant_id, filter, after, limit).await
+            }
+        }
+    }
+
     /// Get events for a specific aggregate.
     pub async fn get_aggregate_events(
         &self,
@@ -910,6 +2366,47 @@ impl AppServices {
         }
     }
 
+    /// Every event of an aggregate's stream, in sequence order.
+    pub async fn get_all_aggregate_events(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+    ) -> Result<Vec<StoredEvent>, forgeerp_infra::event_store::EventStoreError> {
+        match self {
+            AppServices::InMemory { event_store, .. } => {
+                event_store.get_all_aggregate_events(tenant_id, aggregate_id).await
+            }
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { event_store, .. } => {
+                event_store.get_all_aggregate_events(tenant_id, aggregate_id).await
+            }
+        }
+    }
+
+    /// First event of an aggregate's stream, if it was ever written.
+    pub async fn first_event(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+    ) -> Result<Option<StoredEvent>, forgeerp_infra::event_store::EventStoreError> {
+        let page = self
+            .get_aggregate_events(tenant_id, aggregate_id, Some(Pagination { limit: 1, offset: 0 }))
+            .await?;
+        Ok(page.events.into_iter().next())
+    }
+
+    /// Event store usage statistics for a tenant.
+    pub async fn tenant_stats(
+        &self,
+        tenant_id: TenantId,
+    ) -> Result<TenantStats, forgeerp_infra::event_store::EventStoreError> {
+        match self {
+            AppServices::InMemory { event_store, .. } => event_store.tenant_stats(tenant_id),
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { event_store, .. } => event_store.as_ref().tenant_stats(tenant_id).await,
+        }
+    }
+
     /// Get a single event by its ID.
     pub async fn get_event_by_id(
         &self,
@@ -927,6 +2424,15 @@ impl AppServices {
         }
     }
 
+    /// Background tasks (subscribers, runners) and their liveness.
+    pub fn tasks(&self) -> &TaskRegistry {
+        match self {
+            AppServices::InMemory { tasks, .. } => tasks,
+            #[cfg(feature = "redis")]
+            AppServices::Persistent { tasks, .. } => tasks,
+        }
+    }
+
     /// Get the event store for replay operations (InMemory).
     pub fn event_store_in_memory(&self) -> Option<Arc<InMemoryEventStore>> {
         match self {
@@ -963,4 +2469,269 @@ pub fn tenant_sse_stream(
     Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
 }
 
+/// What a coalesced SSE message carries for a topic updated several times in one window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoalesceMode {
+    /// Only the most recent payload (same shape as an uncoalesced message).
+    Latest,
+    /// `{"kind": "batch", "updates": [...]}` with every payload, oldest first.
+    List,
+}
+
+/// Batch updates per topic within `window` into one SSE message (`/stream?coalesce_ms=`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coalescing {
+    pub window: Duration,
+    pub mode: CoalesceMode,
+}
+
+impl CoalesceMode {
+    fn merge(self, mut payloads: Vec<serde_json::Value>) -> serde_json::Value {
+        match self {
+            CoalesceMode::Latest => payloads.pop().unwrap_or_default(),
+            CoalesceMode::List => serde_json::json!({ "kind": "batch", "updates": payloads }),
+        }
+    }
+}
+
+/// Like [`tenant_sse_stream`], but each topic gets at most one message per coalescing window.
+pub fn tenant_sse_stream_coalesced(
+    services: Arc<AppServices>,
+    tenant_id: TenantId,
+    coalescing: Coalescing,
+) -> Sse<impl tokio_stream::Stream<Item = Result<SseEvent, Infallible>>> {
+    let updates = coalesced_updates(services.realtime_tx().subscribe(), tenant_id, coalescing);
+    let stream = updates.map(|(topic, payload)| {
+        let data = serde_json::to_string(&payload).unwrap_or_else(|_| "{}".to_string());
+        Ok(SseEvent::default().event(topic).data(data))
+    });
 
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
+}
+
+/// The tenant's `(topic, payload)` updates, merged per topic over each window.
+///
+/// A window opens with the first update after the previous flush; at its end every topic
+/// seen in it is flushed once, in order of first appearance.
+fn coalesced_updates(
+    mut rx: broadcast::Receiver<RealtimeMessage>,
+    tenant_id: TenantId,
+    coalescing: Coalescing,
+) -> UnboundedReceiverStream<(String, serde_json::Value)> {
+    let (tx, out) = unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(first) = next_tenant_message(&mut rx, tenant_id).await {
+            let deadline = tokio::time::Instant::now() + coalescing.window;
+            let mut topics: Vec<(String, Vec<serde_json::Value>)> = vec![(first.topic, vec![first.payload])];
+            while let Ok(Some(m)) = tokio::time::timeout_at(deadline, next_tenant_message(&mut rx, tenant_id)).await {
+                match topics.iter_mut().find(|(topic, _)| *topic == m.topic) {
+                    Some((_, payloads)) => payloads.push(m.payload),
+                    None => topics.push((m.topic, vec![m.payload])),
+                }
+            }
+            for (topic, payloads) in topics {
+                if tx.send((topic, coalescing.mode.merge(payloads))).is_err() {
+                    return; // client went away
+                }
+            }
+        }
+    });
+    UnboundedReceiverStream::new(out)
+}
+
+/// Next message for `tenant_id`; `None` once the channel closes.
+async fn next_tenant_message(
+    rx: &mut broadcast::Receiver<RealtimeMessage>,
+    tenant_id: TenantId,
+) -> Option<RealtimeMessage> {
+    loop {
+        match rx.recv().await {
+            Ok(m) if m.tenant_id == tenant_id => return Some(m),
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(tenant_id: TenantId, topic: &str, n: u64) -> RealtimeMessage {
+        RealtimeMessage {
+            tenant_id,
+            topic: topic.to_string(),
+            payload: serde_json::json!({ "sequence_number": n }),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rapid_updates_to_a_topic_are_coalesced_into_one_message() {
+        let (tx, rx) = broadcast::channel(64);
+        let tenant_id = TenantId::new();
+        let coalescing = Coalescing {
+            window: Duration::from_millis(100),
+            mode: CoalesceMode::Latest,
+        };
+        let mut updates = coalesced_updates(rx, tenant_id, coalescing);
+
+        for n in 1..=5 {
+            tx.send(update(tenant_id, "inventory.item.projection_updated", n)).unwrap();
+        }
+        tx.send(update(tenant_id, "sales.order.projection_updated", 1)).unwrap();
+        tx.send(update(TenantId::new(), "inventory.item.projection_updated", 99)).unwrap();
+
+        let (topic, payload) = updates.next().await.unwrap();
+        assert_eq!(topic, "inventory.item.projection_updated");
+        assert_eq!(payload["sequence_number"], 5);
+        let (topic, payload) = updates.next().await.unwrap();
+        assert_eq!((topic.as_str(), payload["sequence_number"].as_u64()), ("sales.order.projection_updated", Some(1)));
+
+        // An update after the window starts the next batch.
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        tx.send(update(tenant_id, "inventory.item.projection_updated", 6)).unwrap();
+        drop(tx);
+        assert_eq!(updates.next().await.unwrap().1["sequence_number"], 6);
+        assert!(updates.next().await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn list_mode_delivers_every_update_of_the_window_in_one_message() {
+        let (tx, rx) = broadcast::channel(64);
+        let tenant_id = TenantId::new();
+        let coalescing = Coalescing {
+            window: Duration::from_millis(100),
+            mode: CoalesceMode::List,
+        };
+        let mut updates = coalesced_updates(rx, tenant_id, coalescing);
+
+        for n in 1..=3 {
+            tx.send(update(tenant_id, "products.product.projection_updated", n)).unwrap();
+        }
+        drop(tx);
+
+        let (_, payload) = updates.next().await.unwrap();
+        assert_eq!(payload["kind"], "batch");
+        let sequence: Vec<_> = payload["updates"].as_array().unwrap().iter().map(|u| u["sequence_number"].clone()).collect();
+        assert_eq!(sequence, [1, 2, 3]);
+        assert!(updates.next().await.is_none());
+    }
+
+    #[test]
+    fn failed_projection_apply_is_dead_lettered_and_replayable() {
+        let poisoned = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let flag = poisoned.clone();
+        let apply: ProjectionApplyFn = Arc::new(move |_env: &EventEnvelope<serde_json::Value>| {
+            if flag.load(std::sync::atomic::Ordering::SeqCst) {
+                Err("projection apply failed: unknown item".to_string())
+            } else {
+                Ok(())
+            }
+        });
+        let dead_letters = InMemoryProjectionDeadLetters::new();
+        let tenant_id = TenantId::new();
+        let env = EventEnvelope::new(
+            uuid::Uuid::now_v7(),
+            tenant_id,
+            AggregateId::new(),
+            "inventory.item",
+            1,
+            serde_json::json!({}),
+        );
+
+        assert!(apply_or_dead_letter(&apply, &dead_letters, env.clone()).is_err());
+        let letters = dead_letters.list(tenant_id).unwrap();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].envelope, env);
+        assert_eq!(letters[0].error, "projection apply failed: unknown item");
+
+        poisoned.store(false, std::sync::atomic::Ordering::SeqCst);
+        replay_dead_letter(&dead_letters, tenant_id, letters[0].dead_letter_id, apply.as_ref()).unwrap();
+        assert!(dead_letters.list(tenant_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn restart_rebuilds_volatile_models_and_resumes_durable_ones() {
+        use forgeerp_core::aggregate::ExpectedVersion;
+        use forgeerp_infra::event_store::{EventStore, InMemoryEventStore, UncommittedEvent};
+
+        let tenant_id = TenantId::new();
+        let store = InMemoryEventStore::new();
+        let append = || {
+            store
+                .append(
+                    vec![UncommittedEvent {
+                        event_id: uuid::Uuid::now_v7(),
+                        tenant_id,
+                        aggregate_id: AggregateId::new(),
+                        aggregate_type: "inventory.item".to_string(),
+                        event_type: "inventory.item.created".to_string(),
+                        event_version: 1,
+                        occurred_at: chrono::Utc::now(),
+                        payload: serde_json::json!({}),
+                        metadata: Default::default(),
+                        correlation_id: None,
+                        causation_id: None,
+                    }],
+                    ExpectedVersion::Exact(0),
+                )
+                .unwrap();
+        };
+        let recorder = |seen: &Arc<Mutex<Vec<u64>>>| -> ProjectionApplyFn {
+            let seen = seen.clone();
+            Arc::new(move |env: &EventEnvelope<serde_json::Value>| {
+                seen.lock().unwrap().push(env.global_position().unwrap());
+                Ok(())
+            })
+        };
+        // The cursor store and the durable read model survive restarts.
+        let cursors = InMemoryCursorStore::new();
+        let durable = Arc::new(Mutex::new(Vec::new()));
+        let start = || {
+            let volatile = Arc::new(Mutex::new(Vec::new()));
+            let replayed = restore_projections(
+                &store,
+                &cursors,
+                &recorder(&durable),
+                &recorder(&volatile),
+                &InMemoryProjectionDeadLetters::new(),
+            );
+            let volatile = volatile.lock().unwrap().clone();
+            (volatile, replayed.get_position(tenant_id, PROJECTIONS_SUBSCRIBER))
+        };
+
+        append();
+        append();
+        assert_eq!(start(), (vec![1, 2], Some(2)));
+        assert_eq!(cursors.get_position(tenant_id, PROJECTIONS_SUBSCRIBER), Some(2));
+
+        // Committed while the process is down.
+        append();
+        assert_eq!(start(), (vec![1, 2, 3], Some(3)));
+        assert_eq!(*durable.lock().unwrap(), vec![1, 2, 3]);
+        assert_eq!(cursors.get_position(tenant_id, PROJECTIONS_SUBSCRIBER), Some(3));
+    }
+
+    #[tokio::test]
+    async fn insights_are_read_per_tenant_and_kind() {
+        let (tx, mut rx) = broadcast::channel(16);
+        let sink = ApiAiInsightSink::new(tx);
+        let tenant_id = TenantId::new();
+        let insight = |kind: AiInsightKind, key: &str| {
+            AiResult::new(1.0, 1.0)
+                .with_metadata(serde_json::json!({ "kind": kind.as_str() }))
+                .with_idempotency_key(key)
+        };
+        sink.emit(tenant_id, insight(AiInsightKind::InventoryAnomalyDetection, "a"));
+        sink.emit(tenant_id, insight(AiInsightKind::InventoryAnomalyDetection, "a"));
+        sink.emit(tenant_id, insight(AiInsightKind::SalesForecast, "f"));
+        sink.emit(TenantId::new(), insight(AiInsightKind::InventoryAnomalyDetection, "a"));
+
+        let anomalies = sink.insights(tenant_id, AiInsightKind::InventoryAnomalyDetection).await.unwrap();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].idempotency_key.as_deref(), Some("a"));
+        // The replaced copy was not announced again.
+        let announced = std::iter::from_fn(|| rx.try_recv().ok()).filter(|m| m.tenant_id == tenant_id).count();
+        assert_eq!(announced, 2);
+    }
+}
@@ -3,10 +3,35 @@
 //! This enforces authorization at the command boundary (before dispatch),
 //! while keeping domain aggregates and infra auth-agnostic.
 
-use forgeerp_auth::{AuthzError, CommandAuthorization, Permission, Principal, TenantMembership, authorize};
+use serde::Serialize;
+
+use forgeerp_auth::{explain_authorization, CommandAuthorization, DenialKind, Permission, Principal, TenantMembership};
+use forgeerp_infra::projections::default_role_permissions;
 
 use crate::context::{PrincipalContext, TenantContext};
 
+/// Why a command was refused.
+///
+/// Only states what the principal itself lacked; it never names the roles or
+/// permissions that would have granted the command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[error("{}", denied_message(*.kind, .missing_permissions))]
+pub struct CommandDenied {
+    pub kind: DenialKind,
+    /// Required permissions the principal does not hold (all of them, not just the first).
+    pub missing_permissions: Vec<String>,
+}
+
+fn denied_message(kind: DenialKind, missing: &[String]) -> String {
+    match kind {
+        DenialKind::TenantMismatch => "tenant mismatch".to_string(),
+        DenialKind::MissingPermission => {
+            let missing: Vec<String> = missing.iter().map(|p| format!("'{p}'")).collect();
+            format!("forbidden: missing permission {}", missing.join(", "))
+        }
+    }
+}
+
 /// Check authorization for a command in the current request context.
 ///
 /// This is intended to be called **before** dispatching a command.
@@ -14,31 +39,53 @@ pub fn authorize_command<C: CommandAuthorization>(
     tenant: &TenantContext,
     principal: &PrincipalContext,
     command: &C,
-) -> Result<(), AuthzError> {
+) -> Result<(), CommandDenied> {
+    let principal = principal_for(tenant, principal);
+
+    let mut missing_permissions = Vec::new();
+    for perm in command.required_permissions() {
+        let explanation = explain_authorization(&principal, perm, default_role_permissions);
+        match explanation.denial_reason.map(|reason| reason.kind) {
+            None => {}
+            Some(DenialKind::TenantMismatch) => {
+                return Err(CommandDenied {
+                    kind: DenialKind::TenantMismatch,
+                    missing_permissions: Vec::new(),
+                });
+            }
+            Some(DenialKind::MissingPermission) => missing_permissions.push(explanation.required_permission),
+        }
+    }
+
+    if missing_permissions.is_empty() {
+        Ok(())
+    } else {
+        Err(CommandDenied {
+            kind: DenialKind::MissingPermission,
+            missing_permissions,
+        })
+    }
+}
+
+/// Build the auth `Principal` for the current request context.
+pub fn principal_for(tenant: &TenantContext, principal: &PrincipalContext) -> Principal {
     let membership = TenantMembership {
         tenant_id: tenant.tenant_id(),
         roles: principal.roles().to_vec(),
         permissions: permissions_from_roles(principal.roles()),
     };
 
-    let principal = Principal {
+    Principal {
         principal_id: principal.principal_id(),
         active_tenant_id: tenant.tenant_id(),
         membership,
-    };
-
-    for perm in command.required_permissions() {
-        authorize(&principal, perm)?;
     }
-
-    Ok(())
 }
 
 /// Map roles to their granted permissions using the default role-permission mapping.
 ///
 /// This function uses the default role-to-permission mapping from the infra projections.
 pub fn permissions_from_roles(roles: &[forgeerp_auth::Role]) -> Vec<Permission> {
-    use forgeerp_infra::projections::default_role_permissions;
     use std::collections::HashSet;
 
     let mut all_permissions: HashSet<String> = HashSet::new();
@@ -53,4 +100,41 @@ pub fn permissions_from_roles(roles: &[forgeerp_auth::Role]) -> Vec<Permission>
     all_permissions.into_iter().map(Permission::new).collect()
 }
 
+#[cfg(test)]
+mod tests {
+    use forgeerp_auth::{PrincipalId, Role};
+    use forgeerp_core::TenantId;
 
+    use crate::app::routes::common::CmdAuth;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn denial_lists_every_missing_permission_and_nothing_about_other_roles() {
+        let tenant = TenantContext::new(TenantId::new());
+        let principal = PrincipalContext::new(PrincipalId::new(), vec![Role::new("salesperson")]);
+        let command = CmdAuth {
+            inner: (),
+            required: vec![
+                Permission::new("sales.write"),
+                Permission::new("ledger.write"),
+                Permission::new("admin.users.suspend"),
+            ],
+        };
+
+        let denied = authorize_command(&tenant, &principal, &command).unwrap_err();
+        assert_eq!(denied.kind, DenialKind::MissingPermission);
+        assert_eq!(denied.missing_permissions, vec!["ledger.write", "admin.users.suspend"]);
+
+        let response = crate::app::errors::command_denied_to_response(denied);
+        assert_eq!(response.status(), axum::http::StatusCode::FORBIDDEN);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "forbidden");
+        assert_eq!(body["denial"]["kind"], "missing_permission");
+        assert_eq!(body["denial"]["missing_permissions"], serde_json::json!(["ledger.write", "admin.users.suspend"]));
+        // Neither the principal's grants nor the roles that would grant the command.
+        let text = body.to_string();
+        assert!(!text.contains("salesperson") && !text.contains("admin\"") && !text.contains("sales.read"));
+    }
+}
@@ -7,16 +7,39 @@ use forgeerp_core::TenantId;
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct TenantContext {
     tenant_id: TenantId,
+    /// The token's own tenant when a platform admin acts in `tenant_id` instead.
+    home_tenant_id: Option<TenantId>,
 }
 
 impl TenantContext {
     pub fn new(tenant_id: TenantId) -> Self {
-        Self { tenant_id }
+        Self {
+            tenant_id,
+            home_tenant_id: None,
+        }
     }
 
+    /// Context for a cross-tenant override: act in `tenant_id`, authenticated in `home_tenant_id`.
+    pub fn acting_as(tenant_id: TenantId, home_tenant_id: TenantId) -> Self {
+        Self {
+            tenant_id,
+            home_tenant_id: Some(home_tenant_id),
+        }
+    }
+
+    /// Tenant the request operates in.
     pub fn tenant_id(&self) -> TenantId {
         self.tenant_id
     }
+
+    /// Tenant the principal authenticated in (differs from `tenant_id` under an override).
+    pub fn home_tenant_id(&self) -> TenantId {
+        self.home_tenant_id.unwrap_or(self.tenant_id)
+    }
+
+    pub fn is_cross_tenant(&self) -> bool {
+        self.home_tenant_id.is_some()
+    }
 }
 
 /// Principal context for a request (authenticated identity + roles).
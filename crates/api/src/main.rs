@@ -1,5 +1,16 @@
-#[tokio::main]
-async fn main() {
+use std::time::Duration;
+
+/// How long to wait for leftover blocking tasks once the server and workers stopped.
+const RUNTIME_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn main() {
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start tokio runtime");
+    runtime.block_on(run());
+    // Bus subscribers run on blocking threads until the process exits; don't wait on them.
+    runtime.shutdown_timeout(RUNTIME_SHUTDOWN_TIMEOUT);
+}
+
+async fn run() {
     forgeerp_observability::init();
 
     let jwt_secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| {
@@ -7,7 +18,7 @@ async fn main() {
         "dev-secret".to_string()
     });
 
-    let app = forgeerp_api::app::build_app(jwt_secret).await;
+    let (app, services) = forgeerp_api::app::build_app_with_services(jwt_secret).await;
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080")
         .await
@@ -15,5 +26,12 @@ async fn main() {
 
     tracing::info!("listening on {}", listener.local_addr().unwrap());
 
-    axum::serve(listener, app).await.unwrap();
+    forgeerp_api::app::shutdown::serve(listener, app, forgeerp_api::app::shutdown::shutdown_signal())
+        .await
+        .unwrap();
+
+    tracing::info!("server stopped; stopping background workers");
+    if let Err(e) = tokio::task::spawn_blocking(move || services.shutdown()).await {
+        tracing::warn!(error = %e, "background workers did not stop cleanly");
+    }
 }
@@ -1,20 +1,99 @@
-use std::sync::Arc;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
 
 use axum::{
+    body::{Body, Bytes, HttpBody},
     extract::State,
-    http::{HeaderMap, StatusCode},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
+    Json,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
 
-use forgeerp_auth::JwtValidator;
+use forgeerp_auth::{
+    admin, authorize_exact, check_issued_after, check_not_revoked, ApiKeyValidator, JwtClaims, JwtValidator, PrincipalId, RevocationStore,
+    TenantMembership, TokenValidationError,
+};
+use forgeerp_core::TenantId;
+use forgeerp_events::BUSINESS_KEY;
+use forgeerp_infra::clock::{Clock, SystemClock};
+use forgeerp_infra::command_dispatcher::{with_command_principal, with_dry_run, with_envelope_metadata, RetryReport};
+use forgeerp_infra::idempotency::fingerprint_of;
 
+use crate::app::errors;
+use crate::app::routes::common::IDEMPOTENCY_KEY_HEADER;
+use crate::app::routes::events;
 use crate::context::{PrincipalContext, TenantContext};
 
+/// Request header naming the tenant a platform admin wants to act in.
+pub const ACT_AS_TENANT_HEADER: &str = "x-act-as-tenant";
+
+/// Request header carrying a service account's API key (used without a bearer token).
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Request header selecting which of the principal's tenant memberships to act in.
+pub const TENANT_HEADER: &str = "x-tenant-id";
+
+/// Request header grouping the request's events under a business process key.
+pub const BUSINESS_KEY_HEADER: &str = "x-business-key";
+
+/// Looks up a principal's membership in a tenant (`None` when they are not a member).
+pub type MembershipLookup = Arc<dyn Fn(PrincipalId, TenantId) -> Option<TenantMembership> + Send + Sync>;
+
+/// Looks up when a principal was last suspended in a tenant (`None` if never).
+pub type SuspensionLookup = Arc<dyn Fn(PrincipalId, TenantId) -> Option<DateTime<Utc>> + Send + Sync>;
+
 #[derive(Clone)]
 pub struct AuthState {
     pub jwt: Arc<dyn JwtValidator>,
+    /// Service-account keys accepted in `X-Api-Key` (none by default).
+    pub api_keys: Arc<ApiKeyValidator>,
+    /// When set, bearer tokens must carry a `jti` that has not been revoked.
+    pub revocations: Option<Arc<dyn RevocationStore>>,
+    /// Memberships consulted for `X-Tenant-Id` (without it only the token's tenant is allowed).
+    pub memberships: Option<MembershipLookup>,
+    /// Suspensions: bearer tokens issued at or before one are refused.
+    pub suspensions: Option<SuspensionLookup>,
+}
+
+impl AuthState {
+    /// Authenticate with `jwt` (`Hs256JwtValidator`, `Rs256JwtValidator` or any other).
+    pub fn new(jwt: Arc<dyn JwtValidator>) -> Self {
+        Self {
+            jwt,
+            api_keys: Arc::new(ApiKeyValidator::new()),
+            revocations: None,
+            memberships: None,
+            suspensions: None,
+        }
+    }
+
+    /// Let principals pick another tenant they belong to with `X-Tenant-Id`.
+    pub fn with_memberships(mut self, memberships: MembershipLookup) -> Self {
+        self.memberships = Some(memberships);
+        self
+    }
+
+    /// Reject bearer tokens issued before the principal's last suspension.
+    pub fn with_suspensions(mut self, suspensions: SuspensionLookup) -> Self {
+        self.suspensions = Some(suspensions);
+        self
+    }
+
+    /// Reject revoked tokens (and tokens without a `jti`).
+    pub fn with_revocations(mut self, revocations: Arc<dyn RevocationStore>) -> Self {
+        self.revocations = Some(revocations);
+        self
+    }
+
+    /// Also accept service-account API keys.
+    pub fn with_api_keys(mut self, api_keys: Arc<ApiKeyValidator>) -> Self {
+        self.api_keys = api_keys;
+        self
+    }
 }
 
 pub async fn auth_middleware(
@@ -22,21 +101,573 @@ pub async fn auth_middleware(
     mut req: axum::http::Request<axum::body::Body>,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    let token = extract_bearer(req.headers())?;
+    let headers = req.headers();
+    let identity =
+        if headers.contains_key(axum::http::header::AUTHORIZATION) || !headers.contains_key(API_KEY_HEADER) {
+            match bearer_claims(&state, headers).await {
+                Ok(claims) => bearer_identity(&state, headers, claims),
+                Err(status) => Err(status),
+            }
+        } else {
+            api_key_identity(&state, headers)
+        };
+    let (principal, tenant) = match identity {
+        Ok(identity) => identity,
+        // Authentication only forbids acting in a tenant the principal may not access.
+        Err(StatusCode::FORBIDDEN) => {
+            return Ok(errors::json_error(
+                StatusCode::FORBIDDEN,
+                "tenant_isolation",
+                "principal may not act in the requested tenant",
+            ));
+        }
+        Err(status) => return Err(status),
+    };
+    let principal_id = principal.principal_id();
+
+    req.extensions_mut().insert(tenant);
+    req.extensions_mut().insert(principal);
+
+    let mut metadata = BTreeMap::new();
+    if let Some(key) = business_key(req.headers())? {
+        metadata.insert(BUSINESS_KEY.to_string(), key);
+    }
+
+    if tenant.is_cross_tenant() {
+        tracing::info!(
+            principal_id = %principal_id,
+            home_tenant_id = %tenant.home_tenant_id(),
+            tenant_id = %tenant.tenant_id(),
+            method = %req.method(),
+            path = %req.uri().path(),
+            "cross-tenant override"
+        );
+        metadata.insert("acting_principal_id".to_string(), principal_id.to_string());
+        metadata.insert("home_tenant_id".to_string(), tenant.home_tenant_id().to_string());
+    }
+
+    // Commands the domain rejects are logged with the sender (when the log is enabled).
+    let run = with_command_principal(principal_id, next.run(req));
+    if metadata.is_empty() {
+        return Ok(run.await);
+    }
+    Ok(with_envelope_metadata(metadata, run).await)
+}
+
+/// Claims of the bearer JWT. A token signed with a key the validator has not loaded yet
+/// (the identity provider rotated its keys) is checked again after a key refresh.
+async fn bearer_claims(state: &AuthState, headers: &HeaderMap) -> Result<JwtClaims, StatusCode> {
+    let token = extract_bearer(headers)?;
+    let claims = match state.jwt.validate(token, Utc::now()) {
+        Err(TokenValidationError::UnknownKey(_)) if state.jwt.refresh_keys().await => {
+            state.jwt.validate(token, Utc::now())
+        }
+        claims => claims,
+    };
+    claims.map_err(|_e| StatusCode::UNAUTHORIZED)
+}
+
+/// Principal and tenant from the bearer JWT's claims (and an allowed `X-Act-As-Tenant`).
+fn bearer_identity(
+    state: &AuthState,
+    headers: &HeaderMap,
+    claims: JwtClaims,
+) -> Result<(PrincipalContext, TenantContext), StatusCode> {
+    if let Some(revocations) = &state.revocations {
+        check_not_revoked(revocations.as_ref(), &claims).map_err(|_e| StatusCode::UNAUTHORIZED)?;
+    }
+    check_not_suspended(state, &claims, claims.tenant_id)?;
+
+    if let Some(membership) = selected_membership(state, headers, &claims)? {
+        check_not_suspended(state, &claims, membership.tenant_id)?;
+        let principal = PrincipalContext::new(claims.sub, membership.roles);
+        return Ok((principal, TenantContext::new(membership.tenant_id)));
+    }
+
+    let principal = PrincipalContext::new(claims.sub, claims.roles.clone());
+    let tenant = match act_as_tenant(headers, &claims, &principal)? {
+        Some(target) => TenantContext::acting_as(target, claims.tenant_id),
+        None => TenantContext::new(claims.tenant_id),
+    };
+    Ok((principal, tenant))
+}
 
-    let claims = state
-        .jwt
-        .validate(token, Utc::now())
-        .map_err(|_e| StatusCode::UNAUTHORIZED)?;
+/// Refuse a token issued at or before the principal's last suspension in `tenant_id`.
+fn check_not_suspended(state: &AuthState, claims: &JwtClaims, tenant_id: TenantId) -> Result<(), StatusCode> {
+    let Some(lookup) = &state.suspensions else {
+        return Ok(());
+    };
+    check_issued_after(lookup(claims.sub, tenant_id), claims).map_err(|_e| StatusCode::UNAUTHORIZED)
+}
 
-    req.extensions_mut()
-        .insert(TenantContext::new(claims.tenant_id));
-    req.extensions_mut().insert(PrincipalContext::new(
-        claims.sub,
-        claims.roles.clone(),
-    ));
+/// Membership named by `X-Tenant-Id`, when it differs from the token's tenant.
+///
+/// The token's tenant claim alone does not grant access to another tenant: the principal
+/// must be an active member there, and acts with the roles of that membership.
+fn selected_membership(
+    state: &AuthState,
+    headers: &HeaderMap,
+    claims: &JwtClaims,
+) -> Result<Option<TenantMembership>, StatusCode> {
+    let Some(value) = headers.get(TENANT_HEADER) else {
+        return Ok(None);
+    };
+    let target: TenantId = value
+        .to_str()
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    if target == claims.tenant_id {
+        return Ok(None);
+    }
+    // Switching membership and a cross-tenant override do not combine.
+    if headers.contains_key(ACT_AS_TENANT_HEADER) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    state
+        .memberships
+        .as_ref()
+        .and_then(|lookup| lookup(claims.sub, target))
+        .map(Some)
+        .ok_or(StatusCode::FORBIDDEN)
+}
+
+/// Service account from `X-Api-Key` (only consulted without an `Authorization` header).
+///
+/// A key acts in its own tenant only: `X-Act-As-Tenant` or `X-Tenant-Id` naming any other is refused.
+fn api_key_identity(state: &AuthState, headers: &HeaderMap) -> Result<(PrincipalContext, TenantContext), StatusCode> {
+    let key = headers
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let account = state.api_keys.validate(key).map_err(|_e| StatusCode::UNAUTHORIZED)?;
+
+    for value in [headers.get(ACT_AS_TENANT_HEADER), headers.get(TENANT_HEADER)].into_iter().flatten() {
+        let target: TenantId = value
+            .to_str()
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        if target != account.tenant_id {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+    Ok((PrincipalContext::new(account.principal_id, account.roles), TenantContext::new(account.tenant_id)))
+}
+
+/// Business process key from `X-Business-Key`, recorded on every event the request
+/// dispatches. Blank or overlong (over 200 bytes) keys are rejected.
+fn business_key(headers: &HeaderMap) -> Result<Option<String>, StatusCode> {
+    let Some(value) = headers.get(BUSINESS_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value.to_str().map_err(|_| StatusCode::BAD_REQUEST)?.trim();
+    if key.is_empty() || key.len() > 200 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(Some(key.to_string()))
+}
+
+/// Target tenant from `X-Act-As-Tenant`, if present and allowed.
+///
+/// Naming the token's own tenant is not an override. Any other tenant requires an exact
+/// `admin.cross_tenant` grant in the home tenant (the `platform_admin` role; a tenant
+/// admin's `"*"` does not count); without it the request is rejected.
+fn act_as_tenant(
+    headers: &HeaderMap,
+    claims: &JwtClaims,
+    principal: &PrincipalContext,
+) -> Result<Option<TenantId>, StatusCode> {
+    let Some(value) = headers.get(ACT_AS_TENANT_HEADER) else {
+        return Ok(None);
+    };
+    let target: TenantId = value
+        .to_str()
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    if target == claims.tenant_id {
+        return Ok(None);
+    }
+
+    let home = crate::authz::principal_for(&TenantContext::new(claims.tenant_id), principal);
+    authorize_exact(&home, &admin::CROSS_TENANT).map_err(|_| StatusCode::FORBIDDEN)?;
+    Ok(Some(target))
+}
+
+/// Response header carrying the number of dispatch attempts made for the request.
+pub const RETRY_ATTEMPTS_HEADER: &str = "retry-attempts";
+
+tokio::task_local! {
+    static RETRY_REPORT: Cell<Option<RetryReport>>;
+}
+
+/// Record the dispatcher's retry report for the current request (no-op outside one).
+pub fn record_retry(report: &RetryReport) {
+    let _ = RETRY_REPORT.try_with(|cell| cell.set(Some(*report)));
+}
+
+/// Retry report recorded so far in the current request, if any.
+pub fn current_retry() -> Option<RetryReport> {
+    RETRY_REPORT.try_with(Cell::get).ok().flatten()
+}
+
+/// Adds `Retry-Attempts` to responses of requests that dispatched a command.
+pub async fn retry_middleware(req: axum::http::Request<axum::body::Body>, next: Next) -> Response {
+    RETRY_REPORT
+        .scope(Cell::new(None), async {
+            let mut response = next.run(req).await;
+            if let Some(report) = current_retry() {
+                response
+                    .headers_mut()
+                    .insert(RETRY_ATTEMPTS_HEADER, HeaderValue::from(report.attempts));
+            }
+            response
+        })
+        .await
+}
+
+/// Query parameter asking a POST route to preview its command instead of committing it.
+pub const DRY_RUN_PARAM: &str = "dry_run";
+
+/// `?dry_run=true` on a POST runs the handler as a dry run.
+///
+/// Authorization and validation happen as usual and failures are returned unchanged;
+/// a handler that would have succeeded answers 200 with the events its dispatches
+/// would have appended (plus warnings), and nothing is persisted or published.
+pub async fn dry_run_middleware(req: axum::http::Request<axum::body::Body>, next: Next) -> Response {
+    let requested = req
+        .uri()
+        .query()
+        .into_iter()
+        .flat_map(|q| q.split('&'))
+        .find_map(|pair| match pair.split_once('=') {
+            Some((DRY_RUN_PARAM, value)) => Some(value.to_string()),
+            None if pair == DRY_RUN_PARAM => Some("true".to_string()),
+            _ => None,
+        });
+    let dry_run = match requested.as_deref() {
+        None | Some("false") => false,
+        Some("true") => true,
+        Some(other) => {
+            return errors::json_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_query",
+                format!("dry_run must be `true` or `false`, got `{other}`"),
+            );
+        }
+    };
+    if !dry_run {
+        return next.run(req).await;
+    }
+    if req.method() != Method::POST {
+        return errors::json_error(
+            StatusCode::BAD_REQUEST,
+            "dry_run_unsupported",
+            "dry_run is only supported on POST",
+        );
+    }
+
+    let (response, preview) = with_dry_run(next.run(req)).await;
+    if !response.status().is_success() {
+        return response;
+    }
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "dry_run": true,
+            "events": preview.events.iter().map(events::event_to_json).collect::<Vec<_>>(),
+            "warnings": preview.warnings,
+        })),
+    )
+        .into_response()
+}
+
+/// Response header set (to `true`) on a response replayed for a repeated idempotency key.
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Largest request or response body the idempotency middleware buffers (axum's default
+/// request body limit). Larger responses are passed through without being stored.
+pub const IDEMPOTENCY_MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// `(tenant, principal, key, route)`.
+type IdempotencyKey = (TenantId, Option<PrincipalId>, Uuid, String);
+
+/// A stored response and the request body it answered.
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    fingerprint: u64,
+    status: StatusCode,
+    content_type: Option<HeaderValue>,
+    body: Bytes,
+    stored_at: DateTime<Utc>,
+}
+
+impl IntoResponse for CachedResponse {
+    fn into_response(self) -> Response {
+        let mut response = (self.status, self.body).into_response();
+        let headers = response.headers_mut();
+        match self.content_type {
+            Some(content_type) => headers.insert(header::CONTENT_TYPE, content_type),
+            None => headers.remove(header::CONTENT_TYPE),
+        };
+        headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+/// Responses to POST requests that carried an `Idempotency-Key`, per
+/// `(tenant, principal, key, route)`, kept for a TTL.
+///
+/// Sits in front of the dispatcher's idempotency log: that one keeps a retried command
+/// from committing twice, this one answers the retry with the first response itself
+/// (status and body), also for routes that dispatch several commands or none.
+///
+/// Stored bodies are capped in total (`max_bytes`); past the cap the oldest responses
+/// are dropped first, so an evicted key runs again like an expired one.
+///
+/// A key is reserved while its first request runs, so a concurrent repeat is refused
+/// instead of running the command a second time.
+pub struct IdempotencyCache {
+    ttl: chrono::Duration,
+    max_bytes: usize,
+    clock: Arc<dyn Clock>,
+    entries: Mutex<HashMap<IdempotencyKey, CachedResponse>>,
+    /// Keys whose request is running, with its body fingerprint (locked after `entries`).
+    in_flight: Mutex<HashMap<IdempotencyKey, u64>>,
+}
+
+/// What to do with a request carrying an idempotency key.
+enum Admission {
+    /// Answer with the stored response.
+    Replay(CachedResponse),
+    /// The key was used for a different body.
+    Mismatch,
+    /// The key's first request is still running.
+    InFlight,
+    /// Run the request; the key stays reserved until the guard is dropped.
+    Run(InFlightGuard),
+}
+
+/// Reservation of a key while its request runs; released on drop (also when the
+/// handler fails or the request is cancelled).
+struct InFlightGuard {
+    cache: Arc<IdempotencyCache>,
+    key: IdempotencyKey,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let mut in_flight = self.cache.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        in_flight.remove(&self.key);
+    }
+}
+
+impl std::fmt::Debug for IdempotencyCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdempotencyCache")
+            .field("ttl", &self.ttl)
+            .field("max_bytes", &self.max_bytes)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for IdempotencyCache {
+    /// Responses are replayed for 24 hours, like the dispatcher's idempotency log.
+    fn default() -> Self {
+        Self::new(chrono::Duration::hours(24))
+    }
+}
+
+impl IdempotencyCache {
+    /// Default cap on the stored response bodies.
+    pub const DEFAULT_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+    pub fn new(ttl: chrono::Duration) -> Self {
+        Self {
+            ttl,
+            max_bytes: Self::DEFAULT_MAX_BYTES,
+            clock: Arc::new(SystemClock),
+            entries: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Time source for expiry (defaults to the system clock).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Cap on the total size of stored response bodies (default `DEFAULT_MAX_BYTES`).
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Look up `key` and, when it has neither a stored response nor a running request,
+    /// reserve it, in one step.
+    fn admit(self: &Arc<Self>, key: &IdempotencyKey, fingerprint: u64) -> Admission {
+        let cutoff = self.clock.now() - self.ttl;
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some(cached) if cached.stored_at > cutoff => {
+                return if cached.fingerprint == fingerprint {
+                    Admission::Replay(cached.clone())
+                } else {
+                    Admission::Mismatch
+                };
+            }
+            Some(_) => {
+                entries.remove(key);
+            }
+            None => {}
+        }
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        match in_flight.get(key) {
+            Some(running) if *running == fingerprint => Admission::InFlight,
+            Some(_) => Admission::Mismatch,
+            None => {
+                in_flight.insert(key.clone(), fingerprint);
+                Admission::Run(InFlightGuard {
+                    cache: self.clone(),
+                    key: key.clone(),
+                })
+            }
+        }
+    }
+
+    /// Store a response (stamped with the cache's clock), dropping expired ones and then
+    /// the oldest until the bodies fit in `max_bytes`.
+    fn store(&self, key: IdempotencyKey, mut cached: CachedResponse) {
+        if cached.body.len() > self.max_bytes {
+            return;
+        }
+        let now = self.clock.now();
+        let cutoff = now - self.ttl;
+        cached.stored_at = now;
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, cached| cached.stored_at > cutoff);
+        entries.insert(key, cached);
+
+        let mut total: usize = entries.values().map(|cached| cached.body.len()).sum();
+        if total > self.max_bytes {
+            let mut oldest: Vec<_> = entries.iter().map(|(key, cached)| (cached.stored_at, key.clone())).collect();
+            oldest.sort_by_key(|(stored_at, _)| *stored_at);
+            for (_, key) in oldest {
+                if total <= self.max_bytes {
+                    break;
+                }
+                if let Some(evicted) = entries.remove(&key) {
+                    total -= evicted.body.len();
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Replays the stored response of a POST whose `Idempotency-Key` was seen before.
+///
+/// Keys are scoped to the tenant, the principal and the route (path and query). Reusing a
+/// key with a different body is rejected (`400 validation_error`), and repeating it while
+/// the first request still runs answers `409 idempotency_in_progress`. 5xx and 429 responses
+/// are not stored, so a retry after a transient failure runs again. Request bodies over
+/// `IDEMPOTENCY_MAX_BODY_BYTES` are rejected (`413`); larger responses are returned but
+/// not stored. Requests without a key, or with one that is not a UUID (left for the
+/// handler to reject), pass through.
+pub async fn idempotency_middleware(
+    State(cache): State<Arc<IdempotencyCache>>,
+    req: axum::http::Request<Body>,
+    next: Next,
+) -> Response {
+    if req.method() != Method::POST {
+        return next.run(req).await;
+    }
+    let Some(tenant) = req.extensions().get::<TenantContext>().copied() else {
+        return next.run(req).await;
+    };
+    let Some(key) = req
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<Uuid>().ok())
+    else {
+        return next.run(req).await;
+    };
+    let route = req
+        .uri()
+        .path_and_query()
+        .map_or_else(|| req.uri().path().to_string(), |pq| pq.as_str().to_string());
+    let principal_id = req.extensions().get::<PrincipalContext>().map(|p| p.principal_id());
+    let key = (tenant.tenant_id(), principal_id, key, route);
+
+    if req.body().size_hint().lower() > IDEMPOTENCY_MAX_BODY_BYTES as u64 {
+        return errors::json_error(StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large", "request body is too large");
+    }
+    let (parts, body) = req.into_parts();
+    let body = match axum::body::to_bytes(body, IDEMPOTENCY_MAX_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => return errors::json_error(StatusCode::BAD_REQUEST, "invalid_body", e.to_string()),
+    };
+    let fingerprint = fingerprint_of(&body);
+
+    let _in_flight = match cache.admit(&key, fingerprint) {
+        Admission::Replay(cached) => return cached.into_response(),
+        Admission::Mismatch => {
+            return errors::json_error(
+                StatusCode::BAD_REQUEST,
+                "validation_error",
+                "idempotency key was already used for a different request",
+            );
+        }
+        Admission::InFlight => {
+            return errors::json_error(
+                StatusCode::CONFLICT,
+                "idempotency_in_progress",
+                "a request with this idempotency key is still in progress",
+            );
+        }
+        Admission::Run(guard) => guard,
+    };
+
+    let response = next.run(axum::http::Request::from_parts(parts, Body::from(body))).await;
+    let status = response.status();
+    if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        return response;
+    }
 
-    Ok(next.run(req).await)
+    let fits = response
+        .body()
+        .size_hint()
+        .upper()
+        .is_some_and(|len| len <= IDEMPOTENCY_MAX_BODY_BYTES as u64);
+    if !fits {
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let Ok(body) = axum::body::to_bytes(body, IDEMPOTENCY_MAX_BODY_BYTES).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    cache.store(
+        key,
+        CachedResponse {
+            fingerprint,
+            status,
+            content_type: parts.headers.get(header::CONTENT_TYPE).cloned(),
+            body: body.clone(),
+            stored_at: Utc::now(),
+        },
+    );
+    Response::from_parts(parts, Body::from(body))
 }
 
 fn extract_bearer(headers: &HeaderMap) -> Result<&str, StatusCode> {
@@ -58,3 +689,618 @@ fn extract_bearer(headers: &HeaderMap) -> Result<&str, StatusCode> {
     Ok(token)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request, routing::post, Router};
+    use forgeerp_infra::command_dispatcher::DispatchError;
+    use tower::{Service, ServiceExt};
+
+    use forgeerp_infra::event_store::EventStore as _;
+
+    use super::*;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/contended",
+                post(|| async {
+                    record_retry(&RetryReport { attempts: 3, max_attempts: 5, exhausted: false });
+                    StatusCode::CREATED
+                }),
+            )
+            .route(
+                "/exhausted",
+                post(|| async {
+                    record_retry(&RetryReport { attempts: 5, max_attempts: 5, exhausted: true });
+                    errors::dispatch_error_to_response(DispatchError::Concurrency("stale".to_string()))
+                }),
+            )
+            .route("/plain", post(|| async { StatusCode::OK }))
+            .layer(axum::middleware::from_fn(retry_middleware))
+    }
+
+    async fn send(path: &str) -> Response {
+        app()
+            .call(Request::post(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn successful_retried_dispatch_reports_attempts() {
+        let response = send("/contended").await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[RETRY_ATTEMPTS_HEADER], "3");
+
+        assert!(send("/plain").await.headers().get(RETRY_ATTEMPTS_HEADER).is_none());
+    }
+
+    #[tokio::test]
+    async fn exhausted_budget_is_reported_in_error_body() {
+        let response = send("/exhausted").await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(response.headers()[RETRY_ATTEMPTS_HEADER], "5");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "conflict");
+        assert_eq!(body["retry"]["attempts"], 5);
+        assert_eq!(body["retry"]["exhausted"], true);
+    }
+
+    /// Creates a fresh id on every call, like `POST /inventory/items`.
+    fn idempotent_app(cache: Arc<IdempotencyCache>, tenant_id: TenantId) -> Router {
+        let create = || async {
+            (StatusCode::CREATED, Json(serde_json::json!({ "id": Uuid::now_v7().to_string() })))
+        };
+        Router::new()
+            .route("/inventory/items", post(create))
+            .route("/products", post(create))
+            .route("/failing", post(|| async { StatusCode::SERVICE_UNAVAILABLE }))
+            .layer(axum::middleware::from_fn_with_state(cache, idempotency_middleware))
+            .layer(axum::Extension(TenantContext::new(tenant_id)))
+    }
+
+    async fn post_with_key(app: &Router, path: &str, key: Uuid, body: serde_json::Value) -> Response {
+        let request = Request::post(path)
+            .header(IDEMPOTENCY_KEY_HEADER, key.to_string())
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap()
+    }
+
+    async fn created_id(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        body["id"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn repeated_create_with_the_same_key_returns_the_same_item() {
+        let cache = Arc::new(IdempotencyCache::default());
+        let app = idempotent_app(cache.clone(), TenantId::new());
+        let key = Uuid::now_v7();
+        let body = serde_json::json!({ "name": "Widget" });
+
+        let first = post_with_key(&app, "/inventory/items", key, body.clone()).await;
+        assert_eq!(first.status(), StatusCode::CREATED);
+        assert!(first.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+        let first_id = created_id(first).await;
+
+        let retry = post_with_key(&app, "/inventory/items", key, body.clone()).await;
+        assert_eq!(retry.status(), StatusCode::CREATED);
+        assert_eq!(retry.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+        assert_eq!(retry.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(created_id(retry).await, first_id);
+
+        // Another key, or the same key on another route, is a new request.
+        let other = post_with_key(&app, "/inventory/items", Uuid::now_v7(), body.clone()).await;
+        assert_ne!(created_id(other).await, first_id);
+        let other_route = post_with_key(&app, "/products", key, body).await;
+        assert_ne!(created_id(other_route).await, first_id);
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn reused_key_with_another_body_is_rejected_and_failures_are_not_stored() {
+        let cache = Arc::new(IdempotencyCache::default());
+        let app = idempotent_app(cache.clone(), TenantId::new());
+        let key = Uuid::now_v7();
+
+        post_with_key(&app, "/inventory/items", key, serde_json::json!({ "name": "Widget" })).await;
+        let reused = post_with_key(&app, "/inventory/items", key, serde_json::json!({ "name": "Gadget" })).await;
+        assert_eq!(reused.status(), StatusCode::BAD_REQUEST);
+
+        let failing = Uuid::now_v7();
+        for _ in 0..2 {
+            let response = post_with_key(&app, "/failing", failing, serde_json::json!({})).await;
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+            assert!(response.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+        }
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn concurrent_repeat_of_a_running_request_is_refused() {
+        let cache = Arc::new(IdempotencyCache::default());
+        let (entered_tx, mut entered) = tokio::sync::mpsc::unbounded_channel::<()>();
+        let release = Arc::new(tokio::sync::Notify::new());
+        let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let create = {
+            let (release, runs) = (release.clone(), runs.clone());
+            move || async move {
+                runs.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                entered_tx.send(()).unwrap();
+                release.notified().await;
+                (StatusCode::CREATED, Json(serde_json::json!({ "id": Uuid::now_v7().to_string() })))
+            }
+        };
+        let app = Router::new()
+            .route("/inventory/items", post(create))
+            .layer(axum::middleware::from_fn_with_state(cache.clone(), idempotency_middleware))
+            .layer(axum::Extension(TenantContext::new(TenantId::new())));
+        let key = Uuid::now_v7();
+        let body = serde_json::json!({ "name": "Widget" });
+
+        let first = tokio::spawn({
+            let (app, body) = (app.clone(), body.clone());
+            async move { post_with_key(&app, "/inventory/items", key, body).await }
+        });
+        entered.recv().await.unwrap();
+
+        let repeat = post_with_key(&app, "/inventory/items", key, body.clone()).await;
+        assert_eq!(repeat.status(), StatusCode::CONFLICT);
+        let different = post_with_key(&app, "/inventory/items", key, serde_json::json!({ "name": "Gadget" })).await;
+        assert_eq!(different.status(), StatusCode::BAD_REQUEST);
+
+        release.notify_one();
+        let first_id = created_id(first.await.unwrap()).await;
+        let replayed = post_with_key(&app, "/inventory/items", key, body).await;
+        assert_eq!(created_id(replayed).await, first_id);
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn keys_are_scoped_to_the_tenant_and_expire() {
+        let start = Utc::now();
+        let clock = forgeerp_infra::clock::FixedClock::arc(start);
+        let cache = Arc::new(IdempotencyCache::new(chrono::Duration::minutes(5)).with_clock(clock.clone()));
+        let key = Uuid::now_v7();
+        let body = serde_json::json!({ "name": "Widget" });
+
+        let tenant_a = idempotent_app(cache.clone(), TenantId::new());
+        let tenant_b = idempotent_app(cache.clone(), TenantId::new());
+        let first_id = created_id(post_with_key(&tenant_a, "/inventory/items", key, body.clone()).await).await;
+        let other_tenant = post_with_key(&tenant_b, "/inventory/items", key, body.clone()).await;
+        assert_ne!(created_id(other_tenant).await, first_id);
+
+        clock.set(start + chrono::Duration::minutes(6));
+        let expired = post_with_key(&tenant_a, "/inventory/items", key, body).await;
+        assert!(expired.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+        assert_ne!(created_id(expired).await, first_id);
+    }
+
+    #[tokio::test]
+    async fn keys_are_scoped_to_the_principal_and_stored_bodies_are_capped() {
+        let cache = Arc::new(IdempotencyCache::default().with_max_bytes(100));
+        let tenant_id = TenantId::new();
+        let as_principal = || {
+            idempotent_app(cache.clone(), tenant_id)
+                .layer(axum::Extension(PrincipalContext::new(PrincipalId::new(), vec![])))
+        };
+        let (alice, bob) = (as_principal(), as_principal());
+        let key = Uuid::now_v7();
+        let body = serde_json::json!({ "name": "Widget" });
+
+        let first_id = created_id(post_with_key(&alice, "/inventory/items", key, body.clone()).await).await;
+        let other_principal = post_with_key(&bob, "/inventory/items", key, body.clone()).await;
+        assert!(other_principal.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+        assert_ne!(created_id(other_principal).await, first_id);
+        assert_eq!(cache.len(), 2);
+
+        // Each stored body is 45 bytes: from the third on, the oldest is dropped.
+        post_with_key(&bob, "/products", key, body.clone()).await;
+        post_with_key(&bob, "/inventory/items", Uuid::now_v7(), body.clone()).await;
+        assert_eq!(cache.len(), 2);
+        let evicted = post_with_key(&alice, "/inventory/items", key, body).await;
+        assert!(evicted.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+
+        let oversized = Request::post("/inventory/items")
+            .header(IDEMPOTENCY_KEY_HEADER, Uuid::now_v7().to_string())
+            .body(Body::from(vec![b'x'; IDEMPOTENCY_MAX_BODY_BYTES + 1]))
+            .unwrap();
+        let response = alice.clone().oneshot(oversized).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    /// Accepts any token named in `tokens`.
+    struct StaticTokens(Vec<(&'static str, JwtClaims)>);
+
+    impl JwtValidator for StaticTokens {
+        fn validate(
+            &self,
+            token: &str,
+            _now: chrono::DateTime<Utc>,
+        ) -> Result<JwtClaims, forgeerp_auth::TokenValidationError> {
+            self.0
+                .iter()
+                .find(|(t, _)| *t == token)
+                .map(|(_, c)| c.clone())
+                .ok_or(forgeerp_auth::TokenValidationError::InvalidFormat)
+        }
+    }
+
+    fn claims(tenant_id: TenantId, role: &'static str) -> JwtClaims {
+        JwtClaims {
+            sub: forgeerp_auth::PrincipalId::new(),
+            tenant_id,
+            roles: vec![forgeerp_auth::Role::new(role)],
+            issued_at: Utc::now(),
+            expires_at: Utc::now() + chrono::Duration::minutes(10),
+            not_before: None,
+            jti: None,
+            issuer: None,
+            audience: Vec::new(),
+        }
+    }
+
+    /// Echoes the request's tenant and the envelope metadata commands would carry.
+    fn tenant_app(home: TenantId) -> Router {
+        let jwt = StaticTokens(vec![
+            ("admin", claims(home, "admin")),
+            ("support", claims(home, "platform_admin")),
+            ("clerk", claims(home, "salesperson")),
+        ]);
+        whoami_app(AuthState::new(Arc::new(jwt)))
+    }
+
+    fn whoami_app(state: AuthState) -> Router {
+        Router::new()
+            .route(
+                "/whoami",
+                axum::routing::get(
+                    |axum::Extension(tenant): axum::Extension<TenantContext>,
+                     axum::Extension(principal): axum::Extension<PrincipalContext>| async move {
+                    axum::Json(serde_json::json!({
+                        "tenant_id": tenant.tenant_id().to_string(),
+                        "home_tenant_id": tenant.home_tenant_id().to_string(),
+                        "roles": principal.roles().iter().map(|r| r.as_str()).collect::<Vec<_>>(),
+                        "metadata": forgeerp_infra::command_dispatcher::current_envelope_metadata(),
+                    }))
+                },
+                ),
+            )
+            .layer(axum::middleware::from_fn_with_state(state, auth_middleware))
+    }
+
+    async fn whoami(app: Router, token: &str, act_as: Option<String>) -> (StatusCode, serde_json::Value) {
+        let mut req = Request::get("/whoami").header("authorization", format!("Bearer {token}"));
+        if let Some(tenant) = act_as {
+            req = req.header(ACT_AS_TENANT_HEADER, tenant);
+        }
+        let response = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn cross_tenant_admin_acts_in_target_tenant_with_audit_metadata() {
+        let home = TenantId::new();
+        let target = TenantId::new();
+
+        let (status, body) = whoami(tenant_app(home), "support", Some(target.to_string())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tenant_id"], target.to_string());
+        assert_eq!(body["home_tenant_id"], home.to_string());
+        assert_eq!(body["metadata"]["home_tenant_id"], home.to_string());
+        assert!(body["metadata"]["acting_principal_id"].is_string());
+
+        let (_, body) = whoami(tenant_app(home), "support", None).await;
+        assert_eq!(body["tenant_id"], home.to_string());
+        assert_eq!(body["metadata"], serde_json::json!({}));
+    }
+
+    #[tokio::test]
+    async fn tenant_admin_wildcard_does_not_grant_cross_tenant_access() {
+        let home = TenantId::new();
+
+        let (status, _) = whoami(tenant_app(home), "admin", Some(TenantId::new().to_string())).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, body) = whoami(tenant_app(home), "admin", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tenant_id"], home.to_string());
+    }
+
+    #[tokio::test]
+    async fn act_as_header_is_rejected_without_cross_tenant_permission() {
+        let home = TenantId::new();
+
+        let (status, _) = whoami(tenant_app(home), "clerk", Some(TenantId::new().to_string())).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // Naming one's own tenant is not an override.
+        let (status, body) = whoami(tenant_app(home), "clerk", Some(home.to_string())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tenant_id"], home.to_string());
+
+        let (status, _) = whoami(tenant_app(home), "admin", Some("not-a-tenant".to_string())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn business_key_header_is_recorded_as_envelope_metadata() {
+        let send = |key: &'static str| async move {
+            let req = Request::get("/whoami")
+                .header("authorization", "Bearer clerk")
+                .header(BUSINESS_KEY_HEADER, key)
+                .body(Body::empty())
+                .unwrap();
+            let response = tenant_app(TenantId::new()).oneshot(req).await.unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+        };
+
+        let (status, body) = send("order-fulfillment-123").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["metadata"][BUSINESS_KEY], "order-fulfillment-123");
+
+        let (status, _) = send("  ").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    /// `POST /items` creates an inventory item through a real dispatcher; `?deny` fails.
+    fn dry_run_app() -> (Router, Arc<forgeerp_infra::event_store::InMemoryEventStore>, TenantId) {
+        use forgeerp_events::{EventEnvelope, InMemoryEventBus};
+        use forgeerp_infra::command_dispatcher::CommandDispatcher;
+        use forgeerp_infra::event_store::InMemoryEventStore;
+        use forgeerp_inventory::{CreateItem, InventoryCommand, InventoryItem, InventoryItemId};
+
+        let store = Arc::new(InMemoryEventStore::new());
+        let bus: Arc<InMemoryEventBus<EventEnvelope<serde_json::Value>>> = Arc::new(InMemoryEventBus::new());
+        let dispatcher = Arc::new(CommandDispatcher::new(store.clone(), bus));
+        let tenant_id = TenantId::new();
+        let create = move |axum::extract::RawQuery(query): axum::extract::RawQuery| {
+            let dispatcher = dispatcher.clone();
+            async move {
+                if query.is_some_and(|q| q.contains("deny")) {
+                    return errors::json_error(StatusCode::FORBIDDEN, "forbidden", "missing permission");
+                }
+                let item_id = InventoryItemId::new(forgeerp_core::AggregateId::new());
+                let command = InventoryCommand::CreateItem(CreateItem {
+                    tenant_id,
+                    item_id,
+                    name: "Widget".to_string(),
+                    occurred_at: Utc::now(),
+                });
+                match dispatcher.dispatch(tenant_id, item_id.0, "inventory.item", command, |_, id| {
+                    InventoryItem::empty(InventoryItemId::new(id))
+                }) {
+                    Ok(_) => StatusCode::CREATED.into_response(),
+                    Err(e) => errors::dispatch_error_to_response(e),
+                }
+            }
+        };
+        let app = Router::new()
+            .route("/items", post(create).get(|| async { StatusCode::OK }))
+            .layer(axum::middleware::from_fn(dry_run_middleware));
+        (app, store, tenant_id)
+    }
+
+    async fn call(app: Router, req: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let response = app.oneshot(req).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn dry_run_returns_projected_events_and_persists_nothing() {
+        let (app, store, tenant_id) = dry_run_app();
+
+        let (status, body) = call(app.clone(), Request::post("/items?dry_run=true").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["dry_run"], true);
+        assert_eq!(body["events"][0]["event_type"], "inventory.item.created");
+        assert_eq!(body["events"][0]["sequence_number"], 1);
+        assert_eq!(store.tenant_stats(tenant_id).unwrap().event_count, 0);
+
+        let (status, _) = call(app.clone(), Request::post("/items").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(store.tenant_stats(tenant_id).unwrap().event_count, 1);
+    }
+
+    #[tokio::test]
+    async fn dry_run_passes_failures_through_and_is_post_only() {
+        let (app, store, tenant_id) = dry_run_app();
+
+        let (status, body) =
+            call(app.clone(), Request::post("/items?dry_run=true&deny").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"], "forbidden");
+
+        let (status, body) = call(app.clone(), Request::get("/items?dry_run=true").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "dry_run_unsupported");
+
+        let (status, _) = call(app, Request::post("/items?dry_run=maybe").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(store.tenant_stats(tenant_id).unwrap().event_count, 0);
+    }
+
+    /// Knows the `rotated` token only once its keys were refreshed.
+    struct RotatingKeys {
+        claims: JwtClaims,
+        refreshed: std::sync::atomic::AtomicBool,
+        refreshes: std::sync::atomic::AtomicUsize,
+    }
+
+    impl JwtValidator for RotatingKeys {
+        fn validate(&self, token: &str, _now: DateTime<Utc>) -> Result<JwtClaims, TokenValidationError> {
+            match token {
+                "rotated" if self.refreshed.load(std::sync::atomic::Ordering::SeqCst) => Ok(self.claims.clone()),
+                "rotated" | "made-up" => Err(TokenValidationError::UnknownKey(token.to_string())),
+                _ => Err(TokenValidationError::InvalidFormat),
+            }
+        }
+
+        fn refresh_keys(&self) -> forgeerp_auth::KeyRefresh<'_> {
+            self.refreshes.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.refreshed.store(true, std::sync::atomic::Ordering::SeqCst);
+            Box::pin(std::future::ready(true))
+        }
+    }
+
+    #[tokio::test]
+    async fn token_signed_with_a_rotated_key_is_accepted_after_a_key_refresh() {
+        let tenant = TenantId::new();
+        let keys = Arc::new(RotatingKeys {
+            claims: claims(tenant, "admin"),
+            refreshed: std::sync::atomic::AtomicBool::new(false),
+            refreshes: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let app = whoami_app(AuthState::new(keys.clone()));
+
+        let (status, body) = whoami(app.clone(), "rotated", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tenant_id"], tenant.to_string());
+        assert_eq!(whoami(app.clone(), "made-up", None).await.0, StatusCode::UNAUTHORIZED);
+        // Other failures do not refresh the keys.
+        assert_eq!(whoami(app, "garbage", None).await.0, StatusCode::UNAUTHORIZED);
+        assert_eq!(keys.refreshes.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    async fn whoami_with_key(app: Router, headers: &[(&str, &str)]) -> (StatusCode, serde_json::Value) {
+        let mut req = Request::get("/whoami");
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let response = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn api_key_acts_as_its_service_account_in_its_own_tenant_only() {
+        let tenant_a = TenantId::new();
+        let account = forgeerp_auth::ServiceAccount {
+            principal_id: forgeerp_auth::PrincipalId::new(),
+            tenant_id: tenant_a,
+            roles: vec![forgeerp_auth::Role::new("admin")],
+        };
+        let keys = Arc::new(ApiKeyValidator::new().with_key("sk_sync", account.clone()));
+        let app = || whoami_app(AuthState::new(Arc::new(StaticTokens(Vec::new()))).with_api_keys(keys.clone()));
+
+        let (status, body) = whoami_with_key(app(), &[(API_KEY_HEADER, "sk_sync")]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tenant_id"], tenant_a.to_string());
+
+        // Even with a role that may override tenants elsewhere.
+        let tenant_b = TenantId::new().to_string();
+        let (status, _) = whoami_with_key(app(), &[(API_KEY_HEADER, "sk_sync"), (ACT_AS_TENANT_HEADER, &tenant_b)]).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, _) = whoami_with_key(app(), &[(API_KEY_HEADER, "sk_other")]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // A bearer token takes precedence; the key is not a fallback for a bad one.
+        let (status, _) =
+            whoami_with_key(app(), &[("authorization", "Bearer nope"), (API_KEY_HEADER, "sk_sync")]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        keys.revoke(account.principal_id);
+        let (status, _) = whoami_with_key(app(), &[(API_KEY_HEADER, "sk_sync")]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn revoked_or_unidentified_tokens_are_rejected_when_revocation_is_enabled() {
+        let tenant = TenantId::new();
+        let mut with_jti = claims(tenant, "admin");
+        with_jti.jti = Some("token-1".to_string());
+        let user = with_jti.sub;
+        let jwt = StaticTokens(vec![("with-jti", with_jti), ("without-jti", claims(tenant, "admin"))]);
+        let revocations: Arc<dyn RevocationStore> = Arc::new(forgeerp_auth::InMemoryRevocationStore::new());
+        let state = AuthState::new(Arc::new(jwt)).with_revocations(revocations.clone());
+
+        let bearer = |token: &str| format!("Bearer {token}");
+        let (status, _) = whoami_with_key(whoami_app(state.clone()), &[("authorization", &bearer("with-jti"))]).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) =
+            whoami_with_key(whoami_app(state.clone()), &[("authorization", &bearer("without-jti"))]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // What suspending the user does.
+        revocations.revoke_principal(tenant, user, Utc::now());
+        let (status, _) = whoami_with_key(whoami_app(state), &[("authorization", &bearer("with-jti"))]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn tokens_issued_before_a_suspension_are_rejected_without_revocation_store() {
+        let tenant = TenantId::new();
+        let mut before = claims(tenant, "admin");
+        before.issued_at = Utc::now() - chrono::Duration::minutes(5);
+        let user = before.sub;
+        let mut after = before.clone();
+        after.issued_at = Utc::now() + chrono::Duration::seconds(1);
+        let suspended_at = Utc::now();
+        // Backed by the users projection in the app: survives restarts, covers unseen tokens.
+        let suspensions: SuspensionLookup = Arc::new(move |principal_id, tenant_id| {
+            (principal_id == user && tenant_id == tenant).then_some(suspended_at)
+        });
+        let jwt = StaticTokens(vec![("before", before), ("after", after), ("other", claims(tenant, "admin"))]);
+        let state = AuthState::new(Arc::new(jwt)).with_suspensions(suspensions);
+
+        for (token, expected) in [
+            ("before", StatusCode::UNAUTHORIZED),
+            ("after", StatusCode::OK),
+            ("other", StatusCode::OK),
+        ] {
+            let bearer = format!("Bearer {token}");
+            let (status, _) = whoami_with_key(whoami_app(state.clone()), &[("authorization", &bearer)]).await;
+            assert_eq!(status, expected, "{token}");
+        }
+    }
+
+    #[tokio::test]
+    async fn x_tenant_id_switches_between_memberships_and_blocks_other_tenants() {
+        let (home, other, foreign) = (TenantId::new(), TenantId::new(), TenantId::new());
+        let token = claims(home, "admin");
+        let user = token.sub;
+        let memberships: MembershipLookup = Arc::new(move |principal_id, tenant_id| {
+            (principal_id == user && (tenant_id == home || tenant_id == other)).then(|| TenantMembership {
+                tenant_id,
+                roles: vec![forgeerp_auth::Role::new(if tenant_id == home { "admin" } else { "viewer" })],
+                permissions: Vec::new(),
+            })
+        });
+        let state = AuthState::new(Arc::new(StaticTokens(vec![("user", token)]))).with_memberships(memberships);
+        let request = |tenant: TenantId| {
+            let app = whoami_app(state.clone());
+            async move {
+                let tenant = tenant.to_string();
+                whoami_with_key(app, &[("authorization", "Bearer user"), (TENANT_HEADER, &tenant)]).await
+            }
+        };
+
+        let (status, body) = request(other).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tenant_id"], other.to_string());
+        assert_eq!(body["roles"], serde_json::json!(["viewer"]));
+
+        let (status, body) = request(home).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tenant_id"], home.to_string());
+        assert_eq!(body["roles"], serde_json::json!(["admin"]));
+
+        let (status, body) = request(foreign).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"], "tenant_isolation");
+    }
+}
@@ -42,6 +42,10 @@ fn mint_jwt(jwt_secret: &str, tenant_id: TenantId, roles: Vec<Role>) -> String {
         roles,
         issued_at: now,
         expires_at: now + ChronoDuration::minutes(10),
+        not_before: None,
+        jti: None,
+        issuer: None,
+        audience: Vec::new(),
     };
 
     jsonwebtoken::encode(
@@ -157,6 +161,68 @@ async fn inventory_lifecycle_create_adjust_query() {
     assert_eq!(item["quantity"], 10);
 }
 
+#[tokio::test]
+async fn bulk_adjust_reports_each_item_and_keeps_going_after_failures() {
+    let jwt_secret = "test-secret";
+    let srv = TestServer::spawn(jwt_secret).await;
+
+    let tenant_id = TenantId::new();
+    let token = mint_jwt(jwt_secret, tenant_id, vec![Role::new("admin")]);
+
+    let client = reqwest::Client::new();
+
+    let mut ids = Vec::new();
+    for name in ["Widget", "Gadget"] {
+        let res = client
+            .post(format!("{}/inventory/items", srv.base_url))
+            .bearer_auth(&token)
+            .json(&json!({ "name": name }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let created: serde_json::Value = res.json().await.unwrap();
+        ids.push(created["id"].as_str().unwrap().to_string());
+    }
+
+    // The second entry would take stock negative; the third is not an id.
+    let res = client
+        .post(format!("{}/inventory/items/adjust-bulk", srv.base_url))
+        .bearer_auth(&token)
+        .json(&json!([
+            { "item_id": ids[0], "delta": 7 },
+            { "item_id": ids[1], "delta": -3 },
+            { "item_id": "not-an-id", "delta": 1 },
+            { "item_id": ids[1], "delta": 4 },
+        ]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["succeeded"], 2);
+    assert_eq!(body["failed"], 2);
+    let results = body["results"].as_array().unwrap();
+    let ok: Vec<bool> = results.iter().map(|r| r["ok"].as_bool().unwrap()).collect();
+    assert_eq!(ok, vec![true, false, false, true]);
+    assert_eq!(results[1]["error"], "invariant_violation");
+    assert_eq!(results[2]["error"], "invalid_id");
+
+    let item = get_item_eventually(&client, &srv.base_url, &token, &ids[0]).await;
+    assert_eq!(item["quantity"], 7);
+
+    // Without `inventory.items.adjust` the whole request is refused up front.
+    let viewer = mint_jwt(jwt_secret, tenant_id, vec![Role::new("viewer")]);
+    let res = client
+        .post(format!("{}/inventory/items/adjust-bulk", srv.base_url))
+        .bearer_auth(&viewer)
+        .json(&json!([{ "item_id": ids[0], "delta": 1 }]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+}
+
 #[tokio::test]
 async fn unauthorized_access_blocked_for_commands() {
     let jwt_secret = "test-secret";
@@ -223,3 +289,71 @@ async fn tenant_isolation_blocks_cross_tenant_reads_and_writes() {
 }
 
 
+
+#[tokio::test]
+async fn deleted_product_is_tombstoned_and_rejects_commands() {
+    let jwt_secret = "test-secret";
+    let srv = TestServer::spawn(jwt_secret).await;
+
+    let tenant_id = TenantId::new();
+    let token = mint_jwt(jwt_secret, tenant_id, vec![Role::new("admin")]);
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post(format!("{}/products", srv.base_url))
+        .bearer_auth(&token)
+        .json(&json!({ "sku": "TYPO-1", "name": "Created by mistake" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let created: serde_json::Value = res.json().await.unwrap();
+    let id = created["id"].as_str().unwrap().to_string();
+
+    let res = client
+        .delete(format!("{}/products/{}", srv.base_url, id))
+        .bearer_auth(&token)
+        .json(&json!({ "reason": "duplicate" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Further commands are rejected by the terminal state.
+    let res = client
+        .post(format!("{}/products/{}/activate", srv.base_url, id))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Eventually gone from the catalog and visible as a tombstone.
+    let mut tombstones = serde_json::Value::Null;
+    for _ in 0..50 {
+        let res = client
+            .get(format!("{}/admin/tombstones/products", srv.base_url))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap();
+        tombstones = res.json().await.unwrap();
+        if tombstones["items"].as_array().is_some_and(|a| !a.is_empty()) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(tombstones["items"][0]["id"], id);
+    assert_eq!(tombstones["items"][0]["reason"], "duplicate");
+
+    let listed: serde_json::Value = client
+        .get(format!("{}/products", srv.base_url))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(listed["items"].as_array().unwrap().iter().all(|p| p["id"] != id));
+}
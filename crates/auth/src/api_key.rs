@@ -0,0 +1,162 @@
+//! API keys for service-account principals.
+//!
+//! Background integrations cannot carry interactive JWTs. A service account is a fixed
+//! principal (id, tenant, roles) reached through an opaque key. Only the key's SHA-256
+//! hash is kept, and a presented key is compared in constant time against every known
+//! hash.
+
+use std::sync::RwLock;
+
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+use forgeerp_core::TenantId;
+
+use crate::{PrincipalId, Role};
+
+/// The principal an API key authenticates as. It acts in its own tenant only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceAccount {
+    pub principal_id: PrincipalId,
+    pub tenant_id: TenantId,
+    pub roles: Vec<Role>,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ApiKeyError {
+    #[error("missing API key")]
+    MissingKey,
+
+    #[error("unknown API key")]
+    UnknownKey,
+
+    #[error("API key has been revoked")]
+    Revoked,
+
+    #[error("invalid API key hash (expected 64 hex characters)")]
+    InvalidHash,
+}
+
+/// SHA-256 of an API key, as stored.
+pub fn hash_api_key(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+#[derive(Debug)]
+struct KeyEntry {
+    hash: [u8; 32],
+    account: ServiceAccount,
+    revoked: bool,
+}
+
+/// Maps API keys (by hash) to service accounts.
+#[derive(Debug, Default)]
+pub struct ApiKeyValidator {
+    keys: RwLock<Vec<KeyEntry>>,
+}
+
+impl ApiKeyValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept `key` for `account`.
+    pub fn with_key(self, key: &str, account: ServiceAccount) -> Self {
+        self.register_hash(hash_api_key(key), account);
+        self
+    }
+
+    /// Accept the key whose SHA-256 is `hex_hash` (so configuration never holds the key).
+    pub fn with_hashed_key(self, hex_hash: &str, account: ServiceAccount) -> Result<Self, ApiKeyError> {
+        let hash = hex::decode(hex_hash.trim())
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or(ApiKeyError::InvalidHash)?;
+        self.register_hash(hash, account);
+        Ok(self)
+    }
+
+    fn register_hash(&self, hash: [u8; 32], account: ServiceAccount) {
+        let mut keys = self.keys.write().unwrap_or_else(|e| e.into_inner());
+        keys.push(KeyEntry {
+            hash,
+            account,
+            revoked: false,
+        });
+    }
+
+    /// Revoke every key of the service account; returns how many were active.
+    pub fn revoke(&self, principal_id: PrincipalId) -> usize {
+        let mut keys = self.keys.write().unwrap_or_else(|e| e.into_inner());
+        keys.iter_mut()
+            .filter(|entry| entry.account.principal_id == principal_id && !entry.revoked)
+            .map(|entry| entry.revoked = true)
+            .count()
+    }
+
+    /// The service account `key` belongs to.
+    pub fn validate(&self, key: &str) -> Result<ServiceAccount, ApiKeyError> {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(ApiKeyError::MissingKey);
+        }
+        let presented = hash_api_key(key);
+
+        let keys = self.keys.read().unwrap_or_else(|e| e.into_inner());
+        // Compare against every entry so the time taken does not depend on which matched.
+        let mut found = None;
+        for entry in keys.iter() {
+            if bool::from(entry.hash.ct_eq(&presented)) {
+                found = Some(entry);
+            }
+        }
+        match found {
+            None => Err(ApiKeyError::UnknownKey),
+            Some(entry) if entry.revoked => Err(ApiKeyError::Revoked),
+            Some(entry) => Ok(entry.account.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(tenant_id: TenantId) -> ServiceAccount {
+        ServiceAccount {
+            principal_id: PrincipalId::new(),
+            tenant_id,
+            roles: vec![Role::new("warehouse")],
+        }
+    }
+
+    #[test]
+    fn key_maps_to_its_service_account() {
+        let tenant_id = TenantId::new();
+        let sync = account(tenant_id);
+        let keys = ApiKeyValidator::new()
+            .with_key("sk_live_sync", sync.clone())
+            .with_hashed_key(&hex::encode(hash_api_key("sk_live_report")), account(TenantId::new()))
+            .unwrap();
+
+        assert_eq!(keys.validate("sk_live_sync"), Ok(sync));
+        assert_ne!(keys.validate("sk_live_report").unwrap().tenant_id, tenant_id);
+        assert_eq!(keys.validate("sk_live_other"), Err(ApiKeyError::UnknownKey));
+        assert_eq!(keys.validate("  "), Err(ApiKeyError::MissingKey));
+        assert!(ApiKeyValidator::new().with_hashed_key("abc", account(tenant_id)).is_err());
+    }
+
+    #[test]
+    fn revoked_key_is_rejected() {
+        let sync = account(TenantId::new());
+        let keys = ApiKeyValidator::new()
+            .with_key("sk_live_sync", sync.clone())
+            .with_key("sk_live_other", account(TenantId::new()));
+
+        assert_eq!(keys.revoke(sync.principal_id), 1);
+        assert_eq!(keys.validate("sk_live_sync"), Err(ApiKeyError::Revoked));
+        assert!(keys.validate("sk_live_other").is_ok());
+        assert_eq!(keys.revoke(sync.principal_id), 0);
+    }
+}
@@ -45,14 +45,23 @@ pub fn authorize(principal: &Principal, required: &Permission) -> Result<(), Aut
         return Err(AuthzError::TenantMismatch);
     }
 
-    let perms: HashSet<&str> = principal
-        .membership
-        .permissions
-        .iter()
-        .map(|p| p.as_str())
-        .collect();
-
-    if perms.contains("*") || perms.contains(required.as_str()) {
+    if principal.membership.permissions.iter().any(|p| p.grants(required)) {
+        Ok(())
+    } else {
+        Err(AuthzError::Forbidden(required.as_str().to_string()))
+    }
+}
+
+/// Like [`authorize`], but only an exact grant of `required` counts.
+///
+/// For platform-level permissions that no tenant role may reach through the `"*"`
+/// wildcard or a pattern (e.g. [`crate::admin::CROSS_TENANT`]).
+pub fn authorize_exact(principal: &Principal, required: &Permission) -> Result<(), AuthzError> {
+    if principal.active_tenant_id != principal.membership.tenant_id {
+        return Err(AuthzError::TenantMismatch);
+    }
+
+    if principal.membership.permissions.iter().any(|p| p == required) {
         Ok(())
     } else {
         Err(AuthzError::Forbidden(required.as_str().to_string()))
@@ -78,6 +87,10 @@ pub struct AuthorizationExplanation {
     /// Human-readable reason for the decision.
     pub reason: String,
 
+    /// The grant that satisfied the requirement: the permission itself, or the pattern
+    /// (`*`, `inventory.items.*`, `inventory.**`) covering it. `None` when denied.
+    pub matched_grant: Option<String>,
+
     /// Details about the principal's state.
     pub principal: PrincipalState,
 
@@ -144,6 +157,7 @@ where
         return AuthorizationExplanation {
             required_permission: required_str.to_string(),
             granted: false,
+            matched_grant: None,
             reason: format!(
                 "Tenant mismatch: principal is active in tenant {} but membership is for tenant {}",
                 principal.active_tenant_id, principal.membership.tenant_id
@@ -186,22 +200,35 @@ where
     }
 
     let has_wildcard = effective_perms.contains("*");
-    let has_required = effective_perms.contains(required_str);
 
     // Build effective permissions list (sorted for readability)
     let mut effective_perms_list: Vec<String> = effective_perms.into_iter().collect();
     effective_perms_list.sort();
 
-    if has_wildcard || has_required {
-        let reason = if has_wildcard {
-            format!("Principal has wildcard permission '*' (granted by admin role)")
-        } else {
+    // The exact permission if held, otherwise the most specific pattern covering it.
+    let matched_grant = if effective_perms_list.iter().any(|p| p == required_str) {
+        Some(required_str.to_string())
+    } else {
+        effective_perms_list
+            .iter()
+            .filter(|p| Permission::new((*p).clone()).grants(required))
+            .max_by_key(|p| (p.split('.').count(), p.len()))
+            .cloned()
+    };
+
+    if let Some(grant) = matched_grant {
+        let reason = if grant == "*" {
+            "Principal has wildcard permission '*' (granted by admin role)".to_string()
+        } else if grant == required_str {
             format!("Principal has explicit permission '{}'", required_str)
+        } else {
+            format!("Principal has wildcard permission '{}' covering '{}'", grant, required_str)
         };
 
         AuthorizationExplanation {
             required_permission: required_str.to_string(),
             granted: true,
+            matched_grant: Some(grant),
             reason,
             principal: PrincipalState {
                 principal_id: principal.principal_id,
@@ -218,7 +245,7 @@ where
         let mut granting_roles: Vec<String> = Vec::new();
         for role in &principal.membership.roles {
             let role_perms = role_permissions(role.as_str());
-            if role_perms.iter().any(|p| p == required_str || p == "*") {
+            if role_perms.into_iter().any(|p| Permission::new(p).grants(required)) {
                 granting_roles.push(role.as_str().to_string());
             }
         }
@@ -241,6 +268,7 @@ where
         AuthorizationExplanation {
             required_permission: required_str.to_string(),
             granted: false,
+            matched_grant: None,
             reason: format!(
                 "Principal does not have permission '{}'. Current permissions: {:?}",
                 required_str, effective_perms_list
@@ -295,10 +323,8 @@ impl RbacRegistry {
         let mut roles: HashMap<String, RoleDefinition> = HashMap::new();
         let mut permissions: HashMap<String, PermissionDefinition> = HashMap::new();
 
-        // Common roles (you can extend this)
-        let known_roles = vec![
-            "admin", "manager", "accountant", "salesperson", "warehouse", "user",
-        ];
+        // Common roles (you can extend this); the platform role is not a tenant role
+        let known_roles = vec!["admin", "manager", "accountant", "salesperson", "warehouse", "user"];
 
         for role_name in known_roles {
             let perms = role_permissions(role_name);
@@ -346,6 +372,9 @@ fn permission_description(perm: &str) -> Option<String> {
     if perm == "*" {
         return Some("Wildcard permission - grants all permissions".to_string());
     }
+    if Permission::new(perm.to_string()).is_pattern() {
+        return Some(format!("Wildcard permission - grants every permission matching '{}'", perm));
+    }
 
     // Parse permission format: "module.action" or "admin.users.action"
     let parts: Vec<&str> = perm.split('.').collect();
@@ -385,4 +414,84 @@ fn permission_category(perm: &str) -> Option<String> {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Role;
+
+    fn principal(grants: &[&'static str]) -> Principal {
+        let tenant_id = TenantId::new();
+        Principal {
+            principal_id: PrincipalId::new(),
+            active_tenant_id: tenant_id,
+            membership: TenantMembership {
+                tenant_id,
+                roles: vec![Role::new("warehouse")],
+                permissions: grants.iter().map(|p| Permission::new(*p)).collect(),
+            },
+        }
+    }
+
+    fn allowed(grants: &[&'static str], required: &'static str) -> bool {
+        authorize(&principal(grants), &Permission::new(required)).is_ok()
+    }
+
+    #[test]
+    fn single_segment_wildcard_matches_one_segment() {
+        assert!(allowed(&["inventory.items.*"], "inventory.items.adjust"));
+        assert!(!allowed(&["inventory.items.*"], "inventory.items"));
+        assert!(!allowed(&["inventory.items.*"], "inventory.items.stock.adjust"));
+        assert!(allowed(&["inventory.*.adjust"], "inventory.items.adjust"));
+        assert!(!allowed(&["inventory.*"], "sales.orders.create"));
+        assert!(!allowed(&["inventory.*"], "inventory.items.adjust"));
+    }
+
+    #[test]
+    fn double_wildcard_matches_any_suffix() {
+        assert!(allowed(&["inventory.**"], "inventory.items.adjust"));
+        assert!(allowed(&["inventory.**"], "inventory.read"));
+        assert!(!allowed(&["inventory.**"], "inventory"));
+        assert!(!allowed(&["inventory.**"], "sales.orders.create"));
+        // Only as the last segment.
+        assert!(!allowed(&["**.adjust"], "inventory.items.adjust"));
+    }
+
+    #[test]
+    fn exact_and_global_grants_still_work() {
+        assert!(allowed(&["inventory.items.adjust"], "inventory.items.adjust"));
+        assert!(!allowed(&["inventory.items.adjust"], "inventory.items.create"));
+        assert!(allowed(&["*"], "sales.orders.create"));
+        assert!(!allowed(&[], "sales.orders.create"));
+    }
+
+    #[test]
+    fn exact_authorization_ignores_wildcards_and_patterns() {
+        let exact = |grants: &[&'static str]| authorize_exact(&principal(grants), &crate::admin::CROSS_TENANT).is_ok();
+        assert!(exact(&["admin.cross_tenant"]));
+        assert!(!exact(&["*"]));
+        assert!(!exact(&["admin.**"]));
+        assert!(!exact(&["admin.*"]));
+    }
 
+    #[test]
+    fn explanation_records_the_matching_grant() {
+        let no_roles = |_: &str| Vec::new();
+        let required = Permission::new("inventory.items.adjust");
+
+        let explained = explain_authorization(
+            &principal(&["inventory.**", "inventory.items.*", "sales.orders.create"]),
+            &required,
+            no_roles,
+        );
+        assert!(explained.granted);
+        assert_eq!(explained.matched_grant.as_deref(), Some("inventory.items.*"));
+        assert!(explained.reason.contains("'inventory.items.*' covering 'inventory.items.adjust'"));
+
+        let exact = explain_authorization(&principal(&["inventory.items.*", "inventory.items.adjust"]), &required, no_roles);
+        assert_eq!(exact.matched_grant.as_deref(), Some("inventory.items.adjust"));
+
+        let denied = explain_authorization(&principal(&["inventory.*"]), &Permission::new("sales.orders.create"), no_roles);
+        assert!(!denied.granted);
+        assert_eq!(denied.matched_grant, None);
+    }
+}
@@ -2,21 +2,28 @@
 //!
 //! This crate is intentionally decoupled from HTTP and storage.
 
+pub mod api_key;
 pub mod authorize;
 pub mod claims;
 pub mod permissions;
 pub mod principal;
+pub mod revocation;
 pub mod roles;
 pub mod user;
 
+pub use api_key::{hash_api_key, ApiKeyError, ApiKeyValidator, ServiceAccount};
 pub use authorize::{
     explain_authorization, AuthorizationExplanation, CommandAuthorization, DenialKind,
     DenialReason, PermissionDefinition, Principal, PrincipalState, RbacRegistry, RoleDefinition,
-    authorize, AuthzError,
+    authorize, authorize_exact, AuthzError,
+};
+pub use claims::{
+    DEFAULT_JWKS_REFRESH_INTERVAL, Hs256JwtValidator, JwksError, JwtClaims, JwtValidator, KeyRefresh,
+    Rs256JwtValidator, TokenValidationError, validate_claims,
 };
-pub use claims::{Hs256JwtValidator, JwtClaims, JwtValidator, TokenValidationError, validate_claims};
 pub use permissions::{admin, Permission};
 pub use principal::{PrincipalId, TenantMembership};
+pub use revocation::{check_issued_after, check_not_revoked, InMemoryRevocationStore, RevocationStore};
 pub use roles::Role;
 pub use user::{
     ActivateUser, AssignRole, CreateUser, RevokeRole, RoleAssigned, RoleRevoked,
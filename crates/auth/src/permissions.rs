@@ -7,6 +7,11 @@ use serde::{Deserialize, Serialize};
 /// Permissions are modeled as opaque strings (e.g. "inventory.read").
 /// A special wildcard permission `"*"` can be used by policy layers to indicate
 /// "allow all" without hardcoding domain permissions into tokens.
+///
+/// A granted permission may also be a pattern over dot-separated segments: `*` matches
+/// exactly one segment (`inventory.items.*` grants `inventory.items.adjust`) and a
+/// trailing `**` matches any non-empty suffix (`inventory.**` grants every
+/// `inventory.` permission).
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct Permission(Cow<'static, str>);
@@ -23,6 +28,30 @@ impl Permission {
     pub fn is_wildcard(&self) -> bool {
         self.as_str() == "*"
     }
+
+    /// Whether this is a pattern (the `"*"` wildcard or a `*` / `**` segment).
+    pub fn is_pattern(&self) -> bool {
+        self.as_str().split('.').any(|segment| segment == "*" || segment == "**")
+    }
+
+    /// Whether granting this permission satisfies `required`.
+    pub fn grants(&self, required: &Permission) -> bool {
+        if self.is_wildcard() {
+            return true;
+        }
+        let mut granted = self.as_str().split('.');
+        let mut wanted = required.as_str().split('.');
+        loop {
+            match (granted.next(), wanted.next()) {
+                (None, None) => return true,
+                // `**` only as the last segment.
+                (Some("**"), Some(_)) => return granted.next().is_none(),
+                (Some("*"), Some(_)) => {}
+                (Some(g), Some(w)) if g == w => {}
+                _ => return false,
+            }
+        }
+    }
 }
 
 impl core::fmt::Display for Permission {
@@ -62,6 +91,47 @@ pub mod admin {
     /// Permission to activate suspended users.
     pub const USER_ACTIVATE: Permission = Permission(std::borrow::Cow::Borrowed("admin.users.activate"));
 
+    /// Permission to view tenant settings (default currency, rounding).
+    pub const SETTINGS_READ: Permission = Permission(std::borrow::Cow::Borrowed("admin.settings.read"));
+
+    /// Permission to change tenant settings.
+    pub const SETTINGS_WRITE: Permission = Permission(std::borrow::Cow::Borrowed("admin.settings.write"));
+
+    /// Permission to view event store usage statistics for the tenant.
+    pub const TENANT_STATS: Permission = Permission(std::borrow::Cow::Borrowed("admin.tenants.stats"));
+
+    /// Permission to view tombstones of deleted entities.
+    pub const TOMBSTONES_READ: Permission = Permission(std::borrow::Cow::Borrowed("admin.tombstones.read"));
+
+    /// Permission to view AI run usage and rate limits for the tenant.
+    pub const AI_USAGE_READ: Permission = Permission(std::borrow::Cow::Borrowed("admin.ai.usage"));
+
+    /// Permission to change the tenant's AI detection settings.
+    pub const AI_CONFIG_WRITE: Permission = Permission(std::borrow::Cow::Borrowed("admin.ai.config"));
+
+    /// Permission to export the tenant's dead-lettered jobs.
+    pub const DEAD_LETTERS_EXPORT: Permission = Permission(std::borrow::Cow::Borrowed("admin.jobs.dead_letters.export"));
+
+    /// Permission to purge old dead-lettered jobs of the tenant.
+    pub const DEAD_LETTERS_PURGE: Permission = Permission(std::borrow::Cow::Borrowed("admin.jobs.dead_letters.purge"));
+
+    /// Permission to read and export the tenant's admin audit log.
+    pub const AUDIT_READ: Permission = Permission(std::borrow::Cow::Borrowed("admin.audit.read"));
+
+    /// Permission to view commands the domain rejected (when the rejected-command log is on).
+    pub const REJECTED_COMMANDS_READ: Permission = Permission(std::borrow::Cow::Borrowed("admin.rejected_commands.read"));
+
+    /// Permission to compare the versions of A/B projections and switch the active one.
+    pub const PROJECTION_VERSIONS_MANAGE: Permission = Permission(std::borrow::Cow::Borrowed("admin.projection_versions.manage"));
+
+    /// Permission to inspect and replay events the projections failed to apply.
+    pub const PROJECTION_DEAD_LETTERS_MANAGE: Permission = Permission(std::borrow::Cow::Borrowed("admin.projection_dead_letters.manage"));
+
+    /// Permission to act in another tenant via the `X-Act-As-Tenant` header (platform support).
+    ///
+    /// Checked with `authorize_exact`: a tenant admin's `"*"` does not grant it.
+    pub const CROSS_TENANT: Permission = Permission(std::borrow::Cow::Borrowed("admin.cross_tenant"));
+
     /// All admin user permissions (convenience for super-admin setup).
     pub fn all_user_permissions() -> Vec<Permission> {
         vec![
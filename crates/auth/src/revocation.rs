@@ -0,0 +1,166 @@
+//! Revocation of issued JWTs before they expire.
+//!
+//! Tokens are self-contained, so suspending a user does not by itself stop the tokens
+//! they already hold. A [`RevocationStore`] remembers revoked token ids (`jti`) and, per
+//! principal and tenant, a cutoff: every token for that tenant issued at or before it is
+//! refused, including tokens this instance has never seen. [`check_not_revoked`] is consulted after signature and
+//! claims validation. With a store in use every token must carry a `jti`, otherwise it
+//! could never be revoked on its own.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+
+use crate::claims::{JwtClaims, TokenValidationError};
+use forgeerp_core::TenantId;
+
+use crate::PrincipalId;
+
+/// Revoked token ids, and per-principal cutoffs (in one tenant) for tokens issued before a
+/// suspension.
+pub trait RevocationStore: Send + Sync {
+    /// Revoke one token id; it stays revoked until `expires_at`.
+    fn revoke(&self, jti: &str, expires_at: DateTime<Utc>);
+
+    /// Revoke every token of `principal_id` in `tenant_id` issued at or before `cutoff`.
+    fn revoke_principal(&self, tenant_id: TenantId, principal_id: PrincipalId, cutoff: DateTime<Utc>);
+
+    fn is_revoked(&self, jti: &str) -> bool;
+
+    /// Latest cutoff recorded for `principal_id` in `tenant_id`.
+    fn revoked_before(&self, tenant_id: TenantId, principal_id: PrincipalId) -> Option<DateTime<Utc>>;
+}
+
+/// Reject tokens without a `jti`, whose `jti` is revoked, or issued at or before the
+/// principal's cutoff in the token's tenant.
+pub fn check_not_revoked(store: &dyn RevocationStore, claims: &JwtClaims) -> Result<(), TokenValidationError> {
+    let jti = claims.jti.as_deref().ok_or(TokenValidationError::MissingTokenId)?;
+    if store.is_revoked(jti) {
+        return Err(TokenValidationError::Revoked);
+    }
+    check_issued_after(store.revoked_before(claims.tenant_id, claims.sub), claims)
+}
+
+/// Reject a token issued at or before `cutoff` (a suspension of its principal).
+pub fn check_issued_after(cutoff: Option<DateTime<Utc>>, claims: &JwtClaims) -> Result<(), TokenValidationError> {
+    match cutoff {
+        Some(cutoff) if claims.issued_at <= cutoff => Err(TokenValidationError::Revoked),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Default)]
+struct Tokens {
+    /// Per tenant and principal: tokens issued at or before this instant are revoked.
+    cutoffs: HashMap<(TenantId, PrincipalId), DateTime<Utc>>,
+    /// Revoked token id -> expiry.
+    revoked: HashMap<String, DateTime<Utc>>,
+}
+
+/// In-memory revocation store (single API instance).
+#[derive(Debug, Default)]
+pub struct InMemoryRevocationStore {
+    tokens: RwLock<Tokens>,
+}
+
+impl InMemoryRevocationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RevocationStore for InMemoryRevocationStore {
+    fn revoke(&self, jti: &str, expires_at: DateTime<Utc>) {
+        let mut tokens = self.tokens.write().unwrap_or_else(|e| e.into_inner());
+        // An expired token is refused anyway: forget the ids that expired by now.
+        let now = Utc::now();
+        tokens.revoked.retain(|_, expires_at| *expires_at > now);
+        tokens.revoked.insert(jti.to_string(), expires_at);
+    }
+
+    fn revoke_principal(&self, tenant_id: TenantId, principal_id: PrincipalId, cutoff: DateTime<Utc>) {
+        let mut tokens = self.tokens.write().unwrap_or_else(|e| e.into_inner());
+        let entry = tokens.cutoffs.entry((tenant_id, principal_id)).or_insert(cutoff);
+        *entry = (*entry).max(cutoff);
+    }
+
+    fn is_revoked(&self, jti: &str) -> bool {
+        self.tokens.read().unwrap_or_else(|e| e.into_inner()).revoked.contains_key(jti)
+    }
+
+    fn revoked_before(&self, tenant_id: TenantId, principal_id: PrincipalId) -> Option<DateTime<Utc>> {
+        let tokens = self.tokens.read().unwrap_or_else(|e| e.into_inner());
+        tokens.cutoffs.get(&(tenant_id, principal_id)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn claims(sub: PrincipalId, jti: Option<&str>) -> JwtClaims {
+        issued(sub, jti, Utc::now())
+    }
+
+    fn issued(sub: PrincipalId, jti: Option<&str>, now: DateTime<Utc>) -> JwtClaims {
+        issued_in(TenantId::new(), sub, jti, now)
+    }
+
+    fn issued_in(tenant_id: TenantId, sub: PrincipalId, jti: Option<&str>, now: DateTime<Utc>) -> JwtClaims {
+        JwtClaims {
+            sub,
+            tenant_id,
+            roles: Vec::new(),
+            issued_at: now,
+            expires_at: now + Duration::hours(1),
+            not_before: None,
+            jti: jti.map(str::to_string),
+            issuer: None,
+            audience: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn revoking_a_principal_rejects_only_its_tokens_in_that_tenant() {
+        let store = InMemoryRevocationStore::new();
+        let (tenant, other_tenant) = (TenantId::new(), TenantId::new());
+        let alice = PrincipalId::new();
+        let bob = PrincipalId::new();
+        let suspended_at = Utc::now();
+        let before = suspended_at - Duration::minutes(5);
+        let token = |sub, jti| issued_in(tenant, sub, Some(jti), before);
+        for claims in [token(alice, "a1"), token(bob, "b1")] {
+            assert_eq!(check_not_revoked(&store, &claims), Ok(()));
+        }
+
+        store.revoke_principal(tenant, alice, suspended_at);
+        assert_eq!(check_not_revoked(&store, &token(alice, "a1")), Err(TokenValidationError::Revoked));
+        // A token issued before the suspension is rejected even if it was never presented.
+        assert_eq!(check_not_revoked(&store, &token(alice, "a2")), Err(TokenValidationError::Revoked));
+        assert_eq!(check_not_revoked(&store, &token(bob, "b1")), Ok(()));
+        // Neither a token issued after the suspension nor one for another tenant is affected.
+        let after = suspended_at + Duration::seconds(1);
+        assert_eq!(check_not_revoked(&store, &issued_in(tenant, alice, Some("a3"), after)), Ok(()));
+        assert_eq!(check_not_revoked(&store, &issued_in(other_tenant, alice, Some("a4"), before)), Ok(()));
+    }
+
+    #[test]
+    fn revoked_token_id_is_rejected() {
+        let store = InMemoryRevocationStore::new();
+        let token = claims(PrincipalId::new(), Some("t1"));
+        store.revoke("t1", token.expires_at);
+        assert_eq!(check_not_revoked(&store, &token), Err(TokenValidationError::Revoked));
+    }
+
+    #[test]
+    fn token_without_jti_is_rejected() {
+        let store = InMemoryRevocationStore::new();
+        assert_eq!(
+            check_not_revoked(&store, &claims(PrincipalId::new(), None)),
+            Err(TokenValidationError::MissingTokenId)
+        );
+    }
+}
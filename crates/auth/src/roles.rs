@@ -11,6 +11,10 @@ use serde::{Deserialize, Serialize};
 pub struct Role(Cow<'static, str>);
 
 impl Role {
+    /// Platform support (`admin.cross_tenant`). Held through IdP claims or service
+    /// accounts only: tenant user management never grants it.
+    pub const PLATFORM_ADMIN: Role = Role(Cow::Borrowed("platform_admin"));
+
     pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
         Self(name.into())
     }
@@ -105,7 +105,8 @@ impl core::fmt::Display for UserStatus {
 /// - Roles are tenant-scoped (no cross-tenant role grants).
 /// - Suspended users cannot be assigned new roles.
 /// - Users cannot escalate their own privileges.
-#[derive(Debug, Clone)]
+/// - The platform role ([`Role::PLATFORM_ADMIN`]) is never granted to a tenant user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: UserId,
     pub tenant_id: Option<TenantId>,
@@ -388,6 +389,10 @@ impl User {
             return Err(DomainError::validation("display name cannot be empty"));
         }
 
+        if cmd.initial_roles.contains(&Role::PLATFORM_ADMIN) {
+            return Err(DomainError::Unauthorized);
+        }
+
         Ok(vec![UserEvent::Created(UserCreated {
             tenant_id: cmd.tenant_id,
             user_id: cmd.user_id,
@@ -411,6 +416,11 @@ impl User {
             return Err(DomainError::invariant("role already assigned"));
         }
 
+        // Cross-tenant access is not a tenant role, whoever asks
+        if cmd.role == Role::PLATFORM_ADMIN {
+            return Err(DomainError::Unauthorized);
+        }
+
         // Privilege escalation check: actor cannot assign roles they don't have
         // Exception: actors with "admin" role can assign any role
         let actor_has_admin = cmd.actor_roles.iter().any(|r| r.as_str() == "admin");
@@ -647,6 +657,41 @@ mod tests {
         assert!(matches!(result.unwrap_err(), DomainError::Unauthorized));
     }
 
+    #[test]
+    fn platform_admin_is_never_granted_by_tenant_admins() {
+        let tenant_id = TenantId::new();
+        let user_id = UserId::new();
+        let mut user = User::empty(user_id);
+
+        let create = |initial_roles| {
+            UserCommand::Create(CreateUser {
+                tenant_id,
+                user_id,
+                email: "dave@example.com".to_string(),
+                display_name: "Dave".to_string(),
+                initial_roles,
+                occurred_at: now(),
+            })
+        };
+        let result = user.handle(&create(vec![Role::PLATFORM_ADMIN]));
+        assert!(matches!(result.unwrap_err(), DomainError::Unauthorized));
+        for event in user.handle(&create(vec![])).unwrap() {
+            user.apply(&event);
+        }
+
+        // Neither a tenant admin nor a platform admin can assign it.
+        for actor_roles in [vec![Role::new("admin")], vec![Role::PLATFORM_ADMIN]] {
+            let assign_cmd = UserCommand::AssignRole(AssignRole {
+                tenant_id,
+                user_id,
+                role: Role::new("platform_admin"),
+                actor_roles,
+                occurred_at: now(),
+            });
+            assert!(matches!(user.handle(&assign_cmd).unwrap_err(), DomainError::Unauthorized));
+        }
+    }
+
     #[test]
     fn suspend_user_success() {
         let tenant_id = TenantId::new();
@@ -120,6 +120,17 @@ impl ExpectedVersion {
     }
 }
 
+/// Edit-style commands that may carry the aggregate version the client last read.
+///
+/// With `ExpectedVersion::Exact(n)` the command is only executed while the stream is
+/// still at version `n`, so a stale edit is rejected instead of silently overwriting
+/// a concurrent change. `Any` (the default) decides against the latest state.
+pub trait VersionedCommand {
+    fn expected_version(&self) -> ExpectedVersion {
+        ExpectedVersion::Any
+    }
+}
+
 /// Aggregate execution semantics for event-sourced aggregates (pure, deterministic).
 ///
 /// This trait defines the **command-query separation** pattern for event-sourced aggregates:
@@ -179,7 +190,7 @@ impl ExpectedVersion {
 /// - **Version tracking**: Implementations should increment `version()` in `apply` to track
 ///   the number of events applied (typically `version += 1` per event).
 pub trait Aggregate: AggregateRoot {
-    type Command: Clone + core::fmt::Debug;
+    type Command: Clone + core::fmt::Debug + 'static;
     type Event: Clone + core::fmt::Debug;
     type Error: core::fmt::Debug;
 
@@ -37,6 +37,7 @@ pub type DomainResult<T> = Result<T, DomainError>;
 /// - **NotFound**: Requested resource doesn't exist
 /// - **Unauthorized**: Permission denied (domain-level authorization)
 /// - **InvalidId**: Identifier parsing/validation failure
+/// - **Coded**: Any of the above, tagged with the [`ErrorCode`] of the rule that raised it
 ///
 /// ## Error Handling
 ///
@@ -77,6 +78,27 @@ pub enum DomainError {
     /// Authorization failure at the domain boundary.
     #[error("unauthorized")]
     Unauthorized,
+
+    /// `error`, raised by the business rule named `code`.
+    #[error("{error}")]
+    Coded { code: ErrorCode, error: Box<DomainError> },
+}
+
+/// Stable name of one business rule.
+///
+/// Callers that react to a specific rejection (e.g. a saga compensating for short stock)
+/// match on the code; messages are for people and may change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ErrorCode(&'static str);
+
+impl ErrorCode {
+    pub const fn new(code: &'static str) -> Self {
+        Self(code)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
 }
 
 impl DomainError {
@@ -99,6 +121,22 @@ impl DomainError {
     pub fn not_found() -> Self {
         Self::NotFound
     }
+
+    /// Tag this error with the rule that raised it.
+    pub fn with_code(self, code: ErrorCode) -> Self {
+        Self::Coded {
+            code,
+            error: Box::new(self),
+        }
+    }
+
+    /// Code of the rule that raised this error, if it was tagged with one.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::Coded { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
 }
 
 
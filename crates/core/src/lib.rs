@@ -6,12 +6,16 @@ pub mod aggregate;
 pub mod entity;
 pub mod error;
 pub mod id;
+pub mod money;
+pub mod tombstone;
 pub mod value_object;
 
-pub use aggregate::{Aggregate, AggregateRoot, ExpectedVersion};
+pub use aggregate::{Aggregate, AggregateRoot, ExpectedVersion, VersionedCommand};
 pub use entity::Entity;
-pub use error::{DomainError, DomainResult};
+pub use error::{DomainError, DomainResult, ErrorCode};
 pub use id::{AggregateId, TenantId, UserId};
+pub use money::{CurrencyConvention, RoundingMode};
+pub use tombstone::DeletionGuard;
 pub use value_object::ValueObject;
 
 
@@ -0,0 +1,155 @@
+//! Money conventions: currency minor units and rounding.
+//!
+//! Amounts are stored as integer **minor units** (cents, yen, fils, ...). Inputs that
+//! arrive as decimal major-unit strings (e.g. `"12.345"`) are converted with a
+//! [`CurrencyConvention`], which fixes how many decimals the currency has and how
+//! excess precision is rounded. Conversion is exact (integer arithmetic only).
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DomainError, DomainResult};
+
+/// Rounding rule applied when an amount has more precision than the currency allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    /// Round half to even ("banker's rounding").
+    #[default]
+    HalfEven,
+    /// Round half away from zero.
+    HalfUp,
+    /// Truncate toward zero.
+    Down,
+}
+
+/// Currency code + minor-unit precision + rounding rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurrencyConvention {
+    /// ISO 4217 code (e.g. `USD`).
+    pub code: String,
+    /// Number of minor-unit decimals (e.g. 2 for USD, 0 for JPY).
+    pub decimals: u32,
+    pub rounding: RoundingMode,
+}
+
+impl CurrencyConvention {
+    /// Convention for an ISO 4217 code, using the currency's standard minor units.
+    pub fn for_code(code: &str, rounding: RoundingMode) -> DomainResult<Self> {
+        let code = code.trim().to_ascii_uppercase();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(DomainError::validation(format!(
+                "invalid currency code `{code}`"
+            )));
+        }
+        let decimals = minor_unit_decimals(&code);
+        Ok(Self {
+            code,
+            decimals,
+            rounding,
+        })
+    }
+
+    /// Convert a decimal major-unit amount (e.g. `"-12.345"`) to minor units.
+    pub fn to_minor_units(&self, amount: &str) -> DomainResult<i64> {
+        let invalid = || DomainError::validation(format!("invalid amount `{amount}`"));
+
+        let s = amount.trim();
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (int_part, frac_part) = s.split_once('.').unwrap_or((s, ""));
+        if int_part.is_empty() && frac_part.is_empty() {
+            return Err(invalid());
+        }
+        if !int_part.chars().chain(frac_part.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let decimals = self.decimals as usize;
+        let digits = |d: &str| -> DomainResult<i128> {
+            if d.is_empty() {
+                Ok(0)
+            } else {
+                d.parse::<i128>().map_err(|_| invalid())
+            }
+        };
+
+        // Split the fraction into the kept digits and the rounding remainder.
+        let (kept, rest) = if frac_part.len() > decimals {
+            frac_part.split_at(decimals)
+        } else {
+            (frac_part, "")
+        };
+        let scale = 10i128.pow(self.decimals);
+        let kept_value = digits(kept)? * 10i128.pow((decimals - kept.len()) as u32);
+        let mut minor = digits(int_part)?
+            .checked_mul(scale)
+            .and_then(|v| v.checked_add(kept_value))
+            .ok_or_else(invalid)?;
+
+        let first = rest.bytes().next().map(|b| b - b'0').unwrap_or(0);
+        let tail_nonzero = rest.bytes().skip(1).any(|b| b != b'0');
+        let round_up = match self.rounding {
+            RoundingMode::Down => false,
+            RoundingMode::HalfUp => first >= 5,
+            RoundingMode::HalfEven => first > 5 || (first == 5 && (tail_nonzero || minor % 2 == 1)),
+        };
+        if round_up {
+            minor += 1;
+        }
+
+        let minor = if negative { -minor } else { minor };
+        i64::try_from(minor).map_err(|_| invalid())
+    }
+}
+
+/// ISO 4217 minor units for `code` (defaults to 2).
+fn minor_unit_decimals(code: &str) -> u32 {
+    match code {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_decimal_currency_rounds_half_even() {
+        let jpy = CurrencyConvention::for_code("jpy", RoundingMode::HalfEven).unwrap();
+        assert_eq!(jpy.code, "JPY");
+        assert_eq!(jpy.decimals, 0);
+        assert_eq!(jpy.to_minor_units("100").unwrap(), 100);
+        assert_eq!(jpy.to_minor_units("100.5").unwrap(), 100);
+        assert_eq!(jpy.to_minor_units("101.5").unwrap(), 102);
+        assert_eq!(jpy.to_minor_units("100.51").unwrap(), 101);
+        assert_eq!(jpy.to_minor_units("-101.5").unwrap(), -102);
+    }
+
+    #[test]
+    fn rounding_modes_apply_at_currency_precision() {
+        let usd = |mode| CurrencyConvention::for_code("USD", mode).unwrap();
+        assert_eq!(usd(RoundingMode::HalfEven).to_minor_units("1.005").unwrap(), 100);
+        assert_eq!(usd(RoundingMode::HalfEven).to_minor_units("1.015").unwrap(), 102);
+        assert_eq!(usd(RoundingMode::HalfUp).to_minor_units("1.005").unwrap(), 101);
+        assert_eq!(usd(RoundingMode::Down).to_minor_units("1.019").unwrap(), 101);
+        assert_eq!(usd(RoundingMode::HalfEven).to_minor_units("12").unwrap(), 1200);
+        assert_eq!(usd(RoundingMode::HalfEven).to_minor_units(".5").unwrap(), 50);
+
+        let kwd = CurrencyConvention::for_code("KWD", RoundingMode::HalfEven).unwrap();
+        assert_eq!(kwd.to_minor_units("1.2345").unwrap(), 1234);
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        let usd = CurrencyConvention::for_code("USD", RoundingMode::HalfEven).unwrap();
+        for bad in ["", ".", "1.2.3", "abc", "1e3", "--1"] {
+            assert!(usd.to_minor_units(bad).is_err(), "{bad}");
+        }
+        assert!(CurrencyConvention::for_code("US", RoundingMode::HalfEven).is_err());
+    }
+}
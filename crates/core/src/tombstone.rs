@@ -0,0 +1,58 @@
+//! Deletion tombstones.
+//!
+//! Deletable aggregates never disappear from the event store. Deleting one appends
+//! a `…Deleted` event that moves it into a terminal `Deleted` state: the stream
+//! stays as an audit trail, read models drop it from default listings, and every
+//! later command is rejected.
+//!
+//! Whether deletion is safe often depends on facts outside the aggregate (e.g. how
+//! many transactions reference it); callers gather them and pass a [`DeletionGuard`]
+//! with the delete command.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DomainError, DomainResult};
+
+/// Caller-supplied facts checked before an aggregate may be deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct DeletionGuard {
+    /// Transactions (orders, invoices, ...) that still reference the aggregate.
+    pub references: u64,
+}
+
+impl DeletionGuard {
+    /// Deletion is only allowed for unreferenced aggregates.
+    pub fn check(&self) -> DomainResult<()> {
+        if self.references > 0 {
+            return Err(DomainError::invariant(format!(
+                "cannot delete: referenced by {} transaction(s)",
+                self.references
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Reject any command against an aggregate in its terminal `Deleted` state.
+pub fn ensure_not_deleted(deleted: bool) -> DomainResult<()> {
+    if deleted {
+        return Err(DomainError::invariant("aggregate is deleted"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn referenced_aggregates_cannot_be_deleted() {
+        assert!(DeletionGuard::default().check().is_ok());
+        assert!(matches!(
+            DeletionGuard { references: 2 }.check(),
+            Err(DomainError::InvariantViolation(_))
+        ));
+        assert!(ensure_not_deleted(false).is_ok());
+        assert!(ensure_not_deleted(true).is_err());
+    }
+}
@@ -11,6 +11,7 @@ use forgeerp_core::{AggregateId, TenantId};
 use forgeerp_inventory::InventoryItemId;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use sqlx::sqlite::SqliteConnectOptions;
 use sqlx::{Row, SqlitePool};
 use tokio::runtime::Runtime;
 
@@ -23,6 +24,7 @@ pub struct LocalCache {
     /// to satisfy the explicit threading requirement and to allow cheap cloning
     /// of the `LocalCache` handle across threads.
     pool: Arc<tokio::sync::Mutex<Option<SqlitePool>>>,
+    db_path: Option<PathBuf>,
 }
 
 // Re-export from shared types module
@@ -35,6 +37,16 @@ impl LocalCache {
     pub fn new() -> Self {
         Self {
             pool: Arc::new(Mutex::new(None)),
+            db_path: None,
+        }
+    }
+
+    /// Create a LocalCache stored in the SQLite file at `path` instead of the app
+    /// data directory (lazy initialization).
+    pub fn with_path(path: PathBuf) -> Self {
+        Self {
+            pool: Arc::new(Mutex::new(None)),
+            db_path: Some(path),
         }
     }
 
@@ -45,8 +57,11 @@ impl LocalCache {
             return Ok(());
         }
 
-        let db_path = cache_db_path()
-            .context("failed to determine cache DB path - ensure app data directory is accessible")?;
+        let db_path = match &self.db_path {
+            Some(path) => path.clone(),
+            None => cache_db_path()
+                .context("failed to determine cache DB path - ensure app data directory is accessible")?,
+        };
         
         // Ensure parent directory exists
         if let Some(parent) = db_path.parent() {
@@ -54,9 +69,11 @@ impl LocalCache {
                 .with_context(|| format!("failed to create cache directory at {:?}", parent))?;
         }
         
-        let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+        let options = SqliteConnectOptions::new()
+            .filename(&db_path)
+            .create_if_missing(true);
 
-        let pool = SqlitePool::connect(&db_url)
+        let pool = SqlitePool::connect_with(options)
             .await
             .with_context(|| format!("failed to create SQLite pool for LocalCache at {:?}", db_path))?;
 
@@ -327,43 +344,67 @@ impl LocalCache {
     }
 
     /// Get the version of a cached read model.
-    pub(crate) fn get_read_model_version(
+    ///
+    /// Async (unlike the public getters) because it is called from inside sync,
+    /// where a nested runtime cannot be started.
+    pub(crate) async fn get_read_model_version(
         &self,
         tenant_id: TenantId,
         aggregate_type: &str,
         aggregate_id: &AggregateId,
     ) -> anyhow::Result<Option<u64>> {
-        let rt = Runtime::new().context("failed to create runtime for get_read_model_version")?;
-        let pool = rt.block_on(async { self.get_pool().await })?;
+        let pool = self.get_pool().await?;
 
         let key_tenant = tenant_id.to_string();
         let key_agg_id = aggregate_id.to_string();
 
-        rt.block_on(async move {
-            let row = sqlx::query(
-                r#"
-                SELECT version
-                FROM read_models
-                WHERE tenant_id = ?1
-                  AND aggregate_type = ?2
-                  AND aggregate_id = ?3
-                "#,
-            )
-            .bind(&key_tenant)
-            .bind(aggregate_type)
-            .bind(&key_agg_id)
-            .fetch_optional(&pool)
-            .await
-            .context("failed to fetch read model version from cache")?;
+        let row = sqlx::query(
+            r#"
+            SELECT version
+            FROM read_models
+            WHERE tenant_id = ?1
+              AND aggregate_type = ?2
+              AND aggregate_id = ?3
+            "#,
+        )
+        .bind(&key_tenant)
+        .bind(aggregate_type)
+        .bind(&key_agg_id)
+        .fetch_optional(&pool)
+        .await
+        .context("failed to fetch read model version from cache")?;
 
-            let row = match row {
-                Some(row) => row,
-                None => return Ok(None),
-            };
+        let row = match row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        let version: Option<i64> = row.try_get("version")?;
+        Ok(version.map(|v| v as u64))
+    }
+
+    /// Evict cached read models (all tenants) whose aggregate type is not in
+    /// `aggregate_types`; returns how many were removed.
+    pub async fn retain_aggregate_types(&self, aggregate_types: &[String]) -> anyhow::Result<u64> {
+        let pool = self.get_pool().await?;
+
+        let mut sql = String::from("DELETE FROM read_models");
+        if !aggregate_types.is_empty() {
+            let placeholders = vec!["?"; aggregate_types.len()].join(", ");
+            sql.push_str(&format!(" WHERE aggregate_type NOT IN ({placeholders})"));
+        }
+
+        let mut query = sqlx::query(&sql);
+        for aggregate_type in aggregate_types {
+            query = query.bind(aggregate_type);
+        }
+
+        let result = query
+            .execute(&pool)
+            .await
+            .context("failed to evict read models outside the sync scope")?;
 
-            let version: Option<i64> = row.try_get("version")?;
-            Ok(version.map(|v| v as u64))
-        })
+        Ok(result.rows_affected())
     }
 
     /// Generic helper: get an arbitrary read model (ignores staleness).
@@ -3,6 +3,11 @@
 //! This module provides a `CommandQueue` abstraction that stores commands in a
 //! durable SQLite table (`command_queue`). Commands are scoped by `TenantId`
 //! and can be safely retried when connectivity is restored.
+//!
+//! A command is marked `Syncing` while it is being sent. If the app dies before the
+//! outcome is recorded, it is moved back to `Pending` the next time the queue is
+//! opened and resent with the same idempotency key, so the API answers the resend
+//! from its idempotency cache instead of applying the command twice.
 
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -11,6 +16,7 @@ use anyhow::Context;
 use chrono::{DateTime, Duration, Utc};
 use forgeerp_core::{AggregateId, TenantId};
 use serde_json::Value;
+use sqlx::sqlite::SqliteConnectOptions;
 use sqlx::{Row, SqlitePool};
 use tokio::runtime::Runtime;
 use uuid::Uuid;
@@ -56,6 +62,7 @@ impl<'q> sqlx::Encode<'q, sqlx::Sqlite> for CommandStatus {
 #[derive(Debug, Clone)]
 pub struct CommandQueue {
     pool: Arc<tokio::sync::Mutex<Option<SqlitePool>>>,
+    db_path: Option<PathBuf>,
 }
 
 impl CommandQueue {
@@ -65,6 +72,16 @@ impl CommandQueue {
     pub fn new() -> Self {
         Self {
             pool: Arc::new(tokio::sync::Mutex::new(None)),
+            db_path: None,
+        }
+    }
+
+    /// Create a CommandQueue stored in the SQLite file at `path` instead of the
+    /// app data directory (lazy initialization).
+    pub fn with_path(path: PathBuf) -> Self {
+        Self {
+            pool: Arc::new(tokio::sync::Mutex::new(None)),
+            db_path: Some(path),
         }
     }
 
@@ -75,18 +92,25 @@ impl CommandQueue {
             return Ok(());
         }
 
-        let db_path = command_db_path()
-            .context("failed to determine command queue DB path - ensure app data directory is accessible")?;
-        
+        let db_path = match &self.db_path {
+            Some(path) => path.clone(),
+            None => command_db_path()
+                .context("failed to determine command queue DB path - ensure app data directory is accessible")?,
+        };
+
         // Ensure parent directory exists
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)
                 .with_context(|| format!("failed to create command queue directory at {:?}", parent))?;
         }
         
-        let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+        // Without `create_if_missing` a fresh install never gets a database file
+        // and every queued command would only live in memory.
+        let options = SqliteConnectOptions::new()
+            .filename(&db_path)
+            .create_if_missing(true);
 
-        let pool = SqlitePool::connect(&db_url)
+        let pool = SqlitePool::connect_with(options)
             .await
             .with_context(|| format!("failed to create SQLite pool for CommandQueue at {:?}", db_path))?;
 
@@ -101,7 +125,9 @@ impl CommandQueue {
                 status        TEXT NOT NULL,
                 created_at    TEXT NOT NULL,
                 synced_at     TEXT NULL,
-                error         TEXT NULL
+                error         TEXT NULL,
+                idempotency_key TEXT NULL,
+                retry_count   INTEGER NOT NULL DEFAULT 0
             )
             "#,
         )
@@ -109,6 +135,20 @@ impl CommandQueue {
         .await
         .context("failed to create command_queue table")?;
 
+        // Databases created before these columns existed
+        add_column_if_missing(&pool, "idempotency_key", "TEXT NULL").await?;
+        add_column_if_missing(&pool, "retry_count", "INTEGER NOT NULL DEFAULT 0").await?;
+
+        // Commands still `Syncing` were in flight when the app last stopped.
+        let interrupted = sqlx::query("UPDATE command_queue SET status = 'Pending' WHERE status = 'Syncing'")
+            .execute(&pool)
+            .await
+            .context("failed to recover interrupted commands")?
+            .rows_affected();
+        if interrupted > 0 {
+            tracing::info!("Recovered {} command(s) interrupted mid-sync", interrupted);
+        }
+
         *pool_guard = Some(pool);
         Ok(())
     }
@@ -143,6 +183,8 @@ impl CommandQueue {
             created_at,
             synced_at: None,
             error: None,
+            idempotency_key: id,
+            retry_count: 0,
         };
 
         let rt = match Runtime::new() {
@@ -173,9 +215,11 @@ impl CommandQueue {
                     status,
                     created_at,
                     synced_at,
-                    error
+                    error,
+                    idempotency_key,
+                    retry_count
                 )
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, NULL, NULL)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, NULL, NULL, ?1, 0)
                 "#,
             )
             .bind(id.to_string())
@@ -229,7 +273,9 @@ impl CommandQueue {
                     status,
                     created_at,
                     synced_at,
-                    error
+                    error,
+                    idempotency_key,
+                    retry_count
                 FROM command_queue
                 WHERE tenant_id = ?1
                   AND status IN ('Pending', 'Failed')
@@ -258,11 +304,16 @@ impl CommandQueue {
         }
     }
 
-    /// Mark a command as syncing.
+    /// Mark a command as syncing (call before sending it).
     pub fn mark_syncing(&self, id: Uuid) {
         self.update_status(id, CommandStatus::Syncing, None);
     }
 
+    /// Move a command back to Pending without counting a failed attempt.
+    pub fn mark_pending(&self, id: Uuid) {
+        self.update_status(id, CommandStatus::Pending, None);
+    }
+
     /// Mark a command as successfully synced.
     pub fn mark_synced(&self, id: Uuid) {
         let now = Some(Utc::now());
@@ -308,6 +359,122 @@ impl CommandQueue {
         }
     }
 
+    /// Move a command back to Pending after an attempt that did not reach the server.
+    pub fn record_retry(&self, id: Uuid, error: String) {
+        let rt = match Runtime::new() {
+            Ok(rt) => rt,
+            Err(err) => {
+                tracing::error!("failed to create runtime for record_retry: {err:?}");
+                return;
+            }
+        };
+
+        let pool = match rt.block_on(async { self.get_pool().await }) {
+            Ok(pool) => pool,
+            Err(err) => {
+                tracing::warn!("failed to get pool for record_retry (queue may not be initialized): {err:?}");
+                return;
+            }
+        };
+
+        if let Err(err) = rt.block_on(async move {
+            sqlx::query(
+                r#"
+                UPDATE command_queue
+                SET status = 'Pending',
+                    error = ?2,
+                    retry_count = retry_count + 1
+                WHERE id = ?1
+                "#,
+            )
+            .bind(id.to_string())
+            .bind(error)
+            .execute(&pool)
+            .await
+            .context("failed to record command retry")?;
+
+            Ok::<(), anyhow::Error>(())
+        }) {
+            tracing::error!("failed to record command retry: {err:?}");
+        }
+    }
+
+    /// Store a command's rewritten payload and idempotency key before it is replayed,
+    /// so a resend after a crash uses the same key as the replay.
+    pub fn update_for_replay(&self, cmd: &QueuedCommand) {
+        let rt = match Runtime::new() {
+            Ok(rt) => rt,
+            Err(err) => {
+                tracing::error!("failed to create runtime for update_for_replay: {err:?}");
+                return;
+            }
+        };
+
+        let pool = match rt.block_on(async { self.get_pool().await }) {
+            Ok(pool) => pool,
+            Err(err) => {
+                tracing::warn!("failed to get pool for update_for_replay (queue may not be initialized): {err:?}");
+                return;
+            }
+        };
+
+        let id = cmd.id;
+        let payload = cmd.payload.to_string();
+        let idempotency_key = cmd.idempotency_key.to_string();
+
+        if let Err(err) = rt.block_on(async move {
+            sqlx::query(
+                r#"
+                UPDATE command_queue
+                SET payload = ?2,
+                    idempotency_key = ?3
+                WHERE id = ?1
+                "#,
+            )
+            .bind(id.to_string())
+            .bind(payload)
+            .bind(idempotency_key)
+            .execute(&pool)
+            .await
+            .context("failed to update command for replay")?;
+
+            Ok::<(), anyhow::Error>(())
+        }) {
+            tracing::error!("failed to update command for replay: {err:?}");
+        }
+    }
+
+    /// Drop a command from the queue without syncing it.
+    pub fn discard(&self, id: Uuid) {
+        let rt = match Runtime::new() {
+            Ok(rt) => rt,
+            Err(err) => {
+                tracing::error!("failed to create runtime for discard: {err:?}");
+                return;
+            }
+        };
+
+        let pool = match rt.block_on(async { self.get_pool().await }) {
+            Ok(pool) => pool,
+            Err(err) => {
+                tracing::warn!("failed to get pool for discard (queue may not be initialized): {err:?}");
+                return;
+            }
+        };
+
+        if let Err(err) = rt.block_on(async move {
+            sqlx::query("DELETE FROM command_queue WHERE id = ?1")
+                .bind(id.to_string())
+                .execute(&pool)
+                .await
+                .context("failed to discard command")?;
+
+            Ok::<(), anyhow::Error>(())
+        }) {
+            tracing::error!("failed to discard command: {err:?}");
+        }
+    }
+
     /// Retry a failed command by moving it back to Pending and clearing the error.
     pub fn retry_failed(&self, id: Uuid) {
         let rt = match Runtime::new() {
@@ -489,6 +656,14 @@ fn row_to_command(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<QueuedCommand>
 
     let error: Option<String> = row.try_get("error")?;
 
+    // Rows queued before idempotency keys were stored used their id as the key.
+    let idempotency_key = match row.try_get::<Option<String>, _>("idempotency_key")? {
+        Some(key) => Uuid::parse_str(&key).context("invalid UUID in command_queue.idempotency_key")?,
+        None => id,
+    };
+
+    let retry_count: i64 = row.try_get("retry_count")?;
+
     Ok(QueuedCommand {
         id,
         tenant_id,
@@ -499,9 +674,29 @@ fn row_to_command(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<QueuedCommand>
         created_at,
         synced_at,
         error,
+        idempotency_key,
+        retry_count: retry_count.try_into().unwrap_or(u32::MAX),
     })
 }
 
+/// Add a column to `command_queue` unless it already exists.
+async fn add_column_if_missing(pool: &SqlitePool, column: &str, definition: &str) -> anyhow::Result<()> {
+    let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info('command_queue') WHERE name = ?1")
+        .bind(column)
+        .fetch_one(pool)
+        .await
+        .with_context(|| format!("failed to inspect command_queue for column {column}"))?;
+
+    if exists == 0 {
+        sqlx::query(&format!("ALTER TABLE command_queue ADD COLUMN {column} {definition}"))
+            .execute(pool)
+            .await
+            .with_context(|| format!("failed to add column {column} to command_queue"))?;
+    }
+
+    Ok(())
+}
+
 /// Resolve the path to the SQLite database for the command queue.
 ///
 /// We reuse the same physical database file as the local cache:
@@ -528,3 +723,88 @@ fn command_db_path() -> anyhow::Result<PathBuf> {
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn temp_db() -> PathBuf {
+        std::env::temp_dir().join(format!("forgeerp-command-queue-{}.db", Uuid::now_v7()))
+    }
+
+    #[test]
+    fn queued_commands_survive_a_restart() {
+        let path = temp_db();
+        let tenant_id = TenantId::new();
+
+        let queued = CommandQueue::with_path(path.clone()).enqueue(
+            tenant_id,
+            "inventory.adjust_stock".to_string(),
+            AggregateId::new(),
+            json!({ "delta": 3 }),
+        );
+
+        let pending = CommandQueue::with_path(path.clone()).list_pending(tenant_id);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, queued.id);
+        assert_eq!(pending[0].idempotency_key, queued.id);
+        assert_eq!(pending[0].payload, json!({ "delta": 3 }));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn a_command_interrupted_mid_sync_is_pending_again_with_the_same_key() {
+        let path = temp_db();
+        let tenant_id = TenantId::new();
+
+        let queue = CommandQueue::with_path(path.clone());
+        let queued = queue.enqueue(
+            tenant_id,
+            "inventory.adjust_stock".to_string(),
+            AggregateId::new(),
+            json!({ "delta": 3 }),
+        );
+        queue.mark_syncing(queued.id);
+        assert!(queue.list_pending(tenant_id).is_empty());
+        drop(queue);
+
+        // Reopening the file stands in for the app starting again
+        let pending = CommandQueue::with_path(path.clone()).list_pending(tenant_id);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].status, CommandStatus::Pending);
+        assert_eq!(pending[0].idempotency_key, queued.idempotency_key);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn failed_attempts_are_counted_and_synced_commands_leave_the_queue() {
+        let path = temp_db();
+        let tenant_id = TenantId::new();
+
+        let queue = CommandQueue::with_path(path.clone());
+        let queued = queue.enqueue(
+            tenant_id,
+            "inventory.adjust_stock".to_string(),
+            AggregateId::new(),
+            json!({ "delta": 3 }),
+        );
+
+        queue.mark_syncing(queued.id);
+        queue.record_retry(queued.id, "network error: timed out".to_string());
+        queue.mark_syncing(queued.id);
+        queue.record_retry(queued.id, "network error: timed out".to_string());
+
+        let pending = queue.list_pending(tenant_id);
+        assert_eq!(pending[0].retry_count, 2);
+        assert_eq!(pending[0].error.as_deref(), Some("network error: timed out"));
+
+        queue.mark_synced(queued.id);
+        assert!(CommandQueue::with_path(path.clone()).list_pending(tenant_id).is_empty());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
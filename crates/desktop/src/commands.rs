@@ -8,9 +8,14 @@ use tauri::State;
 
 use crate::cache::{LocalCache, InventoryReadModel};
 use crate::command_queue::{CommandQueue, QueuedCommand};
-use crate::offline::{OfflineMode, ConnectivityState};
+use crate::offline::OfflineMode;
 use crate::sync::SyncClient;
-use crate::sync_manager::{SyncManager, ConflictResolution, SyncReadModelResult, SyncResult};
+use crate::sync_manager::{
+    apply_conflict_strategy, CommandOutcome, ConflictResolution, ConflictStrategy, SyncManager,
+    SyncReadModelResult, SyncResult,
+};
+use crate::sync_worker::SyncBackoff;
+use crate::types::ConnectivityStatus;
 
 /// Application state shared across Tauri commands.
 #[derive(Clone)]
@@ -20,6 +25,7 @@ pub struct AppState {
     pub sync_client: Arc<SyncClient>,
     pub sync_manager: Arc<SyncManager>,
     pub offline_mode: Arc<tokio::sync::Mutex<OfflineMode>>,
+    pub sync_backoff: Arc<tokio::sync::Mutex<SyncBackoff>>,
 }
 
 impl AppState {
@@ -36,6 +42,7 @@ impl AppState {
             cache.clone(),
         ));
         let offline_mode = Arc::new(tokio::sync::Mutex::new(OfflineMode::new(api_url)));
+        let sync_backoff = Arc::new(tokio::sync::Mutex::new(SyncBackoff::default()));
 
         Self {
             cache,
@@ -43,6 +50,7 @@ impl AppState {
             sync_client,
             sync_manager,
             offline_mode,
+            sync_backoff,
         }
     }
 
@@ -60,6 +68,7 @@ impl AppState {
             cache.clone(),
         ));
         let offline_mode = Arc::new(tokio::sync::Mutex::new(OfflineMode::new(api_url)));
+        let sync_backoff = Arc::new(tokio::sync::Mutex::new(SyncBackoff::default()));
 
         Self {
             cache,
@@ -67,6 +76,7 @@ impl AppState {
             sync_client,
             sync_manager,
             offline_mode,
+            sync_backoff,
         }
     }
 
@@ -139,6 +149,7 @@ pub async fn adjust_stock(
 
     // If online, attempt immediate sync
     if !is_offline {
+        state.command_queue.mark_syncing(queued.id);
         if let Err(e) = state
             .sync_manager
             .sync_command(&queued)
@@ -146,6 +157,7 @@ pub async fn adjust_stock(
         {
             tracing::warn!("Failed to sync command immediately: {}", e);
             // Command is queued, will sync later
+            state.command_queue.record_retry(queued.id, e.to_string());
         } else {
             state.command_queue.mark_synced(queued.id);
             tracing::info!("Command synced immediately");
@@ -193,13 +205,41 @@ pub async fn sync_now(
     Ok(result)
 }
 
-/// Get the current connectivity state.
+/// Get the current connectivity state and, while the background sync worker is
+/// backing off after failures, when it will try again.
 #[tauri::command]
 pub async fn get_connectivity_state(
     state: State<'_, AppState>,
-) -> Result<ConnectivityState, String> {
-    let offline_guard = state.offline_mode.lock().await;
-    Ok(offline_guard.state())
+) -> Result<ConnectivityStatus, String> {
+    let connectivity = state.offline_mode.lock().await.state();
+    let backoff = state.sync_backoff.lock().await;
+
+    Ok(ConnectivityStatus {
+        state: connectivity,
+        consecutive_failures: backoff.consecutive_failures(),
+        retry_in_secs: backoff.retry_in().map(|wait| wait.as_secs()),
+    })
+}
+
+/// Aggregate types whose read models are synced.
+#[tauri::command]
+pub async fn get_sync_scope(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    Ok(state.sync_manager.sync_scope())
+}
+
+/// Change which aggregate types are synced; cached read models of types removed
+/// from the scope are evicted.
+#[tauri::command]
+pub async fn set_sync_scope(
+    aggregate_types: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.sync_client.set_sync_scope(aggregate_types.clone());
+    state
+        .sync_manager
+        .set_sync_scope(aggregate_types)
+        .await
+        .map_err(|e| format!("Failed to update sync scope: {}", e))
 }
 
 /// List all pending commands for a tenant.
@@ -218,11 +258,15 @@ pub async fn list_pending_commands(
 }
 
 /// Resolve a conflict by applying the specified resolution strategy.
+///
+/// `command_id` is set for conflicts reported on a queued command (`Manual` strategy):
+/// `UseRemote` drops the command, `UseLocal` replays it against the server's version.
 #[tauri::command]
 pub async fn resolve_conflict(
     conflict_aggregate_type: String,
     conflict_aggregate_id: String,
     resolution: ConflictResolution,
+    command_id: Option<String>,
     tenant_id: String,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
@@ -234,6 +278,11 @@ pub async fn resolve_conflict(
         .parse::<AggregateId>()
         .map_err(|e| format!("Invalid aggregate_id: {}", e))?;
 
+    let command_id = command_id
+        .map(|id| id.parse::<uuid::Uuid>())
+        .transpose()
+        .map_err(|e| format!("Invalid command_id: {}", e))?;
+
     tracing::info!(
         "Resolving conflict for {} {} with strategy: {:?}",
         conflict_aggregate_type,
@@ -243,6 +292,10 @@ pub async fn resolve_conflict(
 
     match resolution {
         ConflictResolution::UseRemote => {
+            if let Some(id) = command_id {
+                state.command_queue.discard(id);
+            }
+
             // Fetch and cache the remote version
             let result = state
                 .sync_manager
@@ -262,9 +315,41 @@ pub async fn resolve_conflict(
             }
         }
         ConflictResolution::UseLocal => {
-            // Keep local version - no action needed
-            // In a full implementation, would POST local version to API
-            tracing::info!("Keeping local version (UseLocal resolution)");
+            let queued = command_id.and_then(|id| {
+                state
+                    .command_queue
+                    .list_pending(tenant_id)
+                    .into_iter()
+                    .find(|cmd| cmd.id == id)
+            });
+
+            match queued {
+                Some(cmd) => {
+                    let outcome = apply_conflict_strategy(
+                        state.sync_manager.as_ref(),
+                        ConflictStrategy::ClientWins,
+                        &cmd,
+                        &conflict_aggregate_type,
+                        |replay| state.command_queue.update_for_replay(replay),
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to replay command: {}", e))?;
+
+                    match outcome {
+                        CommandOutcome::Synced => {
+                            state.command_queue.mark_synced(cmd.id);
+                            tracing::info!("Replayed command {} (UseLocal resolution)", cmd.id);
+                        }
+                        _ => {
+                            return Err("Conflict still exists after resolution attempt".to_string());
+                        }
+                    }
+                }
+                None => {
+                    // Keep local version - no action needed
+                    tracing::info!("Keeping local version (UseLocal resolution)");
+                }
+            }
         }
         ConflictResolution::Merge => {
             // Future: implement merge logic
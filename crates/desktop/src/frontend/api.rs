@@ -5,7 +5,7 @@ use wasm_bindgen_futures::JsFuture;
 use web_sys::window;
 
 use crate::types::{
-    ConflictResolution, ConnectivityState, InventoryReadModel, QueuedCommand, SyncResult,
+    ConflictResolution, ConnectivityStatus, InventoryReadModel, QueuedCommand, SyncResult,
 };
 
 /// Helper to invoke Tauri commands from WASM.
@@ -81,12 +81,27 @@ pub async fn sync_now(tenant_id: String) -> Result<SyncResult, String> {
     invoke_tauri("sync_now", args).await
 }
 
-/// Get the current connectivity state.
-pub async fn get_connectivity_state() -> Result<ConnectivityState, String> {
+/// Get the current connectivity state and background sync retry schedule.
+pub async fn get_connectivity_state() -> Result<ConnectivityStatus, String> {
     let args = JsValue::NULL;
     invoke_tauri("get_connectivity_state", args).await
 }
 
+/// Aggregate types whose read models are synced.
+pub async fn get_sync_scope() -> Result<Vec<String>, String> {
+    let args = JsValue::NULL;
+    invoke_tauri("get_sync_scope", args).await
+}
+
+/// Change which aggregate types are synced.
+pub async fn set_sync_scope(aggregate_types: Vec<String>) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "aggregate_types": aggregate_types
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    
+    invoke_tauri("set_sync_scope", args).await
+}
+
 /// List all pending commands for a tenant.
 pub async fn list_pending_commands(tenant_id: String) -> Result<Vec<QueuedCommand>, String> {
     let args = serde_wasm_bindgen::to_value(&serde_json::json!({
@@ -101,12 +116,14 @@ pub async fn resolve_conflict(
     conflict_aggregate_type: String,
     conflict_aggregate_id: String,
     resolution: ConflictResolution,
+    command_id: Option<String>,
     tenant_id: String,
 ) -> Result<(), String> {
     let args = serde_wasm_bindgen::to_value(&serde_json::json!({
         "conflict_aggregate_type": conflict_aggregate_type,
         "conflict_aggregate_id": conflict_aggregate_id,
         "resolution": resolution,
+        "command_id": command_id,
         "tenant_id": tenant_id
     })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
     
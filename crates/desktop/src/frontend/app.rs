@@ -34,7 +34,7 @@ fn InventoryListPage() -> impl IntoView {
     let connectivity_state = create_resource(
         || (),
         |_| async move {
-            api::get_connectivity_state().await.unwrap_or(crate::ConnectivityState::Offline)
+            api::get_connectivity_state().await.ok()
         },
     );
 
@@ -49,8 +49,8 @@ fn InventoryListPage() -> impl IntoView {
                 <h1>"ForgeERP Desktop"</h1>
                 <div class="connectivity">
                     {move || {
-                        connectivity_state.get().map(|state| {
-                            match state {
+                        connectivity_state.get().map(|status| {
+                            match status.map_or(crate::ConnectivityState::Offline, |s| s.state) {
                                 crate::ConnectivityState::Online => {
                                     view! { <span class="status online">"Online"</span> }
                                 }
@@ -60,6 +60,15 @@ fn InventoryListPage() -> impl IntoView {
                             }
                         })
                     }}
+                    {move || {
+                        connectivity_state
+                            .get()
+                            .flatten()
+                            .and_then(|status| status.retry_in_secs)
+                            .map(|secs| {
+                                view! { <span class="status retrying">{format!("Retrying in {}s", secs)}</span> }
+                            })
+                    }}
                 </div>
             </header>
 
@@ -36,6 +36,8 @@ async fn main() {
             adjust_stock,
             sync_now,
             get_connectivity_state,
+            get_sync_scope,
+            set_sync_scope,
             list_pending_commands,
             resolve_conflict,
         ])
@@ -1,5 +1,8 @@
 //! Explicit sync/reconnect with the API.
 
+#[cfg(feature = "tauri")]
+use std::sync::RwLock;
+
 #[cfg(feature = "tauri")]
 use anyhow::Result;
 
@@ -12,6 +15,8 @@ use forgeerp_core::TenantId;
 #[cfg(feature = "tauri")]
 use forgeerp_inventory::InventoryItemId;
 #[cfg(feature = "tauri")]
+use crate::sync_manager::default_sync_scope;
+#[cfg(feature = "tauri")]
 
 /// Client for syncing read models from the API.
 ///
@@ -21,6 +26,7 @@ use forgeerp_inventory::InventoryItemId;
 pub struct SyncClient {
     api_url: String,
     token: Option<String>,
+    sync_scope: RwLock<Vec<String>>,
 }
 
 #[cfg(feature = "tauri")]
@@ -29,6 +35,7 @@ impl SyncClient {
         Self {
             api_url,
             token: None,
+            sync_scope: RwLock::new(default_sync_scope()),
         }
     }
 
@@ -36,9 +43,25 @@ impl SyncClient {
         Self {
             api_url,
             token: Some(token),
+            sync_scope: RwLock::new(default_sync_scope()),
         }
     }
 
+    /// Only pull read models of these aggregate types (default: `DEFAULT_SYNC_SCOPE`).
+    pub fn with_sync_scope(self, sync_scope: Vec<String>) -> Self {
+        self.set_sync_scope(sync_scope);
+        self
+    }
+
+    pub fn set_sync_scope(&self, sync_scope: Vec<String>) {
+        *self.sync_scope.write().unwrap() = sync_scope;
+    }
+
+    /// Whether read models of `aggregate_type` are pulled from the API.
+    pub fn in_sync_scope(&self, aggregate_type: &str) -> bool {
+        self.sync_scope.read().unwrap().iter().any(|t| t == aggregate_type)
+    }
+
     /// Check connectivity by hitting the health endpoint.
     pub async fn check_connectivity(&self) -> bool {
         let client = reqwest::Client::new();
@@ -47,12 +70,18 @@ impl SyncClient {
     }
 
     /// Sync a specific inventory item from the API and update local cache.
+    ///
+    /// Does nothing when `inventory_item` is outside the sync scope.
     pub async fn sync_inventory_item(
         &self,
         cache: &LocalCache,
         tenant_id: TenantId,
         item_id: &InventoryItemId,
     ) -> Result<(), SyncError> {
+        if !self.in_sync_scope("inventory_item") {
+            return Ok(());
+        }
+
         let client = reqwest::Client::new();
         let url = format!("{}/inventory/items/{}", self.api_url, item_id.0);
         let mut req = client.get(&url);
@@ -2,13 +2,14 @@
 //!
 //! This module provides a `SyncManager` that:
 //! - Syncs queued commands to the API (POST to appropriate endpoints)
-//! - Fetches latest read models from the API
+//! - Fetches latest read models from the API, for the aggregate types in its sync scope
 //! - Detects conflicts when local version < remote version
+//! - Applies a `ConflictStrategy` when a replayed command is rejected as stale
 //! - Handles retries with exponential backoff
 //! - Preserves command ordering
 
 #[cfg(feature = "tauri")]
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 #[cfg(feature = "tauri")]
 use std::time::Duration;
 
@@ -18,6 +19,8 @@ use chrono::Utc;
 use forgeerp_core::{AggregateId, TenantId};
 #[cfg(feature = "tauri")]
 use serde_json::Value;
+#[cfg(feature = "tauri")]
+use uuid::Uuid;
 
 #[cfg(feature = "tauri")]
 use crate::cache::LocalCache;
@@ -27,6 +30,103 @@ use crate::command_queue::{CommandQueue, QueuedCommand};
 // Re-export from shared types module
 pub use crate::types::{Conflict, ConflictResolution, SyncResult};
 
+/// Aggregate types whose read models are synced unless the scope is narrowed.
+pub const DEFAULT_SYNC_SCOPE: &[&str] = &["inventory_item", "sales_order", "product"];
+
+/// `DEFAULT_SYNC_SCOPE` as an owned list.
+pub fn default_sync_scope() -> Vec<String> {
+    DEFAULT_SYNC_SCOPE.iter().map(|t| t.to_string()).collect()
+}
+
+/// How to handle a queued command the server rejects because the aggregate moved on
+/// while the client was offline (`409`, i.e. `DispatchError::Concurrency`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// Drop the command and keep the server's state.
+    #[default]
+    ServerWins,
+    /// Replay the command against the server's current version.
+    ClientWins,
+    /// Leave the command queued and report the conflict for the user to resolve.
+    Manual,
+}
+
+/// The calls conflict handling needs from the API, so it can run against a stub.
+#[cfg(feature = "tauri")]
+pub trait SyncApi {
+    /// Send a queued command; a stale write comes back as `SyncError::Conflict`.
+    fn send_command(&self, cmd: &QueuedCommand) -> impl Future<Output = Result<(), SyncError>> + Send;
+
+    /// Current server version of an aggregate's read model.
+    fn remote_version(
+        &self,
+        aggregate_type: &str,
+        aggregate_id: &AggregateId,
+    ) -> impl Future<Output = Result<u64, SyncError>> + Send;
+}
+
+/// What became of a queued command after syncing it.
+#[cfg(feature = "tauri")]
+#[derive(Debug)]
+pub enum CommandOutcome {
+    /// Accepted by the server (possibly after a `ClientWins` replay).
+    Synced,
+    /// Dropped in favour of the server's state (`ServerWins`).
+    Discarded,
+    /// Still queued; the conflict is reported to the UI.
+    Queued(Conflict),
+}
+
+/// Resolve a command the server rejected with a concurrency conflict.
+///
+/// `ClientWins` rewrites the payload's `expected_version` to the server's version and
+/// sends it once more; if that is rejected too, the command stays queued like `Manual`.
+/// The rewritten command needs a fresh idempotency key (the API would reject the old
+/// key with a different body), and is handed to `save_replay` before it is sent.
+#[cfg(feature = "tauri")]
+pub async fn apply_conflict_strategy<A: SyncApi>(
+    api: &A,
+    strategy: ConflictStrategy,
+    cmd: &QueuedCommand,
+    aggregate_type: &str,
+    save_replay: impl FnOnce(&QueuedCommand),
+) -> Result<CommandOutcome, SyncError> {
+    if strategy == ConflictStrategy::ServerWins {
+        return Ok(CommandOutcome::Discarded);
+    }
+
+    let remote_version = api.remote_version(aggregate_type, &cmd.aggregate_id).await?;
+    let conflict = Conflict {
+        aggregate_type: aggregate_type.to_string(),
+        aggregate_id: cmd.aggregate_id,
+        local_version: cmd.payload.get("expected_version").and_then(Value::as_u64),
+        remote_version,
+        // The local command is kept until the user picks a resolution.
+        resolution: ConflictResolution::UseLocal,
+        command_id: Some(cmd.id),
+    };
+
+    if strategy == ConflictStrategy::ClientWins {
+        let mut rebased = cmd.clone();
+        if let Some(payload) = rebased.payload.as_object_mut() {
+            payload.insert("expected_version".to_string(), Value::from(remote_version));
+        }
+        rebased.idempotency_key = Uuid::now_v7();
+        save_replay(&rebased);
+
+        match api.send_command(&rebased).await {
+            Ok(()) => return Ok(CommandOutcome::Synced),
+            Err(SyncError::Conflict(reason)) => {
+                tracing::warn!("Replay of command {} conflicted again: {}", cmd.id, reason);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(CommandOutcome::Queued(conflict))
+}
+
 /// Enhanced sync client with bi-directional sync and conflict detection.
 #[cfg(feature = "tauri")]
 pub struct SyncManager {
@@ -34,6 +134,8 @@ pub struct SyncManager {
     token: Option<String>,
     command_queue: Arc<CommandQueue>,
     cache: Arc<LocalCache>,
+    conflict_strategy: ConflictStrategy,
+    sync_scope: RwLock<Vec<String>>,
 }
 
 #[cfg(feature = "tauri")]
@@ -49,6 +151,8 @@ impl SyncManager {
             token: None,
             command_queue,
             cache,
+            conflict_strategy: ConflictStrategy::default(),
+            sync_scope: RwLock::new(default_sync_scope()),
         }
     }
 
@@ -64,16 +168,58 @@ impl SyncManager {
             token: Some(token),
             command_queue,
             cache,
+            conflict_strategy: ConflictStrategy::default(),
+            sync_scope: RwLock::new(default_sync_scope()),
         }
     }
 
+    /// Use `strategy` for commands that conflict with server state (default: `ServerWins`).
+    pub fn with_conflict_strategy(mut self, strategy: ConflictStrategy) -> Self {
+        self.conflict_strategy = strategy;
+        self
+    }
+
+    pub fn conflict_strategy(&self) -> ConflictStrategy {
+        self.conflict_strategy
+    }
+
+    /// Only pull read models of these aggregate types (default: `DEFAULT_SYNC_SCOPE`).
+    pub fn with_sync_scope(self, sync_scope: Vec<String>) -> Self {
+        *self.sync_scope.write().unwrap() = sync_scope;
+        self
+    }
+
+    pub fn sync_scope(&self) -> Vec<String> {
+        self.sync_scope.read().unwrap().clone()
+    }
+
+    /// Whether read models of `aggregate_type` are pulled from the API.
+    pub fn in_sync_scope(&self, aggregate_type: &str) -> bool {
+        self.sync_scope.read().unwrap().iter().any(|t| t == aggregate_type)
+    }
+
+    /// Change the sync scope and evict cached read models of types no longer in it.
+    pub async fn set_sync_scope(&self, sync_scope: Vec<String>) -> Result<(), SyncError> {
+        *self.sync_scope.write().unwrap() = sync_scope.clone();
+
+        let evicted = self
+            .cache
+            .retain_aggregate_types(&sync_scope)
+            .await
+            .map_err(|e| SyncError::Cache(e.to_string()))?;
+        tracing::info!("Sync scope set to {:?}, evicted {} cached read models", sync_scope, evicted);
+
+        Ok(())
+    }
+
     /// Perform a full bi-directional sync for a tenant.
     ///
     /// This method:
     /// 1. Syncs all pending commands to the API (in created_at order)
     /// 2. Fetches latest read models for aggregates referenced by commands
     /// 3. Detects conflicts when local version < remote version
-    /// 4. Returns a `SyncResult` with sync status and conflicts
+    /// 4. Applies the conflict strategy to commands rejected as stale
+    /// 5. Returns a `SyncResult` with sync status and conflicts
     pub async fn sync_tenant(
         &self,
         tenant_id: TenantId,
@@ -91,14 +237,35 @@ impl SyncManager {
         tracing::info!("Found {} pending commands to sync", pending.len());
 
         for cmd in pending {
-            match self.sync_command(&cmd).await {
-                Ok(()) => {
+            let agg_type = self.aggregate_type_from_command_type(&cmd.command_type);
+
+            // Left `Syncing` if the app dies before the outcome is recorded
+            self.command_queue.mark_syncing(cmd.id);
+
+            let outcome = match self.sync_command(&cmd).await {
+                Ok(()) => Ok(CommandOutcome::Synced),
+                Err(SyncError::Conflict(reason)) => {
+                    tracing::warn!(
+                        "Command {} conflicts with server state ({}), applying {:?}",
+                        cmd.id,
+                        reason,
+                        self.conflict_strategy
+                    );
+                    apply_conflict_strategy(self, self.conflict_strategy, &cmd, &agg_type, |replay| {
+                        self.command_queue.update_for_replay(replay)
+                    })
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+
+            match outcome {
+                Ok(CommandOutcome::Synced) => {
                     self.command_queue.mark_synced(cmd.id);
                     result.synced_commands.push(cmd.id);
                     tracing::info!("Successfully synced command: {}", cmd.id);
 
                     // After successful command sync, fetch the updated read model
-                    let agg_type = self.aggregate_type_from_command_type(&cmd.command_type);
                     if let Ok(Some(read_model_result)) = self
                         .sync_read_model(tenant_id, &agg_type, &cmd.aggregate_id)
                         .await
@@ -113,9 +280,27 @@ impl SyncManager {
                         }
                     }
                 }
-                Err(SyncError::Network(_)) | Err(SyncError::Offline) => {
-                    // Network error - mark as syncing and will retry later
-                    self.command_queue.mark_syncing(cmd.id);
+                Ok(CommandOutcome::Discarded) => {
+                    self.command_queue.discard(cmd.id);
+                    tracing::info!("Discarded command {} in favour of server state", cmd.id);
+
+                    // Whatever the local version was, the server's read model now applies
+                    if let Ok(Some(_)) = self
+                        .sync_read_model(tenant_id, &agg_type, &cmd.aggregate_id)
+                        .await
+                    {
+                        result.synced_read_models.push((agg_type, cmd.aggregate_id));
+                    }
+                }
+                Ok(CommandOutcome::Queued(conflict)) => {
+                    // Back to Pending; the UI resolves it through `resolve_conflict`
+                    self.command_queue.mark_pending(cmd.id);
+                    tracing::warn!("Command {} left queued until its conflict is resolved", cmd.id);
+                    result.conflicts.push(conflict);
+                }
+                Err(e @ SyncError::Network(_)) | Err(e @ SyncError::Offline) => {
+                    // Network error - back to Pending, will retry later
+                    self.command_queue.record_retry(cmd.id, e.to_string());
                     tracing::warn!("Network error syncing command {}, will retry", cmd.id);
                 }
                 Err(e) => {
@@ -145,7 +330,10 @@ impl SyncManager {
         let endpoint = self.command_endpoint(&cmd.command_type, &cmd.aggregate_id);
         let url = format!("{}{}", self.api_url, endpoint);
 
-        let mut req = client.post(&url).json(&cmd.payload);
+        let mut req = client
+            .post(&url)
+            .header("Idempotency-Key", cmd.idempotency_key.to_string())
+            .json(&cmd.payload);
 
         if let Some(token) = &self.token {
             req = req.bearer_auth(token);
@@ -225,52 +413,26 @@ impl SyncManager {
     /// Returns:
     /// - `Ok(Some(SyncReadModelResult::Conflict))` if a conflict is detected
     /// - `Ok(Some(SyncReadModelResult::Synced))` if successfully synced
-    /// - `Ok(None)` if no local version exists (first sync)
+    /// - `Ok(None)` if the aggregate type is outside the sync scope (nothing is fetched)
     pub async fn sync_read_model(
         &self,
         tenant_id: TenantId,
         aggregate_type: &str,
         aggregate_id: &AggregateId,
     ) -> Result<Option<SyncReadModelResult>, SyncError> {
+        if !self.in_sync_scope(aggregate_type) {
+            tracing::debug!("Skipping {} {}: outside the sync scope", aggregate_type, aggregate_id);
+            return Ok(None);
+        }
+
         // Get local version from cache
         let local_version = self
             .cache
             .get_read_model_version(tenant_id, aggregate_type, aggregate_id)
-            .map_err(|e| SyncError::Cache(e.to_string()))?;
-
-        // Fetch remote read model
-        let client = reqwest::Client::new();
-        let endpoint = self.read_model_endpoint(aggregate_type, aggregate_id);
-        let url = format!("{}{}", self.api_url, endpoint);
-
-        let mut req = client.get(&url);
-
-        if let Some(token) = &self.token {
-            req = req.bearer_auth(token);
-        }
-
-        let resp = req
-            .send()
             .await
-            .map_err(|e| SyncError::Network(e.to_string()))?;
-
-        if !resp.status().is_success() {
-            return Err(SyncError::Api(
-                resp.status().as_u16(),
-                resp.text().await.unwrap_or_default(),
-            ));
-        }
-
-        // Extract headers before consuming response body
-        let headers = resp.headers().clone();
-
-        // Read response body once (needed for both version extraction and model caching)
-        let body: Value = resp.json().await.map_err(|e| {
-            SyncError::Parse(format!("Failed to parse read model: {}", e))
-        })?;
+            .map_err(|e| SyncError::Cache(e.to_string()))?;
 
-        // Extract version from response headers or body
-        let remote_version = self.extract_version_from_body(&body, &headers).await?;
+        let (body, remote_version) = self.fetch_read_model(aggregate_type, aggregate_id).await?;
 
         // Check for conflict
         if let Some(local_ver) = local_version {
@@ -291,6 +453,7 @@ impl SyncManager {
                     local_version: Some(local_ver),
                     remote_version,
                     resolution: ConflictResolution::UseRemote,
+                    command_id: None,
                 };
 
                 // Apply resolution
@@ -350,6 +513,48 @@ impl SyncManager {
         ))))
     }
 
+    /// Fetch a read model from the API along with its version.
+    async fn fetch_read_model(
+        &self,
+        aggregate_type: &str,
+        aggregate_id: &AggregateId,
+    ) -> Result<(Value, u64), SyncError> {
+        let client = reqwest::Client::new();
+        let endpoint = self.read_model_endpoint(aggregate_type, aggregate_id);
+        let url = format!("{}{}", self.api_url, endpoint);
+
+        let mut req = client.get(&url);
+
+        if let Some(token) = &self.token {
+            req = req.bearer_auth(token);
+        }
+
+        let resp = req
+            .send()
+            .await
+            .map_err(|e| SyncError::Network(e.to_string()))?;
+
+        if !resp.status().is_success() {
+            return Err(SyncError::Api(
+                resp.status().as_u16(),
+                resp.text().await.unwrap_or_default(),
+            ));
+        }
+
+        // Extract headers before consuming response body
+        let headers = resp.headers().clone();
+
+        // Read response body once (needed for both version extraction and model caching)
+        let body: Value = resp.json().await.map_err(|e| {
+            SyncError::Parse(format!("Failed to parse read model: {}", e))
+        })?;
+
+        // Extract version from response headers or body
+        let remote_version = self.extract_version_from_body(&body, &headers).await?;
+
+        Ok((body, remote_version))
+    }
+
     /// Extract version from API response (headers or body).
     async fn extract_version_from_body(
         &self,
@@ -443,6 +648,22 @@ impl SyncManager {
     }
 }
 
+#[cfg(feature = "tauri")]
+impl SyncApi for SyncManager {
+    async fn send_command(&self, cmd: &QueuedCommand) -> Result<(), SyncError> {
+        self.sync_command(cmd).await
+    }
+
+    async fn remote_version(
+        &self,
+        aggregate_type: &str,
+        aggregate_id: &AggregateId,
+    ) -> Result<u64, SyncError> {
+        let (_, version) = self.fetch_read_model(aggregate_type, aggregate_id).await?;
+        Ok(version)
+    }
+}
+
 /// Result of syncing a read model.
 #[cfg(feature = "tauri")]
 pub enum SyncReadModelResult {
@@ -466,3 +687,210 @@ pub enum SyncError {
     Conflict(String),
 }
 
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::types::CommandStatus;
+
+    /// Stand-in for the API: answers sends from `replies` in order and records them.
+    struct MockSyncClient {
+        remote_version: u64,
+        replies: Mutex<VecDeque<Result<(), SyncError>>>,
+        sent: Mutex<Vec<QueuedCommand>>,
+    }
+
+    impl MockSyncClient {
+        fn new(remote_version: u64, replies: Vec<Result<(), SyncError>>) -> Self {
+            Self {
+                remote_version,
+                replies: Mutex::new(replies.into()),
+                sent: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn sent(&self) -> Vec<QueuedCommand> {
+            self.sent.lock().unwrap().clone()
+        }
+    }
+
+    impl SyncApi for MockSyncClient {
+        async fn send_command(&self, cmd: &QueuedCommand) -> Result<(), SyncError> {
+            self.sent.lock().unwrap().push(cmd.clone());
+            self.replies.lock().unwrap().pop_front().unwrap_or(Ok(()))
+        }
+
+        async fn remote_version(
+            &self,
+            _aggregate_type: &str,
+            _aggregate_id: &AggregateId,
+        ) -> Result<u64, SyncError> {
+            Ok(self.remote_version)
+        }
+    }
+
+    fn queued_adjustment(expected_version: u64) -> QueuedCommand {
+        QueuedCommand {
+            id: Uuid::now_v7(),
+            tenant_id: TenantId::new(),
+            command_type: "inventory.adjust".to_string(),
+            aggregate_id: AggregateId::new(),
+            payload: json!({ "delta": 5, "expected_version": expected_version }),
+            status: CommandStatus::Pending,
+            created_at: Utc::now(),
+            synced_at: None,
+            error: None,
+            idempotency_key: Uuid::now_v7(),
+            retry_count: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn server_wins_discards_the_command_without_replaying_it() {
+        let client = MockSyncClient::new(7, vec![]);
+        let cmd = queued_adjustment(2);
+
+        let outcome =
+            apply_conflict_strategy(&client, ConflictStrategy::ServerWins, &cmd, "inventory_item", |_| {})
+                .await
+                .unwrap();
+
+        assert!(matches!(outcome, CommandOutcome::Discarded));
+        assert!(client.sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_wins_replays_the_command_against_the_server_version() {
+        let client = MockSyncClient::new(7, vec![Ok(())]);
+        let cmd = queued_adjustment(2);
+
+        let mut saved = None;
+        let outcome =
+            apply_conflict_strategy(&client, ConflictStrategy::ClientWins, &cmd, "inventory_item", |replay| {
+                saved = Some(replay.clone())
+            })
+            .await
+            .unwrap();
+
+        assert!(matches!(outcome, CommandOutcome::Synced));
+        let sent = client.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].id, cmd.id);
+        assert_eq!(sent[0].payload, json!({ "delta": 5, "expected_version": 7 }));
+        // A new body needs a new key; the replay is saved before it is sent
+        assert_ne!(sent[0].idempotency_key, cmd.idempotency_key);
+        assert_eq!(saved.map(|replay| replay.idempotency_key), Some(sent[0].idempotency_key));
+    }
+
+    #[tokio::test]
+    async fn client_wins_keeps_the_command_queued_if_the_replay_conflicts_again() {
+        let client = MockSyncClient::new(7, vec![Err(SyncError::Conflict("stale".to_string()))]);
+        let cmd = queued_adjustment(2);
+
+        let mut saved = None;
+        let outcome =
+            apply_conflict_strategy(&client, ConflictStrategy::ClientWins, &cmd, "inventory_item", |replay| {
+                saved = Some(replay.clone())
+            })
+            .await
+            .unwrap();
+
+        let CommandOutcome::Queued(conflict) = &outcome else {
+            panic!("expected the command to stay queued, got {outcome:?}");
+        };
+        assert_eq!(conflict.command_id, Some(cmd.id));
+        assert_eq!(client.sent().len(), 1);
+        // The rewritten command stays queued, so a later resend reuses its key
+        assert_eq!(saved.map(|replay| replay.idempotency_key), Some(client.sent()[0].idempotency_key));
+    }
+
+    #[tokio::test]
+    async fn manual_reports_the_conflict_and_leaves_the_command_queued() {
+        let client = MockSyncClient::new(7, vec![]);
+        let cmd = queued_adjustment(2);
+
+        let outcome =
+            apply_conflict_strategy(&client, ConflictStrategy::Manual, &cmd, "inventory_item", |_| {})
+                .await
+                .unwrap();
+
+        let CommandOutcome::Queued(conflict) = &outcome else {
+            panic!("expected the command to stay queued, got {outcome:?}");
+        };
+        assert_eq!(conflict.aggregate_type, "inventory_item");
+        assert_eq!(conflict.aggregate_id, cmd.aggregate_id);
+        assert_eq!(conflict.local_version, Some(2));
+        assert_eq!(conflict.remote_version, 7);
+        assert_eq!(conflict.command_id, Some(cmd.id));
+        assert!(client.sent().is_empty());
+    }
+
+    /// Answers every request with a read model at version 1 and records the paths.
+    async fn read_model_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requested = Arc::new(Mutex::new(Vec::new()));
+
+        let seen = requested.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 4096];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                if let Some(path) = request.split_whitespace().nth(1) {
+                    seen.lock().unwrap().push(path.to_string());
+                }
+
+                let body = r#"{"stream_version":1}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        (url, requested)
+    }
+
+    #[tokio::test]
+    async fn narrowing_the_scope_stops_fetching_the_excluded_type() {
+        let (url, requested) = read_model_server().await;
+        let cache_path = std::env::temp_dir().join(format!("forgeerp-cache-{}.db", Uuid::now_v7()));
+        let manager = SyncManager::new(
+            url,
+            Arc::new(CommandQueue::new()),
+            Arc::new(LocalCache::with_path(cache_path.clone())),
+        )
+        .with_sync_scope(vec!["inventory_item".to_string(), "product".to_string()]);
+        let tenant_id = TenantId::new();
+        let product_id = AggregateId::new();
+
+        let synced = manager.sync_read_model(tenant_id, "product", &product_id).await.unwrap();
+        assert!(matches!(synced, Some(SyncReadModelResult::Synced(_))));
+        assert_eq!(requested.lock().unwrap().len(), 1);
+
+        manager.set_sync_scope(vec!["inventory_item".to_string()]).await.unwrap();
+
+        let synced = manager.sync_read_model(tenant_id, "product", &product_id).await.unwrap();
+        assert!(synced.is_none());
+        assert_eq!(*requested.lock().unwrap(), vec![format!("/products/{}", product_id)]);
+
+        // The product cached before the scope was narrowed is gone
+        let cached = manager
+            .cache
+            .get_read_model_version(tenant_id, "product", &product_id)
+            .await
+            .unwrap();
+        assert_eq!(cached, None);
+
+        let _ = std::fs::remove_file(cache_path);
+    }
+}
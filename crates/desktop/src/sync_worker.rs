@@ -1,15 +1,94 @@
 //! Background worker for periodic command synchronization.
 
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use forgeerp_core::TenantId;
+use forgeerp_infra::jobs::{BackoffStrategy, RetryPolicy};
 use serde::Serialize;
 use tauri::{AppHandle, Emitter};
 
 use crate::commands::AppState;
 use crate::sync_manager::SyncError;
 
+/// Wait between background sync attempts while they succeed.
+pub const SYNC_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Longest wait between attempts after repeated failures.
+pub const MAX_SYNC_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Spacing of background sync attempts.
+///
+/// Attempts run every `interval` while they succeed. Each consecutive failure doubles
+/// the wait (an exponential `RetryPolicy` based on `interval`) up to `max_delay`; the
+/// next success resets it.
+#[derive(Debug, Clone)]
+pub struct SyncBackoff {
+    interval: Duration,
+    policy: RetryPolicy,
+    consecutive_failures: u32,
+    next_attempt: Option<Instant>,
+}
+
+impl SyncBackoff {
+    pub fn new(interval: Duration, max_delay: Duration) -> Self {
+        Self {
+            interval,
+            policy: RetryPolicy {
+                max_attempts: u32::MAX,
+                base_delay: interval,
+                max_delay,
+                strategy: BackoffStrategy::Exponential,
+                jitter: 0.0,
+            },
+            consecutive_failures: 0,
+            next_attempt: None,
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+    }
+
+    pub fn record_failure(&mut self) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    /// How long to wait before the next attempt.
+    pub fn delay(&self) -> Duration {
+        if self.consecutive_failures == 0 {
+            self.interval
+        } else {
+            self.policy.delay_for_attempt(self.consecutive_failures + 1)
+        }
+    }
+
+    /// Start waiting for the next attempt; returns the wait.
+    pub fn schedule_next(&mut self) -> Duration {
+        let delay = self.delay();
+        self.next_attempt = Some(Instant::now() + delay);
+        delay
+    }
+
+    /// Time left until the next attempt while backing off; `None` while attempts succeed.
+    pub fn retry_in(&self) -> Option<Duration> {
+        if self.consecutive_failures == 0 {
+            return None;
+        }
+        self.next_attempt.map(|at| at.saturating_duration_since(Instant::now()))
+    }
+}
+
+impl Default for SyncBackoff {
+    fn default() -> Self {
+        Self::new(SYNC_INTERVAL, MAX_SYNC_BACKOFF)
+    }
+}
+
 /// Background sync worker that periodically syncs pending commands.
 pub struct SyncWorker {
     app_handle: AppHandle,
@@ -68,8 +147,8 @@ impl SyncWorker {
     /// Start the background sync worker.
     ///
     /// This spawns a background task that:
-    /// - Checks connectivity every 30 seconds
-    /// - Syncs pending commands when online
+    /// - Checks connectivity and syncs pending commands every `SYNC_INTERVAL`
+    /// - Backs off (see `SyncBackoff`) while attempts keep failing
     /// - Emits Tauri events for sync status
     /// - Respects graceful shutdown signals
     pub fn start(self) -> tokio::task::JoinHandle<()> {
@@ -81,114 +160,99 @@ impl SyncWorker {
         tokio::spawn(async move {
             tracing::info!("Background sync worker started");
 
-            let mut sync_interval = tokio::time::interval(Duration::from_secs(30));
-            sync_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
-
-            let mut consecutive_failures = 0u32;
-            let mut backoff_duration = Duration::from_secs(1);
-
             loop {
+                let attempt = Self::sync_active_tenants(&app_handle, &state, &active_tenants).await;
+
+                let delay = {
+                    let mut backoff = state.sync_backoff.lock().await;
+                    match attempt {
+                        Ok(()) => backoff.record_success(),
+                        Err(e) => {
+                            backoff.record_failure();
+                            tracing::warn!(
+                                "Background sync failed (failure count: {}): {}",
+                                backoff.consecutive_failures(),
+                                e
+                            );
+                        }
+                    }
+                    backoff.schedule_next()
+                };
+                tracing::debug!("Next sync attempt in {:?}", delay);
+
                 tokio::select! {
                     _ = shutdown.notified() => {
                         tracing::info!("Background sync worker received shutdown signal");
                         break;
                     }
-                    _ = sync_interval.tick() => {
-                        // Check if offline mode is active
-                        let offline_guard = state.offline_mode.lock().await;
-                        let is_offline = offline_guard.is_offline();
-                        drop(offline_guard);
-
-                        if is_offline {
-                            tracing::debug!("Skipping sync - offline mode is active");
-                            continue;
-                        }
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            }
 
-                        // Check connectivity
-                        let is_online = state.sync_client.check_connectivity().await;
-                        if !is_online {
-                            tracing::debug!("Skipping sync - no connectivity");
-                            let mut offline_guard = state.offline_mode.lock().await;
-                            offline_guard.set_offline();
-                            continue;
-                        }
+            tracing::info!("Background sync worker stopped");
+        })
+    }
 
-                        // Update offline mode to online
-                        {
-                            let mut offline_guard = state.offline_mode.lock().await;
-                            offline_guard.set_online();
-                        }
+    /// One round of the worker: check connectivity, then sync every active tenant
+    /// with pending commands. Fails if the API is unreachable or any tenant failed.
+    async fn sync_active_tenants(
+        app_handle: &AppHandle,
+        state: &Arc<AppState>,
+        active_tenants: &tokio::sync::RwLock<Vec<TenantId>>,
+    ) -> Result<(), String> {
+        // Check connectivity (also how the client comes back online after an outage)
+        if !state.sync_client.check_connectivity().await {
+            let mut offline_guard = state.offline_mode.lock().await;
+            offline_guard.set_offline();
+            return Err("No connectivity".to_string());
+        }
+
+        // Update offline mode to online
+        {
+            let mut offline_guard = state.offline_mode.lock().await;
+            offline_guard.set_online();
+        }
 
-                        // Get all active tenants
-                        let tenants = {
-                            let tenants_guard = active_tenants.read().await;
-                            tenants_guard.clone()
-                        };
+        // Get all active tenants
+        let tenants = {
+            let tenants_guard = active_tenants.read().await;
+            tenants_guard.clone()
+        };
 
-                        if tenants.is_empty() {
-                            tracing::debug!("No active tenants to sync");
-                            continue;
-                        }
+        if tenants.is_empty() {
+            tracing::debug!("No active tenants to sync");
+            return Ok(());
+        }
 
-                        tracing::debug!("Syncing {} active tenant(s)", tenants.len());
-
-                        // Sync each tenant
-                        for tenant_id in tenants {
-                            // Check for pending commands
-                            let pending = state.command_queue.list_pending(tenant_id);
-                            if pending.is_empty() {
-                                tracing::debug!("No pending commands for tenant {}", tenant_id);
-                                continue;
-                            }
-
-                            tracing::info!(
-                                "Syncing {} pending commands for tenant {}",
-                                pending.len(),
-                                tenant_id
-                            );
+        tracing::debug!("Syncing {} active tenant(s)", tenants.len());
 
-                            // Perform sync
-                            match Self::sync_tenant_internal(
-                                &app_handle,
-                                &state,
-                                tenant_id,
-                            )
-                            .await
-                            {
-                                Ok(()) => {
-                                    consecutive_failures = 0;
-                                    backoff_duration = Duration::from_secs(1);
-                                }
-                                Err(e) => {
-                                    consecutive_failures += 1;
-                                    tracing::warn!(
-                                        "Sync failed for tenant {} (failure count: {}): {}",
-                                        tenant_id,
-                                        consecutive_failures,
-                                        e
-                                    );
-
-                                    // Apply exponential backoff
-                                    if consecutive_failures > 0 {
-                                        let backoff = std::cmp::min(
-                                            backoff_duration * (1 << consecutive_failures.min(5)),
-                                            Duration::from_secs(300), // Cap at 5 minutes
-                                        );
-                                        tracing::debug!(
-                                            "Applying backoff of {:?} before next sync attempt",
-                                            backoff
-                                        );
-                                        backoff_duration = backoff;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+        let mut first_error = None;
+
+        // Sync each tenant
+        for tenant_id in tenants {
+            // Check for pending commands
+            let pending = state.command_queue.list_pending(tenant_id);
+            if pending.is_empty() {
+                tracing::debug!("No pending commands for tenant {}", tenant_id);
+                continue;
             }
 
-            tracing::info!("Background sync worker stopped");
-        })
+            tracing::info!(
+                "Syncing {} pending commands for tenant {}",
+                pending.len(),
+                tenant_id
+            );
+
+            if let Err(e) = Self::sync_tenant_internal(app_handle, state, tenant_id).await {
+                tracing::warn!("Sync failed for tenant {}: {}", tenant_id, e);
+                first_error.get_or_insert(e);
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
     }
 
     /// Internal sync method (static to avoid borrowing issues in async closure).
@@ -349,3 +413,32 @@ pub async fn sync_tenant_with_backoff(
     Err(last_error.unwrap_or_else(|| "Unknown error".to_string()))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_with_each_failure_up_to_the_cap_and_resets_after_a_success() {
+        let mut backoff = SyncBackoff::new(Duration::from_secs(30), Duration::from_secs(300));
+        assert_eq!(backoff.delay(), Duration::from_secs(30));
+        assert_eq!(backoff.retry_in(), None);
+
+        let delays: Vec<u64> = (0..5)
+            .map(|_| {
+                backoff.record_failure();
+                backoff.delay().as_secs()
+            })
+            .collect();
+        assert_eq!(delays, [60, 120, 240, 300, 300]);
+        assert_eq!(backoff.consecutive_failures(), 5);
+
+        let wait = backoff.schedule_next();
+        let retry_in = backoff.retry_in().expect("backing off after failures");
+        assert!(retry_in <= wait && retry_in > wait - Duration::from_secs(1));
+
+        backoff.record_success();
+        assert_eq!(backoff.consecutive_failures(), 0);
+        assert_eq!(backoff.delay(), Duration::from_secs(30));
+        assert_eq!(backoff.retry_in(), None);
+    }
+}
@@ -51,6 +51,12 @@ pub struct QueuedCommand {
     pub created_at: DateTime<Utc>,
     pub synced_at: Option<DateTime<Utc>>,
     pub error: Option<String>,
+    /// Sent as `Idempotency-Key`, so resending after a crash mid-sync is answered
+    /// from the API's idempotency cache instead of being applied twice.
+    pub idempotency_key: Uuid,
+    /// Attempts that failed before reaching the server.
+    #[serde(default)]
+    pub retry_count: u32,
 }
 
 /// Connectivity state of the client.
@@ -63,6 +69,16 @@ pub enum ConnectivityState {
     Offline,
 }
 
+/// Connectivity state plus the background sync worker's retry schedule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectivityStatus {
+    pub state: ConnectivityState,
+    /// Background sync attempts that failed in a row (0 after a success).
+    pub consecutive_failures: u32,
+    /// Seconds until the worker tries again, while it is backing off after failures.
+    pub retry_in_secs: Option<u64>,
+}
+
 /// Conflict resolution strategy.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -83,6 +99,9 @@ pub struct Conflict {
     pub local_version: Option<u64>,
     pub remote_version: u64,
     pub resolution: ConflictResolution,
+    /// Queued command that hit the conflict on replay, if any.
+    #[serde(default)]
+    pub command_id: Option<Uuid>,
 }
 
 /// Result of a sync operation.
@@ -133,6 +133,23 @@ pub trait EventBus<M>: Send + Sync {
 
     fn publish(&self, message: M) -> Result<(), Self::Error>;
 
+    /// Publish `messages` in order as one unit.
+    ///
+    /// The default publishes them one by one, so a failure part-way through leaves the
+    /// earlier messages delivered. Buses that can deliver a batch all-or-nothing override
+    /// this and report it via [`EventBus::publishes_batches_atomically`].
+    fn publish_batch(&self, messages: Vec<M>) -> Result<(), Self::Error> {
+        for message in messages {
+            self.publish(message)?;
+        }
+        Ok(())
+    }
+
+    /// Whether `publish_batch` delivers either every message or none.
+    fn publishes_batches_atomically(&self) -> bool {
+        false
+    }
+
     fn subscribe(&self) -> Subscription<M>;
 }
 
@@ -146,6 +163,14 @@ where
         (**self).publish(message)
     }
 
+    fn publish_batch(&self, messages: Vec<M>) -> Result<(), Self::Error> {
+        (**self).publish_batch(messages)
+    }
+
+    fn publishes_batches_atomically(&self) -> bool {
+        (**self).publishes_batches_atomically()
+    }
+
     fn subscribe(&self) -> Subscription<M> {
         (**self).subscribe()
     }
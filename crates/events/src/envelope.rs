@@ -1,8 +1,20 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use forgeerp_core::{AggregateId, TenantId};
 
+/// Metadata key for the `crate/semver` of the service that appended the event.
+pub const PRODUCER_VERSION_KEY: &str = "producer_version";
+
+/// Metadata key for the fingerprint of the payload schema as written by the producer.
+pub const SCHEMA_FINGERPRINT_KEY: &str = "schema_fingerprint";
+
+/// Metadata key for the business process an event belongs to (e.g. `order-fulfillment-123`).
+pub const BUSINESS_KEY: &str = "business_key";
+
 /// Envelope for an event, containing multi-tenant + stream metadata.
 ///
 /// An `EventEnvelope` wraps a domain event with infrastructure metadata needed for
@@ -63,6 +75,21 @@ use forgeerp_core::{AggregateId, TenantId};
 ///
 /// Infrastructure typically uses JSON for flexibility (schema evolution), while domain code
 /// works with strongly-typed event enums.
+///
+/// ## Metadata
+///
+/// `metadata` carries request-level facts that are not part of the domain event (e.g. a
+/// platform admin acting in another tenant). Events appended through the command
+/// dispatcher also record [`PRODUCER_VERSION_KEY`] and [`SCHEMA_FINGERPRINT_KEY`];
+/// events written before that have neither.
+///
+/// ## Correlation and Causation
+///
+/// `correlation_id` is shared by every event of one logical flow (a request's command and
+/// everything sagas dispatch in reaction to it); `causation_id` is the id of the command
+/// that produced the event. Both are `None` on events written before they were recorded.
+/// Reactions derive the context for their follow-up commands with
+/// [`EventEnvelope::child_correlation`].
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EventEnvelope<E> {
     event_id: Uuid,
@@ -75,6 +102,35 @@ pub struct EventEnvelope<E> {
     sequence_number: u64,
 
     payload: E,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: BTreeMap<String, String>,
+
+    /// When the event store recorded the event (unset for envelopes not built from storage).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Utc>>,
+
+    /// Flow the event belongs to (see [Correlation and Causation](#correlation-and-causation)).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    correlation_id: Option<Uuid>,
+
+    /// Command that produced the event.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    causation_id: Option<Uuid>,
+
+    /// Position in the store's global commit order (unset for envelopes not built from storage).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    global_position: Option<u64>,
+}
+
+/// Correlation context for a command dispatched in reaction to an event.
+///
+/// Events produced by that command keep the flow's `correlation_id` and record
+/// `causation_id` (the triggering event's id) as the id of the command that caused them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CorrelationContext {
+    pub correlation_id: Uuid,
+    pub causation_id: Uuid,
 }
 
 impl<E> EventEnvelope<E> {
@@ -93,9 +149,39 @@ impl<E> EventEnvelope<E> {
             aggregate_type: aggregate_type.into(),
             sequence_number,
             payload,
+            metadata: BTreeMap::new(),
+            created_at: None,
+            correlation_id: None,
+            causation_id: None,
+            global_position: None,
         }
     }
 
+    /// Attach request-level metadata.
+    pub fn with_metadata(mut self, metadata: BTreeMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Attach the storage time of the event.
+    pub fn with_created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    /// Attach the correlation and causation ids recorded with the event.
+    pub fn with_correlation(mut self, correlation_id: Option<Uuid>, causation_id: Option<Uuid>) -> Self {
+        self.correlation_id = correlation_id;
+        self.causation_id = causation_id;
+        self
+    }
+
+    /// Attach the event's position in the store's global commit order (`0` leaves it unset).
+    pub fn with_global_position(mut self, global_position: u64) -> Self {
+        self.global_position = (global_position > 0).then_some(global_position);
+        self
+    }
+
     pub fn event_id(&self) -> Uuid {
         self.event_id
     }
@@ -120,9 +206,56 @@ impl<E> EventEnvelope<E> {
         &self.payload
     }
 
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    /// When the event store recorded the event; the business time is the payload's `occurred_at`.
+    pub fn created_at(&self) -> Option<DateTime<Utc>> {
+        self.created_at
+    }
+
+    /// `crate/semver` of the producer that appended the event, if recorded.
+    pub fn producer_version(&self) -> Option<&str> {
+        self.metadata.get(PRODUCER_VERSION_KEY).map(String::as_str)
+    }
+
+    /// Payload schema fingerprint at append time, if recorded.
+    pub fn schema_fingerprint(&self) -> Option<&str> {
+        self.metadata.get(SCHEMA_FINGERPRINT_KEY).map(String::as_str)
+    }
+
+    /// Business process key the event was dispatched under, if any.
+    pub fn business_key(&self) -> Option<&str> {
+        self.metadata.get(BUSINESS_KEY).map(String::as_str)
+    }
+
+    /// Position in the store's global commit order, if known.
+    pub fn global_position(&self) -> Option<u64> {
+        self.global_position
+    }
+
+    /// Flow this event belongs to, if recorded.
+    pub fn correlation_id(&self) -> Option<Uuid> {
+        self.correlation_id
+    }
+
+    /// Command that produced this event, if recorded.
+    pub fn causation_id(&self) -> Option<Uuid> {
+        self.causation_id
+    }
+
+    /// Context for commands caused by this event: same flow (this event starts one if it
+    /// has no correlation id), caused by this event.
+    pub fn child_correlation(&self) -> CorrelationContext {
+        CorrelationContext {
+            correlation_id: self.correlation_id.unwrap_or(self.event_id),
+            causation_id: self.event_id,
+        }
+    }
+
     pub fn into_payload(self) -> E {
         self.payload
     }
 }
 
-
@@ -49,6 +49,20 @@ where
         Ok(())
     }
 
+    /// All-or-nothing: the only failure (a poisoned lock) happens before any send, and
+    /// holding the lock keeps the batch contiguous for every subscriber.
+    fn publish_batch(&self, messages: Vec<M>) -> Result<(), Self::Error> {
+        let mut subs = self.subscribers.lock().map_err(|_| InMemoryBusError::Poisoned)?;
+
+        subs.retain(|tx| messages.iter().all(|m| tx.send(m.clone()).is_ok()));
+
+        Ok(())
+    }
+
+    fn publishes_batches_atomically(&self) -> bool {
+        true
+    }
+
     fn subscribe(&self) -> Subscription<M> {
         let (tx, rx) = mpsc::channel();
 
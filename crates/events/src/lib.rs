@@ -14,10 +14,11 @@ pub mod projection;
 pub mod saga;
 pub mod runner;
 pub mod tenant;
+pub mod upcast;
 
 pub use bus::{EventBus, Subscription};
 pub use command::Command;
-pub use envelope::EventEnvelope;
+pub use envelope::{CorrelationContext, EventEnvelope, BUSINESS_KEY, PRODUCER_VERSION_KEY, SCHEMA_FINGERPRINT_KEY};
 pub use event::Event;
 pub use handler::CommandHandler;
 pub use in_memory_bus::InMemoryEventBus;
@@ -25,5 +26,6 @@ pub use projection::Projection;
 pub use saga::{Saga, SagaAction};
 pub use runner::{ProjectionCursor, ProjectionError, ProjectionRunner};
 pub use tenant::TenantScoped;
+pub use upcast::{RenameField, Upcaster, UpcasterRegistry};
 
 
@@ -82,6 +82,17 @@ pub trait Projection {
     ///
     /// For structured error handling, use `ProjectionRunner::apply()` which returns `ProjectionError`.
     fn apply(&mut self, envelope: &EventEnvelope<Self::Ev>);
+
+    /// Apply a batch of events, in order.
+    ///
+    /// The default applies them one at a time. Projections whose storage can write in bulk
+    /// override it to fold the whole batch and persist once, instead of once per event;
+    /// the result must be the same as applying the events one by one.
+    fn apply_batch(&mut self, envelopes: &[EventEnvelope<Self::Ev>]) {
+        for envelope in envelopes {
+            self.apply(envelope);
+        }
+    }
 }
 
 
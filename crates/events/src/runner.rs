@@ -10,7 +10,8 @@
 //!
 //! - **Sequence tracking**: Detect duplicate events and skip them (idempotency)
 //! - **Tenant isolation**: Ensure events from different tenants don't mix
-//! - **Ordering**: Process events in sequence number order
+//! - **Ordering**: Process events in sequence number order (or global commit order when
+//!   envelopes carry a global position, as when replaying a tenant's whole history)
 //! - **Cursor management**: Track progress for resumable processing
 //!
 //! `ProjectionRunner` encapsulates these concerns, making projections easier to write and
@@ -32,6 +33,7 @@ use crate::{EventEnvelope, Projection};
 pub struct ProjectionCursor {
     tenant_id: TenantId,
     last_sequence_number: u64,
+    last_global_position: Option<u64>,
 }
 
 impl ProjectionCursor {
@@ -42,6 +44,11 @@ impl ProjectionCursor {
     pub fn last_sequence_number(&self) -> u64 {
         self.last_sequence_number
     }
+
+    /// Global position of the last processed envelope, if it carried one.
+    pub fn last_global_position(&self) -> Option<u64> {
+        self.last_global_position
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -88,6 +95,9 @@ pub enum ProjectionError {
 /// - **Duplicate detection**: Reject events with sequence numbers <= last processed
 /// - **Ordering**: Ensure events are processed in sequence number order
 ///
+/// Envelopes that carry a global position (events read across streams in commit order)
+/// are ordered by it instead, so one runner can replay events of many aggregates.
+///
 /// Note: The cursor is stored in memory. For persistent cursors, store them in your
 /// projection's persistence layer and restore them when creating the runner.
 ///
@@ -130,6 +140,7 @@ where
             cursor: Some(ProjectionCursor {
                 tenant_id,
                 last_sequence_number: 0,
+                last_global_position: None,
             }),
         }
     }
@@ -190,6 +201,7 @@ where
                 self.cursor = Some(ProjectionCursor {
                     tenant_id: found_tenant,
                     last_sequence_number: found_seq,
+                    last_global_position: envelope.global_position(),
                 });
                 Ok(())
             }
@@ -200,21 +212,59 @@ where
                         found: found_tenant,
                     });
                 }
-                if found_seq <= c.last_sequence_number {
-                    return Err(ProjectionError::NonMonotonicSequence {
-                        last: c.last_sequence_number,
-                        found: found_seq,
-                    });
+                let (last, found) = match (c.last_global_position, envelope.global_position()) {
+                    (Some(last), Some(found)) => (last, found),
+                    _ => (c.last_sequence_number, found_seq),
+                };
+                if found <= last {
+                    return Err(ProjectionError::NonMonotonicSequence { last, found });
                 }
 
                 self.projection.apply(envelope);
                 c.last_sequence_number = found_seq;
+                c.last_global_position = envelope.global_position();
                 self.cursor = Some(c);
                 Ok(())
             }
         }
     }
 
+    /// Apply a batch of envelopes through [`Projection::apply_batch`], advancing the cursor
+    /// once for the whole batch.
+    ///
+    /// The batch is checked as a whole first (same rules as [`apply`](Self::apply)): if any
+    /// envelope would be rejected, the error is returned and nothing is applied.
+    pub fn apply_batch(&mut self, envelopes: &[EventEnvelope<P::Ev>]) -> Result<(), ProjectionError> {
+        let mut cursor = self.cursor;
+        for envelope in envelopes {
+            let found_seq = envelope.sequence_number();
+            if let Some(c) = cursor {
+                if c.tenant_id != envelope.tenant_id() {
+                    return Err(ProjectionError::TenantMismatch {
+                        expected: c.tenant_id,
+                        found: envelope.tenant_id(),
+                    });
+                }
+                let (last, found) = match (c.last_global_position, envelope.global_position()) {
+                    (Some(last), Some(found)) => (last, found),
+                    _ => (c.last_sequence_number, found_seq),
+                };
+                if found <= last {
+                    return Err(ProjectionError::NonMonotonicSequence { last, found });
+                }
+            }
+            cursor = Some(ProjectionCursor {
+                tenant_id: envelope.tenant_id(),
+                last_sequence_number: found_seq,
+                last_global_position: envelope.global_position(),
+            });
+        }
+
+        self.projection.apply_batch(envelopes);
+        self.cursor = cursor;
+        Ok(())
+    }
+
     /// Apply many envelopes in order.
     pub fn run<'a>(
         &mut self,
@@ -240,7 +290,7 @@ where
     /// ## Usage Pattern
     ///
     /// ```ignore
-    /// let events = event_store.load_all_events_for_tenant(tenant_id)?;
+    /// let events = read_all_envelopes(&event_store, tenant_id)?;
     /// let (projection, cursor) = ProjectionRunner::rebuild_from_scratch(
     ///     || MyProjection::new(),
     ///     &events,
@@ -264,7 +314,8 @@ where
     ///
     /// ## Event Ordering
     ///
-    /// Events should be provided in sequence number order (per aggregate stream).
+    /// Events should be provided in sequence number order (per aggregate stream), or in
+    /// global commit order for a whole tenant (envelopes with a global position).
     /// The runner will validate ordering, but pre-sorting improves performance.
     ///
     /// ## Performance Considerations
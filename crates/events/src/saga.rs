@@ -14,7 +14,10 @@
 //! - Saga state is advanced by emitting saga-specific events into the event store
 //! - `SagaAction::Command` and `SagaAction::Compensate` emit commands to other aggregates
 //! - Actions are idempotent; runners must guard against duplicate deliveries
+//! - A state may declare a timeout; if no event moves the saga on before its
+//!   `timeout_at`, infra asks the saga for its timeout (compensation) actions
 
+use chrono::{DateTime, Duration, Utc};
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value as JsonValue;
 
@@ -59,8 +62,9 @@ pub trait Saga: Send + Sync + 'static {
     type State: Clone + Default + Serialize + DeserializeOwned + Send + Sync + 'static;
     /// JSON-wrapped saga events (persisted in event store).
     type SagaEvent: Serialize + DeserializeOwned + Send + Sync + 'static;
-    /// Correlation id (e.g., `SalesOrderId`), used to route events to a saga instance.
-    type CorrelationId: Clone + Send + Sync + 'static;
+    /// Correlation id (e.g., `SalesOrderId`), used to route events to a saga instance
+    /// (serde, so infra can record it alongside a pending deadline).
+    type CorrelationId: Clone + Serialize + DeserializeOwned + Send + Sync + 'static;
 
     /// Stable saga type identifier (used for aggregate_type: e.g., "saga.sales_ar").
     fn saga_type() -> &'static str;
@@ -91,6 +95,28 @@ pub trait Saga: Send + Sync + 'static {
         correlation: &Self::CorrelationId,
         incoming: &EventEnvelope<JsonValue>,
     ) -> Vec<SagaAction>;
+
+    /// How long the saga may wait in `state` for its next expected event (None: forever).
+    fn timeout(_state: &Self::State) -> Option<Duration> {
+        None
+    }
+
+    /// Deadline of `state`, entered at `entered_at`.
+    fn timeout_at(state: &Self::State, entered_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        Self::timeout(state).map(|timeout| entered_at + timeout)
+    }
+
+    /// Actions to run when the saga is still in `state` at its `timeout_at`.
+    ///
+    /// Typically an `Emit` moving the saga out of the waiting state plus the
+    /// `Compensate` commands undoing or flagging what it already did.
+    fn on_timeout(
+        _state: &Self::State,
+        _tenant_id: TenantId,
+        _correlation: &Self::CorrelationId,
+    ) -> Vec<SagaAction> {
+        vec![]
+    }
 }
 
 
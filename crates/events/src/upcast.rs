@@ -0,0 +1,123 @@
+//! Event upcasting (schema evolution of stored payloads).
+//!
+//! Upcasters upgrade old payloads at read time, one version step each; an
+//! [`UpcasterRegistry`] chains them by event type and version.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::Value as JsonValue;
+
+/// Upgrades a stored event payload by one schema version.
+///
+/// Events are immutable once stored, so when an event's shape changes (a field is
+/// renamed, split, or gains a required value) the old payloads stay as they were
+/// written. An upcaster rewrites a `from_version` payload into the `from_version + 1`
+/// shape at read time, before it is deserialized into the current event type.
+///
+/// Upcasters must be **pure**: the same payload always upcasts to the same result, so
+/// rehydration stays deterministic.
+pub trait Upcaster: Send + Sync {
+    fn upcast(&self, event_type: &str, from_version: u32, payload: JsonValue) -> JsonValue;
+}
+
+impl<F> Upcaster for F
+where
+    F: Fn(&str, u32, JsonValue) -> JsonValue + Send + Sync,
+{
+    fn upcast(&self, event_type: &str, from_version: u32, payload: JsonValue) -> JsonValue {
+        self(event_type, from_version, payload)
+    }
+}
+
+/// Upcasters by event type and the version they upgrade from.
+///
+/// [`UpcasterRegistry::upcast`] chains them: a v1 payload of a type with upcasters
+/// registered for v1 and v2 passes through both and comes out in the v3 shape. The chain
+/// stops at the first version without an upcaster, which is the current one.
+#[derive(Default, Clone)]
+pub struct UpcasterRegistry {
+    upcasters: HashMap<(String, u32), Arc<dyn Upcaster>>,
+}
+
+impl core::fmt::Debug for UpcasterRegistry {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut steps: Vec<_> = self.upcasters.keys().collect();
+        steps.sort();
+        f.debug_struct("UpcasterRegistry").field("steps", &steps).finish()
+    }
+}
+
+impl UpcasterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `upcaster` for `event_type` payloads at `from_version` (replacing any
+    /// upcaster already registered for that step).
+    pub fn register(
+        mut self,
+        event_type: impl Into<String>,
+        from_version: u32,
+        upcaster: impl Upcaster + 'static,
+    ) -> Self {
+        self.upcasters
+            .insert((event_type.into(), from_version), Arc::new(upcaster));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.upcasters.is_empty()
+    }
+
+    /// Upgrade `payload` from `version` through every registered step; returns the
+    /// version reached and the upgraded payload.
+    pub fn upcast(&self, event_type: &str, version: u32, payload: JsonValue) -> (u32, JsonValue) {
+        let mut version = version;
+        let mut payload = payload;
+        while let Some(upcaster) = self.upcasters.get(&(event_type.to_string(), version)) {
+            payload = upcaster.upcast(event_type, version, payload);
+            version += 1;
+        }
+        (version, payload)
+    }
+}
+
+/// Example upcaster: renames a payload field.
+///
+/// The field is looked up at the top level, or inside the variant object of an
+/// externally tagged event enum (`{"ItemCreated": {...}}`), which is how domain events
+/// are stored. Payloads without the field are returned unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameField {
+    from: String,
+    to: String,
+}
+
+impl RenameField {
+    pub fn new(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+}
+
+impl Upcaster for RenameField {
+    fn upcast(&self, _event_type: &str, _from_version: u32, mut payload: JsonValue) -> JsonValue {
+        let Some(object) = payload.as_object_mut() else {
+            return payload;
+        };
+        let fields = if object.contains_key(&self.from) || object.len() != 1 {
+            Some(object)
+        } else {
+            object.values_mut().next().and_then(JsonValue::as_object_mut)
+        };
+        if let Some(fields) = fields
+            && let Some(value) = fields.remove(&self.from)
+        {
+            fields.insert(self.to.clone(), value);
+        }
+        payload
+    }
+}
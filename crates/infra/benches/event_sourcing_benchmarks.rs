@@ -9,7 +9,7 @@ use forgeerp_infra::event_store::{EventStore, InMemoryEventStore, UncommittedEve
 use forgeerp_infra::projections::inventory_stock::InventoryStockProjection;
 use forgeerp_infra::read_model::InMemoryTenantStore;
 use forgeerp_inventory::{
-    AdjustStock, CreateItem, InventoryCommand, InventoryEvent, InventoryItem, InventoryItemId,
+    AdjustStock, CreateItem, DEFAULT_LOCATION, InventoryCommand, InventoryEvent, InventoryItem, InventoryItemId,
     ItemCreated, StockAdjusted,
 };
 use std::collections::HashMap;
@@ -130,6 +130,7 @@ fn bench_command_execution_latency(c: &mut Criterion) {
             let adjust_cmd = AdjustStock {
                 tenant_id,
                 item_id: item_id_typed,
+                location_id: DEFAULT_LOCATION.to_string(),
                 delta: black_box(5),
                 occurred_at: Utc::now(),
             };
@@ -169,6 +170,7 @@ fn bench_event_append_throughput(c: &mut Criterion) {
                                 forgeerp_inventory::StockAdjusted {
                                     tenant_id,
                                     item_id: InventoryItemId::new(item_id),
+                                    location_id: DEFAULT_LOCATION.to_string(),
                                     delta: i as i64,
                                     occurred_at: Utc::now(),
                                 },
@@ -238,6 +240,7 @@ fn bench_projection_rebuild_speed(c: &mut Criterion) {
                         let adjust_event = InventoryEvent::StockAdjusted(StockAdjusted {
                             tenant_id,
                             item_id: item_id_typed,
+                            location_id: DEFAULT_LOCATION.to_string(),
                             delta: (i % 10) as i64,
                             occurred_at: Utc::now(),
                         });
@@ -274,6 +277,62 @@ fn bench_projection_rebuild_speed(c: &mut Criterion) {
     group.finish();
 }
 
+/// 10k inventory envelopes (100 items × 100 events) applied one by one vs as one batch.
+fn bench_projection_batch_apply(c: &mut Criterion) {
+    let mut group = c.benchmark_group("projection_batch_apply");
+    group.throughput(Throughput::Elements(10_000));
+
+    let store = InMemoryEventStore::new();
+    let tenant_id = TenantId::new();
+    let mut envelopes = Vec::with_capacity(10_000);
+    for _ in 0..100 {
+        let item_id = AggregateId::new();
+        let item_id_typed = InventoryItemId::new(item_id);
+        for i in 0..100u64 {
+            let event = if i == 0 {
+                InventoryEvent::ItemCreated(ItemCreated {
+                    tenant_id,
+                    item_id: item_id_typed,
+                    name: "Test Item".to_string(),
+                    occurred_at: Utc::now(),
+                })
+            } else {
+                InventoryEvent::StockAdjusted(StockAdjusted {
+                    tenant_id,
+                    item_id: item_id_typed,
+                    location_id: DEFAULT_LOCATION.to_string(),
+                    delta: (i % 10) as i64,
+                    occurred_at: Utc::now(),
+                })
+            };
+            let uncommitted =
+                UncommittedEvent::from_typed(tenant_id, item_id, "inventory.item", uuid::Uuid::now_v7(), &event).unwrap();
+            let stored = store
+                .append(vec![uncommitted], forgeerp_core::ExpectedVersion::Exact(i))
+                .unwrap();
+            envelopes.push(stored[0].to_envelope());
+        }
+    }
+
+    group.bench_function("one_by_one", |b| {
+        b.iter(|| {
+            let projection = InventoryStockProjection::new(Arc::new(InMemoryTenantStore::new()));
+            for env in &envelopes {
+                projection.apply_envelope(black_box(env)).unwrap();
+            }
+        });
+    });
+
+    group.bench_function("batched", |b| {
+        b.iter(|| {
+            let projection = InventoryStockProjection::new(Arc::new(InMemoryTenantStore::new()));
+            projection.apply_batch(black_box(&envelopes)).unwrap();
+        });
+    });
+
+    group.finish();
+}
+
 fn bench_event_sourcing_vs_naive_crud(c: &mut Criterion) {
     let mut group = c.benchmark_group("event_sourcing_vs_naive_crud");
     group.sample_size(1000);
@@ -307,6 +366,7 @@ fn bench_event_sourcing_vs_naive_crud(c: &mut Criterion) {
             let adjust_cmd = AdjustStock {
                 tenant_id,
                 item_id: item_id_typed,
+                location_id: DEFAULT_LOCATION.to_string(),
                 delta: 10,
                 occurred_at: Utc::now(),
             };
@@ -342,6 +402,7 @@ criterion_group!(
     bench_command_execution_latency,
     bench_event_append_throughput,
     bench_projection_rebuild_speed,
+    bench_projection_batch_apply,
     bench_event_sourcing_vs_naive_crud
 );
 criterion_main!(benches);
@@ -0,0 +1,271 @@
+//! Audit trail of privileged admin operations.
+//!
+//! Projection resets, replay cancellations and dead-letter purges touch no aggregate,
+//! so they leave no business event behind. The [`AdminAuditLog`] records each one
+//! (actor, operation, target, time) in an append-only log kept apart from the event
+//! store. Entries are never edited; they only leave the log once they are older than
+//! the [`AdminAuditRetention`].
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+use forgeerp_auth::PrincipalId;
+use forgeerp_core::TenantId;
+
+use crate::clock::{Clock, SystemClock};
+
+/// Admin operations recorded in the audit log.
+pub mod operation {
+    /// A projection was cleared and rebuilt from the event store.
+    pub const PROJECTION_RESET: &str = "projection.reset";
+    /// A projection replay ran in dry-run mode (read model untouched).
+    pub const PROJECTION_REPLAY_DRY_RUN: &str = "projection.replay_dry_run";
+    /// A running projection replay was cancelled.
+    pub const PROJECTION_REPLAY_CANCEL: &str = "projection.replay_cancel";
+    /// Reads of a versioned projection were switched to another version.
+    pub const PROJECTION_VERSION_ACTIVATE: &str = "projection.version_activate";
+    /// Events a projection failed to apply were replayed.
+    pub const PROJECTION_DEAD_LETTER_REPLAY: &str = "projection.dead_letter_replay";
+    /// Old dead-lettered jobs were purged.
+    pub const DEAD_LETTERS_PURGE: &str = "jobs.dead_letters.purge";
+    /// The tenant's inventory anomaly detection settings were changed.
+    pub const AI_ANOMALY_CONFIG_UPDATE: &str = "ai.inventory_anomaly.config_update";
+}
+
+/// One privileged operation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AdminAuditEntry {
+    pub entry_id: Uuid,
+    /// Tenant the operation acted on.
+    pub tenant_id: TenantId,
+    pub actor: PrincipalId,
+    /// The actor's own tenant when it acted through a cross-tenant override.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor_home_tenant_id: Option<TenantId>,
+    /// See [`operation`].
+    pub operation: String,
+    /// What the operation acted on (projection name, replay job id, ...).
+    pub target: String,
+    /// Operation parameters worth keeping (e.g. a purge cutoff).
+    pub details: JsonValue,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Filters for [`AdminAuditLog::list`]; unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct AdminAuditFilter {
+    pub operation: Option<String>,
+    pub actor: Option<PrincipalId>,
+    pub target: Option<String>,
+    /// Entries recorded at or after this time.
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl AdminAuditFilter {
+    fn matches(&self, entry: &AdminAuditEntry) -> bool {
+        self.operation.as_deref().is_none_or(|op| entry.operation == op)
+            && self.actor.is_none_or(|actor| entry.actor == actor)
+            && self.target.as_deref().is_none_or(|target| entry.target == target)
+            && self.since.is_none_or(|since| entry.recorded_at >= since)
+    }
+}
+
+/// How long audit entries are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdminAuditRetention {
+    /// `None` keeps entries forever.
+    pub max_age: Option<chrono::Duration>,
+}
+
+impl Default for AdminAuditRetention {
+    fn default() -> Self {
+        Self {
+            max_age: Some(chrono::Duration::days(365)),
+        }
+    }
+}
+
+impl AdminAuditRetention {
+    /// Retention from `ADMIN_AUDIT_RETENTION_DAYS` (default 365; 0 keeps entries forever).
+    pub fn from_env() -> Self {
+        match std::env::var("ADMIN_AUDIT_RETENTION_DAYS").ok().and_then(|v| v.trim().parse::<i64>().ok()) {
+            Some(0) => Self { max_age: None },
+            Some(days) if days > 0 => Self {
+                max_age: Some(chrono::Duration::days(days)),
+            },
+            _ => Self::default(),
+        }
+    }
+}
+
+/// Append-only, in-memory admin audit log (all tenants).
+pub struct AdminAuditLog {
+    retention: AdminAuditRetention,
+    clock: Arc<dyn Clock>,
+    /// Oldest first.
+    entries: Mutex<VecDeque<AdminAuditEntry>>,
+}
+
+impl std::fmt::Debug for AdminAuditLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminAuditLog")
+            .field("retention", &self.retention)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for AdminAuditLog {
+    fn default() -> Self {
+        Self::new(AdminAuditRetention::default())
+    }
+}
+
+impl AdminAuditLog {
+    pub fn new(retention: AdminAuditRetention) -> Self {
+        Self {
+            retention,
+            clock: Arc::new(SystemClock),
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn arc(retention: AdminAuditRetention) -> Arc<Self> {
+        Arc::new(Self::new(retention))
+    }
+
+    /// Time source for `recorded_at` and retention (defaults to the system clock).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Append an entry stamped with the current time; returns it.
+    pub fn record(
+        &self,
+        tenant_id: TenantId,
+        actor: PrincipalId,
+        actor_home_tenant_id: Option<TenantId>,
+        operation: &str,
+        target: impl Into<String>,
+        details: JsonValue,
+    ) -> AdminAuditEntry {
+        let now = self.clock.now();
+        let entry = AdminAuditEntry {
+            entry_id: Uuid::now_v7(),
+            tenant_id,
+            actor,
+            actor_home_tenant_id,
+            operation: operation.to_string(),
+            target: target.into(),
+            details,
+            recorded_at: now,
+        };
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        self.expire(&mut entries, now);
+        entries.push_back(entry.clone());
+        entry
+    }
+
+    /// A tenant's entries matching `filter`, oldest first.
+    pub fn list(&self, tenant_id: TenantId, filter: &AdminAuditFilter) -> Vec<AdminAuditEntry> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        self.expire(&mut entries, self.clock.now());
+        entries
+            .iter()
+            .filter(|e| e.tenant_id == tenant_id && filter.matches(e))
+            .cloned()
+            .collect()
+    }
+
+    /// Write a tenant's entries as NDJSON, oldest first; returns how many were written.
+    pub fn export(&self, tenant_id: TenantId, writer: &mut dyn Write) -> std::io::Result<usize> {
+        let entries = self.list(tenant_id, &AdminAuditFilter::default());
+        for entry in &entries {
+            serde_json::to_writer(&mut *writer, entry)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(entries.len())
+    }
+
+    /// Drop entries past the retention (they are appended in time order).
+    fn expire(&self, entries: &mut VecDeque<AdminAuditEntry>, now: DateTime<Utc>) {
+        let Some(max_age) = self.retention.max_age else {
+            return;
+        };
+        while entries.front().is_some_and(|e| now - e.recorded_at > max_age) {
+            entries.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+
+    #[test]
+    fn entries_are_tenant_scoped_filterable_and_exported_in_order() {
+        let log = AdminAuditLog::default();
+        let (tenant_id, other_tenant) = (TenantId::new(), TenantId::new());
+        let (admin, support) = (PrincipalId::new(), PrincipalId::new());
+
+        log.record(tenant_id, admin, None, operation::PROJECTION_RESET, "inventory", JsonValue::Null);
+        log.record(tenant_id, support, Some(other_tenant), operation::PROJECTION_REPLAY_CANCEL, "replay-1", JsonValue::Null);
+        log.record(other_tenant, admin, None, operation::PROJECTION_RESET, "sales", JsonValue::Null);
+
+        let all = log.list(tenant_id, &AdminAuditFilter::default());
+        assert_eq!(all.len(), 2);
+        let resets = log.list(
+            tenant_id,
+            &AdminAuditFilter {
+                operation: Some(operation::PROJECTION_RESET.to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!((resets.len(), resets[0].target.as_str()), (1, "inventory"));
+        let by_support = log.list(
+            tenant_id,
+            &AdminAuditFilter {
+                actor: Some(support),
+                ..Default::default()
+            },
+        );
+        assert_eq!(by_support[0].actor_home_tenant_id, Some(other_tenant));
+
+        let mut out = Vec::new();
+        assert_eq!(log.export(tenant_id, &mut out).unwrap(), 2);
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines[0]["operation"], operation::PROJECTION_RESET);
+        assert_eq!(lines[1]["actor"], support.to_string());
+    }
+
+    #[test]
+    fn entries_past_the_retention_are_dropped() {
+        let clock = FixedClock::arc(Utc::now());
+        let log = AdminAuditLog::new(AdminAuditRetention {
+            max_age: Some(chrono::Duration::days(30)),
+        })
+        .with_clock(clock.clone());
+        let tenant_id = TenantId::new();
+        let admin = PrincipalId::new();
+
+        log.record(tenant_id, admin, None, operation::PROJECTION_RESET, "inventory", JsonValue::Null);
+        clock.set(clock.now() + chrono::Duration::days(20));
+        log.record(tenant_id, admin, None, operation::PROJECTION_RESET, "sales", JsonValue::Null);
+        clock.set(clock.now() + chrono::Duration::days(15));
+
+        let kept = log.list(tenant_id, &AdminAuditFilter::default());
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].target, "sales");
+    }
+}
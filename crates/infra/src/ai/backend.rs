@@ -0,0 +1,125 @@
+//! AI backend adapters and configuration.
+//!
+//! - `local`: built-in jobs in-process ([`LocalAiBackend`], the default)
+//! - `http`: POST the [`AiRequest`] as JSON to an external model service, which
+//!   answers with an [`AiResult`]
+//!
+//! Selected via environment:
+//! - `AI_BACKEND`: `local` | `http`
+//! - `AI_BACKEND_URL`: inference endpoint (required for `http`)
+//! - `AI_BACKEND_TIMEOUT_MS`: per-call bound (default 10s)
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use forgeerp_ai::{AiBackend, AiError, AiRequest, AiResult, LocalAiBackend, DEFAULT_BACKEND_TIMEOUT};
+
+/// Calls an external model service over HTTP.
+#[derive(Debug, Clone)]
+pub struct HttpAiBackend {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl HttpAiBackend {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl AiBackend for HttpAiBackend {
+    async fn infer(&self, request: AiRequest) -> Result<AiResult, AiError> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| AiError::InferenceFailed(format!("ai backend request failed: {e}")))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(AiError::InferenceFailed(format!("ai backend returned {status}")));
+        }
+        response
+            .json::<AiResult>()
+            .await
+            .map_err(|e| AiError::InferenceFailed(format!("invalid ai backend response: {e}")))
+    }
+}
+
+/// Which backend to use.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum AiBackendKind {
+    #[default]
+    Local,
+    Http { url: String },
+}
+
+/// Backend selection + time bound.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AiBackendConfig {
+    pub kind: AiBackendKind,
+    pub timeout: Duration,
+}
+
+impl Default for AiBackendConfig {
+    fn default() -> Self {
+        Self {
+            kind: AiBackendKind::Local,
+            timeout: DEFAULT_BACKEND_TIMEOUT,
+        }
+    }
+}
+
+impl AiBackendConfig {
+    /// Read the configuration from `AI_BACKEND*` variables (falls back to local).
+    pub fn from_env() -> Self {
+        let timeout = std::env::var("AI_BACKEND_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_BACKEND_TIMEOUT);
+
+        let kind = match std::env::var("AI_BACKEND").as_deref() {
+            Ok("http") => match std::env::var("AI_BACKEND_URL") {
+                Ok(url) if !url.trim().is_empty() => AiBackendKind::Http { url },
+                _ => {
+                    tracing::warn!("AI_BACKEND=http without AI_BACKEND_URL; using the local backend");
+                    AiBackendKind::Local
+                }
+            },
+            _ => AiBackendKind::Local,
+        };
+
+        Self { kind, timeout }
+    }
+
+    pub fn build(&self) -> ConfiguredAiBackend {
+        match &self.kind {
+            AiBackendKind::Local => ConfiguredAiBackend::Local(LocalAiBackend),
+            AiBackendKind::Http { url } => ConfiguredAiBackend::Http(HttpAiBackend::new(url.clone())),
+        }
+    }
+}
+
+/// Backend chosen at startup.
+#[derive(Debug, Clone)]
+pub enum ConfiguredAiBackend {
+    Local(LocalAiBackend),
+    Http(HttpAiBackend),
+}
+
+#[async_trait]
+impl AiBackend for ConfiguredAiBackend {
+    async fn infer(&self, request: AiRequest) -> Result<AiResult, AiError> {
+        match self {
+            ConfiguredAiBackend::Local(b) => b.infer(request).await,
+            ConfiguredAiBackend::Http(b) => b.infer(request).await,
+        }
+    }
+}
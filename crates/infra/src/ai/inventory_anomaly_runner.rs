@@ -1,23 +1,46 @@
-use std::sync::{mpsc, Arc};
-use std::thread;
-use std::time::{Duration, Instant};
+use std::sync::Arc;
+use std::time::Duration;
 
-use tracing::{info, warn};
+use serde::{Deserialize, Serialize};
 
 use forgeerp_core::TenantId;
 use forgeerp_ai::{
-    AiResult, AiScheduler, InventoryAnomalyJob, InventorySnapshot, LocalAiScheduler, ReadModelReader,
-    TenantScope,
+    anomaly_insights, AiBackend, AiError, AiResult, AiUsageMeter, BackendScheduler, InventoryAnomalyJob,
+    InventorySnapshot, ReadModelReader, TenantScope, DEFAULT_BACKEND_TIMEOUT,
 };
 
+use crate::ai::runner::{spawn_runner, AiRunnerHandle, RunnerSchedule};
+use crate::read_model::{InMemoryTenantStore, TenantStore};
+use crate::workers::liveness::{TaskBeat, TaskRegistry};
+
 /// Sink for AI insights.
 ///
 /// This is intentionally separate from the domain event stream:
 /// AI outputs are *insights*, not domain events.
+///
+/// Results carrying an `idempotency_key` must replace an earlier result with the same
+/// tenant and key instead of being appended (see [`upsert_insight`]).
 pub trait AiInsightSink: Send + Sync + 'static {
     fn emit(&self, tenant_id: TenantId, result: AiResult);
 }
 
+/// Record `result` in `insights`, replacing an entry with the same tenant and idempotency key.
+///
+/// Returns `true` if the insight is new.
+pub fn upsert_insight(insights: &mut Vec<(TenantId, AiResult)>, tenant_id: TenantId, result: AiResult) -> bool {
+    let existing = result.idempotency_key.as_ref().and_then(|key| {
+        insights
+            .iter_mut()
+            .find(|(t, r)| *t == tenant_id && r.idempotency_key.as_ref() == Some(key))
+    });
+    if let Some(existing) = existing {
+        existing.1 = result;
+        return false;
+    }
+    insights.push((tenant_id, result));
+    true
+}
+
 /// In-memory sink for tests/dev.
 #[derive(Debug, Default)]
 pub struct InMemoryAiInsightSink {
@@ -36,7 +59,78 @@ impl InMemoryAiInsightSink {
 
 impl AiInsightSink for InMemoryAiInsightSink {
     fn emit(&self, tenant_id: TenantId, result: AiResult) {
-        self.inner.lock().unwrap().push((tenant_id, result));
+        upsert_insight(&mut self.inner.lock().unwrap(), tenant_id, result);
+    }
+}
+
+/// Detection settings of the inventory anomaly runner.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyConfig {
+    /// Z-score a stock movement must reach to be flagged.
+    pub z_score_threshold: f64,
+    /// Trend points an item needs before it is scored (never fewer than `window + 2`).
+    pub min_samples: usize,
+    /// Previous movements the latest one is compared against.
+    pub window: usize,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            z_score_threshold: 3.0,
+            min_samples: 0,
+            window: 10,
+        }
+    }
+}
+
+impl AnomalyConfig {
+    /// Same rules [`InventoryAnomalyJob`] applies when it runs.
+    pub fn validate(&self) -> Result<(), AiError> {
+        if self.window < 2 {
+            return Err(AiError::InvalidInput(
+                "window must be >= 2 to compute standard deviation".to_string(),
+            ));
+        }
+        if !(self.z_score_threshold.is_finite() && self.z_score_threshold > 0.0) {
+            return Err(AiError::InvalidInput(
+                "z_score_threshold must be a finite positive number".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Per-tenant [`AnomalyConfig`]s, kept in a tenant-scoped store (one entry per tenant).
+#[derive(Clone)]
+pub struct AnomalyConfigs {
+    store: Arc<dyn TenantStore<(), AnomalyConfig>>,
+}
+
+impl std::fmt::Debug for AnomalyConfigs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnomalyConfigs").finish_non_exhaustive()
+    }
+}
+
+impl AnomalyConfigs {
+    pub fn new(store: Arc<dyn TenantStore<(), AnomalyConfig>>) -> Self {
+        Self { store }
+    }
+
+    pub fn in_memory() -> Self {
+        Self::new(Arc::new(InMemoryTenantStore::new()))
+    }
+
+    /// The tenant's own config (`None` = the runner's defaults apply).
+    pub fn get(&self, tenant_id: TenantId) -> Option<AnomalyConfig> {
+        self.store.get(tenant_id, &())
+    }
+
+    pub fn set(&self, tenant_id: TenantId, config: AnomalyConfig) -> Result<(), AiError> {
+        config.validate()?;
+        self.store.upsert(tenant_id, (), config);
+        Ok(())
     }
 }
 
@@ -46,8 +140,16 @@ pub struct InventoryAnomalyRunner {
     pub interval: Duration,
     pub max_retries: u32,
     pub base_backoff: Duration,
-    pub window: usize,
-    pub z_threshold: f64,
+    /// Detection settings for tenants without their own entry in `configs`.
+    pub defaults: AnomalyConfig,
+    /// Per-tenant detection settings, read on every run; `None` = `defaults` for everyone.
+    pub configs: Option<AnomalyConfigs>,
+    /// Upper bound for one backend call.
+    pub backend_timeout: Duration,
+    /// Per-tenant rate limit + cost meter (shared across runners); `None` = unmetered.
+    pub meter: Option<Arc<AiUsageMeter>>,
+    /// Where runners report liveness (as `{name}:{tenant_id}`); `None` = unreported.
+    pub liveness: Option<TaskRegistry>,
 }
 
 impl Default for InventoryAnomalyRunner {
@@ -56,191 +158,431 @@ impl Default for InventoryAnomalyRunner {
             interval: Duration::from_secs(60),
             max_retries: 5,
             base_backoff: Duration::from_millis(250),
-            window: 10,
-            z_threshold: 3.0,
+            defaults: AnomalyConfig::default(),
+            configs: None,
+            backend_timeout: DEFAULT_BACKEND_TIMEOUT,
+            meter: None,
+            liveness: None,
         }
     }
 }
 
-/// Handle for the running AI runner (shutdown + trigger hook).
-#[derive(Debug)]
-pub struct InventoryAnomalyRunnerHandle {
-    shutdown: mpsc::Sender<()>,
-    trigger: mpsc::SyncSender<()>,
-    join: Option<thread::JoinHandle<()>>,
-}
+/// Handle for a running inventory anomaly runner.
+pub type InventoryAnomalyRunnerHandle = AiRunnerHandle;
 
-impl InventoryAnomalyRunnerHandle {
-    /// Event-trigger hook: call after a successful projection update.
+impl InventoryAnomalyRunner {
+    /// Detection settings for `tenant_id`: its stored config, else `defaults`.
+    pub fn config_for(&self, tenant_id: TenantId) -> AnomalyConfig {
+        self.configs
+            .as_ref()
+            .and_then(|configs| configs.get(tenant_id))
+            .unwrap_or(self.defaults)
+    }
+
+    /// Run detection once over the tenant's current snapshot and emit one insight per
+    /// anomaly (keyed, so unchanged anomalies update rather than duplicate).
     ///
-    /// Backpressure: triggers are coalesced (bounded queue). If the runner is already pending,
-    /// this becomes a no-op.
-    pub fn trigger(&self) {
-        // Coalesce: channel capacity=1; ignore if already full.
-        let _ = self.trigger.try_send(());
-    }
-
-    /// Gracefully stop the runner thread.
-    pub fn shutdown(mut self) {
-        let _ = self.shutdown.send(());
-        if let Some(j) = self.join.take() {
-            let _ = j.join();
+    /// Returns the number of insights emitted.
+    pub async fn run_once<R, S, B>(
+        &self,
+        tenant_id: TenantId,
+        reader: &R,
+        sink: &S,
+        scheduler: &BackendScheduler<B>,
+    ) -> Result<usize, AiError>
+    where
+        R: ReadModelReader<InventorySnapshot> + ?Sized,
+        S: AiInsightSink + ?Sized,
+        B: AiBackend,
+    {
+        let config = self.config_for(tenant_id);
+        let snapshot = reader.get_snapshot(tenant_id)?;
+        let job = InventoryAnomalyJob::new(tenant_id, snapshot)
+            .with_window(config.window)
+            .with_z_threshold(config.z_score_threshold)
+            .with_min_samples(config.min_samples);
+        let result = scheduler.run(job.to_request()).await?;
+
+        let insights = anomaly_insights(&result);
+        let emitted = insights.len();
+        for insight in insights {
+            sink.emit(tenant_id, insight);
         }
+        Ok(emitted)
     }
-}
 
-impl InventoryAnomalyRunner {
     /// Spawn a tenant-scoped runner.
     ///
     /// - Schedule: runs every `interval`
     /// - Event-trigger: call `handle.trigger()` after projection updates
     /// - Failures: logged + retried with bounded exponential backoff; never propagate
-    pub fn spawn_for_tenant<R, S>(
+    /// - Backend: every call goes through `backend`, bounded by `backend_timeout`
+    /// - Rate limit: runs over the tenant's `meter` budget are dropped (counted, not retried)
+    /// - Settings: each run uses [`Self::config_for`] the tenant, so config updates apply
+    ///   without respawning
+    pub fn spawn_for_tenant<R, S, B>(
         &self,
         name: &'static str,
         tenant_id: TenantId,
         reader: Arc<R>,
         sink: Arc<S>,
+        backend: B,
     ) -> InventoryAnomalyRunnerHandle
     where
         R: ReadModelReader<InventorySnapshot> + 'static,
         S: AiInsightSink + 'static,
+        B: AiBackend,
     {
-        let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>();
-        let (trigger_tx, trigger_rx) = mpsc::sync_channel::<()>(1);
-
         let cfg = self.clone();
-        let join = thread::Builder::new()
-            .name(name.to_string())
-            .spawn(move || {
-                runner_loop(
-                    name,
-                    tenant_id,
-                    cfg,
-                    shutdown_rx,
-                    trigger_rx,
-                    reader,
-                    sink,
-                )
-            })
-            .expect("failed to spawn inventory anomaly runner thread");
-
-        InventoryAnomalyRunnerHandle {
-            shutdown: shutdown_tx,
-            trigger: trigger_tx,
-            join: Some(join),
+        let schedule = RunnerSchedule {
+            interval: self.interval,
+            max_retries: self.max_retries,
+            base_backoff: self.base_backoff,
+        };
+        let beat = match &self.liveness {
+            Some(registry) => registry.register(format!("{name}:{tenant_id}")),
+            None => TaskBeat::detached(name),
+        };
+        let mut scheduler =
+            BackendScheduler::new(TenantScope::Tenant(tenant_id), backend).with_timeout(self.backend_timeout);
+        if let Some(meter) = &self.meter {
+            scheduler = scheduler.with_meter(meter.clone());
         }
+        spawn_runner(name, tenant_id, schedule, beat, move |rt| {
+            rt.block_on(cfg.run_once(tenant_id, reader.as_ref(), sink.as_ref(), &scheduler))
+        })
     }
 }
 
-fn runner_loop<R, S>(
-    name: &'static str,
-    tenant_id: TenantId,
-    cfg: InventoryAnomalyRunner,
-    shutdown_rx: mpsc::Receiver<()>,
-    trigger_rx: mpsc::Receiver<()>,
-    reader: Arc<R>,
-    sink: Arc<S>,
-) where
-    R: ReadModelReader<InventorySnapshot> + 'static,
-    S: AiInsightSink + 'static,
-{
-    info!(runner = name, tenant = %tenant_id, "inventory anomaly runner started");
-
-    let scheduler = LocalAiScheduler::new(TenantScope::Tenant(tenant_id));
-
-    let mut next_tick = Instant::now() + cfg.interval;
-    let mut pending = true; // run once on startup
-    let mut failures: u32 = 0;
-    let mut backoff_until: Option<Instant> = None;
-
-    loop {
-        // Shutdown has priority.
-        if shutdown_rx.try_recv().is_ok() {
-            break;
-        }
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
 
-        let now = Instant::now();
-        if now >= next_tick {
-            pending = true;
-            // Keep a stable cadence even if we were delayed.
-            while next_tick <= now {
-                next_tick += cfg.interval;
-            }
-        }
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use forgeerp_ai::{AiInsightKind, AiRequest, AnomalyEntry, InventoryItemSnapshot, LocalAiBackend};
+    use forgeerp_events::{EventEnvelope, InMemoryEventBus};
+    use forgeerp_inventory::{CreateItem, InventoryCommand, InventoryItem, InventoryItemId};
+    use serde_json::{json, Value as JsonValue};
+
+    use super::*;
+    use crate::command_dispatcher::CommandDispatcher;
+    use crate::event_store::InMemoryEventStore;
+
+    /// Single-item snapshot whose trend the test controls.
+    struct TrendReader(Mutex<Vec<i64>>);
 
-        // Event-trigger: non-blocking drain to coalesce multiple triggers.
-        while trigger_rx.try_recv().is_ok() {
-            pending = true;
+    impl ReadModelReader<InventorySnapshot> for TrendReader {
+        fn get_snapshot(&self, tenant_id: TenantId) -> Result<InventorySnapshot, AiError> {
+            let trend = self.0.lock().unwrap().clone();
+            Ok(InventorySnapshot {
+                tenant_id,
+                items: vec![InventoryItemSnapshot {
+                    item_id: "item-1".to_string(),
+                    quantity: *trend.last().unwrap(),
+                    historical_trend: trend,
+                }],
+            })
         }
+    }
+
+    /// Test backends: a canned answer, a failure, or a call that never returns.
+    enum MockBackend {
+        Canned(AiResult),
+        Failing,
+        Hanging,
+    }
 
-        // Backoff gate.
-        if let Some(until) = backoff_until {
-            if Instant::now() < until {
-                thread::sleep(Duration::from_millis(50));
-                continue;
+    #[async_trait]
+    impl AiBackend for MockBackend {
+        async fn infer(&self, _request: AiRequest) -> Result<AiResult, AiError> {
+            match self {
+                MockBackend::Canned(result) => Ok(result.clone()),
+                MockBackend::Failing => Err(AiError::InferenceFailed("model service unavailable".to_string())),
+                MockBackend::Hanging => {
+                    tokio::time::sleep(Duration::from_secs(3600)).await;
+                    unreachable!("backend call should have timed out")
+                }
             }
-            backoff_until = None;
         }
+    }
 
-        if !pending {
-            // Wait until next tick or trigger or shutdown.
-            let sleep_for = next_tick
-                .saturating_duration_since(Instant::now())
-                .min(Duration::from_millis(250));
-            thread::sleep(sleep_for);
-            continue;
+    fn runner() -> InventoryAnomalyRunner {
+        InventoryAnomalyRunner {
+            defaults: AnomalyConfig {
+                window: 3,
+                ..Default::default()
+            },
+            backend_timeout: Duration::from_millis(50),
+            ..Default::default()
         }
+    }
 
-        pending = false;
-
-        // 1) Get tenant snapshot (read model).
-        let snapshot = match reader.get_snapshot(tenant_id) {
-            Ok(s) => s,
-            Err(e) => {
-                warn!(runner = name, tenant = %tenant_id, error = ?e, "failed to get inventory snapshot");
-                failures += 1;
-                if failures <= cfg.max_retries {
-                    pending = true;
-                    backoff_until = Some(Instant::now() + backoff(cfg.base_backoff, failures));
-                } else {
-                    failures = 0;
-                }
-                continue;
-            }
+    fn run<B: AiBackend>(
+        tenant_id: TenantId,
+        reader: &TrendReader,
+        sink: &InMemoryAiInsightSink,
+        backend: B,
+    ) -> Result<usize, AiError> {
+        let scheduler = BackendScheduler::new(TenantScope::Tenant(tenant_id), backend)
+            .with_timeout(runner().backend_timeout);
+        run_with(tenant_id, reader, sink, &scheduler)
+    }
+
+    fn run_with<B: AiBackend>(
+        tenant_id: TenantId,
+        reader: &TrendReader,
+        sink: &InMemoryAiInsightSink,
+        scheduler: &BackendScheduler<B>,
+    ) -> Result<usize, AiError> {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(runner().run_once(tenant_id, reader, sink, scheduler))
+    }
+
+    #[test]
+    fn rerunning_over_unchanged_data_keeps_a_single_insight() {
+        let tenant_id = TenantId::new();
+        let reader = TrendReader(Mutex::new(vec![0, 1, 2, 3, 4, 14]));
+        let sink = InMemoryAiInsightSink::new();
+
+        assert_eq!(run(tenant_id, &reader, &sink, LocalAiBackend).unwrap(), 1);
+        assert_eq!(run(tenant_id, &reader, &sink, LocalAiBackend).unwrap(), 1);
+
+        let all = sink.all();
+        assert_eq!(all.len(), 1);
+        assert_eq!(
+            all[0].1.idempotency_key.as_deref(),
+            Some("inventory.anomaly_detection:item-1:1-5")
+        );
+    }
+
+    #[test]
+    fn tenant_config_overrides_the_defaults() {
+        let tenant_id = TenantId::new();
+        let other_tenant = TenantId::new();
+        // Baseline deltas 2, 1, 2 then a jump of 10: z ≈ 14.4.
+        let reader = TrendReader(Mutex::new(vec![0, 1, 3, 4, 6, 16]));
+        let configs = AnomalyConfigs::in_memory();
+        let runner = InventoryAnomalyRunner {
+            configs: Some(configs.clone()),
+            ..runner()
         };
+        let run_once = |tenant_id: TenantId| {
+            let sink = InMemoryAiInsightSink::new();
+            let scheduler = BackendScheduler::new(TenantScope::Tenant(tenant_id), LocalAiBackend);
+            tokio::runtime::Builder::new_current_thread()
+                .enable_time()
+                .build()
+                .unwrap()
+                .block_on(runner.run_once(tenant_id, &reader, &sink, &scheduler))
+                .unwrap()
+        };
+        assert_eq!(runner.config_for(tenant_id), runner.defaults);
+        assert_eq!(run_once(tenant_id), 1);
 
-        // 2) Run deterministic inference.
-        let job = InventoryAnomalyJob::new(tenant_id, snapshot)
-            .with_window(cfg.window)
-            .with_z_threshold(cfg.z_threshold);
+        // A spiky tenant raises its threshold: the same movement is no longer flagged.
+        let spiky = AnomalyConfig {
+            z_score_threshold: 100.0,
+            ..runner.defaults
+        };
+        configs.set(tenant_id, spiky).unwrap();
+        assert_eq!(run_once(tenant_id), 0);
+        // Tenants without a config keep the defaults.
+        assert_eq!(run_once(other_tenant), 1);
 
-        match scheduler.run(job) {
-            Ok(result) => {
-                failures = 0;
-                sink.emit(tenant_id, result);
-            }
-            Err(e) => {
-                warn!(runner = name, tenant = %tenant_id, error = ?e, "inventory anomaly job failed");
-                failures += 1;
-                if failures <= cfg.max_retries {
-                    pending = true;
-                    backoff_until = Some(Instant::now() + backoff(cfg.base_backoff, failures));
-                } else {
-                    failures = 0;
-                }
-            }
-        }
+        // Requiring more history than the trend has also suppresses it.
+        configs
+            .set(tenant_id, AnomalyConfig { min_samples: 7, ..runner.defaults })
+            .unwrap();
+        assert_eq!(run_once(tenant_id), 0);
+
+        assert!(configs
+            .set(tenant_id, AnomalyConfig { window: 1, ..runner.defaults })
+            .is_err());
+        assert_eq!(configs.get(tenant_id).map(|c| c.min_samples), Some(7));
     }
 
-    info!(runner = name, tenant = %tenant_id, "inventory anomaly runner stopped");
-}
+    #[test]
+    fn new_anomaly_on_the_same_item_is_a_distinct_insight() {
+        let tenant_id = TenantId::new();
+        let reader = TrendReader(Mutex::new(vec![0, 1, 2, 3, 4, 14]));
+        let sink = InMemoryAiInsightSink::new();
+        run(tenant_id, &reader, &sink, LocalAiBackend).unwrap();
 
-fn backoff(base: Duration, attempt: u32) -> Duration {
-    // Exponential backoff: base * 2^(attempt-1), capped.
-    let pow = 1u32 << attempt.saturating_sub(1).min(10);
-    let ms = base.as_millis().saturating_mul(pow as u128);
-    Duration::from_millis(ms.min(10_000) as u64)
-}
+        reader.0.lock().unwrap().push(40);
+        run(tenant_id, &reader, &sink, LocalAiBackend).unwrap();
+        run(tenant_id, &reader, &sink, LocalAiBackend).unwrap();
+
+        let keys: Vec<String> = sink.all().into_iter().filter_map(|(_, r)| r.idempotency_key).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "inventory.anomaly_detection:item-1:1-5".to_string(),
+                "inventory.anomaly_detection:item-1:2-6".to_string(),
+            ]
+        );
+        // The same key in another tenant is not collapsed.
+        run(TenantId::new(), &reader, &sink, LocalAiBackend).unwrap();
+        assert_eq!(sink.all().len(), 3);
+    }
+
+    #[test]
+    fn canned_backend_result_becomes_insights() {
+        let tenant_id = TenantId::new();
+        let reader = TrendReader(Mutex::new(vec![5]));
+        let sink = InMemoryAiInsightSink::new();
+        let canned = AiResult::new(1.0, 0.9).with_metadata(json!({
+            "kind": InventoryAnomalyJob::KIND,
+            "anomalies": [{ "item_id": "remote-item", "severity": 2.5, "explanation": "remote model", "window": [0, 9] }],
+        }));
+
+        assert_eq!(run(tenant_id, &reader, &sink, MockBackend::Canned(canned)).unwrap(), 1);
+        let all = sink.all();
+        assert_eq!(all[0].1.explanation.as_deref(), Some("remote model"));
+        assert_eq!(
+            all[0].1.idempotency_key.as_deref(),
+            Some("inventory.anomaly_detection:remote-item:0-9")
+        );
+    }
+
+    #[test]
+    fn anomaly_results_round_trip_through_the_typed_accessor() {
+        let anomalies = vec![
+            AnomalyEntry {
+                item_id: "bolts".to_string(),
+                severity: 2.0,
+                explanation: "spike".to_string(),
+                window: (0, 4),
+            },
+            AnomalyEntry {
+                item_id: "nuts".to_string(),
+                severity: 1.2,
+                explanation: "drop".to_string(),
+                window: (1, 5),
+            },
+        ];
+        let result = AiResult::new(2.0, 0.8).with_metadata(json!({
+            "kind": InventoryAnomalyJob::KIND,
+            "anomalies": anomalies,
+        }));
+        let result: AiResult = serde_json::from_value(serde_json::to_value(&result).unwrap()).unwrap();
+
+        assert_eq!(result.kind(), Some(AiInsightKind::InventoryAnomalyDetection));
+        assert_eq!(result.as_inventory_anomalies(), Some(anomalies));
+
+        let nuts: Vec<AnomalyEntry> = anomaly_insights(&result)
+            .iter()
+            .filter_map(AiResult::as_inventory_anomalies)
+            .flatten()
+            .filter(|a| a.item_id == "nuts")
+            .collect();
+        assert_eq!(nuts.len(), 1);
+        assert_eq!(nuts[0].window, (1, 5));
+
+        // Other kinds and malformed payloads are not anomalies.
+        assert_eq!(AiResult::new(0.0, 1.0).as_inventory_anomalies(), None);
+        let other = AiResult::new(0.0, 1.0).with_metadata(json!({ "kind": "sales.forecast", "anomalies": [] }));
+        assert_eq!(other.kind(), Some(AiInsightKind::SalesForecast));
+        assert_eq!(other.as_inventory_anomalies(), None);
+        let unknown = AiResult::new(0.0, 1.0).with_metadata(json!({ "kind": "sales.churn", "anomalies": [] }));
+        assert_eq!(unknown.kind(), None);
+    }
+
+    #[test]
+    fn failing_or_hanging_backend_is_isolated_from_core_workflows() {
+        let tenant_id = TenantId::new();
+        let reader = TrendReader(Mutex::new(vec![0, 1, 2, 3, 4, 14]));
+        let sink = InMemoryAiInsightSink::new();
+
+        assert!(matches!(
+            run(tenant_id, &reader, &sink, MockBackend::Failing),
+            Err(AiError::InferenceFailed(_))
+        ));
+        let started = std::time::Instant::now();
+        let err = run(tenant_id, &reader, &sink, MockBackend::Hanging).unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(sink.all().is_empty());
+
+        // A runner stuck on a hanging backend does not hold up command dispatch.
+        let dispatcher = CommandDispatcher::new(
+            InMemoryEventStore::new(),
+            InMemoryEventBus::<EventEnvelope<JsonValue>>::new(),
+        );
+        let handle = runner().spawn_for_tenant(
+            "test.ai",
+            tenant_id,
+            Arc::new(reader),
+            Arc::new(InMemoryAiInsightSink::new()),
+            MockBackend::Hanging,
+        );
+        handle.trigger();
+        let item_id = InventoryItemId::new(forgeerp_core::AggregateId::new());
+        let committed = dispatcher
+            .dispatch(
+                tenant_id,
+                item_id.0,
+                "inventory.item",
+                InventoryCommand::CreateItem(CreateItem {
+                    tenant_id,
+                    item_id,
+                    name: "Widget".to_string(),
+                    occurred_at: Utc::now(),
+                }),
+                |_, id| InventoryItem::empty(InventoryItemId::new(id)),
+            )
+            .unwrap();
+        assert_eq!(committed.len(), 1);
+        handle.shutdown();
+    }
+
+    #[test]
+    fn runs_over_the_tenant_rate_are_dropped_and_usage_is_tallied() {
+        let tenant_id = TenantId::new();
+        let reader = TrendReader(Mutex::new(vec![0, 1, 2, 3, 4, 14]));
+        let sink = InMemoryAiInsightSink::new();
+        let meter = Arc::new(AiUsageMeter::new(forgeerp_ai::RateLimit {
+            burst: 2,
+            per_minute: 1,
+        }));
+        let scheduler = BackendScheduler::new(TenantScope::Tenant(tenant_id), LocalAiBackend).with_meter(meter.clone());
 
+        let outcomes: Vec<_> = (0..5).map(|_| run_with(tenant_id, &reader, &sink, &scheduler)).collect();
 
+        assert!(outcomes[..2].iter().all(Result::is_ok));
+        assert!(outcomes[2..].iter().all(|o| matches!(o, Err(AiError::RateLimited(_)))));
+        assert_eq!(
+            meter.usage(tenant_id),
+            forgeerp_ai::AiUsage {
+                runs: 2,
+                failed: 0,
+                dropped: 3,
+                cost_units: 2,
+            }
+        );
+
+        // Other tenants have their own bucket.
+        let other = TenantId::new();
+        let scheduler = BackendScheduler::new(TenantScope::Tenant(other), LocalAiBackend).with_meter(meter.clone());
+        assert!(run_with(other, &reader, &sink, &scheduler).is_ok());
+        assert_eq!(meter.usage(other).runs, 1);
+    }
+
+    #[test]
+    fn token_bucket_refills_over_time() {
+        let meter = AiUsageMeter::new(forgeerp_ai::RateLimit {
+            burst: 1,
+            per_minute: 60,
+        });
+        let tenant_id = TenantId::new();
+        let start = std::time::Instant::now();
+
+        assert!(meter.try_acquire_at(tenant_id, start));
+        assert!(!meter.try_acquire_at(tenant_id, start));
+        assert!(meter.try_acquire_at(tenant_id, start + Duration::from_secs(1)));
+        assert_eq!(meter.usage(tenant_id).dropped, 1);
+    }
+}
@@ -3,10 +3,22 @@
 //! These components trigger AI jobs from projection updates or schedules.
 //! Failures are isolated and must not impact core workflows.
 
+pub mod backend;
 pub mod inventory_anomaly_runner;
+pub mod postgres_sink;
+pub mod reorder_suggestion_runner;
+pub mod runner;
+pub mod sales_forecast_runner;
+
+pub use backend::{AiBackendConfig, AiBackendKind, ConfiguredAiBackend, HttpAiBackend};
 
 pub use inventory_anomaly_runner::{
-    AiInsightSink, InMemoryAiInsightSink, InventoryAnomalyRunner, InventoryAnomalyRunnerHandle,
+    upsert_insight, AiInsightSink, AnomalyConfig, AnomalyConfigs, InMemoryAiInsightSink, InventoryAnomalyRunner,
+    InventoryAnomalyRunnerHandle,
 };
+pub use postgres_sink::{AiInsightStoreError, PostgresAiInsightSink, DEFAULT_AI_INSIGHT_RETENTION_DAYS};
+pub use reorder_suggestion_runner::{InventorySalesReader, ReorderSuggestionRunner, ReorderSuggestionRunnerHandle};
+pub use runner::AiRunnerHandle;
+pub use sales_forecast_runner::{SalesForecastRunner, SalesForecastRunnerHandle};
 
 
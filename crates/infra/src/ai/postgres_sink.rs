@@ -0,0 +1,164 @@
+//! Postgres-backed AI insight sink (`ai_insights` table).
+//!
+//! Runners emit from their own threads and runtimes, so writes are spawned onto the
+//! runtime the sink was created in instead of blocking the caller.
+
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use thiserror::Error;
+use tokio::runtime::Handle;
+use tracing::warn;
+use uuid::Uuid;
+
+use forgeerp_ai::{AiInsightKind, AiResult};
+use forgeerp_core::TenantId;
+
+use crate::ai::inventory_anomaly_runner::AiInsightSink;
+
+/// Default retention of stored insights (days since their last update).
+pub const DEFAULT_AI_INSIGHT_RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, Error)]
+pub enum AiInsightStoreError {
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Stores insights in `ai_insights`, keyed by tenant and kind.
+///
+/// Keyed results replace the row with the same tenant and idempotency key. Each write
+/// also deletes the tenant's insights not updated within the retention.
+#[derive(Debug)]
+pub struct PostgresAiInsightSink {
+    pool: Arc<PgPool>,
+    handle: Handle,
+    retention: Duration,
+}
+
+impl PostgresAiInsightSink {
+    /// Must be called inside a Tokio runtime: writes are spawned onto it.
+    pub fn new(pool: PgPool, retention: Duration) -> Self {
+        Self {
+            pool: Arc::new(pool),
+            handle: Handle::current(),
+            retention,
+        }
+    }
+
+    /// Store `result` in the background; `on_new` runs once it is stored, if it did not
+    /// replace an earlier insight. Failures are logged, never propagated to the runner.
+    pub fn emit_then<F>(&self, tenant_id: TenantId, result: AiResult, on_new: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let pool = self.pool.clone();
+        let retention = self.retention;
+        self.handle.spawn(async move {
+            match upsert(&pool, tenant_id, &result).await {
+                Ok(true) => on_new(),
+                Ok(false) => {}
+                Err(e) => warn!(tenant = %tenant_id, error = %e, "failed to store AI insight"),
+            }
+            if let Err(e) = purge_older_than(&pool, tenant_id, retention).await {
+                warn!(tenant = %tenant_id, error = %e, "failed to purge expired AI insights");
+            }
+        });
+    }
+
+    /// Insights of one kind for the tenant, oldest first.
+    pub async fn query(&self, tenant_id: TenantId, kind: AiInsightKind) -> Result<Vec<AiResult>, AiInsightStoreError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT score, confidence, explanation, metadata, idempotency_key
+            FROM ai_insights
+            WHERE tenant_id = $1 AND kind = $2
+            ORDER BY created_at, insight_id
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(kind.as_str())
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(rows.iter().map(insight_from_row).collect::<Result<_, sqlx::Error>>()?)
+    }
+
+    /// Delete the tenant's insights not updated within the retention; returns how many.
+    pub async fn purge_expired(&self, tenant_id: TenantId) -> Result<u64, AiInsightStoreError> {
+        Ok(purge_older_than(&self.pool, tenant_id, self.retention).await?)
+    }
+}
+
+impl AiInsightSink for PostgresAiInsightSink {
+    fn emit(&self, tenant_id: TenantId, result: AiResult) {
+        self.emit_then(tenant_id, result, || {});
+    }
+}
+
+/// Insert or replace (by idempotency key); `true` if a new row was inserted.
+async fn upsert(pool: &PgPool, tenant_id: TenantId, result: &AiResult) -> Result<bool, sqlx::Error> {
+    let kind = result
+        .metadata
+        .get("kind")
+        .and_then(|k| k.as_str())
+        .unwrap_or("unknown");
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO ai_insights (
+            insight_id,
+            tenant_id,
+            kind,
+            idempotency_key,
+            score,
+            confidence,
+            explanation,
+            metadata
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (tenant_id, idempotency_key) WHERE idempotency_key IS NOT NULL
+        DO UPDATE SET
+            kind = EXCLUDED.kind,
+            score = EXCLUDED.score,
+            confidence = EXCLUDED.confidence,
+            explanation = EXCLUDED.explanation,
+            metadata = EXCLUDED.metadata,
+            updated_at = NOW()
+        RETURNING (xmax = 0) AS inserted
+        "#,
+    )
+    .bind(Uuid::now_v7())
+    .bind(tenant_id.as_uuid())
+    .bind(kind)
+    .bind(&result.idempotency_key)
+    .bind(result.score)
+    .bind(result.confidence)
+    .bind(&result.explanation)
+    .bind(&result.metadata)
+    .fetch_one(pool)
+    .await?;
+
+    row.try_get::<bool, _>("inserted")
+}
+
+async fn purge_older_than(pool: &PgPool, tenant_id: TenantId, retention: Duration) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM ai_insights WHERE tenant_id = $1 AND updated_at < $2")
+        .bind(tenant_id.as_uuid())
+        .bind(Utc::now() - retention)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+fn insight_from_row(row: &PgRow) -> Result<AiResult, sqlx::Error> {
+    Ok(AiResult {
+        score: row.try_get("score")?,
+        confidence: row.try_get("confidence")?,
+        explanation: row.try_get("explanation")?,
+        metadata: row.try_get("metadata")?,
+        idempotency_key: row.try_get("idempotency_key")?,
+    })
+}
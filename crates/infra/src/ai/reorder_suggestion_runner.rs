@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use forgeerp_ai::{
+    reorder_insights, AiBackend, AiError, AiUsageMeter, BackendScheduler, InventorySnapshot, ReadModelReader,
+    ReorderItemSnapshot, ReorderSnapshot, ReorderSuggestionJob, SalesSnapshot, TenantScope, DEFAULT_BACKEND_TIMEOUT,
+};
+use forgeerp_core::{AggregateId, TenantId};
+use forgeerp_products::ProductId;
+
+use crate::ai::inventory_anomaly_runner::AiInsightSink;
+use crate::ai::runner::{spawn_runner, AiRunnerHandle, RunnerSchedule};
+use crate::saga::sales_reservation::InventoryItemLookup;
+use crate::workers::liveness::{TaskBeat, TaskRegistry};
+
+/// Config for the inventory reorder suggestion runner.
+#[derive(Debug, Clone)]
+pub struct ReorderSuggestionRunner {
+    pub interval: Duration,
+    pub max_retries: u32,
+    pub base_backoff: Duration,
+    /// Orders averaged per item.
+    pub window: usize,
+    /// Average orders the stock on hand should cover.
+    pub cover: u32,
+    /// Upper bound for one backend call.
+    pub backend_timeout: Duration,
+    /// Per-tenant rate limit + cost meter (shared across runners); `None` = unmetered.
+    pub meter: Option<Arc<AiUsageMeter>>,
+    /// Where runners report liveness (as `{name}:{tenant_id}`); `None` = unreported.
+    pub liveness: Option<TaskRegistry>,
+}
+
+impl Default for ReorderSuggestionRunner {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(300),
+            max_retries: 5,
+            base_backoff: Duration::from_millis(250),
+            window: 5,
+            cover: 2,
+            backend_timeout: DEFAULT_BACKEND_TIMEOUT,
+            meter: None,
+            liveness: None,
+        }
+    }
+}
+
+/// Handle for a running reorder suggestion runner.
+pub type ReorderSuggestionRunnerHandle = AiRunnerHandle;
+
+impl ReorderSuggestionRunner {
+    /// Suggest reorders once over the tenant's current stock and sales and emit one insight
+    /// per item with sales (keyed by item, so each run replaces the previous suggestion).
+    ///
+    /// Returns the number of insights emitted.
+    pub async fn run_once<R, S, B>(
+        &self,
+        tenant_id: TenantId,
+        reader: &R,
+        sink: &S,
+        scheduler: &BackendScheduler<B>,
+    ) -> Result<usize, AiError>
+    where
+        R: ReadModelReader<ReorderSnapshot> + ?Sized,
+        S: AiInsightSink + ?Sized,
+        B: AiBackend,
+    {
+        let snapshot = reader.get_snapshot(tenant_id)?;
+        let job = ReorderSuggestionJob::new(tenant_id, snapshot)
+            .with_window(self.window)
+            .with_cover(self.cover);
+        let result = scheduler.run(job.to_request()).await?;
+
+        let insights = reorder_insights(&result);
+        let emitted = insights.len();
+        for insight in insights {
+            sink.emit(tenant_id, insight);
+        }
+        Ok(emitted)
+    }
+
+    /// Spawn a tenant-scoped runner (same schedule, trigger and failure isolation as
+    /// [`crate::ai::InventoryAnomalyRunner::spawn_for_tenant`]).
+    pub fn spawn_for_tenant<R, S, B>(
+        &self,
+        name: &'static str,
+        tenant_id: TenantId,
+        reader: Arc<R>,
+        sink: Arc<S>,
+        backend: B,
+    ) -> ReorderSuggestionRunnerHandle
+    where
+        R: ReadModelReader<ReorderSnapshot> + 'static,
+        S: AiInsightSink + 'static,
+        B: AiBackend,
+    {
+        let cfg = self.clone();
+        let schedule = RunnerSchedule {
+            interval: self.interval,
+            max_retries: self.max_retries,
+            base_backoff: self.base_backoff,
+        };
+        let beat = match &self.liveness {
+            Some(registry) => registry.register(format!("{name}:{tenant_id}")),
+            None => TaskBeat::detached(name),
+        };
+        let mut scheduler =
+            BackendScheduler::new(TenantScope::Tenant(tenant_id), backend).with_timeout(self.backend_timeout);
+        if let Some(meter) = &self.meter {
+            scheduler = scheduler.with_meter(meter.clone());
+        }
+        spawn_runner(name, tenant_id, schedule, beat, move |rt| {
+            rt.block_on(cfg.run_once(tenant_id, reader.as_ref(), sink.as_ref(), &scheduler))
+        })
+    }
+}
+
+/// Joins stock on hand with sales velocity into a [`ReorderSnapshot`].
+///
+/// Sales demand is recorded per product; products map to inventory items through
+/// `items`. Products without a stocked item are skipped, and an item sold through
+/// several products gets the demand of all of them.
+pub struct InventorySalesReader<I: ?Sized, S: ?Sized> {
+    inventory: Arc<I>,
+    sales: Arc<S>,
+    items: Arc<dyn InventoryItemLookup>,
+}
+
+impl<I: ?Sized, S: ?Sized> InventorySalesReader<I, S> {
+    pub fn new(inventory: Arc<I>, sales: Arc<S>, items: Arc<dyn InventoryItemLookup>) -> Self {
+        Self { inventory, sales, items }
+    }
+}
+
+impl<I, S> ReadModelReader<ReorderSnapshot> for InventorySalesReader<I, S>
+where
+    I: ReadModelReader<InventorySnapshot> + ?Sized,
+    S: ReadModelReader<SalesSnapshot> + ?Sized,
+{
+    fn get_snapshot(&self, tenant_id: TenantId) -> Result<ReorderSnapshot, AiError> {
+        let stock = self.inventory.get_snapshot(tenant_id)?;
+        let sales = self.sales.get_snapshot(tenant_id)?;
+
+        let mut demand: HashMap<String, Vec<i64>> = HashMap::new();
+        for product in sales.products {
+            let Ok(product_id) = product.product_id.parse::<AggregateId>() else {
+                continue;
+            };
+            if let Some(item_id) = self.items.inventory_item(tenant_id, ProductId::new(product_id)) {
+                demand
+                    .entry(item_id.to_string())
+                    .or_default()
+                    .extend(product.demand_history);
+            }
+        }
+
+        Ok(ReorderSnapshot {
+            tenant_id,
+            items: stock
+                .items
+                .into_iter()
+                .map(|item| ReorderItemSnapshot {
+                    demand_history: demand.remove(&item.item_id).unwrap_or_default(),
+                    item_id: item.item_id,
+                    on_hand: item.quantity,
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forgeerp_ai::{InventoryItemSnapshot, LocalAiBackend, ProductDemandSnapshot};
+    use forgeerp_inventory::InventoryItemId;
+
+    use super::*;
+    use crate::ai::InMemoryAiInsightSink;
+
+    struct StockReader(Vec<(String, i64)>);
+
+    impl ReadModelReader<InventorySnapshot> for StockReader {
+        fn get_snapshot(&self, tenant_id: TenantId) -> Result<InventorySnapshot, AiError> {
+            Ok(InventorySnapshot {
+                tenant_id,
+                items: self
+                    .0
+                    .iter()
+                    .map(|(item_id, quantity)| InventoryItemSnapshot {
+                        item_id: item_id.clone(),
+                        quantity: *quantity,
+                        historical_trend: Vec::new(),
+                    })
+                    .collect(),
+            })
+        }
+    }
+
+    struct DemandReader(Vec<(String, Vec<i64>)>);
+
+    impl ReadModelReader<SalesSnapshot> for DemandReader {
+        fn get_snapshot(&self, tenant_id: TenantId) -> Result<SalesSnapshot, AiError> {
+            Ok(SalesSnapshot {
+                tenant_id,
+                products: self
+                    .0
+                    .iter()
+                    .map(|(product_id, history)| ProductDemandSnapshot {
+                        product_id: product_id.clone(),
+                        demand_history: history.clone(),
+                    })
+                    .collect(),
+            })
+        }
+    }
+
+    fn run(
+        tenant_id: TenantId,
+        reader: &dyn ReadModelReader<ReorderSnapshot>,
+        sink: &InMemoryAiInsightSink,
+    ) -> usize {
+        let runner = ReorderSuggestionRunner {
+            window: 3,
+            cover: 2,
+            ..Default::default()
+        };
+        let scheduler = BackendScheduler::new(TenantScope::Tenant(tenant_id), LocalAiBackend);
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(runner.run_once(tenant_id, reader, sink, &scheduler))
+            .unwrap()
+    }
+
+    #[test]
+    fn suggestions_top_stock_up_to_expected_sales_per_item() {
+        let tenant_id = TenantId::new();
+        let item = || InventoryItemId::new(AggregateId::new());
+        let product = || ProductId::new(AggregateId::new());
+        let (bolts, nuts, washers) = (item(), item(), item());
+        let (bolt_product, nut_product, unstocked) = (product(), product(), product());
+
+        let items: HashMap<ProductId, InventoryItemId> =
+            [(bolt_product, bolts), (nut_product, nuts)].into_iter().collect();
+        let lookup = move |_: TenantId, product_id: ProductId| items.get(&product_id).copied();
+        let reader = InventorySalesReader::new(
+            Arc::new(StockReader(vec![(bolts.to_string(), 3), (nuts.to_string(), 50), (washers.to_string(), 0)])),
+            Arc::new(DemandReader(vec![
+                (bolt_product.to_string(), vec![100, 4, 6, 8]),
+                (nut_product.to_string(), vec![10]),
+                (unstocked.to_string(), vec![7]),
+            ])),
+            Arc::new(lookup),
+        );
+        let sink = InMemoryAiInsightSink::new();
+
+        // Washers have no sales, so no suggestion.
+        assert_eq!(run(tenant_id, &reader, &sink), 2);
+        assert_eq!(run(tenant_id, &reader, &sink), 2);
+
+        let suggestions: Vec<_> = sink
+            .all()
+            .into_iter()
+            .filter_map(|(_, r)| r.as_reorder_suggestions())
+            .flatten()
+            .map(|s| (s.item_id, s.suggested_qty, s.on_hand))
+            .collect();
+        // Bolts: avg(4, 6, 8) * 2 = 12, minus 3 on hand. Nuts: 50 on hand cover 20.
+        assert_eq!(suggestions, vec![(bolts.to_string(), 9, 3), (nuts.to_string(), 0, 50)]);
+        assert_eq!(
+            sink.all()[0].1.idempotency_key,
+            Some(format!("inventory.reorder_suggestion:{bolts}"))
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! Thread loop shared by the tenant-scoped AI runners.
+
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tracing::{debug, info, warn};
+
+use forgeerp_ai::AiError;
+use forgeerp_core::TenantId;
+
+use crate::workers::liveness::TaskBeat;
+
+/// Handle for a running AI runner (shutdown + trigger hook).
+#[derive(Debug)]
+pub struct AiRunnerHandle {
+    shutdown: mpsc::Sender<()>,
+    trigger: mpsc::SyncSender<()>,
+    join: Option<thread::JoinHandle<()>>,
+}
+
+impl AiRunnerHandle {
+    /// Event-trigger hook: call after a successful projection update.
+    ///
+    /// Backpressure: triggers are coalesced (bounded queue). If the runner is already pending,
+    /// this becomes a no-op.
+    pub fn trigger(&self) {
+        // Coalesce: channel capacity=1; ignore if already full.
+        let _ = self.trigger.try_send(());
+    }
+
+    /// Gracefully stop the runner thread.
+    pub fn shutdown(mut self) {
+        let _ = self.shutdown.send(());
+        if let Some(j) = self.join.take() {
+            let _ = j.join();
+        }
+    }
+}
+
+/// When a runner runs and how it retries.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RunnerSchedule {
+    pub interval: Duration,
+    pub max_retries: u32,
+    pub base_backoff: Duration,
+}
+
+/// Spawn the runner thread: `run` is called on startup, every `interval`, and after
+/// triggers, on a runtime owned by the thread.
+pub(crate) fn spawn_runner<F>(
+    name: &'static str,
+    tenant_id: TenantId,
+    schedule: RunnerSchedule,
+    beat: TaskBeat,
+    run: F,
+) -> AiRunnerHandle
+where
+    F: FnMut(&tokio::runtime::Runtime) -> Result<usize, AiError> + Send + 'static,
+{
+    let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>();
+    let (trigger_tx, trigger_rx) = mpsc::sync_channel::<()>(1);
+
+    let join = thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || runner_loop(name, tenant_id, schedule, shutdown_rx, trigger_rx, beat, run))
+        .expect("failed to spawn AI runner thread");
+
+    AiRunnerHandle {
+        shutdown: shutdown_tx,
+        trigger: trigger_tx,
+        join: Some(join),
+    }
+}
+
+fn runner_loop<F>(
+    name: &'static str,
+    tenant_id: TenantId,
+    cfg: RunnerSchedule,
+    shutdown_rx: mpsc::Receiver<()>,
+    trigger_rx: mpsc::Receiver<()>,
+    beat: TaskBeat,
+    mut run: F,
+) where
+    F: FnMut(&tokio::runtime::Runtime) -> Result<usize, AiError>,
+{
+    // Backend calls are async; the runner drives them on its own thread.
+    let rt = match tokio::runtime::Builder::new_current_thread().enable_time().enable_io().build() {
+        Ok(rt) => rt,
+        Err(e) => {
+            warn!(runner = name, tenant = %tenant_id, error = %e, "failed to start AI runner");
+            beat.exited("failed to start runtime");
+            return;
+        }
+    };
+
+    info!(runner = name, tenant = %tenant_id, "AI runner started");
+    let _guard = beat.panic_guard();
+
+    let mut next_tick = Instant::now() + cfg.interval;
+    let mut pending = true; // run once on startup
+    let mut failures: u32 = 0;
+    let mut backoff_until: Option<Instant> = None;
+
+    loop {
+        // Shutdown has priority.
+        if shutdown_rx.try_recv().is_ok() {
+            beat.stopped();
+            break;
+        }
+        beat.beat();
+
+        let now = Instant::now();
+        if now >= next_tick {
+            pending = true;
+            // Keep a stable cadence even if we were delayed.
+            while next_tick <= now {
+                next_tick += cfg.interval;
+            }
+        }
+
+        // Event-trigger: non-blocking drain to coalesce multiple triggers.
+        while trigger_rx.try_recv().is_ok() {
+            pending = true;
+        }
+
+        // Backoff gate.
+        if let Some(until) = backoff_until {
+            if Instant::now() < until {
+                thread::sleep(Duration::from_millis(50));
+                continue;
+            }
+            backoff_until = None;
+        }
+
+        if !pending {
+            // Wait until next tick or trigger or shutdown.
+            let sleep_for = next_tick
+                .saturating_duration_since(Instant::now())
+                .min(Duration::from_millis(250));
+            thread::sleep(sleep_for);
+            continue;
+        }
+
+        pending = false;
+
+        match run(&rt) {
+            Ok(_) => {
+                failures = 0;
+                beat.processed(true);
+            }
+            Err(AiError::RateLimited(reason)) => {
+                // Dropped, not queued: the next trigger or tick tries again.
+                debug!(runner = name, tenant = %tenant_id, reason = %reason, "AI run dropped");
+            }
+            Err(e) => {
+                warn!(runner = name, tenant = %tenant_id, error = ?e, "AI run failed");
+                beat.processed(false);
+                failures += 1;
+                if failures <= cfg.max_retries {
+                    pending = true;
+                    backoff_until = Some(Instant::now() + backoff(cfg.base_backoff, failures));
+                } else {
+                    failures = 0;
+                }
+            }
+        }
+    }
+
+    info!(runner = name, tenant = %tenant_id, "AI runner stopped");
+}
+
+fn backoff(base: Duration, attempt: u32) -> Duration {
+    // Exponential backoff: base * 2^(attempt-1), capped.
+    let pow = 1u32 << attempt.saturating_sub(1).min(10);
+    let ms = base.as_millis().saturating_mul(pow as u128);
+    Duration::from_millis(ms.min(10_000) as u64)
+}
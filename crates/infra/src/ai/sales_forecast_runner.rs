@@ -0,0 +1,201 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use forgeerp_ai::{
+    forecast_insights, AiBackend, AiError, AiUsageMeter, BackendScheduler, ReadModelReader, SalesForecastJob,
+    SalesSnapshot, TenantScope, DEFAULT_BACKEND_TIMEOUT,
+};
+use forgeerp_core::TenantId;
+
+use crate::ai::inventory_anomaly_runner::AiInsightSink;
+use crate::ai::runner::{spawn_runner, AiRunnerHandle, RunnerSchedule};
+use crate::workers::liveness::{TaskBeat, TaskRegistry};
+
+/// Config for the sales demand forecast runner.
+#[derive(Debug, Clone)]
+pub struct SalesForecastRunner {
+    pub interval: Duration,
+    pub max_retries: u32,
+    pub base_backoff: Duration,
+    /// Orders averaged per product.
+    pub window: usize,
+    /// Upper bound for one backend call.
+    pub backend_timeout: Duration,
+    /// Per-tenant rate limit + cost meter (shared across runners); `None` = unmetered.
+    pub meter: Option<Arc<AiUsageMeter>>,
+    /// Where runners report liveness (as `{name}:{tenant_id}`); `None` = unreported.
+    pub liveness: Option<TaskRegistry>,
+}
+
+impl Default for SalesForecastRunner {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(300),
+            max_retries: 5,
+            base_backoff: Duration::from_millis(250),
+            window: 5,
+            backend_timeout: DEFAULT_BACKEND_TIMEOUT,
+            meter: None,
+            liveness: None,
+        }
+    }
+}
+
+/// Handle for a running sales forecast runner.
+pub type SalesForecastRunnerHandle = AiRunnerHandle;
+
+impl SalesForecastRunner {
+    /// Forecast once over the tenant's current sales snapshot and emit one insight per
+    /// product (keyed by product, so each run replaces the previous forecast).
+    ///
+    /// Returns the number of insights emitted.
+    pub async fn run_once<R, S, B>(
+        &self,
+        tenant_id: TenantId,
+        reader: &R,
+        sink: &S,
+        scheduler: &BackendScheduler<B>,
+    ) -> Result<usize, AiError>
+    where
+        R: ReadModelReader<SalesSnapshot> + ?Sized,
+        S: AiInsightSink + ?Sized,
+        B: AiBackend,
+    {
+        let snapshot = reader.get_snapshot(tenant_id)?;
+        let job = SalesForecastJob::new(tenant_id, snapshot).with_window(self.window);
+        let result = scheduler.run(job.to_request()).await?;
+
+        let insights = forecast_insights(&result);
+        let emitted = insights.len();
+        for insight in insights {
+            sink.emit(tenant_id, insight);
+        }
+        Ok(emitted)
+    }
+
+    /// Spawn a tenant-scoped runner (same schedule, trigger and failure isolation as
+    /// [`crate::ai::InventoryAnomalyRunner::spawn_for_tenant`]).
+    pub fn spawn_for_tenant<R, S, B>(
+        &self,
+        name: &'static str,
+        tenant_id: TenantId,
+        reader: Arc<R>,
+        sink: Arc<S>,
+        backend: B,
+    ) -> SalesForecastRunnerHandle
+    where
+        R: ReadModelReader<SalesSnapshot> + 'static,
+        S: AiInsightSink + 'static,
+        B: AiBackend,
+    {
+        let cfg = self.clone();
+        let schedule = RunnerSchedule {
+            interval: self.interval,
+            max_retries: self.max_retries,
+            base_backoff: self.base_backoff,
+        };
+        let beat = match &self.liveness {
+            Some(registry) => registry.register(format!("{name}:{tenant_id}")),
+            None => TaskBeat::detached(name),
+        };
+        let mut scheduler =
+            BackendScheduler::new(TenantScope::Tenant(tenant_id), backend).with_timeout(self.backend_timeout);
+        if let Some(meter) = &self.meter {
+            scheduler = scheduler.with_meter(meter.clone());
+        }
+        spawn_runner(name, tenant_id, schedule, beat, move |rt| {
+            rt.block_on(cfg.run_once(tenant_id, reader.as_ref(), sink.as_ref(), &scheduler))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use forgeerp_ai::{AiRequest, AiResult, LocalAiBackend, ProductDemandSnapshot};
+
+    use super::*;
+    use crate::ai::InMemoryAiInsightSink;
+
+    struct DemandReader(Vec<(&'static str, Vec<i64>)>);
+
+    impl ReadModelReader<SalesSnapshot> for DemandReader {
+        fn get_snapshot(&self, tenant_id: TenantId) -> Result<SalesSnapshot, AiError> {
+            Ok(SalesSnapshot {
+                tenant_id,
+                products: self
+                    .0
+                    .iter()
+                    .map(|(product_id, history)| ProductDemandSnapshot {
+                        product_id: product_id.to_string(),
+                        demand_history: history.clone(),
+                    })
+                    .collect(),
+            })
+        }
+    }
+
+    struct FailingBackend;
+
+    #[async_trait]
+    impl AiBackend for FailingBackend {
+        async fn infer(&self, _request: AiRequest) -> Result<AiResult, AiError> {
+            Err(AiError::InferenceFailed("model service unavailable".to_string()))
+        }
+    }
+
+    fn run<B: AiBackend>(
+        tenant_id: TenantId,
+        reader: &DemandReader,
+        sink: &InMemoryAiInsightSink,
+        backend: B,
+    ) -> Result<usize, AiError> {
+        let runner = SalesForecastRunner {
+            window: 3,
+            ..Default::default()
+        };
+        let scheduler = BackendScheduler::new(TenantScope::Tenant(tenant_id), backend);
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(runner.run_once(tenant_id, reader, sink, &scheduler))
+    }
+
+    #[test]
+    fn forecast_is_the_moving_average_of_recent_orders_per_product() {
+        let tenant_id = TenantId::new();
+        let reader = DemandReader(vec![("bolts", vec![100, 2, 4, 6]), ("nuts", vec![5]), ("washers", vec![])]);
+        let sink = InMemoryAiInsightSink::new();
+
+        assert_eq!(run(tenant_id, &reader, &sink, LocalAiBackend).unwrap(), 2);
+        // Re-running replaces each product's forecast instead of appending.
+        assert_eq!(run(tenant_id, &reader, &sink, LocalAiBackend).unwrap(), 2);
+
+        let forecasts: Vec<_> = sink
+            .all()
+            .into_iter()
+            .filter_map(|(_, r)| r.as_sales_forecasts())
+            .flatten()
+            .map(|f| (f.product_id, f.forecast, f.observations))
+            .collect();
+        assert_eq!(
+            forecasts,
+            vec![("bolts".to_string(), 4.0, 3), ("nuts".to_string(), 5.0, 1)]
+        );
+        assert_eq!(sink.all()[0].1.idempotency_key.as_deref(), Some("sales.forecast:bolts"));
+    }
+
+    #[test]
+    fn failing_backend_emits_nothing() {
+        let tenant_id = TenantId::new();
+        let reader = DemandReader(vec![("bolts", vec![1, 2, 3])]);
+        let sink = InMemoryAiInsightSink::new();
+
+        assert!(matches!(
+            run(tenant_id, &reader, &sink, FailingBackend),
+            Err(AiError::InferenceFailed(_))
+        ));
+        assert!(sink.all().is_empty());
+    }
+}
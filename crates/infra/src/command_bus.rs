@@ -0,0 +1,265 @@
+//! Typed command bus: routes commands to registered aggregate handlers.
+//!
+//! `CommandDispatcher` knows how to run one aggregate's lifecycle; callers still had
+//! to pick the aggregate type, factory, and permissions for every command by hand.
+//! The bus keeps that wiring in one place:
+//!
+//! ```text
+//! send(principal, cmd)
+//!   ↓
+//! 1. Look up the route registered for the command's Rust type
+//!   ↓
+//! 2. Authorize the principal for the route's required permissions
+//!   ↓
+//! 3. Dispatch to the route's aggregate (tenant = principal's active tenant)
+//! ```
+//!
+//! Routes are keyed by command type, so each command enum is registered once.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use thiserror::Error;
+
+use forgeerp_auth::{authorize, AuthzError, Permission, Principal};
+use forgeerp_core::{Aggregate, AggregateId, DomainError, TenantId};
+use forgeerp_events::{Command, EventBus, EventEnvelope};
+
+use crate::command_dispatcher::{CommandDispatcher, DispatchError};
+use crate::event_store::{EventStore, StoredEvent};
+
+#[derive(Debug, Error)]
+pub enum CommandBusError {
+    /// No route registered for the command type.
+    #[error("no handler registered for command type `{0}`")]
+    NoHandler(&'static str),
+    /// The principal lacks a permission required by the route.
+    #[error("unauthorized: {0}")]
+    Unauthorized(AuthzError),
+    /// The routed dispatch failed.
+    #[error("dispatch failed: {0:?}")]
+    Dispatch(DispatchError),
+}
+
+/// Registration for one aggregate's command type.
+pub struct AggregateRoute<A: Aggregate> {
+    /// Aggregate type string stored on the stream (e.g. `inventory.item`).
+    pub aggregate_type: &'static str,
+    /// Factory for an empty aggregate to rehydrate into.
+    pub make_aggregate: fn(TenantId, AggregateId) -> A,
+    /// Permissions required to send a given command.
+    pub permissions: fn(&A::Command) -> Vec<Permission>,
+}
+
+type HandlerFn<C> = Box<dyn Fn(TenantId, C) -> Result<Vec<StoredEvent>, DispatchError> + Send + Sync>;
+type PermissionsFn<C> = Box<dyn Fn(&C) -> Vec<Permission> + Send + Sync>;
+
+struct Route<C> {
+    permissions: PermissionsFn<C>,
+    handler: HandlerFn<C>,
+}
+
+/// Command bus mapping command types to aggregate routes.
+#[derive(Default)]
+pub struct CommandBus {
+    routes: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl CommandBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the route for `A::Command`, dispatched through `dispatcher`.
+    ///
+    /// Registering the same command type again replaces the previous route.
+    pub fn register<A, S, B>(mut self, dispatcher: Arc<CommandDispatcher<S, B>>, route: AggregateRoute<A>) -> Self
+    where
+        A: Aggregate<Error = DomainError> + Serialize + DeserializeOwned + 'static,
+        A::Command: Command,
+        A::Event: forgeerp_events::Event + Serialize + DeserializeOwned,
+        S: EventStore + Send + Sync + 'static,
+        B: EventBus<EventEnvelope<JsonValue>> + Send + Sync + 'static,
+    {
+        let AggregateRoute {
+            aggregate_type,
+            make_aggregate,
+            permissions,
+        } = route;
+
+        let handler: HandlerFn<A::Command> = Box::new(move |tenant_id, command: A::Command| {
+            dispatcher.dispatch::<A>(
+                tenant_id,
+                command.target_aggregate_id(),
+                aggregate_type,
+                command,
+                make_aggregate,
+            )
+        });
+
+        self.routes.insert(
+            TypeId::of::<A::Command>(),
+            Box::new(Route {
+                permissions: Box::new(permissions),
+                handler,
+            }),
+        );
+        self
+    }
+
+    /// Whether a route is registered for `C`.
+    pub fn handles<C: Command>(&self) -> bool {
+        self.routes.contains_key(&TypeId::of::<C>())
+    }
+
+    /// Authorize and dispatch a command within the principal's active tenant.
+    pub fn send<C: Command>(&self, principal: &Principal, command: C) -> Result<Vec<StoredEvent>, CommandBusError> {
+        let route = self
+            .routes
+            .get(&TypeId::of::<C>())
+            .and_then(|r| r.downcast_ref::<Route<C>>())
+            .ok_or(CommandBusError::NoHandler(std::any::type_name::<C>()))?;
+
+        for perm in (route.permissions)(&command) {
+            authorize(principal, &perm).map_err(CommandBusError::Unauthorized)?;
+        }
+
+        (route.handler)(principal.active_tenant_id, command).map_err(CommandBusError::Dispatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use forgeerp_auth::{PrincipalId, TenantMembership};
+    use forgeerp_events::InMemoryEventBus;
+    use forgeerp_inventory::{AdjustStock, CreateItem, DEFAULT_LOCATION, InventoryCommand, InventoryItem, InventoryItemId};
+
+    use super::*;
+    use crate::event_store::InMemoryEventStore;
+
+    fn inventory_permissions(cmd: &InventoryCommand) -> Vec<Permission> {
+        match cmd {
+            InventoryCommand::CreateItem(_) | InventoryCommand::RenameItem(_) | InventoryCommand::SetReorderPoint(_) => {
+                vec![Permission::new("inventory.items.create")]
+            }
+            InventoryCommand::AdjustStock(_) | InventoryCommand::TransferStock(_) => {
+                vec![Permission::new("inventory.items.adjust")]
+            }
+            InventoryCommand::ReserveStock(_) | InventoryCommand::ReleaseReservation(_) => {
+                vec![Permission::new("inventory.items.reserve")]
+            }
+        }
+    }
+
+    fn setup() -> (Arc<InMemoryEventStore>, CommandBus) {
+        let store = Arc::new(InMemoryEventStore::new());
+        let bus: Arc<InMemoryEventBus<EventEnvelope<JsonValue>>> = Arc::new(InMemoryEventBus::new());
+        let dispatcher = Arc::new(CommandDispatcher::new(store.clone(), bus));
+        let command_bus = CommandBus::new().register(
+            dispatcher,
+            AggregateRoute::<InventoryItem> {
+                aggregate_type: "inventory.item",
+                make_aggregate: |_, id| InventoryItem::empty(InventoryItemId::new(id)),
+                permissions: inventory_permissions,
+            },
+        );
+        (store, command_bus)
+    }
+
+    fn principal(tenant_id: TenantId, permissions: &[&'static str]) -> Principal {
+        Principal {
+            principal_id: PrincipalId::new(),
+            active_tenant_id: tenant_id,
+            membership: TenantMembership {
+                tenant_id,
+                roles: vec![],
+                permissions: permissions.iter().map(|p| Permission::new(*p)).collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn create_and_adjust_are_routed_end_to_end() {
+        let (store, command_bus) = setup();
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+        let principal = principal(tenant_id, &["inventory.items.create", "inventory.items.adjust"]);
+
+        command_bus
+            .send(
+                &principal,
+                InventoryCommand::CreateItem(CreateItem {
+                    tenant_id,
+                    item_id,
+                    name: "Widget".to_string(),
+                    occurred_at: Utc::now(),
+                }),
+            )
+            .unwrap();
+        let committed = command_bus
+            .send(
+                &principal,
+                InventoryCommand::AdjustStock(AdjustStock {
+                    tenant_id,
+                    item_id,
+                    location_id: DEFAULT_LOCATION.to_string(),
+                    delta: 7,
+                    occurred_at: Utc::now(),
+                }),
+            )
+            .unwrap();
+        assert_eq!(committed[0].sequence_number, 2);
+
+        let stream = store.load_stream(tenant_id, item_id.0).unwrap();
+        assert_eq!(stream.len(), 2);
+        assert!(stream.iter().all(|e| e.aggregate_type == "inventory.item"));
+        assert_eq!(stream[1].event_type, "inventory.item.stock_adjusted");
+    }
+
+    #[test]
+    fn missing_permission_is_rejected_before_dispatch() {
+        let (store, command_bus) = setup();
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+
+        let err = command_bus
+            .send(
+                &principal(tenant_id, &["inventory.items.adjust"]),
+                InventoryCommand::CreateItem(CreateItem {
+                    tenant_id,
+                    item_id,
+                    name: "Widget".to_string(),
+                    occurred_at: Utc::now(),
+                }),
+            )
+            .unwrap_err();
+        assert!(matches!(err, CommandBusError::Unauthorized(_)));
+        assert!(store.load_stream(tenant_id, item_id.0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn unregistered_command_type_has_no_handler() {
+        #[derive(Debug, Clone)]
+        struct Unrouted(AggregateId);
+
+        impl Command for Unrouted {
+            fn target_aggregate_id(&self) -> AggregateId {
+                self.0
+            }
+        }
+
+        let (_, command_bus) = setup();
+        assert!(command_bus.handles::<InventoryCommand>());
+        assert!(!command_bus.handles::<Unrouted>());
+        let err = command_bus
+            .send(&principal(TenantId::new(), &["*"]), Unrouted(AggregateId::new()))
+            .unwrap_err();
+        assert!(matches!(err, CommandBusError::NoHandler(_)));
+    }
+}
@@ -45,17 +45,128 @@
 //! - **Tenant-aware**: All operations are scoped to a tenant ID
 //! - **Failure handling**: Maps domain errors, store errors, and bus errors consistently
 //!
+//! ## Conflict Retries
+//!
+//! Optimistic-concurrency conflicts on append are retried (reload → rehydrate → decide →
+//! append) up to the dispatcher's [`RetryPolicy`] budget. Each dispatch produces a
+//! [`RetryReport`] (attempts made, whether the budget ran out) that callers can surface
+//! to clients, either from [`CommandDispatcher::dispatch_with_report`] or via an observer.
+//! [`CommandDispatcher::dispatch_with_retry`] runs one command under its own budget and
+//! backoff instead. Every retry handles the command again against the reloaded state, so
+//! a command the winning writer made invalid fails with its domain error, not a conflict.
+//!
+//! ## Envelope Metadata
+//!
+//! Request-level facts (e.g. a cross-tenant admin override) are attached to every event
+//! dispatched inside [`with_envelope_metadata`], without threading them through each call.
+//! Every appended event also records the [`PRODUCER_VERSION`] that wrote it and its
+//! payload schema fingerprint, to trace malformed events back to a deploy. A
+//! [`BUSINESS_KEY`](forgeerp_events::BUSINESS_KEY) entry groups the events of one business
+//! process across aggregates (see `EventFilter::business_key`).
+//!
+//! ## Correlation
+//!
+//! Every dispatched event records a correlation id (shared by all events of one flow)
+//! and a causation id (the command that produced it). A top-level dispatch starts a new
+//! flow under a fresh command id; inside [`with_correlation_of`] (saga reactions) the
+//! command joins the triggering event's flow and is recorded as caused by that event.
+//!
+//! ## Upcasting
+//!
+//! Stored payloads are upgraded through the dispatcher's
+//! [`UpcasterRegistry`](forgeerp_events::UpcasterRegistry) (see
+//! [`CommandDispatcher::with_upcasters`]) before they are deserialized for rehydration,
+//! so aggregates only ever see the current shape of each event.
+//!
+//! ## Snapshots
+//!
+//! With a [`SnapshotPolicy`] the dispatcher rehydrates from the aggregate's latest
+//! [`Snapshot`] (its serde-serialized state) and replays only the events after it, and
+//! keeps a new snapshot once `every` events were appended since the last one. A snapshot
+//! the aggregate no longer deserializes is ignored in favour of a full replay.
+//!
+//! ## Stream Guards
+//!
+//! A [`StreamGuard`] registered for an aggregate type caps how many events one aggregate
+//! may append per time window and how long its stream may grow, so a misbehaving client
+//! cannot produce a stream that makes rehydration pathological.
+//!
+//! ## Command Enrichment
+//!
+//! A [`CommandEnricher`](crate::enrichment::CommandEnricher) registered for a command type
+//! fills server-owned fields (time from the [`Clock`], ids from the
+//! [`IdGenerator`](crate::enrichment::IdGenerator), tenant defaults) before the command is
+//! handled, so handlers submit only what the client decided.
+//!
+//! ## Timestamp Policy
+//!
+//! Commands carry a client-supplied `occurred_at` that ends up on every event. With a
+//! [`TimestampPolicy`], events dated outside a window around the injected [`Clock`]'s
+//! time are either rejected or re-dated to server time, keeping the client's value in
+//! the [`CLIENT_TIMESTAMP_KEY`] metadata entry.
+//!
+//! ## Concurrency Limit
+//!
+//! A [`ConcurrencyLimit`] bounds how many dispatches run at once, overall and per
+//! tenant, so a burst degrades into queued or rejected commands
+//! (`DispatchError::Overloaded`) instead of exhausting the store's connections. The
+//! per-tenant cap keeps one tenant from taking every slot.
+//!
+//! ## Idempotency Keys
+//!
+//! With an [`IdempotencyLog`] attached, a dispatch under an idempotency key (see
+//! [`CommandDispatcher::dispatch_idempotent`] and [`with_idempotency_key_sync`]) records
+//! the events it committed; a repeat with the same key returns those events instead of
+//! handling the command again, and a different request under the same key is rejected
+//! with `DispatchError::Validation`. A repeat arriving while the first dispatch still runs
+//! is refused with `DispatchError::Concurrency`.
+//!
+//! ## Rejected Commands
+//!
+//! With a [`RejectedCommandLog`] attached, commands the domain rejects (validation or
+//! invariant failures) are logged with their type, tenant, error and the principal of
+//! the enclosing [`with_command_principal`] scope. Nothing reaches the event stream.
+//!
+//! ## Publish Failures
+//!
+//! Events are appended before they are published. If the bus fails (or a
+//! [`GuardedEventBus`](crate::event_bus::GuardedEventBus) refuses or times out the
+//! publish), the dispatch returns `DispatchError::Publish` carrying the committed events
+//! so they can be relayed later.
+//!
+//! ## Dry Runs
+//!
+//! [`CommandDispatcher::dispatch_preview`] runs the pipeline up to the decision (load,
+//! rehydrate, handle, timestamp policy) and returns the would-be events as a
+//! [`DispatchPreview`] without appending or publishing. Inside [`with_dry_run`], every
+//! dispatch behaves that way, so request handlers can be previewed unchanged.
+//!
 //! This module contains no IO itself; it composes infrastructure traits.
 
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
-use forgeerp_core::{Aggregate, AggregateId, DomainError, ExpectedVersion, TenantId};
-use forgeerp_events::{EventBus, EventEnvelope};
+use forgeerp_auth::PrincipalId;
+use forgeerp_core::{Aggregate, AggregateId, DomainError, ErrorCode, ExpectedVersion, TenantId, VersionedCommand};
+use forgeerp_events::{
+    CorrelationContext, EventBus, EventEnvelope, UpcasterRegistry, BUSINESS_KEY, PRODUCER_VERSION_KEY,
+    SCHEMA_FINGERPRINT_KEY,
+};
 
-use crate::event_store::{EventStore, EventStoreError, StoredEvent, UncommittedEvent};
+use crate::clock::{Clock, SystemClock};
+use crate::enrichment::{CommandEnricher, EnrichContext, Enrichers, IdGenerator, UuidV7Ids};
+use crate::event_store::{EventStore, EventStoreError, Snapshot, StoredEvent, UncommittedEvent};
+use crate::idempotency::{fingerprint_of, IdempotencyClaim, IdempotencyKey, IdempotencyLog, IdempotencyRecord};
+use crate::rejected_commands::{command_type_name, RejectedCommandLog};
 
 #[derive(Debug)]
 pub enum DispatchError {
@@ -76,7 +187,20 @@ pub enum DispatchError {
     /// Persisting to the event store failed.
     Store(EventStoreError),
     /// Publication failed after a successful append (at-least-once; retry may duplicate).
-    Publish(String),
+    /// Carries the committed events so they can be relayed without appending them again.
+    Publish {
+        committed: Vec<StoredEvent>,
+        reason: String,
+    },
+    /// The aggregate exceeded its [`StreamGuard`]; nothing was appended.
+    StreamLimit(String),
+    /// The stream moved past the version a [`VersionedCommand`] was based on (stale edit).
+    PreconditionFailed(String),
+    /// No dispatch slot was free under the [`ConcurrencyLimit`]; nothing was attempted.
+    Overloaded(String),
+    /// A domain rejection tagged with the [`ErrorCode`] of the rule that raised it; `error`
+    /// is what it maps to otherwise.
+    Rejected { code: ErrorCode, error: Box<DispatchError> },
 }
 
 impl From<EventStoreError> for DispatchError {
@@ -84,7 +208,28 @@ impl From<EventStoreError> for DispatchError {
         match &value {
             EventStoreError::Concurrency(msg) => DispatchError::Concurrency(msg.clone()),
             EventStoreError::TenantIsolation(msg) => DispatchError::TenantIsolation(msg.clone()),
-            _ => DispatchError::Store(value),
+            _ => match value {
+                EventStoreError::Publish { committed, reason } => DispatchError::Publish { committed, reason },
+                other => DispatchError::Store(other),
+            },
+        }
+    }
+}
+
+impl DispatchError {
+    /// Code of the domain rule that rejected the command, if it has one.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            DispatchError::Rejected { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Events persisted by a dispatch whose publication failed.
+    pub fn committed(&self) -> Option<&[StoredEvent]> {
+        match self {
+            DispatchError::Publish { committed, .. } => Some(committed),
+            _ => None,
         }
     }
 }
@@ -98,10 +243,355 @@ impl From<DomainError> for DispatchError {
             DomainError::Unauthorized => DispatchError::Unauthorized,
             DomainError::NotFound => DispatchError::NotFound,
             DomainError::InvalidId(msg) => DispatchError::Validation(msg),
+            DomainError::Coded { code, error } => DispatchError::Rejected {
+                code,
+                error: Box::new((*error).into()),
+            },
+        }
+    }
+}
+
+/// Retry budget for optimistic-concurrency conflicts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts per dispatch, including the first (minimum 1).
+    pub max_attempts: u32,
+    /// Pause before the first retry, doubled before each further one (zero retries at once).
+    pub backoff: std::time::Duration,
+}
+
+impl Default for RetryPolicy {
+    /// No retries: conflicts are returned to the caller immediately.
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff: std::time::Duration::ZERO,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Pause before retrying after `attempts` failed attempts.
+    fn pause_after(&self, attempts: u32) -> std::time::Duration {
+        self.backoff.saturating_mul(1 << attempts.saturating_sub(1).min(16))
+    }
+}
+
+/// How much retry effort one dispatch spent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RetryReport {
+    /// Attempts made (1 = succeeded or failed without retrying).
+    pub attempts: u32,
+    pub max_attempts: u32,
+    /// The dispatch failed with a conflict after using the whole budget.
+    pub exhausted: bool,
+}
+
+/// How often aggregate state is snapshotted.
+///
+/// Registered with [`CommandDispatcher::with_snapshot_policy`]; without one, aggregates
+/// are rehydrated by replaying their full stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotPolicy {
+    /// Keep a snapshot once this many events were appended since the last one (minimum 1).
+    pub every: u64,
+}
+
+impl Default for SnapshotPolicy {
+    fn default() -> Self {
+        Self { every: 100 }
+    }
+}
+
+/// Limits on how fast and how far one aggregate's stream may grow.
+///
+/// Registered per aggregate type with [`CommandDispatcher::with_stream_guard`]; unset
+/// limits are not enforced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamGuard {
+    /// Max events appended to one aggregate within `window`.
+    pub max_events_per_window: Option<u32>,
+    pub window: Duration,
+    /// Max events in one aggregate's stream (as loaded for rehydration).
+    pub max_stream_length: Option<u64>,
+}
+
+impl Default for StreamGuard {
+    fn default() -> Self {
+        Self {
+            max_events_per_window: None,
+            window: Duration::from_secs(60),
+            max_stream_length: None,
+        }
+    }
+}
+
+/// What a dispatch does when the [`ConcurrencyLimit`] has no free slot for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverloadAction {
+    /// Fail immediately with `DispatchError::Overloaded`.
+    #[default]
+    Reject,
+    /// Wait up to the given time for a slot, then fail with `DispatchError::Overloaded`.
+    Queue(Duration),
+}
+
+/// Bound on dispatches in flight at once.
+///
+/// Registered with [`CommandDispatcher::with_concurrency_limit`]; without one, dispatch
+/// concurrency is unbounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyLimit {
+    /// Max dispatches in flight across all tenants (minimum 1).
+    pub max_in_flight: usize,
+    /// Max dispatches in flight for one tenant, so no tenant can take every slot.
+    pub max_per_tenant: Option<usize>,
+    pub overload: OverloadAction,
+}
+
+impl ConcurrencyLimit {
+    /// Read `DISPATCH_MAX_IN_FLIGHT` (unset disables the limit),
+    /// `DISPATCH_MAX_IN_FLIGHT_PER_TENANT` and `DISPATCH_QUEUE_TIMEOUT_MS`
+    /// (queue for up to that long; unset or 0 rejects immediately).
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.trim().parse::<u64>().ok());
+        let max_in_flight = var("DISPATCH_MAX_IN_FLIGHT").filter(|n| *n > 0)?;
+        Some(Self {
+            max_in_flight: max_in_flight as usize,
+            max_per_tenant: var("DISPATCH_MAX_IN_FLIGHT_PER_TENANT")
+                .filter(|n| *n > 0)
+                .map(|n| n as usize),
+            overload: match var("DISPATCH_QUEUE_TIMEOUT_MS") {
+                Some(ms) if ms > 0 => OverloadAction::Queue(Duration::from_millis(ms)),
+                _ => OverloadAction::Reject,
+            },
+        })
+    }
+}
+
+/// Slots taken under a [`ConcurrencyLimit`].
+#[derive(Debug, Default)]
+struct Slots {
+    total: usize,
+    by_tenant: HashMap<TenantId, usize>,
+}
+
+#[derive(Debug)]
+struct DispatchLimiter {
+    limit: ConcurrencyLimit,
+    slots: Mutex<Slots>,
+    freed: Condvar,
+}
+
+/// A taken dispatch slot; freed on drop.
+struct DispatchPermit<'a> {
+    limiter: &'a DispatchLimiter,
+    tenant_id: TenantId,
+}
+
+impl DispatchLimiter {
+    fn new(limit: ConcurrencyLimit) -> Self {
+        Self {
+            limit,
+            slots: Mutex::new(Slots::default()),
+            freed: Condvar::new(),
+        }
+    }
+
+    fn has_room(&self, slots: &Slots, tenant_id: TenantId) -> bool {
+        slots.total < self.limit.max_in_flight.max(1)
+            && self
+                .limit
+                .max_per_tenant
+                .is_none_or(|max| slots.by_tenant.get(&tenant_id).copied().unwrap_or(0) < max.max(1))
+    }
+
+    fn acquire(&self, tenant_id: TenantId) -> Result<DispatchPermit<'_>, DispatchError> {
+        let mut slots = self.slots.lock().expect("dispatch limiter lock poisoned");
+        if !self.has_room(&slots, tenant_id) {
+            let OverloadAction::Queue(timeout) = self.limit.overload else {
+                return Err(self.overloaded(tenant_id));
+            };
+            let (waited, result) = off_runtime(|| {
+                self.freed
+                    .wait_timeout_while(slots, timeout, |slots| !self.has_room(slots, tenant_id))
+                    .expect("dispatch limiter lock poisoned")
+            });
+            if result.timed_out() {
+                return Err(self.overloaded(tenant_id));
+            }
+            slots = waited;
+        }
+        slots.total += 1;
+        *slots.by_tenant.entry(tenant_id).or_default() += 1;
+        Ok(DispatchPermit {
+            limiter: self,
+            tenant_id,
+        })
+    }
+
+    fn overloaded(&self, tenant_id: TenantId) -> DispatchError {
+        DispatchError::Overloaded(format!(
+            "too many commands in flight (limit {} overall, {} per tenant) for tenant {tenant_id}; retry later",
+            self.limit.max_in_flight,
+            self.limit
+                .max_per_tenant
+                .map_or_else(|| "no limit".to_string(), |n| n.to_string())
+        ))
+    }
+}
+
+/// An idempotency key claimed for a running dispatch; released on drop unless the
+/// dispatch was recorded (also when it fails or panics).
+struct ClaimedKey<'a> {
+    log: &'a IdempotencyLog,
+    tenant_id: TenantId,
+    key: Uuid,
+}
+
+impl Drop for ClaimedKey<'_> {
+    fn drop(&mut self) {
+        self.log.release(self.tenant_id, self.key);
+    }
+}
+
+/// Run a blocking wait without stalling the async runtime.
+///
+/// Handlers dispatch inline, so on a multi-threaded runtime worker the worker's other
+/// tasks are handed to another thread while `f` blocks (`block_in_place`). Unlike
+/// `spawn_blocking`, this keeps the request's task-local scopes (dry run, principal,
+/// envelope metadata). Elsewhere `f` simply runs.
+fn off_runtime<R>(f: impl FnOnce() -> R) -> R {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(f)
         }
+        _ => f(),
     }
 }
 
+impl Drop for DispatchPermit<'_> {
+    fn drop(&mut self) {
+        let mut slots = self.limiter.slots.lock().expect("dispatch limiter lock poisoned");
+        slots.total -= 1;
+        if let Some(count) = slots.by_tenant.get_mut(&self.tenant_id) {
+            *count -= 1;
+            if *count == 0 {
+                slots.by_tenant.remove(&self.tenant_id);
+            }
+        }
+        drop(slots);
+        self.limiter.freed.notify_all();
+    }
+}
+
+/// What to do with an event whose `occurred_at` is outside the accepted window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampAction {
+    /// Fail the command with `DispatchError::Validation`.
+    #[default]
+    Reject,
+    /// Re-date the event to server time, keeping the client value as metadata.
+    Override,
+}
+
+/// Accepted distance between a command's `occurred_at` and server time.
+///
+/// Registered with [`CommandDispatcher::with_timestamp_policy`]; without one, client
+/// timestamps are stored as given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampPolicy {
+    /// How far `occurred_at` may lie ahead of server time (clock skew).
+    pub max_future: Duration,
+    /// How far `occurred_at` may lie behind server time (backdating).
+    pub max_past: Duration,
+    pub action: TimestampAction,
+}
+
+impl Default for TimestampPolicy {
+    fn default() -> Self {
+        Self {
+            max_future: Duration::from_secs(5 * 60),
+            max_past: Duration::from_secs(30 * 24 * 60 * 60),
+            action: TimestampAction::Reject,
+        }
+    }
+}
+
+impl TimestampPolicy {
+    /// Read overrides from `COMMAND_TIMESTAMP_MAX_FUTURE_SECS`,
+    /// `COMMAND_TIMESTAMP_MAX_PAST_SECS` and `COMMAND_TIMESTAMP_ACTION` (`reject` | `override`).
+    pub fn from_env() -> Self {
+        let secs = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(Duration::from_secs)
+        };
+        let default = Self::default();
+        Self {
+            max_future: secs("COMMAND_TIMESTAMP_MAX_FUTURE_SECS").unwrap_or(default.max_future),
+            max_past: secs("COMMAND_TIMESTAMP_MAX_PAST_SECS").unwrap_or(default.max_past),
+            action: match std::env::var("COMMAND_TIMESTAMP_ACTION").as_deref() {
+                Ok("override") => TimestampAction::Override,
+                _ => default.action,
+            },
+        }
+    }
+
+    /// `occurred_at` lies within the window around `now`.
+    pub fn accepts(&self, occurred_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        let within = |limit: Duration, delta: chrono::Duration| {
+            delta.to_std().map(|d| d <= limit).unwrap_or(true)
+        };
+        within(self.max_future, occurred_at - now) && within(self.max_past, now - occurred_at)
+    }
+}
+
+/// Metadata key holding the client's `occurred_at` when the policy re-dated an event.
+pub const CLIENT_TIMESTAMP_KEY: &str = "client_timestamp";
+
+/// Callback invoked with the report of every dispatch.
+pub type RetryObserver = Arc<dyn Fn(&RetryReport) + Send + Sync>;
+
+/// What a command would do if dispatched; nothing was appended or published.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DispatchPreview {
+    /// Would-be events, numbered from the stream's current version (provisional).
+    pub events: Vec<StoredEvent>,
+    /// Things the client may want to know before committing (no-op, re-dated events).
+    pub warnings: Vec<String>,
+}
+
+/// Failure of a single attempt; only append conflicts are worth retrying.
+enum AttemptError {
+    Conflict(DispatchError),
+    Fatal(DispatchError),
+}
+
+impl<E: Into<DispatchError>> From<E> for AttemptError {
+    fn from(value: E) -> Self {
+        AttemptError::Fatal(value.into())
+    }
+}
+
+/// An aggregate rebuilt from its latest snapshot (if any) and the events after it.
+struct Rehydrated<A> {
+    aggregate: A,
+    /// Stream version the aggregate reflects.
+    version: u64,
+    /// Version of the snapshot it started from (0 for a full replay).
+    snapshot_version: u64,
+}
+
+/// Would-be events of a command and the stream version they were decided against, plus
+/// the snapshot to keep once they are committed (when one is due).
+struct Decision {
+    events: Vec<UncommittedEvent>,
+    version: u64,
+    snapshot: Option<Snapshot>,
+}
+
 /// Reusable command execution engine for event-sourced aggregates.
 ///
 /// `CommandDispatcher` orchestrates the full event-sourcing pipeline: loading events,
@@ -123,9 +613,10 @@ impl From<DomainError> for DispatchError {
 /// ## Error Semantics
 ///
 /// - **Domain errors**: Validation failures, invariant violations → `DispatchError::Validation` / `InvariantViolation`
+/// - **Coded domain errors**: the same, wrapped in `DispatchError::Rejected` with the rule's `ErrorCode`
 /// - **Concurrency errors**: Version mismatch → `DispatchError::Concurrency`
 /// - **Tenant errors**: Cross-tenant access → `DispatchError::TenantIsolation`
-/// - **Bus errors**: Publication failures → `DispatchError::Publish` (events are persisted, but publication failed)
+/// - **Bus errors**: Publication failures → `DispatchError::Publish` (events are persisted and handed back, but publication failed)
 ///
 /// ## At-Least-Once Delivery
 ///
@@ -149,15 +640,255 @@ impl From<DomainError> for DispatchError {
 /// - **Deterministic**: Same events produce same state (required for replay)
 /// - **Side-effect free**: No IO, no external state (pure functions only)
 /// - **Version-aware**: Track version in `apply()` for optimistic concurrency
-#[derive(Debug)]
 pub struct CommandDispatcher<S, B> {
     store: S,
     bus: B,
+    retry_policy: RetryPolicy,
+    retry_observer: Option<RetryObserver>,
+    stream_guards: HashMap<String, StreamGuard>,
+    timestamp_policy: Option<TimestampPolicy>,
+    clock: Arc<dyn Clock>,
+    enrichers: Enrichers,
+    ids: Arc<dyn IdGenerator>,
+    /// Append times per guarded aggregate, oldest first (only within the guard window).
+    recent_appends: Mutex<HashMap<(TenantId, AggregateId), VecDeque<Instant>>>,
+    limiter: Option<DispatchLimiter>,
+    rejected_log: Option<Arc<RejectedCommandLog>>,
+    upcasters: Arc<UpcasterRegistry>,
+    snapshot_policy: Option<SnapshotPolicy>,
+    idempotency: Option<Arc<IdempotencyLog>>,
+}
+
+impl<S: std::fmt::Debug, B: std::fmt::Debug> std::fmt::Debug for CommandDispatcher<S, B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommandDispatcher")
+            .field("store", &self.store)
+            .field("bus", &self.bus)
+            .field("retry_policy", &self.retry_policy)
+            .field("stream_guards", &self.stream_guards)
+            .field("timestamp_policy", &self.timestamp_policy)
+            .field("concurrency_limit", &self.limiter.as_ref().map(|l| l.limit))
+            .field("rejected_command_log", &self.rejected_log)
+            .field("upcasters", &self.upcasters)
+            .field("snapshot_policy", &self.snapshot_policy)
+            .field("idempotency_log", &self.idempotency)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<S, B> CommandDispatcher<S, B> {
     pub fn new(store: S, bus: B) -> Self {
-        Self { store, bus }
+        Self {
+            store,
+            bus,
+            retry_policy: RetryPolicy::default(),
+            retry_observer: None,
+            stream_guards: HashMap::new(),
+            timestamp_policy: None,
+            clock: Arc::new(SystemClock),
+            enrichers: Enrichers::default(),
+            ids: Arc::new(UuidV7Ids),
+            recent_appends: Mutex::new(HashMap::new()),
+            limiter: None,
+            rejected_log: None,
+            upcasters: Arc::new(UpcasterRegistry::new()),
+            snapshot_policy: None,
+            idempotency: None,
+        }
+    }
+
+    /// Retry append conflicts up to `policy.max_attempts` times per dispatch.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Invoke `observer` with the [`RetryReport`] of every dispatch.
+    pub fn with_retry_observer(mut self, observer: RetryObserver) -> Self {
+        self.retry_observer = Some(observer);
+        self
+    }
+
+    /// Enforce `guard` on every aggregate of `aggregate_type`.
+    pub fn with_stream_guard(mut self, aggregate_type: impl Into<String>, guard: StreamGuard) -> Self {
+        self.stream_guards.insert(aggregate_type.into(), guard);
+        self
+    }
+
+    /// Check every event's `occurred_at` against `policy`.
+    pub fn with_timestamp_policy(mut self, policy: TimestampPolicy) -> Self {
+        self.timestamp_policy = Some(policy);
+        self
+    }
+
+    /// Bound how many dispatches run at once (overall and per tenant).
+    pub fn with_concurrency_limit(mut self, limit: ConcurrencyLimit) -> Self {
+        self.limiter = Some(DispatchLimiter::new(limit));
+        self
+    }
+
+    /// Keep commands the domain rejects (validation/invariant failures) in `log`.
+    pub fn with_rejected_command_log(mut self, log: Arc<RejectedCommandLog>) -> Self {
+        self.rejected_log = Some(log);
+        self
+    }
+
+    /// Upgrade stored payloads with `upcasters` before rehydrating aggregates from them.
+    pub fn with_upcasters(mut self, upcasters: Arc<UpcasterRegistry>) -> Self {
+        self.upcasters = upcasters;
+        self
+    }
+
+    /// Rehydrate from snapshots and keep new ones according to `policy`.
+    pub fn with_snapshot_policy(mut self, policy: SnapshotPolicy) -> Self {
+        self.snapshot_policy = Some(policy);
+        self
+    }
+
+    /// Honour idempotency keys, recording what each key's dispatch committed in `log`.
+    pub fn with_idempotency_log(mut self, log: Arc<IdempotencyLog>) -> Self {
+        self.idempotency = Some(log);
+        self
+    }
+
+    /// The log, key and request fingerprint a dispatch of `command` is checked against
+    /// (real dispatches under an idempotency key only).
+    fn idempotency_of<C: std::fmt::Debug>(
+        &self,
+        aggregate_type: &str,
+        aggregate_id: AggregateId,
+        command: &C,
+    ) -> Option<(&IdempotencyLog, Uuid, u64)> {
+        let log = self.idempotency.as_deref().filter(|_| !is_dry_run())?;
+        let key = current_idempotency_key()?;
+        let fingerprint = key
+            .fingerprint
+            .unwrap_or_else(|| fingerprint_of(&(aggregate_type, aggregate_id, command)));
+        Some((log, key.key, fingerprint))
+    }
+
+    /// Note `error` in the rejected-command log (real dispatches only, not dry runs).
+    fn note_rejection<C: std::fmt::Debug>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_type: &str,
+        aggregate_id: AggregateId,
+        command: &C,
+        error: &DispatchError,
+    ) {
+        if let Some(log) = self.rejected_log.as_ref().filter(|_| !is_dry_run()) {
+            let command_type = command_type_name(command);
+            log.record(tenant_id, aggregate_type, aggregate_id, command_type, current_command_principal(), error);
+        }
+    }
+
+    /// Take a dispatch slot for `tenant_id` (always granted without a limit).
+    fn admit(&self, tenant_id: TenantId) -> Result<Option<DispatchPermit<'_>>, DispatchError> {
+        self.limiter.as_ref().map(|l| l.acquire(tenant_id)).transpose()
+    }
+
+    /// Time source for the timestamp policy (defaults to the system clock).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Fill server-owned fields of every `C` command before it is handled.
+    pub fn with_enricher<C: 'static>(mut self, enricher: impl CommandEnricher<C> + 'static) -> Self {
+        self.enrichers.insert(enricher);
+        self
+    }
+
+    /// Id source handed to enrichers (defaults to UUIDv7).
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Run the registered enricher (if any) on a command about to be dispatched.
+    fn enrich<C: 'static>(&self, tenant_id: TenantId, aggregate_id: AggregateId, command: &mut C) {
+        let ctx = EnrichContext {
+            tenant_id,
+            aggregate_id,
+            now: self.clock.now(),
+            ids: self.ids.as_ref(),
+        };
+        self.enrichers.apply(&ctx, command);
+    }
+
+    /// Apply the timestamp policy to events about to be appended.
+    fn check_timestamps(&self, events: &mut [UncommittedEvent]) -> Result<(), DispatchError> {
+        let Some(policy) = self.timestamp_policy else {
+            return Ok(());
+        };
+        let now = self.clock.now();
+        for event in events.iter_mut().filter(|e| !policy.accepts(e.occurred_at, now)) {
+            match policy.action {
+                TimestampAction::Reject => {
+                    return Err(DispatchError::Validation(format!(
+                        "occurred_at {} is outside the accepted window around server time {}",
+                        event.occurred_at.to_rfc3339(),
+                        now.to_rfc3339()
+                    )));
+                }
+                TimestampAction::Override => {
+                    event
+                        .metadata
+                        .insert(CLIENT_TIMESTAMP_KEY.to_string(), event.occurred_at.to_rfc3339());
+                    event.occurred_at = now;
+                    redate_payload(&mut event.payload, now);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject the command if the aggregate is over its guard's limits.
+    fn check_stream_guard(
+        &self,
+        guard: &StreamGuard,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: &str,
+        stream_length: u64,
+    ) -> Result<(), DispatchError> {
+        if let Some(max) = guard.max_stream_length
+            && stream_length >= max
+        {
+            return Err(DispatchError::StreamLimit(format!(
+                "{aggregate_type} {aggregate_id} has reached the maximum stream length of {max} events"
+            )));
+        }
+        let Some(max) = guard.max_events_per_window else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+        let mut recent = self.recent_appends.lock().expect("stream guard lock poisoned");
+        let Some(times) = recent.get_mut(&(tenant_id, aggregate_id)) else {
+            return Ok(());
+        };
+        while times.front().is_some_and(|t| now.duration_since(*t) >= guard.window) {
+            times.pop_front();
+        }
+        if times.is_empty() {
+            recent.remove(&(tenant_id, aggregate_id));
+        } else if times.len() >= max as usize {
+            return Err(DispatchError::StreamLimit(format!(
+                "{aggregate_type} {aggregate_id} appended {max} events within {:?}; retry later",
+                guard.window
+            )));
+        }
+        Ok(())
+    }
+
+    fn record_appends(&self, tenant_id: TenantId, aggregate_id: AggregateId, count: usize) {
+        let now = Instant::now();
+        let mut recent = self.recent_appends.lock().expect("stream guard lock poisoned");
+        recent
+            .entry((tenant_id, aggregate_id))
+            .or_default()
+            .extend(std::iter::repeat_n(now, count));
     }
 
     pub fn into_parts(self) -> (S, B) {
@@ -212,7 +943,8 @@ where
     /// - Expects that version when appending new events
     /// - If version changed (concurrent modification), append fails with `DispatchError::Concurrency`
     ///
-    /// Callers should retry by reloading and re-executing the command (or surface a conflict error).
+    /// Conflicts are retried (full reload + re-decide) within the `RetryPolicy` budget;
+    /// once exhausted, `DispatchError::Concurrency` is returned.
     ///
     /// ## Tenant Isolation
     ///
@@ -228,56 +960,759 @@ where
         aggregate_id: AggregateId,
         aggregate_type: impl Into<String>,
         command: A::Command,
-        make_aggregate: impl FnOnce(TenantId, AggregateId) -> A,
+        make_aggregate: impl Fn(TenantId, AggregateId) -> A,
     ) -> Result<Vec<StoredEvent>, DispatchError>
     where
-        A: Aggregate<Error = DomainError>,
+        A: Aggregate<Error = DomainError> + Serialize + DeserializeOwned,
         A::Event: forgeerp_events::Event + Serialize + DeserializeOwned,
     {
-        // 1) Load history (tenant-scoped)
-        let history = self.store.load_stream(tenant_id, aggregate_id)?;
-        validate_loaded_stream(tenant_id, aggregate_id, &history)?;
-        let expected = ExpectedVersion::Exact(stream_version(&history));
+        self.dispatch_with_report(tenant_id, aggregate_id, aggregate_type, command, make_aggregate)
+            .0
+    }
 
-        // 2) Rehydrate aggregate
-        let mut aggregate = make_aggregate(tenant_id, aggregate_id);
-        apply_history::<A>(&mut aggregate, &history)?;
+    /// Like [`dispatch`](Self::dispatch), honouring the command's expected version.
+    ///
+    /// With `ExpectedVersion::Exact(n)` the command only runs while the stream is at
+    /// version `n`; otherwise it fails with `DispatchError::PreconditionFailed`. The check
+    /// is repeated on every retry, so of two edits based on the same version the one that
+    /// loses the append fails instead of being re-decided against the winner's state.
+    pub fn dispatch_versioned<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: impl Into<String>,
+        command: A::Command,
+        make_aggregate: impl Fn(TenantId, AggregateId) -> A,
+    ) -> Result<Vec<StoredEvent>, DispatchError>
+    where
+        A: Aggregate<Error = DomainError> + Serialize + DeserializeOwned,
+        A::Command: VersionedCommand,
+        A::Event: forgeerp_events::Event + Serialize + DeserializeOwned,
+    {
+        let precondition = command.expected_version();
+        self.run(tenant_id, aggregate_id, aggregate_type.into(), command, make_aggregate, precondition)
+            .0
+    }
 
-        // 3) Decide events (no mutation)
-        let decided = aggregate.handle(&command).map_err(DispatchError::from)?;
-        if decided.is_empty() {
-            return Ok(vec![]);
+    /// Like [`dispatch`](Self::dispatch), under a client-supplied idempotency key.
+    ///
+    /// A repeat with the same key (in the same tenant) returns the events the first
+    /// dispatch committed without handling the command again; a different command under
+    /// the same key fails with `DispatchError::Validation`. Without a key, or without an
+    /// [`IdempotencyLog`] attached, this is a plain `dispatch`.
+    pub fn dispatch_idempotent<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: impl Into<String>,
+        command: A::Command,
+        make_aggregate: impl Fn(TenantId, AggregateId) -> A,
+        idempotency_key: Option<Uuid>,
+    ) -> Result<Vec<StoredEvent>, DispatchError>
+    where
+        A: Aggregate<Error = DomainError> + Serialize + DeserializeOwned,
+        A::Event: forgeerp_events::Event + Serialize + DeserializeOwned,
+    {
+        let dispatch = || self.dispatch(tenant_id, aggregate_id, aggregate_type, command, make_aggregate);
+        match idempotency_key {
+            Some(key) => with_idempotency_key_sync(IdempotencyKey::new(key), dispatch),
+            None => dispatch(),
         }
+    }
 
-        // 4) Persist (append-only, optimistic)
-        let aggregate_type = aggregate_type.into();
-        let uncommitted = decided
-            .iter()
-            .map(|ev| {
-                UncommittedEvent::from_typed(
-                    tenant_id,
-                    aggregate_id,
-                    aggregate_type.clone(),
-                    Uuid::now_v7(),
-                    ev,
-                )
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let committed = self.store.append(uncommitted, expected)?;
+    /// Like [`dispatch`](Self::dispatch), retrying append conflicts under `policy` instead
+    /// of the dispatcher's own [`RetryPolicy`].
+    ///
+    /// Each retry reloads the stream and handles the command again against the fresh
+    /// state; if the concurrent write made the command invalid, its domain error is
+    /// returned. A conflict is returned once `policy.max_attempts` are used up.
+    pub fn dispatch_with_retry<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: impl Into<String>,
+        command: A::Command,
+        make_aggregate: impl Fn(TenantId, AggregateId) -> A,
+        policy: RetryPolicy,
+    ) -> Result<Vec<StoredEvent>, DispatchError>
+    where
+        A: Aggregate<Error = DomainError> + Serialize + DeserializeOwned,
+        A::Event: forgeerp_events::Event + Serialize + DeserializeOwned,
+    {
+        RETRY_POLICY.sync_scope(policy, || {
+            self.dispatch(tenant_id, aggregate_id, aggregate_type, command, make_aggregate)
+        })
+    }
 
-        // 5) Publish committed events (after append)
-        for stored in &committed {
-            self.bus
-                .publish(stored.to_envelope())
-                .map_err(|e| DispatchError::Publish(format!("{e:?}")))?;
-        }
+    /// Like [`dispatch`](Self::dispatch), also returning how many attempts were made.
+    pub fn dispatch_with_report<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: impl Into<String>,
+        command: A::Command,
+        make_aggregate: impl Fn(TenantId, AggregateId) -> A,
+    ) -> (Result<Vec<StoredEvent>, DispatchError>, RetryReport)
+    where
+        A: Aggregate<Error = DomainError> + Serialize + DeserializeOwned,
+        A::Event: forgeerp_events::Event + Serialize + DeserializeOwned,
+    {
+        self.run(tenant_id, aggregate_id, aggregate_type.into(), command, make_aggregate, ExpectedVersion::Any)
+    }
 
-        Ok(committed)
+    /// Run the command up to the decision and return the events it would append.
+    ///
+    /// Enrichment, stream guards, the timestamp policy and the aggregate's own checks
+    /// apply exactly as in [`dispatch`](Self::dispatch), so a command that would fail
+    /// fails here with the same error; the store and bus are left untouched.
+    pub fn dispatch_preview<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: impl Into<String>,
+        mut command: A::Command,
+        make_aggregate: impl Fn(TenantId, AggregateId) -> A,
+    ) -> Result<DispatchPreview, DispatchError>
+    where
+        A: Aggregate<Error = DomainError> + Serialize + DeserializeOwned,
+        A::Event: forgeerp_events::Event + Serialize + DeserializeOwned,
+    {
+        let aggregate_type = aggregate_type.into();
+        let _permit = self.admit(tenant_id)?;
+        self.enrich(tenant_id, aggregate_id, &mut command);
+        let decision =
+            self.decide::<A>(tenant_id, aggregate_id, &aggregate_type, &command, &make_aggregate, ExpectedVersion::Any)?;
+        Ok(self.preview_of(decision.events, decision.version))
     }
-}
 
-fn stream_version(stream: &[StoredEvent]) -> u64 {
+    /// Decide commands for several aggregates and append all their events atomically.
+    ///
+    /// Every command is decided against its aggregate's current state first; if any is
+    /// rejected, or the store refuses the multi-stream append, nothing is written. Conflicts
+    /// are not retried: the whole batch fails with `DispatchError::Concurrency`.
+    pub fn dispatch_batch<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_type: impl Into<String>,
+        commands: Vec<(AggregateId, A::Command)>,
+        make_aggregate: impl Fn(TenantId, AggregateId) -> A,
+    ) -> Result<Vec<StoredEvent>, DispatchError>
+    where
+        A: Aggregate<Error = DomainError> + Serialize + DeserializeOwned,
+        A::Event: forgeerp_events::Event + Serialize + DeserializeOwned,
+    {
+        let aggregate_type = aggregate_type.into();
+        let guard = self.stream_guards.get(&aggregate_type);
+        let _permit = self.admit(tenant_id)?;
+
+        let mut commands = commands;
+        for (aggregate_id, command) in &mut commands {
+            self.enrich(tenant_id, *aggregate_id, command);
+        }
+
+        // One flow for the whole batch; each command is the cause of its own events.
+        let flow = command_correlation();
+        let mut batches = Vec::with_capacity(commands.len());
+        let mut snapshots = Vec::new();
+        for (aggregate_id, command) in &commands {
+            let rehydrated = self.rehydrate(tenant_id, *aggregate_id, &aggregate_type, &make_aggregate)?;
+            let version = rehydrated.version;
+            let decided = rehydrated.aggregate.handle(command).map_err(|e| {
+                let e = DispatchError::from(e);
+                self.note_rejection(tenant_id, &aggregate_type, *aggregate_id, command, &e);
+                e
+            })?;
+            if decided.is_empty() {
+                continue;
+            }
+            let correlation = CorrelationContext {
+                causation_id: command_correlation().causation_id,
+                ..flow
+            };
+            let mut uncommitted = to_uncommitted(tenant_id, *aggregate_id, &aggregate_type, &decided, correlation)?;
+            self.check_timestamps(&mut uncommitted)?;
+            batches.push((uncommitted, ExpectedVersion::Exact(version)));
+            snapshots.extend(self.snapshot_due(tenant_id, *aggregate_id, &aggregate_type, rehydrated, &decided));
+        }
+
+        if is_dry_run() {
+            let mut preview = DispatchPreview::default();
+            for (uncommitted, expected) in batches {
+                let version = match expected {
+                    ExpectedVersion::Exact(v) => v,
+                    ExpectedVersion::Any => 0,
+                };
+                let p = self.preview_of(uncommitted, version);
+                preview.events.extend(p.events);
+                preview.warnings.extend(p.warnings);
+            }
+            return Ok(record_preview(preview));
+        }
+
+        let committed = self.store.append_streams(batches)?;
+        for snapshot in &snapshots {
+            self.keep_snapshot(snapshot);
+        }
+        if guard.is_some_and(|g| g.max_events_per_window.is_some()) {
+            for (aggregate_id, _) in &commands {
+                let count = committed.iter().filter(|e| e.aggregate_id == *aggregate_id).count();
+                self.record_appends(tenant_id, *aggregate_id, count);
+            }
+        }
+
+        self.publish(committed)
+    }
+
+    fn run<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: String,
+        command: A::Command,
+        make_aggregate: impl Fn(TenantId, AggregateId) -> A,
+        precondition: ExpectedVersion,
+    ) -> (Result<Vec<StoredEvent>, DispatchError>, RetryReport)
+    where
+        A: Aggregate<Error = DomainError> + Serialize + DeserializeOwned,
+        A::Event: forgeerp_events::Event + Serialize + DeserializeOwned,
+    {
+        // Fixed before the first attempt, so retries record the same command.
+        with_correlation_sync(command_correlation(), || {
+            self.run_correlated(tenant_id, aggregate_id, aggregate_type, command, make_aggregate, precondition)
+        })
+    }
+
+    fn run_correlated<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: String,
+        mut command: A::Command,
+        make_aggregate: impl Fn(TenantId, AggregateId) -> A,
+        precondition: ExpectedVersion,
+    ) -> (Result<Vec<StoredEvent>, DispatchError>, RetryReport)
+    where
+        A: Aggregate<Error = DomainError> + Serialize + DeserializeOwned,
+        A::Event: forgeerp_events::Event + Serialize + DeserializeOwned,
+    {
+        let policy = RETRY_POLICY.try_with(|policy| *policy).unwrap_or(self.retry_policy);
+        let max_attempts = policy.max_attempts.max(1);
+        let _permit = match self.admit(tenant_id) {
+            Ok(permit) => permit,
+            Err(e) => {
+                let report = RetryReport {
+                    attempts: 0,
+                    max_attempts,
+                    exhausted: false,
+                };
+                return (Err(e), report);
+            }
+        };
+
+        // Fingerprinted before enrichment, which stamps fresh ids and times on every attempt.
+        let idempotency = self.idempotency_of(&aggregate_type, aggregate_id, &command);
+        // Held until the dispatch is recorded, so a concurrent repeat is refused.
+        let mut _claim = None;
+        if let Some((log, key, fingerprint)) = idempotency {
+            let refused = match log.claim(tenant_id, key) {
+                IdempotencyClaim::Claimed => {
+                    _claim = Some(ClaimedKey { log, tenant_id, key });
+                    None
+                }
+                IdempotencyClaim::Recorded(record) => Some(self.replay_idempotent(tenant_id, &record, fingerprint)),
+                IdempotencyClaim::InFlight => Some(Err(DispatchError::Concurrency(
+                    "a dispatch under this idempotency key is still in progress".to_string(),
+                ))),
+            };
+            if let Some(result) = refused {
+                let report = RetryReport {
+                    attempts: 0,
+                    max_attempts,
+                    exhausted: false,
+                };
+                return (result, report);
+            }
+        }
+        self.enrich(tenant_id, aggregate_id, &mut command);
+
+        if is_dry_run() {
+            let result = self
+                .decide::<A>(tenant_id, aggregate_id, &aggregate_type, &command, &make_aggregate, precondition)
+                .map(|decision| record_preview(self.preview_of(decision.events, decision.version)));
+            let report = RetryReport {
+                attempts: 1,
+                max_attempts,
+                exhausted: false,
+            };
+            return (result, report);
+        }
+
+        let mut attempts = 0;
+        let (result, exhausted) = loop {
+            attempts += 1;
+            match self.attempt::<A>(tenant_id, aggregate_id, &aggregate_type, &command, &make_aggregate, precondition) {
+                Ok(committed) => break (Ok(committed), false),
+                Err(AttemptError::Conflict(_)) if attempts < max_attempts => {
+                    let pause = policy.pause_after(attempts);
+                    if !pause.is_zero() {
+                        off_runtime(|| std::thread::sleep(pause));
+                    }
+                    continue;
+                }
+                Err(AttemptError::Conflict(e)) => break (Err(e), true),
+                Err(AttemptError::Fatal(e)) => break (Err(e), false),
+            }
+        };
+
+        // Events stored before a publish failure count as the key's result too.
+        let committed = match &result {
+            Ok(committed) => Some(committed.as_slice()),
+            Err(e) => e.committed(),
+        };
+        if let (Some(committed), Some((log, key, fingerprint))) = (committed, idempotency) {
+            let event_ids = committed.iter().map(|e| e.event_id).collect();
+            log.record(tenant_id, key, &aggregate_type, aggregate_id, fingerprint, event_ids);
+        }
+        if let Err(e) = &result {
+            self.note_rejection(tenant_id, &aggregate_type, aggregate_id, &command, e);
+        }
+        let report = RetryReport {
+            attempts,
+            max_attempts,
+            exhausted,
+        };
+        if let Some(observer) = &self.retry_observer {
+            observer(&report);
+        }
+        (result, report)
+    }
+
+    /// The events an earlier dispatch under the same idempotency key committed, unless
+    /// that dispatch was for a different request.
+    fn replay_idempotent(
+        &self,
+        tenant_id: TenantId,
+        record: &IdempotencyRecord,
+        fingerprint: u64,
+    ) -> Result<Vec<StoredEvent>, DispatchError> {
+        if record.fingerprint != fingerprint {
+            return Err(DispatchError::Validation(
+                "idempotency key was already used for a different request".to_string(),
+            ));
+        }
+        let stream = self.store.load_stream(tenant_id, record.aggregate_id)?;
+        Ok(stream
+            .into_iter()
+            .filter(|e| record.event_ids.contains(&e.event_id))
+            .collect())
+    }
+
+    /// Publish `committed` in order; on a bus failure the events come back in the error.
+    fn publish(&self, committed: Vec<StoredEvent>) -> Result<Vec<StoredEvent>, DispatchError> {
+        for stored in &committed {
+            if let Err(e) = self.bus.publish(stored.to_envelope()) {
+                return Err(DispatchError::Publish {
+                    committed,
+                    reason: format!("{e:?}"),
+                });
+            }
+        }
+        Ok(committed)
+    }
+
+    /// One load → rehydrate → decide → append → publish pass.
+    fn attempt<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: &str,
+        command: &A::Command,
+        make_aggregate: &impl Fn(TenantId, AggregateId) -> A,
+        precondition: ExpectedVersion,
+    ) -> Result<Vec<StoredEvent>, AttemptError>
+    where
+        A: Aggregate<Error = DomainError> + Serialize + DeserializeOwned,
+        A::Event: forgeerp_events::Event + Serialize + DeserializeOwned,
+    {
+        // 1-3) Load, rehydrate, decide
+        let decision = self.decide::<A>(tenant_id, aggregate_id, aggregate_type, command, make_aggregate, precondition)?;
+        if decision.events.is_empty() {
+            return Ok(vec![]);
+        }
+        let expected = ExpectedVersion::Exact(decision.version);
+        let guard = self.stream_guards.get(aggregate_type);
+
+        // 4) Persist (append-only, optimistic)
+        let committed = self.store.append(decision.events, expected).map_err(|e| match e {
+            // Someone appended since the load, so the edit's version is stale too.
+            EventStoreError::Concurrency(msg) if matches!(precondition, ExpectedVersion::Exact(_)) => {
+                AttemptError::Fatal(DispatchError::PreconditionFailed(msg))
+            }
+            EventStoreError::Concurrency(msg) => AttemptError::Conflict(DispatchError::Concurrency(msg)),
+            other => AttemptError::Fatal(other.into()),
+        })?;
+        if guard.is_some_and(|g| g.max_events_per_window.is_some()) {
+            self.record_appends(tenant_id, aggregate_id, committed.len());
+        }
+        if let Some(snapshot) = &decision.snapshot {
+            self.keep_snapshot(snapshot);
+        }
+
+        // 5) Publish committed events (after append)
+        self.publish(committed).map_err(AttemptError::Fatal)
+    }
+
+    /// Load → validate → rehydrate → decide, returning the would-be events and the
+    /// stream version they were decided against. Nothing is written.
+    fn decide<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: &str,
+        command: &A::Command,
+        make_aggregate: &impl Fn(TenantId, AggregateId) -> A,
+        precondition: ExpectedVersion,
+    ) -> Result<Decision, DispatchError>
+    where
+        A: Aggregate<Error = DomainError> + Serialize + DeserializeOwned,
+        A::Event: forgeerp_events::Event + Serialize + DeserializeOwned,
+    {
+        // 1-2) Load history (tenant-scoped) and rehydrate
+        let rehydrated = self.rehydrate(tenant_id, aggregate_id, aggregate_type, make_aggregate)?;
+        let version = rehydrated.version;
+        if let ExpectedVersion::Exact(based_on) = precondition
+            && based_on != version
+        {
+            return Err(DispatchError::PreconditionFailed(format!(
+                "edit is based on version {based_on}, but the stream is at version {version}"
+            )));
+        }
+
+        // 3) Decide events (no mutation)
+        let decided = rehydrated.aggregate.handle(command)?;
+        if decided.is_empty() {
+            return Ok(Decision {
+                events: vec![],
+                version,
+                snapshot: None,
+            });
+        }
+        let mut events = to_uncommitted(tenant_id, aggregate_id, aggregate_type, &decided, command_correlation())?;
+        self.check_timestamps(&mut events)?;
+        let snapshot = self.snapshot_due(tenant_id, aggregate_id, aggregate_type, rehydrated, &decided);
+        Ok(Decision {
+            events,
+            version,
+            snapshot,
+        })
+    }
+
+    /// Rebuild an aggregate from its latest usable snapshot and the events after it (or
+    /// from its full stream), enforcing the stream guard of its type on the way.
+    fn rehydrate<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: &str,
+        make_aggregate: &impl Fn(TenantId, AggregateId) -> A,
+    ) -> Result<Rehydrated<A>, DispatchError>
+    where
+        A: Aggregate + DeserializeOwned,
+        A::Event: DeserializeOwned,
+    {
+        let snapshot = match self.snapshot_policy {
+            Some(_) => self.store.load_snapshot(tenant_id, aggregate_id)?,
+            None => None,
+        };
+        // A snapshot of another stream or type, or one the aggregate's current shape no
+        // longer deserializes, is ignored: the stream is replayed in full instead.
+        let restored = snapshot
+            .filter(|s| s.tenant_id == tenant_id && s.aggregate_id == aggregate_id && s.aggregate_type == aggregate_type)
+            .and_then(|s| serde_json::from_value::<A>(s.state).ok().map(|aggregate| (aggregate, s.version)));
+        let (mut aggregate, snapshot_version) =
+            restored.unwrap_or_else(|| (make_aggregate(tenant_id, aggregate_id), 0));
+
+        let history = if snapshot_version > 0 {
+            self.store.load_stream_after(tenant_id, aggregate_id, snapshot_version)?
+        } else {
+            self.store.load_stream(tenant_id, aggregate_id)?
+        };
+        validate_loaded_stream(tenant_id, aggregate_id, &history)?;
+        let version = stream_version(&history).max(snapshot_version);
+        if let Some(guard) = self.stream_guards.get(aggregate_type) {
+            self.check_stream_guard(guard, tenant_id, aggregate_id, aggregate_type, version)?;
+        }
+
+        apply_history::<A>(&mut aggregate, &history, &self.upcasters)?;
+        Ok(Rehydrated {
+            aggregate,
+            version,
+            snapshot_version,
+        })
+    }
+
+    /// The snapshot to keep once `decided` is committed, if the policy calls for one.
+    fn snapshot_due<A>(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        aggregate_type: &str,
+        rehydrated: Rehydrated<A>,
+        decided: &[A::Event],
+    ) -> Option<Snapshot>
+    where
+        A: Aggregate + Serialize,
+    {
+        let policy = self.snapshot_policy?;
+        let version = rehydrated.version + decided.len() as u64;
+        if version < rehydrated.snapshot_version + policy.every.max(1) || is_dry_run() {
+            return None;
+        }
+        let mut aggregate = rehydrated.aggregate;
+        for event in decided {
+            aggregate.apply(event);
+        }
+        Some(Snapshot {
+            tenant_id,
+            aggregate_id,
+            aggregate_type: aggregate_type.to_string(),
+            version,
+            state: serde_json::to_value(&aggregate).ok()?,
+            created_at: self.clock.now(),
+        })
+    }
+
+    /// Store a snapshot of events that are already committed. A failure is not the
+    /// command's: it only means the next rehydration replays further back.
+    fn keep_snapshot(&self, snapshot: &Snapshot) {
+        let _ = self.store.store_snapshot(snapshot.tenant_id, snapshot.aggregate_id, snapshot);
+    }
+
+    /// Number decided events as the store would, without storing them.
+    fn preview_of(&self, uncommitted: Vec<UncommittedEvent>, version: u64) -> DispatchPreview {
+        let now = self.clock.now();
+        let mut warnings = Vec::new();
+        if uncommitted.is_empty() {
+            warnings.push("command would not change the aggregate".to_string());
+        }
+        let events = uncommitted
+            .into_iter()
+            .zip(version + 1..)
+            .map(|(e, sequence_number)| {
+                if let Some(client) = e.metadata.get(CLIENT_TIMESTAMP_KEY) {
+                    warnings.push(format!(
+                        "{} would be re-dated from {client} to server time {}",
+                        e.event_type,
+                        e.occurred_at.to_rfc3339()
+                    ));
+                }
+                StoredEvent {
+                    event_id: e.event_id,
+                    tenant_id: e.tenant_id,
+                    aggregate_id: e.aggregate_id,
+                    aggregate_type: e.aggregate_type,
+                    sequence_number,
+                    // Not committed, so no position in the global order yet.
+                    global_position: Default::default(),
+                    event_type: e.event_type,
+                    event_version: e.event_version,
+                    occurred_at: e.occurred_at,
+                    created_at: now,
+                    payload: e.payload,
+                    metadata: e.metadata,
+                    correlation_id: e.correlation_id,
+                    causation_id: e.causation_id,
+                }
+            })
+            .collect();
+        DispatchPreview { events, warnings }
+    }
+}
+
+tokio::task_local! {
+    static DRY_RUN: RefCell<DispatchPreview>;
+}
+
+/// Run `f` as a dry run: dispatches within it are previewed instead of committed.
+///
+/// Returns `f`'s output and everything the dispatches inside it would have done.
+/// Dispatch calls still return the would-be events, so callers work unchanged.
+pub async fn with_dry_run<F: Future>(f: F) -> (F::Output, DispatchPreview) {
+    DRY_RUN
+        .scope(RefCell::new(DispatchPreview::default()), async {
+            let output = f.await;
+            (output, DRY_RUN.with(|preview| preview.take()))
+        })
+        .await
+}
+
+/// Whether the current task is inside [`with_dry_run`].
+pub fn is_dry_run() -> bool {
+    DRY_RUN.try_with(|_| ()).is_ok()
+}
+
+/// Add a preview to the enclosing dry run; returns its events.
+fn record_preview(preview: DispatchPreview) -> Vec<StoredEvent> {
+    let events = preview.events.clone();
+    let _ = DRY_RUN.try_with(|current| {
+        let mut current = current.borrow_mut();
+        current.events.extend(preview.events);
+        current.warnings.extend(preview.warnings);
+    });
+    events
+}
+
+/// `crate/semver` recorded on every event this build appends.
+pub const PRODUCER_VERSION: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+tokio::task_local! {
+    static ENVELOPE_METADATA: BTreeMap<String, String>;
+}
+
+tokio::task_local! {
+    static COMMAND_PRINCIPAL: PrincipalId;
+}
+
+tokio::task_local! {
+    static CORRELATION: CorrelationContext;
+}
+
+tokio::task_local! {
+    static IDEMPOTENCY_KEY: IdempotencyKey;
+}
+
+tokio::task_local! {
+    /// Per-call override of the dispatcher's retry policy (see `dispatch_with_retry`).
+    static RETRY_POLICY: RetryPolicy;
+}
+
+/// Run `f` under a client's idempotency key: dispatches within it are recorded under
+/// the key, and repeats of them return what was committed the first time.
+pub fn with_idempotency_key_sync<R>(key: IdempotencyKey, f: impl FnOnce() -> R) -> R {
+    IDEMPOTENCY_KEY.sync_scope(key, f)
+}
+
+/// Idempotency key of the enclosing [`with_idempotency_key_sync`] scope.
+pub fn current_idempotency_key() -> Option<IdempotencyKey> {
+    IDEMPOTENCY_KEY.try_with(|key| *key).ok()
+}
+
+/// Run `f` on behalf of `principal`: commands rejected within it are logged with it.
+pub async fn with_command_principal<F: Future>(principal: PrincipalId, f: F) -> F::Output {
+    COMMAND_PRINCIPAL.scope(principal, f).await
+}
+
+/// Principal of the enclosing [`with_command_principal`] scope.
+pub fn current_command_principal() -> Option<PrincipalId> {
+    COMMAND_PRINCIPAL.try_with(|principal| *principal).ok()
+}
+
+/// Run `f` with `metadata` recorded on every event dispatched within it.
+pub async fn with_envelope_metadata<F: Future>(metadata: BTreeMap<String, String>, f: F) -> F::Output {
+    ENVELOPE_METADATA.scope(metadata, f).await
+}
+
+/// Wrap decided events for appending, stamping the envelope metadata of the current scope
+/// and the command's correlation.
+fn to_uncommitted<E>(
+    tenant_id: TenantId,
+    aggregate_id: AggregateId,
+    aggregate_type: &str,
+    decided: &[E],
+    correlation: CorrelationContext,
+) -> Result<Vec<UncommittedEvent>, EventStoreError>
+where
+    E: forgeerp_events::Event + Serialize,
+{
+    let metadata = current_envelope_metadata();
+    decided
+        .iter()
+        .map(|ev| {
+            UncommittedEvent::from_typed(tenant_id, aggregate_id, aggregate_type, Uuid::now_v7(), ev).map(|e| {
+                let mut metadata = metadata.clone();
+                metadata.insert(PRODUCER_VERSION_KEY.to_string(), PRODUCER_VERSION.to_string());
+                metadata.insert(SCHEMA_FINGERPRINT_KEY.to_string(), e.schema_fingerprint());
+                UncommittedEvent {
+                    metadata,
+                    correlation_id: Some(correlation.correlation_id),
+                    causation_id: Some(correlation.causation_id),
+                    ..e
+                }
+            })
+        })
+        .collect()
+}
+
+/// Replace the payload's `occurred_at`, at the top level or inside the variant object of
+/// an externally tagged event enum, so rehydration sees the same time as the envelope.
+fn redate_payload(payload: &mut JsonValue, now: DateTime<Utc>) {
+    let Some(object) = payload.as_object_mut() else {
+        return;
+    };
+    let now = JsonValue::String(now.to_rfc3339());
+    if let Some(occurred_at) = object.get_mut("occurred_at") {
+        *occurred_at = now;
+    } else if object.len() == 1
+        && let Some(occurred_at) = object.values_mut().next().and_then(|v| v.get_mut("occurred_at"))
+    {
+        *occurred_at = now;
+    }
+}
+
+/// Synchronous [`with_envelope_metadata`] for code running outside a request task
+/// (e.g. saga reactions dispatching follow-up commands).
+pub fn with_envelope_metadata_sync<R>(metadata: BTreeMap<String, String>, f: impl FnOnce() -> R) -> R {
+    ENVELOPE_METADATA.sync_scope(metadata, f)
+}
+
+/// Run `f` with `envelope`'s business key (if any) added to the current metadata, so
+/// commands caused by the event join the same business process.
+pub fn with_business_key_of<R>(envelope: &EventEnvelope<JsonValue>, f: impl FnOnce() -> R) -> R {
+    match envelope.business_key() {
+        Some(key) => {
+            let mut metadata = current_envelope_metadata();
+            metadata.insert(BUSINESS_KEY.to_string(), key.to_string());
+            with_envelope_metadata_sync(metadata, f)
+        }
+        None => f(),
+    }
+}
+
+/// Run `f` with commands dispatched within it joining `correlation`'s flow, caused by
+/// `correlation.causation_id`.
+pub fn with_correlation_sync<R>(correlation: CorrelationContext, f: impl FnOnce() -> R) -> R {
+    CORRELATION.sync_scope(correlation, f)
+}
+
+/// Run `f` with commands dispatched within it recorded as caused by `envelope`, in the
+/// same flow (see [`EventEnvelope::child_correlation`]).
+pub fn with_correlation_of<R>(envelope: &EventEnvelope<JsonValue>, f: impl FnOnce() -> R) -> R {
+    with_correlation_sync(envelope.child_correlation(), f)
+}
+
+/// Correlation of the enclosing [`with_correlation_sync`] scope, if any.
+pub fn current_correlation() -> Option<CorrelationContext> {
+    CORRELATION.try_with(|correlation| *correlation).ok()
+}
+
+/// Correlation for a command about to be dispatched: the enclosing scope's, or a new
+/// flow started by a fresh command id.
+fn command_correlation() -> CorrelationContext {
+    current_correlation().unwrap_or_else(|| {
+        let command_id = Uuid::now_v7();
+        CorrelationContext {
+            correlation_id: command_id,
+            causation_id: command_id,
+        }
+    })
+}
+
+/// Metadata of the enclosing [`with_envelope_metadata`] scope (empty outside one).
+pub fn current_envelope_metadata() -> BTreeMap<String, String> {
+    ENVELOPE_METADATA.try_with(Clone::clone).unwrap_or_default()
+}
+
+fn stream_version(stream: &[StoredEvent]) -> u64 {
     stream.last().map(|e| e.sequence_number).unwrap_or(0)
 }
 
@@ -316,7 +1751,7 @@ fn validate_loaded_stream(
     Ok(())
 }
 
-fn apply_history<A>(aggregate: &mut A, history: &[StoredEvent]) -> Result<(), DispatchError>
+fn apply_history<A>(aggregate: &mut A, history: &[StoredEvent], upcasters: &UpcasterRegistry) -> Result<(), DispatchError>
 where
     A: Aggregate,
     A::Event: DeserializeOwned,
@@ -326,8 +1761,8 @@ where
     sorted.sort_by_key(|e| e.sequence_number);
 
     for stored in sorted {
-        let ev: A::Event = serde_json::from_value(stored.payload)
-            .map_err(|e| DispatchError::Deserialize(e.to_string()))?;
+        let (_, payload) = upcasters.upcast(&stored.event_type, stored.event_version, stored.payload);
+        let ev: A::Event = serde_json::from_value(payload).map_err(|e| DispatchError::Deserialize(e.to_string()))?;
         aggregate.apply(&ev);
     }
 
@@ -335,3 +1770,1356 @@ where
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use chrono::Utc;
+    use forgeerp_events::{InMemoryEventBus, RenameField};
+    use forgeerp_inventory::{
+        AdjustStock, CreateItem, DEFAULT_LOCATION, InventoryCommand, InventoryEvent, InventoryItem, InventoryItemId, ItemCreated,
+        StockAdjusted,
+    };
+
+    use forgeerp_parties::{Party, PartyCommand, PartyId, PartyKind, RegisterParty, UpdateDetails};
+
+    use super::*;
+    use crate::event_store::InMemoryEventStore;
+
+    /// Store whose first `conflicts` appends lose a race with another writer.
+    struct ContendedStore {
+        inner: InMemoryEventStore,
+        conflicts: AtomicU32,
+    }
+
+    impl EventStore for ContendedStore {
+        fn append(
+            &self,
+            events: Vec<UncommittedEvent>,
+            expected_version: ExpectedVersion,
+        ) -> Result<Vec<StoredEvent>, EventStoreError> {
+            if self.conflicts.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)).is_ok() {
+                return Err(EventStoreError::Concurrency("concurrent writer".to_string()));
+            }
+            self.inner.append(events, expected_version)
+        }
+
+        fn load_stream(
+            &self,
+            tenant_id: TenantId,
+            aggregate_id: AggregateId,
+        ) -> Result<Vec<StoredEvent>, EventStoreError> {
+            self.inner.load_stream(tenant_id, aggregate_id)
+        }
+
+        fn tenant_stats(&self, tenant_id: TenantId) -> Result<crate::event_store::TenantStats, EventStoreError> {
+            self.inner.tenant_stats(tenant_id)
+        }
+    }
+
+    fn dispatch_create(
+        conflicts: u32,
+        policy: RetryPolicy,
+    ) -> (Result<Vec<StoredEvent>, DispatchError>, RetryReport, Vec<RetryReport>) {
+        let store = ContendedStore {
+            inner: InMemoryEventStore::new(),
+            conflicts: AtomicU32::new(conflicts),
+        };
+        let bus: InMemoryEventBus<EventEnvelope<JsonValue>> = InMemoryEventBus::new();
+        let observed: Arc<Mutex<Vec<RetryReport>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink = observed.clone();
+        let dispatcher = CommandDispatcher::new(store, bus)
+            .with_retry_policy(policy)
+            .with_retry_observer(Arc::new(move |r| sink.lock().unwrap().push(*r)));
+
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+        let (result, report) = dispatcher.dispatch_with_report(
+            tenant_id,
+            item_id.0,
+            "inventory.item",
+            InventoryCommand::CreateItem(CreateItem {
+                tenant_id,
+                item_id,
+                name: "Widget".to_string(),
+                occurred_at: Utc::now(),
+            }),
+            |_, id| InventoryItem::empty(InventoryItemId::new(id)),
+        );
+        let observed = observed.lock().unwrap().clone();
+        (result, report, observed)
+    }
+
+    #[test]
+    fn contended_command_that_eventually_succeeds_reports_attempts() {
+        let (result, report, observed) = dispatch_create(2, RetryPolicy {
+            max_attempts: 3,
+            ..RetryPolicy::default()
+        });
+
+        assert_eq!(result.unwrap().len(), 1);
+        assert_eq!(
+            report,
+            RetryReport {
+                attempts: 3,
+                max_attempts: 3,
+                exhausted: false
+            }
+        );
+        assert_eq!(observed, vec![report]);
+    }
+
+    #[test]
+    fn contended_command_past_budget_reports_exhausted() {
+        let (result, report, _) = dispatch_create(5, RetryPolicy {
+            max_attempts: 3,
+            ..RetryPolicy::default()
+        });
+
+        assert!(matches!(result, Err(DispatchError::Concurrency(_))));
+        assert_eq!(report.attempts, 3);
+        assert!(report.exhausted);
+    }
+
+    #[test]
+    fn default_policy_does_not_retry() {
+        let (result, report, _) = dispatch_create(1, RetryPolicy::default());
+
+        assert!(matches!(result, Err(DispatchError::Concurrency(_))));
+        assert_eq!(report.attempts, 1);
+        assert!(report.exhausted);
+    }
+
+    #[test]
+    fn retry_backoff_does_not_stall_the_runtime() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_time()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let contended = tokio::spawn(async {
+                dispatch_create(1, RetryPolicy {
+                    max_attempts: 2,
+                    backoff: std::time::Duration::from_millis(500),
+                })
+            });
+            // The only worker keeps running tasks and timers while the dispatch backs off.
+            let started = std::time::Instant::now();
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            assert_eq!(tokio::spawn(async { 42 }).await.unwrap(), 42);
+            assert!(started.elapsed() < std::time::Duration::from_millis(300));
+
+            let (result, report, _) = contended.await.unwrap();
+            assert_eq!(result.unwrap().len(), 1);
+            assert_eq!(report.attempts, 2);
+        });
+    }
+
+    /// Store where another writer adjusts the stock just before the next append, which
+    /// then fails with a real version conflict.
+    struct RacingStore {
+        inner: InMemoryEventStore,
+        interloper: Mutex<Option<i64>>,
+    }
+
+    impl EventStore for RacingStore {
+        fn append(
+            &self,
+            events: Vec<UncommittedEvent>,
+            expected_version: ExpectedVersion,
+        ) -> Result<Vec<StoredEvent>, EventStoreError> {
+            if let Some(delta) = self.interloper.lock().unwrap().take() {
+                let first = &events[0];
+                let adjusted = UncommittedEvent::from_typed(
+                    first.tenant_id,
+                    first.aggregate_id,
+                    "inventory.item",
+                    Uuid::now_v7(),
+                    &InventoryEvent::StockAdjusted(StockAdjusted {
+                        tenant_id: first.tenant_id,
+                        item_id: InventoryItemId::new(first.aggregate_id),
+                        location_id: DEFAULT_LOCATION.to_string(),
+                        delta,
+                        occurred_at: Utc::now(),
+                    }),
+                )?;
+                self.inner.append(vec![adjusted], ExpectedVersion::Any)?;
+            }
+            self.inner.append(events, expected_version)
+        }
+
+        fn load_stream(
+            &self,
+            tenant_id: TenantId,
+            aggregate_id: AggregateId,
+        ) -> Result<Vec<StoredEvent>, EventStoreError> {
+            self.inner.load_stream(tenant_id, aggregate_id)
+        }
+
+        fn tenant_stats(&self, tenant_id: TenantId) -> Result<crate::event_store::TenantStats, EventStoreError> {
+            self.inner.tenant_stats(tenant_id)
+        }
+    }
+
+    /// Item with 5 in stock whose next `AdjustStock` races a concurrent adjustment by
+    /// `interloper`; returns the dispatch result and the final stream.
+    fn adjust_racing(interloper: i64, delta: i64) -> (Result<Vec<StoredEvent>, DispatchError>, Vec<StoredEvent>) {
+        let store = Arc::new(RacingStore {
+            inner: InMemoryEventStore::new(),
+            interloper: Mutex::new(None),
+        });
+        let dispatcher = CommandDispatcher::new(store.clone(), InMemoryEventBus::<EventEnvelope<JsonValue>>::new());
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+        let make = |_, id| InventoryItem::empty(InventoryItemId::new(id));
+        let adjust = |delta| {
+            InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id,
+                item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta,
+                occurred_at: Utc::now(),
+            })
+        };
+        dispatcher
+            .dispatch(tenant_id, item_id.0, "inventory.item", create_item(tenant_id, item_id, "Widget"), make)
+            .unwrap();
+        dispatcher.dispatch(tenant_id, item_id.0, "inventory.item", adjust(5), make).unwrap();
+
+        *store.interloper.lock().unwrap() = Some(interloper);
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            backoff: std::time::Duration::from_millis(1),
+        };
+        let result = dispatcher.dispatch_with_retry(tenant_id, item_id.0, "inventory.item", adjust(delta), make, policy);
+        (result, store.load_stream(tenant_id, item_id.0).unwrap())
+    }
+
+    #[test]
+    fn dispatch_with_retry_succeeds_against_the_concurrent_writers_state() {
+        let (result, stream) = adjust_racing(2, -4);
+
+        // The first attempt decided against version 2 and lost the append to the
+        // concurrent +2; the retry handled the command again at version 3.
+        let committed = result.unwrap();
+        assert_eq!(committed.len(), 1);
+        assert_eq!(committed[0].sequence_number, 4);
+        assert_eq!(stream.len(), 4);
+    }
+
+    #[test]
+    fn dispatch_with_retry_returns_the_domain_error_once_the_command_is_invalid() {
+        let (result, stream) = adjust_racing(-3, -4);
+
+        // 5 on hand allowed taking 4; after the concurrent -3 only 2 are left.
+        assert!(matches!(result, Err(DispatchError::InvariantViolation(msg)) if msg.contains("cannot go negative")));
+        assert_eq!(stream.len(), 3);
+    }
+
+    #[test]
+    fn coded_domain_errors_keep_their_code_and_category() {
+        let code = ErrorCode::new("test.rule");
+        let err = DispatchError::from(DomainError::conflict("taken").with_code(code));
+        assert_eq!(err.code(), Some(code));
+        assert!(matches!(err, DispatchError::Rejected { error, .. } if matches!(*error, DispatchError::Concurrency(_))));
+        assert_eq!(DispatchError::from(DomainError::conflict("taken")).code(), None);
+    }
+
+    #[test]
+    fn retry_backoff_doubles_per_retry() {
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            backoff: std::time::Duration::from_millis(5),
+        };
+
+        assert_eq!(policy.pause_after(1), std::time::Duration::from_millis(5));
+        assert_eq!(policy.pause_after(3), std::time::Duration::from_millis(20));
+        assert!(RetryPolicy::default().pause_after(1).is_zero());
+    }
+
+    #[test]
+    fn scoped_metadata_is_recorded_on_stored_and_published_events() {
+        let bus: Arc<InMemoryEventBus<EventEnvelope<JsonValue>>> = Arc::new(InMemoryEventBus::new());
+        let sub = bus.subscribe();
+        let dispatcher = CommandDispatcher::new(InMemoryEventStore::new(), bus);
+        let tenant_id = TenantId::new();
+        let create = || {
+            let item_id = InventoryItemId::new(AggregateId::new());
+            dispatcher
+                .dispatch(
+                    tenant_id,
+                    item_id.0,
+                    "inventory.item",
+                    InventoryCommand::CreateItem(CreateItem {
+                        tenant_id,
+                        item_id,
+                        name: "Widget".to_string(),
+                        occurred_at: Utc::now(),
+                    }),
+                    |_, id| InventoryItem::empty(InventoryItemId::new(id)),
+                )
+                .unwrap()
+        };
+        let metadata = BTreeMap::from([("acted_by".to_string(), "admin".to_string())]);
+
+        let scoped = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(with_envelope_metadata(metadata.clone(), async { create() }));
+        assert_eq!(scoped[0].metadata["acted_by"], "admin");
+        assert_eq!(sub.try_recv().unwrap().metadata()["acted_by"], "admin");
+
+        let unscoped = create();
+        assert!(!unscoped[0].metadata.contains_key("acted_by"));
+        assert!(!sub.try_recv().unwrap().metadata().contains_key("acted_by"));
+    }
+
+    #[test]
+    fn events_record_their_flow_and_causing_command_across_follow_ups() {
+        let bus: Arc<InMemoryEventBus<EventEnvelope<JsonValue>>> = Arc::new(InMemoryEventBus::new());
+        let sub = bus.subscribe();
+        let dispatcher = CommandDispatcher::new(InMemoryEventStore::new(), bus);
+        let tenant_id = TenantId::new();
+        let create = || {
+            let item_id = InventoryItemId::new(AggregateId::new());
+            dispatcher
+                .dispatch(tenant_id, item_id.0, "inventory.item", create_item(tenant_id, item_id, "Widget"), |_, id| {
+                    InventoryItem::empty(InventoryItemId::new(id))
+                })
+                .unwrap()
+        };
+
+        // A top-level command starts a flow under its own id.
+        let root = create();
+        let flow = root[0].correlation_id.unwrap();
+        assert_eq!(root[0].causation_id, Some(flow));
+        let published = sub.try_recv().unwrap();
+        assert_eq!((published.correlation_id(), published.causation_id()), (Some(flow), Some(flow)));
+
+        // Follow-ups stay in the flow and point at the event that caused them.
+        let child = with_correlation_of(&published, create);
+        assert_eq!(child[0].correlation_id, Some(flow));
+        assert_eq!(child[0].causation_id, Some(root[0].event_id));
+        let grandchild = with_correlation_of(&child[0].to_envelope(), create);
+        assert_eq!(grandchild[0].correlation_id, Some(flow));
+        assert_eq!(grandchild[0].causation_id, Some(child[0].event_id));
+
+        let unrelated = create();
+        assert_ne!(unrelated[0].correlation_id, Some(flow));
+
+        // A batch is one flow; each command causes its own events.
+        let ids = [AggregateId::new(), AggregateId::new()];
+        let batch = dispatcher
+            .dispatch_batch(
+                tenant_id,
+                "inventory.item",
+                ids.iter()
+                    .map(|id| (*id, create_item(tenant_id, InventoryItemId::new(*id), "Batch")))
+                    .collect(),
+                |_, id| InventoryItem::empty(InventoryItemId::new(id)),
+            )
+            .unwrap();
+        assert_eq!(batch[0].correlation_id, batch[1].correlation_id);
+        assert_ne!(batch[0].causation_id, batch[1].causation_id);
+    }
+
+    fn create_item(tenant_id: TenantId, item_id: InventoryItemId, name: &str) -> InventoryCommand {
+        InventoryCommand::CreateItem(CreateItem {
+            tenant_id,
+            item_id,
+            name: name.to_string(),
+            occurred_at: Utc::now(),
+        })
+    }
+
+    #[test]
+    fn appended_events_record_producer_version_and_schema_fingerprint() {
+        let bus: Arc<InMemoryEventBus<EventEnvelope<JsonValue>>> = Arc::new(InMemoryEventBus::new());
+        let sub = bus.subscribe();
+        let dispatcher = CommandDispatcher::new(InMemoryEventStore::new(), bus);
+        let tenant_id = TenantId::new();
+        let create = |name: &str| {
+            let item_id = InventoryItemId::new(AggregateId::new());
+            dispatcher
+                .dispatch(tenant_id, item_id.0, "inventory.item", create_item(tenant_id, item_id, name), |_, id| {
+                    InventoryItem::empty(InventoryItemId::new(id))
+                })
+                .unwrap()
+                .remove(0)
+        };
+
+        let widget = create("Widget");
+        let envelope = sub.try_recv().unwrap();
+        assert_eq!(envelope.producer_version(), Some(PRODUCER_VERSION));
+        assert!(PRODUCER_VERSION.starts_with("forgeerp-infra/"));
+        assert_eq!(envelope.schema_fingerprint(), Some(widget.metadata[SCHEMA_FINGERPRINT_KEY].as_str()));
+
+        // Same event type and fields, different values: same fingerprint.
+        let gadget = create("Gadget");
+        assert_eq!(gadget.metadata[SCHEMA_FINGERPRINT_KEY], widget.metadata[SCHEMA_FINGERPRINT_KEY]);
+
+        let stock = UncommittedEvent::from_typed(
+            tenant_id,
+            widget.aggregate_id,
+            "inventory.item",
+            Uuid::now_v7(),
+            &InventoryEvent::StockAdjusted(StockAdjusted {
+                tenant_id,
+                item_id: InventoryItemId::new(widget.aggregate_id),
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta: 1,
+                occurred_at: Utc::now(),
+            }),
+        )
+        .unwrap();
+        assert_ne!(stock.schema_fingerprint(), widget.metadata[SCHEMA_FINGERPRINT_KEY]);
+    }
+
+    type GuardedDispatcher = CommandDispatcher<InMemoryEventStore, Arc<InMemoryEventBus<EventEnvelope<JsonValue>>>>;
+
+    fn adjust_stock(
+        dispatcher: &GuardedDispatcher,
+        tenant_id: TenantId,
+        item_id: InventoryItemId,
+    ) -> Result<Vec<StoredEvent>, DispatchError> {
+        dispatcher.dispatch(
+            tenant_id,
+            item_id.0,
+            "inventory.item",
+            InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id,
+                item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta: 1,
+                occurred_at: Utc::now(),
+            }),
+            |_, id| InventoryItem::empty(InventoryItemId::new(id)),
+        )
+    }
+
+    fn guarded_item(guard: StreamGuard) -> (GuardedDispatcher, TenantId, InventoryItemId) {
+        let dispatcher = CommandDispatcher::new(InMemoryEventStore::new(), Arc::new(InMemoryEventBus::new()))
+            .with_stream_guard("inventory.item", guard);
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+        dispatcher
+            .dispatch(tenant_id, item_id.0, "inventory.item", create_item(tenant_id, item_id, "Widget"), |_, id| {
+                InventoryItem::empty(InventoryItemId::new(id))
+            })
+            .unwrap();
+        (dispatcher, tenant_id, item_id)
+    }
+
+    #[test]
+    fn commands_over_the_aggregate_rate_are_rejected_until_the_window_passes() {
+        let (dispatcher, tenant_id, item_id) = guarded_item(StreamGuard {
+            max_events_per_window: Some(3),
+            window: Duration::from_millis(100),
+            ..StreamGuard::default()
+        });
+
+        // Creation counts towards the window.
+        adjust_stock(&dispatcher, tenant_id, item_id).unwrap();
+        adjust_stock(&dispatcher, tenant_id, item_id).unwrap();
+        assert!(matches!(
+            adjust_stock(&dispatcher, tenant_id, item_id),
+            Err(DispatchError::StreamLimit(_))
+        ));
+
+        // Other aggregates of the same type are unaffected.
+        let other = InventoryItemId::new(AggregateId::new());
+        dispatcher
+            .dispatch(tenant_id, other.0, "inventory.item", create_item(tenant_id, other, "Gadget"), |_, id| {
+                InventoryItem::empty(InventoryItemId::new(id))
+            })
+            .unwrap();
+        adjust_stock(&dispatcher, tenant_id, other).unwrap();
+
+        std::thread::sleep(Duration::from_millis(120));
+        let committed = adjust_stock(&dispatcher, tenant_id, item_id).unwrap();
+        assert_eq!(committed[0].sequence_number, 4);
+    }
+
+    #[test]
+    fn commands_past_the_maximum_stream_length_are_rejected() {
+        let (dispatcher, tenant_id, item_id) = guarded_item(StreamGuard {
+            max_stream_length: Some(3),
+            ..StreamGuard::default()
+        });
+
+        adjust_stock(&dispatcher, tenant_id, item_id).unwrap();
+        adjust_stock(&dispatcher, tenant_id, item_id).unwrap();
+        let err = adjust_stock(&dispatcher, tenant_id, item_id).unwrap_err();
+        assert!(matches!(err, DispatchError::StreamLimit(msg) if msg.contains("maximum stream length")));
+    }
+
+    #[test]
+    fn events_written_without_producer_metadata_still_load() {
+        let store = Arc::new(InMemoryEventStore::new());
+        let bus: Arc<InMemoryEventBus<EventEnvelope<JsonValue>>> = Arc::new(InMemoryEventBus::new());
+        let dispatcher = CommandDispatcher::new(store.clone(), bus);
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+
+        // An event as written before producer metadata existed (no `metadata` field).
+        let mut legacy = serde_json::to_value(
+            UncommittedEvent::from_typed(
+                tenant_id,
+                item_id.0,
+                "inventory.item",
+                Uuid::now_v7(),
+                &InventoryEvent::ItemCreated(ItemCreated {
+                    tenant_id,
+                    item_id,
+                    name: "Widget".to_string(),
+                    occurred_at: Utc::now(),
+                }),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        legacy.as_object_mut().unwrap().remove("metadata");
+        let legacy: UncommittedEvent = serde_json::from_value(legacy).unwrap();
+        store.append(vec![legacy], ExpectedVersion::Exact(0)).unwrap();
+
+        let history = store.load_stream(tenant_id, item_id.0).unwrap();
+        assert_eq!(history[0].to_envelope().producer_version(), None);
+        assert_eq!(history[0].to_envelope().schema_fingerprint(), None);
+
+        let committed = dispatcher
+            .dispatch(
+                tenant_id,
+                item_id.0,
+                "inventory.item",
+                InventoryCommand::AdjustStock(AdjustStock {
+                    tenant_id,
+                    item_id,
+                    location_id: DEFAULT_LOCATION.to_string(),
+                    delta: 5,
+                    occurred_at: Utc::now(),
+                }),
+                |_, id| InventoryItem::empty(InventoryItemId::new(id)),
+            )
+            .unwrap();
+        assert_eq!(committed[0].sequence_number, 2);
+        assert_eq!(committed[0].to_envelope().producer_version(), Some(PRODUCER_VERSION));
+    }
+
+    /// Test aggregate whose only event is at v2 (v1 called `body` `text`).
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    enum NoteEvent {
+        Written { body: String, occurred_at: chrono::DateTime<Utc> },
+    }
+
+    impl forgeerp_events::Event for NoteEvent {
+        fn event_type(&self) -> &'static str {
+            "test.note.written"
+        }
+
+        fn version(&self) -> u32 {
+            2
+        }
+
+        fn occurred_at(&self) -> chrono::DateTime<Utc> {
+            let NoteEvent::Written { occurred_at, .. } = self;
+            *occurred_at
+        }
+    }
+
+    /// Rejects writing the same body twice, so a dispatch shows what rehydration read.
+    #[derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    struct Note {
+        id: AggregateId,
+        version: u64,
+        bodies: Vec<String>,
+    }
+
+    impl forgeerp_core::AggregateRoot for Note {
+        type Id = AggregateId;
+
+        fn id(&self) -> &AggregateId {
+            &self.id
+        }
+
+        fn version(&self) -> u64 {
+            self.version
+        }
+    }
+
+    impl Aggregate for Note {
+        type Command = String;
+        type Event = NoteEvent;
+        type Error = DomainError;
+
+        fn apply(&mut self, event: &NoteEvent) {
+            let NoteEvent::Written { body, .. } = event;
+            self.bodies.push(body.clone());
+            self.version += 1;
+        }
+
+        fn handle(&self, body: &String) -> Result<Vec<NoteEvent>, DomainError> {
+            if self.bodies.contains(body) {
+                return Err(DomainError::validation("already written"));
+            }
+            Ok(vec![NoteEvent::Written {
+                body: body.clone(),
+                occurred_at: Utc::now(),
+            }])
+        }
+    }
+
+    #[test]
+    fn v1_payloads_are_upcast_before_rehydration() {
+        let store = Arc::new(InMemoryEventStore::new());
+        let tenant_id = TenantId::new();
+        let note_id = AggregateId::new();
+        let v1 = serde_json::json!({ "Written": { "text": "first", "occurred_at": Utc::now() } });
+        store
+            .append(
+                vec![UncommittedEvent {
+                    event_id: Uuid::now_v7(),
+                    tenant_id,
+                    aggregate_id: note_id,
+                    aggregate_type: "test.note".to_string(),
+                    event_type: "test.note.written".to_string(),
+                    event_version: 1,
+                    occurred_at: Utc::now(),
+                    payload: v1.clone(),
+                    metadata: Default::default(),
+                    correlation_id: None,
+                    causation_id: None,
+                }],
+                ExpectedVersion::Exact(0),
+            )
+            .unwrap();
+        let upcasters = UpcasterRegistry::new().register("test.note.written", 1, RenameField::new("text", "body"));
+
+        let (version, upcast) = upcasters.upcast("test.note.written", 1, v1);
+        assert_eq!(version, 2);
+        assert!(matches!(serde_json::from_value(upcast).unwrap(), NoteEvent::Written { body, .. } if body == "first"));
+
+        let write = |dispatcher: &CommandDispatcher<_, _>, body: &str| {
+            dispatcher.dispatch(tenant_id, note_id, "test.note", body.to_string(), |_, id| Note {
+                id,
+                version: 0,
+                bodies: Vec::new(),
+            })
+        };
+        let plain = CommandDispatcher::new(store.clone(), InMemoryEventBus::<EventEnvelope<JsonValue>>::new());
+        assert!(matches!(write(&plain, "second"), Err(DispatchError::Deserialize(_))));
+
+        let upcasting = CommandDispatcher::new(store, InMemoryEventBus::<EventEnvelope<JsonValue>>::new())
+            .with_upcasters(Arc::new(upcasters));
+        assert!(matches!(write(&upcasting, "first"), Err(DispatchError::Validation(_))));
+        let committed = write(&upcasting, "second").unwrap();
+        assert_eq!((committed[0].sequence_number, committed[0].event_version), (2, 2));
+    }
+
+    #[test]
+    fn rehydrating_from_a_snapshot_matches_a_full_replay() {
+        let store = Arc::new(InMemoryEventStore::new());
+        let tenant_id = TenantId::new();
+        let note_id = AggregateId::new();
+        let new_note = |_, id| Note {
+            id,
+            version: 0,
+            bodies: Vec::new(),
+        };
+        let snapshotting = CommandDispatcher::new(store.clone(), InMemoryEventBus::<EventEnvelope<JsonValue>>::new())
+            .with_snapshot_policy(SnapshotPolicy { every: 3 });
+        for body in ["a", "b", "c", "d", "e", "f", "g"] {
+            snapshotting
+                .dispatch(tenant_id, note_id, "test.note", body.to_string(), new_note)
+                .unwrap();
+        }
+
+        // Kept after the 3rd and 6th event; the 7th is replayed on top.
+        let snapshot = store.load_snapshot(tenant_id, note_id).unwrap().unwrap();
+        assert_eq!(snapshot.version, 6);
+        let from_snapshot = snapshotting.rehydrate(tenant_id, note_id, "test.note", &new_note).unwrap();
+        assert_eq!((from_snapshot.version, from_snapshot.snapshot_version), (7, 6));
+
+        let replaying = CommandDispatcher::new(store.clone(), InMemoryEventBus::<EventEnvelope<JsonValue>>::new());
+        let full = replaying.rehydrate(tenant_id, note_id, "test.note", &new_note).unwrap();
+        assert_eq!((full.version, full.snapshot_version), (7, 0));
+        assert_eq!(from_snapshot.aggregate, full.aggregate);
+
+        // State held only in the snapshot still drives decisions.
+        let again = snapshotting.dispatch(tenant_id, note_id, "test.note", "a".to_string(), new_note);
+        assert!(matches!(again, Err(DispatchError::Validation(_))));
+
+        // A snapshot the aggregate cannot read falls back to a full replay.
+        store
+            .store_snapshot(tenant_id, note_id, &Snapshot {
+                state: serde_json::json!({ "unexpected": true }),
+                version: 7,
+                ..snapshot
+            })
+            .unwrap();
+        let fallback = snapshotting.rehydrate(tenant_id, note_id, "test.note", &new_note).unwrap();
+        assert_eq!(fallback.snapshot_version, 0);
+        assert_eq!(fallback.aggregate, full.aggregate);
+    }
+
+    #[test]
+    fn repeated_idempotency_key_returns_the_first_dispatch_events() {
+        let dispatcher = CommandDispatcher::new(InMemoryEventStore::new(), InMemoryEventBus::new())
+            .with_idempotency_log(Arc::new(IdempotencyLog::default()));
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+        let new_item = |_, id| InventoryItem::empty(InventoryItemId::new(id));
+        let adjust = |delta: i64, key: Option<Uuid>| {
+            let command = InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id,
+                item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta,
+                occurred_at: chrono::DateTime::UNIX_EPOCH,
+            });
+            dispatcher.dispatch_idempotent(tenant_id, item_id.0, "inventory.item", command, new_item, key)
+        };
+        let create = InventoryCommand::CreateItem(CreateItem {
+            tenant_id,
+            item_id,
+            name: "Widget".to_string(),
+            occurred_at: Utc::now(),
+        });
+        dispatcher
+            .dispatch(tenant_id, item_id.0, "inventory.item", create, new_item)
+            .unwrap();
+
+        let key = Uuid::now_v7();
+        let first = adjust(5, Some(key)).unwrap();
+        let retried = adjust(5, Some(key)).unwrap();
+        assert_eq!(retried, first);
+        assert_eq!(dispatcher.store.load_stream(tenant_id, item_id.0).unwrap().len(), 2);
+
+        // Same key, different request.
+        assert!(matches!(adjust(7, Some(key)), Err(DispatchError::Validation(_))));
+        // A dispatch that committed nothing leaves its key free for the retry.
+        let failing = Uuid::now_v7();
+        for _ in 0..2 {
+            assert!(matches!(adjust(-100, Some(failing)), Err(DispatchError::InvariantViolation(_))));
+        }
+        // Without a key every dispatch is handled.
+        adjust(5, None).unwrap();
+        assert_eq!(dispatcher.store.load_stream(tenant_id, item_id.0).unwrap().len(), 3);
+
+        // Keys are per tenant.
+        let other_tenant = TenantId::new();
+        let other_item = InventoryItemId::new(AggregateId::new());
+        let create_other = InventoryCommand::CreateItem(CreateItem {
+            tenant_id: other_tenant,
+            item_id: other_item,
+            name: "Gadget".to_string(),
+            occurred_at: Utc::now(),
+        });
+        let created = dispatcher
+            .dispatch_idempotent(other_tenant, other_item.0, "inventory.item", create_other, new_item, Some(key))
+            .unwrap();
+        assert_eq!(created[0].tenant_id, other_tenant);
+    }
+
+    type PartyDispatcher = CommandDispatcher<InMemoryEventStore, InMemoryEventBus<EventEnvelope<JsonValue>>>;
+
+    fn registered_party() -> (PartyDispatcher, TenantId, PartyId) {
+        let dispatcher = CommandDispatcher::new(InMemoryEventStore::new(), InMemoryEventBus::new());
+        let tenant_id = TenantId::new();
+        let party_id = PartyId::new(AggregateId::new());
+        let register = PartyCommand::RegisterParty(RegisterParty {
+            tenant_id,
+            party_id,
+            kind: PartyKind::Customer,
+            name: "Acme".to_string(),
+            contact: None,
+            occurred_at: Utc::now(),
+        });
+        dispatcher
+            .dispatch_versioned(tenant_id, party_id.0, "parties.party", register, |_, id| {
+                Party::empty(PartyId::new(id))
+            })
+            .unwrap();
+        (dispatcher, tenant_id, party_id)
+    }
+
+    fn rename(
+        dispatcher: &PartyDispatcher,
+        tenant_id: TenantId,
+        party_id: PartyId,
+        name: &str,
+        expected_version: Option<u64>,
+    ) -> Result<Vec<StoredEvent>, DispatchError> {
+        let update = PartyCommand::UpdateDetails(UpdateDetails {
+            tenant_id,
+            party_id,
+            name: Some(name.to_string()),
+            contact: None,
+            expected_version,
+            occurred_at: Utc::now(),
+        });
+        dispatcher.dispatch_versioned(tenant_id, party_id.0, "parties.party", update, |_, id| {
+            Party::empty(PartyId::new(id))
+        })
+    }
+
+    #[test]
+    fn concurrent_edits_of_one_version_admit_exactly_one() {
+        let (dispatcher, tenant_id, party_id) = registered_party();
+        let dispatcher = Arc::new(dispatcher);
+        let barrier = Arc::new(std::sync::Barrier::new(2));
+
+        let results: Vec<_> = ["Acme Ltd", "Acme Inc"]
+            .into_iter()
+            .map(|name| {
+                let (dispatcher, barrier) = (dispatcher.clone(), barrier.clone());
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    rename(&dispatcher, tenant_id, party_id, name, Some(1))
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect();
+
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+        assert_eq!(
+            results
+                .iter()
+                .filter(|r| matches!(r, Err(DispatchError::PreconditionFailed(_))))
+                .count(),
+            1
+        );
+        assert_eq!(dispatcher.store.load_stream(tenant_id, party_id.0).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn stale_edit_is_rejected_and_unversioned_edit_uses_latest_state() {
+        let (dispatcher, tenant_id, party_id) = registered_party();
+
+        rename(&dispatcher, tenant_id, party_id, "Acme Ltd", Some(1)).unwrap();
+        assert!(matches!(
+            rename(&dispatcher, tenant_id, party_id, "Acme Inc", Some(1)),
+            Err(DispatchError::PreconditionFailed(msg)) if msg.contains("stream is at version 2")
+        ));
+
+        // Without an expected version the edit is decided against the latest state.
+        let committed = rename(&dispatcher, tenant_id, party_id, "Acme Inc", None).unwrap();
+        assert_eq!(committed[0].sequence_number, 3);
+    }
+
+    #[test]
+    fn edit_losing_the_append_race_fails_its_precondition_without_retrying() {
+        let store = ContendedStore {
+            inner: InMemoryEventStore::new(),
+            conflicts: AtomicU32::new(0),
+        };
+        let dispatcher = CommandDispatcher::new(store, InMemoryEventBus::<EventEnvelope<JsonValue>>::new())
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 3,
+                ..RetryPolicy::default()
+            });
+        let tenant_id = TenantId::new();
+        let party_id = PartyId::new(AggregateId::new());
+        let make = |_: TenantId, id: AggregateId| Party::empty(PartyId::new(id));
+        let register = PartyCommand::RegisterParty(RegisterParty {
+            tenant_id,
+            party_id,
+            kind: PartyKind::Supplier,
+            name: "Globex".to_string(),
+            contact: None,
+            occurred_at: Utc::now(),
+        });
+        dispatcher.dispatch(tenant_id, party_id.0, "parties.party", register, make).unwrap();
+
+        dispatcher.store.conflicts.store(1, Ordering::SeqCst);
+        let update = PartyCommand::UpdateDetails(UpdateDetails {
+            tenant_id,
+            party_id,
+            name: Some("Globex Corp".to_string()),
+            contact: None,
+            expected_version: Some(1),
+            occurred_at: Utc::now(),
+        });
+        let result = dispatcher.dispatch_versioned(tenant_id, party_id.0, "parties.party", update, make);
+        assert!(matches!(result, Err(DispatchError::PreconditionFailed(_))));
+    }
+
+    fn dispatch_item_at(
+        action: TimestampAction,
+        occurred_at: DateTime<Utc>,
+    ) -> (Result<Vec<StoredEvent>, DispatchError>, DateTime<Utc>) {
+        let now = Utc::now();
+        let dispatcher = CommandDispatcher::new(InMemoryEventStore::new(), InMemoryEventBus::<EventEnvelope<JsonValue>>::new())
+            .with_clock(crate::clock::FixedClock::arc(now))
+            .with_timestamp_policy(TimestampPolicy {
+                action,
+                ..TimestampPolicy::default()
+            });
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+        let create = InventoryCommand::CreateItem(CreateItem {
+            tenant_id,
+            item_id,
+            name: "Widget".to_string(),
+            occurred_at,
+        });
+        let make = |_: TenantId, id: AggregateId| InventoryItem::empty(InventoryItemId::new(id));
+        let result = dispatcher.dispatch(tenant_id, item_id.0, "inventory.item", create, make).and_then(|created| {
+            // The stream must still rehydrate after any re-dating.
+            let adjust = InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id,
+                item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta: 1,
+                occurred_at: now,
+            });
+            dispatcher.dispatch(tenant_id, item_id.0, "inventory.item", adjust, make)?;
+            Ok(created)
+        });
+        (result, now)
+    }
+
+    #[test]
+    fn far_future_timestamp_is_rejected_by_default_policy() {
+        let (result, _) = dispatch_item_at(TimestampAction::Reject, Utc::now() + chrono::Duration::days(365));
+        assert!(matches!(result, Err(DispatchError::Validation(msg)) if msg.contains("occurred_at")));
+    }
+
+    #[test]
+    fn far_future_timestamp_is_overridden_with_server_time() {
+        let client = Utc::now() + chrono::Duration::days(365);
+        let (result, now) = dispatch_item_at(TimestampAction::Override, client);
+        let created = result.unwrap();
+
+        assert_eq!(created[0].occurred_at, now);
+        assert_eq!(created[0].metadata.get(CLIENT_TIMESTAMP_KEY), Some(&client.to_rfc3339()));
+        let ItemCreated { occurred_at, .. } = match serde_json::from_value(created[0].payload.clone()).unwrap() {
+            InventoryEvent::ItemCreated(e) => e,
+            other => panic!("unexpected event {other:?}"),
+        };
+        assert_eq!(occurred_at, now);
+    }
+
+    #[test]
+    fn reasonable_timestamp_passes_through() {
+        let client = Utc::now() - chrono::Duration::hours(2);
+        for action in [TimestampAction::Reject, TimestampAction::Override] {
+            let (result, _) = dispatch_item_at(action, client);
+            let created = result.unwrap();
+            assert_eq!(created[0].occurred_at, client);
+            assert!(!created[0].metadata.contains_key(CLIENT_TIMESTAMP_KEY));
+        }
+    }
+
+    fn stamp_inventory_command(ctx: &EnrichContext<'_>, command: &mut InventoryCommand) {
+        match command {
+            InventoryCommand::CreateItem(c) => c.occurred_at = ctx.now,
+            InventoryCommand::RenameItem(c) => c.occurred_at = ctx.now,
+            InventoryCommand::AdjustStock(c) => c.occurred_at = ctx.now,
+            InventoryCommand::TransferStock(c) => c.occurred_at = ctx.now,
+            InventoryCommand::ReserveStock(c) => c.occurred_at = ctx.now,
+            InventoryCommand::ReleaseReservation(c) => c.occurred_at = ctx.now,
+            InventoryCommand::SetReorderPoint(c) => c.occurred_at = ctx.now,
+        }
+    }
+
+    #[test]
+    fn command_without_occurred_at_is_stamped_with_clock_time_before_handle() {
+        let now = Utc::now() - chrono::Duration::minutes(3);
+        let dispatcher = CommandDispatcher::new(InMemoryEventStore::new(), InMemoryEventBus::<EventEnvelope<JsonValue>>::new())
+            .with_clock(crate::clock::FixedClock::arc(now))
+            .with_enricher(stamp_inventory_command);
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+        let create = InventoryCommand::CreateItem(CreateItem {
+            tenant_id,
+            item_id,
+            name: "Widget".to_string(),
+            occurred_at: Default::default(),
+        });
+        let make = |_: TenantId, id: AggregateId| InventoryItem::empty(InventoryItemId::new(id));
+
+        let created = dispatcher.dispatch(tenant_id, item_id.0, "inventory.item", create, make).unwrap();
+
+        assert_eq!(created[0].occurred_at, now);
+        let ItemCreated { occurred_at, .. } = match serde_json::from_value(created[0].payload.clone()).unwrap() {
+            InventoryEvent::ItemCreated(e) => e,
+            other => panic!("unexpected event {other:?}"),
+        };
+        assert_eq!(occurred_at, now);
+    }
+
+    #[test]
+    fn events_sharing_a_business_key_are_found_across_aggregates() {
+        use crate::event_store::{EventFilter, EventQuery, Pagination};
+
+        let dispatcher = CommandDispatcher::new(Arc::new(InMemoryEventStore::new()), InMemoryEventBus::<EventEnvelope<JsonValue>>::new());
+        let tenant_id = TenantId::new();
+        let create_item = |name: &str| {
+            let item_id = InventoryItemId::new(AggregateId::new());
+            let create = InventoryCommand::CreateItem(CreateItem {
+                tenant_id,
+                item_id,
+                name: name.to_string(),
+                occurred_at: Utc::now(),
+            });
+            dispatcher
+                .dispatch(tenant_id, item_id.0, "inventory.item", create, |_, id| {
+                    InventoryItem::empty(InventoryItemId::new(id))
+                })
+                .unwrap()
+        };
+        let register_party = || {
+            let party_id = PartyId::new(AggregateId::new());
+            let register = PartyCommand::RegisterParty(RegisterParty {
+                tenant_id,
+                party_id,
+                kind: PartyKind::Customer,
+                name: "Acme".to_string(),
+                contact: None,
+                occurred_at: Utc::now(),
+            });
+            dispatcher
+                .dispatch(tenant_id, party_id.0, "parties.party", register, |_, id| Party::empty(PartyId::new(id)))
+                .unwrap()
+        };
+        let keyed = |key: &str| BTreeMap::from([(BUSINESS_KEY.to_string(), key.to_string())]);
+
+        let item = with_envelope_metadata_sync(keyed("order-123"), || create_item("Widget"));
+        let party = with_envelope_metadata_sync(keyed("order-123"), register_party);
+        // A follow-up reacting to a keyed event inherits the key.
+        let follow_up = with_business_key_of(&item[0].to_envelope(), || create_item("Gadget"));
+        with_envelope_metadata_sync(keyed("order-456"), || create_item("Other"));
+        create_item("Unkeyed");
+
+        let filter = EventFilter {
+            business_key: Some("order-123".to_string()),
+            ..Default::default()
+        };
+        let result = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(dispatcher.store.query_events(tenant_id, filter, Pagination::default()))
+            .unwrap();
+
+        let mut found: Vec<AggregateId> = result.events.iter().map(|e| e.aggregate_id).collect();
+        let mut expected = vec![item[0].aggregate_id, party[0].aggregate_id, follow_up[0].aggregate_id];
+        found.sort_by_key(|id| id.to_string());
+        expected.sort_by_key(|id| id.to_string());
+        assert_eq!(found, expected);
+        assert!(result.events.iter().all(|e| e.to_envelope().business_key() == Some("order-123")));
+    }
+
+    fn preview_adjust(
+        dispatcher: &GuardedDispatcher,
+        tenant_id: TenantId,
+        item_id: InventoryItemId,
+        delta: i64,
+    ) -> Result<DispatchPreview, DispatchError> {
+        dispatcher.dispatch_preview(
+            tenant_id,
+            item_id.0,
+            "inventory.item",
+            InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id,
+                item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta,
+                occurred_at: Utc::now(),
+            }),
+            |_, id| InventoryItem::empty(InventoryItemId::new(id)),
+        )
+    }
+
+    #[test]
+    fn preview_returns_the_would_be_events_without_appending_or_publishing() {
+        let bus: Arc<InMemoryEventBus<EventEnvelope<JsonValue>>> = Arc::new(InMemoryEventBus::new());
+        let dispatcher = CommandDispatcher::new(InMemoryEventStore::new(), bus.clone());
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+        dispatcher
+            .dispatch(tenant_id, item_id.0, "inventory.item", create_item(tenant_id, item_id, "Widget"), |_, id| {
+                InventoryItem::empty(InventoryItemId::new(id))
+            })
+            .unwrap();
+        let sub = bus.subscribe();
+
+        let preview = preview_adjust(&dispatcher, tenant_id, item_id, 5).unwrap();
+        assert_eq!(preview.events.len(), 1);
+        assert_eq!(preview.events[0].sequence_number, 2);
+        assert_eq!(preview.events[0].event_type, "inventory.item.stock_adjusted");
+        assert!(preview.warnings.is_empty());
+        assert_eq!(dispatcher.store.load_stream(tenant_id, item_id.0).unwrap().len(), 1);
+        assert!(sub.try_recv().is_err());
+
+        let committed = adjust_stock(&dispatcher, tenant_id, item_id).unwrap();
+        assert_eq!(
+            (committed[0].sequence_number, &committed[0].event_type),
+            (preview.events[0].sequence_number, &preview.events[0].event_type)
+        );
+    }
+
+    #[test]
+    fn preview_fails_exactly_like_dispatch() {
+        let dispatcher: GuardedDispatcher =
+            CommandDispatcher::new(InMemoryEventStore::new(), Arc::new(InMemoryEventBus::new()));
+        let tenant_id = TenantId::new();
+        let missing = InventoryItemId::new(AggregateId::new());
+
+        let previewed = preview_adjust(&dispatcher, tenant_id, missing, 1).unwrap_err();
+        let dispatched = adjust_stock(&dispatcher, tenant_id, missing).unwrap_err();
+        assert_eq!(format!("{previewed:?}"), format!("{dispatched:?}"));
+        assert!(dispatcher.store.load_stream(tenant_id, missing.0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn dispatches_inside_a_dry_run_are_previewed() {
+        let dispatcher: GuardedDispatcher =
+            CommandDispatcher::new(InMemoryEventStore::new(), Arc::new(InMemoryEventBus::new()));
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+
+        let (returned, preview) = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(with_dry_run(async {
+                assert!(is_dry_run());
+                dispatcher
+                    .dispatch(tenant_id, item_id.0, "inventory.item", create_item(tenant_id, item_id, "Widget"), |_, id| {
+                        InventoryItem::empty(InventoryItemId::new(id))
+                    })
+                    .unwrap()
+            }));
+
+        assert!(!is_dry_run());
+        assert_eq!(returned, preview.events);
+        assert_eq!(preview.events[0].event_type, "inventory.item.created");
+        assert!(dispatcher.store.load_stream(tenant_id, item_id.0).unwrap().is_empty());
+    }
+
+    /// Store that holds the held tenant's dispatches inside `load_stream` until released.
+    struct HeldStore {
+        inner: InMemoryEventStore,
+        held: TenantId,
+        entered: Mutex<std::sync::mpsc::Sender<()>>,
+        release: Mutex<std::sync::mpsc::Receiver<()>>,
+    }
+
+    impl EventStore for HeldStore {
+        fn append(
+            &self,
+            events: Vec<UncommittedEvent>,
+            expected_version: ExpectedVersion,
+        ) -> Result<Vec<StoredEvent>, EventStoreError> {
+            self.inner.append(events, expected_version)
+        }
+
+        fn load_stream(
+            &self,
+            tenant_id: TenantId,
+            aggregate_id: AggregateId,
+        ) -> Result<Vec<StoredEvent>, EventStoreError> {
+            if tenant_id == self.held {
+                self.entered.lock().unwrap().send(()).unwrap();
+                self.release.lock().unwrap().recv().unwrap();
+            }
+            self.inner.load_stream(tenant_id, aggregate_id)
+        }
+
+        fn tenant_stats(&self, tenant_id: TenantId) -> Result<crate::event_store::TenantStats, EventStoreError> {
+            self.inner.tenant_stats(tenant_id)
+        }
+    }
+
+    type HeldDispatcher = CommandDispatcher<HeldStore, InMemoryEventBus<EventEnvelope<JsonValue>>>;
+
+    /// Dispatcher under `limit` whose `held` tenant blocks; returns the entered/release ends.
+    fn limited(
+        limit: ConcurrencyLimit,
+        held: TenantId,
+    ) -> (HeldDispatcher, std::sync::mpsc::Receiver<()>, std::sync::mpsc::Sender<()>) {
+        let (entered_tx, entered_rx) = std::sync::mpsc::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel();
+        let store = HeldStore {
+            inner: InMemoryEventStore::new(),
+            held,
+            entered: Mutex::new(entered_tx),
+            release: Mutex::new(release_rx),
+        };
+        let dispatcher = CommandDispatcher::new(store, InMemoryEventBus::new()).with_concurrency_limit(limit);
+        (dispatcher, entered_rx, release_tx)
+    }
+
+    fn create_for(dispatcher: &HeldDispatcher, tenant_id: TenantId) -> Result<Vec<StoredEvent>, DispatchError> {
+        let item_id = InventoryItemId::new(AggregateId::new());
+        dispatcher.dispatch(tenant_id, item_id.0, "inventory.item", create_item(tenant_id, item_id, "Widget"), |_, id| {
+            InventoryItem::empty(InventoryItemId::new(id))
+        })
+    }
+
+    #[test]
+    fn concurrent_dispatches_under_one_idempotency_key_commit_once() {
+        let tenant_id = TenantId::new();
+        let (entered_tx, entered) = std::sync::mpsc::channel();
+        let (release, release_rx) = std::sync::mpsc::channel();
+        let store = HeldStore {
+            inner: InMemoryEventStore::new(),
+            held: tenant_id,
+            entered: Mutex::new(entered_tx),
+            release: Mutex::new(release_rx),
+        };
+        let dispatcher = CommandDispatcher::new(store, InMemoryEventBus::new())
+            .with_idempotency_log(Arc::new(IdempotencyLog::default()));
+        let item_id = InventoryItemId::new(AggregateId::new());
+        let key = Uuid::now_v7();
+        let command = create_item(tenant_id, item_id, "Widget");
+        let create = || {
+            dispatcher.dispatch_idempotent(
+                tenant_id,
+                item_id.0,
+                "inventory.item",
+                command.clone(),
+                |_, id| InventoryItem::empty(InventoryItemId::new(id)),
+                Some(key),
+            )
+        };
+
+        let first = std::thread::scope(|s| {
+            let first = s.spawn(create);
+            entered.recv().unwrap();
+            // The repeat is refused without reaching the store.
+            assert!(matches!(create(), Err(DispatchError::Concurrency(_))));
+            release.send(()).unwrap();
+            first.join().unwrap().unwrap()
+        });
+        assert_eq!(first.len(), 1);
+
+        // Once recorded, the repeat gets the first dispatch's events back.
+        release.send(()).unwrap();
+        assert_eq!(create().unwrap(), first);
+        assert_eq!(dispatcher.store.inner.load_stream(tenant_id, item_id.0).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn dispatches_beyond_the_global_limit_are_rejected() {
+        let (busy, other) = (TenantId::new(), TenantId::new());
+        let limit = ConcurrencyLimit {
+            max_in_flight: 1,
+            max_per_tenant: None,
+            overload: OverloadAction::Reject,
+        };
+        let (dispatcher, entered, release) = limited(limit, busy);
+
+        std::thread::scope(|s| {
+            let in_flight = s.spawn(|| create_for(&dispatcher, busy));
+            entered.recv().unwrap();
+            assert!(matches!(create_for(&dispatcher, other), Err(DispatchError::Overloaded(_))));
+            release.send(()).unwrap();
+            assert_eq!(in_flight.join().unwrap().unwrap().len(), 1);
+        });
+        // The slot is free again once the in-flight dispatch finishes.
+        assert_eq!(create_for(&dispatcher, other).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn per_tenant_limit_isolates_a_busy_tenant() {
+        let (busy, other) = (TenantId::new(), TenantId::new());
+        let limit = ConcurrencyLimit {
+            max_in_flight: 4,
+            max_per_tenant: Some(1),
+            overload: OverloadAction::Reject,
+        };
+        let (dispatcher, entered, release) = limited(limit, busy);
+
+        std::thread::scope(|s| {
+            let in_flight = s.spawn(|| create_for(&dispatcher, busy));
+            entered.recv().unwrap();
+            assert!(matches!(create_for(&dispatcher, busy), Err(DispatchError::Overloaded(_))));
+            assert_eq!(create_for(&dispatcher, other).unwrap().len(), 1);
+            release.send(()).unwrap();
+            in_flight.join().unwrap().unwrap();
+        });
+    }
+
+    #[test]
+    fn queued_dispatch_waits_for_a_free_slot() {
+        let (busy, other) = (TenantId::new(), TenantId::new());
+        let limit = ConcurrencyLimit {
+            max_in_flight: 1,
+            max_per_tenant: None,
+            overload: OverloadAction::Queue(Duration::from_secs(10)),
+        };
+        let (dispatcher, entered, release) = limited(limit, busy);
+
+        std::thread::scope(|s| {
+            let in_flight = s.spawn(|| create_for(&dispatcher, busy));
+            entered.recv().unwrap();
+            let queued = s.spawn(|| create_for(&dispatcher, other));
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(!queued.is_finished());
+            release.send(()).unwrap();
+            in_flight.join().unwrap().unwrap();
+            assert_eq!(queued.join().unwrap().unwrap().len(), 1);
+        });
+    }
+
+    #[test]
+    fn queued_dispatch_does_not_stall_the_runtime() {
+        let (busy, other) = (TenantId::new(), TenantId::new());
+        let limit = ConcurrencyLimit {
+            max_in_flight: 1,
+            max_per_tenant: None,
+            overload: OverloadAction::Queue(Duration::from_secs(10)),
+        };
+        let (dispatcher, entered, release) = limited(limit, busy);
+        let dispatcher = Arc::new(dispatcher);
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_time()
+            .build()
+            .unwrap();
+
+        std::thread::scope(|s| {
+            let in_flight = s.spawn(|| create_for(&dispatcher, busy));
+            entered.recv().unwrap();
+            runtime.block_on(async {
+                let queued = tokio::spawn({
+                    let dispatcher = dispatcher.clone();
+                    async move { create_for(&dispatcher, other) }
+                });
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                // The only worker is not stuck behind the queued dispatch.
+                let other_task = tokio::time::timeout(Duration::from_secs(1), tokio::spawn(async { 42 })).await;
+                assert_eq!(other_task.unwrap().unwrap(), 42);
+
+                release.send(()).unwrap();
+                assert_eq!(queued.await.unwrap().unwrap().len(), 1);
+            });
+            in_flight.join().unwrap().unwrap();
+        });
+    }
+
+    #[test]
+    fn queued_dispatch_gives_up_after_the_timeout() {
+        let (busy, other) = (TenantId::new(), TenantId::new());
+        let limit = ConcurrencyLimit {
+            max_in_flight: 1,
+            max_per_tenant: None,
+            overload: OverloadAction::Queue(Duration::from_millis(20)),
+        };
+        let (dispatcher, entered, release) = limited(limit, busy);
+
+        std::thread::scope(|s| {
+            let in_flight = s.spawn(|| create_for(&dispatcher, busy));
+            entered.recv().unwrap();
+            assert!(matches!(create_for(&dispatcher, other), Err(DispatchError::Overloaded(_))));
+            release.send(()).unwrap();
+            in_flight.join().unwrap().unwrap();
+        });
+    }
+}
@@ -0,0 +1,81 @@
+//! Server-side command enrichment.
+//!
+//! Fields the server owns (the time a command happened, ids of entities it creates,
+//! tenant defaults) should not be filled in by every handler. A [`CommandEnricher`]
+//! registered on the `CommandDispatcher` for a command type sets them right before the
+//! command is handled, from an [`EnrichContext`] carrying the dispatcher's clock and
+//! [`IdGenerator`]. Handlers then submit only what the client decided.
+//!
+//! Enrichment runs once per dispatch (not per conflict retry), so generated values stay
+//! stable across retries.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use forgeerp_core::{AggregateId, TenantId};
+
+/// Source of new aggregate/entity ids.
+pub trait IdGenerator: Send + Sync {
+    fn aggregate_id(&self) -> AggregateId;
+}
+
+/// Time-ordered UUIDv7 ids (the same ids `AggregateId::new` creates).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidV7Ids;
+
+impl IdGenerator for UuidV7Ids {
+    fn aggregate_id(&self) -> AggregateId {
+        AggregateId::new()
+    }
+}
+
+/// Server-derived facts available to enrichers.
+pub struct EnrichContext<'a> {
+    pub tenant_id: TenantId,
+    /// Aggregate the command is dispatched to.
+    pub aggregate_id: AggregateId,
+    /// Dispatcher clock time for this dispatch.
+    pub now: DateTime<Utc>,
+    pub ids: &'a dyn IdGenerator,
+}
+
+/// Fills server-owned fields of a command of type `C`.
+pub trait CommandEnricher<C>: Send + Sync {
+    fn enrich(&self, ctx: &EnrichContext<'_>, command: &mut C);
+}
+
+impl<C, F> CommandEnricher<C> for F
+where
+    F: Fn(&EnrichContext<'_>, &mut C) + Send + Sync,
+{
+    fn enrich(&self, ctx: &EnrichContext<'_>, command: &mut C) {
+        self(ctx, command)
+    }
+}
+
+/// Enrichers keyed by command type (at most one per type).
+#[derive(Default)]
+pub struct Enrichers {
+    by_command: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Enrichers {
+    /// Register the enricher for `C`, replacing any previous one.
+    pub fn insert<C: 'static>(&mut self, enricher: impl CommandEnricher<C> + 'static) {
+        let enricher: Arc<dyn CommandEnricher<C>> = Arc::new(enricher);
+        self.by_command.insert(TypeId::of::<C>(), Box::new(enricher));
+    }
+
+    /// Apply the enricher registered for `C`, if any.
+    pub fn apply<C: 'static>(&self, ctx: &EnrichContext<'_>, command: &mut C) {
+        if let Some(enricher) = self
+            .by_command
+            .get(&TypeId::of::<C>())
+            .and_then(|e| e.downcast_ref::<Arc<dyn CommandEnricher<C>>>())
+        {
+            enricher.enrich(ctx, command);
+        }
+    }
+}
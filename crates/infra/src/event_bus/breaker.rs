@@ -0,0 +1,456 @@
+//! Publish timeout and circuit breaker around an event bus.
+//!
+//! Events are appended before they are published, so a slow or unreachable bus must not
+//! hold command dispatch hostage. [`GuardedEventBus`] wraps any bus: each publish gets
+//! at most [`PublishBreakerConfig::timeout`], and after `failure_threshold` consecutive
+//! failures (errors or timeouts) the [`PublishBreaker`] opens and publishes fail at once
+//! for `open_for`. The first publish after that is a trial: success closes the breaker,
+//! failure opens it again.
+//!
+//! A failed publish surfaces as the store's or dispatcher's publish error, which hands
+//! back the committed events for relay. A timed-out publish keeps running in the
+//! background and may still deliver, so relaying it can duplicate (at-least-once).
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use forgeerp_events::{EventBus, Subscription};
+
+use crate::clock::{Clock, SystemClock};
+
+/// When the breaker opens and how long publishes may take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublishBreakerConfig {
+    /// Longest a single publish may take before it counts as failed (`None`: no limit).
+    pub timeout: Option<Duration>,
+    /// Consecutive failures that open the breaker (minimum 1).
+    pub failure_threshold: u32,
+    /// How long the breaker stays open before a trial publish.
+    pub open_for: Duration,
+}
+
+impl Default for PublishBreakerConfig {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            failure_threshold: 5,
+            open_for: Duration::from_secs(30),
+        }
+    }
+}
+
+impl PublishBreakerConfig {
+    /// Read `BUS_PUBLISH_TIMEOUT_MS` (unset or 0: no timeout),
+    /// `BUS_BREAKER_FAILURE_THRESHOLD` (default 5) and `BUS_BREAKER_OPEN_SECS` (default 30).
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.trim().parse::<u64>().ok());
+        let defaults = Self::default();
+        Self {
+            timeout: var("BUS_PUBLISH_TIMEOUT_MS").filter(|ms| *ms > 0).map(Duration::from_millis),
+            failure_threshold: var("BUS_BREAKER_FAILURE_THRESHOLD")
+                .filter(|n| *n > 0)
+                .map_or(defaults.failure_threshold, |n| n.min(u64::from(u32::MAX)) as u32),
+            open_for: var("BUS_BREAKER_OPEN_SECS").map_or(defaults.open_for, Duration::from_secs),
+        }
+    }
+}
+
+/// Breaker position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Publishing normally.
+    Closed,
+    /// Failing fast until the open period ends.
+    Open,
+    /// Open period over; the next publish is a trial.
+    HalfOpen,
+}
+
+impl BreakerState {
+    /// Gauge value for metrics (0 closed, 1 open, 2 half-open).
+    pub fn as_gauge(self) -> u8 {
+        match self {
+            BreakerState::Closed => 0,
+            BreakerState::Open => 1,
+            BreakerState::HalfOpen => 2,
+        }
+    }
+}
+
+/// Breaker counters for metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BreakerStats {
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    /// Times the breaker opened.
+    pub trips: u64,
+    /// Publishes that timed out.
+    pub timeouts: u64,
+    /// Publishes refused while open.
+    pub rejected: u64,
+}
+
+#[derive(Debug)]
+struct BreakerInner {
+    consecutive_failures: u32,
+    /// Set while open (or half-open, once passed).
+    open_until: Option<DateTime<Utc>>,
+    /// A half-open trial publish is in flight.
+    trial_in_flight: bool,
+    trips: u64,
+    timeouts: u64,
+    rejected: u64,
+}
+
+/// Shared breaker state of one bus.
+pub struct PublishBreaker {
+    config: PublishBreakerConfig,
+    clock: Arc<dyn Clock>,
+    inner: Mutex<BreakerInner>,
+}
+
+impl std::fmt::Debug for PublishBreaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PublishBreaker")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PublishBreaker {
+    pub fn new(config: PublishBreakerConfig) -> Self {
+        Self {
+            config,
+            clock: Arc::new(SystemClock),
+            inner: Mutex::new(BreakerInner {
+                consecutive_failures: 0,
+                open_until: None,
+                trial_in_flight: false,
+                trips: 0,
+                timeouts: 0,
+                rejected: 0,
+            }),
+        }
+    }
+
+    pub fn arc(config: PublishBreakerConfig) -> Arc<Self> {
+        Arc::new(Self::new(config))
+    }
+
+    /// Time source for the open period (defaults to the system clock).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn config(&self) -> PublishBreakerConfig {
+        self.config
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn state_of(&self, inner: &BreakerInner) -> BreakerState {
+        match inner.open_until {
+            None => BreakerState::Closed,
+            Some(until) if self.clock.now() < until => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.state_of(&self.lock())
+    }
+
+    pub fn stats(&self) -> BreakerStats {
+        let inner = self.lock();
+        BreakerStats {
+            state: self.state_of(&inner),
+            consecutive_failures: inner.consecutive_failures,
+            trips: inner.trips,
+            timeouts: inner.timeouts,
+            rejected: inner.rejected,
+        }
+    }
+
+    /// Whether a publish may go ahead now; refusals are counted.
+    fn admit(&self) -> bool {
+        let mut inner = self.lock();
+        let admitted = match self.state_of(&inner) {
+            BreakerState::Closed => true,
+            BreakerState::Open => false,
+            // One trial at a time; the rest keep failing fast until it settles.
+            BreakerState::HalfOpen => !std::mem::replace(&mut inner.trial_in_flight, true),
+        };
+        if !admitted {
+            inner.rejected += 1;
+        }
+        admitted
+    }
+
+    fn record_success(&self) {
+        let mut inner = self.lock();
+        inner.consecutive_failures = 0;
+        inner.open_until = None;
+        inner.trial_in_flight = false;
+    }
+
+    fn record_failure(&self, timed_out: bool) {
+        let mut inner = self.lock();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        if timed_out {
+            inner.timeouts += 1;
+        }
+        let trial_failed = std::mem::replace(&mut inner.trial_in_flight, false);
+        if trial_failed || inner.consecutive_failures >= self.config.failure_threshold.max(1) {
+            if inner.open_until.is_none() || trial_failed {
+                inner.trips += 1;
+            }
+            let open_for = chrono::Duration::from_std(self.config.open_for).unwrap_or(chrono::Duration::MAX);
+            inner.open_until = Some(self.clock.now() + open_for);
+        }
+    }
+}
+
+/// Why a guarded publish failed.
+#[derive(Debug)]
+pub enum GuardedPublishError<E> {
+    /// The breaker is open; the bus was not called.
+    Open,
+    /// The bus did not answer within the timeout.
+    TimedOut(Duration),
+    /// The bus reported an error.
+    Bus(E),
+}
+
+/// Event bus wrapper applying a [`PublishBreaker`] (and its timeout) to every publish.
+///
+/// Subscriptions go straight to the inner bus.
+pub struct GuardedEventBus<B> {
+    inner: Arc<B>,
+    breaker: Arc<PublishBreaker>,
+}
+
+impl<B> std::fmt::Debug for GuardedEventBus<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GuardedEventBus")
+            .field("breaker", &self.breaker)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B> Clone for GuardedEventBus<B> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            breaker: self.breaker.clone(),
+        }
+    }
+}
+
+impl<B> GuardedEventBus<B> {
+    pub fn new(inner: Arc<B>, breaker: Arc<PublishBreaker>) -> Self {
+        Self { inner, breaker }
+    }
+
+    pub fn inner(&self) -> &Arc<B> {
+        &self.inner
+    }
+
+    pub fn breaker(&self) -> &Arc<PublishBreaker> {
+        &self.breaker
+    }
+
+    /// Run `publish` under the breaker. With a timeout it runs on its own thread, which
+    /// is left to finish in the background if the timeout passes.
+    fn guarded<E, F>(&self, publish: F) -> Result<(), GuardedPublishError<E>>
+    where
+        B: Send + Sync + 'static,
+        E: Send + 'static,
+        F: FnOnce(&B) -> Result<(), E> + Send + 'static,
+    {
+        if !self.breaker.admit() {
+            return Err(GuardedPublishError::Open);
+        }
+        let result = match self.breaker.config.timeout {
+            None => publish(&self.inner).map_err(GuardedPublishError::Bus),
+            Some(timeout) => {
+                let (tx, rx) = std::sync::mpsc::channel();
+                let inner = self.inner.clone();
+                std::thread::spawn(move || {
+                    let _ = tx.send(publish(&inner));
+                });
+                match rx.recv_timeout(timeout) {
+                    Ok(result) => result.map_err(GuardedPublishError::Bus),
+                    Err(_) => Err(GuardedPublishError::TimedOut(timeout)),
+                }
+            }
+        };
+        match &result {
+            Ok(()) => self.breaker.record_success(),
+            Err(e) => self.breaker.record_failure(matches!(e, GuardedPublishError::TimedOut(_))),
+        }
+        result
+    }
+}
+
+impl<M, B> EventBus<M> for GuardedEventBus<B>
+where
+    M: Send + 'static,
+    B: EventBus<M> + 'static,
+{
+    type Error = GuardedPublishError<B::Error>;
+
+    fn publish(&self, message: M) -> Result<(), Self::Error> {
+        self.guarded(move |bus: &B| bus.publish(message))
+    }
+
+    fn publish_batch(&self, messages: Vec<M>) -> Result<(), Self::Error> {
+        self.guarded(move |bus: &B| bus.publish_batch(messages))
+    }
+
+    fn publishes_batches_atomically(&self) -> bool {
+        self.inner.publishes_batches_atomically()
+    }
+
+    fn subscribe(&self) -> Subscription<M> {
+        self.inner.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::Instant;
+
+    use forgeerp_core::{AggregateId, TenantId};
+    use forgeerp_events::{EventEnvelope, InMemoryEventBus};
+    use forgeerp_inventory::{CreateItem, InventoryCommand, InventoryItem, InventoryItemId};
+    use serde_json::Value as JsonValue;
+
+    use crate::clock::FixedClock;
+    use crate::command_dispatcher::{CommandDispatcher, DispatchError};
+    use crate::event_store::{EventStore, InMemoryEventStore};
+
+    type Envelope = EventEnvelope<JsonValue>;
+
+    /// Bus that takes `delay_ms` per publish (0: healthy), then delivers.
+    #[derive(Default)]
+    struct SlowBus {
+        delay_ms: AtomicU64,
+        delivered: InMemoryEventBus<Envelope>,
+    }
+
+    impl EventBus<Envelope> for SlowBus {
+        type Error = String;
+
+        fn publish(&self, message: Envelope) -> Result<(), Self::Error> {
+            std::thread::sleep(Duration::from_millis(self.delay_ms.load(Ordering::SeqCst)));
+            self.delivered.publish(message).map_err(|e| format!("{e:?}"))
+        }
+
+        fn subscribe(&self) -> Subscription<Envelope> {
+            self.delivered.subscribe()
+        }
+    }
+
+    fn config() -> PublishBreakerConfig {
+        PublishBreakerConfig {
+            timeout: Some(Duration::from_millis(20)),
+            failure_threshold: 3,
+            open_for: Duration::from_secs(30),
+        }
+    }
+
+    fn envelope() -> Envelope {
+        EventEnvelope::new(
+            uuid::Uuid::now_v7(),
+            TenantId::new(),
+            AggregateId::new(),
+            "test.aggregate".to_string(),
+            1,
+            serde_json::json!({}),
+        )
+    }
+
+    #[test]
+    fn slow_bus_trips_the_breaker_after_the_threshold_and_recovers_after_a_trial() {
+        let clock = FixedClock::arc(Utc::now());
+        let breaker = Arc::new(PublishBreaker::new(config()).with_clock(clock.clone()));
+        let slow = Arc::new(SlowBus::default());
+        slow.delay_ms.store(500, Ordering::SeqCst);
+        let bus = GuardedEventBus::new(slow.clone(), breaker.clone());
+
+        for _ in 0..2 {
+            assert!(matches!(bus.publish(envelope()), Err(GuardedPublishError::TimedOut(_))));
+            assert_eq!(breaker.state(), BreakerState::Closed);
+        }
+        assert!(matches!(bus.publish(envelope()), Err(GuardedPublishError::TimedOut(_))));
+        assert_eq!(breaker.state(), BreakerState::Open);
+
+        // Open: refused without touching the bus.
+        let started = Instant::now();
+        assert!(matches!(bus.publish(envelope()), Err(GuardedPublishError::Open)));
+        assert!(started.elapsed() < Duration::from_millis(20));
+        let stats = breaker.stats();
+        assert_eq!((stats.trips, stats.timeouts, stats.rejected), (1, 3, 1));
+
+        // After the open period a failing trial re-opens it; a good one closes it.
+        clock.set(clock.now() + chrono::Duration::seconds(31));
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert!(matches!(bus.publish(envelope()), Err(GuardedPublishError::TimedOut(_))));
+        assert_eq!((breaker.state(), breaker.stats().trips), (BreakerState::Open, 2));
+        clock.set(clock.now() + chrono::Duration::seconds(31));
+        slow.delay_ms.store(0, Ordering::SeqCst);
+        bus.publish(envelope()).unwrap();
+        assert_eq!(breaker.stats().state, BreakerState::Closed);
+        assert_eq!(breaker.stats().consecutive_failures, 0);
+    }
+
+    #[test]
+    fn dispatch_commits_and_returns_events_while_the_breaker_is_open() {
+        let breaker = PublishBreaker::arc(PublishBreakerConfig {
+            failure_threshold: 1,
+            ..config()
+        });
+        let slow = Arc::new(SlowBus::default());
+        slow.delay_ms.store(500, Ordering::SeqCst);
+        let store = Arc::new(InMemoryEventStore::new());
+        let dispatcher = CommandDispatcher::new(store.clone(), GuardedEventBus::new(slow, breaker.clone()));
+        let tenant_id = TenantId::new();
+        let create = |item_id: InventoryItemId| {
+            dispatcher.dispatch::<InventoryItem>(
+                tenant_id,
+                item_id.0,
+                "inventory.item",
+                InventoryCommand::CreateItem(CreateItem {
+                    tenant_id,
+                    item_id,
+                    name: "Widget".to_string(),
+                    occurred_at: Utc::now(),
+                }),
+                |_, id| InventoryItem::empty(InventoryItemId::new(id)),
+            )
+        };
+
+        // The timeout trips the breaker; the next dispatch fails fast.
+        let first = InventoryItemId::new(AggregateId::new());
+        assert!(matches!(create(first), Err(DispatchError::Publish { .. })));
+        assert_eq!(breaker.state(), BreakerState::Open);
+
+        let second = InventoryItemId::new(AggregateId::new());
+        let started = Instant::now();
+        let err = create(second).unwrap_err();
+        assert!(started.elapsed() < Duration::from_millis(20));
+        let committed = err.committed().expect("publish errors carry the committed events").to_vec();
+        assert_eq!(committed.len(), 1);
+        assert_eq!(store.load_stream(tenant_id, second.0).unwrap(), committed);
+        assert_eq!(store.load_stream(tenant_id, first.0).unwrap().len(), 1);
+    }
+}
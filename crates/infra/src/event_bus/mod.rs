@@ -1,7 +1,12 @@
 //! Infrastructure event bus implementations.
 //!
 //! The core event bus abstraction lives in `forgeerp-events` as pure mechanics.
-//! This module provides infrastructure-backed implementations (e.g. Redis).
+//! This module provides infrastructure-backed implementations (e.g. Redis), and a
+//! [`GuardedEventBus`] wrapper adding a publish timeout and circuit breaker to any of them.
+
+pub mod breaker;
+
+pub use breaker::{BreakerState, BreakerStats, GuardedEventBus, GuardedPublishError, PublishBreaker, PublishBreakerConfig};
 
 #[cfg(feature = "redis")]
 pub mod redis_pubsub;
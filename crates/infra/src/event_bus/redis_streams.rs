@@ -88,6 +88,20 @@ impl RedisStreamsEventBus {
         })
     }
 
+    /// `PING` the server over a fresh connection, giving up after `timeout`.
+    pub fn ping(&self, timeout: Duration) -> Result<(), RedisStreamsError> {
+        let mut conn = self
+            .client
+            .get_connection_with_timeout(timeout)
+            .map_err(|e| RedisStreamsError::Connection(e.to_string()))?;
+        conn.set_read_timeout(Some(timeout))
+            .map_err(|e| RedisStreamsError::Connection(e.to_string()))?;
+        redis::cmd("PING")
+            .query::<String>(&mut conn)
+            .map_err(|e| RedisStreamsError::Command(e.to_string()))?;
+        Ok(())
+    }
+
     /// Ensure a consumer group exists (idempotent).
     ///
     /// Consumer groups enable multiple consumers to process the same stream,
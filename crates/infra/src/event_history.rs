@@ -0,0 +1,166 @@
+//! Human-readable aggregate timelines.
+//!
+//! Raw stored events are an operator concern (`/admin/events`). For end-user activity
+//! logs, the [`EventHistoryRegistry`] decodes each stored event into its typed domain
+//! event and renders a short description ("Created 'Widget'", "Stock +10"). Renderers
+//! are registered per aggregate type; events of unregistered types, or that no longer
+//! decode, fall back to their event type so a timeline never has holes.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+use forgeerp_inventory::{DEFAULT_LOCATION, InventoryEvent};
+
+use crate::event_store::StoredEvent;
+
+/// Decodes a stored payload into its typed event and describes it; `None` if it does not decode.
+pub type HistoryDescriber = fn(&JsonValue) -> Option<String>;
+
+/// One rendered timeline entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HistoryEntry {
+    pub event_id: Uuid,
+    pub sequence_number: u64,
+    /// e.g. `inventory.item.stock_adjusted`.
+    pub event_type: String,
+    pub occurred_at: DateTime<Utc>,
+    /// Readable description, e.g. `Stock +10`.
+    pub summary: String,
+}
+
+/// Describers keyed by aggregate type.
+#[derive(Debug, Clone)]
+pub struct EventHistoryRegistry {
+    describers: HashMap<&'static str, HistoryDescriber>,
+}
+
+impl EventHistoryRegistry {
+    /// Empty registry (every entry falls back to its event type).
+    pub fn new() -> Self {
+        Self {
+            describers: HashMap::new(),
+        }
+    }
+
+    /// Register (or replace) the describer for an aggregate type.
+    pub fn register(mut self, aggregate_type: &'static str, describe: HistoryDescriber) -> Self {
+        self.describers.insert(aggregate_type, describe);
+        self
+    }
+
+    /// Render one stored event.
+    pub fn render(&self, event: &StoredEvent) -> HistoryEntry {
+        let summary = self
+            .describers
+            .get(event.aggregate_type.as_str())
+            .and_then(|describe| describe(&event.payload))
+            .unwrap_or_else(|| event.event_type.clone());
+        HistoryEntry {
+            event_id: event.event_id,
+            sequence_number: event.sequence_number,
+            event_type: event.event_type.clone(),
+            occurred_at: event.occurred_at,
+            summary,
+        }
+    }
+
+    /// Render a stream page, keeping its order.
+    pub fn render_all(&self, events: &[StoredEvent]) -> Vec<HistoryEntry> {
+        events.iter().map(|e| self.render(e)).collect()
+    }
+}
+
+impl Default for EventHistoryRegistry {
+    /// Registry with describers for the aggregates that expose a history.
+    fn default() -> Self {
+        Self::new().register("inventory.item", describe_inventory_event)
+    }
+}
+
+fn describe_inventory_event(payload: &JsonValue) -> Option<String> {
+    let event: InventoryEvent = serde_json::from_value(payload.clone()).ok()?;
+    Some(match event {
+        InventoryEvent::ItemCreated(e) => format!("Created '{}'", e.name),
+        InventoryEvent::ItemRenamed(e) => format!("Renamed to '{}'", e.name),
+        InventoryEvent::StockAdjusted(e) if e.location_id == DEFAULT_LOCATION => format!("Stock {:+}", e.delta),
+        InventoryEvent::StockAdjusted(e) => format!("Stock {:+} at {}", e.delta, e.location_id),
+        InventoryEvent::StockReserved(e) => format!("Reserved {}", e.quantity),
+        InventoryEvent::ReservationReleased(e) => format!("Released reservation of {}", e.quantity),
+        InventoryEvent::ReorderPointSet(e) => match e.reorder_point {
+            Some(point) => format!("Reorder point set to {point}"),
+            None => "Reorder point cleared".to_string(),
+        },
+        InventoryEvent::StockFellBelowReorderPoint(e) => {
+            format!("Stock {} fell below reorder point {}", e.current_stock, e.reorder_point)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use forgeerp_core::{AggregateId, TenantId};
+    use forgeerp_events::{EventEnvelope, InMemoryEventBus};
+    use forgeerp_inventory::{AdjustStock, CreateItem, InventoryCommand, InventoryItem, InventoryItemId};
+
+    use crate::command_dispatcher::CommandDispatcher;
+    use crate::event_store::{EventStore, InMemoryEventStore};
+
+    #[test]
+    fn create_and_adjustments_render_as_an_ordered_readable_timeline() {
+        let store = Arc::new(InMemoryEventStore::new());
+        let dispatcher = CommandDispatcher::new(store.clone(), Arc::new(InMemoryEventBus::<EventEnvelope<JsonValue>>::new()));
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+        let dispatch = |command| {
+            dispatcher
+                .dispatch::<InventoryItem>(tenant_id, item_id.0, "inventory.item", command, |_, id| {
+                    InventoryItem::empty(InventoryItemId::new(id))
+                })
+                .unwrap()
+        };
+        let adjust = |delta| {
+            InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id,
+                item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta,
+                occurred_at: Utc::now(),
+            })
+        };
+
+        dispatch(InventoryCommand::CreateItem(CreateItem {
+            tenant_id,
+            item_id,
+            name: "Widget".to_string(),
+            occurred_at: Utc::now(),
+        }));
+        dispatch(adjust(10));
+        dispatch(adjust(-5));
+
+        let events = store.load_stream(tenant_id, item_id.0).unwrap();
+        let history = EventHistoryRegistry::default().render_all(&events);
+        let rendered: Vec<_> = history
+            .iter()
+            .map(|e| (e.sequence_number, e.event_type.as_str(), e.summary.as_str()))
+            .collect();
+        assert_eq!(
+            rendered,
+            [
+                (1, "inventory.item.created", "Created 'Widget'"),
+                (2, "inventory.item.stock_adjusted", "Stock +10"),
+                (3, "inventory.item.stock_adjusted", "Stock -5"),
+            ]
+        );
+
+        // Without a describer the event type stands in.
+        let bare = EventHistoryRegistry::new().render(&events[1]);
+        assert_eq!(bare.summary, "inventory.item.stock_adjusted");
+    }
+}
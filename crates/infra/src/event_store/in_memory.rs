@@ -1,10 +1,13 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::RwLock;
 
+use chrono::Utc;
+
 use forgeerp_core::{AggregateId, ExpectedVersion, TenantId};
 
-use super::query::{EventFilter, EventQuery, EventQueryResult, Pagination};
-use super::r#trait::{EventStore, EventStoreError, StoredEvent, UncommittedEvent};
+use super::query::{event_order, EventCursor, EventFilter, EventPage, EventQuery, EventQueryResult, Pagination};
+use super::r#trait::{EventStore, EventStoreError, GlobalPosition, Snapshot, StoredEvent, TenantStats, UncommittedEvent};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 struct StreamKey {
@@ -18,6 +21,11 @@ struct StreamKey {
 #[derive(Debug, Default)]
 pub struct InMemoryEventStore {
     streams: RwLock<HashMap<StreamKey, Vec<StoredEvent>>>,
+    /// Last global position handed out; only advanced under the `streams` write guard,
+    /// so positions follow commit order.
+    last_global_position: AtomicU64,
+    /// Latest snapshot per stream.
+    snapshots: RwLock<HashMap<StreamKey, Snapshot>>,
 }
 
 impl InMemoryEventStore {
@@ -28,22 +36,41 @@ impl InMemoryEventStore {
     fn current_version(stream: &[StoredEvent]) -> u64 {
         stream.last().map(|e| e.sequence_number).unwrap_or(0)
     }
-}
 
-impl EventStore for InMemoryEventStore {
-    fn append(
-        &self,
-        events: Vec<UncommittedEvent>,
-        expected_version: ExpectedVersion,
-    ) -> Result<Vec<StoredEvent>, EventStoreError> {
-        if events.is_empty() {
-            return Ok(vec![]);
-        }
+    /// The tenant's events matching `filter`, in `query_events` order.
+    fn matching_events(&self, tenant_id: TenantId, filter: &EventFilter) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let streams = self
+            .streams
+            .read()
+            .map_err(|_| EventStoreError::InvalidAppend("lock poisoned".to_string()))?;
 
-        // All events must target the same tenant + aggregate stream.
+        let mut filtered: Vec<StoredEvent> = streams
+            .iter()
+            .filter(|(key, _)| key.tenant_id == tenant_id)
+            .flat_map(|(_, stream)| stream.iter())
+            .filter(|e| {
+                filter.aggregate_id.is_none_or(|id| e.aggregate_id == id)
+                    && filter.aggregate_type.as_ref().is_none_or(|t| e.aggregate_type == *t)
+                    && filter.event_type.as_ref().is_none_or(|t| e.event_type == *t)
+                    && filter.occurred_after.is_none_or(|after| e.occurred_at >= after)
+                    && filter.occurred_before.is_none_or(|before| e.occurred_at <= before)
+                    && filter
+                        .business_key
+                        .as_ref()
+                        .is_none_or(|key| e.metadata.get(forgeerp_events::BUSINESS_KEY) == Some(key))
+            })
+            .cloned()
+            .collect();
+
+        filtered.sort_by(event_order);
+        Ok(filtered)
+    }
+
+    /// All events of one batch must target the same tenant + aggregate stream.
+    fn validate_batch(events: &[UncommittedEvent]) -> Result<StreamKey, EventStoreError> {
         let tenant_id = events[0].tenant_id;
         let aggregate_id = events[0].aggregate_id;
-        let aggregate_type = events[0].aggregate_type.clone();
+        let aggregate_type = &events[0].aggregate_type;
 
         for (idx, e) in events.iter().enumerate() {
             if e.tenant_id != tenant_id {
@@ -56,60 +83,114 @@ impl EventStore for InMemoryEventStore {
                     "batch contains multiple aggregate_ids (index {idx})"
                 )));
             }
-            if e.aggregate_type != aggregate_type {
+            if &e.aggregate_type != aggregate_type {
                 return Err(EventStoreError::AggregateTypeMismatch(format!(
                     "batch contains multiple aggregate_types (index {idx})"
                 )));
             }
         }
 
-        let key = StreamKey {
+        Ok(StreamKey {
             tenant_id,
             aggregate_id,
-        };
+        })
+    }
+}
+
+impl EventStore for InMemoryEventStore {
+    fn append(
+        &self,
+        events: Vec<UncommittedEvent>,
+        expected_version: ExpectedVersion,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        self.append_streams(vec![(events, expected_version)])
+    }
 
+    fn append_streams(
+        &self,
+        batches: Vec<(Vec<UncommittedEvent>, ExpectedVersion)>,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let batches: Vec<_> = batches.into_iter().filter(|(events, _)| !events.is_empty()).collect();
+        if batches.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut keys = Vec::with_capacity(batches.len());
+        for (events, _) in &batches {
+            let key = Self::validate_batch(events)?;
+            if keys.iter().any(|(k, _)| *k == key) {
+                return Err(EventStoreError::InvalidAppend(
+                    "multi-stream append targets the same stream twice".to_string(),
+                ));
+            }
+            keys.push((key, events[0].aggregate_type.clone()));
+        }
+
+        // Version check, type check and insert happen under one write guard (never held
+        // across an `.await`), so two concurrent appends against the same expected
+        // version cannot both succeed: the loser observes the winner's events.
         let mut streams = self
             .streams
             .write()
             .map_err(|_| EventStoreError::InvalidAppend("lock poisoned".to_string()))?;
 
-        let stream = streams.entry(key).or_default();
-        let current = Self::current_version(stream);
+        // Check every stream before touching any, so a rejected batch writes nothing.
+        let mut currents = Vec::with_capacity(batches.len());
+        for ((key, aggregate_type), (_, expected_version)) in keys.iter().zip(&batches) {
+            let (current, existing_type) = match streams.get(key) {
+                Some(stream) => (
+                    Self::current_version(stream),
+                    stream.first().map(|e| e.aggregate_type.as_str()),
+                ),
+                None => (0, None),
+            };
 
-        if !expected_version.matches(current) {
-            return Err(EventStoreError::Concurrency(format!(
-                "expected {expected_version:?}, found {current}"
-            )));
-        }
+            if !expected_version.matches(current) {
+                return Err(EventStoreError::Concurrency(format!(
+                    "expected {expected_version:?}, found {current}"
+                )));
+            }
 
-        // Enforce aggregate type stability across the stream.
-        if let Some(existing) = stream.first() {
-            if existing.aggregate_type != aggregate_type {
+            // Enforce aggregate type stability across the stream.
+            if let Some(existing_type) = existing_type
+                && existing_type != aggregate_type
+            {
                 return Err(EventStoreError::AggregateTypeMismatch(format!(
                     "stream aggregate_type is '{}', attempted append with '{}'",
-                    existing.aggregate_type, aggregate_type
+                    existing_type, aggregate_type
                 )));
             }
+            currents.push(current);
         }
 
-        // Assign sequence numbers and append (append-only).
-        let mut next = current + 1;
-        let mut committed = Vec::with_capacity(events.len());
-        for e in events {
-            let stored = StoredEvent {
-                event_id: e.event_id,
-                tenant_id: e.tenant_id,
-                aggregate_id: e.aggregate_id,
-                aggregate_type: e.aggregate_type,
-                sequence_number: next,
-                event_type: e.event_type,
-                event_version: e.event_version,
-                occurred_at: e.occurred_at,
-                payload: e.payload,
-            };
-            next += 1;
-            stream.push(stored.clone());
-            committed.push(stored);
+        let created_at = Utc::now();
+        let mut committed = Vec::new();
+        for (((key, _), (events, _)), current) in keys.into_iter().zip(batches).zip(currents) {
+            // Only create the stream once the append is known to succeed, so rejected
+            // appends never leave empty streams behind.
+            let stream = streams.entry(key).or_default();
+
+            // Assign sequence numbers and append (append-only).
+            for (offset, e) in events.into_iter().enumerate() {
+                let stored = StoredEvent {
+                    event_id: e.event_id,
+                    tenant_id: e.tenant_id,
+                    aggregate_id: e.aggregate_id,
+                    aggregate_type: e.aggregate_type,
+                    sequence_number: current + 1 + offset as u64,
+                    global_position: GlobalPosition(self.last_global_position.fetch_add(1, Ordering::Relaxed) + 1),
+                    event_type: e.event_type,
+                    event_version: e.event_version,
+                    occurred_at: e.occurred_at,
+                    created_at,
+                    payload: e.payload,
+                    metadata: e.metadata,
+                    correlation_id: e.correlation_id,
+                    causation_id: e.causation_id,
+                };
+                stream.push(stored.clone());
+                committed.push(stored);
+            }
         }
 
         Ok(committed)
@@ -132,73 +213,154 @@ impl EventStore for InMemoryEventStore {
 
         Ok(streams.get(&key).cloned().unwrap_or_default())
     }
-}
 
-#[async_trait::async_trait]
-impl EventQuery for InMemoryEventStore {
-    async fn query_events(
+    fn load_stream_after(
         &self,
         tenant_id: TenantId,
-        filter: EventFilter,
-        pagination: Pagination,
-    ) -> Result<EventQueryResult, EventStoreError> {
-        // Use spawn_blocking since we're accessing RwLock (blocking operation)
-        let streams = {
-            let guard = self
-                .streams
-                .read()
-                .map_err(|_| EventStoreError::InvalidAppend("lock poisoned".to_string()))?;
-            guard.clone()
+        aggregate_id: AggregateId,
+        after_version: u64,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let key = StreamKey {
+            tenant_id,
+            aggregate_id,
         };
 
-        // Collect all events for the tenant
-        let mut all_events: Vec<StoredEvent> = Vec::new();
+        let streams = self
+            .streams
+            .read()
+            .map_err(|_| EventStoreError::InvalidAppend("lock poisoned".to_string()))?;
+
+        Ok(streams
+            .get(&key)
+            .map(|stream| stream.iter().filter(|e| e.sequence_number > after_version).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    fn load_snapshot(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+    ) -> Result<Option<Snapshot>, EventStoreError> {
+        let key = StreamKey {
+            tenant_id,
+            aggregate_id,
+        };
+
+        let snapshots = self
+            .snapshots
+            .read()
+            .map_err(|_| EventStoreError::InvalidAppend("lock poisoned".to_string()))?;
+
+        Ok(snapshots.get(&key).cloned())
+    }
+
+    fn store_snapshot(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        snapshot: &Snapshot,
+    ) -> Result<(), EventStoreError> {
+        if snapshot.tenant_id != tenant_id {
+            return Err(EventStoreError::TenantIsolation(format!(
+                "snapshot tenant_id mismatch: expected {}, got {}",
+                tenant_id.as_uuid(),
+                snapshot.tenant_id.as_uuid()
+            )));
+        }
+        if snapshot.aggregate_id != aggregate_id {
+            return Err(EventStoreError::InvalidAppend(format!(
+                "snapshot aggregate_id mismatch: expected {}, got {}",
+                aggregate_id.as_uuid(),
+                snapshot.aggregate_id.as_uuid()
+            )));
+        }
+
+        let mut snapshots = self
+            .snapshots
+            .write()
+            .map_err(|_| EventStoreError::InvalidAppend("lock poisoned".to_string()))?;
+
+        // Keep the latest; an older snapshot arriving late never replaces a newer one.
+        let key = StreamKey {
+            tenant_id,
+            aggregate_id,
+        };
+        if snapshots.get(&key).is_none_or(|current| current.version <= snapshot.version) {
+            snapshots.insert(key, snapshot.clone());
+        }
+        Ok(())
+    }
+
+    fn tenant_stats(&self, tenant_id: TenantId) -> Result<TenantStats, EventStoreError> {
+        let streams = self
+            .streams
+            .read()
+            .map_err(|_| EventStoreError::InvalidAppend("lock poisoned".to_string()))?;
+
+        let mut stats = TenantStats::default();
         for (key, stream) in streams.iter() {
-            if key.tenant_id == tenant_id {
-                all_events.extend(stream.iter().cloned());
+            if key.tenant_id != tenant_id || stream.is_empty() {
+                continue;
+            }
+            stats.aggregate_count += 1;
+            for e in stream {
+                stats.event_count += 1;
+                // Serialized payload plus type strings; ids/timestamps are fixed-size.
+                stats.approx_bytes += (e.payload.to_string().len()
+                    + e.aggregate_type.len()
+                    + e.event_type.len()) as u64;
+                stats.oldest = Some(stats.oldest.map_or(e.occurred_at, |t| t.min(e.occurred_at)));
+                stats.newest = Some(stats.newest.map_or(e.occurred_at, |t| t.max(e.occurred_at)));
             }
         }
+        Ok(stats)
+    }
 
-        // Apply filters
-        let mut filtered: Vec<StoredEvent> = all_events
-            .into_iter()
-            .filter(|e| {
-                if let Some(agg_id) = filter.aggregate_id {
-                    if e.aggregate_id != agg_id {
-                        return false;
-                    }
-                }
-                if let Some(ref agg_type) = filter.aggregate_type {
-                    if e.aggregate_type != *agg_type {
-                        return false;
-                    }
-                }
-                if let Some(ref evt_type) = filter.event_type {
-                    if e.event_type != *evt_type {
-                        return false;
-                    }
-                }
-                if let Some(after) = filter.occurred_after {
-                    if e.occurred_at < after {
-                        return false;
-                    }
-                }
-                if let Some(before) = filter.occurred_before {
-                    if e.occurred_at > before {
-                        return false;
-                    }
-                }
-                true
-            })
+    fn stream_ids(&self) -> Result<Vec<(TenantId, AggregateId)>, EventStoreError> {
+        let streams = self
+            .streams
+            .read()
+            .map_err(|_| EventStoreError::InvalidAppend("lock poisoned".to_string()))?;
+
+        Ok(streams
+            .iter()
+            .filter(|(_, stream)| !stream.is_empty())
+            .map(|(key, _)| (key.tenant_id, key.aggregate_id))
+            .collect())
+    }
+
+    fn read_all(
+        &self,
+        tenant_id: TenantId,
+        after: Option<GlobalPosition>,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let streams = self
+            .streams
+            .read()
+            .map_err(|_| EventStoreError::InvalidAppend("lock poisoned".to_string()))?;
+
+        let after = after.unwrap_or_default();
+        let mut events: Vec<StoredEvent> = streams
+            .iter()
+            .filter(|(key, _)| key.tenant_id == tenant_id)
+            .flat_map(|(_, stream)| stream.iter().filter(|e| e.global_position > after).cloned())
             .collect();
+        events.sort_by_key(|e| e.global_position);
+        events.truncate(limit);
+        Ok(events)
+    }
+}
 
-        // Sort by occurred_at (descending), then sequence_number (ascending)
-        filtered.sort_by(|a, b| {
-            match b.occurred_at.cmp(&a.occurred_at) {
-                std::cmp::Ordering::Equal => a.sequence_number.cmp(&b.sequence_number),
-                other => other,
-            }
-        });
+#[async_trait::async_trait]
+impl EventQuery for InMemoryEventStore {
+    async fn query_events(
+        &self,
+        tenant_id: TenantId,
+        filter: EventFilter,
+        pagination: Pagination,
+    ) -> Result<EventQueryResult, EventStoreError> {
+        let filtered = self.matching_events(tenant_id, &filter)?;
 
         let total = filtered.len() as u64;
 
@@ -216,6 +378,34 @@ impl EventQuery for InMemoryEventStore {
         })
     }
 
+    async fn query_events_after(
+        &self,
+        tenant_id: TenantId,
+        filter: EventFilter,
+        after: Option<EventCursor>,
+        limit: u32,
+    ) -> Result<EventPage, EventStoreError> {
+        let mut events: Vec<StoredEvent> = self
+            .matching_events(tenant_id, &filter)?
+            .into_iter()
+            .filter(|e| after.is_none_or(|c| c.precedes(e)))
+            .take(limit as usize + 1)
+            .collect();
+
+        let next_cursor = if events.len() > limit as usize {
+            events.truncate(limit as usize);
+            events.last().map(EventCursor::at)
+        } else {
+            None
+        };
+
+        Ok(EventPage {
+            events,
+            limit,
+            next_cursor,
+        })
+    }
+
     async fn get_aggregate_events(
         &self,
         tenant_id: TenantId,
@@ -245,6 +435,50 @@ impl EventQuery for InMemoryEventStore {
         })
     }
 
+    async fn get_events_for_aggregates(
+        &self,
+        tenant_id: TenantId,
+        aggregate_ids: &[AggregateId],
+        pagination: Option<Pagination>,
+    ) -> Result<EventQueryResult, EventStoreError> {
+        let pagination = pagination.unwrap_or_default();
+
+        let mut ids: Vec<AggregateId> = aggregate_ids.to_vec();
+        ids.sort_by_key(|id| *id.as_uuid());
+        ids.dedup();
+
+        let mut all_events: Vec<StoredEvent> = Vec::new();
+        {
+            let guard = self
+                .streams
+                .read()
+                .map_err(|_| EventStoreError::InvalidAppend("lock poisoned".to_string()))?;
+            for aggregate_id in ids {
+                if let Some(stream) = guard.get(&StreamKey { tenant_id, aggregate_id }) {
+                    all_events.extend(stream.iter().cloned());
+                }
+            }
+        }
+
+        let total = all_events.len() as u64;
+
+        let start = pagination.offset as usize;
+        let paginated: Vec<StoredEvent> = all_events
+            .into_iter()
+            .skip(start)
+            .take(pagination.limit as usize)
+            .collect();
+
+        let has_more = total > (pagination.offset + pagination.limit) as u64;
+
+        Ok(EventQueryResult {
+            events: paginated,
+            total,
+            pagination,
+            has_more,
+        })
+    }
+
     async fn get_event_by_id(
         &self,
         tenant_id: TenantId,
@@ -271,3 +505,355 @@ impl EventQuery for InMemoryEventStore {
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Barrier, Mutex};
+
+    use chrono::Utc;
+
+    use super::*;
+    use crate::event_store::SequenceAnomaly;
+
+    fn event(tenant_id: TenantId, aggregate_id: AggregateId) -> UncommittedEvent {
+        UncommittedEvent {
+            event_id: uuid::Uuid::now_v7(),
+            tenant_id,
+            aggregate_id,
+            aggregate_type: "test.aggregate".to_string(),
+            event_type: "test.aggregate.touched".to_string(),
+            event_version: 1,
+            occurred_at: Utc::now(),
+            payload: serde_json::json!({}),
+            metadata: Default::default(),
+            correlation_id: None,
+            causation_id: None,
+        }
+    }
+
+    #[test]
+    fn concurrent_appends_to_one_stream_allocate_contiguous_sequences() {
+        const WRITERS: usize = 16;
+        const APPENDS_PER_WRITER: usize = 25;
+
+        let store = Arc::new(InMemoryEventStore::new());
+        let tenant_id = TenantId::new();
+        let aggregate_id = AggregateId::new();
+        let barrier = Arc::new(Barrier::new(WRITERS));
+        // expected version -> number of successful appends made against it
+        let winners: Arc<Mutex<HashMap<u64, usize>>> = Arc::new(Mutex::new(HashMap::new()));
+
+        let handles: Vec<_> = (0..WRITERS)
+            .map(|_| {
+                let store = store.clone();
+                let barrier = barrier.clone();
+                let winners = winners.clone();
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    let mut done = 0;
+                    while done < APPENDS_PER_WRITER {
+                        let current = store
+                            .load_stream(tenant_id, aggregate_id)
+                            .unwrap()
+                            .last()
+                            .map(|e| e.sequence_number)
+                            .unwrap_or(0);
+                        match store.append(
+                            vec![event(tenant_id, aggregate_id)],
+                            ExpectedVersion::Exact(current),
+                        ) {
+                            Ok(committed) => {
+                                assert_eq!(committed[0].sequence_number, current + 1);
+                                *winners.lock().unwrap().entry(current).or_default() += 1;
+                                done += 1;
+                            }
+                            Err(EventStoreError::Concurrency(_)) => continue,
+                            Err(e) => panic!("unexpected append error: {e:?}"),
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        let total = (WRITERS * APPENDS_PER_WRITER) as u64;
+        let stream = store.load_stream(tenant_id, aggregate_id).unwrap();
+        let sequences: Vec<u64> = stream.iter().map(|e| e.sequence_number).collect();
+        assert_eq!(sequences, (1..=total).collect::<Vec<_>>());
+
+        let winners = winners.lock().unwrap();
+        assert_eq!(winners.len() as u64, total);
+        assert!(winners.values().all(|&n| n == 1));
+    }
+
+    #[test]
+    fn rejected_append_does_not_create_stream() {
+        let store = InMemoryEventStore::new();
+        let tenant_id = TenantId::new();
+        let aggregate_id = AggregateId::new();
+
+        let err = store
+            .append(vec![event(tenant_id, aggregate_id)], ExpectedVersion::Exact(3))
+            .unwrap_err();
+        assert!(matches!(err, EventStoreError::Concurrency(_)));
+        assert!(store.streams.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn gapless_stream_passes_the_sequence_check() {
+        let store = InMemoryEventStore::new();
+        let tenant_id = TenantId::new();
+        let aggregate_id = AggregateId::new();
+        let events = (0..4).map(|_| event(tenant_id, aggregate_id)).collect();
+        store.append(events, ExpectedVersion::Exact(0)).unwrap();
+
+        assert_eq!(store.verify_sequence_integrity(tenant_id, aggregate_id).unwrap(), None);
+        // An empty stream is trivially intact.
+        assert_eq!(store.verify_sequence_integrity(tenant_id, AggregateId::new()).unwrap(), None);
+    }
+
+    #[test]
+    fn deleted_middle_event_is_reported_as_a_gap() {
+        let store = InMemoryEventStore::new();
+        let tenant_id = TenantId::new();
+        let aggregate_id = AggregateId::new();
+        let events = (0..4).map(|_| event(tenant_id, aggregate_id)).collect();
+        store.append(events, ExpectedVersion::Exact(0)).unwrap();
+
+        // Simulate a manual DB edit that removed sequence 2.
+        let key = StreamKey {
+            tenant_id,
+            aggregate_id,
+        };
+        store.streams.write().unwrap().get_mut(&key).unwrap().remove(1);
+
+        assert_eq!(
+            store.verify_sequence_integrity(tenant_id, aggregate_id).unwrap(),
+            Some(SequenceAnomaly::Gap { missing: 2, found: 3 })
+        );
+        let report = crate::event_store::verify_all_streams(&store).unwrap();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].aggregate_id, aggregate_id);
+
+        // A copied-in duplicate is reported as such.
+        let duplicate = store.load_stream(tenant_id, aggregate_id).unwrap()[0].clone();
+        let mut streams = store.streams.write().unwrap();
+        let stream = streams.get_mut(&key).unwrap();
+        stream.truncate(1);
+        stream.push(duplicate);
+        drop(streams);
+        assert_eq!(
+            store.verify_sequence_integrity(tenant_id, aggregate_id).unwrap(),
+            Some(SequenceAnomaly::Duplicate { sequence: 1 })
+        );
+    }
+
+    fn block_on<F: std::future::Future>(f: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(f)
+    }
+
+    fn seed(store: &InMemoryEventStore, tenant_id: TenantId, count: usize) -> AggregateId {
+        let aggregate_id = AggregateId::new();
+        for v in 0..count {
+            store
+                .append(vec![event(tenant_id, aggregate_id)], ExpectedVersion::Exact(v as u64))
+                .unwrap();
+        }
+        aggregate_id
+    }
+
+    #[test]
+    fn batch_query_returns_union_of_per_aggregate_queries() {
+        let store = InMemoryEventStore::new();
+        let tenant_id = TenantId::new();
+        let ids: Vec<AggregateId> = [3, 1, 4].iter().map(|n| seed(&store, tenant_id, *n)).collect();
+        let _unrequested = seed(&store, tenant_id, 2);
+        let other_tenant = seed(&store, TenantId::new(), 2);
+
+        let mut requested = ids.clone();
+        requested.push(ids[0]); // duplicates are ignored
+        requested.push(other_tenant); // other tenants' streams never match
+        let page = Pagination::new(Some(1000), None);
+        let batch = block_on(store.get_events_for_aggregates(tenant_id, &requested, Some(page))).unwrap();
+
+        let mut expected: Vec<(uuid::Uuid, u64)> = Vec::new();
+        for id in &ids {
+            let single = block_on(store.get_aggregate_events(tenant_id, *id, Some(page))).unwrap();
+            expected.extend(single.events.iter().map(|e| (e.event_id, e.sequence_number)));
+        }
+        expected.sort();
+        let mut got: Vec<(uuid::Uuid, u64)> = batch.events.iter().map(|e| (e.event_id, e.sequence_number)).collect();
+        got.sort();
+
+        assert_eq!(got, expected);
+        assert_eq!(batch.total, 8);
+        assert!(!batch.has_more);
+        assert!(batch.events.iter().all(|e| e.tenant_id == tenant_id));
+        // Grouped by aggregate, each stream in sequence order.
+        let keys: Vec<(uuid::Uuid, u64)> = batch
+            .events
+            .iter()
+            .map(|e| (*e.aggregate_id.as_uuid(), e.sequence_number))
+            .collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+    }
+
+    #[test]
+    fn whole_aggregate_stream_is_read_past_the_page_cap() {
+        let store = InMemoryEventStore::new();
+        let tenant_id = TenantId::new();
+        let aggregate_id = seed(&store, tenant_id, 2_050);
+
+        let events = block_on(store.get_all_aggregate_events(tenant_id, aggregate_id)).unwrap();
+        let sequences: Vec<u64> = events.iter().map(|e| e.sequence_number).collect();
+        assert_eq!(sequences, (1..=2_050).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn tenant_stats_count_events_and_aggregates() {
+        let store = InMemoryEventStore::new();
+        let tenant_id = TenantId::new();
+        for n in [3, 1, 2] {
+            seed(&store, tenant_id, n);
+        }
+        seed(&store, TenantId::new(), 4);
+
+        let stats = store.tenant_stats(tenant_id).unwrap();
+        assert_eq!(stats.event_count, 6);
+        assert_eq!(stats.aggregate_count, 3);
+        assert!(stats.approx_bytes > 0);
+        assert!(stats.oldest.unwrap() <= stats.newest.unwrap());
+
+        assert_eq!(store.tenant_stats(TenantId::new()).unwrap(), TenantStats::default());
+    }
+
+    #[test]
+    fn batch_query_respects_pagination() {
+        let store = InMemoryEventStore::new();
+        let tenant_id = TenantId::new();
+        let ids: Vec<AggregateId> = [2, 3].iter().map(|n| seed(&store, tenant_id, *n)).collect();
+
+        let all = block_on(store.get_events_for_aggregates(tenant_id, &ids, None)).unwrap();
+        let first = block_on(store.get_events_for_aggregates(tenant_id, &ids, Some(Pagination::new(Some(3), None)))).unwrap();
+        let second =
+            block_on(store.get_events_for_aggregates(tenant_id, &ids, Some(Pagination::new(Some(3), Some(3))))).unwrap();
+
+        assert_eq!(first.total, 5);
+        assert_eq!(first.events.len(), 3);
+        assert!(first.has_more);
+        assert_eq!(second.events.len(), 2);
+        assert!(!second.has_more);
+
+        let paged: Vec<uuid::Uuid> = first.events.iter().chain(&second.events).map(|e| e.event_id).collect();
+        let unpaged: Vec<uuid::Uuid> = all.events.iter().map(|e| e.event_id).collect();
+        assert_eq!(paged, unpaged);
+    }
+
+    #[test]
+    fn backdated_event_is_recorded_at_append_time() {
+        let store = InMemoryEventStore::new();
+        let tenant_id = TenantId::new();
+        let aggregate_id = AggregateId::new();
+        let occurred_at = Utc::now() - chrono::Duration::days(30);
+
+        let before = Utc::now();
+        let committed = store
+            .append(
+                vec![UncommittedEvent {
+                    occurred_at,
+                    ..event(tenant_id, aggregate_id)
+                }],
+                ExpectedVersion::Exact(0),
+            )
+            .unwrap();
+        let after = Utc::now();
+
+        assert_eq!(committed[0].occurred_at, occurred_at);
+        assert!(before <= committed[0].created_at && committed[0].created_at <= after);
+        assert_eq!(committed[0].to_envelope().created_at(), Some(committed[0].created_at));
+
+        let queried = block_on(store.query_events(tenant_id, EventFilter::default(), Pagination::default())).unwrap();
+        assert_eq!(queried.events[0].occurred_at, occurred_at);
+        assert_eq!(queried.events[0].created_at, committed[0].created_at);
+        let by_id = block_on(store.get_event_by_id(tenant_id, committed[0].event_id)).unwrap().unwrap();
+        assert_eq!((by_id.occurred_at, by_id.created_at), (occurred_at, committed[0].created_at));
+    }
+
+    fn append_at(store: &InMemoryEventStore, tenant_id: TenantId, occurred_at: chrono::DateTime<Utc>) -> uuid::Uuid {
+        let committed = store
+            .append(
+                vec![UncommittedEvent {
+                    occurred_at,
+                    ..event(tenant_id, AggregateId::new())
+                }],
+                ExpectedVersion::Exact(0),
+            )
+            .unwrap();
+        committed[0].event_id
+    }
+
+    #[test]
+    fn cursor_paging_is_stable_when_events_arrive_mid_pagination() {
+        let store = InMemoryEventStore::new();
+        let tenant_id = TenantId::new();
+        let base = Utc::now() - chrono::Duration::hours(1);
+        let original: Vec<uuid::Uuid> = (0..5)
+            .map(|i| append_at(&store, tenant_id, base - chrono::Duration::minutes(i)))
+            .collect();
+
+        let first = block_on(store.query_events_after(tenant_id, EventFilter::default(), None, 2)).unwrap();
+        let cursor = first.next_cursor.expect("more pages");
+        assert_eq!(EventCursor::decode(&cursor.encode()), Ok(cursor));
+
+        // A newer event arrives: offset paging would now serve the second page shifted by one.
+        append_at(&store, tenant_id, Utc::now());
+        let shifted = block_on(store.query_events(tenant_id, EventFilter::default(), Pagination::new(Some(2), Some(2)))).unwrap();
+        assert_eq!(shifted.events[0].event_id, first.events[1].event_id);
+
+        let mut seen: Vec<uuid::Uuid> = first.events.iter().map(|e| e.event_id).collect();
+        let mut after = Some(cursor);
+        while let Some(c) = after {
+            let page = block_on(store.query_events_after(tenant_id, EventFilter::default(), Some(c), 2)).unwrap();
+            seen.extend(page.events.iter().map(|e| e.event_id));
+            after = page.next_cursor;
+        }
+        assert_eq!(seen, original);
+    }
+
+    #[test]
+    fn read_all_returns_interleaved_appends_in_commit_order() {
+        let store = InMemoryEventStore::new();
+        let tenant_id = TenantId::new();
+        let (a, b) = (AggregateId::new(), AggregateId::new());
+
+        // a, b, a, b, a, with another tenant's event in between.
+        let mut committed = Vec::new();
+        for (i, aggregate_id) in [a, b, a, b, a].into_iter().enumerate() {
+            if i == 2 {
+                store.append(vec![event(TenantId::new(), AggregateId::new())], ExpectedVersion::Any).unwrap();
+            }
+            committed.extend(store.append(vec![event(tenant_id, aggregate_id)], ExpectedVersion::Any).unwrap());
+        }
+        let commit_order: Vec<_> = committed.iter().map(|e| e.event_id).collect();
+
+        let all = store.read_all(tenant_id, None, 100).unwrap();
+        assert_eq!(all.iter().map(|e| e.event_id).collect::<Vec<_>>(), commit_order);
+        assert!(all.windows(2).all(|w| w[0].global_position < w[1].global_position));
+
+        // Paging resumes after the last position seen.
+        let first = store.read_all(tenant_id, None, 2).unwrap();
+        let rest = store.read_all(tenant_id, Some(first[1].global_position), 100).unwrap();
+        let paged: Vec<_> = first.iter().chain(&rest).map(|e| e.event_id).collect();
+        assert_eq!(paged, commit_order);
+
+        let envelopes = crate::event_store::read_all_envelopes(&store, tenant_id).unwrap();
+        assert_eq!(envelopes.iter().map(|e| e.event_id()).collect::<Vec<_>>(), commit_order);
+        assert_eq!(envelopes[0].global_position(), Some(all[0].global_position.0));
+    }
+}
@@ -0,0 +1,102 @@
+//! Sequence-integrity sweep over every stream.
+//!
+//! [`EventStore::verify_sequence_integrity`] checks one stream; [`verify_all_streams`]
+//! runs it over every stream in the store, and [`IntegritySchedule`] decides whether
+//! (and how often) the API runs that sweep in the background.
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use forgeerp_core::{AggregateId, TenantId};
+
+use super::r#trait::{EventStore, EventStoreError, SequenceAnomaly};
+
+/// A stream whose sequence numbers are not contiguous.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StreamAnomaly {
+    pub tenant_id: TenantId,
+    pub aggregate_id: AggregateId,
+    pub anomaly: SequenceAnomaly,
+}
+
+/// Check every stream; returns the first anomaly of each broken one.
+pub fn verify_all_streams<S>(store: &S) -> Result<Vec<StreamAnomaly>, EventStoreError>
+where
+    S: EventStore + ?Sized,
+{
+    let mut anomalies = Vec::new();
+    for (tenant_id, aggregate_id) in store.stream_ids()? {
+        if let Some(anomaly) = store.verify_sequence_integrity(tenant_id, aggregate_id)? {
+            anomalies.push(StreamAnomaly {
+                tenant_id,
+                aggregate_id,
+                anomaly,
+            });
+        }
+    }
+    Ok(anomalies)
+}
+
+/// How often the background sweep runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntegritySchedule {
+    pub interval: Duration,
+}
+
+impl IntegritySchedule {
+    /// Schedule from `SEQUENCE_INTEGRITY_CHECK_INTERVAL_SECS`; unset, invalid or 0 disables the sweep.
+    pub fn from_env() -> Option<Self> {
+        std::env::var("SEQUENCE_INTEGRITY_CHECK_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .map(|secs| Self {
+                interval: Duration::from_secs(secs),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use forgeerp_core::ExpectedVersion;
+
+    use crate::event_store::{InMemoryEventStore, UncommittedEvent};
+
+    fn append(store: &InMemoryEventStore, tenant_id: TenantId, aggregate_id: AggregateId, count: usize) {
+        let events = (0..count)
+            .map(|_| UncommittedEvent {
+                event_id: uuid::Uuid::now_v7(),
+                tenant_id,
+                aggregate_id,
+                aggregate_type: "test.aggregate".to_string(),
+                event_type: "test.aggregate.touched".to_string(),
+                event_version: 1,
+                occurred_at: Utc::now(),
+                payload: serde_json::json!({}),
+                metadata: Default::default(),
+                correlation_id: None,
+                causation_id: None,
+            })
+            .collect();
+        store.append(events, ExpectedVersion::Any).unwrap();
+    }
+
+    #[test]
+    fn sweep_covers_every_stream_of_every_tenant() {
+        let store = InMemoryEventStore::new();
+        let (tenant_a, tenant_b) = (TenantId::new(), TenantId::new());
+        let (first, second) = (AggregateId::new(), AggregateId::new());
+        append(&store, tenant_a, first, 3);
+        append(&store, tenant_b, second, 2);
+
+        let mut streams = store.stream_ids().unwrap();
+        streams.sort_by_key(|(tenant_id, _)| *tenant_id.as_uuid());
+        let mut expected = vec![(tenant_a, first), (tenant_b, second)];
+        expected.sort_by_key(|(tenant_id, _)| *tenant_id.as_uuid());
+        assert_eq!(streams, expected);
+        assert!(verify_all_streams(&store).unwrap().is_empty());
+    }
+}
@@ -4,14 +4,50 @@
 //! loading tenant-scoped event streams without making any storage assumptions.
 
 pub mod in_memory;
+pub mod integrity;
 pub mod postgres;
 pub mod query;
 pub mod r#trait;
 
 pub use in_memory::InMemoryEventStore;
-pub use postgres::{PostgresEventStore, Snapshot};
-pub use query::{EventFilter, EventQuery, EventQueryResult, Pagination};
-pub use r#trait::{EventStore, EventStoreError, StoredEvent, UncommittedEvent};
+pub use integrity::{verify_all_streams, IntegritySchedule, StreamAnomaly};
+pub use postgres::PostgresEventStore;
+pub use query::{EventCursor, EventFilter, EventPage, EventQuery, EventQueryResult, InvalidCursor, Pagination};
+pub use r#trait::{
+    first_sequence_anomaly, read_all_envelopes, EventStore, EventStoreError, GlobalPosition, SequenceAnomaly, Snapshot,
+    StoredEvent, TenantStats, UncommittedEvent, READ_ALL_PAGE_SIZE,
+};
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Mutex, MutexGuard};
+
+use forgeerp_core::{AggregateId, ExpectedVersion, TenantId};
+use forgeerp_events::{EventBus, EventEnvelope};
+use serde_json::Value as JsonValue;
+
+/// How `PublishingEventStore` hands committed events to the bus.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PublishMode {
+    /// Publish committed events one at a time. A bus failure part-way through an append
+    /// leaves the earlier events delivered and the rest only in the store.
+    #[default]
+    PerEvent,
+    /// Publish the events of one append as a single `publish_batch`: consumers see all of
+    /// them or none. Requires a bus whose batches are atomic; with any other bus nothing
+    /// is published and the append reports `EventStoreError::Publish`.
+    Atomic,
+    /// `Atomic`, plus strict ordering per aggregate: appends to one aggregate are appended
+    /// and published one at a time, so batches reach the bus in stream order. A batch that
+    /// fails to publish is held, and later batches of that aggregate queue behind it until
+    /// it goes out (on the next append to the aggregate or `publish_pending`).
+    OrderedPerAggregate,
+}
+
+/// Lock stripes serializing append+publish per aggregate in `OrderedPerAggregate` mode.
+const ORDERING_STRIPES: usize = 64;
+
+type StreamKey = (TenantId, AggregateId);
 
 /// Adapter that publishes committed events to an `EventBus` after a successful append.
 ///
@@ -26,6 +62,16 @@ pub use r#trait::{EventStore, EventStoreError, StoredEvent, UncommittedEvent};
 /// - **Consistency**: Consumers only receive events that were successfully stored
 /// - **Recovery**: If publication fails, events are still in the store and can be republished
 ///
+/// What a consumer may observe beyond that depends on the [`PublishMode`]:
+///
+/// | Mode | Partial append on bus failure | Order within an aggregate |
+/// |------|-------------------------------|---------------------------|
+/// | `PerEvent` (default) | possible | per append only |
+/// | `Atomic` | never | per append only |
+/// | `OrderedPerAggregate` | never | stream order across appends |
+///
+/// Order across different aggregates is never guaranteed.
+///
 /// ## Usage Pattern
 ///
 /// This adapter is useful when you want to combine event storage and publication in one step:
@@ -33,7 +79,8 @@ pub use r#trait::{EventStore, EventStoreError, StoredEvent, UncommittedEvent};
 /// ```ignore
 /// let store = InMemoryEventStore::new();
 /// let bus = InMemoryEventBus::new();
-/// let publishing_store = PublishingEventStore::new(store, bus);
+/// let publishing_store = PublishingEventStore::new(store, bus)
+///     .with_publish_mode(PublishMode::OrderedPerAggregate);
 ///
 /// // append() both stores and publishes events
 /// let committed = publishing_store.append(events, expected_version)?;
@@ -41,10 +88,10 @@ pub use r#trait::{EventStore, EventStoreError, StoredEvent, UncommittedEvent};
 ///
 /// ## Error Handling
 ///
-/// If publication fails after a successful append, `append()` returns `EventStoreError::Publish`.
-/// The events are already persisted, so:
+/// If publication fails after a successful append, `append()` returns `EventStoreError::Publish`
+/// carrying the `committed` events. They are already persisted, so:
 /// - Retrying `append()` is idempotent (events already stored, will fail on version check)
-/// - Caller can retry just the publication step (events are in store)
+/// - Caller can retry just the publication step with [`PublishingEventStore::republish`]
 /// - This gives at-least-once delivery semantics
 ///
 /// ## When to Use
@@ -58,48 +105,427 @@ pub use r#trait::{EventStore, EventStoreError, StoredEvent, UncommittedEvent};
 pub struct PublishingEventStore<S, B> {
     store: S,
     bus: B,
+    mode: PublishMode,
+    stripes: Vec<Mutex<()>>,
+    /// Batches not yet published, per aggregate (`OrderedPerAggregate` only).
+    pending: Mutex<HashMap<StreamKey, VecDeque<Vec<EventEnvelope<JsonValue>>>>>,
 }
 
 impl<S, B> PublishingEventStore<S, B> {
     pub fn new(store: S, bus: B) -> Self {
-        Self { store, bus }
+        Self {
+            store,
+            bus,
+            mode: PublishMode::default(),
+            stripes: (0..ORDERING_STRIPES).map(|_| Mutex::new(())).collect(),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_publish_mode(mut self, mode: PublishMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn publish_mode(&self) -> PublishMode {
+        self.mode
     }
 
     pub fn into_parts(self) -> (S, B) {
         (self.store, self.bus)
     }
+
+    fn stripe(key: &StreamKey) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        (hasher.finish() % ORDERING_STRIPES as u64) as usize
+    }
+
+    /// Lock the stripes of `keys` in index order (no lock-order inversions between appends).
+    fn lock_streams<'a>(&self, keys: impl IntoIterator<Item = &'a StreamKey>) -> Vec<MutexGuard<'_, ()>> {
+        let mut stripes: Vec<usize> = keys.into_iter().map(Self::stripe).collect();
+        stripes.sort_unstable();
+        stripes.dedup();
+        stripes
+            .into_iter()
+            .map(|i| self.stripes[i].lock().unwrap_or_else(|e| e.into_inner()))
+            .collect()
+    }
+}
+
+impl<S, B> PublishingEventStore<S, B>
+where
+    B: EventBus<EventEnvelope<JsonValue>>,
+{
+    /// Publish `committed` according to the mode; the error is the bus failure reason.
+    /// Callers in `OrderedPerAggregate` mode hold the stripes of every aggregate in `committed`.
+    fn publish(&self, committed: &[StoredEvent]) -> Result<(), String> {
+        let envelopes: Vec<_> = committed.iter().map(StoredEvent::to_envelope).collect();
+        match self.mode {
+            PublishMode::PerEvent => {
+                for envelope in envelopes {
+                    self.bus.publish(envelope).map_err(|err| format!("{err:?}"))?;
+                }
+                Ok(())
+            }
+            PublishMode::Atomic => self.publish_atomically(envelopes),
+            PublishMode::OrderedPerAggregate => {
+                let mut by_stream: Vec<(StreamKey, Vec<EventEnvelope<JsonValue>>)> = Vec::new();
+                for envelope in envelopes {
+                    let key = (envelope.tenant_id(), envelope.aggregate_id());
+                    match by_stream.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, batch)) => batch.push(envelope),
+                        None => by_stream.push((key, vec![envelope])),
+                    }
+                }
+                let mut result = Ok(());
+                for (key, batch) in by_stream {
+                    self.pending_queue(|pending| pending.entry(key).or_default().push_back(batch));
+                    result = result.and(self.drain_pending(key));
+                }
+                result
+            }
+        }
+    }
+
+    fn publish_atomically(&self, envelopes: Vec<EventEnvelope<JsonValue>>) -> Result<(), String> {
+        if !self.bus.publishes_batches_atomically() {
+            return Err("bus cannot publish batches atomically; events were stored but not published".to_string());
+        }
+        self.bus.publish_batch(envelopes).map_err(|err| format!("{err:?}"))
+    }
+
+    /// `committed` once published, or a `Publish` error that hands it back.
+    fn published(&self, committed: Vec<StoredEvent>) -> Result<Vec<StoredEvent>, EventStoreError> {
+        match self.publish(&committed) {
+            Ok(()) => Ok(committed),
+            Err(reason) => Err(EventStoreError::Publish { committed, reason }),
+        }
+    }
+
+    fn pending_queue<T>(&self, f: impl FnOnce(&mut HashMap<StreamKey, VecDeque<Vec<EventEnvelope<JsonValue>>>>) -> T) -> T {
+        f(&mut self.pending.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Publish the held batches of one aggregate, oldest first, stopping at the first failure.
+    /// The caller holds the aggregate's stripe.
+    fn drain_pending(&self, key: StreamKey) -> Result<(), String> {
+        while let Some(batch) = self.pending_queue(|pending| pending.get(&key).and_then(|q| q.front().cloned())) {
+            self.publish_atomically(batch)?;
+            self.pending_queue(|pending| {
+                if let Some(queue) = pending.get_mut(&key) {
+                    queue.pop_front();
+                    if queue.is_empty() {
+                        pending.remove(&key);
+                    }
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Publish the `committed` events of a `Publish` error again, without re-appending.
+    ///
+    /// In `OrderedPerAggregate` mode failed batches are already held, so this retries
+    /// those instead ([`Self::publish_pending`]) and fails while any remain.
+    pub fn republish(&self, committed: &[StoredEvent]) -> Result<(), EventStoreError> {
+        let result = match self.mode {
+            PublishMode::OrderedPerAggregate => match self.publish_pending() {
+                0 => Ok(()),
+                held => Err(format!("{held} events still held after retrying publication")),
+            },
+            _ => self.publish(committed),
+        };
+        result.map_err(|reason| EventStoreError::Publish {
+            committed: committed.to_vec(),
+            reason,
+        })
+    }
+
+    /// Events held back after a failed publish (`OrderedPerAggregate` mode).
+    pub fn pending_events(&self) -> usize {
+        self.pending_queue(|pending| pending.values().flatten().map(Vec::len).sum())
+    }
+
+    /// Retry publishing every held batch, in order per aggregate. Returns the number of
+    /// events still held (aggregates whose bus publish failed again).
+    pub fn publish_pending(&self) -> usize {
+        let keys: Vec<StreamKey> = self.pending_queue(|pending| pending.keys().copied().collect());
+        for key in keys {
+            let _guard = self.lock_streams([&key]);
+            let _ = self.drain_pending(key);
+        }
+        self.pending_events()
+    }
 }
 
 impl<S, B> EventStore for PublishingEventStore<S, B>
 where
     S: EventStore,
-    B: forgeerp_events::EventBus<forgeerp_events::EventEnvelope<serde_json::Value>>,
+    B: EventBus<EventEnvelope<JsonValue>>,
 {
     fn append(
         &self,
         events: Vec<UncommittedEvent>,
-        expected_version: forgeerp_core::ExpectedVersion,
+        expected_version: ExpectedVersion,
     ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let keys: Vec<StreamKey> = events.first().map(|e| (e.tenant_id, e.aggregate_id)).into_iter().collect();
+        let _guard = (self.mode == PublishMode::OrderedPerAggregate).then(|| self.lock_streams(&keys));
+
         // 1) Append (durable step)
         let committed = self.store.append(events, expected_version)?;
 
-        // 2) Publish committed events (best-effort; at-least-once acceptable)
-        for e in &committed {
-            self.bus
-                .publish(e.to_envelope())
-                .map_err(|err| EventStoreError::Publish(format!("{err:?}")))?;
-        }
+        // 2) Publish committed events (at-least-once; see `PublishMode` for partial failures)
+        self.published(committed)
+    }
 
-        Ok(committed)
+    fn append_streams(
+        &self,
+        batches: Vec<(Vec<UncommittedEvent>, ExpectedVersion)>,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let keys: Vec<StreamKey> = batches
+            .iter()
+            .filter_map(|(events, _)| events.first().map(|e| (e.tenant_id, e.aggregate_id)))
+            .collect();
+        let _guard = (self.mode == PublishMode::OrderedPerAggregate).then(|| self.lock_streams(&keys));
+
+        let committed = self.store.append_streams(batches)?;
+        self.published(committed)
     }
 
     fn load_stream(
         &self,
-        tenant_id: forgeerp_core::TenantId,
-        aggregate_id: forgeerp_core::AggregateId,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
     ) -> Result<Vec<StoredEvent>, EventStoreError> {
         self.store.load_stream(tenant_id, aggregate_id)
     }
+
+    fn load_stream_after(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        after_version: u64,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        self.store.load_stream_after(tenant_id, aggregate_id, after_version)
+    }
+
+    fn load_snapshot(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+    ) -> Result<Option<Snapshot>, EventStoreError> {
+        self.store.load_snapshot(tenant_id, aggregate_id)
+    }
+
+    fn store_snapshot(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        snapshot: &Snapshot,
+    ) -> Result<(), EventStoreError> {
+        self.store.store_snapshot(tenant_id, aggregate_id, snapshot)
+    }
+
+    fn tenant_stats(&self, tenant_id: TenantId) -> Result<TenantStats, EventStoreError> {
+        self.store.tenant_stats(tenant_id)
+    }
+
+    fn verify_sequence_integrity(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+    ) -> Result<Option<SequenceAnomaly>, EventStoreError> {
+        self.store.verify_sequence_integrity(tenant_id, aggregate_id)
+    }
+
+    fn stream_ids(&self) -> Result<Vec<(TenantId, AggregateId)>, EventStoreError> {
+        self.store.stream_ids()
+    }
+
+    fn read_all(
+        &self,
+        tenant_id: TenantId,
+        after: Option<GlobalPosition>,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        self.store.read_all(tenant_id, after, limit)
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use chrono::Utc;
+    use forgeerp_events::{InMemoryEventBus, Subscription};
+
+    fn event(tenant_id: TenantId, aggregate_id: AggregateId) -> UncommittedEvent {
+        UncommittedEvent {
+            event_id: uuid::Uuid::now_v7(),
+            tenant_id,
+            aggregate_id,
+            aggregate_type: "test.aggregate".to_string(),
+            event_type: "test.aggregate.touched".to_string(),
+            event_version: 1,
+            occurred_at: Utc::now(),
+            payload: serde_json::json!({}),
+            metadata: Default::default(),
+            correlation_id: None,
+            causation_id: None,
+        }
+    }
+
+    /// Bus whose transport fails on the second message of a batch while `failing` is set.
+    /// Batches are staged and only delivered once every message went through.
+    #[derive(Default)]
+    struct FlakyBus {
+        failing: AtomicBool,
+        atomic: bool,
+        delivered: Mutex<Vec<EventEnvelope<JsonValue>>>,
+    }
+
+    impl FlakyBus {
+        fn atomic() -> Self {
+            Self { atomic: true, ..Self::default() }
+        }
+
+        fn sequences(&self) -> Vec<u64> {
+            self.delivered.lock().unwrap().iter().map(|e| e.sequence_number()).collect()
+        }
+    }
+
+    impl EventBus<EventEnvelope<JsonValue>> for FlakyBus {
+        type Error = String;
+
+        fn publish(&self, message: EventEnvelope<JsonValue>) -> Result<(), Self::Error> {
+            self.delivered.lock().unwrap().push(message);
+            Ok(())
+        }
+
+        fn publish_batch(&self, messages: Vec<EventEnvelope<JsonValue>>) -> Result<(), Self::Error> {
+            let mut staged = Vec::new();
+            for (i, message) in messages.into_iter().enumerate() {
+                if i == 1 && self.failing.load(Ordering::SeqCst) {
+                    return Err("transport down".to_string());
+                }
+                staged.push(message);
+            }
+            self.delivered.lock().unwrap().extend(staged);
+            Ok(())
+        }
+
+        fn publishes_batches_atomically(&self) -> bool {
+            self.atomic
+        }
+
+        fn subscribe(&self) -> Subscription<EventEnvelope<JsonValue>> {
+            Subscription::new(std::sync::mpsc::channel().1)
+        }
+    }
+
+    #[test]
+    fn atomic_mode_delivers_nothing_when_the_bus_fails_mid_batch() {
+        let bus = Arc::new(FlakyBus::atomic());
+        bus.failing.store(true, Ordering::SeqCst);
+        let store = PublishingEventStore::new(InMemoryEventStore::new(), bus.clone()).with_publish_mode(PublishMode::Atomic);
+        let (tenant_id, aggregate_id) = (TenantId::new(), AggregateId::new());
+
+        let events = (0..3).map(|_| event(tenant_id, aggregate_id)).collect();
+        let result = store.append(events, ExpectedVersion::Exact(0));
+
+        assert!(matches!(result, Err(EventStoreError::Publish { .. })));
+        assert!(bus.sequences().is_empty());
+        // Still in the store for relay.
+        assert_eq!(store.load_stream(tenant_id, aggregate_id).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn publish_error_hands_back_the_committed_events_for_republishing() {
+        let bus = Arc::new(FlakyBus::atomic());
+        bus.failing.store(true, Ordering::SeqCst);
+        let store = PublishingEventStore::new(InMemoryEventStore::new(), bus.clone()).with_publish_mode(PublishMode::Atomic);
+        let (tenant_id, aggregate_id) = (TenantId::new(), AggregateId::new());
+
+        let events = (0..3).map(|_| event(tenant_id, aggregate_id)).collect();
+        let err = store.append(events, ExpectedVersion::Exact(0)).unwrap_err();
+
+        let committed = err.committed().expect("publish errors carry the committed events").to_vec();
+        assert_eq!(committed.iter().map(|e| e.sequence_number).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(store.load_stream(tenant_id, aggregate_id).unwrap(), committed);
+
+        // Still failing: the events come back again.
+        assert_eq!(store.republish(&committed).unwrap_err().committed().map(<[_]>::len), Some(3));
+        bus.failing.store(false, Ordering::SeqCst);
+        store.republish(&committed).unwrap();
+        assert_eq!(bus.sequences(), vec![1, 2, 3]);
+        assert_eq!(store.load_stream(tenant_id, aggregate_id).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn atomic_mode_refuses_a_bus_without_atomic_batches() {
+        let bus = Arc::new(FlakyBus::default());
+        let store = PublishingEventStore::new(InMemoryEventStore::new(), bus.clone()).with_publish_mode(PublishMode::Atomic);
+        let (tenant_id, aggregate_id) = (TenantId::new(), AggregateId::new());
+
+        let result = store.append(vec![event(tenant_id, aggregate_id)], ExpectedVersion::Exact(0));
+
+        assert!(matches!(result, Err(EventStoreError::Publish { reason, .. }) if reason.contains("atomically")));
+        assert!(bus.sequences().is_empty());
+        assert_eq!(store.load_stream(tenant_id, aggregate_id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn ordered_mode_preserves_stream_order_under_concurrent_appends() {
+        let bus = Arc::new(InMemoryEventBus::<EventEnvelope<JsonValue>>::new());
+        let subscription = bus.subscribe();
+        let store = Arc::new(
+            PublishingEventStore::new(InMemoryEventStore::new(), bus).with_publish_mode(PublishMode::OrderedPerAggregate),
+        );
+        let (tenant_id, aggregate_id) = (TenantId::new(), AggregateId::new());
+
+        let writers: Vec<_> = (0..8)
+            .map(|_| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..25 {
+                        let events = (0..2).map(|_| event(tenant_id, aggregate_id)).collect();
+                        store.append(events, ExpectedVersion::Any).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let received: Vec<u64> = std::iter::from_fn(|| subscription.try_recv().ok())
+            .map(|e| e.sequence_number())
+            .collect();
+        assert_eq!(received, (1..=400).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn ordered_mode_holds_later_batches_behind_a_failed_one() {
+        let bus = Arc::new(FlakyBus::atomic());
+        let store = PublishingEventStore::new(InMemoryEventStore::new(), bus.clone())
+            .with_publish_mode(PublishMode::OrderedPerAggregate);
+        let (tenant_id, aggregate_id) = (TenantId::new(), AggregateId::new());
+        let append = |n: usize| store.append((0..n).map(|_| event(tenant_id, aggregate_id)).collect(), ExpectedVersion::Any);
 
+        append(1).unwrap();
+        bus.failing.store(true, Ordering::SeqCst);
+        assert!(append(2).is_err());
+        // Publishable on its own, but queued behind the failed batch.
+        assert!(append(1).is_err());
+        assert_eq!(bus.sequences(), vec![1]);
+        assert_eq!(store.pending_events(), 3);
+
+        bus.failing.store(false, Ordering::SeqCst);
+        assert_eq!(store.publish_pending(), 0);
+        append(1).unwrap();
+        assert_eq!(bus.sequences(), vec![1, 2, 3, 4, 5]);
+    }
+}
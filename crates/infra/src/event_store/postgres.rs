@@ -30,8 +30,8 @@ use tracing::{instrument, Span};
 
 use forgeerp_core::{AggregateId, ExpectedVersion, TenantId};
 
-use super::query::{EventFilter, EventQuery, EventQueryResult, Pagination};
-use super::r#trait::{EventStore, EventStoreError, StoredEvent, UncommittedEvent};
+use super::query::{EventCursor, EventFilter, EventPage, EventQuery, EventQueryResult, Pagination};
+use super::r#trait::{EventStore, EventStoreError, GlobalPosition, Snapshot, StoredEvent, TenantStats, UncommittedEvent};
 
 /// Postgres-backed append-only event store.
 ///
@@ -72,10 +72,29 @@ impl PostgresEventStore {
         }
     }
 
+    /// Cheap round trip (`SELECT 1`) to check the database is reachable.
+    pub async fn ping(&self) -> Result<(), EventStoreError> {
+        sqlx::query("SELECT 1")
+            .execute(&*self.pool)
+            .await
+            .map_err(|e| map_sqlx_error("ping", e))?;
+        Ok(())
+    }
+
     /// Load all events for a tenant + aggregate stream.
     ///
     /// Events are returned in sequence number order (ascending).
     /// Returns an empty vector if the stream doesn't exist.
+    pub async fn load_stream(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        self.load_stream_after(tenant_id, aggregate_id, 0).await
+    }
+
+    /// Load the events of a stream after `after_version` (e.g. the tail after a snapshot),
+    /// in sequence number order.
     #[instrument(
         skip(self),
         fields(
@@ -84,10 +103,11 @@ impl PostgresEventStore {
         ),
         err
     )]
-    pub async fn load_stream(
+    pub async fn load_stream_after(
         &self,
         tenant_id: TenantId,
         aggregate_id: AggregateId,
+        after_version: u64,
     ) -> Result<Vec<StoredEvent>, EventStoreError> {
         let span = Span::current();
         span.record("operation", "load_stream");
@@ -104,14 +124,19 @@ impl PostgresEventStore {
                 event_version,
                 occurred_at,
                 payload,
-                created_at
+                metadata,
+                correlation_id,
+                causation_id,
+                created_at,
+                global_seq
             FROM events
-            WHERE tenant_id = $1 AND aggregate_id = $2
+            WHERE tenant_id = $1 AND aggregate_id = $2 AND sequence_number > $3
             ORDER BY sequence_number ASC
             "#,
         )
         .bind(tenant_id.as_uuid())
         .bind(aggregate_id.as_uuid())
+        .bind(after_version as i64)
         .fetch_all(&*self.pool)
         .await
         .map_err(|e| map_sqlx_error("load_stream", e))?;
@@ -127,6 +152,117 @@ impl PostgresEventStore {
         Ok(stored_events)
     }
 
+    /// Usage statistics for a tenant, computed in a single aggregate query.
+    ///
+    /// `approx_bytes` is the summed on-disk row size (`pg_column_size`), excluding indexes.
+    #[instrument(skip(self), fields(tenant_id = %tenant_id.as_uuid()), err)]
+    pub async fn tenant_stats(&self, tenant_id: TenantId) -> Result<TenantStats, EventStoreError> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                COUNT(*) AS event_count,
+                COUNT(DISTINCT aggregate_id) AS aggregate_count,
+                COALESCE(SUM(pg_column_size(e.*)), 0)::bigint AS approx_bytes,
+                MIN(occurred_at) AS oldest,
+                MAX(occurred_at) AS newest
+            FROM events e
+            WHERE tenant_id = $1
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(|e| map_sqlx_error("tenant_stats", e))?;
+
+        let read = |e: sqlx::Error| EventStoreError::InvalidAppend(format!("failed to read tenant stats: {}", e));
+        let event_count: i64 = row.try_get("event_count").map_err(read)?;
+        let aggregate_count: i64 = row.try_get("aggregate_count").map_err(read)?;
+        let approx_bytes: i64 = row.try_get("approx_bytes").map_err(read)?;
+
+        Ok(TenantStats {
+            event_count: event_count as u64,
+            aggregate_count: aggregate_count as u64,
+            approx_bytes: approx_bytes as u64,
+            oldest: row.try_get("oldest").map_err(read)?,
+            newest: row.try_get("newest").map_err(read)?,
+        })
+    }
+
+    /// Every `(tenant, aggregate)` stream that has events.
+    #[instrument(skip(self), err)]
+    pub async fn stream_ids(&self) -> Result<Vec<(TenantId, AggregateId)>, EventStoreError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT tenant_id, aggregate_id
+            FROM events
+            ORDER BY tenant_id, aggregate_id
+            "#,
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| map_sqlx_error("stream_ids", e))?;
+
+        let read = |e: sqlx::Error| EventStoreError::InvalidAppend(format!("failed to read stream ids: {}", e));
+        rows.iter()
+            .map(|row| {
+                let tenant_id: uuid::Uuid = row.try_get("tenant_id").map_err(read)?;
+                let aggregate_id: uuid::Uuid = row.try_get("aggregate_id").map_err(read)?;
+                Ok((TenantId::from_uuid(tenant_id), AggregateId::from_uuid(aggregate_id)))
+            })
+            .collect()
+    }
+
+    /// A tenant's events after `after`, in `global_seq` order.
+    ///
+    /// `global_seq` is allocated at insert time, but appends hold a per-tenant lock until
+    /// they commit (see [`lock_tenant_appends`]), so a tenant's positions become visible
+    /// in order: once a position is readable, every lower one of the tenant is too.
+    #[instrument(skip(self), fields(tenant_id = %tenant_id.as_uuid()), err)]
+    pub async fn read_all(
+        &self,
+        tenant_id: TenantId,
+        after: Option<GlobalPosition>,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                event_id,
+                tenant_id,
+                aggregate_id,
+                aggregate_type,
+                sequence_number,
+                event_type,
+                event_version,
+                occurred_at,
+                payload,
+                metadata,
+                correlation_id,
+                causation_id,
+                created_at,
+                global_seq
+            FROM events
+            WHERE tenant_id = $1 AND global_seq > $2
+            ORDER BY global_seq ASC
+            LIMIT $3
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(after.unwrap_or_default().0 as i64)
+        .bind(limit.min(i64::MAX as usize) as i64)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| map_sqlx_error("read_all", e))?;
+
+        rows.iter()
+            .map(|row| {
+                StoredEventRow::from_row(row)
+                    .map(StoredEvent::from)
+                    .map_err(|e| EventStoreError::InvalidAppend(format!("failed to deserialize event row: {}", e)))
+            })
+            .collect()
+    }
+
     /// Append events to a stream with optimistic concurrency control.
     ///
     /// This method:
@@ -201,110 +337,84 @@ impl PostgresEventStore {
             .begin()
             .await
             .map_err(|e| map_sqlx_error("begin_transaction", e))?;
+        lock_tenant_appends(&mut tx, [tenant_id]).await?;
 
-        // Check current version and aggregate type
-        let (current_version, existing_aggregate_type) = check_stream_version(
+        let stored_events = match insert_stream_events(
             &mut tx,
             tenant_id,
             aggregate_id,
+            aggregate_type,
+            events,
+            expected_version,
         )
-        .await?;
-
-        // Validate aggregate type consistency
-        if let Some(ref existing_type) = existing_aggregate_type {
-            if existing_type != &aggregate_type {
+        .await
+        {
+            Ok(stored) => stored,
+            Err(e) => {
                 tx.rollback()
                     .await
                     .map_err(|e| map_sqlx_error("rollback", e))?;
-                return Err(EventStoreError::AggregateTypeMismatch(format!(
-                    "stream aggregate_type is '{}', attempted append with '{}'",
-                    existing_type, aggregate_type
-                )));
+                return Err(e);
             }
-        }
+        };
 
-        // Validate expected version
-        if !expected_version.matches(current_version) {
-            tx.rollback()
-                .await
-                .map_err(|e| map_sqlx_error("rollback", e))?;
-            return Err(EventStoreError::Concurrency(format!(
-                "optimistic concurrency check failed: expected {:?}, found {}",
-                expected_version, current_version
-            )));
-        }
+        // Commit transaction
+        tx.commit()
+            .await
+            .map_err(|e| map_sqlx_error("commit_transaction", e))?;
 
-        // Insert events with sequence numbers starting at current_version + 1
-        let mut stored_events = Vec::with_capacity(events.len());
-        let mut next_sequence = current_version + 1;
+        span.record("committed_events", stored_events.len());
+        Ok(stored_events)
+    }
 
-        for event in events {
-            // Verify tenant/aggregate consistency one more time
-            if event.tenant_id != tenant_id || event.aggregate_id != aggregate_id {
-                tx.rollback()
-                    .await
-                    .map_err(|e| map_sqlx_error("rollback", e))?;
-                return Err(EventStoreError::TenantIsolation(
-                    "event tenant/aggregate mismatch in batch".to_string(),
-                ));
+    /// Append to several streams in one transaction (all streams or none).
+    ///
+    /// Each batch is checked like [`Self::append_events`]; any failure rolls back every batch.
+    #[instrument(skip(self, batches), fields(stream_count = batches.len()), err)]
+    pub async fn append_streams(
+        &self,
+        batches: Vec<(Vec<UncommittedEvent>, ExpectedVersion)>,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let span = Span::current();
+        span.record("operation", "append_streams");
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| map_sqlx_error("begin_transaction", e))?;
+        lock_tenant_appends(&mut tx, batches.iter().filter_map(|(events, _)| events.first()).map(|e| e.tenant_id))
+            .await?;
+
+        let mut stored_events = Vec::new();
+        for (events, expected_version) in batches {
+            if events.is_empty() {
+                continue;
             }
+            let tenant_id = events[0].tenant_id;
+            let aggregate_id = events[0].aggregate_id;
+            let aggregate_type = events[0].aggregate_type.clone();
 
-        sqlx::query(
-            r#"
-            INSERT INTO events (
-                event_id,
+            match insert_stream_events(
+                &mut tx,
                 tenant_id,
                 aggregate_id,
                 aggregate_type,
-                sequence_number,
-                event_type,
-                event_version,
-                occurred_at,
-                payload
+                events,
+                expected_version,
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            "#,
-        )
-        .bind(event.event_id)
-        .bind(tenant_id.as_uuid())
-        .bind(aggregate_id.as_uuid())
-        .bind(&aggregate_type)
-        .bind(next_sequence as i64)
-        .bind(&event.event_type)
-        .bind(event.event_version as i32)
-        .bind(event.occurred_at)
-        .bind(&event.payload)
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| {
-            // Map unique constraint violations to concurrency errors
-            // (happens when another transaction inserts concurrently)
-            if is_unique_violation(&e) {
-                EventStoreError::Concurrency(format!(
-                    "concurrent append detected: sequence_number {} already exists",
-                    next_sequence
-                ))
-            } else {
-                map_sqlx_error("insert_event", e)
+            .await
+            {
+                Ok(stored) => stored_events.extend(stored),
+                Err(e) => {
+                    tx.rollback()
+                        .await
+                        .map_err(|e| map_sqlx_error("rollback", e))?;
+                    return Err(e);
+                }
             }
-        })?;
-
-            let stored = StoredEvent {
-                event_id: event.event_id,
-                tenant_id: event.tenant_id,
-                aggregate_id: event.aggregate_id,
-                aggregate_type: event.aggregate_type,
-                sequence_number: next_sequence,
-                event_type: event.event_type,
-                event_version: event.event_version,
-                occurred_at: event.occurred_at,
-                payload: event.payload,
-            };
-            stored_events.push(stored);
-            next_sequence += 1;
         }
 
-        // Commit transaction
         tx.commit()
             .await
             .map_err(|e| map_sqlx_error("commit_transaction", e))?;
@@ -432,15 +542,150 @@ impl PostgresEventStore {
     }
 }
 
-/// Aggregate snapshot for fast rehydration.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Snapshot {
-    pub tenant_id: TenantId,
-    pub aggregate_id: AggregateId,
-    pub aggregate_type: String,
-    pub version: u64,
-    pub state: serde_json::Value,
-    pub created_at: DateTime<Utc>,
+/// Check the stream version and insert `events` within `tx`.
+///
+/// Shared by single-stream and multi-stream appends; the caller commits or rolls back.
+/// Serialize appends of `tenants` until the transaction ends.
+///
+/// `global_seq` comes from a sequence when the row is inserted, not when it commits, so
+/// two concurrent appends could become visible out of order and a `read_all` reader
+/// would page past the later-committing one for good. Taking the tenant's advisory lock
+/// before the first insert and holding it to commit rules that out. Tenants are locked
+/// in a fixed order so multi-tenant batches cannot deadlock.
+async fn lock_tenant_appends(
+    tx: &mut Transaction<'_, Postgres>,
+    tenants: impl IntoIterator<Item = TenantId>,
+) -> Result<(), EventStoreError> {
+    let tenants: std::collections::BTreeSet<uuid::Uuid> = tenants.into_iter().map(|t| *t.as_uuid()).collect();
+    for tenant_id in tenants {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1::text, 0))")
+            .bind(tenant_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| map_sqlx_error("lock_tenant_appends", e))?;
+    }
+    Ok(())
+}
+
+async fn insert_stream_events(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: TenantId,
+    aggregate_id: AggregateId,
+    aggregate_type: String,
+    events: Vec<UncommittedEvent>,
+    expected_version: ExpectedVersion,
+) -> Result<Vec<StoredEvent>, EventStoreError> {
+    // Check current version and aggregate type
+    let (current_version, existing_aggregate_type) = check_stream_version(
+        tx,
+        tenant_id,
+        aggregate_id,
+    )
+    .await?;
+
+    // Validate aggregate type consistency
+    if let Some(ref existing_type) = existing_aggregate_type
+        && existing_type != &aggregate_type
+    {
+        return Err(EventStoreError::AggregateTypeMismatch(format!(
+            "stream aggregate_type is '{}', attempted append with '{}'",
+            existing_type, aggregate_type
+        )));
+    }
+
+    // Validate expected version
+    if !expected_version.matches(current_version) {
+        return Err(EventStoreError::Concurrency(format!(
+            "optimistic concurrency check failed: expected {:?}, found {}",
+            expected_version, current_version
+        )));
+    }
+
+    // Insert events with sequence numbers starting at current_version + 1
+    let mut stored_events = Vec::with_capacity(events.len());
+
+    for (offset, event) in events.into_iter().enumerate() {
+        let next_sequence = current_version + 1 + offset as u64;
+        // Verify tenant/aggregate consistency one more time
+        if event.tenant_id != tenant_id || event.aggregate_id != aggregate_id {
+            return Err(EventStoreError::TenantIsolation(
+                "event tenant/aggregate mismatch in batch".to_string(),
+            ));
+        }
+
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO events (
+            event_id,
+            tenant_id,
+            aggregate_id,
+            aggregate_type,
+            sequence_number,
+            event_type,
+            event_version,
+            occurred_at,
+            payload,
+            metadata,
+            correlation_id,
+            causation_id
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        RETURNING created_at, global_seq
+        "#,
+    )
+    .bind(event.event_id)
+    .bind(tenant_id.as_uuid())
+    .bind(aggregate_id.as_uuid())
+    .bind(&aggregate_type)
+    .bind(next_sequence as i64)
+    .bind(&event.event_type)
+    .bind(event.event_version as i32)
+    .bind(event.occurred_at)
+    .bind(&event.payload)
+    .bind(serde_json::to_value(&event.metadata).unwrap_or_default())
+    .bind(event.correlation_id)
+    .bind(event.causation_id)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(|e| {
+        // Map unique constraint violations to concurrency errors
+        // (happens when another transaction inserts concurrently)
+        if is_unique_violation(&e) {
+            EventStoreError::Concurrency(format!(
+                "concurrent append detected: sequence_number {} already exists",
+                next_sequence
+            ))
+        } else {
+            map_sqlx_error("insert_event", e)
+        }
+    })?;
+        let created_at: DateTime<Utc> = inserted
+            .try_get("created_at")
+            .map_err(|e| map_sqlx_error("insert_event", e))?;
+        let global_seq: i64 = inserted
+            .try_get("global_seq")
+            .map_err(|e| map_sqlx_error("insert_event", e))?;
+
+        let stored = StoredEvent {
+            event_id: event.event_id,
+            tenant_id: event.tenant_id,
+            aggregate_id: event.aggregate_id,
+            aggregate_type: event.aggregate_type,
+            sequence_number: next_sequence,
+            global_position: GlobalPosition(global_seq as u64),
+            event_type: event.event_type,
+            event_version: event.event_version,
+            occurred_at: event.occurred_at,
+            created_at,
+            payload: event.payload,
+            metadata: event.metadata,
+            correlation_id: event.correlation_id,
+            causation_id: event.causation_id,
+        };
+        stored_events.push(stored);
+    }
+
+    Ok(stored_events)
 }
 
 /// Check the current version of a stream.
@@ -542,8 +787,11 @@ struct StoredEventRow {
     event_version: i32,
     occurred_at: DateTime<Utc>,
     payload: serde_json::Value,
-    #[allow(dead_code)] // Not used in StoredEvent, but kept for potential future use (e.g., monitoring)
+    metadata: serde_json::Value,
+    correlation_id: Option<uuid::Uuid>,
+    causation_id: Option<uuid::Uuid>,
     created_at: DateTime<Utc>,
+    global_seq: i64,
 }
 
 impl<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> for StoredEventRow {
@@ -558,7 +806,11 @@ impl<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> for StoredEventRow {
             event_version: row.try_get("event_version")?,
             occurred_at: row.try_get("occurred_at")?,
             payload: row.try_get("payload")?,
+            metadata: row.try_get("metadata")?,
+            correlation_id: row.try_get("correlation_id")?,
+            causation_id: row.try_get("causation_id")?,
             created_at: row.try_get("created_at")?,
+            global_seq: row.try_get("global_seq")?,
         })
     }
 }
@@ -571,10 +823,15 @@ impl From<StoredEventRow> for StoredEvent {
             aggregate_id: AggregateId::from_uuid(row.aggregate_id),
             aggregate_type: row.aggregate_type,
             sequence_number: row.sequence_number as u64,
+            global_position: GlobalPosition(row.global_seq as u64),
             event_type: row.event_type,
             event_version: row.event_version as u32,
             occurred_at: row.occurred_at,
+            created_at: row.created_at,
             payload: row.payload,
+            metadata: serde_json::from_value(row.metadata).unwrap_or_default(),
+            correlation_id: row.correlation_id,
+            causation_id: row.causation_id,
         }
     }
 }
@@ -645,6 +902,18 @@ impl EventStore for PostgresEventStore {
         )
     }
 
+    fn append_streams(
+        &self,
+        batches: Vec<(Vec<UncommittedEvent>, ExpectedVersion)>,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|_| EventStoreError::InvalidAppend(
+                "PostgresEventStore requires async runtime (tokio). Ensure you're calling from within a tokio runtime context.".to_string()
+            ))?;
+
+        handle.block_on(self.append_streams(batches))
+    }
+
     fn load_stream(
         &self,
         tenant_id: TenantId,
@@ -657,6 +926,79 @@ impl EventStore for PostgresEventStore {
 
         handle.block_on(self.load_stream(tenant_id, aggregate_id))
     }
+
+    fn load_stream_after(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        after_version: u64,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|_| EventStoreError::InvalidAppend(
+                "PostgresEventStore requires async runtime (tokio). Ensure you're calling from within a tokio runtime context.".to_string()
+            ))?;
+
+        handle.block_on(self.load_stream_after(tenant_id, aggregate_id, after_version))
+    }
+
+    fn load_snapshot(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+    ) -> Result<Option<Snapshot>, EventStoreError> {
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|_| EventStoreError::InvalidAppend(
+                "PostgresEventStore requires async runtime (tokio). Ensure you're calling from within a tokio runtime context.".to_string()
+            ))?;
+
+        handle.block_on(self.load_snapshot(tenant_id, aggregate_id))
+    }
+
+    fn store_snapshot(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        snapshot: &Snapshot,
+    ) -> Result<(), EventStoreError> {
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|_| EventStoreError::InvalidAppend(
+                "PostgresEventStore requires async runtime (tokio). Ensure you're calling from within a tokio runtime context.".to_string()
+            ))?;
+
+        handle.block_on(self.store_snapshot(tenant_id, aggregate_id, snapshot))
+    }
+
+    fn tenant_stats(&self, tenant_id: TenantId) -> Result<TenantStats, EventStoreError> {
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|_| EventStoreError::InvalidAppend(
+                "PostgresEventStore requires async runtime (tokio). Ensure you're calling from within a tokio runtime context.".to_string()
+            ))?;
+
+        handle.block_on(self.tenant_stats(tenant_id))
+    }
+
+    fn stream_ids(&self) -> Result<Vec<(TenantId, AggregateId)>, EventStoreError> {
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|_| EventStoreError::InvalidAppend(
+                "PostgresEventStore requires async runtime (tokio). Ensure you're calling from within a tokio runtime context.".to_string()
+            ))?;
+
+        handle.block_on(self.stream_ids())
+    }
+
+    fn read_all(
+        &self,
+        tenant_id: TenantId,
+        after: Option<GlobalPosition>,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|_| EventStoreError::InvalidAppend(
+                "PostgresEventStore requires async runtime (tokio). Ensure you're calling from within a tokio runtime context.".to_string()
+            ))?;
+
+        handle.block_on(self.read_all(tenant_id, after, limit))
+    }
 }
 
 #[async_trait::async_trait]
@@ -684,6 +1026,7 @@ impl EventQuery for PostgresEventStore {
                 AND ($4::text IS NULL OR event_type = $4)
                 AND ($5::timestamp IS NULL OR occurred_at >= $5)
                 AND ($6::timestamp IS NULL OR occurred_at <= $6)
+                AND ($7::text IS NULL OR metadata->>'business_key' = $7)
             "#,
         )
         .bind(tenant_id.as_uuid())
@@ -692,6 +1035,7 @@ impl EventQuery for PostgresEventStore {
         .bind(evt_type_param)
         .bind(filter.occurred_after)
         .bind(filter.occurred_before)
+        .bind(filter.business_key.as_deref())
         .fetch_one(&*self.pool)
         .await
         .map_err(|e| map_sqlx_error("count_events", e))?;
@@ -713,7 +1057,11 @@ impl EventQuery for PostgresEventStore {
                 event_version,
                 occurred_at,
                 payload,
-                created_at
+                metadata,
+                correlation_id,
+                causation_id,
+                created_at,
+                global_seq
             FROM events
             WHERE tenant_id = $1
                 AND ($2::uuid IS NULL OR aggregate_id = $2)
@@ -721,8 +1069,9 @@ impl EventQuery for PostgresEventStore {
                 AND ($4::text IS NULL OR event_type = $4)
                 AND ($5::timestamp IS NULL OR occurred_at >= $5)
                 AND ($6::timestamp IS NULL OR occurred_at <= $6)
-            ORDER BY occurred_at DESC, sequence_number ASC
-            LIMIT $7 OFFSET $8
+                AND ($7::text IS NULL OR metadata->>'business_key' = $7)
+            ORDER BY occurred_at DESC, sequence_number ASC, event_id ASC
+            LIMIT $8 OFFSET $9
             "#,
         )
         .bind(tenant_id.as_uuid())
@@ -731,6 +1080,7 @@ impl EventQuery for PostgresEventStore {
         .bind(evt_type_param)
         .bind(filter.occurred_after)
         .bind(filter.occurred_before)
+        .bind(filter.business_key.as_deref())
         .bind(pagination.limit as i64)
         .bind(pagination.offset as i64)
         .fetch_all(&*self.pool)
@@ -754,6 +1104,85 @@ impl EventQuery for PostgresEventStore {
         })
     }
 
+    async fn query_events_after(
+        &self,
+        tenant_id: TenantId,
+        filter: EventFilter,
+        after: Option<EventCursor>,
+        limit: u32,
+    ) -> Result<EventPage, EventStoreError> {
+        let agg_id_param: Option<uuid::Uuid> = filter.aggregate_id.map(|id| *id.as_uuid());
+
+        // Keyset condition mirrors the ORDER BY: rows strictly after the cursor.
+        // One extra row is fetched to tell whether another page exists.
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                event_id,
+                tenant_id,
+                aggregate_id,
+                aggregate_type,
+                sequence_number,
+                event_type,
+                event_version,
+                occurred_at,
+                payload,
+                metadata,
+                correlation_id,
+                causation_id,
+                created_at,
+                global_seq
+            FROM events
+            WHERE tenant_id = $1
+                AND ($2::uuid IS NULL OR aggregate_id = $2)
+                AND ($3::text IS NULL OR aggregate_type = $3)
+                AND ($4::text IS NULL OR event_type = $4)
+                AND ($5::timestamp IS NULL OR occurred_at >= $5)
+                AND ($6::timestamp IS NULL OR occurred_at <= $6)
+                AND ($7::text IS NULL OR metadata->>'business_key' = $7)
+                AND ($8::timestamp IS NULL
+                    OR occurred_at < $8
+                    OR (occurred_at = $8 AND (sequence_number, event_id) > ($9, $10)))
+            ORDER BY occurred_at DESC, sequence_number ASC, event_id ASC
+            LIMIT $11
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(agg_id_param)
+        .bind(filter.aggregate_type.as_deref())
+        .bind(filter.event_type.as_deref())
+        .bind(filter.occurred_after)
+        .bind(filter.occurred_before)
+        .bind(filter.business_key.as_deref())
+        .bind(after.map(|c| c.occurred_at))
+        .bind(after.map(|c| c.sequence_number as i64))
+        .bind(after.map(|c| c.event_id))
+        .bind(limit as i64 + 1)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| map_sqlx_error("query_events_after", e))?;
+
+        let mut events: Vec<StoredEvent> = Vec::with_capacity(rows.len());
+        for row in rows {
+            let stored = StoredEventRow::from_row(&row)
+                .map_err(|e| EventStoreError::InvalidAppend(format!("failed to deserialize event row: {}", e)))?;
+            events.push(stored.into());
+        }
+
+        let next_cursor = if events.len() > limit as usize {
+            events.truncate(limit as usize);
+            events.last().map(EventCursor::at)
+        } else {
+            None
+        };
+
+        Ok(EventPage {
+            events,
+            limit,
+            next_cursor,
+        })
+    }
+
     async fn get_aggregate_events(
         &self,
         tenant_id: TenantId,
@@ -790,7 +1219,11 @@ impl EventQuery for PostgresEventStore {
                 event_version,
                 occurred_at,
                 payload,
-                created_at
+                metadata,
+                correlation_id,
+                causation_id,
+                created_at,
+                global_seq
             FROM events
             WHERE tenant_id = $1 AND aggregate_id = $2
             ORDER BY sequence_number ASC
@@ -822,6 +1255,77 @@ impl EventQuery for PostgresEventStore {
         })
     }
 
+    async fn get_events_for_aggregates(
+        &self,
+        tenant_id: TenantId,
+        aggregate_ids: &[AggregateId],
+        pagination: Option<Pagination>,
+    ) -> Result<EventQueryResult, EventStoreError> {
+        let pagination = pagination.unwrap_or_default();
+        let ids: Vec<uuid::Uuid> = aggregate_ids.iter().map(|id| *id.as_uuid()).collect();
+
+        let count_row = sqlx::query(
+            "SELECT COUNT(*) as total FROM events WHERE tenant_id = $1 AND aggregate_id = ANY($2)"
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(&ids)
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(|e| map_sqlx_error("count_events_for_aggregates", e))?;
+
+        let total: i64 = count_row
+            .try_get("total")
+            .map_err(|e| EventStoreError::InvalidAppend(format!("failed to read count: {}", e)))?;
+
+        // Single round-trip for all streams, grouped by aggregate then in stream order
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                event_id,
+                tenant_id,
+                aggregate_id,
+                aggregate_type,
+                sequence_number,
+                event_type,
+                event_version,
+                occurred_at,
+                payload,
+                metadata,
+                correlation_id,
+                causation_id,
+                created_at,
+                global_seq
+            FROM events
+            WHERE tenant_id = $1 AND aggregate_id = ANY($2)
+            ORDER BY aggregate_id ASC, sequence_number ASC
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(&ids)
+        .bind(pagination.limit as i64)
+        .bind(pagination.offset as i64)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| map_sqlx_error("get_events_for_aggregates", e))?;
+
+        let mut events = Vec::with_capacity(rows.len());
+        for row in rows {
+            let stored = StoredEventRow::from_row(&row)
+                .map_err(|e| EventStoreError::InvalidAppend(format!("failed to deserialize event row: {}", e)))?;
+            events.push(stored.into());
+        }
+
+        let has_more = total > (pagination.offset + pagination.limit) as i64;
+
+        Ok(EventQueryResult {
+            events,
+            total: total as u64,
+            pagination,
+            has_more,
+        })
+    }
+
     async fn get_event_by_id(
         &self,
         tenant_id: TenantId,
@@ -839,7 +1343,11 @@ impl EventQuery for PostgresEventStore {
                 event_version,
                 occurred_at,
                 payload,
-                created_at
+                metadata,
+                correlation_id,
+                causation_id,
+                created_at,
+                global_seq
             FROM events
             WHERE tenant_id = $1 AND event_id = $2
             LIMIT 1
@@ -2,10 +2,21 @@
 //!
 //! This module provides read-only query capabilities for inspecting events
 //! in the event store. All queries are tenant-scoped and paginated by default.
+//!
+//! Two paging styles are offered. Offset paging ([`Pagination`]) is kept for
+//! compatibility, but pages shift when events are appended between requests.
+//! Keyset paging ([`EventQuery::query_events_after`]) resumes from an [`EventCursor`]
+//! holding the sort key of the last event seen, so it is stable under concurrent
+//! appends and should be preferred by new callers.
+
+use std::cmp::Ordering;
 
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
 use chrono::{DateTime, Utc};
 use forgeerp_core::{AggregateId, TenantId};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::event_store::{EventStoreError, StoredEvent};
 
@@ -36,6 +47,64 @@ impl Pagination {
     }
 }
 
+/// Keyset position in the `query_events` order: the sort key of the last event served.
+///
+/// Events are ordered by `occurred_at` (descending), then `sequence_number` and
+/// `event_id` (ascending); the event id makes the order total across streams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventCursor {
+    pub occurred_at: DateTime<Utc>,
+    pub sequence_number: u64,
+    pub event_id: uuid::Uuid,
+}
+
+/// A cursor string that is not one produced by [`EventCursor::encode`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("invalid event cursor")]
+pub struct InvalidCursor;
+
+impl EventCursor {
+    /// Cursor positioned at `event` (the next page starts after it).
+    pub fn at(event: &StoredEvent) -> Self {
+        Self {
+            occurred_at: event.occurred_at,
+            sequence_number: event.sequence_number,
+            event_id: event.event_id,
+        }
+    }
+
+    /// Opaque, URL-safe token (base64 of the JSON sort key).
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("cursor serializes");
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    pub fn decode(token: &str) -> Result<Self, InvalidCursor> {
+        let bytes = URL_SAFE_NO_PAD.decode(token).map_err(|_| InvalidCursor)?;
+        serde_json::from_slice(&bytes).map_err(|_| InvalidCursor)
+    }
+
+    /// Whether `event` sorts strictly after this position.
+    pub fn precedes(&self, event: &StoredEvent) -> bool {
+        compare_key(
+            (self.occurred_at, self.sequence_number, self.event_id),
+            (event.occurred_at, event.sequence_number, event.event_id),
+        ) == Ordering::Less
+    }
+}
+
+/// Total order used by `query_events`: newest first, then stream position, then id.
+pub fn event_order(a: &StoredEvent, b: &StoredEvent) -> Ordering {
+    compare_key(
+        (a.occurred_at, a.sequence_number, a.event_id),
+        (b.occurred_at, b.sequence_number, b.event_id),
+    )
+}
+
+fn compare_key(a: (DateTime<Utc>, u64, uuid::Uuid), b: (DateTime<Utc>, u64, uuid::Uuid)) -> Ordering {
+    b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2))
+}
+
 /// Filter criteria for event queries.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventFilter {
@@ -49,6 +118,9 @@ pub struct EventFilter {
     pub occurred_after: Option<DateTime<Utc>>,
     /// Filter events that occurred before this time (optional).
     pub occurred_before: Option<DateTime<Utc>>,
+    /// Filter by the `business_key` metadata entry, across aggregates (optional).
+    #[serde(default)]
+    pub business_key: Option<String>,
 }
 
 impl Default for EventFilter {
@@ -59,6 +131,7 @@ impl Default for EventFilter {
             event_type: None,
             occurred_after: None,
             occurred_before: None,
+            business_key: None,
         }
     }
 }
@@ -76,6 +149,17 @@ pub struct EventQueryResult {
     pub has_more: bool,
 }
 
+/// Keyset-paginated event query result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventPage {
+    /// The events of this page, in `query_events` order.
+    pub events: Vec<StoredEvent>,
+    /// Page size used.
+    pub limit: u32,
+    /// Position to resume from; `None` on the last page.
+    pub next_cursor: Option<EventCursor>,
+}
+
 /// Async query interface for event inspection.
 ///
 /// This trait provides read-only query capabilities for inspecting events.
@@ -93,6 +177,19 @@ pub trait EventQuery: Send + Sync {
         pagination: Pagination,
     ) -> Result<EventQueryResult, EventStoreError>;
 
+    /// Query events with keyset paging, starting after `after` (or from the top).
+    ///
+    /// Same filter and order as [`EventQuery::query_events`]. Events appended while a
+    /// client pages through the results never shift items between pages: newer events
+    /// sort before the cursor and are simply not part of the remaining pages.
+    async fn query_events_after(
+        &self,
+        tenant_id: TenantId,
+        filter: EventFilter,
+        after: Option<EventCursor>,
+        limit: u32,
+    ) -> Result<EventPage, EventStoreError>;
+
     /// Get events for a specific aggregate stream.
     ///
     /// This is a convenience method that queries events for a specific aggregate.
@@ -112,6 +209,38 @@ pub trait EventQuery: Send + Sync {
         self.query_events(tenant_id, filter, pagination.unwrap_or_default()).await
     }
 
+    /// Every event of an aggregate stream, read page by page through
+    /// [`EventQuery::get_aggregate_events`] so no page cap truncates it.
+    async fn get_all_aggregate_events(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let mut events = Vec::new();
+        loop {
+            let page = Pagination::new(Some(1000), Some(events.len() as u32));
+            let result = self.get_aggregate_events(tenant_id, aggregate_id, Some(page)).await?;
+            let done = !result.has_more || result.events.is_empty();
+            events.extend(result.events);
+            if done {
+                return Ok(events);
+            }
+        }
+    }
+
+    /// Get events for several aggregate streams in one query.
+    ///
+    /// Results are flattened (each `StoredEvent` carries its `aggregate_id`) and ordered
+    /// by aggregate id, then sequence_number (ascending), so pages are stable and each
+    /// stream's events stay contiguous. Duplicate ids are ignored; ids belonging to other
+    /// tenants match nothing.
+    async fn get_events_for_aggregates(
+        &self,
+        tenant_id: TenantId,
+        aggregate_ids: &[AggregateId],
+        pagination: Option<Pagination>,
+    ) -> Result<EventQueryResult, EventStoreError>;
+
     /// Get a single event by its ID.
     ///
     /// Returns the event if it exists and belongs to the tenant.
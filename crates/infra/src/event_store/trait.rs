@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -43,8 +45,28 @@ pub struct UncommittedEvent {
     pub occurred_at: DateTime<Utc>,
 
     pub payload: JsonValue,
+
+    /// Request-level metadata (see `EventEnvelope::metadata`).
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+
+    /// Flow the event belongs to (see `EventEnvelope::correlation_id`).
+    #[serde(default)]
+    pub correlation_id: Option<Uuid>,
+    /// Command that produced the event (see `EventEnvelope::causation_id`).
+    #[serde(default)]
+    pub causation_id: Option<Uuid>,
 }
 
+/// Position of an event in the store's global commit order, across all streams.
+///
+/// Positions increase with every appended event, so reading a tenant's events by
+/// position replays them in the order they were committed. `0` marks an event that was
+/// not read from a store (e.g. a dry-run preview).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GlobalPosition(pub u64);
+
 /// A stored event in an append-only stream (assigned a sequence number).
 ///
 /// `StoredEvent` represents an event that has been **persisted** to the event store and
@@ -75,12 +97,30 @@ pub struct StoredEvent {
 
     /// Monotonically increasing position in the aggregate stream.
     pub sequence_number: u64,
+    /// Position in the store's global commit order (see [`EventStore::read_all`]).
+    #[serde(default)]
+    pub global_position: GlobalPosition,
 
     pub event_type: String,
     pub event_version: u32,
+    /// Business time: when it happened (from the command; may be backdated).
     pub occurred_at: DateTime<Utc>,
+    /// Storage time: when the store appended it.
+    #[serde(default)]
+    pub created_at: DateTime<Utc>,
 
     pub payload: JsonValue,
+
+    /// Request-level metadata (see `EventEnvelope::metadata`).
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+
+    /// Flow the event belongs to (see `EventEnvelope::correlation_id`).
+    #[serde(default)]
+    pub correlation_id: Option<Uuid>,
+    /// Command that produced the event (see `EventEnvelope::causation_id`).
+    #[serde(default)]
+    pub causation_id: Option<Uuid>,
 }
 
 impl StoredEvent {
@@ -98,6 +138,96 @@ impl StoredEvent {
             self.sequence_number,
             self.payload.clone(),
         )
+        .with_metadata(self.metadata.clone())
+        .with_created_at(self.created_at)
+        .with_correlation(self.correlation_id, self.causation_id)
+        .with_global_position(self.global_position.0)
+    }
+}
+
+/// Per-tenant usage figures (capacity planning, usage-based billing).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantStats {
+    pub event_count: u64,
+    /// Number of distinct aggregate streams.
+    pub aggregate_count: u64,
+    /// Approximate storage footprint in bytes (backend-specific estimate).
+    pub approx_bytes: u64,
+    /// Earliest `occurred_at` across the tenant's events.
+    pub oldest: Option<DateTime<Utc>>,
+    /// Latest `occurred_at` across the tenant's events.
+    pub newest: Option<DateTime<Utc>>,
+}
+
+/// First irregularity found in a stream's sequence numbers (which must run 1, 2, 3, ...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SequenceAnomaly {
+    /// `missing` is absent: the stream jumps from `missing - 1` to `found`.
+    Gap { missing: u64, found: u64 },
+    /// `sequence` occurs more than once (or after a higher number).
+    Duplicate { sequence: u64 },
+}
+
+impl core::fmt::Display for SequenceAnomaly {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SequenceAnomaly::Gap { missing, found } => write!(f, "sequence {missing} missing (next is {found})"),
+            SequenceAnomaly::Duplicate { sequence } => write!(f, "sequence {sequence} is duplicated"),
+        }
+    }
+}
+
+/// First anomaly in `stream` (events in load order), if any.
+pub fn first_sequence_anomaly(stream: &[StoredEvent]) -> Option<SequenceAnomaly> {
+    stream.iter().zip(1u64..).find_map(|(event, expected)| {
+        let sequence = event.sequence_number;
+        match sequence.cmp(&expected) {
+            core::cmp::Ordering::Equal => None,
+            core::cmp::Ordering::Greater => Some(SequenceAnomaly::Gap {
+                missing: expected,
+                found: sequence,
+            }),
+            core::cmp::Ordering::Less => Some(SequenceAnomaly::Duplicate { sequence }),
+        }
+    })
+}
+
+/// Aggregate state captured at a stream version, for fast rehydration.
+///
+/// Rehydrating from a snapshot deserializes `state` and applies only the events after
+/// `version`; the result must equal a full replay of the stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub tenant_id: TenantId,
+    pub aggregate_id: AggregateId,
+    pub aggregate_type: String,
+    pub version: u64,
+    pub state: JsonValue,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Events per [`EventStore::read_all`] call in [`read_all_envelopes`].
+pub const READ_ALL_PAGE_SIZE: usize = 500;
+
+/// The tenant's whole history as envelopes, in global commit order: a cold rebuild of a
+/// projection (e.g. through `ProjectionRunner::rebuild_from_scratch`) without the bus.
+pub fn read_all_envelopes<S>(
+    store: &S,
+    tenant_id: TenantId,
+) -> Result<Vec<forgeerp_events::EventEnvelope<JsonValue>>, EventStoreError>
+where
+    S: EventStore + ?Sized,
+{
+    let mut envelopes = Vec::new();
+    let mut after = None;
+    loop {
+        let page = store.read_all(tenant_id, after, READ_ALL_PAGE_SIZE)?;
+        let Some(last) = page.last() else {
+            return Ok(envelopes);
+        };
+        after = Some(last.global_position);
+        envelopes.extend(page.iter().map(StoredEvent::to_envelope));
     }
 }
 
@@ -113,7 +243,8 @@ impl StoredEvent {
 /// - **TenantIsolation**: Cross-tenant access attempted (security violation)
 /// - **AggregateTypeMismatch**: Event type doesn't match stream's aggregate type
 /// - **InvalidAppend**: Invalid event data or stream state
-/// - **Publish**: Event publication failed (after successful append)
+/// - **Publish**: Event publication failed (after successful append); carries the
+///   committed events so they can be republished without appending them again
 #[derive(Debug, Error)]
 pub enum EventStoreError {
     #[error("optimistic concurrency check failed: {0}")]
@@ -128,8 +259,22 @@ pub enum EventStoreError {
     #[error("invalid append: {0}")]
     InvalidAppend(String),
 
-    #[error("event publication failed: {0}")]
-    Publish(String),
+    #[error("event publication failed ({} events stored but not published): {reason}", committed.len())]
+    Publish {
+        /// Events the append persisted; they are in the store but (some) missed the bus.
+        committed: Vec<StoredEvent>,
+        reason: String,
+    },
+}
+
+impl EventStoreError {
+    /// Events persisted by an append whose publication failed.
+    pub fn committed(&self) -> Option<&[StoredEvent]> {
+        match self {
+            EventStoreError::Publish { committed, .. } => Some(committed),
+            _ => None,
+        }
+    }
 }
 
 /// Append-only, tenant-scoped event store.
@@ -189,12 +334,103 @@ pub trait EventStore: Send + Sync {
         expected_version: ExpectedVersion,
     ) -> Result<Vec<StoredEvent>, EventStoreError>;
 
+    /// Append to several streams atomically: every batch is committed or none is.
+    ///
+    /// Each batch follows the `append` rules for its own stream; committed events are
+    /// returned in batch order. Stores that cannot write several streams in one step
+    /// keep this default, which rejects the append.
+    fn append_streams(
+        &self,
+        batches: Vec<(Vec<UncommittedEvent>, ExpectedVersion)>,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let _ = batches;
+        Err(EventStoreError::InvalidAppend(
+            "multi-stream append is not supported by this store".to_string(),
+        ))
+    }
+
     /// Load the full stream for a tenant + aggregate.
     fn load_stream(
         &self,
         tenant_id: TenantId,
         aggregate_id: AggregateId,
     ) -> Result<Vec<StoredEvent>, EventStoreError>;
+
+    /// Load the events of a stream with `sequence_number > after_version` (the tail after
+    /// a snapshot). The default filters the full stream.
+    fn load_stream_after(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        after_version: u64,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let mut stream = self.load_stream(tenant_id, aggregate_id)?;
+        stream.retain(|e| e.sequence_number > after_version);
+        Ok(stream)
+    }
+
+    /// The latest snapshot of an aggregate, if any.
+    ///
+    /// Stores without snapshot support keep this default and the `store_snapshot`
+    /// default: nothing is kept, so every rehydration replays the full stream.
+    fn load_snapshot(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+    ) -> Result<Option<Snapshot>, EventStoreError> {
+        let _ = (tenant_id, aggregate_id);
+        Ok(None)
+    }
+
+    /// Keep `snapshot` of an aggregate (replacing any snapshot at the same version).
+    fn store_snapshot(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        snapshot: &Snapshot,
+    ) -> Result<(), EventStoreError> {
+        let _ = (tenant_id, aggregate_id, snapshot);
+        Ok(())
+    }
+
+    /// Usage statistics for a tenant (all zeros / `None` when it has no events).
+    fn tenant_stats(&self, tenant_id: TenantId) -> Result<TenantStats, EventStoreError>;
+
+    /// Check that a stream's sequence numbers run 1, 2, 3, ... without gaps or duplicates;
+    /// returns the first anomaly. Catches corruption from bad imports or manual edits.
+    fn verify_sequence_integrity(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+    ) -> Result<Option<SequenceAnomaly>, EventStoreError> {
+        Ok(first_sequence_anomaly(&self.load_stream(tenant_id, aggregate_id)?))
+    }
+
+    /// Every stream in the store, across tenants. Stores that cannot enumerate their
+    /// streams keep this default, which rejects the call.
+    fn stream_ids(&self) -> Result<Vec<(TenantId, AggregateId)>, EventStoreError> {
+        Err(EventStoreError::InvalidAppend(
+            "listing streams is not supported by this store".to_string(),
+        ))
+    }
+
+    /// Up to `limit` of the tenant's events across all streams, in global commit order,
+    /// starting after `after` (from the first event when `None`).
+    ///
+    /// Page through by passing the last returned event's `global_position`; an empty
+    /// page means the reader has caught up. Stores without a global order keep this
+    /// default, which rejects the call.
+    fn read_all(
+        &self,
+        tenant_id: TenantId,
+        after: Option<GlobalPosition>,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let _ = (tenant_id, after, limit);
+        Err(EventStoreError::InvalidAppend(
+            "global reads are not supported by this store".to_string(),
+        ))
+    }
 }
 
 impl<S> EventStore for Arc<S>
@@ -209,6 +445,13 @@ where
         (**self).append(events, expected_version)
     }
 
+    fn append_streams(
+        &self,
+        batches: Vec<(Vec<UncommittedEvent>, ExpectedVersion)>,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        (**self).append_streams(batches)
+    }
+
     fn load_stream(
         &self,
         tenant_id: TenantId,
@@ -216,6 +459,57 @@ where
     ) -> Result<Vec<StoredEvent>, EventStoreError> {
         (**self).load_stream(tenant_id, aggregate_id)
     }
+
+    fn load_stream_after(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        after_version: u64,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        (**self).load_stream_after(tenant_id, aggregate_id, after_version)
+    }
+
+    fn load_snapshot(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+    ) -> Result<Option<Snapshot>, EventStoreError> {
+        (**self).load_snapshot(tenant_id, aggregate_id)
+    }
+
+    fn store_snapshot(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        snapshot: &Snapshot,
+    ) -> Result<(), EventStoreError> {
+        (**self).store_snapshot(tenant_id, aggregate_id, snapshot)
+    }
+
+    fn tenant_stats(&self, tenant_id: TenantId) -> Result<TenantStats, EventStoreError> {
+        (**self).tenant_stats(tenant_id)
+    }
+
+    fn read_all(
+        &self,
+        tenant_id: TenantId,
+        after: Option<GlobalPosition>,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        (**self).read_all(tenant_id, after, limit)
+    }
+
+    fn verify_sequence_integrity(
+        &self,
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+    ) -> Result<Option<SequenceAnomaly>, EventStoreError> {
+        (**self).verify_sequence_integrity(tenant_id, aggregate_id)
+    }
+
+    fn stream_ids(&self) -> Result<Vec<(TenantId, AggregateId)>, EventStoreError> {
+        (**self).stream_ids()
+    }
 }
 
 impl UncommittedEvent {
@@ -245,8 +539,42 @@ impl UncommittedEvent {
             event_version: event.version(),
             occurred_at: event.occurred_at(),
             payload,
+            metadata: BTreeMap::new(),
+            correlation_id: None,
+            causation_id: None,
         })
     }
+
+    /// Fingerprint of the schema this event was written with.
+    ///
+    /// Covers the event type, version and the payload's field names (values are
+    /// ignored), so two events share a fingerprint when the same producer wrote the
+    /// same kind of event. Stable across processes and builds.
+    pub fn schema_fingerprint(&self) -> String {
+        let mut shape = format!("{}@v{}", self.event_type, self.event_version);
+        payload_shape(&self.payload, &mut shape);
+        // FNV-1a: `DefaultHasher` output may change between Rust releases.
+        let hash = shape.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+            (h ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        format!("{hash:016x}")
+    }
+}
+
+/// Append the (sorted) field names of nested objects in `value` to `out`.
+fn payload_shape(value: &JsonValue, out: &mut String) {
+    if let JsonValue::Object(fields) = value {
+        let mut keys: Vec<&String> = fields.keys().collect();
+        keys.sort();
+        out.push('{');
+        for key in keys {
+            out.push_str(key);
+            out.push(':');
+            payload_shape(&fields[key.as_str()], out);
+            out.push(',');
+        }
+        out.push('}');
+    }
 }
 
 
@@ -0,0 +1,188 @@
+//! Client-supplied idempotency keys for command dispatch.
+//!
+//! A client that retries a request after a timeout cannot tell whether the first attempt
+//! committed. When it sends the same idempotency key again, the
+//! [`CommandDispatcher`](crate::command_dispatcher::CommandDispatcher) looks the key up in
+//! an attached [`IdempotencyLog`] and returns the events committed the first time instead
+//! of handling the command again. Reusing a key for a different request is rejected.
+//!
+//! Records are kept per tenant for a retention window, in memory. A key is claimed before
+//! its first dispatch runs, so a concurrent repeat is refused rather than handled twice.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use forgeerp_core::{AggregateId, TenantId};
+
+use crate::clock::{Clock, SystemClock};
+
+/// A client's idempotency key, with a fingerprint of the request it came with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IdempotencyKey {
+    pub key: Uuid,
+    /// Fingerprint of the client's request (see [`fingerprint_of`]); `None` fingerprints
+    /// the dispatched command instead.
+    pub fingerprint: Option<u64>,
+}
+
+impl IdempotencyKey {
+    pub fn new(key: Uuid) -> Self {
+        Self { key, fingerprint: None }
+    }
+
+    /// Compare retries by `request` rather than by the command built from it (whose
+    /// server-stamped ids and times differ on every attempt).
+    pub fn with_request(mut self, request: &impl std::fmt::Debug) -> Self {
+        self.fingerprint = Some(fingerprint_of(request));
+        self
+    }
+}
+
+/// Fingerprint of a value's `Debug` form.
+///
+/// Only comparable within one process: neither `Debug` output nor `DefaultHasher` is
+/// guaranteed to stay the same across builds, so fingerprints are kept in memory only.
+pub fn fingerprint_of(value: &impl std::fmt::Debug) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!("{value:?}").hash(&mut hasher);
+    hasher.finish()
+}
+
+/// What a key's first dispatch committed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotencyRecord {
+    pub aggregate_type: String,
+    pub aggregate_id: AggregateId,
+    pub fingerprint: u64,
+    /// Ids of the committed events, in stream order (empty if the command was a no-op).
+    pub event_ids: Vec<Uuid>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Outcome of [`IdempotencyLog::claim`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyClaim {
+    /// The key is new and now reserved: dispatch, then [`record`](IdempotencyLog::record)
+    /// the result or [`release`](IdempotencyLog::release) the key.
+    Claimed,
+    /// What the key's first dispatch committed.
+    Recorded(IdempotencyRecord),
+    /// The key's first dispatch is still running.
+    InFlight,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    records: HashMap<(TenantId, Uuid), IdempotencyRecord>,
+    claimed: HashSet<(TenantId, Uuid)>,
+}
+
+/// In-memory `(tenant, idempotency key) → committed events` records.
+pub struct IdempotencyLog {
+    retention: Duration,
+    clock: Arc<dyn Clock>,
+    entries: Mutex<Entries>,
+}
+
+impl std::fmt::Debug for IdempotencyLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdempotencyLog")
+            .field("retention", &self.retention)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for IdempotencyLog {
+    /// Keys are honoured for 24 hours.
+    fn default() -> Self {
+        Self::new(Duration::hours(24))
+    }
+}
+
+impl IdempotencyLog {
+    /// Log honouring each key for `retention` after its first dispatch.
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            retention,
+            clock: Arc::new(SystemClock),
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Time source for `recorded_at` and expiry (defaults to the system clock).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The unexpired record of `key` in `tenant_id`, if any.
+    pub fn get(&self, tenant_id: TenantId, key: Uuid) -> Option<IdempotencyRecord> {
+        let cutoff = self.clock.now() - self.retention;
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .records
+            .get(&(tenant_id, key))
+            .filter(|record| record.recorded_at > cutoff)
+            .cloned()
+    }
+
+    /// Look up `key` in `tenant_id` and, when it has neither an unexpired record nor a
+    /// running dispatch, reserve it, in one step.
+    pub fn claim(&self, tenant_id: TenantId, key: Uuid) -> IdempotencyClaim {
+        let cutoff = self.clock.now() - self.retention;
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(record) = entries.records.get(&(tenant_id, key)).filter(|r| r.recorded_at > cutoff) {
+            return IdempotencyClaim::Recorded(record.clone());
+        }
+        if entries.claimed.insert((tenant_id, key)) {
+            IdempotencyClaim::Claimed
+        } else {
+            IdempotencyClaim::InFlight
+        }
+    }
+
+    /// Give up a claim whose dispatch committed nothing, so the key can be used again.
+    pub fn release(&self, tenant_id: TenantId, key: Uuid) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.claimed.remove(&(tenant_id, key));
+    }
+
+    /// Record what `key`'s dispatch committed (ending its claim), dropping expired records.
+    pub fn record(
+        &self,
+        tenant_id: TenantId,
+        key: Uuid,
+        aggregate_type: &str,
+        aggregate_id: AggregateId,
+        fingerprint: u64,
+        event_ids: Vec<Uuid>,
+    ) {
+        let now = self.clock.now();
+        let cutoff = now - self.retention;
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.claimed.remove(&(tenant_id, key));
+        entries.records.retain(|_, record| record.recorded_at > cutoff);
+        entries.records.insert(
+            (tenant_id, key),
+            IdempotencyRecord {
+                aggregate_type: aggregate_type.to_string(),
+                aggregate_id,
+                fingerprint,
+                event_ids,
+                recorded_at: now,
+            },
+        );
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,303 @@
+//! Domain event → integration event mapping.
+//!
+//! Internal domain events are free to change shape as the domain evolves.
+//! External consumers instead subscribe to **integration events**: a curated,
+//! explicitly versioned subset (e.g. `public.invoice.issued.v1`) published on a
+//! separate "public" bus.
+//!
+//! Mappings are declarative: one [`IntegrationMapping`] per internal event,
+//! keyed by aggregate type + payload variant (the same tag the sagas correlate
+//! on). Events without a mapping are internal-only and never leave the system.
+
+use std::collections::HashMap;
+
+use forgeerp_core::TenantId;
+use forgeerp_events::{EventBus, EventEnvelope};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use uuid::Uuid;
+
+/// Stable, externally-versioned event published to the public bus.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrationEvent {
+    /// Id of the source domain event (lets consumers deduplicate redeliveries).
+    pub event_id: Uuid,
+    pub tenant_id: TenantId,
+    /// Public event name, including the schema version (e.g. `public.invoice.issued.v1`).
+    pub event_type: String,
+    pub payload: JsonValue,
+}
+
+/// Builds the curated public payload from the internal event body.
+///
+/// Returning `None` drops the event (e.g. a required field is missing).
+pub type PayloadMapper = fn(&JsonValue) -> Option<JsonValue>;
+
+/// Declarative mapping for one internal event.
+#[derive(Debug, Clone, Copy)]
+pub struct IntegrationMapping {
+    /// Aggregate type of the source stream (e.g. `invoicing.invoice`).
+    pub aggregate_type: &'static str,
+    /// Payload variant of the internal event enum (e.g. `InvoiceIssued`).
+    pub variant: &'static str,
+    /// Public event name (e.g. `public.invoice.issued.v1`).
+    pub public_type: &'static str,
+    pub map: PayloadMapper,
+}
+
+/// Registry of integration mappings.
+#[derive(Debug, Clone)]
+pub struct IntegrationEventMapper {
+    mappings: HashMap<(&'static str, &'static str), IntegrationMapping>,
+}
+
+impl IntegrationEventMapper {
+    /// Empty mapper (nothing is published).
+    pub fn new() -> Self {
+        Self {
+            mappings: HashMap::new(),
+        }
+    }
+
+    /// Register (or replace) the mapping for an internal event.
+    pub fn register(mut self, mapping: IntegrationMapping) -> Self {
+        self.mappings
+            .insert((mapping.aggregate_type, mapping.variant), mapping);
+        self
+    }
+
+    /// Translate a domain envelope; `None` for internal-only events.
+    pub fn map(&self, envelope: &EventEnvelope<JsonValue>) -> Option<IntegrationEvent> {
+        let (variant, body) = envelope.payload().as_object()?.iter().next()?;
+        let mapping = self
+            .mappings
+            .get(&(envelope.aggregate_type(), variant.as_str()))?;
+
+        Some(IntegrationEvent {
+            event_id: envelope.event_id(),
+            tenant_id: envelope.tenant_id(),
+            event_type: mapping.public_type.to_string(),
+            payload: (mapping.map)(body)?,
+        })
+    }
+}
+
+impl Default for IntegrationEventMapper {
+    /// Mapper with the integration events currently part of the public contract.
+    fn default() -> Self {
+        Self::new()
+            .register(IntegrationMapping {
+                aggregate_type: "invoicing.invoice",
+                variant: "InvoiceIssued",
+                public_type: "public.invoice.issued.v1",
+                map: |e| {
+                    Some(json!({
+                        "invoice_id": e.get("invoice_id")?,
+                        "sales_order_id": e.get("sales_order_id")?,
+                        "total_amount": e.get("total_amount")?,
+                        "due_date": e.get("due_date")?,
+                        "issued_at": e.get("occurred_at")?,
+                    }))
+                },
+            })
+            .register(IntegrationMapping {
+                aggregate_type: "invoicing.invoice",
+                variant: "PaymentRegistered",
+                public_type: "public.invoice.payment_received.v1",
+                map: |e| {
+                    Some(json!({
+                        "invoice_id": e.get("invoice_id")?,
+                        "amount": e.get("amount")?,
+                        "total_paid": e.get("new_total_paid")?,
+                        "received_at": e.get("occurred_at")?,
+                    }))
+                },
+            })
+            .register(IntegrationMapping {
+                aggregate_type: "invoicing.invoice",
+                variant: "PaymentReversed",
+                public_type: "public.invoice.payment_reversed.v1",
+                map: |e| {
+                    Some(json!({
+                        "invoice_id": e.get("invoice_id")?,
+                        "payment_id": e.get("payment_id")?,
+                        "amount": e.get("amount")?,
+                        "total_paid": e.get("new_total_paid")?,
+                        "reversed_at": e.get("occurred_at")?,
+                    }))
+                },
+            })
+            .register(IntegrationMapping {
+                aggregate_type: "invoicing.invoice",
+                variant: "InvoiceVoided",
+                public_type: "public.invoice.voided.v1",
+                map: |e| {
+                    Some(json!({
+                        "invoice_id": e.get("invoice_id")?,
+                        "voided_at": e.get("occurred_at")?,
+                    }))
+                },
+            })
+            .register(IntegrationMapping {
+                aggregate_type: "sales.order",
+                variant: "OrderConfirmed",
+                public_type: "public.sales_order.confirmed.v1",
+                map: |e| {
+                    Some(json!({
+                        "order_id": e.get("order_id")?,
+                        "confirmed_at": e.get("occurred_at")?,
+                    }))
+                },
+            })
+            .register(IntegrationMapping {
+                aggregate_type: "purchasing.order",
+                variant: "GoodsReceived",
+                public_type: "public.purchase_order.goods_received.v1",
+                map: |e| {
+                    let lines = e
+                        .get("lines")?
+                        .as_array()?
+                        .iter()
+                        .map(|l| {
+                            Some(json!({
+                                "product_id": l.get("product_id")?,
+                                "quantity": l.get("quantity")?,
+                            }))
+                        })
+                        .collect::<Option<Vec<_>>>()?;
+                    Some(json!({
+                        "order_id": e.get("order_id")?,
+                        "supplier_id": e.get("supplier_id")?,
+                        "lines": lines,
+                        "received_at": e.get("occurred_at")?,
+                    }))
+                },
+            })
+    }
+}
+
+/// Forwards mapped domain events to the public bus.
+pub struct IntegrationEventRelay<B> {
+    mapper: IntegrationEventMapper,
+    public_bus: B,
+}
+
+impl<B> IntegrationEventRelay<B>
+where
+    B: EventBus<IntegrationEvent>,
+{
+    pub fn new(mapper: IntegrationEventMapper, public_bus: B) -> Self {
+        Self { mapper, public_bus }
+    }
+
+    /// Map and publish one domain envelope.
+    ///
+    /// Returns `Ok(false)` when the event is internal-only.
+    pub fn relay(&self, envelope: &EventEnvelope<JsonValue>) -> Result<bool, B::Error> {
+        match self.mapper.map(envelope) {
+            Some(event) => {
+                self.public_bus.publish(event)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use forgeerp_core::AggregateId;
+    use forgeerp_events::InMemoryEventBus;
+    use forgeerp_invoicing::{InvoiceEvent, InvoiceId, InvoiceIssued, InvoiceLine, PaymentRegistered};
+    use forgeerp_products::ProductId;
+    use forgeerp_sales::SalesOrderId;
+
+    use super::*;
+
+    fn envelope(tenant_id: TenantId, aggregate_type: &str, event: &InvoiceEvent) -> EventEnvelope<JsonValue> {
+        EventEnvelope::new(
+            Uuid::now_v7(),
+            tenant_id,
+            AggregateId::new(),
+            aggregate_type,
+            1,
+            serde_json::to_value(event).unwrap(),
+        )
+    }
+
+    fn invoice_issued(tenant_id: TenantId) -> InvoiceEvent {
+        let sales_order_id = SalesOrderId::new(AggregateId::new());
+        InvoiceEvent::InvoiceIssued(InvoiceIssued {
+            tenant_id,
+            invoice_id: InvoiceId::new(AggregateId::new()),
+            sales_order_id,
+            lines: vec![InvoiceLine {
+                line_no: 1,
+                sales_order_id,
+                product_id: ProductId::new(AggregateId::new()),
+                quantity: 2,
+                unit_price: 500,
+            }],
+            payment_terms_days: 30,
+            due_date: Utc::now(),
+            total_amount: 1000,
+            currency: None,
+            occurred_at: Utc::now(),
+        })
+    }
+
+    #[test]
+    fn invoice_issued_maps_to_curated_public_event() {
+        let tenant_id = TenantId::new();
+        let event = invoice_issued(tenant_id);
+        let env = envelope(tenant_id, "invoicing.invoice", &event);
+        let InvoiceEvent::InvoiceIssued(issued) = &event else { unreachable!() };
+
+        let public = IntegrationEventMapper::default().map(&env).expect("mapped");
+
+        assert_eq!(public.event_type, "public.invoice.issued.v1");
+        assert_eq!(public.event_id, env.event_id());
+        assert_eq!(public.tenant_id, tenant_id);
+        assert_eq!(public.payload["invoice_id"], json!(issued.invoice_id));
+        assert_eq!(public.payload["total_amount"], json!(1000));
+        // Internal details (line breakdown, tenant id in body) are not part of the contract.
+        assert!(public.payload.get("lines").is_none());
+        assert!(public.payload.get("tenant_id").is_none());
+    }
+
+    #[test]
+    fn unmapped_events_are_not_published() {
+        let tenant_id = TenantId::new();
+        let bus: Arc<InMemoryEventBus<IntegrationEvent>> = Arc::new(InMemoryEventBus::new());
+        let sub = bus.subscribe();
+        let relay = IntegrationEventRelay::new(
+            IntegrationEventMapper::new().register(IntegrationMapping {
+                aggregate_type: "invoicing.invoice",
+                variant: "InvoiceIssued",
+                public_type: "public.invoice.issued.v1",
+                map: |e| Some(e.clone()),
+            }),
+            bus.clone(),
+        );
+
+        let payment = InvoiceEvent::PaymentRegistered(PaymentRegistered {
+            tenant_id,
+            invoice_id: InvoiceId::new(AggregateId::new()),
+            payment_id: None,
+            amount: 10,
+            new_total_paid: 10,
+            currency: None,
+            occurred_at: Utc::now(),
+        });
+        assert!(!relay.relay(&envelope(tenant_id, "invoicing.invoice", &payment)).unwrap());
+        // Same variant on a different aggregate type is not a match either.
+        assert!(!relay.relay(&envelope(tenant_id, "sales.order", &invoice_issued(tenant_id))).unwrap());
+        assert!(sub.try_recv().is_err());
+
+        assert!(relay.relay(&envelope(tenant_id, "invoicing.invoice", &invoice_issued(tenant_id))).unwrap());
+        assert_eq!(sub.try_recv().unwrap().event_type, "public.invoice.issued.v1");
+    }
+}
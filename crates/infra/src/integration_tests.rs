@@ -15,13 +15,13 @@ mod tests {
     use forgeerp_core::{AggregateId, TenantId};
     use forgeerp_events::{EventBus, EventEnvelope, InMemoryEventBus};
     use forgeerp_inventory::{
-        AdjustStock, CreateItem, InventoryCommand, InventoryItem, InventoryItemId,
+        AdjustStock, CreateItem, DEFAULT_LOCATION, InventoryCommand, InventoryItem, InventoryItemId, RenameItem,
     };
 
     use crate::command_dispatcher::{CommandDispatcher, DispatchError};
     use crate::event_store::InMemoryEventStore;
-    use crate::projections::inventory_stock::InventoryStockProjection;
-    use crate::read_model::InMemoryTenantStore;
+    use crate::projections::inventory_stock::{InventoryReadModel, InventoryStockProjection};
+    use crate::read_model::{InMemoryTenantStore, TenantStore, Watermark};
 
     fn test_tenant_id() -> TenantId {
         TenantId::new()
@@ -140,6 +140,7 @@ mod tests {
         let adjust_cmd = AdjustStock {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 10,
             occurred_at: Utc::now(),
         };
@@ -190,6 +191,7 @@ mod tests {
             let adjust_cmd = AdjustStock {
                 tenant_id,
                 item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
                 delta,
                 occurred_at: Utc::now(),
             };
@@ -298,6 +300,7 @@ mod tests {
         let adjust1 = AdjustStock {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 10,
             occurred_at: Utc::now(),
         };
@@ -317,6 +320,7 @@ mod tests {
         let adjust2 = AdjustStock {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 5,
             occurred_at: Utc::now(),
         };
@@ -364,6 +368,7 @@ mod tests {
         let adjust_cmd = AdjustStock {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: -1,
             occurred_at: Utc::now(),
         };
@@ -437,6 +442,7 @@ mod tests {
         let adjust1 = AdjustStock {
             tenant_id,
             item_id: item1_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 20,
             occurred_at: Utc::now(),
         };
@@ -454,6 +460,7 @@ mod tests {
         let adjust2 = AdjustStock {
             tenant_id,
             item_id: item2_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 30,
             occurred_at: Utc::now(),
         };
@@ -481,4 +488,189 @@ mod tests {
         assert_eq!(item2.quantity, 30);
         assert_eq!(item2.name, "Item 2");
     }
+    fn adjust(
+        dispatcher: &CommandDispatcher<InMemoryEventStore, Arc<InMemoryEventBus<EventEnvelope<serde_json::Value>>>>,
+        tenant_id: TenantId,
+        item_id: InventoryItemId,
+        delta: i64,
+    ) {
+        let cmd = InventoryCommand::AdjustStock(AdjustStock {
+            tenant_id,
+            item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
+            delta,
+            occurred_at: Utc::now(),
+        });
+        dispatcher
+            .dispatch(tenant_id, item_id.0, "inventory.item", cmd, |_, id| {
+                InventoryItem::empty(InventoryItemId::new(id))
+            })
+            .unwrap();
+    }
+
+    fn create(
+        dispatcher: &CommandDispatcher<InMemoryEventStore, Arc<InMemoryEventBus<EventEnvelope<serde_json::Value>>>>,
+        tenant_id: TenantId,
+        item_id: InventoryItemId,
+    ) {
+        let cmd = InventoryCommand::CreateItem(CreateItem {
+            tenant_id,
+            item_id,
+            name: "Item".to_string(),
+            occurred_at: Utc::now(),
+        });
+        dispatcher
+            .dispatch(tenant_id, item_id.0, "inventory.item", cmd, |_, id| {
+                InventoryItem::empty(InventoryItemId::new(id))
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn rename_updates_read_model_name_and_keeps_quantity() {
+        let (dispatcher, projection) = setup();
+        let tenant_id = test_tenant_id();
+        let item_id = test_item_id();
+
+        create(&dispatcher, tenant_id, item_id);
+        adjust(&dispatcher, tenant_id, item_id, 4);
+        let cmd = InventoryCommand::RenameItem(RenameItem {
+            tenant_id,
+            item_id,
+            name: "Widget".to_string(),
+            occurred_at: Utc::now(),
+        });
+        dispatcher
+            .dispatch(tenant_id, item_id.0, "inventory.item", cmd, |_, id| {
+                InventoryItem::empty(InventoryItemId::new(id))
+            })
+            .unwrap();
+        wait_for_processing();
+
+        let rm = projection.get(tenant_id, &item_id).unwrap();
+        assert_eq!((rm.name.as_str(), rm.quantity, rm.last_sequence), ("Widget", 4, 3));
+    }
+
+    #[test]
+    fn watermark_advances_on_each_projection_update() {
+        let (dispatcher, projection) = setup();
+        let tenant_id = test_tenant_id();
+        let item_id = test_item_id();
+
+        assert_eq!(projection.watermark(tenant_id), Watermark::default());
+
+        create(&dispatcher, tenant_id, item_id);
+        wait_for_processing();
+        let created = projection.get(tenant_id, &item_id).unwrap().watermark();
+        assert_eq!(created.last_sequence, 1);
+
+        adjust(&dispatcher, tenant_id, item_id, 5);
+        wait_for_processing();
+        let adjusted = projection.get(tenant_id, &item_id).unwrap().watermark();
+        assert_eq!(adjusted.last_sequence, 2);
+        assert!(adjusted.updated_at >= created.updated_at);
+        assert_eq!(projection.watermark(tenant_id), adjusted);
+    }
+
+    #[test]
+    fn tenant_watermark_is_the_highest_item_watermark() {
+        let (dispatcher, projection) = setup();
+        let tenant_id = test_tenant_id();
+        let (busy, quiet) = (test_item_id(), test_item_id());
+
+        create(&dispatcher, tenant_id, busy);
+        for delta in [1, 2, 3] {
+            adjust(&dispatcher, tenant_id, busy, delta);
+        }
+        create(&dispatcher, tenant_id, quiet);
+        wait_for_processing();
+
+        let items = projection.list(tenant_id);
+        let highest = items.iter().map(|rm| rm.last_sequence).max().unwrap();
+        let latest = items.iter().map(|rm| rm.updated_at).max();
+
+        let watermark = projection.watermark(tenant_id);
+        assert_eq!(watermark.last_sequence, highest);
+        assert_eq!(watermark.last_sequence, 4);
+        assert_eq!(watermark.updated_at, latest);
+        // Other tenants are unaffected.
+        assert_eq!(projection.watermark(test_tenant_id()), Watermark::default());
+    }
+
+    /// Read-model store counting its writes (stand-in for database round trips).
+    #[derive(Default)]
+    struct CountingStore {
+        inner: InMemoryTenantStore<InventoryItemId, InventoryReadModel>,
+        writes: std::sync::atomic::AtomicUsize,
+    }
+
+    impl TenantStore<InventoryItemId, InventoryReadModel> for CountingStore {
+        fn get(&self, tenant_id: TenantId, key: &InventoryItemId) -> Option<InventoryReadModel> {
+            self.inner.get(tenant_id, key)
+        }
+
+        fn upsert(&self, tenant_id: TenantId, key: InventoryItemId, value: InventoryReadModel) {
+            self.writes.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.inner.upsert(tenant_id, key, value)
+        }
+
+        fn upsert_many(&self, tenant_id: TenantId, entries: Vec<(InventoryItemId, InventoryReadModel)>) {
+            self.writes.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.inner.upsert_many(tenant_id, entries)
+        }
+
+        fn list(&self, tenant_id: TenantId) -> Vec<InventoryReadModel> {
+            self.inner.list(tenant_id)
+        }
+
+        fn clear_tenant(&self, tenant_id: TenantId) {
+            self.inner.clear_tenant(tenant_id)
+        }
+    }
+
+    /// 10k committed inventory envelopes: 100 items, each created then adjusted 99 times.
+    fn ten_thousand_envelopes(tenant_id: TenantId) -> Vec<EventEnvelope<serde_json::Value>> {
+        let bus = Arc::new(InMemoryEventBus::new());
+        let sub = bus.subscribe();
+        let dispatcher = CommandDispatcher::new(InMemoryEventStore::new(), bus);
+        for _ in 0..100 {
+            let item_id = test_item_id();
+            create(&dispatcher, tenant_id, item_id);
+            for delta in 1..100 {
+                adjust(&dispatcher, tenant_id, item_id, delta);
+            }
+        }
+        std::iter::from_fn(|| sub.try_recv().ok()).collect()
+    }
+
+    #[test]
+    fn batch_apply_of_10k_events_matches_one_by_one_with_one_write() {
+        let tenant_id = test_tenant_id();
+        let envelopes = ten_thousand_envelopes(tenant_id);
+        assert_eq!(envelopes.len(), 10_000);
+
+        let one_by_one_store = Arc::new(CountingStore::default());
+        let one_by_one = InventoryStockProjection::new(one_by_one_store.clone());
+        for env in &envelopes {
+            one_by_one.apply_envelope(env).unwrap();
+        }
+
+        let batched_store = Arc::new(CountingStore::default());
+        let batched = InventoryStockProjection::new(batched_store.clone());
+        batched.apply_batch(&envelopes).unwrap();
+        // Redelivered envelopes are skipped, as one by one.
+        batched.apply_batch(&envelopes[..10]).unwrap();
+
+        let sorted = |projection: &InventoryStockProjection<Arc<CountingStore>>| {
+            let mut items = projection.list(tenant_id);
+            items.sort_by_key(|rm| *rm.item_id.0.as_uuid());
+            items
+        };
+        assert_eq!(sorted(&batched), sorted(&one_by_one));
+        assert!(sorted(&batched).iter().all(|rm| rm.quantity == 4950 && rm.last_sequence == 100));
+
+        let writes = |store: &CountingStore| store.writes.load(std::sync::atomic::Ordering::SeqCst);
+        assert_eq!(writes(&one_by_one_store), 10_000);
+        assert_eq!(writes(&batched_store), 1);
+    }
 }
@@ -0,0 +1,260 @@
+//! Dead-letter rate alerting.
+//!
+//! A broken dependency (e.g. the AI backend) makes every job of one kind dead-letter,
+//! and nobody notices until the queue is inspected. The [`DeadLetterMonitor`] counts
+//! dead letters per job kind in fixed windows; once a kind exceeds the configured
+//! threshold within a window it sends one [`DeadLetterAlert`] through the [`Notifier`]
+//! (and counts it), then stays quiet until the next window.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::error;
+
+use crate::clock::{Clock, SystemClock};
+
+use super::types::JobKind;
+
+/// Delivers operator alerts (log, pager, chat webhook...).
+pub trait Notifier: Send + Sync {
+    fn notify(&self, alert: &DeadLetterAlert);
+}
+
+/// Notifier that logs alerts at error level (the default sink).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn notify(&self, alert: &DeadLetterAlert) {
+        error!(
+            job_kind = %alert.job_kind,
+            dead_letters = alert.dead_letters,
+            threshold = alert.threshold,
+            window_secs = alert.window.as_secs(),
+            "dead-letter rate threshold exceeded"
+        );
+    }
+}
+
+/// Alert threshold: more than `threshold` dead letters of one kind within `window`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadLetterAlertConfig {
+    pub threshold: u32,
+    pub window: Duration,
+}
+
+impl Default for DeadLetterAlertConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 10,
+            window: Duration::from_secs(300),
+        }
+    }
+}
+
+impl DeadLetterAlertConfig {
+    /// Config from `DEAD_LETTER_ALERT_THRESHOLD` and `DEAD_LETTER_ALERT_WINDOW_SECS`
+    /// (defaults for unset or invalid values).
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        let defaults = Self::default();
+        Self {
+            threshold: var("DEAD_LETTER_ALERT_THRESHOLD")
+                .and_then(|v| u32::try_from(v).ok())
+                .unwrap_or(defaults.threshold),
+            window: var("DEAD_LETTER_ALERT_WINDOW_SECS")
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.window),
+        }
+    }
+}
+
+/// Sent when a job kind crosses the dead-letter threshold.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeadLetterAlert {
+    pub job_kind: String,
+    /// Dead letters of this kind in the window so far.
+    pub dead_letters: u32,
+    pub threshold: u32,
+    pub window: Duration,
+    pub window_started_at: DateTime<Utc>,
+}
+
+/// Dead-letter counters for one job kind.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeadLetterKindStats {
+    pub job_kind: String,
+    /// Dead letters in the current window.
+    pub in_window: u32,
+    pub total: u64,
+    /// Alerts sent for this kind.
+    pub alerts: u64,
+}
+
+#[derive(Debug)]
+struct KindWindow {
+    started_at: DateTime<Utc>,
+    count: u32,
+    alerted: bool,
+    total: u64,
+    alerts: u64,
+}
+
+/// Tracks dead-letter rates per job kind and alerts on threshold crossings.
+pub struct DeadLetterMonitor {
+    config: DeadLetterAlertConfig,
+    notifier: Arc<dyn Notifier>,
+    clock: Arc<dyn Clock>,
+    kinds: Mutex<BTreeMap<String, KindWindow>>,
+}
+
+impl DeadLetterMonitor {
+    pub fn new(config: DeadLetterAlertConfig, notifier: Arc<dyn Notifier>) -> Self {
+        Self {
+            config,
+            notifier,
+            clock: Arc::new(SystemClock),
+            kinds: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Time source for windows (defaults to the system clock).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn config(&self) -> DeadLetterAlertConfig {
+        self.config
+    }
+
+    /// Record one dead-lettered job; returns the alert if this crossed the threshold.
+    pub fn record(&self, kind: &JobKind) -> Option<DeadLetterAlert> {
+        let now = self.clock.now();
+        let window = chrono::Duration::from_std(self.config.window).unwrap_or(chrono::Duration::MAX);
+        let alert = {
+            let mut kinds = self.kinds.lock().unwrap_or_else(|e| e.into_inner());
+            let w = kinds.entry(kind.type_name().to_string()).or_insert(KindWindow {
+                started_at: now,
+                count: 0,
+                alerted: false,
+                total: 0,
+                alerts: 0,
+            });
+            if now - w.started_at >= window {
+                w.started_at = now;
+                w.count = 0;
+                w.alerted = false;
+            }
+            w.count += 1;
+            w.total += 1;
+            if w.count > self.config.threshold && !w.alerted {
+                w.alerted = true;
+                w.alerts += 1;
+                Some(DeadLetterAlert {
+                    job_kind: kind.type_name().to_string(),
+                    dead_letters: w.count,
+                    threshold: self.config.threshold,
+                    window: self.config.window,
+                    window_started_at: w.started_at,
+                })
+            } else {
+                None
+            }
+        };
+        if let Some(alert) = &alert {
+            self.notifier.notify(alert);
+        }
+        alert
+    }
+
+    /// Per-kind counters, ordered by kind.
+    pub fn stats(&self) -> Vec<DeadLetterKindStats> {
+        let kinds = self.kinds.lock().unwrap_or_else(|e| e.into_inner());
+        kinds
+            .iter()
+            .map(|(kind, w)| DeadLetterKindStats {
+                job_kind: kind.clone(),
+                in_window: w.count,
+                total: w.total,
+                alerts: w.alerts,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+
+    #[derive(Default)]
+    struct RecordingNotifier {
+        alerts: Mutex<Vec<DeadLetterAlert>>,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn notify(&self, alert: &DeadLetterAlert) {
+            self.alerts.lock().unwrap().push(alert.clone());
+        }
+    }
+
+    fn monitor(threshold: u32) -> (DeadLetterMonitor, Arc<RecordingNotifier>, Arc<FixedClock>) {
+        let notifier = Arc::new(RecordingNotifier::default());
+        let clock = FixedClock::arc(Utc::now());
+        let config = DeadLetterAlertConfig {
+            threshold,
+            window: Duration::from_secs(60),
+        };
+        let monitor = DeadLetterMonitor::new(config, notifier.clone()).with_clock(clock.clone());
+        (monitor, notifier, clock)
+    }
+
+    #[test]
+    fn crossing_the_threshold_alerts_once_per_window() {
+        let (monitor, notifier, clock) = monitor(3);
+        let kind = JobKind::ai_inference("ai.inventory_anomaly");
+
+        for _ in 0..3 {
+            assert!(monitor.record(&kind).is_none());
+        }
+        let alert = monitor.record(&kind).expect("fourth dead letter exceeds the threshold");
+        assert_eq!(alert.dead_letters, 4);
+        for _ in 0..5 {
+            assert!(monitor.record(&kind).is_none());
+        }
+        assert_eq!(notifier.alerts.lock().unwrap().len(), 1);
+
+        // The next window alerts again once it crosses the threshold.
+        clock.set(clock.now() + chrono::Duration::seconds(61));
+        for _ in 0..4 {
+            monitor.record(&kind);
+        }
+        assert_eq!(notifier.alerts.lock().unwrap().len(), 2);
+        let stats = monitor.stats();
+        assert_eq!((stats[0].total, stats[0].alerts, stats[0].in_window), (13, 2, 4));
+    }
+
+    #[test]
+    fn staying_below_the_threshold_never_alerts() {
+        let (monitor, notifier, clock) = monitor(3);
+        let kind = JobKind::custom("reports.export");
+        let other = JobKind::custom("mail.send");
+
+        for _ in 0..4 {
+            for _ in 0..3 {
+                monitor.record(&kind);
+            }
+            // Dead letters of another kind do not count toward this one.
+            monitor.record(&other);
+            clock.set(clock.now() + chrono::Duration::seconds(61));
+        }
+
+        assert!(notifier.alerts.lock().unwrap().is_empty());
+        assert!(monitor.stats().iter().all(|s| s.alerts == 0));
+    }
+}
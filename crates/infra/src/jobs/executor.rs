@@ -10,6 +10,7 @@ use tracing::{debug, error, info, warn};
 
 use forgeerp_core::TenantId;
 
+use super::alerting::DeadLetterMonitor;
 use super::store::{JobStore, JobStoreError};
 use super::types::{Job, JobId, JobKind, JobResult, JobStatus};
 
@@ -98,6 +99,7 @@ pub struct ExecutorStats {
 pub struct JobExecutor<S: JobStore> {
     store: S,
     handlers: HashMap<String, JobHandler>,
+    dead_letters: Option<Arc<DeadLetterMonitor>>,
 }
 
 impl<S: JobStore + 'static> JobExecutor<S> {
@@ -106,6 +108,19 @@ impl<S: JobStore + 'static> JobExecutor<S> {
         Self {
             store,
             handlers: HashMap::new(),
+            dead_letters: None,
+        }
+    }
+
+    /// Report dead-lettered jobs to `monitor` (alerts on dead-letter rate spikes).
+    pub fn with_dead_letter_monitor(mut self, monitor: Arc<DeadLetterMonitor>) -> Self {
+        self.dead_letters = Some(monitor);
+        self
+    }
+
+    fn record_dead_letter(&self, job: &Job) {
+        if let Some(monitor) = &self.dead_letters {
+            monitor.record(&job.kind);
         }
     }
 
@@ -186,6 +201,7 @@ impl<S: JobStore + 'static> JobExecutor<S> {
                     self.store
                         .dead_letter(job.clone(), error.clone())
                         .map_err(|e| e.to_string())?;
+                    self.record_dead_letter(job);
                 }
 
                 Err(error)
@@ -207,7 +223,7 @@ impl<S: JobStore + 'static> JobExecutor<S> {
     }
 }
 
-fn executor_loop<S: JobStore>(
+fn executor_loop<S: JobStore + 'static>(
     executor: JobExecutor<S>,
     config: JobExecutorConfig,
     shutdown_rx: mpsc::Receiver<()>,
@@ -285,7 +301,7 @@ fn executor_loop<S: JobStore>(
     info!(executor = %config.name, "job executor stopped");
 }
 
-fn execute_job<S: JobStore>(executor: &JobExecutor<S>, job: &mut Job) -> Result<(), String> {
+fn execute_job<S: JobStore + 'static>(executor: &JobExecutor<S>, job: &mut Job) -> Result<(), String> {
     let handler = match executor.get_handler(&job.kind) {
         Some(h) => h,
         None => {
@@ -313,6 +329,7 @@ fn execute_job<S: JobStore>(executor: &JobExecutor<S>, job: &mut Job) -> Result<
             if matches!(job.status, JobStatus::DeadLettered { .. }) {
                 warn!(job_id = %job.id, error = %error, "job dead-lettered");
                 executor.store.dead_letter(job.clone(), error.clone()).ok();
+                executor.record_dead_letter(job);
             }
 
             Err(error)
@@ -392,6 +409,29 @@ mod tests {
         assert!(matches!(claimed.status, JobStatus::DeadLettered { .. }));
     }
 
+    #[test]
+    fn dead_lettered_jobs_are_reported_to_the_monitor() {
+        use crate::jobs::alerting::{DeadLetterAlertConfig, LogNotifier};
+
+        let store = Arc::new(InMemoryJobStore::new());
+        let monitor = Arc::new(DeadLetterMonitor::new(
+            DeadLetterAlertConfig::default(),
+            Arc::new(LogNotifier),
+        ));
+        let mut executor = JobExecutor::new(store.clone()).with_dead_letter_monitor(monitor.clone());
+        executor.register_handler("test", |_job| JobResult::Failure("backend down".to_string()));
+
+        let tenant = test_tenant();
+        let job = Job::new(tenant, JobKind::custom("test"), serde_json::json!({}))
+            .with_retry_policy(super::super::types::RetryPolicy::no_retry());
+        store.enqueue(job).unwrap();
+
+        let mut claimed = store.claim_next(Some(tenant)).unwrap().unwrap();
+        assert!(executor.execute_one(&mut claimed).is_err());
+        assert!(matches!(claimed.status, JobStatus::DeadLettered { .. }));
+        assert_eq!(monitor.stats()[0].total, 1);
+    }
+
     #[test]
     fn wildcard_handler() {
         let store = Arc::new(InMemoryJobStore::new());
@@ -14,11 +14,16 @@
 //! - `JobStore`: Persistence for jobs (in-memory or durable)
 //! - `JobExecutor`: Runs jobs with retry logic
 //! - `DeadLetterQueue`: Failed jobs for inspection/replay
+//! - `DeadLetterMonitor`: Alerts when a job kind dead-letters faster than a threshold
 
+pub mod alerting;
 pub mod executor;
 pub mod store;
 pub mod types;
 
+pub use alerting::{
+    DeadLetterAlert, DeadLetterAlertConfig, DeadLetterKindStats, DeadLetterMonitor, LogNotifier, Notifier,
+};
 pub use executor::{JobExecutor, JobExecutorHandle};
 pub use store::{InMemoryJobStore, JobStore};
 pub use types::{
@@ -1,6 +1,7 @@
 //! Job storage implementations.
 
 use std::collections::HashMap;
+use std::io::Write;
 use std::sync::{Arc, RwLock};
 
 use chrono::{DateTime, Utc};
@@ -56,6 +57,22 @@ pub trait JobStore: Send + Sync {
     /// Delete a dead-lettered job.
     fn delete_dead_letter(&self, tenant_id: TenantId, job_id: JobId) -> Result<(), JobStoreError>;
 
+    /// Delete the tenant's jobs that were dead-lettered before `older_than`.
+    ///
+    /// Returns the number of entries removed.
+    fn purge_dead_letters(&self, tenant_id: TenantId, older_than: DateTime<Utc>) -> Result<usize, JobStoreError>;
+
+    /// Write the tenant's dead-lettered jobs to `writer` as NDJSON (one entry per line,
+    /// oldest first). Returns the number of entries written.
+    fn export_dead_letters(&self, tenant_id: TenantId, writer: &mut dyn Write) -> Result<usize, JobStoreError> {
+        let entries = self.list_dead_letters(tenant_id, usize::MAX)?;
+        for entry in &entries {
+            serde_json::to_writer(&mut *writer, entry).map_err(|e| JobStoreError::Storage(e.to_string()))?;
+            writer.write_all(b"\n").map_err(|e| JobStoreError::Storage(e.to_string()))?;
+        }
+        Ok(entries.len())
+    }
+
     /// Get job statistics.
     fn stats(&self, tenant_id: TenantId) -> Result<JobStats, JobStoreError>;
 }
@@ -279,6 +296,13 @@ impl JobStore for InMemoryJobStore {
         Ok(())
     }
 
+    fn purge_dead_letters(&self, tenant_id: TenantId, older_than: DateTime<Utc>) -> Result<usize, JobStoreError> {
+        let mut dls = self.dead_letters.write().unwrap();
+        let before = dls.len();
+        dls.retain(|_, e| e.job.tenant_id != tenant_id || e.dead_lettered_at >= older_than);
+        Ok(before - dls.len())
+    }
+
     fn stats(&self, tenant_id: TenantId) -> Result<JobStats, JobStoreError> {
         let jobs = self.jobs.read().unwrap();
         let dls = self.dead_letters.read().unwrap();
@@ -364,6 +388,14 @@ impl JobStore for Arc<InMemoryJobStore> {
         (**self).delete_dead_letter(tenant_id, job_id)
     }
 
+    fn purge_dead_letters(&self, tenant_id: TenantId, older_than: DateTime<Utc>) -> Result<usize, JobStoreError> {
+        (**self).purge_dead_letters(tenant_id, older_than)
+    }
+
+    fn export_dead_letters(&self, tenant_id: TenantId, writer: &mut dyn Write) -> Result<usize, JobStoreError> {
+        (**self).export_dead_letters(tenant_id, writer)
+    }
+
     fn stats(&self, tenant_id: TenantId) -> Result<JobStats, JobStoreError> {
         (**self).stats(tenant_id)
     }
@@ -464,5 +496,53 @@ mod tests {
         assert_eq!(stats.pending, 3);
         assert_eq!(stats.running, 2);
     }
-}
 
+    /// Dead-letter a fresh job for `tenant`, `age` ago.
+    fn dead_lettered(store: &InMemoryJobStore, tenant: TenantId, age: chrono::Duration) -> JobId {
+        let job = Job::new(tenant, JobKind::custom("test"), serde_json::json!({}));
+        let job_id = job.id;
+        store.dead_letter(job, "boom".to_string()).unwrap();
+        store.dead_letters.write().unwrap().get_mut(&job_id).unwrap().dead_lettered_at = Utc::now() - age;
+        job_id
+    }
+
+    #[test]
+    fn export_writes_one_ndjson_line_per_dead_letter() {
+        let store = InMemoryJobStore::new();
+        let tenant = test_tenant();
+        let ids = [
+            dead_lettered(&store, tenant, chrono::Duration::days(2)),
+            dead_lettered(&store, tenant, chrono::Duration::days(1)),
+        ];
+        dead_lettered(&store, test_tenant(), chrono::Duration::days(1));
+
+        let mut out = Vec::new();
+        assert_eq!(store.export_dead_letters(tenant, &mut out).unwrap(), 2);
+
+        let lines: Vec<DeadLetterEntry> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.iter().map(|e| e.job.id).collect::<Vec<_>>(), ids);
+        assert_eq!(lines[0].reason, "boom");
+    }
+
+    #[test]
+    fn purge_removes_only_the_tenants_entries_older_than_the_cutoff() {
+        let store = InMemoryJobStore::new();
+        let tenant = test_tenant();
+        let other = test_tenant();
+        dead_lettered(&store, tenant, chrono::Duration::days(10));
+        let recent = dead_lettered(&store, tenant, chrono::Duration::hours(1));
+        dead_lettered(&store, other, chrono::Duration::days(10));
+
+        let cutoff = Utc::now() - chrono::Duration::days(1);
+        assert_eq!(store.purge_dead_letters(tenant, cutoff).unwrap(), 1);
+
+        let kept = store.list_dead_letters(tenant, 10).unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].job.id, recent);
+        assert_eq!(store.list_dead_letters(other, 10).unwrap().len(), 1);
+    }
+}
@@ -3,12 +3,26 @@
 pub mod event_bus;
 pub mod event_store;
 pub mod command_dispatcher;
+pub mod command_bus;
+pub mod clock;
+pub mod enrichment;
 pub mod read_model;
 pub mod projections;
 pub mod workers;
 pub mod ai;
 pub mod saga;
 pub mod jobs;
+pub mod integration_events;
+pub mod product_import;
+pub mod redaction;
+pub mod sku_registry;
+pub mod tenant_settings;
+pub mod three_way_match;
+pub mod webhook_policy;
+pub mod admin_audit;
+pub mod rejected_commands;
+pub mod idempotency;
+pub mod event_history;
 
 #[cfg(test)]
 mod integration_tests;
@@ -0,0 +1,484 @@
+//! Bulk product import with a validation report.
+//!
+//! An import runs in two passes. The first validates every row without writing
+//! anything: the create command is decided against an empty product, the price is
+//! converted with the tenant's money convention, and SKUs are checked against the
+//! other rows and against the [`SkuRegistry`]. Every failure is reported with its row
+//! number (1-based, the CSV header is not counted).
+//!
+//! The second pass depends on the [`ImportMode`]:
+//!
+//! - `Atomic`: only when every row is valid, all products are created with one
+//!   multi-stream append; otherwise nothing is written and the report lists all errors.
+//! - `BestEffort`: valid rows are created one by one; failed rows are reported.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::Utc;
+use forgeerp_core::{Aggregate, AggregateId, TenantId};
+use forgeerp_events::{EventBus, EventEnvelope};
+use forgeerp_products::{CreateProduct, PricingMetadata, Product, ProductCommand, ProductId};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::command_dispatcher::{CommandDispatcher, DispatchError};
+use crate::event_store::EventStore;
+use crate::sku_registry::SkuRegistry;
+use crate::tenant_settings::TenantSettings;
+
+/// Whether an import with invalid rows writes the valid ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportMode {
+    /// All rows or none.
+    #[default]
+    Atomic,
+    /// Valid rows are written, invalid ones reported.
+    BestEffort,
+}
+
+impl FromStr for ImportMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "atomic" => Ok(Self::Atomic),
+            "best-effort" => Ok(Self::BestEffort),
+            other => Err(format!("unknown import mode `{other}` (expected atomic or best-effort)")),
+        }
+    }
+}
+
+/// One product to import (a CSV record or a JSON array element).
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ImportRow {
+    pub sku: String,
+    pub name: String,
+    /// Decimal price (e.g. `12.50`), converted with the currency's convention.
+    #[serde(default)]
+    pub base_price: Option<String>,
+    /// Defaults to the tenant's currency when omitted.
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+/// Why a row was not imported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RowError {
+    pub row: usize,
+    pub sku: String,
+    pub message: String,
+}
+
+/// A row that was written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportedRow {
+    pub row: usize,
+    pub sku: String,
+    pub product_id: ProductId,
+}
+
+/// Outcome of an import.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportReport {
+    pub mode: ImportMode,
+    pub total_rows: usize,
+    pub imported: Vec<ImportedRow>,
+    pub errors: Vec<RowError>,
+}
+
+impl ImportReport {
+    /// Nothing was written (an atomic import with errors, or no valid rows).
+    pub fn is_rejected(&self) -> bool {
+        self.imported.is_empty() && !self.errors.is_empty()
+    }
+}
+
+/// Parse CSV with a header row naming the columns `sku`, `name` and optionally
+/// `base_price` and `currency` (any order). Fields may be double-quoted (`""`
+/// escapes a quote); quoted fields cannot span lines.
+pub fn parse_csv(input: &str) -> Result<Vec<ImportRow>, String> {
+    let mut lines = input.lines().filter(|l| !l.trim().is_empty());
+    let header = lines.next().ok_or_else(|| "csv is empty".to_string())?;
+    let columns: Vec<String> = split_csv_line(header)?
+        .into_iter()
+        .map(|c| c.trim().to_ascii_lowercase())
+        .collect();
+
+    let position = |name: &str| columns.iter().position(|c| c == name);
+    let (Some(sku), Some(name)) = (position("sku"), position("name")) else {
+        return Err("csv header must contain `sku` and `name` columns".to_string());
+    };
+    let (base_price, currency) = (position("base_price"), position("currency"));
+
+    lines
+        .enumerate()
+        .map(|(idx, line)| {
+            let fields = split_csv_line(line).map_err(|e| format!("row {}: {e}", idx + 1))?;
+            let field = |i: Option<usize>| {
+                i.and_then(|i| fields.get(i))
+                    .map(|f| f.trim().to_string())
+                    .filter(|f| !f.is_empty())
+            };
+            Ok(ImportRow {
+                sku: field(Some(sku)).unwrap_or_default(),
+                name: field(Some(name)).unwrap_or_default(),
+                base_price: field(base_price),
+                currency: field(currency),
+            })
+        })
+        .collect()
+}
+
+fn split_csv_line(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted field".to_string());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+/// Validate every row; valid rows become create commands for new products.
+pub fn validate_rows(
+    tenant_id: TenantId,
+    rows: &[ImportRow],
+    settings: &TenantSettings,
+    registry: &SkuRegistry,
+) -> Vec<Result<CreateProduct, RowError>> {
+    let mut first_row_by_sku: HashMap<String, usize> = HashMap::new();
+
+    rows.iter()
+        .enumerate()
+        .map(|(idx, row)| {
+            let row_number = idx + 1;
+            let error = |message: String| RowError {
+                row: row_number,
+                sku: row.sku.clone(),
+                message,
+            };
+
+            let pricing = to_pricing(row, settings).map_err(error)?;
+            let product_id = ProductId::new(AggregateId::new());
+            let command = CreateProduct {
+                tenant_id,
+                product_id,
+                sku: row.sku.trim().to_string(),
+                name: row.name.trim().to_string(),
+                pricing,
+                inventory_item_id: None,
+                occurred_at: Utc::now(),
+            };
+            Product::empty(product_id)
+                .handle(&ProductCommand::CreateProduct(command.clone()))
+                .map_err(|e| error(e.to_string()))?;
+
+            if let Some(first) = first_row_by_sku.get(&command.sku) {
+                return Err(error(format!("duplicate SKU {} (first used on row {first})", command.sku)));
+            }
+            first_row_by_sku.insert(command.sku.clone(), row_number);
+            if let Some(holder) = registry.holder(tenant_id, &command.sku) {
+                return Err(error(format!("SKU {} is already used by product {holder}", command.sku)));
+            }
+            Ok(command)
+        })
+        .collect()
+}
+
+fn to_pricing(row: &ImportRow, settings: &TenantSettings) -> Result<Option<PricingMetadata>, String> {
+    if row.base_price.is_none() && row.currency.is_none() {
+        return Ok(None);
+    }
+    let convention = settings
+        .convention(row.currency.as_deref())
+        .map_err(|e| e.to_string())?;
+    let base_price = match &row.base_price {
+        Some(price) => {
+            let minor = convention.to_minor_units(price).map_err(|e| e.to_string())?;
+            Some(u64::try_from(minor).map_err(|_| format!("price `{price}` cannot be negative"))?)
+        }
+        None => None,
+    };
+    Ok(Some(PricingMetadata {
+        base_price,
+        currency: Some(convention.code),
+    }))
+}
+
+/// Validate `rows` and create the products according to `mode`.
+///
+/// Returns `Err` only when an atomic commit fails after validation passed: nothing is
+/// written after a store error, while after a publish failure the products were stored
+/// ([`DispatchError::committed`]) and keep their SKUs.
+pub fn import_products<S, B>(
+    dispatcher: &CommandDispatcher<S, B>,
+    registry: &SkuRegistry,
+    settings: &TenantSettings,
+    tenant_id: TenantId,
+    rows: &[ImportRow],
+    mode: ImportMode,
+) -> Result<ImportReport, DispatchError>
+where
+    S: EventStore,
+    B: EventBus<EventEnvelope<JsonValue>>,
+{
+    let mut report = ImportReport {
+        mode,
+        total_rows: rows.len(),
+        imported: Vec::new(),
+        errors: Vec::new(),
+    };
+    let mut valid = Vec::new();
+    for (idx, result) in validate_rows(tenant_id, rows, settings, registry).into_iter().enumerate() {
+        match result {
+            Ok(command) => valid.push((idx + 1, command)),
+            Err(e) => report.errors.push(e),
+        }
+    }
+
+    match mode {
+        ImportMode::Atomic if !report.errors.is_empty() => Ok(report),
+        ImportMode::Atomic => {
+            import_atomic(dispatcher, registry, tenant_id, valid, &mut report)?;
+            Ok(report)
+        }
+        ImportMode::BestEffort => {
+            for (row, command) in valid {
+                let (sku, product_id) = (command.sku.clone(), command.product_id);
+                let result = registry.create_with(tenant_id, &sku, product_id, || {
+                    dispatcher.dispatch(
+                        tenant_id,
+                        product_id.0,
+                        "products.product",
+                        ProductCommand::CreateProduct(command),
+                        |_, id| Product::empty(ProductId::new(id)),
+                    )
+                });
+                match result {
+                    Ok(_) => report.imported.push(ImportedRow { row, sku, product_id }),
+                    Err(e) => report.errors.push(RowError {
+                        row,
+                        sku,
+                        message: format!("{e:?}"),
+                    }),
+                }
+            }
+            report.errors.sort_by_key(|e| e.row);
+            Ok(report)
+        }
+    }
+}
+
+fn import_atomic<S, B>(
+    dispatcher: &CommandDispatcher<S, B>,
+    registry: &SkuRegistry,
+    tenant_id: TenantId,
+    valid: Vec<(usize, CreateProduct)>,
+    report: &mut ImportReport,
+) -> Result<(), DispatchError>
+where
+    S: EventStore,
+    B: EventBus<EventEnvelope<JsonValue>>,
+{
+    let release_all = |valid: &[(usize, CreateProduct)]| {
+        for (_, command) in valid {
+            registry.release(tenant_id, command.product_id);
+        }
+    };
+
+    // Reserve every SKU first: one taken since validation rejects the whole import.
+    for (row, command) in &valid {
+        if let Err(e) = registry.reserve(tenant_id, &command.sku, command.product_id) {
+            release_all(&valid);
+            report.errors.push(RowError {
+                row: *row,
+                sku: command.sku.clone(),
+                message: e.to_string(),
+            });
+            return Ok(());
+        }
+    }
+
+    let commands = valid
+        .iter()
+        .map(|(_, command)| (command.product_id.0, ProductCommand::CreateProduct(command.clone())))
+        .collect();
+    if let Err(e) = dispatcher.dispatch_batch(tenant_id, "products.product", commands, |_, id| {
+        Product::empty(ProductId::new(id))
+    }) {
+        // Products whose events were stored but not published exist and keep their SKUs.
+        if e.committed().is_none() {
+            release_all(&valid);
+        }
+        return Err(e);
+    }
+
+    report.imported = valid
+        .into_iter()
+        .map(|(row, command)| ImportedRow {
+            row,
+            sku: command.sku,
+            product_id: command.product_id,
+        })
+        .collect();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use forgeerp_events::InMemoryEventBus;
+
+    use super::*;
+    use crate::event_store::InMemoryEventStore;
+
+    type Dispatcher = CommandDispatcher<Arc<InMemoryEventStore>, Arc<InMemoryEventBus<EventEnvelope<JsonValue>>>>;
+
+    fn setup() -> (Arc<InMemoryEventStore>, Dispatcher, SkuRegistry) {
+        let store = Arc::new(InMemoryEventStore::new());
+        let dispatcher = CommandDispatcher::new(store.clone(), Arc::new(InMemoryEventBus::new()));
+        (store, dispatcher, SkuRegistry::new())
+    }
+
+    fn stored_products(store: &InMemoryEventStore, tenant_id: TenantId, report: &ImportReport) -> usize {
+        report
+            .imported
+            .iter()
+            .filter(|r| !store.load_stream(tenant_id, r.product_id.0).unwrap().is_empty())
+            .count()
+    }
+
+    const CSV: &str = "sku,name,base_price\nSKU-1,Widget,12.50\n\"SKU-2\",\"Gadget, large\",3\nSKU-3,Gizmo,\n";
+
+    #[test]
+    fn atomic_import_of_valid_rows_creates_every_product() {
+        let (store, dispatcher, registry) = setup();
+        let tenant_id = TenantId::new();
+        let rows = parse_csv(CSV).unwrap();
+        assert_eq!(rows[1].name, "Gadget, large");
+
+        let report = import_products(
+            &dispatcher,
+            &registry,
+            &TenantSettings::default(),
+            tenant_id,
+            &rows,
+            ImportMode::Atomic,
+        )
+        .unwrap();
+
+        assert!(report.errors.is_empty());
+        assert_eq!(report.imported.len(), 3);
+        assert_eq!(stored_products(&store, tenant_id, &report), 3);
+        assert_eq!(registry.holder(tenant_id, "SKU-2"), Some(report.imported[1].product_id));
+    }
+
+    #[test]
+    fn atomic_import_with_a_bad_row_writes_nothing() {
+        let (store, dispatcher, registry) = setup();
+        let tenant_id = TenantId::new();
+        let rows = vec![
+            ImportRow { sku: "SKU-1".into(), name: "Widget".into(), ..Default::default() },
+            ImportRow { sku: "SKU-2".into(), name: " ".into(), ..Default::default() },
+            ImportRow { sku: "SKU-1".into(), name: "Copy".into(), base_price: Some("-1".into()), ..Default::default() },
+        ];
+
+        let report = import_products(
+            &dispatcher,
+            &registry,
+            &TenantSettings::default(),
+            tenant_id,
+            &rows,
+            ImportMode::Atomic,
+        )
+        .unwrap();
+
+        assert!(report.is_rejected());
+        assert_eq!(report.errors.iter().map(|e| e.row).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(store.tenant_stats(tenant_id).unwrap().event_count, 0);
+        assert_eq!(registry.holder(tenant_id, "SKU-1"), None);
+    }
+
+    /// Bus that is down: every publication fails.
+    struct DownBus;
+
+    impl EventBus<EventEnvelope<JsonValue>> for DownBus {
+        type Error = String;
+
+        fn publish(&self, _message: EventEnvelope<JsonValue>) -> Result<(), String> {
+            Err("bus unavailable".to_string())
+        }
+
+        fn subscribe(&self) -> forgeerp_events::Subscription<EventEnvelope<JsonValue>> {
+            InMemoryEventBus::new().subscribe()
+        }
+    }
+
+    #[test]
+    fn atomic_import_stored_before_a_publish_failure_keeps_its_skus() {
+        let store = Arc::new(InMemoryEventStore::new());
+        let dispatcher = CommandDispatcher::new(store.clone(), DownBus);
+        let registry = SkuRegistry::new();
+        let tenant_id = TenantId::new();
+        let rows = parse_csv(CSV).unwrap();
+
+        let err = import_products(
+            &dispatcher,
+            &registry,
+            &TenantSettings::default(),
+            tenant_id,
+            &rows,
+            ImportMode::Atomic,
+        )
+        .unwrap_err();
+
+        let committed = err.committed().expect("publish errors carry the committed events");
+        assert_eq!(committed.len(), 3);
+        assert_eq!(store.tenant_stats(tenant_id).unwrap().event_count, 3);
+        for sku in ["SKU-1", "SKU-2", "SKU-3"] {
+            assert!(registry.holder(tenant_id, sku).is_some());
+        }
+    }
+
+    #[test]
+    fn best_effort_import_writes_the_valid_rows() {
+        let (store, dispatcher, registry) = setup();
+        let tenant_id = TenantId::new();
+        let rows = parse_csv("name,sku\nWidget,SKU-1\nNameless,\nGadget,SKU-2\nWidget again,SKU-1\n").unwrap();
+
+        let report = import_products(
+            &dispatcher,
+            &registry,
+            &TenantSettings::default(),
+            tenant_id,
+            &rows,
+            ImportMode::BestEffort,
+        )
+        .unwrap();
+
+        assert_eq!(report.imported.iter().map(|r| r.row).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(report.errors.iter().map(|e| e.row).collect::<Vec<_>>(), vec![2, 4]);
+        assert_eq!(stored_products(&store, tenant_id, &report), 2);
+    }
+}
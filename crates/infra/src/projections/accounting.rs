@@ -204,18 +204,14 @@ where
         let ev: LedgerEvent = serde_json::from_value(envelope.payload().clone())
             .map_err(|e| AccountingProjectionError::Deserialize(e.to_string()))?;
 
-        let event_tenant = match &ev {
-            LedgerEvent::JournalEntryPosted(e) => e.tenant_id,
-        };
-
-        if event_tenant != tenant_id {
+        if ev.tenant_id() != tenant_id {
             return Err(AccountingProjectionError::TenantIsolation(
                 "event tenant_id does not match envelope tenant_id".to_string(),
             ));
         }
 
-        let LedgerEvent::JournalEntryPosted(e) = ev;
-        for line in &e.lines {
+        // A reversal carries the inverse lines, so it applies like any posting.
+        for line in ev.lines() {
             let code = line.account.code.clone();
             let mut rm = self
                 .store
@@ -273,4 +269,81 @@ where
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_model::InMemoryTenantStore;
+    use chrono::Utc;
+    use forgeerp_accounting::{
+        Account, JournalEntryLine, JournalEntryPosted, JournalEntryReversed, LedgerId,
+    };
+
+    fn make_envelope(
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        seq: u64,
+        event: LedgerEvent,
+    ) -> EventEnvelope<JsonValue> {
+        EventEnvelope::new(
+            uuid::Uuid::now_v7(),
+            tenant_id,
+            aggregate_id,
+            "accounting.ledger".to_string(),
+            seq,
+            serde_json::to_value(&event).unwrap(),
+        )
+    }
+
+    fn line(code: &str, kind: AccountKind, amount: i64, is_debit: bool) -> JournalEntryLine {
+        JournalEntryLine {
+            account: Account {
+                code: code.to_string(),
+                name: code.to_string(),
+                kind,
+            },
+            amount,
+            is_debit,
+        }
+    }
 
+    #[test]
+    fn reversal_cancels_the_original_balances() {
+        let proj = AccountBalancesProjection::new(Arc::new(InMemoryTenantStore::<String, AccountBalance>::new()));
+        let tenant_id = TenantId::new();
+        let ledger_id = LedgerId::new(AggregateId::new());
+        let entry_id = uuid::Uuid::now_v7();
+
+        let posted = LedgerEvent::JournalEntryPosted(JournalEntryPosted {
+            tenant_id,
+            ledger_id,
+            entry_id,
+            lines: vec![
+                line("1200", AccountKind::Asset, 500, true),
+                line("4000", AccountKind::Revenue, 500, false),
+            ],
+            description: None,
+            currency: None,
+            occurred_at: Utc::now(),
+        });
+        proj.apply_envelope(&make_envelope(tenant_id, ledger_id.0, 1, posted)).unwrap();
+        assert_eq!(proj.get(tenant_id, "1200").unwrap().balance, 500);
+        assert_eq!(proj.get(tenant_id, "4000").unwrap().balance, -500);
+
+        let reversed = LedgerEvent::JournalEntryReversed(JournalEntryReversed {
+            tenant_id,
+            ledger_id,
+            entry_id: uuid::Uuid::now_v7(),
+            reversed_entry_id: entry_id,
+            lines: vec![
+                line("1200", AccountKind::Asset, 500, false),
+                line("4000", AccountKind::Revenue, 500, true),
+            ],
+            description: None,
+            currency: None,
+            occurred_at: Utc::now(),
+        });
+        proj.apply_envelope(&make_envelope(tenant_id, ledger_id.0, 2, reversed)).unwrap();
+        assert_eq!(proj.get(tenant_id, "1200").unwrap().balance, 0);
+        assert_eq!(proj.get(tenant_id, "4000").unwrap().balance, 0);
+    }
+}
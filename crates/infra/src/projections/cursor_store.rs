@@ -5,12 +5,24 @@
 //! - Idempotent projections (replays <= cursor are ignored)
 //! - Resume after crash (projections can continue from last offset)
 //! - Deterministic rebuilds (clear offsets and replay from scratch)
+//!
+//! A subscriber feeding projections from the bus also keeps its **position**: the global
+//! position (see [`EventStore::read_all`]) of the last event it handled per tenant. At
+//! startup, [`catch_up`] applies only the events committed after it instead of the
+//! whole history. With several apply shards, [`SubscriberPositions`] decides which
+//! position is safe to store.
 
-use std::sync::Arc;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
 
-use forgeerp_core::{AggregateId, TenantId};
+use serde_json::Value as JsonValue;
 use sqlx::{PgPool, Row};
 
+use forgeerp_core::{AggregateId, TenantId};
+use forgeerp_events::EventEnvelope;
+
+use crate::event_store::{EventStore, EventStoreError, GlobalPosition, READ_ALL_PAGE_SIZE};
+
 /// Projection cursor store for persisting offsets.
 pub trait ProjectionCursorStore: Send + Sync {
     /// Get the last processed sequence_number for a (tenant, aggregate, projection) stream.
@@ -32,6 +44,156 @@ pub trait ProjectionCursorStore: Send + Sync {
 
     /// Clear all cursors for a tenant + projection (for rebuilds).
     fn clear_cursors(&self, tenant_id: TenantId, projection_name: &str);
+
+    /// Global position of the last event the subscriber `projection_name` handled for the
+    /// tenant. Stores that only track per-stream cursors keep this default.
+    fn get_position(&self, tenant_id: TenantId, projection_name: &str) -> Option<u64> {
+        let _ = (tenant_id, projection_name);
+        None
+    }
+
+    /// Move the subscriber's position forward (never back) to `position`.
+    fn update_position(&self, tenant_id: TenantId, projection_name: &str, position: u64) {
+        let _ = (tenant_id, projection_name, position);
+    }
+}
+
+/// Projection catch-up error.
+#[derive(Debug, thiserror::Error)]
+pub enum ProjectionCatchUpError {
+    #[error("event store error: {0}")]
+    Store(#[from] EventStoreError),
+    #[error("projection apply failed: {0}")]
+    Apply(String),
+}
+
+/// Apply the tenant's events committed after the stored position of `projection_name`,
+/// a page at a time, persisting the position after each applied page. Returns how many
+/// events were applied.
+pub fn catch_up<S>(
+    store: &S,
+    cursors: &dyn ProjectionCursorStore,
+    projection_name: &str,
+    tenant_id: TenantId,
+    apply: impl Fn(&[EventEnvelope<JsonValue>]) -> Result<(), String>,
+) -> Result<usize, ProjectionCatchUpError>
+where
+    S: EventStore + ?Sized,
+{
+    let mut after = cursors.get_position(tenant_id, projection_name).map(GlobalPosition);
+    let mut applied = 0;
+    loop {
+        let page = store.read_all(tenant_id, after, READ_ALL_PAGE_SIZE)?;
+        let Some(last) = page.last().map(|e| e.global_position) else {
+            return Ok(applied);
+        };
+        let envelopes: Vec<_> = page.iter().map(|e| e.to_envelope()).collect();
+        apply(&envelopes).map_err(ProjectionCatchUpError::Apply)?;
+        cursors.update_position(tenant_id, projection_name, last.0);
+        applied += envelopes.len();
+        after = Some(last);
+    }
+}
+
+/// Positions a subscriber stores while several shards apply its events in parallel.
+///
+/// Shards finish out of order, so storing the latest handled position would let a restart
+/// skip events a slower shard still had in flight. Every routed position is tracked until
+/// it is handled; the stored position is the highest one below which nothing is in flight.
+pub struct SubscriberPositions {
+    cursors: Arc<dyn ProjectionCursorStore>,
+    name: String,
+    tenants: Mutex<HashMap<TenantId, InFlight>>,
+}
+
+#[derive(Debug, Default)]
+struct InFlight {
+    routed: BTreeSet<u64>,
+    handled: u64,
+}
+
+impl SubscriberPositions {
+    pub fn new(cursors: Arc<dyn ProjectionCursorStore>, name: impl Into<String>) -> Self {
+        Self {
+            cursors,
+            name: name.into(),
+            tenants: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// An event at `position` was handed to a shard.
+    pub fn routed(&self, tenant_id: TenantId, position: u64) {
+        let mut tenants = self.tenants.lock().unwrap_or_else(|e| e.into_inner());
+        tenants.entry(tenant_id).or_default().routed.insert(position);
+    }
+
+    /// The event at `position` was handled; stores the tenant's committed position.
+    pub fn handled(&self, tenant_id: TenantId, position: u64) {
+        let committed = {
+            let mut tenants = self.tenants.lock().unwrap_or_else(|e| e.into_inner());
+            let in_flight = tenants.entry(tenant_id).or_default();
+            in_flight.routed.remove(&position);
+            in_flight.handled = in_flight.handled.max(position);
+            match in_flight.routed.first() {
+                Some(lowest) => lowest.saturating_sub(1).min(in_flight.handled),
+                None => in_flight.handled,
+            }
+        };
+        if committed > 0 {
+            self.cursors.update_position(tenant_id, &self.name, committed);
+        }
+    }
+}
+
+/// In-memory cursor store (dev path): cursors and positions live as long as the store.
+#[derive(Debug, Default)]
+pub struct InMemoryCursorStore {
+    cursors: RwLock<HashMap<(TenantId, AggregateId, String), u64>>,
+    positions: RwLock<HashMap<(TenantId, String), u64>>,
+}
+
+impl InMemoryCursorStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn arc() -> Arc<Self> {
+        Arc::new(Self::new())
+    }
+}
+
+impl ProjectionCursorStore for InMemoryCursorStore {
+    fn get_cursor(&self, tenant_id: TenantId, aggregate_id: AggregateId, projection_name: &str) -> Option<u64> {
+        let cursors = self.cursors.read().ok()?;
+        cursors.get(&(tenant_id, aggregate_id, projection_name.to_string())).copied()
+    }
+
+    fn update_cursor(&self, tenant_id: TenantId, aggregate_id: AggregateId, projection_name: &str, sequence_number: u64) {
+        if let Ok(mut cursors) = self.cursors.write() {
+            cursors.insert((tenant_id, aggregate_id, projection_name.to_string()), sequence_number);
+        }
+    }
+
+    fn clear_cursors(&self, tenant_id: TenantId, projection_name: &str) {
+        if let Ok(mut cursors) = self.cursors.write() {
+            cursors.retain(|(t, _, name), _| !(*t == tenant_id && name == projection_name));
+        }
+        if let Ok(mut positions) = self.positions.write() {
+            positions.remove(&(tenant_id, projection_name.to_string()));
+        }
+    }
+
+    fn get_position(&self, tenant_id: TenantId, projection_name: &str) -> Option<u64> {
+        let positions = self.positions.read().ok()?;
+        positions.get(&(tenant_id, projection_name.to_string())).copied()
+    }
+
+    fn update_position(&self, tenant_id: TenantId, projection_name: &str, position: u64) {
+        if let Ok(mut positions) = self.positions.write() {
+            let stored = positions.entry((tenant_id, projection_name.to_string())).or_default();
+            *stored = (*stored).max(position);
+        }
+    }
 }
 
 /// Postgres-backed projection cursor store.
@@ -148,7 +310,182 @@ impl ProjectionCursorStore for PostgresCursorStore {
             .bind(&projection_name)
             .execute(&*pool)
             .await;
+            let _ = sqlx::query("DELETE FROM projection_positions WHERE tenant_id = $1 AND projection_name = $2")
+                .bind(tenant_id_uuid)
+                .bind(&projection_name)
+                .execute(&*pool)
+                .await;
+        });
+    }
+
+    fn get_position(&self, tenant_id: TenantId, projection_name: &str) -> Option<u64> {
+        let handle = tokio::runtime::Handle::try_current().ok()?;
+        let pool = self.pool.clone();
+        let tenant_id_uuid = tenant_id.as_uuid();
+        let projection_name = projection_name.to_string();
+
+        handle.block_on(async {
+            sqlx::query(
+                r#"
+                SELECT global_position
+                FROM projection_positions
+                WHERE tenant_id = $1 AND projection_name = $2
+                "#,
+            )
+            .bind(tenant_id_uuid)
+            .bind(&projection_name)
+            .fetch_optional(&*pool)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|row| row.try_get::<i64, _>("global_position").ok())
+            .map(|position| position as u64)
+        })
+    }
+
+    fn update_position(&self, tenant_id: TenantId, projection_name: &str, position: u64) {
+        let handle = match tokio::runtime::Handle::try_current() {
+            Ok(h) => h,
+            Err(_) => return,
+        };
+
+        let pool = self.pool.clone();
+        let tenant_id_uuid = tenant_id.as_uuid();
+        let projection_name = projection_name.to_string();
+
+        handle.block_on(async {
+            let _ = sqlx::query(
+                r#"
+                INSERT INTO projection_positions (tenant_id, projection_name, global_position)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (tenant_id, projection_name)
+                DO UPDATE SET
+                    global_position = GREATEST(projection_positions.global_position, EXCLUDED.global_position),
+                    updated_at = NOW()
+                "#,
+            )
+            .bind(tenant_id_uuid)
+            .bind(&projection_name)
+            .bind(position as i64)
+            .execute(&*pool)
+            .await;
         });
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use forgeerp_events::InMemoryEventBus;
+    use forgeerp_inventory::{AdjustStock, CreateItem, DEFAULT_LOCATION, InventoryCommand, InventoryItem, InventoryItemId};
+
+    use super::*;
+    use crate::command_dispatcher::CommandDispatcher;
+    use crate::event_store::InMemoryEventStore;
+    use crate::projections::inventory_stock::{InventoryReadModel, InventoryStockProjection};
+    use crate::read_model::{InMemoryTenantStore, TenantStore};
+
+    type ReadModels = Arc<InMemoryTenantStore<InventoryItemId, InventoryReadModel>>;
+
+    /// Catch `read_models` up through a fresh projection, as the subscriber does at startup.
+    fn start(
+        store: &InMemoryEventStore,
+        cursors: &InMemoryCursorStore,
+        read_models: &ReadModels,
+        tenant_id: TenantId,
+    ) -> usize {
+        let projection = InventoryStockProjection::new(read_models.clone());
+        catch_up(store, cursors, "projections", tenant_id, |page| {
+            page.iter()
+                .try_for_each(|env| projection.apply_envelope(env))
+                .map_err(|e| e.to_string())
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn restart_resumes_from_the_stored_position_without_double_applying() {
+        let tenant_id = TenantId::new();
+        let store = Arc::new(InMemoryEventStore::new());
+        let bus: Arc<InMemoryEventBus<EventEnvelope<JsonValue>>> = Arc::new(InMemoryEventBus::new());
+        let dispatcher = CommandDispatcher::new(store.clone(), bus);
+        let item_id = InventoryItemId::new(AggregateId::new());
+        let dispatch = |cmd| {
+            dispatcher
+                .dispatch(tenant_id, item_id.0, "inventory.item", cmd, |_, id| {
+                    InventoryItem::empty(InventoryItemId::new(id))
+                })
+                .unwrap();
+        };
+        let adjust = |delta| {
+            InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id,
+                item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta,
+                occurred_at: Utc::now(),
+            })
+        };
+        dispatch(InventoryCommand::CreateItem(CreateItem {
+            tenant_id,
+            item_id,
+            name: "Widget".to_string(),
+            occurred_at: Utc::now(),
+        }));
+        dispatch(adjust(5));
+
+        // Both survive the restart; the projection's own per-stream cursors do not.
+        let cursors = InMemoryCursorStore::new();
+        let read_models: ReadModels = Arc::new(InMemoryTenantStore::new());
+        assert_eq!(start(&store, &cursors, &read_models, tenant_id), 2);
+        assert_eq!(cursors.get_position(tenant_id, "projections"), Some(2));
+
+        // Committed while the process is down.
+        dispatch(adjust(3));
+
+        assert_eq!(start(&store, &cursors, &read_models, tenant_id), 1);
+        assert_eq!(cursors.get_position(tenant_id, "projections"), Some(3));
+        let quantity = |read_models: &ReadModels| read_models.list(tenant_id)[0].quantity;
+        assert_eq!(quantity(&read_models), 8);
+
+        // Nothing new: nothing applied.
+        assert_eq!(start(&store, &cursors, &read_models, tenant_id), 0);
+        assert_eq!(quantity(&read_models), 8);
+
+        // Clearing the position (a rebuild) re-reads the whole history.
+        cursors.clear_cursors(tenant_id, "projections");
+        assert_eq!(start(&store, &cursors, &read_models, tenant_id), 3);
+        assert_eq!(quantity(&read_models), 8);
+    }
+
+    #[test]
+    fn stored_position_waits_for_the_slowest_shard() {
+        let cursors = InMemoryCursorStore::arc();
+        let positions = SubscriberPositions::new(cursors.clone(), "projections");
+        let tenant_id = TenantId::new();
+        for position in [1, 2, 3, 4] {
+            positions.routed(tenant_id, position);
+        }
+
+        // A fast shard finishes 2 and 4 while 1 and 3 are still being applied.
+        positions.handled(tenant_id, 2);
+        positions.handled(tenant_id, 4);
+        assert_eq!(cursors.get_position(tenant_id, "projections"), None);
+
+        positions.handled(tenant_id, 1);
+        assert_eq!(cursors.get_position(tenant_id, "projections"), Some(2));
+        positions.handled(tenant_id, 3);
+        assert_eq!(cursors.get_position(tenant_id, "projections"), Some(4));
+    }
+
+    #[test]
+    fn positions_never_move_back() {
+        let cursors = InMemoryCursorStore::new();
+        let tenant_id = TenantId::new();
+        cursors.update_position(tenant_id, "projections", 7);
+        cursors.update_position(tenant_id, "projections", 4);
+        assert_eq!(cursors.get_position(tenant_id, "projections"), Some(7));
+        assert_eq!(cursors.get_position(TenantId::new(), "projections"), None);
+    }
+}
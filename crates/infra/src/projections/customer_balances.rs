@@ -283,6 +283,8 @@ where
             InvoiceEvent::InvoiceIssued(e) => e.tenant_id,
             InvoiceEvent::PaymentRegistered(e) => e.tenant_id,
             InvoiceEvent::InvoiceVoided(e) => e.tenant_id,
+            InvoiceEvent::CreditNoteIssued(e) => e.tenant_id,
+            InvoiceEvent::PaymentReversed(e) => e.tenant_id,
         };
 
         if event_tenant != tenant_id {
@@ -312,6 +314,12 @@ where
             (InvoiceEvent::InvoiceVoided(e), None) => {
                 PartyId::new(e.invoice_id.0)
             }
+            (InvoiceEvent::CreditNoteIssued(e), None) => {
+                PartyId::new(e.invoice_id.0)
+            }
+            (InvoiceEvent::PaymentReversed(e), None) => {
+                PartyId::new(e.invoice_id.0)
+            }
         };
 
         match ev {
@@ -393,6 +401,64 @@ where
                 }
                 let _ = e; // silence unused warning
             }
+            InvoiceEvent::CreditNoteIssued(e) => {
+                let mapping = {
+                    let mappings = self.invoice_mappings.read().ok();
+                    mappings.and_then(|m| m.get(&(tenant_id, aggregate_id)).cloned())
+                };
+
+                if let Some(m) = mapping {
+                    // Track the net amount so later payments settle the invoice correctly.
+                    let net_total = m.total_amount.saturating_sub(e.amount);
+                    let settled = m.status == InvoiceStatus::Open && m.total_paid >= net_total;
+                    if let Ok(mut mappings) = self.invoice_mappings.write()
+                        && let Some(mapping) = mappings.get_mut(&(tenant_id, aggregate_id))
+                    {
+                        mapping.total_amount = net_total;
+                        if settled {
+                            mapping.status = InvoiceStatus::Paid;
+                        }
+                    }
+
+                    if let Some(mut balance) = self.store.get(tenant_id, &m.customer_id) {
+                        let outstanding = m.total_amount.saturating_sub(m.total_paid);
+                        balance.outstanding_balance = balance
+                            .outstanding_balance
+                            .saturating_sub(e.amount.min(outstanding));
+                        if settled {
+                            balance.open_invoice_count = balance.open_invoice_count.saturating_sub(1);
+                        }
+                        self.store.upsert(tenant_id, m.customer_id, balance);
+                    }
+                }
+            }
+            InvoiceEvent::PaymentReversed(e) => {
+                let mapping = {
+                    let mappings = self.invoice_mappings.read().ok();
+                    mappings.and_then(|m| m.get(&(tenant_id, aggregate_id)).cloned())
+                };
+
+                if let Some(m) = mapping {
+                    let reopened = m.status == InvoiceStatus::Paid && e.new_total_paid < m.total_amount;
+                    if let Ok(mut mappings) = self.invoice_mappings.write()
+                        && let Some(mapping) = mappings.get_mut(&(tenant_id, aggregate_id))
+                    {
+                        mapping.total_paid = e.new_total_paid;
+                        if reopened {
+                            mapping.status = InvoiceStatus::Open;
+                        }
+                    }
+
+                    if let Some(mut balance) = self.store.get(tenant_id, &m.customer_id) {
+                        balance.total_paid = balance.total_paid.saturating_sub(e.amount);
+                        balance.outstanding_balance += e.amount;
+                        if reopened {
+                            balance.open_invoice_count += 1;
+                        }
+                        self.store.upsert(tenant_id, m.customer_id, balance);
+                    }
+                }
+            }
         }
 
         self.update_cursor(tenant_id, aggregate_id, seq);
@@ -487,8 +553,10 @@ mod tests {
                 quantity: 2,
                 unit_price: 100,
             }],
+            payment_terms_days: 30,
             due_date: Utc::now(),
             total_amount: 200,
+            currency: None,
             occurred_at: Utc::now(),
         });
 
@@ -524,8 +592,10 @@ mod tests {
                 quantity: 2,
                 unit_price: 100,
             }],
+            payment_terms_days: 30,
             due_date: Utc::now(),
             total_amount: 200,
+            currency: None,
             occurred_at: Utc::now(),
         });
 
@@ -534,8 +604,10 @@ mod tests {
         let payment = InvoiceEvent::PaymentRegistered(PaymentRegistered {
             tenant_id,
             invoice_id,
+            payment_id: None,
             amount: 50,
             new_total_paid: 50,
+            currency: None,
             occurred_at: Utc::now(),
         });
 
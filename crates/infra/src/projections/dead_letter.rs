@@ -0,0 +1,248 @@
+//! Dead letters of projection applies.
+//!
+//! A projection subscriber that fails to apply an event cannot stop the bus, and dropping
+//! the event leaves the read model silently behind the event store. Instead the envelope
+//! is kept in a [`ProjectionDeadLetterStore`] with the error, so an admin can see which
+//! events a read model is missing and replay them once the cause is fixed
+//! ([`replay_dead_letter`]).
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+use forgeerp_core::TenantId;
+use forgeerp_events::EventEnvelope;
+
+use crate::clock::{Clock, SystemClock};
+
+/// Applies one envelope to the read models fed by a projection subscriber.
+pub type ProjectionApplyFn = Arc<dyn Fn(&EventEnvelope<JsonValue>) -> Result<(), String> + Send + Sync>;
+
+/// An envelope a projection failed to apply.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectionDeadLetter {
+    pub dead_letter_id: Uuid,
+    pub envelope: EventEnvelope<JsonValue>,
+    /// Error of the most recent attempt.
+    pub error: String,
+    /// Replays attempted since the event was dead-lettered.
+    pub retry_count: u32,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Projection dead-letter store error.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ProjectionDeadLetterError {
+    #[error("dead letter not found: {0}")]
+    NotFound(Uuid),
+    #[error("replay failed (attempt {retry_count}): {error}")]
+    ReplayFailed { retry_count: u32, error: String },
+    #[error("storage error: {0}")]
+    Storage(String),
+}
+
+/// Envelopes that projections failed to apply, per tenant.
+pub trait ProjectionDeadLetterStore: Send + Sync {
+    /// Keep `envelope` with the error its apply failed with.
+    fn record(&self, envelope: EventEnvelope<JsonValue>, error: String)
+        -> Result<ProjectionDeadLetter, ProjectionDeadLetterError>;
+
+    /// The tenant's dead letters, oldest first.
+    fn list(&self, tenant_id: TenantId) -> Result<Vec<ProjectionDeadLetter>, ProjectionDeadLetterError>;
+
+    fn get(&self, tenant_id: TenantId, id: Uuid) -> Result<Option<ProjectionDeadLetter>, ProjectionDeadLetterError>;
+
+    /// Drop a dead letter (after a successful replay).
+    fn remove(&self, tenant_id: TenantId, id: Uuid) -> Result<Option<ProjectionDeadLetter>, ProjectionDeadLetterError>;
+
+    /// Count a failed replay and keep its error.
+    fn record_retry_failure(
+        &self,
+        tenant_id: TenantId,
+        id: Uuid,
+        error: String,
+    ) -> Result<ProjectionDeadLetter, ProjectionDeadLetterError>;
+}
+
+/// Apply a dead letter again with `apply`; removes it on success, otherwise counts the
+/// retry and returns [`ProjectionDeadLetterError::ReplayFailed`].
+pub fn replay_dead_letter(
+    store: &dyn ProjectionDeadLetterStore,
+    tenant_id: TenantId,
+    id: Uuid,
+    apply: impl Fn(&EventEnvelope<JsonValue>) -> Result<(), String>,
+) -> Result<ProjectionDeadLetter, ProjectionDeadLetterError> {
+    let letter = store
+        .get(tenant_id, id)?
+        .ok_or(ProjectionDeadLetterError::NotFound(id))?;
+    match apply(&letter.envelope) {
+        Ok(()) => {
+            store.remove(tenant_id, id)?;
+            Ok(letter)
+        }
+        Err(error) => {
+            let letter = store.record_retry_failure(tenant_id, id, error)?;
+            Err(ProjectionDeadLetterError::ReplayFailed {
+                retry_count: letter.retry_count,
+                error: letter.error,
+            })
+        }
+    }
+}
+
+/// In-memory dead letters (all tenants).
+pub struct InMemoryProjectionDeadLetters {
+    clock: Arc<dyn Clock>,
+    /// Per tenant, by id (UUIDv7, so in recording order).
+    letters: Mutex<HashMap<TenantId, BTreeMap<Uuid, ProjectionDeadLetter>>>,
+}
+
+impl std::fmt::Debug for InMemoryProjectionDeadLetters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InMemoryProjectionDeadLetters").finish_non_exhaustive()
+    }
+}
+
+impl Default for InMemoryProjectionDeadLetters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InMemoryProjectionDeadLetters {
+    pub fn new() -> Self {
+        Self {
+            clock: Arc::new(SystemClock),
+            letters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn arc() -> Arc<Self> {
+        Arc::new(Self::new())
+    }
+
+    /// Time source for `recorded_at` (defaults to the system clock).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+impl ProjectionDeadLetterStore for InMemoryProjectionDeadLetters {
+    fn record(
+        &self,
+        envelope: EventEnvelope<JsonValue>,
+        error: String,
+    ) -> Result<ProjectionDeadLetter, ProjectionDeadLetterError> {
+        let letter = ProjectionDeadLetter {
+            dead_letter_id: Uuid::now_v7(),
+            envelope,
+            error,
+            retry_count: 0,
+            recorded_at: self.clock.now(),
+        };
+        let mut letters = self.letters.lock().unwrap_or_else(|e| e.into_inner());
+        letters
+            .entry(letter.envelope.tenant_id())
+            .or_default()
+            .insert(letter.dead_letter_id, letter.clone());
+        Ok(letter)
+    }
+
+    fn list(&self, tenant_id: TenantId) -> Result<Vec<ProjectionDeadLetter>, ProjectionDeadLetterError> {
+        let letters = self.letters.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(letters
+            .get(&tenant_id)
+            .map(|tenant| tenant.values().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    fn get(&self, tenant_id: TenantId, id: Uuid) -> Result<Option<ProjectionDeadLetter>, ProjectionDeadLetterError> {
+        let letters = self.letters.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(letters.get(&tenant_id).and_then(|tenant| tenant.get(&id)).cloned())
+    }
+
+    fn remove(&self, tenant_id: TenantId, id: Uuid) -> Result<Option<ProjectionDeadLetter>, ProjectionDeadLetterError> {
+        let mut letters = self.letters.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(letters.get_mut(&tenant_id).and_then(|tenant| tenant.remove(&id)))
+    }
+
+    fn record_retry_failure(
+        &self,
+        tenant_id: TenantId,
+        id: Uuid,
+        error: String,
+    ) -> Result<ProjectionDeadLetter, ProjectionDeadLetterError> {
+        let mut letters = self.letters.lock().unwrap_or_else(|e| e.into_inner());
+        let letter = letters
+            .get_mut(&tenant_id)
+            .and_then(|tenant| tenant.get_mut(&id))
+            .ok_or(ProjectionDeadLetterError::NotFound(id))?;
+        letter.retry_count += 1;
+        letter.error = error;
+        Ok(letter.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use forgeerp_core::AggregateId;
+
+    use super::*;
+
+    fn envelope(tenant_id: TenantId) -> EventEnvelope<JsonValue> {
+        EventEnvelope::new(
+            Uuid::now_v7(),
+            tenant_id,
+            AggregateId::new(),
+            "inventory.item",
+            1,
+            serde_json::json!({ "StockAdjusted": { "delta": 1 } }),
+        )
+    }
+
+    #[test]
+    fn poisoned_event_is_recorded_and_a_later_replay_succeeds() {
+        let store = InMemoryProjectionDeadLetters::new();
+        let tenant_id = TenantId::new();
+        let fixed = AtomicBool::new(false);
+        let apply = |_: &EventEnvelope<JsonValue>| {
+            if fixed.load(Ordering::SeqCst) {
+                Ok(())
+            } else {
+                Err("unknown location".to_string())
+            }
+        };
+
+        // What the subscriber does when the projection errors.
+        let env = envelope(tenant_id);
+        let error = apply(&env).unwrap_err();
+        let letter = store.record(env.clone(), error).unwrap();
+        assert_eq!(store.list(tenant_id).unwrap(), vec![letter.clone()]);
+        assert!(store.list(TenantId::new()).unwrap().is_empty());
+
+        let err = replay_dead_letter(&store, tenant_id, letter.dead_letter_id, apply).unwrap_err();
+        assert!(matches!(err, ProjectionDeadLetterError::ReplayFailed { retry_count: 1, .. }));
+        assert_eq!(store.list(tenant_id).unwrap()[0].retry_count, 1);
+
+        fixed.store(true, Ordering::SeqCst);
+        let replayed = replay_dead_letter(&store, tenant_id, letter.dead_letter_id, apply).unwrap();
+        assert_eq!(replayed.envelope, env);
+        assert!(store.list(tenant_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn replaying_another_tenants_dead_letter_is_not_found() {
+        let store = InMemoryProjectionDeadLetters::new();
+        let letter = store.record(envelope(TenantId::new()), "boom".to_string()).unwrap();
+
+        let err = replay_dead_letter(&store, TenantId::new(), letter.dead_letter_id, |_| Ok(())).unwrap_err();
+        assert!(matches!(err, ProjectionDeadLetterError::NotFound(id) if id == letter.dead_letter_id));
+    }
+}
@@ -0,0 +1,274 @@
+//! Golden replay checks for projections.
+//!
+//! Each [`GoldenCase`] pairs a checked-in event log (`<name>.events.ndjson`, one
+//! [`StoredEvent`] per line) with a snapshot of the read model it must rebuild into
+//! (`<name>.snapshot.json`), both under [`golden_dir`]. The test in this module replays
+//! every case from scratch and fails on any difference, so a change in projection
+//! behaviour shows up as a snapshot diff in review instead of as silent drift.
+//!
+//! Regenerate the snapshots after an intended change with:
+//!
+//! ```text
+//! UPDATE_GOLDEN=1 cargo test -p forgeerp-infra golden
+//! ```
+//!
+//! To cover another projection, write its replay function (rebuild, then render the
+//! read model as JSON in a stable order), add it to [`GOLDEN_CASES`] and drop its event
+//! log next to the others; the first `UPDATE_GOLDEN=1` run writes its snapshot.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::Value as JsonValue;
+
+use forgeerp_core::TenantId;
+use forgeerp_events::EventEnvelope;
+use forgeerp_inventory::InventoryItemId;
+use forgeerp_invoicing::InvoiceId;
+
+use crate::event_store::StoredEvent;
+use crate::projections::inventory_stock::{InventoryReadModel, InventoryStockProjection};
+use crate::projections::invoicing::{InvoiceAgingProjection, InvoiceAgingReadModel};
+use crate::read_model::InMemoryTenantStore;
+
+/// Rebuilds a projection from `events` and renders its read model (deterministically).
+pub type GoldenReplay = fn(Vec<EventEnvelope<JsonValue>>) -> Result<JsonValue, String>;
+
+/// One golden log + snapshot pair.
+#[derive(Debug, Clone, Copy)]
+pub struct GoldenCase {
+    /// File stem of the log and snapshot.
+    pub name: &'static str,
+    pub replay: GoldenReplay,
+}
+
+impl GoldenCase {
+    pub fn log_path(&self, dir: &Path) -> PathBuf {
+        dir.join(format!("{}.events.ndjson", self.name))
+    }
+
+    pub fn snapshot_path(&self, dir: &Path) -> PathBuf {
+        dir.join(format!("{}.snapshot.json", self.name))
+    }
+}
+
+/// Projections under golden replay checks.
+pub const GOLDEN_CASES: &[GoldenCase] = &[
+    GoldenCase {
+        name: "inventory_stock",
+        replay: replay_inventory_stock,
+    },
+    GoldenCase {
+        name: "ar_aging",
+        replay: replay_ar_aging,
+    },
+];
+
+/// Checked-in golden logs and snapshots (`crates/infra/golden`).
+pub fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("golden")
+}
+
+/// Compare against the snapshots, or rewrite them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoldenMode {
+    Verify,
+    Update,
+}
+
+impl GoldenMode {
+    /// `Update` when `UPDATE_GOLDEN` is set to anything but `0`.
+    pub fn from_env() -> Self {
+        match std::env::var("UPDATE_GOLDEN") {
+            Ok(v) if !v.is_empty() && v != "0" => GoldenMode::Update,
+            _ => GoldenMode::Verify,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GoldenError {
+    #[error("{path}: {message}")]
+    Io { path: PathBuf, message: String },
+
+    #[error("{path}:{line}: invalid event: {message}")]
+    Parse { path: PathBuf, line: usize, message: String },
+
+    #[error("golden case `{name}` failed to replay: {message}")]
+    Replay { name: String, message: String },
+
+    #[error(
+        "golden case `{name}` drifted from {path} at line {line}:\n  expected: {expected}\n  actual:   {actual}\n\
+         (rerun with UPDATE_GOLDEN=1 if the change is intended)"
+    )]
+    Drift {
+        name: String,
+        path: PathBuf,
+        line: usize,
+        expected: String,
+        actual: String,
+    },
+}
+
+/// Parse an NDJSON event log; blank lines are skipped. Errors carry the 1-based line.
+pub fn parse_event_log(text: &str) -> Result<Vec<StoredEvent>, (usize, String)> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| (i + 1, e.to_string())))
+        .collect()
+}
+
+/// Snapshot rendering used on disk (pretty JSON, trailing newline).
+pub fn render_snapshot(snapshot: &JsonValue) -> String {
+    let mut text = serde_json::to_string_pretty(snapshot).unwrap_or_default();
+    text.push('\n');
+    text
+}
+
+/// Replay one case from `dir` and verify (or, in `Update` mode, write) its snapshot.
+pub fn check_case(case: &GoldenCase, dir: &Path, mode: GoldenMode) -> Result<(), GoldenError> {
+    let log_path = case.log_path(dir);
+    let log = std::fs::read_to_string(&log_path).map_err(|e| GoldenError::Io {
+        path: log_path.clone(),
+        message: e.to_string(),
+    })?;
+    let events = parse_event_log(&log).map_err(|(line, message)| GoldenError::Parse {
+        path: log_path.clone(),
+        line,
+        message,
+    })?;
+    let envelopes = events.iter().map(StoredEvent::to_envelope).collect();
+    let actual = (case.replay)(envelopes).map_err(|message| GoldenError::Replay {
+        name: case.name.to_string(),
+        message,
+    })?;
+    let actual = render_snapshot(&actual);
+
+    let snapshot_path = case.snapshot_path(dir);
+    if mode == GoldenMode::Update {
+        return std::fs::write(&snapshot_path, actual).map_err(|e| GoldenError::Io {
+            path: snapshot_path,
+            message: e.to_string(),
+        });
+    }
+    let expected = std::fs::read_to_string(&snapshot_path).map_err(|e| GoldenError::Io {
+        path: snapshot_path.clone(),
+        message: format!("{e} (run with UPDATE_GOLDEN=1 to create it)"),
+    })?;
+    match first_difference(&expected, &actual) {
+        None => Ok(()),
+        Some((line, expected, actual)) => Err(GoldenError::Drift {
+            name: case.name.to_string(),
+            path: snapshot_path,
+            line,
+            expected,
+            actual,
+        }),
+    }
+}
+
+/// First differing line (1-based) of two snapshots, with both sides (`<end>` past the end).
+fn first_difference(expected: &str, actual: &str) -> Option<(usize, String, String)> {
+    let (mut expected, mut actual) = (expected.lines(), actual.lines());
+    let mut line = 0;
+    loop {
+        line += 1;
+        match (expected.next(), actual.next()) {
+            (None, None) => return None,
+            (e, a) if e == a => continue,
+            (e, a) => {
+                let show = |side: Option<&str>| side.map_or_else(|| "<end>".to_string(), |s| s.trim().to_string());
+                return Some((line, show(e), show(a)));
+            }
+        }
+    }
+}
+
+/// Tenants of a log in a stable order.
+fn tenants_of(envelopes: &[EventEnvelope<JsonValue>]) -> Vec<TenantId> {
+    let by_id: BTreeMap<String, TenantId> = envelopes.iter().map(|e| (e.tenant_id().to_string(), e.tenant_id())).collect();
+    by_id.into_values().collect()
+}
+
+/// Per-tenant rows, tenants and rows in id order.
+fn per_tenant(rows: impl IntoIterator<Item = (String, String, JsonValue)>) -> JsonValue {
+    let mut tenants: BTreeMap<String, BTreeMap<String, JsonValue>> = BTreeMap::new();
+    for (tenant, id, row) in rows {
+        tenants.entry(tenant).or_default().insert(id, row);
+    }
+    serde_json::json!(tenants
+        .into_iter()
+        .map(|(tenant, rows)| (tenant, rows.into_values().collect::<Vec<_>>()))
+        .collect::<BTreeMap<_, _>>())
+}
+
+fn replay_inventory_stock(envelopes: Vec<EventEnvelope<JsonValue>>) -> Result<JsonValue, String> {
+    let tenants = tenants_of(&envelopes);
+    let projection = InventoryStockProjection::new(Arc::new(InMemoryTenantStore::<InventoryItemId, InventoryReadModel>::new()));
+    projection.rebuild_from_scratch(envelopes).map_err(|e| e.to_string())?;
+    Ok(per_tenant(tenants.into_iter().flat_map(|tenant_id| {
+        projection.list(tenant_id).into_iter().map(move |rm| {
+            (
+                tenant_id.to_string(),
+                rm.item_id.to_string(),
+                serde_json::json!({
+                    "item_id": rm.item_id.to_string(),
+                    "name": rm.name,
+                    "quantity": rm.quantity,
+                    "last_sequence": rm.last_sequence,
+                    "updated_at": rm.updated_at,
+                }),
+            )
+        })
+    })))
+}
+
+fn replay_ar_aging(envelopes: Vec<EventEnvelope<JsonValue>>) -> Result<JsonValue, String> {
+    let tenants = tenants_of(&envelopes);
+    let projection = InvoiceAgingProjection::new(Arc::new(InMemoryTenantStore::<InvoiceId, InvoiceAgingReadModel>::new()));
+    projection.rebuild_from_scratch(envelopes).map_err(|e| e.to_string())?;
+    Ok(per_tenant(tenants.into_iter().flat_map(|tenant_id| {
+        projection.list(tenant_id).into_iter().map(move |rm| {
+            (
+                tenant_id.to_string(),
+                rm.invoice_id.to_string(),
+                serde_json::json!({
+                    "invoice_id": rm.invoice_id.to_string(),
+                    "total_amount": rm.total_amount,
+                    "outstanding_amount": rm.outstanding_amount,
+                    "due_date": rm.due_date,
+                    "status": rm.status,
+                }),
+            )
+        })
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn golden_event_logs_replay_into_their_snapshots() {
+        let mode = GoldenMode::from_env();
+        let failures: Vec<String> = GOLDEN_CASES
+            .iter()
+            .filter_map(|case| check_case(case, &golden_dir(), mode).err())
+            .map(|e| e.to_string())
+            .collect();
+        assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+    }
+
+    #[test]
+    fn drift_reports_the_first_differing_line() {
+        assert_eq!(first_difference("a\nb\n", "a\nb\n"), None);
+        assert_eq!(
+            first_difference("{\n  \"quantity\": 5\n}\n", "{\n  \"quantity\": 6\n}\n"),
+            Some((2, "\"quantity\": 5".to_string(), "\"quantity\": 6".to_string()))
+        );
+        assert_eq!(first_difference("a\n", "a\nb\n"), Some((2, "<end>".to_string(), "b".to_string())));
+        assert_eq!(parse_event_log("\n{}\n").unwrap_err().0, 2);
+    }
+}
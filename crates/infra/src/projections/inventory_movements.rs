@@ -0,0 +1,397 @@
+//! Inventory Movement Projection.
+//!
+//! Stock ledger per item: one movement per `StockAdjusted` event with the delta,
+//! the resulting balance and when it happened. Movements are kept in stream order,
+//! so running balances are exact and the read model can be rebuilt from the events.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+use serde_json::Value as JsonValue;
+use thiserror::Error;
+
+use forgeerp_core::{AggregateId, TenantId};
+use forgeerp_events::EventEnvelope;
+use forgeerp_inventory::{InventoryEvent, InventoryItemId};
+
+use crate::projections::cursor_store::ProjectionCursorStore;
+use crate::read_model::TenantStore;
+
+/// One stock movement of an item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InventoryMovement {
+    /// Sequence number of the `StockAdjusted` event in the item stream.
+    pub sequence: u64,
+    pub delta: i64,
+    /// Quantity on hand after this movement.
+    pub balance: i64,
+    pub occurred_at: DateTime<Utc>,
+    /// Reason recorded on the event, if any.
+    pub reason: Option<String>,
+}
+
+/// Read model: movement history of one item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InventoryMovementHistory {
+    pub item_id: InventoryItemId,
+    /// Current balance (balance after the last movement).
+    pub balance: i64,
+    /// Movements in stream order.
+    pub movements: Vec<InventoryMovement>,
+}
+
+impl InventoryMovementHistory {
+    pub fn new(item_id: InventoryItemId) -> Self {
+        Self {
+            item_id,
+            balance: 0,
+            movements: Vec::new(),
+        }
+    }
+}
+
+/// Tenant+aggregate cursor for idempotent projection.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct CursorKey {
+    tenant_id: TenantId,
+    aggregate_id: AggregateId,
+}
+
+#[derive(Debug, Error)]
+pub enum InventoryMovementError {
+    #[error("failed to deserialize inventory event: {0}")]
+    Deserialize(String),
+
+    #[error("tenant isolation violation: {0}")]
+    TenantIsolation(String),
+
+    #[error("non-monotonic sequence number (last={last}, found={found})")]
+    NonMonotonicSequence { last: u64, found: u64 },
+}
+
+/// In-memory cursor store (no persistence).
+pub struct InMemoryCursorStore;
+
+impl ProjectionCursorStore for InMemoryCursorStore {
+    fn get_cursor(
+        &self,
+        _tenant_id: TenantId,
+        _aggregate_id: AggregateId,
+        _projection_name: &str,
+    ) -> Option<u64> {
+        None
+    }
+
+    fn update_cursor(
+        &self,
+        _tenant_id: TenantId,
+        _aggregate_id: AggregateId,
+        _projection_name: &str,
+        _sequence_number: u64,
+    ) {
+        // no-op
+    }
+
+    fn clear_cursors(&self, _tenant_id: TenantId, _projection_name: &str) {
+        // no-op
+    }
+}
+
+/// Inventory movement projection: stock ledger per item.
+///
+/// Rebuildable from inventory events. Tenant-isolated.
+#[derive(Debug)]
+pub struct InventoryMovementProjection<S, C = InMemoryCursorStore>
+where
+    S: TenantStore<InventoryItemId, InventoryMovementHistory>,
+{
+    store: S,
+    cursors: RwLock<HashMap<CursorKey, u64>>,
+    cursor_store: Option<Arc<C>>,
+    projection_name: String,
+}
+
+impl<S> InventoryMovementProjection<S>
+where
+    S: TenantStore<InventoryItemId, InventoryMovementHistory>,
+{
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            cursors: RwLock::new(HashMap::new()),
+            cursor_store: None,
+            projection_name: "inventory.movements".to_string(),
+        }
+    }
+
+    pub fn with_persistent_cursors<C: ProjectionCursorStore + 'static>(
+        self,
+        cursor_store: Arc<C>,
+        projection_name: impl Into<String>,
+    ) -> InventoryMovementProjection<S, C> {
+        InventoryMovementProjection {
+            store: self.store,
+            cursors: RwLock::new(HashMap::new()),
+            cursor_store: Some(cursor_store),
+            projection_name: projection_name.into(),
+        }
+    }
+}
+
+impl<S, C> InventoryMovementProjection<S, C>
+where
+    S: TenantStore<InventoryItemId, InventoryMovementHistory>,
+    C: ProjectionCursorStore + 'static,
+{
+    fn get_cursor(&self, tenant_id: TenantId, aggregate_id: AggregateId) -> u64 {
+        if let Some(ref cursor_store) = self.cursor_store {
+            cursor_store
+                .get_cursor(tenant_id, aggregate_id, &self.projection_name)
+                .unwrap_or(0)
+        } else {
+            match self.cursors.read() {
+                Ok(cursors) => *cursors
+                    .get(&CursorKey { tenant_id, aggregate_id })
+                    .unwrap_or(&0),
+                Err(_) => 0,
+            }
+        }
+    }
+
+    fn update_cursor(&self, tenant_id: TenantId, aggregate_id: AggregateId, sequence_number: u64) {
+        if let Ok(mut cursors) = self.cursors.write() {
+            cursors.insert(CursorKey { tenant_id, aggregate_id }, sequence_number);
+        }
+
+        if let Some(ref cursor_store) = self.cursor_store {
+            cursor_store.update_cursor(tenant_id, aggregate_id, &self.projection_name, sequence_number);
+        }
+    }
+
+    fn clear_cursors(&self, tenant_id: TenantId) {
+        if let Ok(mut cursors) = self.cursors.write() {
+            cursors.retain(|k, _| k.tenant_id != tenant_id);
+        }
+
+        if let Some(ref cursor_store) = self.cursor_store {
+            cursor_store.clear_cursors(tenant_id, &self.projection_name);
+        }
+    }
+
+    /// Movement history of one item.
+    pub fn get(&self, tenant_id: TenantId, item_id: &InventoryItemId) -> Option<InventoryMovementHistory> {
+        self.store.get(tenant_id, item_id)
+    }
+
+    /// Apply envelope into the movement history.
+    pub fn apply_envelope(&self, envelope: &EventEnvelope<JsonValue>) -> Result<(), InventoryMovementError> {
+        if envelope.aggregate_type() != "inventory.item" {
+            return Ok(());
+        }
+
+        let tenant_id = envelope.tenant_id();
+        let aggregate_id = envelope.aggregate_id();
+        let seq = envelope.sequence_number();
+
+        let last = self.get_cursor(tenant_id, aggregate_id);
+
+        if seq == 0 {
+            return Err(InventoryMovementError::NonMonotonicSequence { last, found: seq });
+        }
+
+        if seq <= last {
+            return Ok(());
+        }
+
+        if seq != last + 1 && last != 0 {
+            return Err(InventoryMovementError::NonMonotonicSequence { last, found: seq });
+        }
+
+        let ev: InventoryEvent = serde_json::from_value(envelope.payload().clone())
+            .map_err(|e| InventoryMovementError::Deserialize(e.to_string()))?;
+
+        let (event_tenant, item_id) = match &ev {
+            InventoryEvent::ItemCreated(e) => (e.tenant_id, e.item_id),
+            InventoryEvent::ItemRenamed(e) => (e.tenant_id, e.item_id),
+            InventoryEvent::StockAdjusted(e) => (e.tenant_id, e.item_id),
+            InventoryEvent::StockReserved(e) => (e.tenant_id, e.item_id),
+            InventoryEvent::ReservationReleased(e) => (e.tenant_id, e.item_id),
+            InventoryEvent::ReorderPointSet(e) => (e.tenant_id, e.item_id),
+            InventoryEvent::StockFellBelowReorderPoint(e) => (e.tenant_id, e.item_id),
+        };
+
+        if event_tenant != tenant_id {
+            return Err(InventoryMovementError::TenantIsolation(
+                "event tenant_id does not match envelope tenant_id".to_string(),
+            ));
+        }
+
+        if item_id.0 != aggregate_id {
+            return Err(InventoryMovementError::TenantIsolation(
+                "event item_id does not match envelope aggregate_id".to_string(),
+            ));
+        }
+
+        match ev {
+            InventoryEvent::ItemCreated(e) => {
+                self.store.upsert(tenant_id, e.item_id, InventoryMovementHistory::new(e.item_id));
+            }
+            InventoryEvent::StockAdjusted(e) => {
+                let mut history = self
+                    .store
+                    .get(tenant_id, &e.item_id)
+                    .unwrap_or_else(|| InventoryMovementHistory::new(e.item_id));
+                history.balance += e.delta;
+                history.movements.push(InventoryMovement {
+                    sequence: seq,
+                    delta: e.delta,
+                    balance: history.balance,
+                    occurred_at: e.occurred_at,
+                    reason: reason(envelope.payload()),
+                });
+                self.store.upsert(tenant_id, e.item_id, history);
+            }
+            // Renames, reservations and reorder points do not move stock.
+            InventoryEvent::ItemRenamed(_)
+            | InventoryEvent::StockReserved(_)
+            | InventoryEvent::ReservationReleased(_)
+            | InventoryEvent::ReorderPointSet(_)
+            | InventoryEvent::StockFellBelowReorderPoint(_) => {}
+        }
+
+        self.update_cursor(tenant_id, aggregate_id, seq);
+        Ok(())
+    }
+
+    /// Rebuild the read model from scratch.
+    pub fn rebuild_from_scratch(
+        &self,
+        envelopes: impl IntoIterator<Item = EventEnvelope<JsonValue>>,
+    ) -> Result<(), InventoryMovementError> {
+        let mut envs: Vec<_> = envelopes.into_iter().collect();
+
+        {
+            let mut tenants = envs.iter().map(|e| e.tenant_id()).collect::<Vec<_>>();
+            tenants.sort_by_key(|t| *t.as_uuid().as_bytes());
+            tenants.dedup();
+            for t in tenants {
+                self.store.clear_tenant(t);
+                self.clear_cursors(t);
+            }
+        }
+
+        envs.sort_by_key(|e| {
+            (
+                *e.tenant_id().as_uuid().as_bytes(),
+                *e.aggregate_id().as_uuid().as_bytes(),
+                e.sequence_number(),
+            )
+        });
+
+        for env in &envs {
+            self.apply_envelope(env)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Optional `reason` of a `StockAdjusted` body (not every adjustment records one).
+fn reason(payload: &JsonValue) -> Option<String> {
+    payload
+        .get("StockAdjusted")?
+        .get("reason")?
+        .as_str()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_model::InMemoryTenantStore;
+    use forgeerp_inventory::{DEFAULT_LOCATION, ItemCreated, StockAdjusted};
+
+    type Projection = InventoryMovementProjection<Arc<InMemoryTenantStore<InventoryItemId, InventoryMovementHistory>>>;
+
+    fn make_envelope(tenant_id: TenantId, aggregate_id: AggregateId, seq: u64, payload: JsonValue) -> EventEnvelope<JsonValue> {
+        EventEnvelope::new(uuid::Uuid::now_v7(), tenant_id, aggregate_id, "inventory.item".to_string(), seq, payload)
+    }
+
+    /// Envelopes for creating `item_id` and adjusting it by each of `deltas`.
+    fn stream(tenant_id: TenantId, item_id: InventoryItemId, deltas: &[i64]) -> Vec<EventEnvelope<JsonValue>> {
+        let created = InventoryEvent::ItemCreated(ItemCreated {
+            tenant_id,
+            item_id,
+            name: "Widget".to_string(),
+            occurred_at: Utc::now(),
+        });
+        let mut envs = vec![make_envelope(tenant_id, item_id.0, 1, serde_json::to_value(&created).unwrap())];
+        for (i, delta) in deltas.iter().enumerate() {
+            let adjusted = InventoryEvent::StockAdjusted(StockAdjusted {
+                tenant_id,
+                item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta: *delta,
+                occurred_at: Utc::now(),
+            });
+            let seq = i as u64 + 2;
+            envs.push(make_envelope(tenant_id, item_id.0, seq, serde_json::to_value(&adjusted).unwrap()));
+        }
+        envs
+    }
+
+    #[test]
+    fn adjustments_produce_ordered_movements_with_running_balances() {
+        let proj: Projection = InventoryMovementProjection::new(Arc::new(InMemoryTenantStore::new()));
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+
+        for env in stream(tenant_id, item_id, &[100, -30, 15, -85]) {
+            proj.apply_envelope(&env).unwrap();
+        }
+
+        let history = proj.get(tenant_id, &item_id).unwrap();
+        let ledger: Vec<(u64, i64, i64)> = history.movements.iter().map(|m| (m.sequence, m.delta, m.balance)).collect();
+        assert_eq!(ledger, vec![(2, 100, 100), (3, -30, 70), (4, 15, 85), (5, -85, 0)]);
+        assert_eq!(history.balance, 0);
+        assert!(history.movements.windows(2).all(|w| w[0].occurred_at <= w[1].occurred_at));
+
+        // Other tenants see nothing.
+        assert!(proj.get(TenantId::new(), &item_id).is_none());
+    }
+
+    #[test]
+    fn redelivery_is_ignored_and_rebuild_reproduces_the_ledger() {
+        let proj: Projection = InventoryMovementProjection::new(Arc::new(InMemoryTenantStore::new()));
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+        let envs = stream(tenant_id, item_id, &[10, 5]);
+
+        for env in envs.iter().chain(envs.iter()) {
+            proj.apply_envelope(env).unwrap();
+        }
+        let live = proj.get(tenant_id, &item_id).unwrap();
+        assert_eq!(live.movements.len(), 2);
+
+        proj.rebuild_from_scratch(envs.into_iter().rev()).unwrap();
+        assert_eq!(proj.get(tenant_id, &item_id).unwrap(), live);
+    }
+
+    #[test]
+    fn reason_is_recorded_when_present() {
+        let proj: Projection = InventoryMovementProjection::new(Arc::new(InMemoryTenantStore::new()));
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+        let mut envs = stream(tenant_id, item_id, &[4]);
+        let mut payload = envs[1].payload().clone();
+        payload["StockAdjusted"]["reason"] = JsonValue::from("cycle count");
+        envs[1] = make_envelope(tenant_id, item_id.0, 2, payload);
+
+        for env in &envs {
+            proj.apply_envelope(env).unwrap();
+        }
+        let history = proj.get(tenant_id, &item_id).unwrap();
+        assert_eq!(history.movements[0].reason.as_deref(), Some("cycle count"));
+    }
+}
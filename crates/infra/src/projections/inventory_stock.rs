@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
+use chrono::{DateTime, Utc};
 use serde_json::Value as JsonValue;
 use thiserror::Error;
 
@@ -9,7 +10,7 @@ use forgeerp_ai::{AiError, InventoryItemSnapshot, InventorySnapshot, ReadModelRe
 use forgeerp_events::EventEnvelope;
 use forgeerp_inventory::{InventoryEvent, InventoryItemId};
 
-use crate::read_model::TenantStore;
+use crate::read_model::{TenantStore, Watermark};
 use crate::projections::cursor_store::ProjectionCursorStore;
 
 /// Queryable inventory read model: current stock per item.
@@ -18,6 +19,16 @@ pub struct InventoryReadModel {
     pub item_id: InventoryItemId,
     pub name: String,
     pub quantity: i64,
+    /// Sequence number of the last event applied to this item.
+    pub last_sequence: u64,
+    /// Storage time of the last event applied to this item.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl InventoryReadModel {
+    pub fn watermark(&self) -> Watermark {
+        Watermark::new(self.last_sequence, self.updated_at)
+    }
 }
 
 /// Tenant+aggregate cursor to support at-least-once delivery (idempotent projection).
@@ -172,89 +183,81 @@ where
         self.store.list(tenant_id)
     }
 
+    /// Highest item watermark for a tenant (compare with a cached one to detect changes).
+    pub fn watermark(&self, tenant_id: TenantId) -> Watermark {
+        Watermark::max_of(self.list(tenant_id).iter().map(InventoryReadModel::watermark))
+    }
+
     /// Apply a published envelope into the projection.
     ///
     /// - Enforces tenant isolation
     /// - Enforces monotonic sequence per (tenant, aggregate) stream
     /// - Idempotent for at-least-once delivery (replays <= cursor are ignored)
     pub fn apply_envelope(&self, envelope: &EventEnvelope<JsonValue>) -> Result<(), InventoryProjectionError> {
-        // Ignore non-inventory aggregates (allows sharing a bus across modules).
-        if envelope.aggregate_type() != "inventory.item" {
+        let last = self.get_cursor(envelope.tenant_id(), envelope.aggregate_id());
+        let Some((item_id, event)) = decode(envelope, last)? else {
             return Ok(());
-        }
+        };
 
         let tenant_id = envelope.tenant_id();
-        let aggregate_id = envelope.aggregate_id();
         let seq = envelope.sequence_number();
-
-        // Cursor check (per tenant + aggregate stream).
-        let last = self.get_cursor(tenant_id, aggregate_id);
-
-        if seq == 0 {
-            return Err(InventoryProjectionError::NonMonotonicSequence { last, found: seq });
-        }
-
-        if seq <= last {
-            // Duplicate or replay; safe to ignore.
-            return Ok(());
+        if let Some(rm) = fold(self.store.get(tenant_id, &item_id), item_id, event, envelope) {
+            self.store.upsert(tenant_id, item_id, rm);
         }
 
-        if seq != last + 1 && last != 0 {
-            // We allow first event to be any positive sequence (some stores start at 1),
-            // but after that we enforce strict monotonic increments.
-            return Err(InventoryProjectionError::NonMonotonicSequence { last, found: seq });
-        }
+        // Advance cursor after successful apply.
+        self.update_cursor(tenant_id, envelope.aggregate_id(), seq);
 
-        // Deserialize the inventory event from payload.
-        let inv: InventoryEvent = serde_json::from_value(envelope.payload().clone())
-            .map_err(|e| InventoryProjectionError::Deserialize(e.to_string()))?;
+        Ok(())
+    }
 
-        // Validate tenant isolation at the event level.
-        let (event_tenant, item_id) = match &inv {
-            InventoryEvent::ItemCreated(e) => (e.tenant_id, e.item_id),
-            InventoryEvent::StockAdjusted(e) => (e.tenant_id, e.item_id),
-        };
+    /// Apply a batch of envelopes, in order, with the same checks and result as applying
+    /// them one by one with [`apply_envelope`](Self::apply_envelope).
+    ///
+    /// The batch is folded in memory: each touched item is written once per tenant through
+    /// [`TenantStore::upsert_many`] and each stream's cursor is persisted once, instead of
+    /// once per event. On an error, the envelopes before the failing one are still written.
+    pub fn apply_batch(&self, envelopes: &[EventEnvelope<JsonValue>]) -> Result<(), InventoryProjectionError> {
+        let mut items: HashMap<(TenantId, InventoryItemId), Option<InventoryReadModel>> = HashMap::new();
+        let mut cursors: HashMap<CursorKey, u64> = HashMap::new();
+
+        let result = envelopes.iter().try_for_each(|envelope| {
+            let key = CursorKey {
+                tenant_id: envelope.tenant_id(),
+                aggregate_id: envelope.aggregate_id(),
+            };
+            let last = match cursors.get(&key) {
+                Some(last) => *last,
+                None => self.get_cursor(key.tenant_id, key.aggregate_id),
+            };
+            let Some((item_id, event)) = decode(envelope, last)? else {
+                return Ok(());
+            };
+
+            let staged = items
+                .entry((key.tenant_id, item_id))
+                .or_insert_with(|| self.store.get(key.tenant_id, &item_id));
+            if let Some(rm) = fold(staged.clone(), item_id, event, envelope) {
+                *staged = Some(rm);
+            }
+            cursors.insert(key, envelope.sequence_number());
+            Ok(())
+        });
 
-        if event_tenant != tenant_id {
-            return Err(InventoryProjectionError::TenantIsolation(
-                "event tenant_id does not match envelope tenant_id".to_string(),
-            ));
+        let mut by_tenant: HashMap<TenantId, Vec<(InventoryItemId, InventoryReadModel)>> = HashMap::new();
+        for ((tenant_id, item_id), rm) in items {
+            if let Some(rm) = rm {
+                by_tenant.entry(tenant_id).or_default().push((item_id, rm));
+            }
         }
-
-        if item_id.0 != aggregate_id {
-            return Err(InventoryProjectionError::TenantIsolation(
-                "event item_id does not match envelope aggregate_id".to_string(),
-            ));
+        for (tenant_id, entries) in by_tenant {
+            self.store.upsert_many(tenant_id, entries);
         }
-
-        // Apply update.
-        match inv {
-            InventoryEvent::ItemCreated(e) => {
-                self.store.upsert(
-                    tenant_id,
-                    e.item_id,
-                    InventoryReadModel {
-                        item_id: e.item_id,
-                        name: e.name,
-                        quantity: 0,
-                    },
-                );
-            }
-            InventoryEvent::StockAdjusted(e) => {
-                let mut rm = self.store.get(tenant_id, &e.item_id).unwrap_or(InventoryReadModel {
-                    item_id: e.item_id,
-                    name: String::new(),
-                    quantity: 0,
-                });
-                rm.quantity += e.delta;
-                self.store.upsert(tenant_id, e.item_id, rm);
-            }
+        for (key, seq) in cursors {
+            self.update_cursor(key.tenant_id, key.aggregate_id, seq);
         }
 
-        // Advance cursor after successful apply.
-        self.update_cursor(tenant_id, aggregate_id, seq);
-
-        Ok(())
+        result
     }
 
     /// Rebuild the read model from scratch by replaying envelopes.
@@ -287,11 +290,118 @@ where
             )
         });
 
-        for env in &envs {
-            self.apply_envelope(env)?;
-        }
+        self.apply_batch(&envs)
+    }
+}
 
-        Ok(())
+/// Check `envelope` against its stream's cursor `last` and decode it.
+///
+/// `Ok(None)` for envelopes the projection skips: other aggregates, and replays at or
+/// below the cursor.
+fn decode(
+    envelope: &EventEnvelope<JsonValue>,
+    last: u64,
+) -> Result<Option<(InventoryItemId, InventoryEvent)>, InventoryProjectionError> {
+    // Ignore non-inventory aggregates (allows sharing a bus across modules).
+    if envelope.aggregate_type() != "inventory.item" {
+        return Ok(None);
+    }
+
+    let tenant_id = envelope.tenant_id();
+    let aggregate_id = envelope.aggregate_id();
+    let seq = envelope.sequence_number();
+
+    if seq == 0 {
+        return Err(InventoryProjectionError::NonMonotonicSequence { last, found: seq });
+    }
+
+    if seq <= last {
+        // Duplicate or replay; safe to ignore.
+        return Ok(None);
+    }
+
+    if seq != last + 1 && last != 0 {
+        // We allow first event to be any positive sequence (some stores start at 1),
+        // but after that we enforce strict monotonic increments.
+        return Err(InventoryProjectionError::NonMonotonicSequence { last, found: seq });
+    }
+
+    // Deserialize the inventory event from payload.
+    let inv: InventoryEvent = serde_json::from_value(envelope.payload().clone())
+        .map_err(|e| InventoryProjectionError::Deserialize(e.to_string()))?;
+
+    // Validate tenant isolation at the event level.
+    let (event_tenant, item_id) = match &inv {
+        InventoryEvent::ItemCreated(e) => (e.tenant_id, e.item_id),
+        InventoryEvent::ItemRenamed(e) => (e.tenant_id, e.item_id),
+        InventoryEvent::StockAdjusted(e) => (e.tenant_id, e.item_id),
+        InventoryEvent::StockReserved(e) => (e.tenant_id, e.item_id),
+        InventoryEvent::ReservationReleased(e) => (e.tenant_id, e.item_id),
+        InventoryEvent::ReorderPointSet(e) => (e.tenant_id, e.item_id),
+        InventoryEvent::StockFellBelowReorderPoint(e) => (e.tenant_id, e.item_id),
+    };
+
+    if event_tenant != tenant_id {
+        return Err(InventoryProjectionError::TenantIsolation(
+            "event tenant_id does not match envelope tenant_id".to_string(),
+        ));
+    }
+
+    if item_id.0 != aggregate_id {
+        return Err(InventoryProjectionError::TenantIsolation(
+            "event item_id does not match envelope aggregate_id".to_string(),
+        ));
+    }
+
+    Ok(Some((item_id, inv)))
+}
+
+/// The item's read model after `event` (stamping the entry's watermark); `None` when
+/// there is nothing to write.
+fn fold(
+    current: Option<InventoryReadModel>,
+    item_id: InventoryItemId,
+    event: InventoryEvent,
+    envelope: &EventEnvelope<JsonValue>,
+) -> Option<InventoryReadModel> {
+    let seq = envelope.sequence_number();
+    let updated_at = envelope.created_at().unwrap_or_else(Utc::now);
+    match event {
+        InventoryEvent::ItemCreated(e) => Some(InventoryReadModel {
+            item_id: e.item_id,
+            name: e.name,
+            quantity: 0,
+            last_sequence: seq,
+            updated_at,
+        }),
+        InventoryEvent::ItemRenamed(e) => current.map(|mut rm| {
+            rm.name = e.name;
+            rm.last_sequence = seq;
+            rm.updated_at = updated_at;
+            rm
+        }),
+        InventoryEvent::StockAdjusted(e) => {
+            let mut rm = current.unwrap_or(InventoryReadModel {
+                item_id,
+                name: String::new(),
+                quantity: 0,
+                last_sequence: 0,
+                updated_at,
+            });
+            rm.quantity += e.delta;
+            rm.last_sequence = seq;
+            rm.updated_at = updated_at;
+            Some(rm)
+        }
+        // Reservations and reorder points leave on-hand stock unchanged; only the watermark moves.
+        InventoryEvent::StockReserved(_)
+        | InventoryEvent::ReservationReleased(_)
+        | InventoryEvent::ReorderPointSet(_)
+        | InventoryEvent::StockFellBelowReorderPoint(_) => current.map(|mut rm| {
+            rm.last_sequence = seq;
+            rm.updated_at = updated_at;
+            rm
+        }),
     }
 }
 
@@ -18,6 +18,7 @@ use thiserror::Error;
 use forgeerp_core::{AggregateId, TenantId};
 use forgeerp_events::EventEnvelope;
 use forgeerp_inventory::{InventoryEvent, InventoryItemId};
+use forgeerp_purchasing::LineLandedCost;
 
 use crate::projections::cursor_store::ProjectionCursorStore;
 use crate::read_model::TenantStore;
@@ -268,6 +269,13 @@ where
         }
     }
 
+    /// Value an item at the landed unit cost of a received purchase order line.
+    pub fn apply_landed_cost(&self, tenant_id: TenantId, item_id: InventoryItemId, line: &LineLandedCost) {
+        if let Some(unit_cost) = line.unit_landed_cost() {
+            self.set_unit_cost(tenant_id, item_id, unit_cost);
+        }
+    }
+
     /// Apply envelope into inventory valuation.
     pub fn apply_envelope(
         &self,
@@ -300,7 +308,12 @@ where
 
         let (event_tenant, item_id) = match &ev {
             InventoryEvent::ItemCreated(e) => (e.tenant_id, e.item_id),
+            InventoryEvent::ItemRenamed(e) => (e.tenant_id, e.item_id),
             InventoryEvent::StockAdjusted(e) => (e.tenant_id, e.item_id),
+            InventoryEvent::StockReserved(e) => (e.tenant_id, e.item_id),
+            InventoryEvent::ReservationReleased(e) => (e.tenant_id, e.item_id),
+            InventoryEvent::ReorderPointSet(e) => (e.tenant_id, e.item_id),
+            InventoryEvent::StockFellBelowReorderPoint(e) => (e.tenant_id, e.item_id),
         };
 
         if event_tenant != tenant_id {
@@ -320,6 +333,12 @@ where
                 let val = InventoryValuation::new(e.item_id, e.name);
                 self.store.upsert(tenant_id, e.item_id, val);
             }
+            InventoryEvent::ItemRenamed(e) => {
+                if let Some(mut val) = self.store.get(tenant_id, &e.item_id) {
+                    val.name = e.name;
+                    self.store.upsert(tenant_id, e.item_id, val);
+                }
+            }
             InventoryEvent::StockAdjusted(e) => {
                 let mut val = self.store.get(tenant_id, &e.item_id).unwrap_or_else(|| {
                     InventoryValuation::new(e.item_id, String::new())
@@ -328,6 +347,11 @@ where
                 val.recalculate_value();
                 self.store.upsert(tenant_id, e.item_id, val);
             }
+            // Reservations and reorder points do not move stock.
+            InventoryEvent::StockReserved(_)
+            | InventoryEvent::ReservationReleased(_)
+            | InventoryEvent::ReorderPointSet(_)
+            | InventoryEvent::StockFellBelowReorderPoint(_) => {}
         }
 
         self.update_cursor(tenant_id, aggregate_id, seq);
@@ -372,7 +396,7 @@ mod tests {
     use super::*;
     use crate::read_model::InMemoryTenantStore;
     use forgeerp_core::AggregateId;
-    use forgeerp_inventory::{ItemCreated, StockAdjusted};
+    use forgeerp_inventory::{DEFAULT_LOCATION, ItemCreated, StockAdjusted};
     use chrono::Utc;
 
     fn make_envelope(
@@ -411,6 +435,7 @@ mod tests {
         let adjusted = InventoryEvent::StockAdjusted(StockAdjusted {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 100,
             occurred_at: Utc::now(),
         });
@@ -442,6 +467,7 @@ mod tests {
         let adjusted = InventoryEvent::StockAdjusted(StockAdjusted {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 50,
             occurred_at: Utc::now(),
         });
@@ -477,6 +503,7 @@ mod tests {
             let adjusted = InventoryEvent::StockAdjusted(StockAdjusted {
                 tenant_id,
                 item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
                 delta: 100,
                 occurred_at: Utc::now(),
             });
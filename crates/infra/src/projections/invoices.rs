@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
@@ -10,7 +11,7 @@ use forgeerp_invoicing::{InvoiceEvent, InvoiceId, InvoiceLine, InvoiceStatus};
 use forgeerp_sales::SalesOrderId;
 
 use crate::projections::cursor_store::ProjectionCursorStore;
-use crate::read_model::TenantStore;
+use crate::read_model::{ListPage, PageStart, TenantStore};
 
 /// Queryable invoice read model (header + lines).
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -19,11 +20,26 @@ pub struct InvoiceReadModel {
     pub sales_order_id: SalesOrderId,
     pub due_date: Option<chrono::DateTime<chrono::Utc>>,
     pub status: InvoiceStatus,
+    /// ISO code the invoice is billed in (`None` for invoices issued before it was recorded).
+    pub currency: Option<String>,
     pub total_amount: u64,
     pub total_paid: u64,
+    /// Sum of credit notes issued against the invoice.
+    pub credited_total: u64,
     pub lines: Vec<InvoiceLine>,
 }
 
+impl InvoiceReadModel {
+    /// Invoiced amount after credit notes.
+    pub fn net_total(&self) -> u64 {
+        self.total_amount.saturating_sub(self.credited_total)
+    }
+
+    pub fn outstanding_amount(&self) -> u64 {
+        self.net_total().saturating_sub(self.total_paid)
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 struct CursorKey {
     tenant_id: TenantId,
@@ -151,6 +167,18 @@ where
         self.store.list(tenant_id)
     }
 
+    /// One page of the tenant's invoices that `keep` accepts, ordered by `order`.
+    pub fn list_page(
+        &self,
+        tenant_id: TenantId,
+        keep: &dyn Fn(&InvoiceReadModel) -> bool,
+        order: &dyn Fn(&InvoiceReadModel, &InvoiceReadModel) -> Ordering,
+        start: PageStart<'_, InvoiceReadModel>,
+        limit: usize,
+    ) -> ListPage<InvoiceReadModel> {
+        self.store.list_page(tenant_id, keep, order, start, limit)
+    }
+
     pub fn apply_envelope(
         &self,
         envelope: &EventEnvelope<JsonValue>,
@@ -181,6 +209,8 @@ where
             InvoiceEvent::InvoiceIssued(e) => (e.tenant_id, e.invoice_id),
             InvoiceEvent::PaymentRegistered(e) => (e.tenant_id, e.invoice_id),
             InvoiceEvent::InvoiceVoided(e) => (e.tenant_id, e.invoice_id),
+            InvoiceEvent::CreditNoteIssued(e) => (e.tenant_id, e.invoice_id),
+            InvoiceEvent::PaymentReversed(e) => (e.tenant_id, e.invoice_id),
         };
 
         if event_tenant != tenant_id {
@@ -204,8 +234,10 @@ where
                         sales_order_id: e.sales_order_id,
                         due_date: Some(e.due_date),
                         status: InvoiceStatus::Open,
+                        currency: e.currency,
                         total_amount: e.total_amount,
                         total_paid: 0,
+                        credited_total: 0,
                         lines: e.lines,
                     },
                 );
@@ -216,12 +248,14 @@ where
                     sales_order_id: SalesOrderId::new(AggregateId::new()),
                     due_date: None,
                     status: InvoiceStatus::Open,
+                    currency: None,
                     total_amount: 0,
                     total_paid: 0,
+                    credited_total: 0,
                     lines: vec![],
                 });
                 rm.total_paid = e.new_total_paid;
-                if rm.total_paid >= rm.total_amount {
+                if rm.total_paid >= rm.net_total() {
                     rm.status = InvoiceStatus::Paid;
                 }
                 self.store.upsert(tenant_id, e.invoice_id, rm);
@@ -232,13 +266,51 @@ where
                     sales_order_id: SalesOrderId::new(AggregateId::new()),
                     due_date: None,
                     status: InvoiceStatus::Open,
+                    currency: None,
                     total_amount: 0,
                     total_paid: 0,
+                    credited_total: 0,
                     lines: vec![],
                 });
                 rm.status = InvoiceStatus::Void;
                 self.store.upsert(tenant_id, e.invoice_id, rm);
             }
+            InvoiceEvent::CreditNoteIssued(e) => {
+                let mut rm = self.store.get(tenant_id, &e.invoice_id).unwrap_or(InvoiceReadModel {
+                    invoice_id: e.invoice_id,
+                    sales_order_id: SalesOrderId::new(AggregateId::new()),
+                    due_date: None,
+                    status: InvoiceStatus::Open,
+                    currency: None,
+                    total_amount: 0,
+                    total_paid: 0,
+                    credited_total: 0,
+                    lines: vec![],
+                });
+                rm.credited_total = e.new_credited_total;
+                if rm.status == InvoiceStatus::Open && rm.total_paid >= rm.net_total() {
+                    rm.status = InvoiceStatus::Paid;
+                }
+                self.store.upsert(tenant_id, e.invoice_id, rm);
+            }
+            InvoiceEvent::PaymentReversed(e) => {
+                let mut rm = self.store.get(tenant_id, &e.invoice_id).unwrap_or(InvoiceReadModel {
+                    invoice_id: e.invoice_id,
+                    sales_order_id: SalesOrderId::new(AggregateId::new()),
+                    due_date: None,
+                    status: InvoiceStatus::Open,
+                    currency: None,
+                    total_amount: 0,
+                    total_paid: 0,
+                    credited_total: 0,
+                    lines: vec![],
+                });
+                rm.total_paid = e.new_total_paid;
+                if rm.status == InvoiceStatus::Paid && rm.total_paid < rm.net_total() {
+                    rm.status = InvoiceStatus::Open;
+                }
+                self.store.upsert(tenant_id, e.invoice_id, rm);
+            }
         }
 
         self.update_cursor(tenant_id, aggregate_id, seq);
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
+use serde::Serialize;
 use serde_json::Value as JsonValue;
 use thiserror::Error;
 
@@ -16,7 +17,7 @@ use crate::read_model::TenantStore;
 /// This model stores enough information to compute aging buckets at query time:
 /// - original invoice amount
 /// - outstanding amount
-/// - due date
+/// - due date (issue time plus payment terms)
 /// - current status
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct InvoiceAgingReadModel {
@@ -27,6 +28,40 @@ pub struct InvoiceAgingReadModel {
     pub status: InvoiceStatus,
 }
 
+/// AR aging bucket, by whole days past the due date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AgingBucket {
+    #[serde(rename = "current")]
+    Current,
+    #[serde(rename = "1-30")]
+    Days1To30,
+    #[serde(rename = "31-60")]
+    Days31To60,
+    #[serde(rename = "61-90")]
+    Days61To90,
+    #[serde(rename = "90+")]
+    Over90,
+}
+
+impl InvoiceAgingReadModel {
+    /// Whole days past `due_date` at `now`; 0 when not yet due or undated.
+    pub fn days_past_due(&self, now: chrono::DateTime<chrono::Utc>) -> i64 {
+        self.due_date
+            .map(|due| now.signed_duration_since(due).num_days().max(0))
+            .unwrap_or(0)
+    }
+
+    pub fn bucket(&self, now: chrono::DateTime<chrono::Utc>) -> AgingBucket {
+        match self.days_past_due(now) {
+            0 => AgingBucket::Current,
+            1..=30 => AgingBucket::Days1To30,
+            31..=60 => AgingBucket::Days31To60,
+            61..=90 => AgingBucket::Days61To90,
+            _ => AgingBucket::Over90,
+        }
+    }
+}
+
 /// Tenant+aggregate cursor to support at-least-once delivery (idempotent projection).
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 struct CursorKey {
@@ -216,6 +251,8 @@ where
             InvoiceEvent::InvoiceIssued(e) => (e.tenant_id, e.invoice_id),
             InvoiceEvent::PaymentRegistered(e) => (e.tenant_id, e.invoice_id),
             InvoiceEvent::InvoiceVoided(e) => (e.tenant_id, e.invoice_id),
+            InvoiceEvent::CreditNoteIssued(e) => (e.tenant_id, e.invoice_id),
+            InvoiceEvent::PaymentReversed(e) => (e.tenant_id, e.invoice_id),
         };
 
         if event_tenant != tenant_id {
@@ -277,6 +314,42 @@ where
                 rm.status = InvoiceStatus::Void;
                 self.store.upsert(tenant_id, e.invoice_id, rm);
             }
+            InvoiceEvent::CreditNoteIssued(e) => {
+                let mut rm = self
+                    .store
+                    .get(tenant_id, &e.invoice_id)
+                    .unwrap_or(InvoiceAgingReadModel {
+                        invoice_id: e.invoice_id,
+                        total_amount: 0,
+                        outstanding_amount: 0,
+                        due_date: None,
+                        status: InvoiceStatus::Open,
+                    });
+                // Credits reduce what the customer still owes.
+                rm.outstanding_amount = rm.outstanding_amount.saturating_sub(e.amount);
+                if rm.outstanding_amount == 0 && rm.status == InvoiceStatus::Open {
+                    rm.status = InvoiceStatus::Paid;
+                }
+                self.store.upsert(tenant_id, e.invoice_id, rm);
+            }
+            InvoiceEvent::PaymentReversed(e) => {
+                let mut rm = self
+                    .store
+                    .get(tenant_id, &e.invoice_id)
+                    .unwrap_or(InvoiceAgingReadModel {
+                        invoice_id: e.invoice_id,
+                        total_amount: 0,
+                        outstanding_amount: 0,
+                        due_date: None,
+                        status: InvoiceStatus::Open,
+                    });
+                // The reversed amount is owed again.
+                rm.outstanding_amount = rm.outstanding_amount.saturating_add(e.amount);
+                if rm.status == InvoiceStatus::Paid && rm.outstanding_amount > 0 {
+                    rm.status = InvoiceStatus::Open;
+                }
+                self.store.upsert(tenant_id, e.invoice_id, rm);
+            }
         }
 
         self.update_cursor(tenant_id, aggregate_id, seq);
@@ -316,4 +389,110 @@ where
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_model::InMemoryTenantStore;
+    use chrono::{Duration, Utc};
+    use forgeerp_invoicing::{CreditNoteIssued, InvoiceIssued, InvoiceLine, PaymentRegistered};
+    use forgeerp_products::ProductId;
+    use forgeerp_sales::SalesOrderId;
+
+    fn make_envelope(
+        tenant_id: TenantId,
+        aggregate_id: AggregateId,
+        seq: u64,
+        event: InvoiceEvent,
+    ) -> EventEnvelope<JsonValue> {
+        EventEnvelope::new(
+            uuid::Uuid::now_v7(),
+            tenant_id,
+            aggregate_id,
+            "invoicing.invoice".to_string(),
+            seq,
+            serde_json::to_value(&event).unwrap(),
+        )
+    }
+
+    #[test]
+    fn credit_note_reduces_outstanding_and_payment_settles_the_rest() {
+        let proj = InvoiceAgingProjection::new(Arc::new(
+            InMemoryTenantStore::<InvoiceId, InvoiceAgingReadModel>::new(),
+        ));
+
+        let tenant_id = TenantId::new();
+        let invoice_id = InvoiceId::new(AggregateId::new());
+        let sales_order_id = SalesOrderId::new(AggregateId::new());
+        let line = InvoiceLine {
+            line_no: 1,
+            sales_order_id,
+            product_id: ProductId::new(AggregateId::new()),
+            quantity: 2,
+            unit_price: 100,
+        };
+
+        let issued = InvoiceEvent::InvoiceIssued(InvoiceIssued {
+            tenant_id,
+            invoice_id,
+            sales_order_id,
+            lines: vec![line.clone()],
+            payment_terms_days: 30,
+            due_date: Utc::now() + Duration::days(30),
+            total_amount: 200,
+            currency: None,
+            occurred_at: Utc::now(),
+        });
+        proj.apply_envelope(&make_envelope(tenant_id, invoice_id.0, 1, issued)).unwrap();
+
+        let credited = InvoiceEvent::CreditNoteIssued(CreditNoteIssued {
+            tenant_id,
+            invoice_id,
+            lines: vec![InvoiceLine { quantity: 1, ..line }],
+            amount: 100,
+            new_credited_total: 100,
+            reason: None,
+            currency: None,
+            occurred_at: Utc::now(),
+        });
+        proj.apply_envelope(&make_envelope(tenant_id, invoice_id.0, 2, credited)).unwrap();
+
+        let rm = proj.get(tenant_id, &invoice_id).unwrap();
+        assert_eq!(rm.total_amount, 200);
+        assert_eq!(rm.outstanding_amount, 100);
+        assert_eq!(rm.status, InvoiceStatus::Open);
+
+        let payment = InvoiceEvent::PaymentRegistered(PaymentRegistered {
+            tenant_id,
+            invoice_id,
+            payment_id: None,
+            amount: 100,
+            new_total_paid: 100,
+            currency: None,
+            occurred_at: Utc::now(),
+        });
+        proj.apply_envelope(&make_envelope(tenant_id, invoice_id.0, 3, payment)).unwrap();
+
+        let rm = proj.get(tenant_id, &invoice_id).unwrap();
+        assert_eq!(rm.outstanding_amount, 0);
+        assert_eq!(rm.status, InvoiceStatus::Paid);
+    }
+
+    #[test]
+    fn buckets_by_days_past_due_date() {
+        let issued_at = Utc::now();
+        let rm = InvoiceAgingReadModel {
+            invoice_id: InvoiceId::new(AggregateId::new()),
+            total_amount: 100,
+            outstanding_amount: 100,
+            // 0-day terms: due on issue.
+            due_date: Some(issued_at),
+            status: InvoiceStatus::Open,
+        };
 
+        assert_eq!(rm.days_past_due(issued_at - Duration::days(5)), 0);
+        assert_eq!(rm.bucket(issued_at), AgingBucket::Current);
+        assert_eq!(rm.bucket(issued_at + Duration::days(1)), AgingBucket::Days1To30);
+        assert_eq!(rm.bucket(issued_at + Duration::days(45)), AgingBucket::Days31To60);
+        assert_eq!(rm.bucket(issued_at + Duration::days(91)), AgingBucket::Over90);
+    }
+}
@@ -7,10 +7,14 @@
 //! - **Idempotent**: Safe for at-least-once delivery
 
 pub mod cursor_store;
+pub mod dead_letter;
+pub mod golden;
 pub mod replay;
+pub mod versioned;
 
 // Domain projections
 pub mod inventory_stock;
+pub mod inventory_movements;
 pub mod parties;
 pub mod invoicing;
 pub mod accounting;
@@ -19,19 +23,42 @@ pub mod sales_orders;
 pub mod purchasing;
 pub mod invoices;
 pub mod users;
+pub mod tenant_settings;
 
 // ERP read models
 pub mod customer_balances;
 pub mod inventory_valuation;
 pub mod open_invoices;
+pub mod supplier_performance;
+pub mod trial_balance;
 
-pub use cursor_store::{PostgresCursorStore, ProjectionCursorStore};
-pub use replay::{ReplayError, ReplayHandle, ReplayProgress, ReplayPhase, ApplyEnvelopeFn, ClearTenantFn};
+pub use cursor_store::{
+    catch_up, InMemoryCursorStore, PostgresCursorStore, ProjectionCatchUpError, ProjectionCursorStore,
+    SubscriberPositions,
+};
+pub use dead_letter::{
+    replay_dead_letter, InMemoryProjectionDeadLetters, ProjectionApplyFn, ProjectionDeadLetter, ProjectionDeadLetterError,
+    ProjectionDeadLetterStore,
+};
+pub use replay::{
+    ReplayError, ReplayHandle, ReplayProgress, ReplayPhase, ApplyEnvelopeFn, ClearTenantFn, StreamingEvents,
+    STREAM_PAGE_SIZE,
+};
+pub use versioned::{
+    snapshot_store, ProjectionComparison, ProjectionDivergence, ProjectionVersion, ProjectionVersionError,
+    ProjectionVersionRegistry, ProjectionVersions, ProjectionVersionsInfo, SnapshotFn,
+};
 
 // Re-export ERP read models
 pub use customer_balances::{CustomerBalance, CustomerBalancesProjection, CustomerBalanceProjectionError};
+pub use inventory_movements::{InventoryMovement, InventoryMovementError, InventoryMovementHistory, InventoryMovementProjection};
 pub use inventory_valuation::{InventoryValuation, InventoryValuationProjection, InventoryValuationSummary, InventoryValuationError};
 pub use open_invoices::{OpenInvoice, OpenInvoicesProjection, OpenInvoicesSummary, OpenInvoicesProjectionError};
+pub use supplier_performance::{
+    PerformanceWindow, SupplierOrderRecord, SupplierPerformance, SupplierPerformanceError, SupplierPerformanceProjection,
+};
+pub use trial_balance::{KindTotals, TrialBalance, TrialBalanceError, TrialBalanceProjection};
 pub use users::{default_role_permissions, EffectivePermissions, UserReadModel, UsersProjection};
+pub use tenant_settings::{SettingHistoryEntry, TenantSettingsProjection};
 
 
@@ -277,6 +277,8 @@ where
             InvoiceEvent::InvoiceIssued(e) => (e.tenant_id, e.invoice_id),
             InvoiceEvent::PaymentRegistered(e) => (e.tenant_id, e.invoice_id),
             InvoiceEvent::InvoiceVoided(e) => (e.tenant_id, e.invoice_id),
+            InvoiceEvent::CreditNoteIssued(e) => (e.tenant_id, e.invoice_id),
+            InvoiceEvent::PaymentReversed(e) => (e.tenant_id, e.invoice_id),
         };
 
         if event_tenant != tenant_id {
@@ -312,10 +314,11 @@ where
             InvoiceEvent::PaymentRegistered(e) => {
                 if let Some(mut inv) = self.store.get(tenant_id, &e.invoice_id) {
                     inv.amount_paid = e.new_total_paid;
-                    inv.outstanding_amount = inv.total_amount.saturating_sub(e.new_total_paid);
+                    // Outstanding may already be reduced by credit notes.
+                    inv.outstanding_amount = inv.outstanding_amount.saturating_sub(e.amount);
                     
                     // If fully paid, remove from open invoices
-                    if e.new_total_paid >= inv.total_amount {
+                    if inv.outstanding_amount == 0 {
                         // Remove from store by clearing the entry
                         // Note: TenantStore doesn't have a delete method, so we use a marker pattern
                         // In production, you'd add a delete method to TenantStore
@@ -337,6 +340,22 @@ where
                     self.store.upsert(tenant_id, invoice_id, inv);
                 }
             }
+            InvoiceEvent::CreditNoteIssued(e) => {
+                if let Some(mut inv) = self.store.get(tenant_id, &e.invoice_id) {
+                    inv.outstanding_amount = inv.outstanding_amount.saturating_sub(e.amount);
+                    inv.refresh_calculated_fields(now);
+                    self.store.upsert(tenant_id, e.invoice_id, inv);
+                }
+            }
+            InvoiceEvent::PaymentReversed(e) => {
+                // Re-open: the reversed amount is outstanding again.
+                if let Some(mut inv) = self.store.get(tenant_id, &e.invoice_id) {
+                    inv.amount_paid = e.new_total_paid;
+                    inv.outstanding_amount = inv.outstanding_amount.saturating_add(e.amount);
+                    inv.refresh_calculated_fields(now);
+                    self.store.upsert(tenant_id, e.invoice_id, inv);
+                }
+            }
         }
 
         self.update_cursor(tenant_id, aggregate_id, seq);
@@ -381,7 +400,7 @@ mod tests {
     use super::*;
     use crate::read_model::InMemoryTenantStore;
     use forgeerp_core::AggregateId;
-    use forgeerp_invoicing::{InvoiceIssued, PaymentRegistered, InvoiceVoided};
+    use forgeerp_invoicing::{InvoiceIssued, PaymentId, PaymentRegistered, PaymentReversed, InvoiceVoided};
     use forgeerp_products::ProductId;
     use chrono::{Utc, Duration};
 
@@ -421,8 +440,10 @@ mod tests {
                 quantity: 2,
                 unit_price: 100,
             }],
+            payment_terms_days: 30,
             due_date: Utc::now() + Duration::days(30),
             total_amount: 200,
+            currency: None,
             occurred_at: Utc::now(),
         });
 
@@ -454,8 +475,10 @@ mod tests {
                 quantity: 2,
                 unit_price: 100,
             }],
+            payment_terms_days: 30,
             due_date: Utc::now() + Duration::days(30),
             total_amount: 200,
+            currency: None,
             occurred_at: Utc::now(),
         });
         proj.apply_envelope(&make_envelope(tenant_id, invoice_id.0, 1, issued)).unwrap();
@@ -463,8 +486,10 @@ mod tests {
         let payment = InvoiceEvent::PaymentRegistered(PaymentRegistered {
             tenant_id,
             invoice_id,
+            payment_id: None,
             amount: 50,
             new_total_paid: 50,
+            currency: None,
             occurred_at: Utc::now(),
         });
         proj.apply_envelope(&make_envelope(tenant_id, invoice_id.0, 2, payment)).unwrap();
@@ -494,8 +519,10 @@ mod tests {
                 quantity: 2,
                 unit_price: 100,
             }],
+            payment_terms_days: 30,
             due_date: Utc::now() + Duration::days(30),
             total_amount: 200,
+            currency: None,
             occurred_at: Utc::now(),
         });
         proj.apply_envelope(&make_envelope(tenant_id, invoice_id.0, 1, issued)).unwrap();
@@ -503,8 +530,10 @@ mod tests {
         let payment = InvoiceEvent::PaymentRegistered(PaymentRegistered {
             tenant_id,
             invoice_id,
+            payment_id: None,
             amount: 200,
             new_total_paid: 200,
+            currency: None,
             occurred_at: Utc::now(),
         });
         proj.apply_envelope(&make_envelope(tenant_id, invoice_id.0, 2, payment)).unwrap();
@@ -513,6 +542,63 @@ mod tests {
         assert_eq!(inv.outstanding_amount, 0);
     }
 
+    #[test]
+    fn payment_reversal_reopens_invoice() {
+        let store = Arc::new(InMemoryTenantStore::<InvoiceId, OpenInvoice>::new());
+        let proj = OpenInvoicesProjection::new(store.clone());
+
+        let tenant_id = TenantId::new();
+        let invoice_id = InvoiceId::new(AggregateId::new());
+        let sales_order_id = SalesOrderId::new(AggregateId::new());
+        let payment_id = PaymentId::new(AggregateId::new());
+
+        let issued = InvoiceEvent::InvoiceIssued(InvoiceIssued {
+            tenant_id,
+            invoice_id,
+            sales_order_id,
+            lines: vec![InvoiceLine {
+                line_no: 1,
+                sales_order_id,
+                product_id: ProductId::new(AggregateId::new()),
+                quantity: 2,
+                unit_price: 100,
+            }],
+            payment_terms_days: 30,
+            due_date: Utc::now() + Duration::days(30),
+            total_amount: 200,
+            currency: None,
+            occurred_at: Utc::now(),
+        });
+        proj.apply_envelope(&make_envelope(tenant_id, invoice_id.0, 1, issued)).unwrap();
+
+        let payment = InvoiceEvent::PaymentRegistered(PaymentRegistered {
+            tenant_id,
+            invoice_id,
+            payment_id: Some(payment_id),
+            amount: 200,
+            new_total_paid: 200,
+            currency: None,
+            occurred_at: Utc::now(),
+        });
+        proj.apply_envelope(&make_envelope(tenant_id, invoice_id.0, 2, payment)).unwrap();
+        assert_eq!(proj.get(tenant_id, &invoice_id).unwrap().outstanding_amount, 0);
+
+        let reversed = InvoiceEvent::PaymentReversed(PaymentReversed {
+            tenant_id,
+            invoice_id,
+            payment_id,
+            amount: 200,
+            new_total_paid: 0,
+            reason: Some("Check bounced".to_string()),
+            occurred_at: Utc::now(),
+        });
+        proj.apply_envelope(&make_envelope(tenant_id, invoice_id.0, 3, reversed)).unwrap();
+
+        let inv = proj.get(tenant_id, &invoice_id).unwrap();
+        assert_eq!(inv.amount_paid, 0);
+        assert_eq!(inv.outstanding_amount, 200);
+    }
+
     #[test]
     fn void_zeroes_outstanding() {
         let store = Arc::new(InMemoryTenantStore::<InvoiceId, OpenInvoice>::new());
@@ -533,8 +619,10 @@ mod tests {
                 quantity: 2,
                 unit_price: 100,
             }],
+            payment_terms_days: 30,
             due_date: Utc::now() + Duration::days(30),
             total_amount: 200,
+            currency: None,
             occurred_at: Utc::now(),
         });
         proj.apply_envelope(&make_envelope(tenant_id, invoice_id.0, 1, issued)).unwrap();
@@ -581,8 +669,10 @@ mod tests {
                     quantity: 1,
                     unit_price: 100,
                 }],
+                payment_terms_days: 30,
                 due_date,
                 total_amount: 100,
+                currency: None,
                 occurred_at: Utc::now(),
             });
             proj.apply_envelope(&make_envelope(tenant_id, invoice_id.0, 1, issued)).unwrap();
@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
@@ -6,9 +7,9 @@ use thiserror::Error;
 
 use forgeerp_core::{AggregateId, TenantId};
 use forgeerp_events::EventEnvelope;
-use forgeerp_parties::{PartyEvent, PartyId, PartyKind, PartyStatus};
+use forgeerp_parties::{ContactInfo, PartyEvent, PartyId, PartyKind, PartyStatus};
 
-use crate::read_model::TenantStore;
+use crate::read_model::{ListPage, PageStart, TenantStore};
 use crate::projections::cursor_store::ProjectionCursorStore;
 
 /// Queryable party read model: basic directory for customers and suppliers.
@@ -19,7 +20,20 @@ pub struct PartyReadModel {
     pub name: String,
     pub email: Option<String>,
     pub phone: Option<String>,
+    pub address: Option<String>,
     pub status: PartyStatus,
+    /// Stream version after the last applied event (send it back as `expected_version` on edits).
+    pub version: u64,
+}
+
+impl PartyReadModel {
+    pub fn contact(&self) -> ContactInfo {
+        ContactInfo {
+            email: self.email.clone(),
+            phone: self.phone.clone(),
+            address: self.address.clone(),
+        }
+    }
 }
 
 /// Tenant+aggregate cursor to support at-least-once delivery (idempotent projection).
@@ -187,6 +201,19 @@ where
         self.store.list(tenant_id)
     }
 
+    /// One page of the parties of `kind` that `keep` accepts, ordered by `order`.
+    pub fn list_page(
+        &self,
+        tenant_id: TenantId,
+        kind: PartyKind,
+        keep: &dyn Fn(&PartyReadModel) -> bool,
+        order: &dyn Fn(&PartyReadModel, &PartyReadModel) -> Ordering,
+        start: PageStart<'_, PartyReadModel>,
+        limit: usize,
+    ) -> ListPage<PartyReadModel> {
+        self.store.list_page(tenant_id, &|rm| rm.kind == kind && keep(rm), order, start, limit)
+    }
+
     /// Simple in-memory search by name substring (case-insensitive) for a tenant.
     pub fn search_by_name(&self, tenant_id: TenantId, query: &str) -> Vec<PartyReadModel> {
         let q = query.to_lowercase();
@@ -268,7 +295,9 @@ where
                         name: e.name,
                         email: e.contact.email,
                         phone: e.contact.phone,
+                        address: e.contact.address,
                         status: PartyStatus::Active,
+                        version: seq,
                     },
                 );
             }
@@ -279,11 +308,15 @@ where
                     name: String::new(),
                     email: None,
                     phone: None,
+                    address: None,
                     status: PartyStatus::Active,
+                    version: 0,
                 });
                 rm.name = e.name;
                 rm.email = e.contact.email;
                 rm.phone = e.contact.phone;
+                rm.address = e.contact.address;
+                rm.version = seq;
                 self.store.upsert(tenant_id, e.party_id, rm);
             }
             PartyEvent::PartySuspended(e) => {
@@ -293,9 +326,12 @@ where
                     name: String::new(),
                     email: None,
                     phone: None,
+                    address: None,
                     status: PartyStatus::Active,
+                    version: 0,
                 });
                 rm.status = PartyStatus::Suspended;
+                rm.version = seq;
                 self.store.upsert(tenant_id, e.party_id, rm);
             }
         }
@@ -1,6 +1,8 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
+use chrono::{DateTime, Utc};
 use serde_json::Value as JsonValue;
 use thiserror::Error;
 
@@ -10,7 +12,7 @@ use forgeerp_products::{ProductEvent, ProductId, ProductStatus};
 use forgeerp_products::product::PricingMetadata;
 
 use crate::projections::cursor_store::ProjectionCursorStore;
-use crate::read_model::TenantStore;
+use crate::read_model::{ListPage, PageStart, TenantStore};
 
 /// Queryable product read model (catalog).
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -20,6 +22,17 @@ pub struct ProductReadModel {
     pub name: String,
     pub status: ProductStatus,
     pub pricing: PricingMetadata,
+    /// Inventory item that holds this product's stock, if it is stocked.
+    pub inventory_item_id: Option<AggregateId>,
+    /// Set once the product is deleted; the row is then only visible as a tombstone.
+    pub tombstone: Option<ProductTombstone>,
+}
+
+/// Audit record kept for a deleted product.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProductTombstone {
+    pub reason: Option<String>,
+    pub deleted_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -145,12 +158,39 @@ where
         }
     }
 
+    /// Catalog entry (deleted products are not returned).
     pub fn get(&self, tenant_id: TenantId, product_id: &ProductId) -> Option<ProductReadModel> {
-        self.store.get(tenant_id, product_id)
+        self.store.get(tenant_id, product_id).filter(|rm| rm.tombstone.is_none())
     }
 
+    /// Catalog listing (deleted products are not returned).
     pub fn list(&self, tenant_id: TenantId) -> Vec<ProductReadModel> {
-        self.store.list(tenant_id)
+        self.store
+            .list(tenant_id)
+            .into_iter()
+            .filter(|rm| rm.tombstone.is_none())
+            .collect()
+    }
+
+    /// One page of the live products that `keep` accepts, ordered by `order`.
+    pub fn list_page(
+        &self,
+        tenant_id: TenantId,
+        keep: &dyn Fn(&ProductReadModel) -> bool,
+        order: &dyn Fn(&ProductReadModel, &ProductReadModel) -> Ordering,
+        start: PageStart<'_, ProductReadModel>,
+        limit: usize,
+    ) -> ListPage<ProductReadModel> {
+        self.store.list_page(tenant_id, &|rm| rm.tombstone.is_none() && keep(rm), order, start, limit)
+    }
+
+    /// Deleted products (admin audit view).
+    pub fn list_tombstones(&self, tenant_id: TenantId) -> Vec<ProductReadModel> {
+        self.store
+            .list(tenant_id)
+            .into_iter()
+            .filter(|rm| rm.tombstone.is_some())
+            .collect()
     }
 
     pub fn apply_envelope(
@@ -183,6 +223,8 @@ where
             ProductEvent::ProductCreated(e) => (e.tenant_id, e.product_id),
             ProductEvent::ProductActivated(e) => (e.tenant_id, e.product_id),
             ProductEvent::ProductArchived(e) => (e.tenant_id, e.product_id),
+            ProductEvent::ProductPricingUpdated(e) => (e.tenant_id, e.product_id),
+            ProductEvent::ProductDeleted(e) => (e.tenant_id, e.product_id),
         };
 
         if event_tenant != tenant_id {
@@ -207,6 +249,8 @@ where
                         name: e.name,
                         status: ProductStatus::Draft,
                         pricing: e.pricing,
+                        inventory_item_id: e.inventory_item_id,
+                        tombstone: None,
                     },
                 );
             }
@@ -217,6 +261,8 @@ where
                     name: String::new(),
                     status: ProductStatus::Draft,
                     pricing: PricingMetadata::default(),
+                    inventory_item_id: None,
+                    tombstone: None,
                 });
                 rm.status = ProductStatus::Active;
                 self.store.upsert(tenant_id, e.product_id, rm);
@@ -228,10 +274,35 @@ where
                     name: String::new(),
                     status: ProductStatus::Draft,
                     pricing: PricingMetadata::default(),
+                    inventory_item_id: None,
+                    tombstone: None,
                 });
                 rm.status = ProductStatus::Archived;
                 self.store.upsert(tenant_id, e.product_id, rm);
             }
+            ProductEvent::ProductPricingUpdated(e) => {
+                if let Some(mut rm) = self.store.get(tenant_id, &e.product_id) {
+                    rm.pricing = e.pricing;
+                    self.store.upsert(tenant_id, e.product_id, rm);
+                }
+            }
+            ProductEvent::ProductDeleted(e) => {
+                let mut rm = self.store.get(tenant_id, &e.product_id).unwrap_or(ProductReadModel {
+                    product_id: e.product_id,
+                    sku: String::new(),
+                    name: String::new(),
+                    status: ProductStatus::Draft,
+                    pricing: PricingMetadata::default(),
+                    inventory_item_id: None,
+                    tombstone: None,
+                });
+                rm.status = ProductStatus::Deleted;
+                rm.tombstone = Some(ProductTombstone {
+                    reason: e.reason,
+                    deleted_at: e.occurred_at,
+                });
+                self.store.upsert(tenant_id, e.product_id, rm);
+            }
         }
 
         self.update_cursor(tenant_id, aggregate_id, seq);
@@ -270,3 +341,110 @@ where
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use forgeerp_products::{ProductCreated, ProductDeleted, ProductPricingUpdated};
+
+    use super::*;
+    use crate::read_model::InMemoryTenantStore;
+
+    fn envelope(tenant_id: TenantId, product_id: ProductId, seq: u64, event: ProductEvent) -> EventEnvelope<JsonValue> {
+        EventEnvelope::new(
+            uuid::Uuid::now_v7(),
+            tenant_id,
+            product_id.0,
+            "products.product",
+            seq,
+            serde_json::to_value(event).unwrap(),
+        )
+    }
+
+    #[test]
+    fn pricing_update_replaces_catalog_price() {
+        let projection = ProductCatalogProjection::new(Arc::new(InMemoryTenantStore::new()));
+        let tenant_id = TenantId::new();
+        let product_id = ProductId::new(AggregateId::new());
+        let pricing = |base_price| PricingMetadata {
+            base_price: Some(base_price),
+            currency: Some("USD".to_string()),
+        };
+
+        for (seq, event) in [
+            ProductEvent::ProductCreated(ProductCreated {
+                tenant_id,
+                product_id,
+                sku: "W-1".to_string(),
+                name: "Widget".to_string(),
+                pricing: pricing(1_000),
+                inventory_item_id: None,
+                occurred_at: Utc::now(),
+            }),
+            ProductEvent::ProductPricingUpdated(ProductPricingUpdated {
+                tenant_id,
+                product_id,
+                pricing: pricing(1_200),
+                occurred_at: Utc::now(),
+            }),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            projection.apply_envelope(&envelope(tenant_id, product_id, seq as u64 + 1, event)).unwrap();
+        }
+
+        let rm = projection.get(tenant_id, &product_id).unwrap();
+        assert_eq!((rm.name.as_str(), rm.pricing), ("Widget", pricing(1_200)));
+    }
+
+    #[test]
+    fn deleted_product_leaves_catalog_but_keeps_tombstone() {
+        let projection = ProductCatalogProjection::new(Arc::new(InMemoryTenantStore::new()));
+        let tenant_id = TenantId::new();
+        let product_id = ProductId::new(AggregateId::new());
+
+        projection
+            .apply_envelope(&envelope(
+                tenant_id,
+                product_id,
+                1,
+                ProductEvent::ProductCreated(ProductCreated {
+                    tenant_id,
+                    product_id,
+                    sku: "TYPO-1".to_string(),
+                    name: "Typo".to_string(),
+                    pricing: PricingMetadata::default(),
+                    inventory_item_id: None,
+                    occurred_at: Utc::now(),
+                }),
+            ))
+            .unwrap();
+        assert_eq!(projection.list(tenant_id).len(), 1);
+
+        projection
+            .apply_envelope(&envelope(
+                tenant_id,
+                product_id,
+                2,
+                ProductEvent::ProductDeleted(ProductDeleted {
+                    tenant_id,
+                    product_id,
+                    reason: Some("created by mistake".to_string()),
+                    occurred_at: Utc::now(),
+                }),
+            ))
+            .unwrap();
+
+        assert!(projection.list(tenant_id).is_empty());
+        assert!(projection.get(tenant_id, &product_id).is_none());
+        let tombstones = projection.list_tombstones(tenant_id);
+        assert_eq!(tombstones.len(), 1);
+        assert_eq!(tombstones[0].sku, "TYPO-1");
+        assert_eq!(tombstones[0].status, ProductStatus::Deleted);
+        assert_eq!(
+            tombstones[0].tombstone.as_ref().unwrap().reason.as_deref(),
+            Some("created by mistake")
+        );
+    }
+}
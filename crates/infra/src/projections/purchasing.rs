@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock};
 
 use serde_json::Value as JsonValue;
@@ -8,11 +9,12 @@ use forgeerp_core::{AggregateId, TenantId};
 use forgeerp_events::EventEnvelope;
 use forgeerp_parties::PartyId;
 use forgeerp_purchasing::{
-    LineItem, PurchaseOrderEvent, PurchaseOrderId, PurchaseOrderStatus,
+    order::landed_costs, AllocationBasis, LineItem, LineLandedCost, PurchaseOrderEvent, PurchaseOrderId,
+    PurchaseOrderStatus,
 };
 
 use crate::projections::cursor_store::ProjectionCursorStore;
-use crate::read_model::TenantStore;
+use crate::read_model::{ListPage, PageStart, TenantStore};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PurchaseOrderReadModel {
@@ -20,6 +22,46 @@ pub struct PurchaseOrderReadModel {
     pub supplier_id: PartyId,
     pub status: PurchaseOrderStatus,
     pub lines: Vec<LineItem>,
+    /// Agreed unit cost per line number, for priced lines.
+    pub unit_costs: BTreeMap<u32, u64>,
+    /// Order-level freight and discount (minor units) and how they are spread.
+    pub freight: u64,
+    pub discount: u64,
+    pub allocation_basis: AllocationBasis,
+    /// Landed cost per line (value plus allocated freight, less allocated discount).
+    pub landed_costs: Vec<LineLandedCost>,
+}
+
+impl PurchaseOrderReadModel {
+    /// Empty draft order.
+    pub fn new(order_id: PurchaseOrderId, supplier_id: PartyId) -> Self {
+        Self {
+            order_id,
+            supplier_id,
+            status: PurchaseOrderStatus::Draft,
+            lines: vec![],
+            unit_costs: BTreeMap::new(),
+            freight: 0,
+            discount: 0,
+            allocation_basis: AllocationBasis::default(),
+            landed_costs: vec![],
+        }
+    }
+
+    fn refresh_landed_costs(&mut self) {
+        self.landed_costs = landed_costs(
+            &self.lines,
+            &self.unit_costs,
+            self.freight,
+            self.discount,
+            self.allocation_basis,
+        );
+    }
+
+    /// Landed cost of one line, if it exists.
+    pub fn landed_cost(&self, line_no: u32) -> Option<&LineLandedCost> {
+        self.landed_costs.iter().find(|l| l.line_no == line_no)
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -149,6 +191,18 @@ where
         self.store.list(tenant_id)
     }
 
+    /// One page of the tenant's purchase orders that `keep` accepts, ordered by `order`.
+    pub fn list_page(
+        &self,
+        tenant_id: TenantId,
+        keep: &dyn Fn(&PurchaseOrderReadModel) -> bool,
+        order: &dyn Fn(&PurchaseOrderReadModel, &PurchaseOrderReadModel) -> Ordering,
+        start: PageStart<'_, PurchaseOrderReadModel>,
+        limit: usize,
+    ) -> ListPage<PurchaseOrderReadModel> {
+        self.store.list_page(tenant_id, keep, order, start, limit)
+    }
+
     pub fn apply_envelope(
         &self,
         envelope: &EventEnvelope<JsonValue>,
@@ -195,44 +249,33 @@ where
 
         match ev {
             PurchaseOrderEvent::PurchaseOrderCreated(e) => {
-                self.store.upsert(
-                    tenant_id,
-                    e.order_id,
-                    PurchaseOrderReadModel {
-                        order_id: e.order_id,
-                        supplier_id: e.supplier_id,
-                        status: PurchaseOrderStatus::Draft,
-                        lines: vec![],
-                    },
-                );
+                let mut rm = PurchaseOrderReadModel::new(e.order_id, e.supplier_id);
+                rm.freight = e.freight;
+                rm.discount = e.discount;
+                rm.allocation_basis = e.allocation_basis;
+                self.store.upsert(tenant_id, e.order_id, rm);
             }
             PurchaseOrderEvent::PurchaseOrderLineAdded(e) => {
                 let mut rm = self
                     .store
                     .get(tenant_id, &e.order_id)
-                    .unwrap_or(PurchaseOrderReadModel {
-                        order_id: e.order_id,
-                        supplier_id: PartyId::new(AggregateId::new()),
-                        status: PurchaseOrderStatus::Draft,
-                        lines: vec![],
-                    });
+                    .unwrap_or_else(|| PurchaseOrderReadModel::new(e.order_id, PartyId::new(AggregateId::new())));
                 rm.lines.push(LineItem {
                     line_no: e.line_no,
                     product_id: e.product_id,
                     quantity: e.quantity,
                 });
+                if let Some(cost) = e.unit_cost {
+                    rm.unit_costs.insert(e.line_no, cost);
+                }
+                rm.refresh_landed_costs();
                 self.store.upsert(tenant_id, e.order_id, rm);
             }
             PurchaseOrderEvent::PurchaseOrderApproved(e) => {
                 let mut rm = self
                     .store
                     .get(tenant_id, &e.order_id)
-                    .unwrap_or(PurchaseOrderReadModel {
-                        order_id: e.order_id,
-                        supplier_id: PartyId::new(AggregateId::new()),
-                        status: PurchaseOrderStatus::Draft,
-                        lines: vec![],
-                    });
+                    .unwrap_or_else(|| PurchaseOrderReadModel::new(e.order_id, PartyId::new(AggregateId::new())));
                 rm.status = PurchaseOrderStatus::Approved;
                 self.store.upsert(tenant_id, e.order_id, rm);
             }
@@ -240,14 +283,10 @@ where
                 let mut rm = self
                     .store
                     .get(tenant_id, &e.order_id)
-                    .unwrap_or(PurchaseOrderReadModel {
-                        order_id: e.order_id,
-                        supplier_id: e.supplier_id,
-                        status: PurchaseOrderStatus::Draft,
-                        lines: vec![],
-                    });
+                    .unwrap_or_else(|| PurchaseOrderReadModel::new(e.order_id, e.supplier_id));
                 rm.status = PurchaseOrderStatus::Received;
                 rm.lines = e.lines;
+                rm.refresh_landed_costs();
                 self.store.upsert(tenant_id, e.order_id, rm);
             }
         }
@@ -287,4 +326,83 @@ where
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use forgeerp_inventory::{DEFAULT_LOCATION, InventoryEvent, InventoryItemId, ItemCreated, StockAdjusted};
+    use forgeerp_products::ProductId;
+    use forgeerp_purchasing::{PurchaseOrderCreated, PurchaseOrderLineAdded};
 
+    use crate::projections::inventory_valuation::{InventoryValuation, InventoryValuationProjection};
+    use crate::read_model::InMemoryTenantStore;
+
+    fn envelope(tenant_id: TenantId, aggregate_id: AggregateId, aggregate_type: &str, seq: u64, payload: JsonValue) -> EventEnvelope<JsonValue> {
+        EventEnvelope::new(uuid::Uuid::now_v7(), tenant_id, aggregate_id, aggregate_type.to_string(), seq, payload)
+    }
+
+    #[test]
+    fn landed_costs_are_surfaced_per_line_and_feed_valuation() {
+        let projection = PurchaseOrdersProjection::new(Arc::new(InMemoryTenantStore::new()));
+        let tenant_id = TenantId::new();
+        let order_id = PurchaseOrderId::new(AggregateId::new());
+        let po = |seq, event: PurchaseOrderEvent| {
+            projection
+                .apply_envelope(&envelope(tenant_id, order_id.0, "purchasing.order", seq, serde_json::to_value(event).unwrap()))
+                .unwrap()
+        };
+
+        po(
+            1,
+            PurchaseOrderEvent::PurchaseOrderCreated(PurchaseOrderCreated {
+                tenant_id,
+                order_id,
+                supplier_id: PartyId::new(AggregateId::new()),
+                expected_at: None,
+                freight: 500,
+                discount: 100,
+                allocation_basis: AllocationBasis::Quantity,
+                occurred_at: Utc::now(),
+            }),
+        );
+        for (line_no, quantity) in [(1, 2), (2, 4), (3, 1)] {
+            po(
+                u64::from(line_no) + 1,
+                PurchaseOrderEvent::PurchaseOrderLineAdded(PurchaseOrderLineAdded {
+                    tenant_id,
+                    order_id,
+                    line_no,
+                    product_id: ProductId::new(AggregateId::new()),
+                    quantity,
+                    unit_cost: Some(1_000),
+                    occurred_at: Utc::now(),
+                }),
+            );
+        }
+
+        let rm = projection.get(tenant_id, &order_id).unwrap();
+        // 500 by 2:4:1 → 142.9, 285.7, 71.4; the two leftover units go to lines 1 and 2.
+        assert_eq!(rm.landed_costs.iter().map(|l| l.freight).collect::<Vec<_>>(), [143, 286, 71]);
+        assert_eq!(rm.landed_costs.iter().map(|l| l.freight).sum::<u64>(), 500);
+        assert_eq!(rm.landed_costs.iter().map(|l| l.discount).sum::<u64>(), 100);
+        let line = *rm.landed_cost(2).unwrap();
+        assert_eq!(line.landed_cost, 4_000 + 286 - 57);
+
+        let valuation = InventoryValuationProjection::new(Arc::new(InMemoryTenantStore::<InventoryItemId, InventoryValuation>::new()));
+        let item_id = InventoryItemId::new(AggregateId::new());
+        for (seq, event) in [
+            InventoryEvent::ItemCreated(ItemCreated { tenant_id, item_id, name: "Widget".to_string(), occurred_at: Utc::now() }),
+            InventoryEvent::StockAdjusted(StockAdjusted { tenant_id, item_id, location_id: DEFAULT_LOCATION.to_string(), delta: 4, occurred_at: Utc::now() }),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            valuation
+                .apply_envelope(&envelope(tenant_id, item_id.0, "inventory.item", seq as u64 + 1, serde_json::to_value(event).unwrap()))
+                .unwrap();
+        }
+        valuation.apply_landed_cost(tenant_id, item_id, &line);
+        // 4229 / 4 rounds to 1057 per unit.
+        assert_eq!(valuation.get(tenant_id, &item_id).unwrap().unit_cost, Some(1_057));
+    }
+}
@@ -2,19 +2,29 @@
 //!
 //! This module provides utilities for replaying events through projections,
 //! supporting rebuilds, dry-runs, and progress reporting.
+//!
+//! Replays stream from the event store ([`StreamingEvents`]): only aggregate ids are
+//! collected up front, and events are then applied one bounded page at a time in
+//! (aggregate, sequence) order, so memory does not grow with the tenant's history.
+//!
+//! [`rebuild_projection`] reads the tenant's events in the store's global order instead
+//! ([`EventStore::read_all`]), without discovering aggregates first.
 
+use std::collections::HashSet;
 use std::sync::{
     atomic::{AtomicBool, AtomicU64, Ordering},
     Arc,
 };
 
-use forgeerp_core::TenantId;
+use forgeerp_core::{AggregateId, TenantId};
 use forgeerp_events::EventEnvelope;
 use serde_json::Value as JsonValue;
 use thiserror::Error;
 use tokio::sync::RwLock;
 
-use crate::event_store::{EventQuery, EventFilter, Pagination, StoredEvent, EventStoreError};
+use crate::event_store::{
+    EventFilter, EventQuery, EventStore, EventStoreError, GlobalPosition, Pagination, StoredEvent, READ_ALL_PAGE_SIZE,
+};
 
 /// Error type for projection replay operations.
 #[derive(Debug, Error)]
@@ -106,6 +116,117 @@ impl ReplayHandle {
     }
 }
 
+/// Default number of events (and aggregate ids) fetched per page.
+pub const STREAM_PAGE_SIZE: u32 = 500;
+
+/// Bounded-memory event source for rebuilds.
+///
+/// Opening collects the ids of the tenant's aggregates of the requested types; pages
+/// are then read with [`EventQuery::get_events_for_aggregates`] for one batch of ids
+/// at a time, ordered by aggregate and sequence number. Each page holds at most
+/// `page_size` events. Events appended during the rebuild may be returned twice;
+/// projections skip them by cursor.
+pub struct StreamingEvents<'a, Q> {
+    query: &'a Q,
+    tenant_id: TenantId,
+    aggregate_ids: Vec<AggregateId>,
+    total_events: u64,
+    page_size: u32,
+    /// Index of the first id in the current batch.
+    batch_start: usize,
+    /// Offset within the current batch's events.
+    offset: u32,
+}
+
+impl<'a, Q> StreamingEvents<'a, Q>
+where
+    Q: EventQuery + Send + Sync,
+{
+    /// Discover the tenant's aggregates of `aggregate_types` (ids only).
+    pub async fn open(
+        query: &'a Q,
+        tenant_id: TenantId,
+        aggregate_types: &[String],
+        page_size: u32,
+    ) -> Result<Self, EventStoreError> {
+        let page_size = page_size.clamp(1, 1000);
+        let mut ids: HashSet<AggregateId> = HashSet::new();
+        let mut total_events = 0;
+
+        for aggregate_type in aggregate_types {
+            let mut offset = 0;
+            loop {
+                let filter = EventFilter {
+                    aggregate_type: Some(aggregate_type.clone()),
+                    ..Default::default()
+                };
+                let result = query
+                    .query_events(tenant_id, filter, Pagination { limit: page_size, offset })
+                    .await?;
+                if offset == 0 {
+                    total_events += result.total;
+                }
+                ids.extend(result.events.iter().map(|e| e.aggregate_id));
+                if !result.has_more || result.events.is_empty() {
+                    break;
+                }
+                offset += page_size;
+            }
+        }
+
+        let mut aggregate_ids: Vec<AggregateId> = ids.into_iter().collect();
+        aggregate_ids.sort_by_key(|id| *id.as_uuid());
+
+        Ok(Self {
+            query,
+            tenant_id,
+            aggregate_ids,
+            total_events,
+            page_size,
+            batch_start: 0,
+            offset: 0,
+        })
+    }
+
+    /// Events matching the aggregate types when the stream was opened.
+    pub fn total_events(&self) -> u64 {
+        self.total_events
+    }
+
+    pub fn aggregate_count(&self) -> usize {
+        self.aggregate_ids.len()
+    }
+
+    /// Next page of events (`None` once every aggregate has been read).
+    pub async fn next_page(&mut self) -> Result<Option<Vec<StoredEvent>>, EventStoreError> {
+        while self.batch_start < self.aggregate_ids.len() {
+            let batch_end = (self.batch_start + self.page_size as usize).min(self.aggregate_ids.len());
+            let result = self
+                .query
+                .get_events_for_aggregates(
+                    self.tenant_id,
+                    &self.aggregate_ids[self.batch_start..batch_end],
+                    Some(Pagination {
+                        limit: self.page_size,
+                        offset: self.offset,
+                    }),
+                )
+                .await?;
+
+            if result.has_more && !result.events.is_empty() {
+                self.offset += result.events.len() as u32;
+            } else {
+                self.batch_start = batch_end;
+                self.offset = 0;
+            }
+            if !result.events.is_empty() {
+                return Ok(Some(result.events));
+            }
+        }
+        Ok(None)
+    }
+}
+
 /// Callback function for applying an event envelope to a projection.
 pub type ApplyEnvelopeFn = Arc<dyn Fn(&EventEnvelope<JsonValue>) -> Result<(), String> + Send + Sync>;
 
@@ -115,9 +236,9 @@ pub type ClearTenantFn = Arc<dyn Fn(TenantId) + Send + Sync>;
 /// Replay a single projection for a tenant.
 ///
 /// This function:
-/// 1. Loads all relevant events from the event store
+/// 1. Discovers the relevant aggregates in the event store
 /// 2. Optionally clears the projection state
-/// 3. Replays events through the projection
+/// 3. Streams events page by page through the projection
 /// 4. Reports progress via the handle
 ///
 /// If `dry_run` is true, events are validated but not written to the read model.
@@ -208,53 +329,19 @@ async fn run_replay<Q>(
 where
     Q: EventQuery + Send + Sync,
 {
-    // Phase 1: Load events
+    // Phase 1: Discover aggregates (ids only; events are streamed later)
     {
         let mut prog = progress.write().await;
         prog.phase = ReplayPhase::Loading;
     }
 
-    let mut all_events: Vec<StoredEvent> = Vec::new();
-    let mut offset = 0u32;
-    const PAGE_SIZE: u32 = 1000;
-
-    loop {
-        if cancellation.load(Ordering::Relaxed) {
-            return Err(ReplayError::Cancelled);
-        }
-
-        let filter = EventFilter {
-            aggregate_type: None, // We'll filter in-memory for multiple types
-            ..Default::default()
-        };
-        let pagination = Pagination::new(Some(PAGE_SIZE), Some(offset));
-
-        // Call query_events directly on Arc<Q> - this should work if Q: EventQuery + Send + Sync
-        let result = {
-            let query_ref: &Q = &*event_query;
-            query_ref.query_events(tenant_id, filter, pagination).await?
-        };
-
-        // Filter by aggregate types
-        let relevant_events: Vec<StoredEvent> = result
-            .events
-            .into_iter()
-            .filter(|e| aggregate_types.contains(&e.aggregate_type))
-            .collect();
-
-        all_events.extend(relevant_events);
-
-        if !result.has_more {
-            break;
-        }
-
-        offset += PAGE_SIZE;
-    }
+    let query_ref: &Q = &event_query;
+    let mut events = StreamingEvents::open(query_ref, tenant_id, &aggregate_types, STREAM_PAGE_SIZE).await?;
 
     // Update progress with total
     {
         let mut prog = progress.write().await;
-        prog.total_events = all_events.len() as u64;
+        prog.total_events = events.total_events();
     }
 
     if cancellation.load(Ordering::Relaxed) {
@@ -275,58 +362,330 @@ where
         return Err(ReplayError::Cancelled);
     }
 
-    // Phase 3: Replay events
+    // Phase 3: Replay events, one page at a time (ordered by aggregate, sequence)
     {
         let mut prog = progress.write().await;
         prog.phase = ReplayPhase::Replaying;
     }
 
-    // Sort events by tenant, aggregate, sequence for deterministic replay
-    all_events.sort_by_key(|e| {
-        (
-            *e.tenant_id.as_uuid().as_bytes(),
-            *e.aggregate_id.as_uuid().as_bytes(),
-            e.sequence_number,
-        )
-    });
+    let mut last_aggregate_id: Option<AggregateId> = None;
+
+    while let Some(page) = events.next_page().await? {
+        for event in &page {
+            if cancellation.load(Ordering::Relaxed) {
+                return Err(ReplayError::Cancelled);
+            }
+
+            // Track aggregate count
+            if Some(event.aggregate_id) != last_aggregate_id {
+                processed_aggregates.fetch_add(1, Ordering::Relaxed);
+                last_aggregate_id = Some(event.aggregate_id);
+            }
 
-    let mut last_aggregate_id: Option<forgeerp_core::AggregateId> = None;
+            // Apply to projection (skip in dry run; the caller can provide a
+            // validation-only function if needed)
+            if !dry_run {
+                apply_envelope(&event.to_envelope()).map_err(ReplayError::Projection)?;
+            }
 
-    for event in &all_events {
-        if cancellation.load(Ordering::Relaxed) {
-            return Err(ReplayError::Cancelled);
+            processed_events.fetch_add(1, Ordering::Relaxed);
         }
 
-        // Track aggregate count
-        if Some(event.aggregate_id) != last_aggregate_id {
-            processed_aggregates.fetch_add(1, Ordering::Relaxed);
-            last_aggregate_id = Some(event.aggregate_id);
+        // Update progress once per page
+        let mut prog = progress.write().await;
+        prog.processed_events = processed_events.load(Ordering::Relaxed);
+        prog.processed_aggregates = processed_aggregates.load(Ordering::Relaxed);
+    }
+
+    Ok(())
+}
+
+/// Rebuild a projection for a tenant from the store's global event order.
+///
+/// Clears the tenant's projection state (read model and cursors, via `clear_tenant`), then
+/// reads the tenant's events with [`EventStore::read_all`] a page at a time and applies
+/// those of `aggregate_types`. Runs on a blocking thread, since the Postgres store blocks
+/// on the runtime to read. `total_events` is the tenant's event count (all types) and
+/// `processed_events` counts the events read so far, applied or not.
+pub fn rebuild_projection<S>(
+    store: Arc<S>,
+    tenant_id: TenantId,
+    aggregate_types: Vec<String>,
+    apply_envelope: ApplyEnvelopeFn,
+    clear_tenant: ClearTenantFn,
+) -> ReplayHandle
+where
+    S: EventStore + Send + Sync + 'static,
+{
+    let progress = Arc::new(RwLock::new(ReplayProgress {
+        total_events: 0,
+        processed_events: 0,
+        processed_aggregates: 0,
+        phase: ReplayPhase::Loading,
+        is_complete: false,
+        error: None,
+    }));
+    let handle = ReplayHandle {
+        progress: progress.clone(),
+        cancellation: Arc::new(AtomicBool::new(false)),
+    };
+
+    let cancellation = handle.cancellation.clone();
+    tokio::task::spawn_blocking(move || {
+        let result = run_rebuild(
+            store.as_ref(),
+            tenant_id,
+            &aggregate_types,
+            &apply_envelope,
+            &clear_tenant,
+            &progress,
+            &cancellation,
+        );
+
+        let mut prog = progress.blocking_write();
+        prog.is_complete = true;
+        match result {
+            Ok(()) => prog.phase = ReplayPhase::Complete,
+            Err(e) => {
+                prog.phase = ReplayPhase::Failed;
+                prog.error = Some(match e {
+                    ReplayError::Cancelled => "Replay cancelled".to_string(),
+                    e => e.to_string(),
+                });
+            }
         }
+    });
 
-        // Convert StoredEvent to EventEnvelope
-        let envelope = event.to_envelope();
-
-        // Apply to projection (skip in dry run)
-        if !dry_run {
-            apply_envelope(&envelope)
-                .map_err(|e| ReplayError::Projection(e))?;
-        } else {
-            // In dry run, just validate that the envelope can be processed
-            // (by attempting to deserialize, but not applying)
-            // The apply_envelope function may include validation logic
-            // For now, we'll skip it in dry run - the caller can provide
-            // a validation-only function if needed
+    handle
+}
+
+fn run_rebuild<S>(
+    store: &S,
+    tenant_id: TenantId,
+    aggregate_types: &[String],
+    apply_envelope: &ApplyEnvelopeFn,
+    clear_tenant: &ClearTenantFn,
+    progress: &RwLock<ReplayProgress>,
+    cancellation: &AtomicBool,
+) -> Result<(), ReplayError>
+where
+    S: EventStore + ?Sized,
+{
+    let total_events = store.tenant_stats(tenant_id)?.event_count;
+    {
+        let mut prog = progress.blocking_write();
+        prog.total_events = total_events;
+        prog.phase = ReplayPhase::Clearing;
+    }
+    if cancellation.load(Ordering::Relaxed) {
+        return Err(ReplayError::Cancelled);
+    }
+    clear_tenant(tenant_id);
+    progress.blocking_write().phase = ReplayPhase::Replaying;
+
+    let mut aggregates: HashSet<AggregateId> = HashSet::new();
+    let mut processed_events = 0;
+    let mut after: Option<GlobalPosition> = None;
+    loop {
+        let page = store.read_all(tenant_id, after, READ_ALL_PAGE_SIZE)?;
+        let Some(last) = page.last().map(|e| e.global_position) else {
+            return Ok(());
+        };
+        for event in page.iter().filter(|e| aggregate_types.contains(&e.aggregate_type)) {
+            if cancellation.load(Ordering::Relaxed) {
+                return Err(ReplayError::Cancelled);
+            }
+            apply_envelope(&event.to_envelope()).map_err(ReplayError::Projection)?;
+            aggregates.insert(event.aggregate_id);
         }
+        processed_events += page.len() as u64;
+        after = Some(last);
 
-        // Update progress
-        let count = processed_events.fetch_add(1, Ordering::Relaxed) + 1;
-        {
-            let mut prog = progress.write().await;
-            prog.processed_events = count;
-            prog.processed_aggregates = processed_aggregates.load(Ordering::Relaxed);
+        let mut prog = progress.blocking_write();
+        prog.processed_events = processed_events;
+        prog.processed_aggregates = aggregates.len() as u64;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use forgeerp_events::InMemoryEventBus;
+    use forgeerp_inventory::{AdjustStock, CreateItem, DEFAULT_LOCATION, InventoryCommand, InventoryItem, InventoryItemId};
+
+    use super::*;
+    use crate::command_dispatcher::CommandDispatcher;
+    use crate::event_store::InMemoryEventStore;
+    use crate::projections::inventory_stock::{InventoryReadModel, InventoryStockProjection};
+    use crate::read_model::InMemoryTenantStore;
+
+    type Projection = InventoryStockProjection<Arc<InMemoryTenantStore<InventoryItemId, InventoryReadModel>>>;
+
+    const ITEMS: usize = 40;
+    const ADJUSTMENTS: usize = 24;
+
+    fn block_on<F: std::future::Future>(f: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(f)
+    }
+
+    fn projection() -> Projection {
+        InventoryStockProjection::new(Arc::new(InMemoryTenantStore::new()))
+    }
+
+    /// `ITEMS` inventory items with `ADJUSTMENTS` stock movements each.
+    fn seed(tenant_id: TenantId) -> Arc<InMemoryEventStore> {
+        let store = Arc::new(InMemoryEventStore::new());
+        let bus: Arc<InMemoryEventBus<EventEnvelope<JsonValue>>> = Arc::new(InMemoryEventBus::new());
+        let dispatcher = CommandDispatcher::new(store.clone(), bus);
+        for n in 0..ITEMS {
+            let item_id = InventoryItemId::new(AggregateId::new());
+            let dispatch = |cmd| {
+                dispatcher
+                    .dispatch(tenant_id, item_id.0, "inventory.item", cmd, |_, id| {
+                        InventoryItem::empty(InventoryItemId::new(id))
+                    })
+                    .unwrap();
+            };
+            dispatch(InventoryCommand::CreateItem(CreateItem {
+                tenant_id,
+                item_id,
+                name: format!("Item {n}"),
+                occurred_at: Utc::now(),
+            }));
+            for delta in 1..=ADJUSTMENTS as i64 {
+                dispatch(InventoryCommand::AdjustStock(AdjustStock {
+                    tenant_id,
+                    item_id,
+                    location_id: DEFAULT_LOCATION.to_string(),
+                    delta,
+                    occurred_at: Utc::now(),
+                }));
+            }
         }
+        store
     }
 
-    Ok(())
-}
+    fn sorted(mut items: Vec<InventoryReadModel>) -> Vec<InventoryReadModel> {
+        items.sort_by_key(|i| *i.item_id.0.as_uuid());
+        items
+    }
 
+    #[test]
+    fn streaming_rebuild_matches_full_rebuild_in_bounded_pages() {
+        const PAGE: u32 = 64;
+        let tenant_id = TenantId::new();
+        let store = seed(tenant_id);
+        let types = vec!["inventory.item".to_string()];
+
+        let streamed = projection();
+        block_on(async {
+            let mut events = StreamingEvents::open(store.as_ref(), tenant_id, &types, PAGE).await.unwrap();
+            assert_eq!(events.total_events(), (ITEMS * (ADJUSTMENTS + 1)) as u64);
+            assert_eq!(events.aggregate_count(), ITEMS);
+
+            let mut pages = 0;
+            while let Some(page) = events.next_page().await.unwrap() {
+                assert!(page.len() <= PAGE as usize, "page of {} events", page.len());
+                pages += 1;
+                for event in &page {
+                    streamed.apply_envelope(&event.to_envelope()).unwrap();
+                }
+            }
+            assert!(pages >= (ITEMS * (ADJUSTMENTS + 1)).div_ceil(PAGE as usize));
+        });
+
+        // Reference: the whole history loaded at once.
+        let all = block_on(store.query_events(tenant_id, EventFilter::default(), Pagination::new(Some(1000), None)))
+            .unwrap();
+        assert!(!all.has_more);
+        let full = projection();
+        full.rebuild_from_scratch(all.events.iter().map(StoredEvent::to_envelope)).unwrap();
+        let expected = sorted(full.list(tenant_id));
+        assert_eq!(expected.len(), ITEMS);
+        assert!(expected.iter().all(|i| i.quantity == (1..=ADJUSTMENTS as i64).sum::<i64>()));
+        assert_eq!(sorted(streamed.list(tenant_id)), expected);
+    }
+
+    #[test]
+    fn run_replay_streams_every_event_and_reports_progress() {
+        let tenant_id = TenantId::new();
+        let store = seed(tenant_id);
+        let target = Arc::new(projection());
+
+        let apply: ApplyEnvelopeFn = {
+            let target = target.clone();
+            Arc::new(move |env| target.apply_envelope(env).map_err(|e| e.to_string()))
+        };
+        let clear: ClearTenantFn = Arc::new(|_| {});
+        let progress = Arc::new(RwLock::new(ReplayProgress {
+            total_events: 0,
+            processed_events: 0,
+            processed_aggregates: 0,
+            phase: ReplayPhase::Loading,
+            is_complete: false,
+            error: None,
+        }));
+
+        block_on(run_replay(
+            store,
+            tenant_id,
+            vec!["inventory.item".to_string()],
+            apply,
+            clear,
+            false,
+            progress.clone(),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(AtomicU64::new(0)),
+            Arc::new(AtomicU64::new(0)),
+        ))
+        .unwrap();
+
+        let progress = progress.try_read().unwrap();
+        assert_eq!(progress.total_events, (ITEMS * (ADJUSTMENTS + 1)) as u64);
+        assert_eq!(progress.processed_events, progress.total_events);
+        assert_eq!(progress.processed_aggregates, ITEMS as u64);
+        assert_eq!(target.list(tenant_id).len(), ITEMS);
+    }
+
+    #[test]
+    fn rebuild_resets_the_tenant_and_replays_it_in_global_order() {
+        let tenant_id = TenantId::new();
+        let store = seed(tenant_id);
+        let target = Arc::new(projection());
+        let all = store.read_all(tenant_id, None, 10_000).unwrap();
+        for event in &all {
+            target.apply_envelope(&event.to_envelope()).unwrap();
+        }
+        let expected = sorted(target.list(tenant_id));
+
+        let apply: ApplyEnvelopeFn = {
+            let target = target.clone();
+            Arc::new(move |env| target.apply_envelope(env).map_err(|e| e.to_string()))
+        };
+        let clear: ClearTenantFn = {
+            let target = target.clone();
+            // Replayed from sequence 1, so the projection's cursors must go too.
+            Arc::new(move |tenant_id| {
+                let reset =
+                    EventEnvelope::new(uuid::Uuid::now_v7(), tenant_id, AggregateId::new(), "other", 1, JsonValue::Null);
+                target.rebuild_from_scratch(std::iter::once(reset)).unwrap();
+            })
+        };
+        let progress = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let handle = rebuild_projection(store, tenant_id, vec!["inventory.item".to_string()], apply, clear);
+                handle.wait_for_completion().await
+            })
+            .unwrap();
+
+        assert_eq!(progress.phase, ReplayPhase::Complete);
+        assert_eq!(progress.total_events, all.len() as u64);
+        assert_eq!(progress.processed_events, progress.total_events);
+        assert_eq!(progress.processed_aggregates, ITEMS as u64);
+        assert_eq!(sorted(target.list(tenant_id)), expected);
+    }
+}
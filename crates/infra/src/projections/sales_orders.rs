@@ -1,16 +1,18 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
 use serde_json::Value as JsonValue;
 use thiserror::Error;
 
+use forgeerp_ai::{AiError, ProductDemandSnapshot, ReadModelReader, SalesSnapshot};
 use forgeerp_core::{AggregateId, TenantId};
 use forgeerp_events::EventEnvelope;
 use forgeerp_sales::{SalesOrderEvent, SalesOrderId, SalesOrderStatus};
 use forgeerp_products::ProductId;
 
 use crate::projections::cursor_store::ProjectionCursorStore;
-use crate::read_model::TenantStore;
+use crate::read_model::{ListPage, PageStart, TenantStore};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SalesOrderLineReadModel {
@@ -25,6 +27,22 @@ pub struct SalesOrderReadModel {
     pub order_id: SalesOrderId,
     pub status: SalesOrderStatus,
     pub lines: Vec<SalesOrderLineReadModel>,
+    /// Lines confirmed without enough stock to reserve.
+    pub backordered_lines: Vec<u32>,
+    /// Why the order was flagged for attention (e.g. its invoice never arrived).
+    pub flag_reason: Option<String>,
+    /// Sum of `quantity * unit_price` over the lines (smallest currency unit).
+    pub total_amount: u64,
+}
+
+impl SalesOrderReadModel {
+    fn recompute_total(&mut self) {
+        self.total_amount = self
+            .lines
+            .iter()
+            .map(|l| (l.quantity.max(0) as u64).saturating_mul(l.unit_price))
+            .fold(0, u64::saturating_add);
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -154,6 +172,18 @@ where
         self.store.list(tenant_id)
     }
 
+    /// One page of the tenant's sales orders that `keep` accepts, ordered by `order`.
+    pub fn list_page(
+        &self,
+        tenant_id: TenantId,
+        keep: &dyn Fn(&SalesOrderReadModel) -> bool,
+        order: &dyn Fn(&SalesOrderReadModel, &SalesOrderReadModel) -> Ordering,
+        start: PageStart<'_, SalesOrderReadModel>,
+        limit: usize,
+    ) -> ListPage<SalesOrderReadModel> {
+        self.store.list_page(tenant_id, keep, order, start, limit)
+    }
+
     pub fn apply_envelope(
         &self,
         envelope: &EventEnvelope<JsonValue>,
@@ -183,8 +213,12 @@ where
         let (event_tenant, order_id) = match &ev {
             SalesOrderEvent::SalesOrderCreated(e) => (e.tenant_id, e.order_id),
             SalesOrderEvent::LineAdded(e) => (e.tenant_id, e.order_id),
+            SalesOrderEvent::LineRemoved(e) => (e.tenant_id, e.order_id),
             SalesOrderEvent::OrderConfirmed(e) => (e.tenant_id, e.order_id),
             SalesOrderEvent::OrderInvoiced(e) => (e.tenant_id, e.order_id),
+            SalesOrderEvent::OrderCancelled(e) => (e.tenant_id, e.order_id),
+            SalesOrderEvent::OrderBackordered(e) => (e.tenant_id, e.order_id),
+            SalesOrderEvent::OrderFlagged(e) => (e.tenant_id, e.order_id),
         };
 
         if event_tenant != tenant_id {
@@ -207,6 +241,9 @@ where
                         order_id: e.order_id,
                         status: SalesOrderStatus::Draft,
                         lines: vec![],
+                        backordered_lines: vec![],
+                        flag_reason: None,
+                        total_amount: 0,
                     },
                 );
             }
@@ -215,6 +252,9 @@ where
                     order_id: e.order_id,
                     status: SalesOrderStatus::Draft,
                     lines: vec![],
+                    backordered_lines: vec![],
+                    flag_reason: None,
+                    total_amount: 0,
                 });
                 rm.lines.push(SalesOrderLineReadModel {
                     line_no: e.line_no,
@@ -222,13 +262,24 @@ where
                     quantity: e.quantity,
                     unit_price: e.unit_price,
                 });
+                rm.recompute_total();
                 self.store.upsert(tenant_id, e.order_id, rm);
             }
+            SalesOrderEvent::LineRemoved(e) => {
+                if let Some(mut rm) = self.store.get(tenant_id, &e.order_id) {
+                    rm.lines.retain(|l| l.line_no != e.line_no);
+                    rm.recompute_total();
+                    self.store.upsert(tenant_id, e.order_id, rm);
+                }
+            }
             SalesOrderEvent::OrderConfirmed(e) => {
                 let mut rm = self.store.get(tenant_id, &e.order_id).unwrap_or(SalesOrderReadModel {
                     order_id: e.order_id,
                     status: SalesOrderStatus::Draft,
                     lines: vec![],
+                    backordered_lines: vec![],
+                    flag_reason: None,
+                    total_amount: 0,
                 });
                 rm.status = SalesOrderStatus::Confirmed;
                 self.store.upsert(tenant_id, e.order_id, rm);
@@ -238,10 +289,47 @@ where
                     order_id: e.order_id,
                     status: SalesOrderStatus::Draft,
                     lines: vec![],
+                    backordered_lines: vec![],
+                    flag_reason: None,
+                    total_amount: 0,
                 });
                 rm.status = SalesOrderStatus::Invoiced;
                 self.store.upsert(tenant_id, e.order_id, rm);
             }
+            SalesOrderEvent::OrderCancelled(e) => {
+                let mut rm = self.store.get(tenant_id, &e.order_id).unwrap_or(SalesOrderReadModel {
+                    order_id: e.order_id,
+                    status: SalesOrderStatus::Draft,
+                    lines: vec![],
+                    backordered_lines: vec![],
+                    flag_reason: None,
+                    total_amount: 0,
+                });
+                rm.status = SalesOrderStatus::Cancelled;
+                self.store.upsert(tenant_id, e.order_id, rm);
+            }
+            SalesOrderEvent::OrderBackordered(e) => {
+                let mut rm = self.store.get(tenant_id, &e.order_id).unwrap_or(SalesOrderReadModel {
+                    order_id: e.order_id,
+                    status: SalesOrderStatus::Confirmed,
+                    lines: vec![],
+                    backordered_lines: vec![],
+                    flag_reason: None,
+                    total_amount: 0,
+                });
+                for line_no in e.line_nos {
+                    if !rm.backordered_lines.contains(&line_no) {
+                        rm.backordered_lines.push(line_no);
+                    }
+                }
+                self.store.upsert(tenant_id, e.order_id, rm);
+            }
+            SalesOrderEvent::OrderFlagged(e) => {
+                if let Some(mut rm) = self.store.get(tenant_id, &e.order_id) {
+                    rm.flag_reason = Some(e.reason);
+                    self.store.upsert(tenant_id, e.order_id, rm);
+                }
+            }
         }
 
         self.update_cursor(tenant_id, aggregate_id, seq);
@@ -279,4 +367,192 @@ where
     }
 }
 
+impl<S, C> ReadModelReader<SalesSnapshot> for SalesOrdersProjection<S, C>
+where
+    S: TenantStore<SalesOrderId, SalesOrderReadModel> + Send + Sync + 'static,
+    C: ProjectionCursorStore + Send + Sync + 'static,
+{
+    /// Demand per product: line quantities of confirmed (or later, not cancelled) orders,
+    /// oldest order first.
+    fn get_snapshot(&self, tenant_id: TenantId) -> Result<SalesSnapshot, AiError> {
+        let mut orders: Vec<SalesOrderReadModel> = self
+            .list(tenant_id)
+            .into_iter()
+            .filter(|o| {
+                matches!(
+                    o.status,
+                    SalesOrderStatus::Confirmed | SalesOrderStatus::Invoiced | SalesOrderStatus::Closed
+                )
+            })
+            .collect();
+        // Order ids are UUIDv7, so this is creation order.
+        orders.sort_by_key(|o| *o.order_id.0.as_uuid());
+
+        let mut products: Vec<ProductDemandSnapshot> = Vec::new();
+        for line in orders.iter().flat_map(|o| &o.lines) {
+            let product_id = line.product_id.to_string();
+            match products.iter_mut().find(|p| p.product_id == product_id) {
+                Some(product) => product.demand_history.push(line.quantity),
+                None => products.push(ProductDemandSnapshot {
+                    product_id,
+                    demand_history: vec![line.quantity],
+                }),
+            }
+        }
+
+        Ok(SalesSnapshot { tenant_id, products })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use forgeerp_sales::{LineAdded, LineRemoved, SalesOrderCreated};
+
+    use super::*;
+    use crate::read_model::InMemoryTenantStore;
+
+    #[test]
+    fn removing_a_line_recomputes_the_order_total() {
+        let projection = SalesOrdersProjection::new(Arc::new(InMemoryTenantStore::new()));
+        let tenant_id = TenantId::new();
+        let order_id = SalesOrderId::new(AggregateId::new());
+        let line = |line_no, quantity, unit_price| {
+            SalesOrderEvent::LineAdded(LineAdded {
+                tenant_id,
+                order_id,
+                line_no,
+                product_id: ProductId::new(AggregateId::new()),
+                quantity,
+                unit_price,
+                occurred_at: Utc::now(),
+            })
+        };
+        let events = [
+            SalesOrderEvent::SalesOrderCreated(SalesOrderCreated {
+                tenant_id,
+                order_id,
+                occurred_at: Utc::now(),
+            }),
+            line(1, 2, 500),
+            line(2, 1, 250),
+        ];
+        for (seq, event) in events.into_iter().enumerate() {
+            projection
+                .apply_envelope(&EventEnvelope::new(
+                    uuid::Uuid::now_v7(),
+                    tenant_id,
+                    order_id.0,
+                    "sales.order",
+                    seq as u64 + 1,
+                    serde_json::to_value(event).unwrap(),
+                ))
+                .unwrap();
+        }
+        assert_eq!(projection.get(tenant_id, &order_id).unwrap().total_amount, 1_250);
+
+        let removed = SalesOrderEvent::LineRemoved(LineRemoved {
+            tenant_id,
+            order_id,
+            line_no: 1,
+            occurred_at: Utc::now(),
+        });
+        projection
+            .apply_envelope(&EventEnvelope::new(
+                uuid::Uuid::now_v7(),
+                tenant_id,
+                order_id.0,
+                "sales.order",
+                4,
+                serde_json::to_value(removed).unwrap(),
+            ))
+            .unwrap();
+        let rm = projection.get(tenant_id, &order_id).unwrap();
+        assert_eq!((rm.lines.len(), rm.lines[0].line_no, rm.total_amount), (1, 2, 250));
+    }
 
+    #[test]
+    fn sales_snapshot_lists_demand_of_confirmed_orders_oldest_first() {
+        let projection = SalesOrdersProjection::new(Arc::new(InMemoryTenantStore::new()));
+        let tenant_id = TenantId::new();
+        let bolts = ProductId::new(AggregateId::new());
+        let order = |status, quantity| {
+            let order_id = SalesOrderId::new(AggregateId::new());
+            projection.store.upsert(
+                tenant_id,
+                order_id,
+                SalesOrderReadModel {
+                    order_id,
+                    status,
+                    lines: vec![SalesOrderLineReadModel {
+                        line_no: 1,
+                        product_id: bolts,
+                        quantity,
+                        unit_price: 10,
+                    }],
+                    backordered_lines: Vec::new(),
+                    flag_reason: None,
+                    total_amount: 0,
+                },
+            );
+        };
+        order(SalesOrderStatus::Confirmed, 3);
+        order(SalesOrderStatus::Draft, 50);
+        order(SalesOrderStatus::Invoiced, 5);
+        order(SalesOrderStatus::Cancelled, 70);
+        order(SalesOrderStatus::Closed, 7);
+
+        let snapshot = projection.get_snapshot(tenant_id).unwrap();
+        assert_eq!(
+            snapshot.products,
+            vec![ProductDemandSnapshot {
+                product_id: bolts.to_string(),
+                demand_history: vec![3, 5, 7],
+            }]
+        );
+        assert!(projection.get_snapshot(TenantId::new()).unwrap().products.is_empty());
+    }
+
+    #[test]
+    fn paged_listing_orders_without_overlap() {
+        let projection = SalesOrdersProjection::new(Arc::new(InMemoryTenantStore::new()));
+        let tenant_id = TenantId::new();
+        for _ in 0..5 {
+            let order_id = SalesOrderId::new(AggregateId::new());
+            projection.store.upsert(
+                tenant_id,
+                order_id,
+                SalesOrderReadModel {
+                    order_id,
+                    status: SalesOrderStatus::Draft,
+                    lines: Vec::new(),
+                    backordered_lines: Vec::new(),
+                    flag_reason: None,
+                    total_amount: 0,
+                },
+            );
+        }
+
+        let mut all: Vec<_> = projection.list(tenant_id).into_iter().map(|rm| rm.order_id).collect();
+        all.sort_by_key(|id| *id.0.as_uuid());
+        let by_id = |a: &SalesOrderReadModel, b: &SalesOrderReadModel| a.order_id.0.as_uuid().cmp(b.order_id.0.as_uuid());
+        let page = |start| projection.list_page(tenant_id, &|_| true, &by_id, start, 2);
+
+        let pages: Vec<_> = [0, 2, 4].into_iter().map(|offset| page(PageStart::Offset(offset))).collect();
+        assert!(pages.iter().all(|p| p.total == 5));
+        assert_eq!(pages.iter().map(|p| p.offset).collect::<Vec<_>>(), [0, 2, 4]);
+        let paged: Vec<_> = pages.into_iter().flat_map(|p| p.items).map(|rm| rm.order_id).collect();
+        assert_eq!(paged, all);
+
+        // A keyset page starts after the given position and knows where it lies.
+        let after = |rm: &SalesOrderReadModel| rm.order_id.0.as_uuid() > all[1].0.as_uuid();
+        let next = page(PageStart::After(&after));
+        assert_eq!(next.items.iter().map(|rm| rm.order_id).collect::<Vec<_>>(), all[2..4]);
+        assert_eq!((next.offset, next.total), (2, 5));
+        assert!(next.has_more());
+
+        let past_end = page(PageStart::Offset(9));
+        assert!(past_end.items.is_empty() && !past_end.has_more());
+        assert_eq!(projection.list_page(TenantId::new(), &|_| true, &by_id, PageStart::Offset(0), 2).total, 0);
+    }
+}
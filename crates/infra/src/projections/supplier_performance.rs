@@ -0,0 +1,540 @@
+//! Supplier Performance Projection.
+//!
+//! Delivery record of every purchase order (supplier, promised date, line prices,
+//! completion time and what was received), from which per-supplier metrics are
+//! computed over a completion window: on-time receipt rate, average receipt delay
+//! and total spend.
+//!
+//! ## Partial receipts
+//!
+//! An order is measured once, at its final completion: when an order has several
+//! `GoodsReceived` events the last one sets the completion time, and its lines
+//! replace the earlier ones (as in the `PurchaseOrder` aggregate).
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value as JsonValue;
+use thiserror::Error;
+
+use forgeerp_core::{AggregateId, TenantId};
+use forgeerp_events::EventEnvelope;
+use forgeerp_parties::PartyId;
+use forgeerp_purchasing::{LineItem, PurchaseOrderEvent, PurchaseOrderId};
+
+use crate::projections::cursor_store::ProjectionCursorStore;
+use crate::read_model::TenantStore;
+
+/// Read model: delivery record of one purchase order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupplierOrderRecord {
+    pub order_id: PurchaseOrderId,
+    pub supplier_id: PartyId,
+    /// Promised delivery date, if one was agreed.
+    pub expected_at: Option<DateTime<Utc>>,
+    /// Unit cost (minor units) per line number, for priced lines.
+    pub unit_costs: HashMap<u32, u64>,
+    /// Final completion (last goods receipt); `None` while open.
+    pub received_at: Option<DateTime<Utc>>,
+    pub received_lines: Vec<LineItem>,
+}
+
+impl SupplierOrderRecord {
+    /// Received quantity × unit cost over the priced lines.
+    pub fn spend(&self) -> u64 {
+        self.received_lines
+            .iter()
+            .filter_map(|l| Some(self.unit_costs.get(&l.line_no)? * l.quantity.max(0) as u64))
+            .sum()
+    }
+
+    /// Time past the promised date (zero when on time); `None` when undated or open.
+    pub fn delay(&self) -> Option<Duration> {
+        let late_by = self.received_at? - self.expected_at?;
+        Some(late_by.max(Duration::zero()))
+    }
+}
+
+/// Orders completed within `[from, to)`; open bounds are unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerformanceWindow {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl PerformanceWindow {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.from.is_none_or(|from| at >= from) && self.to.is_none_or(|to| at < to)
+    }
+}
+
+/// Metrics of one supplier over a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupplierPerformance {
+    pub supplier_id: PartyId,
+    pub window: PerformanceWindow,
+    /// Orders completed in the window.
+    pub orders_received: u64,
+    /// Completed orders that had a promised date (the base of the two metrics below).
+    pub orders_with_due_date: u64,
+    pub orders_on_time: u64,
+    /// `orders_on_time / orders_with_due_date`; `None` without dated orders.
+    pub on_time_rate: Option<f64>,
+    /// Mean delay past the promised date, on-time orders counting as zero.
+    pub average_delay: Option<Duration>,
+    /// Spend on the completed orders, in minor units (unpriced lines count as zero).
+    pub total_spend: u64,
+}
+
+/// Tenant+aggregate cursor for idempotent projection.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct CursorKey {
+    tenant_id: TenantId,
+    aggregate_id: AggregateId,
+}
+
+#[derive(Debug, Error)]
+pub enum SupplierPerformanceError {
+    #[error("failed to deserialize purchase order event: {0}")]
+    Deserialize(String),
+
+    #[error("tenant isolation violation: {0}")]
+    TenantIsolation(String),
+
+    #[error("non-monotonic sequence number (last={last}, found={found})")]
+    NonMonotonicSequence { last: u64, found: u64 },
+}
+
+/// In-memory cursor store (no persistence).
+pub struct InMemoryCursorStore;
+
+impl ProjectionCursorStore for InMemoryCursorStore {
+    fn get_cursor(
+        &self,
+        _tenant_id: TenantId,
+        _aggregate_id: AggregateId,
+        _projection_name: &str,
+    ) -> Option<u64> {
+        None
+    }
+
+    fn update_cursor(
+        &self,
+        _tenant_id: TenantId,
+        _aggregate_id: AggregateId,
+        _projection_name: &str,
+        _sequence_number: u64,
+    ) {
+        // no-op
+    }
+
+    fn clear_cursors(&self, _tenant_id: TenantId, _projection_name: &str) {
+        // no-op
+    }
+}
+
+/// Supplier performance projection: purchase order delivery records per tenant.
+///
+/// Rebuildable from purchase order events. Tenant-isolated.
+#[derive(Debug)]
+pub struct SupplierPerformanceProjection<S, C = InMemoryCursorStore>
+where
+    S: TenantStore<PurchaseOrderId, SupplierOrderRecord>,
+{
+    store: S,
+    cursors: RwLock<HashMap<CursorKey, u64>>,
+    cursor_store: Option<Arc<C>>,
+    projection_name: String,
+}
+
+impl<S> SupplierPerformanceProjection<S>
+where
+    S: TenantStore<PurchaseOrderId, SupplierOrderRecord>,
+{
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            cursors: RwLock::new(HashMap::new()),
+            cursor_store: None,
+            projection_name: "purchasing.supplier_performance".to_string(),
+        }
+    }
+
+    pub fn with_persistent_cursors<C: ProjectionCursorStore + 'static>(
+        self,
+        cursor_store: Arc<C>,
+        projection_name: impl Into<String>,
+    ) -> SupplierPerformanceProjection<S, C> {
+        SupplierPerformanceProjection {
+            store: self.store,
+            cursors: RwLock::new(HashMap::new()),
+            cursor_store: Some(cursor_store),
+            projection_name: projection_name.into(),
+        }
+    }
+}
+
+impl<S, C> SupplierPerformanceProjection<S, C>
+where
+    S: TenantStore<PurchaseOrderId, SupplierOrderRecord>,
+    C: ProjectionCursorStore + 'static,
+{
+    fn get_cursor(&self, tenant_id: TenantId, aggregate_id: AggregateId) -> u64 {
+        if let Some(ref cursor_store) = self.cursor_store {
+            cursor_store
+                .get_cursor(tenant_id, aggregate_id, &self.projection_name)
+                .unwrap_or(0)
+        } else {
+            match self.cursors.read() {
+                Ok(cursors) => *cursors
+                    .get(&CursorKey { tenant_id, aggregate_id })
+                    .unwrap_or(&0),
+                Err(_) => 0,
+            }
+        }
+    }
+
+    fn update_cursor(&self, tenant_id: TenantId, aggregate_id: AggregateId, sequence_number: u64) {
+        if let Ok(mut cursors) = self.cursors.write() {
+            cursors.insert(CursorKey { tenant_id, aggregate_id }, sequence_number);
+        }
+
+        if let Some(ref cursor_store) = self.cursor_store {
+            cursor_store.update_cursor(tenant_id, aggregate_id, &self.projection_name, sequence_number);
+        }
+    }
+
+    fn clear_cursors(&self, tenant_id: TenantId) {
+        if let Ok(mut cursors) = self.cursors.write() {
+            cursors.retain(|k, _| k.tenant_id != tenant_id);
+        }
+
+        if let Some(ref cursor_store) = self.cursor_store {
+            cursor_store.clear_cursors(tenant_id, &self.projection_name);
+        }
+    }
+
+    /// Delivery record of one order.
+    pub fn get(&self, tenant_id: TenantId, order_id: &PurchaseOrderId) -> Option<SupplierOrderRecord> {
+        self.store.get(tenant_id, order_id)
+    }
+
+    /// Metrics of `supplier_id` over the orders it completed within `window`.
+    pub fn performance(
+        &self,
+        tenant_id: TenantId,
+        supplier_id: PartyId,
+        window: PerformanceWindow,
+    ) -> SupplierPerformance {
+        let completed: Vec<_> = self
+            .store
+            .list(tenant_id)
+            .into_iter()
+            .filter(|r| r.supplier_id == supplier_id)
+            .filter(|r| r.received_at.is_some_and(|at| window.contains(at)))
+            .collect();
+
+        let delays: Vec<Duration> = completed.iter().filter_map(SupplierOrderRecord::delay).collect();
+        let orders_with_due_date = delays.len() as u64;
+        let orders_on_time = delays.iter().filter(|d| d.is_zero()).count() as u64;
+        let (on_time_rate, average_delay) = if delays.is_empty() {
+            (None, None)
+        } else {
+            let total: Duration = delays.iter().copied().sum();
+            (
+                Some(orders_on_time as f64 / orders_with_due_date as f64),
+                Some(total / delays.len() as i32),
+            )
+        };
+
+        SupplierPerformance {
+            supplier_id,
+            window,
+            orders_received: completed.len() as u64,
+            orders_with_due_date,
+            orders_on_time,
+            on_time_rate,
+            average_delay,
+            total_spend: completed.iter().map(SupplierOrderRecord::spend).sum(),
+        }
+    }
+
+    /// Apply envelope into the delivery records.
+    pub fn apply_envelope(&self, envelope: &EventEnvelope<JsonValue>) -> Result<(), SupplierPerformanceError> {
+        if envelope.aggregate_type() != "purchasing.order" {
+            return Ok(());
+        }
+
+        let tenant_id = envelope.tenant_id();
+        let aggregate_id = envelope.aggregate_id();
+        let seq = envelope.sequence_number();
+
+        let last = self.get_cursor(tenant_id, aggregate_id);
+
+        if seq == 0 {
+            return Err(SupplierPerformanceError::NonMonotonicSequence { last, found: seq });
+        }
+
+        if seq <= last {
+            return Ok(());
+        }
+
+        if seq != last + 1 && last != 0 {
+            return Err(SupplierPerformanceError::NonMonotonicSequence { last, found: seq });
+        }
+
+        let ev: PurchaseOrderEvent = serde_json::from_value(envelope.payload().clone())
+            .map_err(|e| SupplierPerformanceError::Deserialize(e.to_string()))?;
+
+        let (event_tenant, order_id) = match &ev {
+            PurchaseOrderEvent::PurchaseOrderCreated(e) => (e.tenant_id, e.order_id),
+            PurchaseOrderEvent::PurchaseOrderLineAdded(e) => (e.tenant_id, e.order_id),
+            PurchaseOrderEvent::PurchaseOrderApproved(e) => (e.tenant_id, e.order_id),
+            PurchaseOrderEvent::GoodsReceived(e) => (e.tenant_id, e.order_id),
+        };
+
+        if event_tenant != tenant_id {
+            return Err(SupplierPerformanceError::TenantIsolation(
+                "event tenant_id does not match envelope tenant_id".to_string(),
+            ));
+        }
+
+        if order_id.0 != aggregate_id {
+            return Err(SupplierPerformanceError::TenantIsolation(
+                "event order_id does not match envelope aggregate_id".to_string(),
+            ));
+        }
+
+        match ev {
+            PurchaseOrderEvent::PurchaseOrderCreated(e) => {
+                self.store.upsert(
+                    tenant_id,
+                    e.order_id,
+                    SupplierOrderRecord {
+                        order_id: e.order_id,
+                        supplier_id: e.supplier_id,
+                        expected_at: e.expected_at,
+                        unit_costs: HashMap::new(),
+                        received_at: None,
+                        received_lines: Vec::new(),
+                    },
+                );
+            }
+            PurchaseOrderEvent::PurchaseOrderLineAdded(e) => {
+                if let (Some(mut record), Some(cost)) = (self.store.get(tenant_id, &e.order_id), e.unit_cost) {
+                    record.unit_costs.insert(e.line_no, cost);
+                    self.store.upsert(tenant_id, e.order_id, record);
+                }
+            }
+            PurchaseOrderEvent::PurchaseOrderApproved(_) => {}
+            PurchaseOrderEvent::GoodsReceived(e) => {
+                let mut record = self.store.get(tenant_id, &e.order_id).unwrap_or(SupplierOrderRecord {
+                    order_id: e.order_id,
+                    supplier_id: e.supplier_id,
+                    expected_at: None,
+                    unit_costs: HashMap::new(),
+                    received_at: None,
+                    received_lines: Vec::new(),
+                });
+                record.received_at = Some(e.occurred_at);
+                record.received_lines = e.lines;
+                self.store.upsert(tenant_id, e.order_id, record);
+            }
+        }
+
+        self.update_cursor(tenant_id, aggregate_id, seq);
+        Ok(())
+    }
+
+    /// Rebuild the read model from scratch.
+    pub fn rebuild_from_scratch(
+        &self,
+        envelopes: impl IntoIterator<Item = EventEnvelope<JsonValue>>,
+    ) -> Result<(), SupplierPerformanceError> {
+        let mut envs: Vec<_> = envelopes.into_iter().collect();
+
+        {
+            let mut tenants = envs.iter().map(|e| e.tenant_id()).collect::<Vec<_>>();
+            tenants.sort_by_key(|t| *t.as_uuid().as_bytes());
+            tenants.dedup();
+            for t in tenants {
+                self.store.clear_tenant(t);
+                self.clear_cursors(t);
+            }
+        }
+
+        envs.sort_by_key(|e| {
+            (
+                *e.tenant_id().as_uuid().as_bytes(),
+                *e.aggregate_id().as_uuid().as_bytes(),
+                e.sequence_number(),
+            )
+        });
+
+        for env in &envs {
+            self.apply_envelope(env)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_model::InMemoryTenantStore;
+    use chrono::TimeZone;
+    use forgeerp_products::ProductId;
+    use forgeerp_purchasing::{GoodsReceived, PurchaseOrderApproved, PurchaseOrderCreated, PurchaseOrderLineAdded};
+
+    type Projection = SupplierPerformanceProjection<Arc<InMemoryTenantStore<PurchaseOrderId, SupplierOrderRecord>>>;
+
+    fn make_envelope(tenant_id: TenantId, aggregate_id: AggregateId, seq: u64, ev: &PurchaseOrderEvent) -> EventEnvelope<JsonValue> {
+        EventEnvelope::new(
+            uuid::Uuid::now_v7(),
+            tenant_id,
+            aggregate_id,
+            "purchasing.order".to_string(),
+            seq,
+            serde_json::to_value(ev).unwrap(),
+        )
+    }
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, d, 12, 0, 0).unwrap()
+    }
+
+    /// Envelopes of one order: created (due `expected`), one line of `quantity` at
+    /// `unit_cost`, approved, then a goods receipt at each of `receipts`.
+    fn order(
+        tenant_id: TenantId,
+        supplier_id: PartyId,
+        expected: Option<DateTime<Utc>>,
+        quantity: i64,
+        unit_cost: Option<u64>,
+        receipts: &[DateTime<Utc>],
+    ) -> Vec<EventEnvelope<JsonValue>> {
+        let order_id = PurchaseOrderId::new(AggregateId::new());
+        let line = LineItem { line_no: 1, product_id: ProductId::new(AggregateId::new()), quantity };
+        let mut events = vec![
+            PurchaseOrderEvent::PurchaseOrderCreated(PurchaseOrderCreated {
+                tenant_id,
+                order_id,
+                supplier_id,
+                expected_at: expected,
+                freight: 0,
+                discount: 0,
+                allocation_basis: Default::default(),
+                occurred_at: day(1),
+            }),
+            PurchaseOrderEvent::PurchaseOrderLineAdded(PurchaseOrderLineAdded {
+                tenant_id,
+                order_id,
+                line_no: line.line_no,
+                product_id: line.product_id,
+                quantity,
+                unit_cost,
+                occurred_at: day(1),
+            }),
+            PurchaseOrderEvent::PurchaseOrderApproved(PurchaseOrderApproved { tenant_id, order_id, occurred_at: day(1) }),
+        ];
+        events.extend(receipts.iter().map(|at| {
+            PurchaseOrderEvent::GoodsReceived(GoodsReceived {
+                tenant_id,
+                order_id,
+                supplier_id,
+                lines: vec![line.clone()],
+                occurred_at: *at,
+            })
+        }));
+        events
+            .iter()
+            .enumerate()
+            .map(|(i, ev)| make_envelope(tenant_id, order_id.0, i as u64 + 1, ev))
+            .collect()
+    }
+
+    #[test]
+    fn on_time_rate_average_delay_and_spend_over_completed_orders() {
+        let proj: Projection = SupplierPerformanceProjection::new(Arc::new(InMemoryTenantStore::new()));
+        let tenant_id = TenantId::new();
+        let supplier = PartyId::new(AggregateId::new());
+
+        let envs = [
+            // Early and exactly on time.
+            order(tenant_id, supplier, Some(day(10)), 10, Some(500), &[day(8)]),
+            order(tenant_id, supplier, Some(day(10)), 2, Some(1_000), &[day(10)]),
+            // Two days and four days late.
+            order(tenant_id, supplier, Some(day(10)), 1, Some(250), &[day(12)]),
+            order(tenant_id, supplier, Some(day(10)), 4, None, &[day(14)]),
+            // No promised date: counts for spend only.
+            order(tenant_id, supplier, None, 3, Some(100), &[day(9)]),
+            // Still open.
+            order(tenant_id, supplier, Some(day(2)), 5, Some(100), &[]),
+            // Another supplier.
+            order(tenant_id, PartyId::new(AggregateId::new()), Some(day(10)), 1, Some(9_999), &[day(20)]),
+        ];
+        for env in envs.iter().flatten() {
+            proj.apply_envelope(env).unwrap();
+        }
+
+        let perf = proj.performance(tenant_id, supplier, PerformanceWindow::default());
+        assert_eq!(perf.orders_received, 5);
+        assert_eq!(perf.orders_with_due_date, 4);
+        assert_eq!(perf.orders_on_time, 2);
+        assert_eq!(perf.on_time_rate, Some(0.5));
+        // (0 + 0 + 2 + 4 days) / 4
+        assert_eq!(perf.average_delay, Some(Duration::hours(36)));
+        assert_eq!(perf.total_spend, 10 * 500 + 2 * 1_000 + 250 + 3 * 100);
+
+        // Window on completion time: only the late receipts.
+        let window = PerformanceWindow { from: Some(day(11)), to: None };
+        let late = proj.performance(tenant_id, supplier, window);
+        assert_eq!((late.orders_received, late.orders_on_time), (2, 0));
+        assert_eq!(late.average_delay, Some(Duration::days(3)));
+
+        // Other tenants see nothing.
+        let other = proj.performance(TenantId::new(), supplier, PerformanceWindow::default());
+        assert_eq!((other.orders_received, other.on_time_rate), (0, None));
+    }
+
+    #[test]
+    fn partial_receipts_are_measured_at_final_completion() {
+        let proj: Projection = SupplierPerformanceProjection::new(Arc::new(InMemoryTenantStore::new()));
+        let tenant_id = TenantId::new();
+        let supplier = PartyId::new(AggregateId::new());
+
+        // First delivery on time, the rest a day late: the order counts once, late.
+        for env in order(tenant_id, supplier, Some(day(10)), 4, Some(100), &[day(9), day(11)]) {
+            proj.apply_envelope(&env).unwrap();
+        }
+
+        let perf = proj.performance(tenant_id, supplier, PerformanceWindow::default());
+        assert_eq!((perf.orders_received, perf.orders_on_time), (1, 0));
+        assert_eq!(perf.average_delay, Some(Duration::days(1)));
+        assert_eq!(perf.total_spend, 400);
+    }
+
+    #[test]
+    fn redelivery_is_ignored_and_rebuild_reproduces_the_metrics() {
+        let proj: Projection = SupplierPerformanceProjection::new(Arc::new(InMemoryTenantStore::new()));
+        let tenant_id = TenantId::new();
+        let supplier = PartyId::new(AggregateId::new());
+        let envs: Vec<_> = [
+            order(tenant_id, supplier, Some(day(10)), 1, Some(100), &[day(9)]),
+            order(tenant_id, supplier, Some(day(10)), 1, Some(100), &[day(13)]),
+        ]
+        .concat();
+
+        for env in envs.iter().chain(envs.iter()) {
+            proj.apply_envelope(env).unwrap();
+        }
+        let live = proj.performance(tenant_id, supplier, PerformanceWindow::default());
+        assert_eq!(live.orders_received, 2);
+        assert_eq!(live.total_spend, 200);
+
+        proj.rebuild_from_scratch(envs.into_iter().rev()).unwrap();
+        assert_eq!(proj.performance(tenant_id, supplier, PerformanceWindow::default()), live);
+    }
+}
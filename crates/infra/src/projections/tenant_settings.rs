@@ -0,0 +1,185 @@
+//! Tenant settings projection: current values and change history per tenant.
+//!
+//! Built from `tenant.settings` events. Sagas and request handlers read a tenant's
+//! [`TenantSettings`] from here; tenants that never changed a setting get the defaults.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use forgeerp_core::TenantId;
+use forgeerp_events::EventEnvelope;
+
+use crate::event_store::StoredEvent;
+use crate::tenant_settings::{TenantSettings, TenantSettingsEvent, AGGREGATE_TYPE};
+
+/// One recorded change of a setting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingHistoryEntry {
+    pub key: String,
+    pub value: JsonValue,
+    pub previous: JsonValue,
+    /// Position of the change in the tenant's settings stream.
+    pub version: u64,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+struct TenantEntry {
+    settings: TenantSettings,
+    version: u64,
+    history: Vec<SettingHistoryEntry>,
+}
+
+/// Current settings and their history, per tenant.
+#[derive(Debug, Default)]
+pub struct TenantSettingsProjection {
+    tenants: RwLock<HashMap<TenantId, TenantEntry>>,
+}
+
+impl TenantSettingsProjection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn arc() -> Arc<Self> {
+        Arc::new(Self::new())
+    }
+
+    /// Apply a `tenant.settings` event; other events and already applied ones are ignored.
+    pub fn apply_envelope(&self, envelope: &EventEnvelope<JsonValue>) -> Result<(), anyhow::Error> {
+        if envelope.aggregate_type() != AGGREGATE_TYPE {
+            return Ok(());
+        }
+        let event: TenantSettingsEvent = serde_json::from_value(envelope.payload().clone())?;
+
+        let mut tenants = self.tenants.write().unwrap_or_else(|e| e.into_inner());
+        let entry = tenants.entry(envelope.tenant_id()).or_default();
+        if envelope.sequence_number() <= entry.version {
+            return Ok(());
+        }
+        match event {
+            TenantSettingsEvent::SettingChanged(change) => {
+                entry.settings.apply_change(&change);
+                entry.history.push(SettingHistoryEntry {
+                    key: change.key,
+                    value: change.value,
+                    previous: change.previous,
+                    version: envelope.sequence_number(),
+                    changed_at: change.occurred_at,
+                });
+            }
+        }
+        entry.version = envelope.sequence_number();
+        Ok(())
+    }
+
+    /// Apply events straight from a dispatch result (before the bus delivers them).
+    pub fn apply_committed(&self, committed: &[StoredEvent]) -> Result<(), anyhow::Error> {
+        committed.iter().try_for_each(|event| self.apply_envelope(&event.to_envelope()))
+    }
+
+    /// Current settings for `tenant_id` (defaults when none were changed).
+    pub fn get(&self, tenant_id: TenantId) -> TenantSettings {
+        self.tenants
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&tenant_id)
+            .map(|entry| entry.settings.clone())
+            .unwrap_or_default()
+    }
+
+    /// Version of the tenant's settings stream seen so far (0 before any change).
+    pub fn version(&self, tenant_id: TenantId) -> u64 {
+        self.tenants
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&tenant_id)
+            .map_or(0, |entry| entry.version)
+    }
+
+    /// Changes for `tenant_id`, oldest first, optionally only those of one key.
+    pub fn history(&self, tenant_id: TenantId, key: Option<&str>) -> Vec<SettingHistoryEntry> {
+        self.tenants
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&tenant_id)
+            .map(|entry| {
+                entry
+                    .history
+                    .iter()
+                    .filter(|change| key.is_none_or(|key| change.key == key))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Drop a tenant's state (projection reset).
+    pub fn clear_tenant(&self, tenant_id: TenantId) {
+        self.tenants.write().unwrap_or_else(|e| e.into_inner()).remove(&tenant_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forgeerp_core::AggregateId;
+    use uuid::Uuid;
+
+    use crate::tenant_settings::{keys, settings_aggregate_id, SettingChanged};
+
+    fn change(tenant_id: TenantId, sequence: u64, key: &str, value: &str, previous: &str) -> EventEnvelope<JsonValue> {
+        let event = TenantSettingsEvent::SettingChanged(SettingChanged {
+            tenant_id,
+            key: key.to_string(),
+            value: value.into(),
+            previous: previous.into(),
+            occurred_at: Utc::now(),
+        });
+        EventEnvelope::new(
+            Uuid::now_v7(),
+            tenant_id,
+            settings_aggregate_id(tenant_id),
+            AGGREGATE_TYPE,
+            sequence,
+            serde_json::to_value(event).unwrap(),
+        )
+    }
+
+    #[test]
+    fn projection_reflects_changes_and_keeps_prior_values() {
+        let projection = TenantSettingsProjection::new();
+        let tenant_id = TenantId::new();
+
+        projection.apply_envelope(&change(tenant_id, 1, keys::DEFAULT_CURRENCY, "EUR", "USD")).unwrap();
+        projection.apply_envelope(&change(tenant_id, 2, keys::SHORT_STOCK, "backorder", "fail_confirmation")).unwrap();
+        projection.apply_envelope(&change(tenant_id, 3, keys::DEFAULT_CURRENCY, "GBP", "EUR")).unwrap();
+        // Redelivery is ignored.
+        projection.apply_envelope(&change(tenant_id, 3, keys::DEFAULT_CURRENCY, "GBP", "EUR")).unwrap();
+
+        let settings = projection.get(tenant_id);
+        assert_eq!(settings.default_currency, "GBP");
+        assert_eq!(projection.version(tenant_id), 3);
+
+        let currency: Vec<_> = projection
+            .history(tenant_id, Some(keys::DEFAULT_CURRENCY))
+            .into_iter()
+            .map(|c| (c.previous, c.value, c.version))
+            .collect();
+        assert_eq!(
+            currency,
+            vec![("USD".into(), "EUR".into(), 1), ("EUR".into(), "GBP".into(), 3)]
+        );
+        assert_eq!(projection.history(tenant_id, None).len(), 3);
+
+        // Other tenants, and other aggregate types, are unaffected.
+        assert_eq!(projection.get(TenantId::new()), TenantSettings::default());
+        let other = EventEnvelope::new(Uuid::now_v7(), tenant_id, AggregateId::new(), "auth.user", 4, JsonValue::Null);
+        projection.apply_envelope(&other).unwrap();
+        assert_eq!(projection.version(tenant_id), 3);
+    }
+}
@@ -0,0 +1,369 @@
+//! Trial Balance Projection.
+//!
+//! Debit and credit totals per [`AccountKind`], built from ledger events. The
+//! trial balance is `balanced` when total debits equal total credits across all
+//! kinds, which holds for any sequence of accepted journal entries (postings and
+//! reversals alike).
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use serde_json::Value as JsonValue;
+use thiserror::Error;
+
+use forgeerp_accounting::{AccountKind, LedgerEvent};
+use forgeerp_core::{AggregateId, TenantId};
+use forgeerp_events::EventEnvelope;
+
+use crate::projections::cursor_store::ProjectionCursorStore;
+use crate::read_model::TenantStore;
+
+/// Read model: debit/credit totals of all accounts of one kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KindTotals {
+    pub kind: AccountKind,
+    pub debit_total: i128,
+    pub credit_total: i128,
+}
+
+impl KindTotals {
+    /// Signed balance (debit-positive convention, as in `AccountBalance`).
+    pub fn balance(&self) -> i128 {
+        self.debit_total - self.credit_total
+    }
+}
+
+/// Trial balance of a tenant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrialBalance {
+    /// One entry per account kind with postings, in `AccountKind` order.
+    pub kinds: Vec<KindTotals>,
+    pub total_debits: i128,
+    pub total_credits: i128,
+    pub balanced: bool,
+}
+
+/// Tenant+aggregate cursor for idempotent projection.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct CursorKey {
+    tenant_id: TenantId,
+    aggregate_id: AggregateId,
+}
+
+#[derive(Debug, Error)]
+pub enum TrialBalanceError {
+    #[error("failed to deserialize accounting event: {0}")]
+    Deserialize(String),
+
+    #[error("tenant isolation violation: {0}")]
+    TenantIsolation(String),
+
+    #[error("non-monotonic sequence number (last={last}, found={found})")]
+    NonMonotonicSequence { last: u64, found: u64 },
+}
+
+/// In-memory cursor store (no persistence).
+pub struct InMemoryCursorStore;
+
+impl ProjectionCursorStore for InMemoryCursorStore {
+    fn get_cursor(
+        &self,
+        _tenant_id: TenantId,
+        _aggregate_id: AggregateId,
+        _projection_name: &str,
+    ) -> Option<u64> {
+        None
+    }
+
+    fn update_cursor(
+        &self,
+        _tenant_id: TenantId,
+        _aggregate_id: AggregateId,
+        _projection_name: &str,
+        _sequence_number: u64,
+    ) {
+        // no-op
+    }
+
+    fn clear_cursors(&self, _tenant_id: TenantId, _projection_name: &str) {
+        // no-op
+    }
+}
+
+/// Projection: ledger → trial balance per tenant.
+///
+/// Rebuildable from ledger events. Tenant-isolated.
+#[derive(Debug)]
+pub struct TrialBalanceProjection<S, C = InMemoryCursorStore>
+where
+    S: TenantStore<AccountKind, KindTotals>,
+{
+    store: S,
+    cursors: RwLock<HashMap<CursorKey, u64>>,
+    cursor_store: Option<Arc<C>>,
+    projection_name: String,
+}
+
+impl<S> TrialBalanceProjection<S>
+where
+    S: TenantStore<AccountKind, KindTotals>,
+{
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            cursors: RwLock::new(HashMap::new()),
+            cursor_store: None,
+            projection_name: "accounting.trial_balance".to_string(),
+        }
+    }
+
+    pub fn with_persistent_cursors<C: ProjectionCursorStore + 'static>(
+        self,
+        cursor_store: Arc<C>,
+        projection_name: impl Into<String>,
+    ) -> TrialBalanceProjection<S, C> {
+        TrialBalanceProjection {
+            store: self.store,
+            cursors: RwLock::new(HashMap::new()),
+            cursor_store: Some(cursor_store),
+            projection_name: projection_name.into(),
+        }
+    }
+}
+
+impl<S, C> TrialBalanceProjection<S, C>
+where
+    S: TenantStore<AccountKind, KindTotals>,
+    C: ProjectionCursorStore + 'static,
+{
+    fn get_cursor(&self, tenant_id: TenantId, aggregate_id: AggregateId) -> u64 {
+        if let Some(ref cursor_store) = self.cursor_store {
+            cursor_store
+                .get_cursor(tenant_id, aggregate_id, &self.projection_name)
+                .unwrap_or(0)
+        } else {
+            match self.cursors.read() {
+                Ok(cursors) => *cursors
+                    .get(&CursorKey { tenant_id, aggregate_id })
+                    .unwrap_or(&0),
+                Err(_) => 0,
+            }
+        }
+    }
+
+    fn update_cursor(&self, tenant_id: TenantId, aggregate_id: AggregateId, sequence_number: u64) {
+        if let Ok(mut cursors) = self.cursors.write() {
+            cursors.insert(CursorKey { tenant_id, aggregate_id }, sequence_number);
+        }
+
+        if let Some(ref cursor_store) = self.cursor_store {
+            cursor_store.update_cursor(tenant_id, aggregate_id, &self.projection_name, sequence_number);
+        }
+    }
+
+    fn clear_cursors(&self, tenant_id: TenantId) {
+        if let Ok(mut cursors) = self.cursors.write() {
+            cursors.retain(|k, _| k.tenant_id != tenant_id);
+        }
+
+        if let Some(ref cursor_store) = self.cursor_store {
+            cursor_store.clear_cursors(tenant_id, &self.projection_name);
+        }
+    }
+
+    /// Trial balance of a tenant: totals per account kind and overall.
+    pub fn trial_balance(&self, tenant_id: TenantId) -> TrialBalance {
+        let mut kinds = self.store.list(tenant_id);
+        kinds.sort_by_key(|k| k.kind);
+        let total_debits = kinds.iter().map(|k| k.debit_total).sum();
+        let total_credits = kinds.iter().map(|k| k.credit_total).sum();
+        TrialBalance {
+            kinds,
+            total_debits,
+            total_credits,
+            balanced: total_debits == total_credits,
+        }
+    }
+
+    pub fn apply_envelope(&self, envelope: &EventEnvelope<JsonValue>) -> Result<(), TrialBalanceError> {
+        if envelope.aggregate_type() != "accounting.ledger" {
+            return Ok(());
+        }
+
+        let tenant_id = envelope.tenant_id();
+        let aggregate_id = envelope.aggregate_id();
+        let seq = envelope.sequence_number();
+
+        let last = self.get_cursor(tenant_id, aggregate_id);
+
+        if seq == 0 {
+            return Err(TrialBalanceError::NonMonotonicSequence { last, found: seq });
+        }
+
+        if seq <= last {
+            return Ok(());
+        }
+
+        if seq != last + 1 && last != 0 {
+            return Err(TrialBalanceError::NonMonotonicSequence { last, found: seq });
+        }
+
+        let ev: LedgerEvent = serde_json::from_value(envelope.payload().clone())
+            .map_err(|e| TrialBalanceError::Deserialize(e.to_string()))?;
+
+        if ev.tenant_id() != tenant_id {
+            return Err(TrialBalanceError::TenantIsolation(
+                "event tenant_id does not match envelope tenant_id".to_string(),
+            ));
+        }
+
+        for line in ev.lines() {
+            let kind = line.account.kind;
+            let mut totals = self.store.get(tenant_id, &kind).unwrap_or(KindTotals {
+                kind,
+                debit_total: 0,
+                credit_total: 0,
+            });
+            if line.is_debit {
+                totals.debit_total += line.amount as i128;
+            } else {
+                totals.credit_total += line.amount as i128;
+            }
+            self.store.upsert(tenant_id, kind, totals);
+        }
+
+        self.update_cursor(tenant_id, aggregate_id, seq);
+        Ok(())
+    }
+
+    pub fn rebuild_from_scratch(
+        &self,
+        envelopes: impl IntoIterator<Item = EventEnvelope<JsonValue>>,
+    ) -> Result<(), TrialBalanceError> {
+        let mut envs: Vec<_> = envelopes.into_iter().collect();
+
+        {
+            let mut tenants = envs.iter().map(|e| e.tenant_id()).collect::<Vec<_>>();
+            tenants.sort_by_key(|t| *t.as_uuid().as_bytes());
+            tenants.dedup();
+            for t in tenants {
+                self.store.clear_tenant(t);
+                self.clear_cursors(t);
+            }
+        }
+
+        envs.sort_by_key(|e| {
+            (
+                *e.tenant_id().as_uuid().as_bytes(),
+                *e.aggregate_id().as_uuid().as_bytes(),
+                e.sequence_number(),
+            )
+        });
+
+        for env in &envs {
+            self.apply_envelope(env)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_model::InMemoryTenantStore;
+    use chrono::Utc;
+    use forgeerp_accounting::{
+        Account, JournalCommand, JournalEntryLine, Ledger, LedgerId, PostJournalEntry, ReverseJournalEntry,
+    };
+    use forgeerp_core::Aggregate;
+
+    const KINDS: [AccountKind; 5] = [
+        AccountKind::Asset,
+        AccountKind::Liability,
+        AccountKind::Equity,
+        AccountKind::Revenue,
+        AccountKind::Expense,
+    ];
+
+    fn account(kind: AccountKind) -> Account {
+        let code = format!("{kind:?}");
+        Account {
+            name: code.clone(),
+            code,
+            kind,
+        }
+    }
+
+    #[test]
+    fn balanced_entries_always_yield_a_balanced_trial_balance() {
+        let tenant_id = TenantId::new();
+        let ledger_id = LedgerId::new(AggregateId::new());
+        let mut ledger = Ledger::empty(ledger_id);
+        let proj = TrialBalanceProjection::new(Arc::new(InMemoryTenantStore::<AccountKind, KindTotals>::new()));
+        let mut seq = 0;
+
+        let mut run = |ledger: &mut Ledger, cmd: JournalCommand| {
+            for ev in ledger.handle(&cmd).unwrap() {
+                ledger.apply(&ev);
+                seq += 1;
+                let env = EventEnvelope::new(
+                    uuid::Uuid::now_v7(),
+                    tenant_id,
+                    ledger_id.0,
+                    "accounting.ledger".to_string(),
+                    seq,
+                    serde_json::to_value(&ev).unwrap(),
+                );
+                proj.apply_envelope(&env).unwrap();
+            }
+        };
+
+        // Every debit/credit kind pair, with varying amounts; every third entry is reversed.
+        let mut n: i64 = 0;
+        for debit in KINDS {
+            for credit in KINDS {
+                n += 1;
+                let entry_id = uuid::Uuid::now_v7();
+                let amount = n * 137 % 1_000 + 1;
+                run(
+                    &mut ledger,
+                    JournalCommand::PostJournalEntry(PostJournalEntry {
+                        tenant_id,
+                        ledger_id,
+                        entry_id,
+                        lines: vec![
+                            JournalEntryLine { account: account(debit), amount, is_debit: true },
+                            JournalEntryLine { account: account(credit), amount, is_debit: false },
+                        ],
+                        currency: None,
+                        occurred_at: Utc::now(),
+                        description: None,
+                    }),
+                );
+                if n % 3 == 0 {
+                    run(
+                        &mut ledger,
+                        JournalCommand::ReverseJournalEntry(ReverseJournalEntry {
+                            tenant_id,
+                            ledger_id,
+                            entry_id,
+                            reversal_entry_id: uuid::Uuid::now_v7(),
+                            occurred_at: Utc::now(),
+                            description: None,
+                        }),
+                    );
+                }
+
+                let tb = proj.trial_balance(tenant_id);
+                assert!(tb.balanced);
+                assert_eq!(tb.total_debits, tb.total_credits);
+                assert_eq!(tb.kinds.iter().map(KindTotals::balance).sum::<i128>(), 0);
+            }
+        }
+
+        let tb = proj.trial_balance(tenant_id);
+        let kinds: Vec<_> = tb.kinds.iter().map(|k| k.kind).collect();
+        assert_eq!(kinds, KINDS);
+    }
+}
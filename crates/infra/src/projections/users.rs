@@ -8,7 +8,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use forgeerp_auth::{
-    Role, RoleAssigned, RoleRevoked, UserActivated, UserCreated, UserEvent, UserId,
+    Permission, Role, RoleAssigned, RoleRevoked, TenantMembership, UserActivated, UserCreated, UserEvent, UserId,
     UserStatus, UserSuspended,
 };
 use forgeerp_core::TenantId;
@@ -29,6 +29,9 @@ pub struct UserReadModel {
     pub display_name: String,
     pub roles: Vec<String>,
     pub status: String,
+    /// Last suspension; tokens issued at or before it stay revoked after reactivation.
+    #[serde(default)]
+    pub suspended_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -88,6 +91,7 @@ where
             display_name: e.display_name,
             roles: e.initial_roles.iter().map(|r| r.as_str().to_string()).collect(),
             status: UserStatus::Active.to_string(),
+            suspended_at: None,
             created_at: e.occurred_at,
             updated_at: e.occurred_at,
         };
@@ -120,6 +124,7 @@ where
     fn apply_suspended(&self, tenant_id: TenantId, e: UserSuspended) -> Result<(), anyhow::Error> {
         if let Some(mut model) = self.store.get(tenant_id, &e.user_id) {
             model.status = UserStatus::Suspended.to_string();
+            model.suspended_at = Some(e.occurred_at);
             model.updated_at = e.occurred_at;
             self.store.upsert(tenant_id, e.user_id, model);
         }
@@ -145,6 +150,11 @@ where
         self.store.list(tenant_id)
     }
 
+    /// Drop the tenant's users (before replaying them).
+    pub fn clear_tenant(&self, tenant_id: TenantId) {
+        self.store.clear_tenant(tenant_id);
+    }
+
     /// Get a user by email (linear scan).
     pub fn get_by_email(&self, tenant_id: TenantId, email: &str) -> Option<UserReadModel> {
         let normalized = email.trim().to_lowercase();
@@ -181,6 +191,30 @@ where
             permissions: all_permissions.into_iter().collect(),
         })
     }
+
+    /// When the user was last suspended in `tenant_id` (tokens issued until then are revoked).
+    pub fn suspended_at(&self, tenant_id: TenantId, user_id: &UserId) -> Option<DateTime<Utc>> {
+        self.get(tenant_id, user_id)?.suspended_at
+    }
+
+    /// The user's membership in `tenant_id`: `None` unless they are an active user there.
+    pub fn membership<F>(&self, tenant_id: TenantId, user_id: &UserId, role_permissions: F) -> Option<TenantMembership>
+    where
+        F: Fn(&str) -> Vec<String>,
+    {
+        let model = self.get(tenant_id, user_id)?;
+        if model.status != UserStatus::Active.to_string() {
+            return None;
+        }
+        let mut permissions: Vec<String> = model.roles.iter().flat_map(|role| role_permissions(role)).collect();
+        permissions.sort();
+        permissions.dedup();
+        Some(TenantMembership {
+            tenant_id: model.tenant_id,
+            roles: model.roles.into_iter().map(Role::new).collect(),
+            permissions: permissions.into_iter().map(Permission::new).collect(),
+        })
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -258,6 +292,9 @@ pub fn default_role_permissions(role: &str) -> Vec<String> {
             "inventory.read".to_string(),
             "products.read".to_string(),
         ],
+        // Platform support, from IdP claims or service accounts only (tenant user
+        // management refuses to grant it); never through a tenant admin's wildcard
+        "platform_admin" => vec!["admin.cross_tenant".to_string()],
         _ => vec![], // Unknown roles get no permissions
     }
 }
@@ -390,6 +427,18 @@ mod tests {
 
         let user = projection.get(tenant_id, &user_id).unwrap();
         assert_eq!(user.status, "Suspended");
+        assert_eq!(projection.suspended_at(tenant_id, &user_id), Some(now));
+
+        // Reactivation keeps the cutoff: tokens from before the suspension stay revoked.
+        let activate_event = UserEvent::Activated(UserActivated {
+            tenant_id,
+            user_id,
+            occurred_at: now,
+        });
+        projection
+            .apply_envelope(&make_envelope(tenant_id, user_id, activate_event))
+            .unwrap();
+        assert_eq!(projection.suspended_at(tenant_id, &user_id), Some(now));
     }
 
     #[test]
@@ -456,5 +505,40 @@ mod tests {
         // List for tenant B is empty
         assert!(projection.list(tenant_b).is_empty());
     }
-}
 
+    #[test]
+    fn membership_exists_per_tenant_while_the_user_is_active() {
+        let store = Arc::new(InMemoryTenantStore::new());
+        let projection = UsersProjection::new(store);
+
+        let tenant_a = TenantId::new();
+        let tenant_b = TenantId::new();
+        let user_id = UserId::new();
+        for (tenant_id, role) in [(tenant_a, "admin"), (tenant_b, "viewer")] {
+            let event = UserEvent::Created(UserCreated {
+                tenant_id,
+                user_id,
+                email: "mia@example.com".to_string(),
+                display_name: "Mia".to_string(),
+                initial_roles: vec![Role::new(role)],
+                occurred_at: Utc::now(),
+            });
+            projection.apply_envelope(&make_envelope(tenant_id, user_id, event)).unwrap();
+        }
+
+        let membership = projection.membership(tenant_b, &user_id, default_role_permissions).unwrap();
+        assert_eq!(membership.tenant_id, tenant_b);
+        assert_eq!(membership.roles, vec![Role::new("viewer")]);
+        assert!(projection.membership(TenantId::new(), &user_id, default_role_permissions).is_none());
+
+        let suspended = UserEvent::Suspended(UserSuspended {
+            tenant_id: tenant_a,
+            user_id,
+            reason: "left".to_string(),
+            occurred_at: Utc::now(),
+        });
+        projection.apply_envelope(&make_envelope(tenant_a, user_id, suspended)).unwrap();
+        assert!(projection.membership(tenant_a, &user_id, default_role_permissions).is_none());
+        assert!(projection.membership(tenant_b, &user_id, default_role_permissions).is_some());
+    }
+}
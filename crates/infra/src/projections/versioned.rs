@@ -0,0 +1,375 @@
+//! Side-by-side projection versions (A/B) for safe projection migrations.
+//!
+//! Changing a projection's logic is risky: the new read model only shows its
+//! differences once it runs against real traffic. [`ProjectionVersions`] pairs the
+//! current (`primary`) and the new (`candidate`) version of one projection. Both apply
+//! every envelope into their own store, but only the **active** version serves reads.
+//! [`ProjectionVersions::compare`] diffs the two read models of a tenant entry by entry,
+//! so the switch (`activate`) happens once the divergences are the intended ones.
+//!
+//! Versions are built from closures ([`ProjectionVersion`]), the same way replays drive
+//! projections ([`ApplyEnvelopeFn`]); [`snapshot_store`] adapts any [`TenantStore`].
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, RwLock};
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use thiserror::Error;
+
+use forgeerp_core::TenantId;
+use forgeerp_events::EventEnvelope;
+
+use crate::projections::replay::ApplyEnvelopeFn;
+use crate::read_model::TenantStore;
+
+/// A tenant's read model as JSON entries keyed by a stable entry key (e.g. the item id).
+pub type SnapshotFn = Arc<dyn Fn(TenantId) -> BTreeMap<String, JsonValue> + Send + Sync>;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ProjectionVersionError {
+    #[error("projection versions must have distinct labels, got `{0}` twice")]
+    DuplicateLabel(String),
+    #[error("unknown projection version: {0}")]
+    UnknownVersion(String),
+    #[error("unknown versioned projection: {0}")]
+    UnknownProjection(String),
+    #[error("projection version `{label}` failed to apply event: {message}")]
+    Apply { label: String, message: String },
+}
+
+/// One version of a projection: how it applies events and how its read model is listed.
+#[derive(Clone)]
+pub struct ProjectionVersion {
+    pub label: String,
+    apply: ApplyEnvelopeFn,
+    snapshot: SnapshotFn,
+}
+
+impl std::fmt::Debug for ProjectionVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProjectionVersion")
+            .field("label", &self.label)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProjectionVersion {
+    pub fn new(label: impl Into<String>, apply: ApplyEnvelopeFn, snapshot: SnapshotFn) -> Self {
+        Self {
+            label: label.into(),
+            apply,
+            snapshot,
+        }
+    }
+}
+
+/// Snapshot of a [`TenantStore`]'s rows, serialized and keyed by `key`.
+pub fn snapshot_store<K, V, S>(store: S, key: fn(&V) -> String) -> SnapshotFn
+where
+    K: 'static,
+    V: Serialize + 'static,
+    S: TenantStore<K, V> + 'static,
+{
+    Arc::new(move |tenant_id| {
+        store
+            .list(tenant_id)
+            .iter()
+            .map(|row| (key(row), serde_json::to_value(row).unwrap_or(JsonValue::Null)))
+            .collect()
+    })
+}
+
+/// One entry whose read models differ; `None` where a version has no such entry.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectionDivergence {
+    pub key: String,
+    pub primary: Option<JsonValue>,
+    pub candidate: Option<JsonValue>,
+}
+
+/// Result of [`ProjectionVersions::compare`] for one tenant.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectionComparison {
+    pub projection: String,
+    pub primary: String,
+    pub candidate: String,
+    pub active: String,
+    /// Distinct entry keys across both versions.
+    pub entries_compared: usize,
+    /// Ordered by key.
+    pub divergences: Vec<ProjectionDivergence>,
+}
+
+/// Labels and active version of a versioned projection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProjectionVersionsInfo {
+    pub projection: String,
+    pub primary: String,
+    pub candidate: String,
+    pub active: String,
+}
+
+/// The primary and candidate versions of one projection.
+#[derive(Debug)]
+pub struct ProjectionVersions {
+    name: String,
+    primary: ProjectionVersion,
+    candidate: ProjectionVersion,
+    /// Label of the version serving reads; starts as the primary.
+    active: RwLock<String>,
+}
+
+impl ProjectionVersions {
+    pub fn new(
+        name: impl Into<String>,
+        primary: ProjectionVersion,
+        candidate: ProjectionVersion,
+    ) -> Result<Self, ProjectionVersionError> {
+        if primary.label == candidate.label {
+            return Err(ProjectionVersionError::DuplicateLabel(primary.label));
+        }
+        Ok(Self {
+            name: name.into(),
+            active: RwLock::new(primary.label.clone()),
+            primary,
+            candidate,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn info(&self) -> ProjectionVersionsInfo {
+        ProjectionVersionsInfo {
+            projection: self.name.clone(),
+            primary: self.primary.label.clone(),
+            candidate: self.candidate.label.clone(),
+            active: self.active_label(),
+        }
+    }
+
+    pub fn active_label(&self) -> String {
+        self.active.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Serve reads from the version labelled `label`.
+    pub fn activate(&self, label: &str) -> Result<(), ProjectionVersionError> {
+        self.version(label)?;
+        *self.active.write().unwrap_or_else(|e| e.into_inner()) = label.to_string();
+        Ok(())
+    }
+
+    /// Apply an envelope to both versions; one failing does not keep it from the other.
+    pub fn apply(&self, envelope: &EventEnvelope<JsonValue>) -> Result<(), ProjectionVersionError> {
+        let primary = (self.primary.apply)(envelope);
+        let candidate = (self.candidate.apply)(envelope);
+        for (version, result) in [(&self.primary, primary), (&self.candidate, candidate)] {
+            result.map_err(|message| ProjectionVersionError::Apply {
+                label: version.label.clone(),
+                message,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// The active version's read model for a tenant.
+    pub fn read(&self, tenant_id: TenantId) -> BTreeMap<String, JsonValue> {
+        let active = self.active_label();
+        let version = if active == self.candidate.label { &self.candidate } else { &self.primary };
+        (version.snapshot)(tenant_id)
+    }
+
+    /// One entry of the active version's read model.
+    pub fn get(&self, tenant_id: TenantId, key: &str) -> Option<JsonValue> {
+        self.read(tenant_id).remove(key)
+    }
+
+    /// Entries of a tenant where the two versions disagree (or only one has the entry).
+    pub fn compare(&self, tenant_id: TenantId) -> ProjectionComparison {
+        let primary = (self.primary.snapshot)(tenant_id);
+        let candidate = (self.candidate.snapshot)(tenant_id);
+        let keys: BTreeSet<&String> = primary.keys().chain(candidate.keys()).collect();
+        let divergences = keys
+            .iter()
+            .filter(|key| primary.get(**key) != candidate.get(**key))
+            .map(|key| ProjectionDivergence {
+                key: (*key).clone(),
+                primary: primary.get(*key).cloned(),
+                candidate: candidate.get(*key).cloned(),
+            })
+            .collect();
+        ProjectionComparison {
+            projection: self.name.clone(),
+            primary: self.primary.label.clone(),
+            candidate: self.candidate.label.clone(),
+            active: self.active_label(),
+            entries_compared: keys.len(),
+            divergences,
+        }
+    }
+
+    fn version(&self, label: &str) -> Result<&ProjectionVersion, ProjectionVersionError> {
+        [&self.primary, &self.candidate]
+            .into_iter()
+            .find(|v| v.label == label)
+            .ok_or_else(|| ProjectionVersionError::UnknownVersion(label.to_string()))
+    }
+}
+
+/// Versioned projections by name, fed together from the event bus.
+#[derive(Debug, Default)]
+pub struct ProjectionVersionRegistry {
+    projections: RwLock<BTreeMap<String, Arc<ProjectionVersions>>>,
+}
+
+impl ProjectionVersionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn arc() -> Arc<Self> {
+        Arc::new(Self::new())
+    }
+
+    /// Register (or replace) a versioned projection under its name.
+    pub fn register(&self, versions: ProjectionVersions) -> Arc<ProjectionVersions> {
+        let versions = Arc::new(versions);
+        self.projections
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(versions.name().to_string(), versions.clone());
+        versions
+    }
+
+    pub fn get(&self, name: &str) -> Result<Arc<ProjectionVersions>, ProjectionVersionError> {
+        self.projections
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ProjectionVersionError::UnknownProjection(name.to_string()))
+    }
+
+    /// All versioned projections, by name.
+    pub fn list(&self) -> Vec<ProjectionVersionsInfo> {
+        self.all().iter().map(|v| v.info()).collect()
+    }
+
+    /// Apply an envelope to every versioned projection; returns the failures.
+    pub fn apply(&self, envelope: &EventEnvelope<JsonValue>) -> Vec<ProjectionVersionError> {
+        self.all().iter().filter_map(|v| v.apply(envelope).err()).collect()
+    }
+
+    fn all(&self) -> Vec<Arc<ProjectionVersions>> {
+        self.projections
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use forgeerp_core::AggregateId;
+    use forgeerp_inventory::{DEFAULT_LOCATION, InventoryEvent, InventoryItemId, StockAdjusted};
+
+    use crate::read_model::InMemoryTenantStore;
+
+    #[derive(Debug, Clone, Serialize)]
+    struct Stock {
+        item_id: InventoryItemId,
+        quantity: i64,
+    }
+
+    type StockStore = Arc<InMemoryTenantStore<InventoryItemId, Stock>>;
+
+    /// Stock per item; the new logic (`floor_at_zero`) never reports negative stock.
+    fn stock_version(label: &str, floor_at_zero: bool) -> ProjectionVersion {
+        let store: StockStore = Arc::new(InMemoryTenantStore::new());
+        let writes = store.clone();
+        let apply: ApplyEnvelopeFn = Arc::new(move |envelope| {
+            let event: InventoryEvent = serde_json::from_value(envelope.payload().clone()).map_err(|e| e.to_string())?;
+            if let InventoryEvent::StockAdjusted(e) = event {
+                let current = writes.get(e.tenant_id, &e.item_id).map_or(0, |s| s.quantity);
+                let quantity = current + e.delta;
+                let quantity = if floor_at_zero { quantity.max(0) } else { quantity };
+                writes.upsert(e.tenant_id, e.item_id, Stock { item_id: e.item_id, quantity });
+            }
+            Ok(())
+        });
+        ProjectionVersion::new(label, apply, snapshot_store(store, |s: &Stock| s.item_id.to_string()))
+    }
+
+    fn adjusted(tenant_id: TenantId, item_id: InventoryItemId, seq: u64, delta: i64) -> EventEnvelope<JsonValue> {
+        let event = InventoryEvent::StockAdjusted(StockAdjusted {
+            tenant_id,
+            item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
+            delta,
+            occurred_at: Utc::now(),
+        });
+        EventEnvelope::new(
+            uuid::Uuid::now_v7(),
+            tenant_id,
+            item_id.0,
+            "inventory.item".to_string(),
+            seq,
+            serde_json::to_value(event).unwrap(),
+        )
+    }
+
+    #[test]
+    fn comparison_reports_exactly_the_entries_the_new_logic_changes() {
+        let registry = ProjectionVersionRegistry::new();
+        let versions = registry.register(
+            ProjectionVersions::new("inventory.stock", stock_version("v1", false), stock_version("v2", true)).unwrap(),
+        );
+        let tenant_id = TenantId::new();
+        let (bolts, nuts, washers) = (
+            InventoryItemId::new(AggregateId::new()),
+            InventoryItemId::new(AggregateId::new()),
+            InventoryItemId::new(AggregateId::new()),
+        );
+
+        for envelope in [
+            adjusted(tenant_id, bolts, 1, 10),
+            adjusted(tenant_id, bolts, 2, -4),
+            adjusted(tenant_id, nuts, 1, -3),
+            adjusted(tenant_id, washers, 1, 5),
+            adjusted(tenant_id, washers, 2, -8),
+            adjusted(TenantId::new(), nuts, 1, -1),
+        ] {
+            assert!(registry.apply(&envelope).is_empty());
+        }
+
+        let comparison = versions.compare(tenant_id);
+        assert_eq!(comparison.entries_compared, 3);
+        let diverging: Vec<_> = comparison
+            .divergences
+            .iter()
+            .map(|d| (d.key.clone(), d.primary.as_ref().unwrap()["quantity"].clone(), d.candidate.as_ref().unwrap()["quantity"].clone()))
+            .collect();
+        let mut expected = vec![
+            (nuts.to_string(), JsonValue::from(-3), JsonValue::from(0)),
+            (washers.to_string(), JsonValue::from(-3), JsonValue::from(0)),
+        ];
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(diverging, expected);
+
+        // Only the active version serves reads.
+        assert_eq!(versions.get(tenant_id, &nuts.to_string()).unwrap()["quantity"], -3);
+        versions.activate("v2").unwrap();
+        assert_eq!(versions.get(tenant_id, &nuts.to_string()).unwrap()["quantity"], 0);
+        assert_eq!(registry.list()[0].active, "v2");
+        assert_eq!(
+            versions.activate("v3"),
+            Err(ProjectionVersionError::UnknownVersion("v3".to_string()))
+        );
+    }
+}
@@ -2,8 +2,10 @@
 
 pub mod postgres;
 pub mod tenant_store;
+pub mod watermark;
 
 pub use postgres::PostgresInventoryStore;
-pub use tenant_store::{InMemoryTenantStore, TenantStore};
+pub use tenant_store::{page_records, InMemoryTenantStore, ListPage, PageStart, TenantStore};
+pub use watermark::Watermark;
 
 
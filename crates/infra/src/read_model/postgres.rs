@@ -121,6 +121,7 @@ impl TenantStore<InventoryItemId, InventoryReadModel> for PostgresInventoryStore
                     item_id,
                     name,
                     quantity,
+                    last_sequence,
                     updated_at
                 FROM inventory_stock
                 WHERE tenant_id = $1 AND item_id = $2
@@ -132,11 +133,19 @@ impl TenantStore<InventoryItemId, InventoryReadModel> for PostgresInventoryStore
             .await
             {
                 Ok(Some(row)) => {
-                    match (row.try_get::<String, _>("name"), row.try_get::<i64, _>("quantity"), row.try_get::<uuid::Uuid, _>("item_id")) {
-                        (Ok(name), Ok(quantity), Ok(item_id)) => Some(InventoryReadModel {
+                    match (
+                        row.try_get::<String, _>("name"),
+                        row.try_get::<i64, _>("quantity"),
+                        row.try_get::<uuid::Uuid, _>("item_id"),
+                        row.try_get::<i64, _>("last_sequence"),
+                        row.try_get::<chrono::DateTime<chrono::Utc>, _>("updated_at"),
+                    ) {
+                        (Ok(name), Ok(quantity), Ok(item_id), Ok(last_sequence), Ok(updated_at)) => Some(InventoryReadModel {
                             item_id: InventoryItemId(forgeerp_core::AggregateId::from_uuid(item_id)),
                             name,
                             quantity,
+                            last_sequence: last_sequence as u64,
+                            updated_at,
                         }),
                         _ => None,
                     }
@@ -167,20 +176,87 @@ impl TenantStore<InventoryItemId, InventoryReadModel> for PostgresInventoryStore
                     tenant_id,
                     item_id,
                     name,
-                    quantity
+                    quantity,
+                    last_sequence,
+                    updated_at
                 )
-                VALUES ($1, $2, $3, $4)
+                VALUES ($1, $2, $3, $4, $5, $6)
                 ON CONFLICT (tenant_id, item_id)
                 DO UPDATE SET
                     name = EXCLUDED.name,
                     quantity = EXCLUDED.quantity,
-                    updated_at = NOW()
+                    last_sequence = EXCLUDED.last_sequence,
+                    updated_at = EXCLUDED.updated_at
                 "#,
             )
             .bind(tenant_id_uuid)
             .bind(item_id_uuid)
             .bind(&value.name)
             .bind(value.quantity)
+            .bind(value.last_sequence as i64)
+            .bind(value.updated_at)
+            .execute(&*pool)
+            .await;
+        });
+    }
+
+    /// One `INSERT ... SELECT FROM UNNEST` statement for the whole batch, so a batch of
+    /// projected balances is a single round trip and a single transaction.
+    fn upsert_many(&self, tenant_id: TenantId, entries: Vec<(InventoryItemId, InventoryReadModel)>) {
+        if entries.is_empty() {
+            return;
+        }
+        let handle = match tokio::runtime::Handle::try_current() {
+            Ok(h) => h,
+            Err(_) => return,
+        };
+
+        let pool = self.pool.clone();
+        let tenant_id_uuid = tenant_id.as_uuid();
+        let mut item_ids = Vec::with_capacity(entries.len());
+        let mut names = Vec::with_capacity(entries.len());
+        let mut quantities = Vec::with_capacity(entries.len());
+        let mut last_sequences = Vec::with_capacity(entries.len());
+        let mut updated_ats = Vec::with_capacity(entries.len());
+        for (key, value) in entries {
+            item_ids.push(*key.0.as_uuid());
+            names.push(value.name);
+            quantities.push(value.quantity);
+            last_sequences.push(value.last_sequence as i64);
+            updated_ats.push(value.updated_at);
+        }
+
+        handle.block_on(async {
+            let span = Span::current();
+            span.record("operation", "upsert_many_inventory_stock");
+
+            let _ = sqlx::query(
+                r#"
+                INSERT INTO inventory_stock (
+                    tenant_id,
+                    item_id,
+                    name,
+                    quantity,
+                    last_sequence,
+                    updated_at
+                )
+                SELECT $1, item_id, name, quantity, last_sequence, updated_at
+                FROM UNNEST($2::uuid[], $3::text[], $4::bigint[], $5::bigint[], $6::timestamptz[])
+                    AS batch(item_id, name, quantity, last_sequence, updated_at)
+                ON CONFLICT (tenant_id, item_id)
+                DO UPDATE SET
+                    name = EXCLUDED.name,
+                    quantity = EXCLUDED.quantity,
+                    last_sequence = EXCLUDED.last_sequence,
+                    updated_at = EXCLUDED.updated_at
+                "#,
+            )
+            .bind(tenant_id_uuid)
+            .bind(&item_ids)
+            .bind(&names)
+            .bind(&quantities)
+            .bind(&last_sequences)
+            .bind(&updated_ats)
             .execute(&*pool)
             .await;
         });
@@ -206,6 +282,7 @@ impl TenantStore<InventoryItemId, InventoryReadModel> for PostgresInventoryStore
                     item_id,
                     name,
                     quantity,
+                    last_sequence,
                     updated_at
                 FROM inventory_stock
                 WHERE tenant_id = $1
@@ -218,11 +295,19 @@ impl TenantStore<InventoryItemId, InventoryReadModel> for PostgresInventoryStore
             {
                 Ok(rows) => rows.into_iter()
                     .filter_map(|r| {
-                        match (r.try_get::<uuid::Uuid, _>("item_id"), r.try_get::<String, _>("name"), r.try_get::<i64, _>("quantity")) {
-                            (Ok(item_id), Ok(name), Ok(quantity)) => Some(InventoryReadModel {
+                        match (
+                            r.try_get::<uuid::Uuid, _>("item_id"),
+                            r.try_get::<String, _>("name"),
+                            r.try_get::<i64, _>("quantity"),
+                            r.try_get::<i64, _>("last_sequence"),
+                            r.try_get::<chrono::DateTime<chrono::Utc>, _>("updated_at"),
+                        ) {
+                            (Ok(item_id), Ok(name), Ok(quantity), Ok(last_sequence), Ok(updated_at)) => Some(InventoryReadModel {
                                 item_id: InventoryItemId(forgeerp_core::AggregateId::from_uuid(item_id)),
                                 name,
                                 quantity,
+                                last_sequence: last_sequence as u64,
+                                updated_at,
                             }),
                             _ => None,
                         }
@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::hash::Hash;
 use std::sync::RwLock;
@@ -5,11 +6,54 @@ use std::sync::RwLock;
 use forgeerp_core::TenantId;
 use std::sync::Arc;
 
+/// Where a page of [`TenantStore::list_page`] starts.
+pub enum PageStart<'a, V> {
+    /// Skip this many matching records.
+    Offset(usize),
+    /// Keyset position: start at the records `after` accepts (those ordered past the
+    /// last record of the previous page).
+    After(&'a dyn Fn(&V) -> bool),
+}
+
+/// One page of matching records, how many records match, and the page's position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListPage<V> {
+    pub items: Vec<V>,
+    pub total: usize,
+    /// Number of matching records ordered before the page.
+    pub offset: usize,
+}
+
+impl<V> ListPage<V> {
+    /// Whether matching records follow this page.
+    pub fn has_more(&self) -> bool {
+        self.offset.saturating_add(self.items.len()) < self.total
+    }
+}
+
 /// Tenant-isolated key/value store abstraction for disposable read models.
 pub trait TenantStore<K, V>: Send + Sync {
     fn get(&self, tenant_id: TenantId, key: &K) -> Option<V>;
     fn upsert(&self, tenant_id: TenantId, key: K, value: V);
+    /// Upsert many entries of one tenant (one write for stores that can write in bulk).
+    fn upsert_many(&self, tenant_id: TenantId, entries: Vec<(K, V)>) {
+        for (key, value) in entries {
+            self.upsert(tenant_id, key, value);
+        }
+    }
     fn list(&self, tenant_id: TenantId) -> Vec<V>;
+    /// One page of the tenant's records that `keep` accepts, ordered by `order` from
+    /// `start`. Stores that can page themselves only copy out the page.
+    fn list_page(
+        &self,
+        tenant_id: TenantId,
+        keep: &dyn Fn(&V) -> bool,
+        order: &dyn Fn(&V, &V) -> Ordering,
+        start: PageStart<'_, V>,
+        limit: usize,
+    ) -> ListPage<V> {
+        page_records(self.list(tenant_id), keep, order, start, limit)
+    }
     /// Clear all read-model records for a tenant (rebuild support).
     fn clear_tenant(&self, tenant_id: TenantId);
 }
@@ -26,10 +70,25 @@ where
         (**self).upsert(tenant_id, key, value)
     }
 
+    fn upsert_many(&self, tenant_id: TenantId, entries: Vec<(K, V)>) {
+        (**self).upsert_many(tenant_id, entries)
+    }
+
     fn list(&self, tenant_id: TenantId) -> Vec<V> {
         (**self).list(tenant_id)
     }
 
+    fn list_page(
+        &self,
+        tenant_id: TenantId,
+        keep: &dyn Fn(&V) -> bool,
+        order: &dyn Fn(&V, &V) -> Ordering,
+        start: PageStart<'_, V>,
+        limit: usize,
+    ) -> ListPage<V> {
+        (**self).list_page(tenant_id, keep, order, start, limit)
+    }
+
     fn clear_tenant(&self, tenant_id: TenantId) {
         (**self).clear_tenant(tenant_id)
     }
@@ -71,6 +130,12 @@ where
         }
     }
 
+    fn upsert_many(&self, tenant_id: TenantId, entries: Vec<(K, V)>) {
+        if let Ok(mut map) = self.inner.write() {
+            map.extend(entries.into_iter().map(|(key, value)| ((tenant_id, key), value)));
+        }
+    }
+
     fn list(&self, tenant_id: TenantId) -> Vec<V> {
         let map = match self.inner.read() {
             Ok(m) => m,
@@ -82,6 +147,37 @@ where
             .collect()
     }
 
+    fn list_page(
+        &self,
+        tenant_id: TenantId,
+        keep: &dyn Fn(&V) -> bool,
+        order: &dyn Fn(&V, &V) -> Ordering,
+        start: PageStart<'_, V>,
+        limit: usize,
+    ) -> ListPage<V> {
+        let map = match self.inner.read() {
+            Ok(m) => m,
+            Err(_) => {
+                return ListPage {
+                    items: vec![],
+                    total: 0,
+                    offset: 0,
+                }
+            }
+        };
+
+        let records: Vec<&V> = map
+            .iter()
+            .filter_map(|((t, _k), v)| (*t == tenant_id && keep(v)).then_some(v))
+            .collect();
+        let page = select_page(records, &|a, b| order(a, b), &|v| start.accepts(v), start.offset(), limit);
+        ListPage {
+            items: page.items.into_iter().cloned().collect(),
+            total: page.total,
+            offset: page.offset,
+        }
+    }
+
     fn clear_tenant(&self, tenant_id: TenantId) {
         if let Ok(mut map) = self.inner.write() {
             map.retain(|(t, _k), _v| *t != tenant_id);
@@ -89,4 +185,54 @@ where
     }
 }
 
+impl<V> PageStart<'_, V> {
+    fn accepts(&self, record: &V) -> bool {
+        match self {
+            PageStart::Offset(_) => true,
+            PageStart::After(after) => after(record),
+        }
+    }
 
+    fn offset(&self) -> usize {
+        match self {
+            PageStart::Offset(offset) => *offset,
+            PageStart::After(_) => 0,
+        }
+    }
+}
+
+/// One page of in-memory `records`, selected as [`TenantStore::list_page`] does (for read
+/// models that hold a list inside a single record).
+pub fn page_records<V>(
+    records: Vec<V>,
+    keep: &dyn Fn(&V) -> bool,
+    order: &dyn Fn(&V, &V) -> Ordering,
+    start: PageStart<'_, V>,
+    limit: usize,
+) -> ListPage<V> {
+    let records: Vec<V> = records.into_iter().filter(|v| keep(v)).collect();
+    select_page(records, &|a, b| order(a, b), &|v| start.accepts(v), start.offset(), limit)
+}
+
+/// Page already-filtered `records`: drop those `accepts` rejects (counting them into the
+/// page offset), skip `skip` more, and keep the next `limit` in `order`. Only the page
+/// itself is sorted.
+fn select_page<T>(
+    mut records: Vec<T>,
+    order: &dyn Fn(&T, &T) -> Ordering,
+    accepts: &dyn Fn(&T) -> bool,
+    skip: usize,
+    limit: usize,
+) -> ListPage<T> {
+    let total = records.len();
+    records.retain(|r| accepts(r));
+    let offset = total - records.len() + skip;
+    let end = skip.saturating_add(limit);
+    if end < records.len() {
+        records.select_nth_unstable_by(end, |a, b| order(a, b));
+        records.truncate(end);
+    }
+    records.sort_by(|a, b| order(a, b));
+    let items = records.into_iter().skip(skip).collect();
+    ListPage { items, total, offset }
+}
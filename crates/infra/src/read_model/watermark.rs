@@ -0,0 +1,34 @@
+//! Change watermarks for polling clients.
+//!
+//! Each read model entry records the sequence number and storage time of the last
+//! event applied to it. A tenant's watermark is the maximum over its entries, so a
+//! client can compare it with the one it cached and only refetch when it moved.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Last change applied to a read model entry (or the maximum over a tenant).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Watermark {
+    /// Highest stream sequence number applied.
+    pub last_sequence: u64,
+    /// Storage time of the most recently applied event.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Watermark {
+    pub fn new(last_sequence: u64, updated_at: DateTime<Utc>) -> Self {
+        Self {
+            last_sequence,
+            updated_at: Some(updated_at),
+        }
+    }
+
+    /// Field-wise maximum of `watermarks` (the default watermark when empty).
+    pub fn max_of(watermarks: impl IntoIterator<Item = Watermark>) -> Self {
+        watermarks.into_iter().fold(Self::default(), |acc, w| Self {
+            last_sequence: acc.last_sequence.max(w.last_sequence),
+            updated_at: acc.updated_at.max(w.updated_at),
+        })
+    }
+}
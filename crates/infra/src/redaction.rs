@@ -0,0 +1,181 @@
+//! Payload redaction for logs and realtime (SSE) broadcasts.
+//!
+//! Stored events are never modified: redaction only applies to the copies that
+//! leave the core through observability channels. Rules are declarative, keyed by
+//! aggregate type + payload variant, and list dotted field paths inside the
+//! event body (e.g. `contact.email`).
+
+use std::collections::HashMap;
+
+use forgeerp_events::EventEnvelope;
+use serde_json::Value as JsonValue;
+
+/// Replacement value for redacted fields.
+pub const REDACTED: &str = "[redacted]";
+
+/// Fields to mask for one internal event.
+#[derive(Debug, Clone, Copy)]
+pub struct RedactionRule {
+    /// Aggregate type of the source stream (e.g. `parties.party`).
+    pub aggregate_type: &'static str,
+    /// Payload variant of the internal event enum (e.g. `PartyRegistered`).
+    pub variant: &'static str,
+    /// Dotted paths inside the event body.
+    pub fields: &'static [&'static str],
+}
+
+/// Registry of redaction rules.
+#[derive(Debug, Clone)]
+pub struct PayloadRedactor {
+    rules: HashMap<(&'static str, &'static str), &'static [&'static str]>,
+}
+
+impl PayloadRedactor {
+    /// Redactor without rules (payloads pass through unchanged).
+    pub fn new() -> Self {
+        Self {
+            rules: HashMap::new(),
+        }
+    }
+
+    /// Register (or replace) the rule for an internal event.
+    pub fn register(mut self, rule: RedactionRule) -> Self {
+        self.rules
+            .insert((rule.aggregate_type, rule.variant), rule.fields);
+        self
+    }
+
+    /// Redacted copy of an envelope payload.
+    pub fn redact_envelope(&self, envelope: &EventEnvelope<JsonValue>) -> JsonValue {
+        self.redact(envelope.aggregate_type(), envelope.payload())
+    }
+
+    /// Redacted copy of an externally-tagged event payload (`{ "Variant": { .. } }`).
+    pub fn redact(&self, aggregate_type: &str, payload: &JsonValue) -> JsonValue {
+        let mut out = payload.clone();
+        let Some(obj) = out.as_object_mut() else {
+            return out;
+        };
+
+        for (variant, body) in obj.iter_mut() {
+            if let Some(fields) = self.rules.get(&(aggregate_type, variant.as_str())) {
+                for path in *fields {
+                    mask_path(body, path);
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Default for PayloadRedactor {
+    /// Redactor with the PII rules for the current domain events.
+    fn default() -> Self {
+        const PARTY_CONTACT: &[&str] = &["contact.email", "contact.phone", "contact.address"];
+
+        Self::new()
+            .register(RedactionRule {
+                aggregate_type: "parties.party",
+                variant: "PartyRegistered",
+                fields: PARTY_CONTACT,
+            })
+            .register(RedactionRule {
+                aggregate_type: "parties.party",
+                variant: "PartyUpdated",
+                fields: PARTY_CONTACT,
+            })
+            .register(RedactionRule {
+                aggregate_type: "auth.user",
+                variant: "UserCreated",
+                fields: &["email"],
+            })
+    }
+}
+
+/// Mask a (non-null) value at a dotted path; missing paths are ignored.
+fn mask_path(value: &mut JsonValue, path: &str) {
+    let mut current = value;
+    let mut segments = path.split('.').peekable();
+    while let Some(segment) = segments.next() {
+        let Some(next) = current.get_mut(segment) else {
+            return;
+        };
+        if segments.peek().is_none() {
+            if !next.is_null() {
+                *next = JsonValue::String(REDACTED.to_string());
+            }
+            return;
+        }
+        current = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use forgeerp_core::{AggregateId, TenantId};
+    use forgeerp_events::{EventBus, InMemoryEventBus};
+    use forgeerp_parties::{ContactInfo, Party, PartyCommand, PartyId, PartyKind, RegisterParty};
+
+    use super::*;
+    use crate::command_dispatcher::CommandDispatcher;
+    use crate::event_store::{EventStore, InMemoryEventStore};
+
+    #[test]
+    fn party_registered_broadcast_masks_email_but_store_keeps_it() {
+        let store = Arc::new(InMemoryEventStore::new());
+        let bus: Arc<InMemoryEventBus<EventEnvelope<JsonValue>>> = Arc::new(InMemoryEventBus::new());
+        let sub = bus.subscribe();
+        let dispatcher = CommandDispatcher::new(store.clone(), bus.clone());
+
+        let tenant_id = TenantId::new();
+        let party_id = PartyId::new(AggregateId::new());
+        dispatcher
+            .dispatch(
+                tenant_id,
+                party_id.0,
+                "parties.party",
+                PartyCommand::RegisterParty(RegisterParty {
+                    tenant_id,
+                    party_id,
+                    kind: PartyKind::Customer,
+                    name: "Acme".to_string(),
+                    contact: Some(ContactInfo {
+                        email: Some("billing@acme.test".to_string()),
+                        phone: None,
+                        address: Some("1 Main St".to_string()),
+                    }),
+                    occurred_at: Utc::now(),
+                }),
+                |_, id| Party::empty(PartyId::new(id)),
+            )
+            .unwrap();
+
+        let env = sub.try_recv().unwrap();
+        let redacted = PayloadRedactor::default().redact_envelope(&env);
+        let contact = &redacted["PartyRegistered"]["contact"];
+        assert_eq!(contact["email"], REDACTED);
+        assert_eq!(contact["address"], REDACTED);
+        assert!(contact["phone"].is_null());
+        assert_eq!(redacted["PartyRegistered"]["name"], "Acme");
+
+        let stored = store.load_stream(tenant_id, party_id.0).unwrap();
+        assert_eq!(
+            stored[0].payload["PartyRegistered"]["contact"]["email"],
+            "billing@acme.test"
+        );
+        assert_eq!(env.payload()["PartyRegistered"]["contact"]["email"], "billing@acme.test");
+    }
+
+    #[test]
+    fn events_without_rules_pass_through() {
+        let payload = serde_json::json!({ "PartySuspended": { "reason": "late payments" } });
+        let redactor = PayloadRedactor::default();
+        assert_eq!(redactor.redact("parties.party", &payload), payload);
+        // Rules are scoped to the aggregate type.
+        let registered = serde_json::json!({ "PartyRegistered": { "contact": { "email": "a@b.c" } } });
+        assert_eq!(redactor.redact("inventory.item", &registered), registered);
+    }
+}
@@ -0,0 +1,256 @@
+//! Opt-in log of commands the domain rejected.
+//!
+//! Only committed events reach the event store, so a command that fails validation or
+//! breaks an invariant leaves no trace. When a [`RejectedCommandLog`] is attached to the
+//! [`CommandDispatcher`](crate::command_dispatcher::CommandDispatcher), each such rejection
+//! is kept here (command type, tenant, principal, error, time) for admins to inspect:
+//! misbehaving clients and probing show up without touching the event stream. The log
+//! is bounded; once full, the oldest entries make room for new ones.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use forgeerp_auth::PrincipalId;
+use forgeerp_core::{AggregateId, TenantId};
+
+use crate::clock::{Clock, SystemClock};
+use crate::command_dispatcher::DispatchError;
+
+/// One rejected command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RejectedCommand {
+    pub entry_id: Uuid,
+    pub tenant_id: TenantId,
+    pub aggregate_type: String,
+    pub aggregate_id: AggregateId,
+    /// Command variant name, e.g. `AdjustStock`.
+    pub command_type: String,
+    /// Who sent it, when dispatched on behalf of an authenticated request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub principal: Option<PrincipalId>,
+    /// `validation` or `invariant_violation`.
+    pub error_kind: String,
+    pub error: String,
+    pub rejected_at: DateTime<Utc>,
+}
+
+/// Filters for [`RejectedCommandLog::list`]; unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct RejectedCommandFilter {
+    pub command_type: Option<String>,
+    pub principal: Option<PrincipalId>,
+    /// Entries recorded at or after this time.
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl RejectedCommandFilter {
+    fn matches(&self, entry: &RejectedCommand) -> bool {
+        self.command_type.as_deref().is_none_or(|t| entry.command_type == t)
+            && self.principal.is_none_or(|p| entry.principal == Some(p))
+            && self.since.is_none_or(|since| entry.rejected_at >= since)
+    }
+}
+
+/// Bounded, in-memory log of rejected commands (all tenants).
+pub struct RejectedCommandLog {
+    capacity: usize,
+    clock: Arc<dyn Clock>,
+    /// Oldest first.
+    entries: Mutex<VecDeque<RejectedCommand>>,
+}
+
+impl std::fmt::Debug for RejectedCommandLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RejectedCommandLog")
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RejectedCommandLog {
+    /// Log keeping at most `capacity` entries (at least one).
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            clock: Arc::new(SystemClock),
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn arc(capacity: usize) -> Arc<Self> {
+        Arc::new(Self::new(capacity))
+    }
+
+    /// Log sized by `REJECTED_COMMAND_LOG_CAPACITY`; `None` (logging off) when unset or 0.
+    pub fn from_env() -> Option<Arc<Self>> {
+        std::env::var("REJECTED_COMMAND_LOG_CAPACITY")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|capacity| *capacity > 0)
+            .map(Self::arc)
+    }
+
+    /// Time source for `rejected_at` (defaults to the system clock).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Record `error` if it is a domain rejection (validation or invariant failure), stamped
+    /// with the current time and evicting the oldest entry when full; other errors are ignored.
+    pub fn record(
+        &self,
+        tenant_id: TenantId,
+        aggregate_type: &str,
+        aggregate_id: AggregateId,
+        command_type: impl Into<String>,
+        principal: Option<PrincipalId>,
+        error: &DispatchError,
+    ) -> Option<RejectedCommand> {
+        let (error_kind, message) = match error {
+            DispatchError::Rejected { error, .. } => {
+                return self.record(tenant_id, aggregate_type, aggregate_id, command_type, principal, error);
+            }
+            DispatchError::Validation(msg) => ("validation", msg),
+            DispatchError::InvariantViolation(msg) => ("invariant_violation", msg),
+            _ => return None,
+        };
+        let entry = RejectedCommand {
+            entry_id: Uuid::now_v7(),
+            tenant_id,
+            aggregate_type: aggregate_type.to_string(),
+            aggregate_id,
+            command_type: command_type.into(),
+            principal,
+            error_kind: error_kind.to_string(),
+            error: message.clone(),
+            rejected_at: self.clock.now(),
+        };
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        while entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry.clone());
+        Some(entry)
+    }
+
+    /// A tenant's entries matching `filter`, newest first, at most `limit`.
+    pub fn list(&self, tenant_id: TenantId, filter: &RejectedCommandFilter, limit: usize) -> Vec<RejectedCommand> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .iter()
+            .rev()
+            .filter(|e| e.tenant_id == tenant_id && filter.matches(e))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Variant name of a command from its `Debug` form (`AdjustStock(AdjustStock { .. })` → `AdjustStock`).
+pub fn command_type_name<C: std::fmt::Debug>(command: &C) -> String {
+    let debug = format!("{command:?}");
+    let end = debug
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(debug.len());
+    debug[..end].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forgeerp_events::{EventEnvelope, InMemoryEventBus};
+    use forgeerp_inventory::{AdjustStock, CreateItem, DEFAULT_LOCATION, InventoryCommand, InventoryItem, InventoryItemId};
+    use serde_json::Value as JsonValue;
+
+    use crate::command_dispatcher::{with_command_principal, CommandDispatcher};
+    use crate::event_store::InMemoryEventStore;
+
+    type Bus = InMemoryEventBus<EventEnvelope<JsonValue>>;
+
+    #[test]
+    fn rejected_adjustment_is_logged_and_accepted_one_is_not() {
+        let log = RejectedCommandLog::arc(100);
+        let dispatcher = CommandDispatcher::new(Arc::new(InMemoryEventStore::new()), Arc::new(Bus::new()))
+            .with_rejected_command_log(log.clone());
+        let tenant_id = TenantId::new();
+        let item_id = InventoryItemId::new(AggregateId::new());
+        let principal = PrincipalId::new();
+        let dispatch = |command: InventoryCommand| {
+            dispatcher.dispatch::<InventoryItem>(tenant_id, item_id.0, "inventory.item", command, |_, id| {
+                InventoryItem::empty(InventoryItemId::new(id))
+            })
+        };
+        let adjust = |delta: i64| {
+            InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id,
+                item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta,
+                occurred_at: Utc::now(),
+            })
+        };
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let (accepted, rejected) = runtime.block_on(with_command_principal(principal, async {
+            dispatch(InventoryCommand::CreateItem(CreateItem {
+                tenant_id,
+                item_id,
+                name: "Widget".to_string(),
+                occurred_at: Utc::now(),
+            }))
+            .unwrap();
+            (dispatch(adjust(5)), dispatch(adjust(-10)))
+        }));
+        assert!(accepted.is_ok());
+        assert!(matches!(rejected, Err(DispatchError::InvariantViolation(_))));
+
+        let entries = log.list(tenant_id, &RejectedCommandFilter::default(), 10);
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.command_type, "AdjustStock");
+        assert_eq!((entry.aggregate_type.as_str(), entry.aggregate_id), ("inventory.item", item_id.0));
+        assert_eq!(entry.principal, Some(principal));
+        assert_eq!(entry.error_kind, "invariant_violation");
+        assert!(entry.error.contains("negative"), "{}", entry.error);
+
+        assert!(log.list(TenantId::new(), &RejectedCommandFilter::default(), 10).is_empty());
+    }
+
+    #[test]
+    fn full_log_evicts_the_oldest_entries() {
+        let log = RejectedCommandLog::new(2);
+        let tenant_id = TenantId::new();
+        let error = DispatchError::Validation("bad".to_string());
+        for command in ["CreateItem", "AdjustStock", "ReserveStock"] {
+            log.record(tenant_id, "inventory.item", AggregateId::new(), command, None, &error);
+        }
+        // Infrastructure failures are not rejections.
+        let conflict = DispatchError::Concurrency("stale".to_string());
+        assert!(log.record(tenant_id, "inventory.item", AggregateId::new(), "AdjustStock", None, &conflict).is_none());
+
+        let kept: Vec<_> = log
+            .list(tenant_id, &RejectedCommandFilter::default(), 10)
+            .into_iter()
+            .map(|e| e.command_type)
+            .collect();
+        assert_eq!(kept, ["ReserveStock", "AdjustStock"]);
+        let filtered = log.list(
+            tenant_id,
+            &RejectedCommandFilter {
+                command_type: Some("AdjustStock".to_string()),
+                ..Default::default()
+            },
+            10,
+        );
+        assert_eq!(filtered.len(), 1);
+    }
+}
@@ -0,0 +1,331 @@
+//! Invoice → Ledger posting handler.
+//!
+//! Posts a balanced journal entry to the tenant's default ledger for every invoice
+//! event that moves money:
+//!
+//! - `InvoiceIssued`     → Dr receivable / Cr revenue (`total_amount`)
+//! - `PaymentRegistered` → Dr cash / Cr receivable (`amount`)
+//! - `CreditNoteIssued`  → Dr revenue / Cr receivable (`amount`)
+//! - `PaymentReversed`   → Dr receivable / Cr cash (`amount`)
+//!
+//! Accounts come from the tenant's [`LedgerAccounts`] and the entry carries the invoice's
+//! currency (a reversal has none and stays in the ledger's). The journal entry id is the
+//! source event id and the ledger ignores entry ids it has already posted, so a
+//! redelivered event posts at most once. The default ledger's id is derived from the
+//! tenant ([`LedgerId::default_for`]), so this also holds across restarts.
+
+use std::sync::Arc;
+
+use forgeerp_accounting::{JournalCommand, JournalEntryLine, Ledger, LedgerId, PostJournalEntry};
+use forgeerp_events::{EventBus, EventEnvelope};
+use forgeerp_invoicing::InvoiceEvent;
+use serde_json::Value as JsonValue;
+
+use crate::command_dispatcher::{with_business_key_of, with_correlation_of, CommandDispatcher, DispatchError};
+use crate::event_store::EventStore;
+use crate::projections::tenant_settings::TenantSettingsProjection;
+use crate::tenant_settings::LedgerAccounts;
+
+/// Result of handling one event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostingOutcome {
+    /// A journal entry was posted.
+    Posted,
+    /// The event had been posted before; nothing was appended.
+    AlreadyPosted,
+    /// Not an invoice event that moves money.
+    Skipped,
+}
+
+/// Posts invoice and payment events to the default ledger.
+pub struct InvoiceLedgerPosting<S, B> {
+    dispatcher: Arc<CommandDispatcher<S, B>>,
+    settings: Arc<TenantSettingsProjection>,
+}
+
+impl<S, B> InvoiceLedgerPosting<S, B>
+where
+    S: EventStore,
+    B: EventBus<EventEnvelope<JsonValue>>,
+{
+    pub fn new(dispatcher: Arc<CommandDispatcher<S, B>>, settings: Arc<TenantSettingsProjection>) -> Self {
+        Self { dispatcher, settings }
+    }
+
+    /// Post the journal entry for `envelope`, if it has one.
+    pub fn handle(&self, envelope: &EventEnvelope<JsonValue>) -> Result<PostingOutcome, DispatchError> {
+        let Some(cmd) = self.journal_entry(envelope)? else {
+            return Ok(PostingOutcome::Skipped);
+        };
+
+        let committed = with_correlation_of(envelope, || {
+            with_business_key_of(envelope, || {
+                self.dispatcher.dispatch::<Ledger>(
+                    cmd.tenant_id,
+                    cmd.ledger_id.0,
+                    "accounting.ledger",
+                    JournalCommand::PostJournalEntry(cmd),
+                    |_, id| Ledger::empty(LedgerId::new(id)),
+                )
+            })
+        })?;
+        Ok(if committed.is_empty() {
+            PostingOutcome::AlreadyPosted
+        } else {
+            PostingOutcome::Posted
+        })
+    }
+
+    /// Journal entry for an invoice event (`None` for other events and zero amounts).
+    pub fn journal_entry(&self, envelope: &EventEnvelope<JsonValue>) -> Result<Option<PostJournalEntry>, DispatchError> {
+        if envelope.aggregate_type() != "invoicing.invoice" {
+            return Ok(None);
+        }
+        let event: InvoiceEvent = serde_json::from_value(envelope.payload().clone())
+            .map_err(|e| DispatchError::Deserialize(e.to_string()))?;
+
+        let tenant_id = envelope.tenant_id();
+        let LedgerAccounts {
+            receivable,
+            revenue,
+            cash,
+        } = self.settings.get(tenant_id).ledger_accounts;
+
+        let (debit, credit, amount, currency, occurred_at, description) = match event {
+            InvoiceEvent::InvoiceIssued(e) => (
+                receivable,
+                revenue,
+                e.total_amount,
+                e.currency,
+                e.occurred_at,
+                format!("Invoice {} issued", e.invoice_id),
+            ),
+            InvoiceEvent::PaymentRegistered(e) => (
+                cash,
+                receivable,
+                e.amount,
+                e.currency,
+                e.occurred_at,
+                format!("Payment for invoice {}", e.invoice_id),
+            ),
+            InvoiceEvent::InvoiceVoided(_) => return Ok(None),
+            InvoiceEvent::PaymentReversed(e) => (
+                receivable,
+                cash,
+                e.amount,
+                None,
+                e.occurred_at,
+                format!("Payment reversal for invoice {}", e.invoice_id),
+            ),
+            InvoiceEvent::CreditNoteIssued(e) => (
+                revenue,
+                receivable,
+                e.amount,
+                e.currency,
+                e.occurred_at,
+                format!("Credit note for invoice {}", e.invoice_id),
+            ),
+        };
+        if amount == 0 {
+            return Ok(None);
+        }
+        let amount = i64::try_from(amount).map_err(|_| DispatchError::Validation("amount out of range".to_string()))?;
+
+        Ok(Some(PostJournalEntry {
+            tenant_id,
+            ledger_id: LedgerId::default_for(tenant_id),
+            entry_id: envelope.event_id(),
+            lines: vec![
+                JournalEntryLine {
+                    account: debit,
+                    amount,
+                    is_debit: true,
+                },
+                JournalEntryLine {
+                    account: credit,
+                    amount,
+                    is_debit: false,
+                },
+            ],
+            currency,
+            occurred_at,
+            description: Some(description),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use forgeerp_core::{AggregateId, TenantId};
+    use forgeerp_events::{InMemoryEventBus, Subscription};
+    use forgeerp_invoicing::{
+        Invoice, InvoiceCommand, InvoiceId, InvoiceLine, IssueInvoice, PaymentId, RegisterPayment,
+    };
+    use forgeerp_products::ProductId;
+    use forgeerp_sales::SalesOrderId;
+
+    use super::*;
+    use crate::event_store::InMemoryEventStore;
+    use crate::projections::accounting::{AccountBalance, AccountBalancesProjection};
+    use crate::read_model::InMemoryTenantStore;
+    use crate::tenant_settings::{change_settings, keys, SetSetting, TenantSettingsCommand};
+
+    type Bus = InMemoryEventBus<EventEnvelope<JsonValue>>;
+
+    struct Fixture {
+        store: Arc<InMemoryEventStore>,
+        dispatcher: Arc<CommandDispatcher<Arc<InMemoryEventStore>, Arc<Bus>>>,
+        posting: InvoiceLedgerPosting<Arc<InMemoryEventStore>, Arc<Bus>>,
+        sub: Subscription<EventEnvelope<JsonValue>>,
+        balances: AccountBalancesProjection<Arc<InMemoryTenantStore<String, AccountBalance>>>,
+        tenant_id: TenantId,
+        invoice_id: InvoiceId,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let store = Arc::new(InMemoryEventStore::new());
+            let bus: Arc<Bus> = Arc::new(InMemoryEventBus::new());
+            let sub = bus.subscribe();
+            let dispatcher = Arc::new(CommandDispatcher::new(store.clone(), bus));
+            let posting = InvoiceLedgerPosting::new(dispatcher.clone(), TenantSettingsProjection::arc());
+            Self {
+                store,
+                dispatcher,
+                posting,
+                sub,
+                balances: AccountBalancesProjection::new(Arc::new(InMemoryTenantStore::new())),
+                tenant_id: TenantId::new(),
+                invoice_id: InvoiceId::new(AggregateId::new()),
+            }
+        }
+
+        fn invoice(&self, command: InvoiceCommand) -> EventEnvelope<JsonValue> {
+            self.dispatcher
+                .dispatch(self.tenant_id, self.invoice_id.0, "invoicing.invoice", command, |_, id| {
+                    Invoice::empty(InvoiceId::new(id))
+                })
+                .unwrap();
+            self.sub.try_recv().unwrap()
+        }
+
+        fn issue(&self) -> EventEnvelope<JsonValue> {
+            let sales_order_id = SalesOrderId::new(AggregateId::new());
+            self.invoice(InvoiceCommand::IssueInvoice(IssueInvoice {
+                tenant_id: self.tenant_id,
+                invoice_id: self.invoice_id,
+                sales_order_id,
+                lines: vec![InvoiceLine {
+                    line_no: 1,
+                    sales_order_id,
+                    product_id: ProductId::new(AggregateId::new()),
+                    quantity: 3,
+                    unit_price: 2_500,
+                }],
+                payment_terms_days: 30,
+                currency: Some("EUR".to_string()),
+                occurred_at: Utc::now(),
+            }))
+        }
+
+        fn pay(&self, amount: u64) -> EventEnvelope<JsonValue> {
+            self.invoice(InvoiceCommand::RegisterPayment(RegisterPayment {
+                tenant_id: self.tenant_id,
+                invoice_id: self.invoice_id,
+                payment_id: PaymentId::new(AggregateId::new()),
+                amount,
+                currency: None,
+                occurred_at: Utc::now(),
+            }))
+        }
+
+        /// Post `env` and feed the resulting ledger events into the projection.
+        fn post(&self, env: &EventEnvelope<JsonValue>) -> PostingOutcome {
+            let outcome = self.posting.handle(env).unwrap();
+            while let Ok(ledger_env) = self.sub.try_recv() {
+                self.balances.apply_envelope(&ledger_env).unwrap();
+            }
+            outcome
+        }
+
+        fn balance(&self, code: &str) -> i128 {
+            self.balances.get(self.tenant_id, code).map(|b| b.balance).unwrap_or(0)
+        }
+    }
+
+    #[test]
+    fn issued_invoice_posts_balanced_receivable_and_revenue() {
+        let f = Fixture::new();
+        let issued = f.issue();
+
+        assert_eq!(f.post(&issued), PostingOutcome::Posted);
+        assert_eq!(f.balance("1200"), 7_500);
+        assert_eq!(f.balance("4000"), -7_500);
+        let total: i128 = f.balances.list(f.tenant_id).iter().map(|b| b.balance).sum();
+        assert_eq!(total, 0);
+    }
+
+    #[test]
+    fn registered_payment_posts_cash_against_receivable() {
+        let f = Fixture::new();
+        let issued = f.issue();
+        f.post(&issued);
+        let paid = f.pay(5_000);
+
+        let entry = f.posting.journal_entry(&paid).unwrap().unwrap();
+        assert_eq!(entry.currency.as_deref(), Some("EUR"));
+        assert_eq!(f.post(&paid), PostingOutcome::Posted);
+        assert_eq!(f.balance("1000"), 5_000);
+        assert_eq!(f.balance("1200"), 2_500);
+        assert_eq!(f.balance("4000"), -7_500);
+    }
+
+    #[test]
+    fn redelivered_event_posts_once() {
+        let f = Fixture::new();
+        let issued = f.issue();
+
+        assert_eq!(f.post(&issued), PostingOutcome::Posted);
+        assert_eq!(f.post(&issued), PostingOutcome::AlreadyPosted);
+        assert_eq!(f.balance("1200"), 7_500);
+    }
+
+    #[test]
+    fn redelivery_after_a_restart_posts_once() {
+        let f = Fixture::new();
+        let issued = f.issue();
+        assert_eq!(f.post(&issued), PostingOutcome::Posted);
+
+        // A fresh process over the same event store resolves the same default ledger.
+        let restarted = InvoiceLedgerPosting::new(
+            Arc::new(CommandDispatcher::new(f.store.clone(), Arc::new(Bus::new()))),
+            TenantSettingsProjection::arc(),
+        );
+        assert_eq!(restarted.handle(&issued).unwrap(), PostingOutcome::AlreadyPosted);
+    }
+
+    #[test]
+    fn tenant_account_mapping_is_used() {
+        let f = Fixture::new();
+        let mut accounts = f.posting.settings.get(f.tenant_id).ledger_accounts;
+        accounts.revenue.code = "4100".to_string();
+        change_settings(
+            &f.dispatcher,
+            &f.posting.settings,
+            TenantSettingsCommand::SetSetting(SetSetting {
+                tenant_id: f.tenant_id,
+                key: keys::LEDGER_ACCOUNTS.to_string(),
+                value: serde_json::to_value(accounts).unwrap(),
+                occurred_at: Utc::now(),
+            }),
+        )
+        .unwrap();
+        assert_eq!(f.sub.try_recv().unwrap().aggregate_type(), "tenant.settings");
+
+        let issued = f.issue();
+        f.post(&issued);
+        assert_eq!(f.balance("4100"), -7_500);
+        assert_eq!(f.balance("4000"), 0);
+    }
+}
@@ -1,12 +1,15 @@
 //! Saga infrastructure: persistence and command execution.
 
+pub mod invoice_ledger;
 pub mod sales_ar;
+pub mod sales_reservation;
+pub mod timeout;
 
 use forgeerp_core::{AggregateId, TenantId};
 use forgeerp_events::Saga;
 use serde_json::Value as JsonValue;
 
-use crate::event_store::{EventStore, StoredEvent, UncommittedEvent};
+use crate::event_store::{EventStore, StoredEvent, UncommittedEvent, READ_ALL_PAGE_SIZE};
 
 /// Repository for persisting saga events via the event store.
 pub struct SagaRepository<S: Saga, E: EventStore> {
@@ -27,6 +30,49 @@ impl<S: Saga, E: EventStore> SagaRepository<S, E> {
         self.event_store.load_stream(tenant_id, saga_id)
     }
 
+    /// Rebuild saga state from its event history.
+    ///
+    /// Saga events are stored as the `Emit` payload under the emitted `event_type`, which
+    /// is put back as the `type` tag before decoding; events the saga does not know
+    /// (e.g. `saga.completed`) are skipped.
+    pub fn rehydrate(
+        &self,
+        tenant_id: TenantId,
+        correlation: &S::CorrelationId,
+    ) -> Result<S::State, crate::event_store::EventStoreError> {
+        let mut state = S::initial_state(tenant_id, correlation);
+        for stored in self.load(tenant_id, S::saga_id(tenant_id, correlation))? {
+            let mut payload = stored.payload;
+            if let Some(obj) = payload.as_object_mut() {
+                obj.entry("type").or_insert_with(|| JsonValue::String(stored.event_type));
+            }
+            if let Ok(event) = serde_json::from_value::<S::SagaEvent>(payload) {
+                S::apply(&mut state, &event);
+            }
+        }
+        Ok(state)
+    }
+
+    /// Every event of this saga type named `event_type`, across tenants, in commit order.
+    pub fn events_named(&self, event_type: &str) -> Result<Vec<StoredEvent>, crate::event_store::EventStoreError> {
+        let tenants: std::collections::HashSet<TenantId> =
+            self.event_store.stream_ids()?.into_iter().map(|(tenant_id, _)| tenant_id).collect();
+        let mut found = Vec::new();
+        for tenant_id in tenants {
+            let mut after = None;
+            loop {
+                let page = self.event_store.read_all(tenant_id, after, READ_ALL_PAGE_SIZE)?;
+                let Some(last) = page.last() else { break };
+                after = Some(last.global_position);
+                found.extend(
+                    page.into_iter()
+                        .filter(|e| e.aggregate_type == S::saga_type() && e.event_type == event_type),
+                );
+            }
+        }
+        Ok(found)
+    }
+
     /// Append a saga event (Emit action).
     pub fn append_emit(
         &self,
@@ -44,6 +90,9 @@ impl<S: Saga, E: EventStore> SagaRepository<S, E> {
             event_version: 1,
             payload,
             occurred_at: chrono::Utc::now(),
+            metadata: Default::default(),
+            correlation_id: None,
+            causation_id: None,
         };
         self.event_store.append(vec![uncommitted], forgeerp_core::ExpectedVersion::Any)
     }
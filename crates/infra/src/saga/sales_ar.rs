@@ -6,13 +6,20 @@
 //! 3. Ledger posted → complete saga
 //!
 //! Compensating action: void invoice if ledger posting fails.
+//!
+//! Timeout: if the invoice is not issued within `INVOICE_ISSUE_TIMEOUT_SECS` of the
+//! order being confirmed, the saga fails and flags the order (`SalesOrder.FlagOrder`).
 
+use chrono::Duration;
 use forgeerp_core::{AggregateId, TenantId};
 use forgeerp_events::{EventEnvelope, Saga, SagaAction};
 use forgeerp_sales::SalesOrderId;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
+/// How long a confirmed order may wait for its invoice before it is flagged.
+pub const INVOICE_ISSUE_TIMEOUT_SECS: i64 = 15 * 60;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum SalesArSagaState {
@@ -30,6 +37,7 @@ pub enum SalesArSagaEvent {
     OrderConfirmedReceived,
     InvoiceIssueRequested,
     InvoiceIssuedReceived { invoice_id: String },
+    InvoiceIssueTimedOut,
     LedgerPostRequested,
     LedgerPostedReceived,
     SagaCompleted,
@@ -103,6 +111,9 @@ impl Saga for SalesArSaga {
                     invoice_id: invoice_id.clone(),
                 };
             }
+            SalesArSagaEvent::InvoiceIssueTimedOut => {
+                *state = SalesArSagaState::Failed;
+            }
             SalesArSagaEvent::LedgerPostRequested => {
                 // No state change; waiting for ledger posted
             }
@@ -222,4 +233,160 @@ impl Saga for SalesArSaga {
             SalesArSagaState::Completed | SalesArSagaState::Failed => vec![],
         }
     }
+
+    fn timeout(state: &Self::State) -> Option<Duration> {
+        match state {
+            SalesArSagaState::WaitingForInvoiceIssued => Some(Duration::seconds(INVOICE_ISSUE_TIMEOUT_SECS)),
+            _ => None,
+        }
+    }
+
+    fn on_timeout(state: &Self::State, tenant_id: TenantId, correlation: &Self::CorrelationId) -> Vec<SagaAction> {
+        if *state != SalesArSagaState::WaitingForInvoiceIssued {
+            return vec![];
+        }
+        vec![
+            SagaAction::Emit {
+                event_type: "invoice_issue_timed_out".to_string(),
+                payload: serde_json::json!({}),
+            },
+            SagaAction::Compensate {
+                aggregate_type: "SalesOrder".to_string(),
+                command_type: "FlagOrder".to_string(),
+                payload: serde_json::json!({
+                    "tenant_id": tenant_id,
+                    "order_id": correlation,
+                    "reason": format!("invoice not issued within {} minutes", INVOICE_ISSUE_TIMEOUT_SECS / 60),
+                    "occurred_at": chrono::Utc::now(),
+                }),
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::Utc;
+
+    use super::*;
+    use crate::event_store::InMemoryEventStore;
+    use crate::saga::timeout::SagaTimeouts;
+    use crate::saga::{CommandExecutor, SagaRepository};
+
+    #[derive(Default)]
+    struct RecordingExecutor {
+        executed: Mutex<Vec<(String, String, JsonValue)>>,
+    }
+
+    impl CommandExecutor for RecordingExecutor {
+        type Error = ();
+
+        fn execute(&self, _: TenantId, aggregate_type: &str, command_type: &str, payload: &JsonValue) -> Result<(), ()> {
+            self.executed
+                .lock()
+                .unwrap()
+                .push((aggregate_type.to_string(), command_type.to_string(), payload.clone()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn order_whose_invoice_never_arrives_is_flagged_after_the_timeout() {
+        let repo = SagaRepository::<SalesArSaga, _>::new(Arc::new(InMemoryEventStore::new()));
+        let executor = RecordingExecutor::default();
+        let timeouts = SagaTimeouts::<SalesArSaga>::new();
+        let tenant_id = TenantId::new();
+        let order_id = SalesOrderId::new(AggregateId::new());
+        let saga_id = SalesArSaga::saga_id(tenant_id, &order_id);
+        let confirmed_at = Utc::now();
+
+        repo.append_emit(tenant_id, saga_id, "order_confirmed_received", serde_json::json!({}))
+            .unwrap();
+        let state = repo.rehydrate(tenant_id, &order_id).unwrap();
+        assert_eq!(state, SalesArSagaState::WaitingForInvoiceIssued);
+        timeouts.track(&repo, tenant_id, &order_id, &state, confirmed_at);
+
+        let timeout = Duration::seconds(INVOICE_ISSUE_TIMEOUT_SECS);
+        assert!(timeouts.fire_expired(&repo, &executor, confirmed_at + timeout - Duration::seconds(1)).is_empty());
+
+        let fired = timeouts.fire_expired(&repo, &executor, confirmed_at + timeout);
+        assert_eq!(fired, [(tenant_id, saga_id)]);
+        let executed = executor.executed.lock().unwrap();
+        match &executed[..] {
+            [(aggregate_type, command_type, payload)] => {
+                assert_eq!((aggregate_type.as_str(), command_type.as_str()), ("SalesOrder", "FlagOrder"));
+                let flag: forgeerp_sales::FlagOrder = serde_json::from_value(payload.clone()).unwrap();
+                assert_eq!((flag.tenant_id, flag.order_id), (tenant_id, order_id));
+            }
+            other => panic!("expected one FlagOrder, got {other:?}"),
+        }
+        let history = repo.load(tenant_id, saga_id).unwrap();
+        assert_eq!(history.last().unwrap().event_type, "invoice_issue_timed_out");
+        assert_eq!(repo.rehydrate(tenant_id, &order_id).unwrap(), SalesArSagaState::Failed);
+        assert_eq!(timeouts.next_timeout_at(), None);
+    }
+
+    #[test]
+    fn deadline_armed_before_a_restart_still_fires() {
+        let store = Arc::new(InMemoryEventStore::new());
+        let repo = SagaRepository::<SalesArSaga, _>::new(store.clone());
+        let executor = RecordingExecutor::default();
+        let tenant_id = TenantId::new();
+        let (waiting, moved_on) = (SalesOrderId::new(AggregateId::new()), SalesOrderId::new(AggregateId::new()));
+        let confirmed_at = Utc::now();
+        let timeout = Duration::seconds(INVOICE_ISSUE_TIMEOUT_SECS);
+
+        let before_restart = SagaTimeouts::<SalesArSaga>::new();
+        for order_id in [waiting, moved_on] {
+            repo.append_emit(tenant_id, SalesArSaga::saga_id(tenant_id, &order_id), "order_confirmed_received", serde_json::json!({}))
+                .unwrap();
+            let state = repo.rehydrate(tenant_id, &order_id).unwrap();
+            before_restart.track(&repo, tenant_id, &order_id, &state, confirmed_at);
+        }
+        repo.append_emit(
+            tenant_id,
+            SalesArSaga::saga_id(tenant_id, &moved_on),
+            "invoice_issued_received",
+            serde_json::json!({ "invoice_id": "inv-1" }),
+        )
+        .unwrap();
+        // Re-tracking the same state records nothing new.
+        before_restart.track(&repo, tenant_id, &waiting, &SalesArSagaState::WaitingForInvoiceIssued, Utc::now());
+        drop(before_restart);
+
+        // Neither saga reacts again after the restart.
+        let repo = SagaRepository::<SalesArSaga, _>::new(store);
+        let timeouts = SagaTimeouts::<SalesArSaga>::new();
+        assert_eq!(timeouts.restore(&repo).unwrap(), 1);
+        assert_eq!(timeouts.next_timeout_at(), Some(confirmed_at + timeout));
+        assert!(timeouts.fire_expired(&repo, &executor, confirmed_at + timeout - Duration::seconds(1)).is_empty());
+
+        let fired = timeouts.fire_expired(&repo, &executor, confirmed_at + timeout);
+        assert_eq!(fired, [(tenant_id, SalesArSaga::saga_id(tenant_id, &waiting))]);
+        assert_eq!(executor.executed.lock().unwrap().len(), 1);
+        assert_eq!(repo.rehydrate(tenant_id, &waiting).unwrap(), SalesArSagaState::Failed);
+    }
+
+    #[test]
+    fn saga_that_moved_on_before_its_deadline_does_not_time_out() {
+        let repo = SagaRepository::<SalesArSaga, _>::new(Arc::new(InMemoryEventStore::new()));
+        let executor = RecordingExecutor::default();
+        let timeouts = SagaTimeouts::<SalesArSaga>::new();
+        let tenant_id = TenantId::new();
+        let order_id = SalesOrderId::new(AggregateId::new());
+        let saga_id = SalesArSaga::saga_id(tenant_id, &order_id);
+        let now = Utc::now();
+
+        repo.append_emit(tenant_id, saga_id, "order_confirmed_received", serde_json::json!({}))
+            .unwrap();
+        timeouts.track(&repo, tenant_id, &order_id, &SalesArSagaState::WaitingForInvoiceIssued, now);
+        repo.append_emit(tenant_id, saga_id, "invoice_issued_received", serde_json::json!({ "invoice_id": "inv-1" }))
+            .unwrap();
+
+        let later = now + Duration::seconds(INVOICE_ISSUE_TIMEOUT_SECS + 1);
+        assert!(timeouts.fire_expired(&repo, &executor, later).is_empty());
+        assert!(executor.executed.lock().unwrap().is_empty());
+    }
 }
@@ -0,0 +1,461 @@
+//! Sales order → stock reservation saga.
+//!
+//! Keeps confirmed sales orders from overselling:
+//!
+//! - `OrderConfirmed` → `ReserveStock` on the inventory item linked to each line's product
+//!   (quantities summed per item). The reservation id is the order id.
+//! - `OrderCancelled` of a confirmed order → `ReleaseReservation` on the same items.
+//!
+//! When an item has too little available stock, the tenant's [`ShortStockPolicy`] decides:
+//! fail the confirmation (release everything reserved for the order, then cancel it) or
+//! keep the order and mark the short lines backordered.
+//!
+//! Reservation ids are single-use and cancelling is idempotent, so a redelivered event
+//! neither reserves twice nor re-reserves stock that was released.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use forgeerp_core::{AggregateId, TenantId};
+use forgeerp_events::{EventBus, EventEnvelope};
+use forgeerp_inventory::{
+    InventoryCommand, InventoryItem, InventoryItemId, ReleaseReservation, ReserveStock, INSUFFICIENT_AVAILABLE_STOCK,
+    UNKNOWN_RESERVATION,
+};
+use forgeerp_products::ProductId;
+use forgeerp_sales::{
+    CancelOrder, MarkBackordered, ORDER_ALREADY_CANCELLED, OrderLine, SalesOrder, SalesOrderCommand, SalesOrderEvent,
+    SalesOrderId,
+};
+use serde_json::Value as JsonValue;
+
+use crate::command_dispatcher::{with_business_key_of, with_correlation_of, CommandDispatcher, DispatchError};
+use crate::event_store::EventStore;
+use crate::projections::tenant_settings::TenantSettingsProjection;
+use crate::tenant_settings::ShortStockPolicy;
+
+/// Reason recorded on orders cancelled for lack of stock.
+pub const INSUFFICIENT_STOCK: &str = "insufficient stock";
+
+/// Resolves the inventory item holding a product's stock (`None`: not stocked).
+pub trait InventoryItemLookup: Send + Sync {
+    fn inventory_item(&self, tenant_id: TenantId, product_id: ProductId) -> Option<InventoryItemId>;
+}
+
+impl<F> InventoryItemLookup for F
+where
+    F: Fn(TenantId, ProductId) -> Option<InventoryItemId> + Send + Sync,
+{
+    fn inventory_item(&self, tenant_id: TenantId, product_id: ProductId) -> Option<InventoryItemId> {
+        self(tenant_id, product_id)
+    }
+}
+
+/// Result of handling one event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReservationOutcome {
+    /// Stock for every stocked line is reserved.
+    Reserved,
+    /// Short lines were flagged backordered; the other lines are reserved.
+    Backordered { line_nos: Vec<u32> },
+    /// Not enough stock: reservations were released and the order cancelled.
+    Rejected { line_nos: Vec<u32> },
+    /// A cancelled order's reservations were released.
+    Released,
+    /// Not a sales order event that reserves or releases stock.
+    Skipped,
+}
+
+/// Reserves stock for confirmed sales orders and releases it when they are cancelled.
+pub struct SalesStockReservation<S, B> {
+    dispatcher: Arc<CommandDispatcher<S, B>>,
+    settings: Arc<TenantSettingsProjection>,
+    items: Arc<dyn InventoryItemLookup>,
+}
+
+/// Stocked lines of an order grouped by inventory item.
+struct ItemDemand {
+    item_id: InventoryItemId,
+    quantity: i64,
+    line_nos: Vec<u32>,
+}
+
+impl<S, B> SalesStockReservation<S, B>
+where
+    S: EventStore,
+    B: EventBus<EventEnvelope<JsonValue>>,
+{
+    pub fn new(
+        dispatcher: Arc<CommandDispatcher<S, B>>,
+        settings: Arc<TenantSettingsProjection>,
+        items: Arc<dyn InventoryItemLookup>,
+    ) -> Self {
+        Self {
+            dispatcher,
+            settings,
+            items,
+        }
+    }
+
+    /// Reserve or release stock for `envelope`, if it is a confirmation or cancellation.
+    pub fn handle(&self, envelope: &EventEnvelope<JsonValue>) -> Result<ReservationOutcome, DispatchError> {
+        if envelope.aggregate_type() != "sales.order" {
+            return Ok(ReservationOutcome::Skipped);
+        }
+        let event: SalesOrderEvent = serde_json::from_value(envelope.payload().clone())
+            .map_err(|e| DispatchError::Deserialize(e.to_string()))?;
+
+        with_correlation_of(envelope, || {
+            with_business_key_of(envelope, || match event {
+                SalesOrderEvent::OrderConfirmed(e) => self.reserve(e.tenant_id, e.order_id, &e.lines, e.occurred_at),
+                SalesOrderEvent::OrderCancelled(e) if e.was_confirmed => {
+                    self.release(e.tenant_id, e.order_id, &e.lines, e.occurred_at)?;
+                    Ok(ReservationOutcome::Released)
+                }
+                _ => Ok(ReservationOutcome::Skipped),
+            })
+        })
+    }
+
+    fn reserve(
+        &self,
+        tenant_id: TenantId,
+        order_id: SalesOrderId,
+        lines: &[OrderLine],
+        occurred_at: DateTime<Utc>,
+    ) -> Result<ReservationOutcome, DispatchError> {
+        let mut short_lines = Vec::new();
+        for demand in self.demand(tenant_id, lines) {
+            let reserve = InventoryCommand::ReserveStock(ReserveStock {
+                tenant_id,
+                item_id: demand.item_id,
+                reservation_id: reservation_id(order_id),
+                quantity: demand.quantity,
+                occurred_at,
+            });
+            match self.inventory(tenant_id, demand.item_id, reserve) {
+                Ok(()) => {}
+                Err(e) if e.code() == Some(INSUFFICIENT_AVAILABLE_STOCK) => {
+                    short_lines.extend(demand.line_nos);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        if short_lines.is_empty() {
+            return Ok(ReservationOutcome::Reserved);
+        }
+        short_lines.sort_unstable();
+
+        match self.settings.get(tenant_id).short_stock {
+            ShortStockPolicy::FailConfirmation => {
+                self.release(tenant_id, order_id, lines, occurred_at)?;
+                let cancel = SalesOrderCommand::CancelOrder(CancelOrder {
+                    tenant_id,
+                    order_id,
+                    reason: Some(INSUFFICIENT_STOCK.to_string()),
+                    occurred_at,
+                });
+                match self.order(tenant_id, order_id, cancel) {
+                    // Cancelled by someone else in the meantime: the outcome is the same.
+                    Ok(()) => {}
+                    Err(e) if e.code() == Some(ORDER_ALREADY_CANCELLED) => {}
+                    Err(e) => return Err(e),
+                }
+                Ok(ReservationOutcome::Rejected { line_nos: short_lines })
+            }
+            ShortStockPolicy::Backorder => {
+                self.order(
+                    tenant_id,
+                    order_id,
+                    SalesOrderCommand::MarkBackordered(MarkBackordered {
+                        tenant_id,
+                        order_id,
+                        line_nos: short_lines.clone(),
+                        occurred_at,
+                    }),
+                )?;
+                Ok(ReservationOutcome::Backordered { line_nos: short_lines })
+            }
+        }
+    }
+
+    fn release(
+        &self,
+        tenant_id: TenantId,
+        order_id: SalesOrderId,
+        lines: &[OrderLine],
+        occurred_at: DateTime<Utc>,
+    ) -> Result<(), DispatchError> {
+        for demand in self.demand(tenant_id, lines) {
+            let release = InventoryCommand::ReleaseReservation(ReleaseReservation {
+                tenant_id,
+                item_id: demand.item_id,
+                reservation_id: reservation_id(order_id),
+                quantity: None,
+                occurred_at,
+            });
+            match self.inventory(tenant_id, demand.item_id, release) {
+                Ok(()) => {}
+                // Short items were never reserved for the order: nothing to release.
+                Err(e) if e.code() == Some(UNKNOWN_RESERVATION) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn demand(&self, tenant_id: TenantId, lines: &[OrderLine]) -> Vec<ItemDemand> {
+        let mut demand: Vec<ItemDemand> = Vec::new();
+        for line in lines {
+            let Some(item_id) = self.items.inventory_item(tenant_id, line.product_id) else {
+                continue;
+            };
+            match demand.iter_mut().find(|d| d.item_id == item_id) {
+                Some(d) => {
+                    d.quantity += line.quantity;
+                    d.line_nos.push(line.line_no);
+                }
+                None => demand.push(ItemDemand {
+                    item_id,
+                    quantity: line.quantity,
+                    line_nos: vec![line.line_no],
+                }),
+            }
+        }
+        demand
+    }
+
+    fn inventory(&self, tenant_id: TenantId, item_id: InventoryItemId, command: InventoryCommand) -> Result<(), DispatchError> {
+        self.dispatcher
+            .dispatch::<InventoryItem>(tenant_id, item_id.0, "inventory.item", command, |_, id| {
+                InventoryItem::empty(InventoryItemId::new(id))
+            })
+            .map(drop)
+    }
+
+    fn order(&self, tenant_id: TenantId, order_id: SalesOrderId, command: SalesOrderCommand) -> Result<(), DispatchError> {
+        self.dispatcher
+            .dispatch::<SalesOrder>(tenant_id, order_id.0, "sales.order", command, |_, id| {
+                SalesOrder::empty(SalesOrderId::new(id))
+            })
+            .map(drop)
+    }
+}
+
+/// Reservation id used for a sales order's stock.
+pub fn reservation_id(order_id: SalesOrderId) -> AggregateId {
+    order_id.0
+}
+
+#[cfg(test)]
+mod tests {
+    use forgeerp_core::Aggregate;
+    use forgeerp_events::{InMemoryEventBus, Subscription};
+    use forgeerp_inventory::{AdjustStock, CreateItem, DEFAULT_LOCATION, InventoryEvent};
+    use forgeerp_sales::{AddLine, ConfirmOrder, CreateSalesOrder, SalesOrderStatus};
+
+    use super::*;
+    use crate::event_store::InMemoryEventStore;
+    use crate::tenant_settings::{change_settings, keys, SetSetting, TenantSettingsCommand};
+
+    type Bus = InMemoryEventBus<EventEnvelope<JsonValue>>;
+
+    struct Fixture {
+        store: Arc<InMemoryEventStore>,
+        dispatcher: Arc<CommandDispatcher<Arc<InMemoryEventStore>, Arc<Bus>>>,
+        settings: Arc<TenantSettingsProjection>,
+        saga: SalesStockReservation<Arc<InMemoryEventStore>, Arc<Bus>>,
+        sub: Subscription<EventEnvelope<JsonValue>>,
+        tenant_id: TenantId,
+        item_id: InventoryItemId,
+        product_id: ProductId,
+    }
+
+    impl Fixture {
+        /// One product linked to one inventory item holding `stock` units.
+        fn new(stock: i64) -> Self {
+            let store = Arc::new(InMemoryEventStore::new());
+            let bus: Arc<Bus> = Arc::new(InMemoryEventBus::new());
+            let sub = bus.subscribe();
+            let dispatcher = Arc::new(CommandDispatcher::new(store.clone(), bus));
+            let settings = TenantSettingsProjection::arc();
+            let item_id = InventoryItemId::new(AggregateId::new());
+            let product_id = ProductId::new(AggregateId::new());
+            let lookup = move |_: TenantId, p: ProductId| (p == product_id).then_some(item_id);
+            let f = Self {
+                saga: SalesStockReservation::new(dispatcher.clone(), settings.clone(), Arc::new(lookup)),
+                store,
+                dispatcher,
+                settings,
+                sub,
+                tenant_id: TenantId::new(),
+                item_id,
+                product_id,
+            };
+            let tenant_id = f.tenant_id;
+            f.inventory(InventoryCommand::CreateItem(CreateItem {
+                tenant_id,
+                item_id,
+                name: "Widget".to_string(),
+                occurred_at: Utc::now(),
+            }));
+            f.inventory(InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id,
+                item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta: stock,
+                occurred_at: Utc::now(),
+            }));
+            f
+        }
+
+        fn inventory(&self, command: InventoryCommand) {
+            self.dispatcher
+                .dispatch::<InventoryItem>(self.tenant_id, self.item_id.0, "inventory.item", command, |_, id| {
+                    InventoryItem::empty(InventoryItemId::new(id))
+                })
+                .unwrap();
+        }
+
+        fn order(&self, order_id: SalesOrderId, command: SalesOrderCommand) {
+            self.dispatcher
+                .dispatch::<SalesOrder>(self.tenant_id, order_id.0, "sales.order", command, |_, id| {
+                    SalesOrder::empty(SalesOrderId::new(id))
+                })
+                .unwrap();
+        }
+
+        /// Create and confirm an order for `quantity` units; returns the confirmation event.
+        fn confirm(&self, quantity: i64) -> (SalesOrderId, EventEnvelope<JsonValue>) {
+            let tenant_id = self.tenant_id;
+            let order_id = SalesOrderId::new(AggregateId::new());
+            self.order(order_id, SalesOrderCommand::CreateSalesOrder(CreateSalesOrder {
+                tenant_id,
+                order_id,
+                occurred_at: Utc::now(),
+            }));
+            self.order(order_id, SalesOrderCommand::AddLine(AddLine {
+                tenant_id,
+                order_id,
+                product_id: self.product_id,
+                quantity,
+                unit_price: 1_000,
+                occurred_at: Utc::now(),
+            }));
+            self.order(order_id, SalesOrderCommand::ConfirmOrder(ConfirmOrder {
+                tenant_id,
+                order_id,
+                occurred_at: Utc::now(),
+            }));
+            (order_id, self.last_order_event())
+        }
+
+        fn cancel(&self, order_id: SalesOrderId) -> EventEnvelope<JsonValue> {
+            self.order(order_id, SalesOrderCommand::CancelOrder(CancelOrder {
+                tenant_id: self.tenant_id,
+                order_id,
+                reason: None,
+                occurred_at: Utc::now(),
+            }));
+            self.last_order_event()
+        }
+
+        fn last_order_event(&self) -> EventEnvelope<JsonValue> {
+            let mut last = None;
+            while let Ok(env) = self.sub.try_recv() {
+                if env.aggregate_type() == "sales.order" {
+                    last = Some(env);
+                }
+            }
+            last.unwrap()
+        }
+
+        fn item(&self) -> InventoryItem {
+            let mut item = InventoryItem::empty(self.item_id);
+            for stored in self.store.load_stream(self.tenant_id, self.item_id.0).unwrap() {
+                let event: InventoryEvent = serde_json::from_value(stored.payload).unwrap();
+                item.apply(&event);
+            }
+            item
+        }
+
+        fn sales_order(&self, order_id: SalesOrderId) -> SalesOrder {
+            let mut order = SalesOrder::empty(order_id);
+            for stored in self.store.load_stream(self.tenant_id, order_id.0).unwrap() {
+                let event: SalesOrderEvent = serde_json::from_value(stored.payload).unwrap();
+                order.apply(&event);
+            }
+            order
+        }
+    }
+
+    #[test]
+    fn confirming_order_with_enough_stock_reserves_it() {
+        let f = Fixture::new(10);
+        let (order_id, confirmed) = f.confirm(4);
+
+        assert_eq!(f.saga.handle(&confirmed).unwrap(), ReservationOutcome::Reserved);
+        let item = f.item();
+        assert_eq!(item.stock(), 10);
+        assert_eq!(item.available(), 6);
+        assert_eq!(item.reservation(reservation_id(order_id)), Some(4));
+
+        // Redelivery does not reserve twice.
+        assert_eq!(f.saga.handle(&confirmed).unwrap(), ReservationOutcome::Reserved);
+        assert_eq!(f.item().available(), 6);
+    }
+
+    #[test]
+    fn cancelling_confirmed_order_releases_its_reservation() {
+        let f = Fixture::new(10);
+        let (order_id, confirmed) = f.confirm(4);
+        f.saga.handle(&confirmed).unwrap();
+
+        let cancelled = f.cancel(order_id);
+        assert_eq!(f.saga.handle(&cancelled).unwrap(), ReservationOutcome::Released);
+        assert_eq!(f.item().available(), 10);
+
+        // A late redelivery of the confirmation does not reserve the stock again.
+        f.saga.handle(&confirmed).unwrap();
+        assert_eq!(f.item().available(), 10);
+    }
+
+    #[test]
+    fn insufficient_stock_fails_confirmation_by_default() {
+        let f = Fixture::new(3);
+        let (order_id, confirmed) = f.confirm(5);
+
+        assert_eq!(
+            f.saga.handle(&confirmed).unwrap(),
+            ReservationOutcome::Rejected { line_nos: vec![1] }
+        );
+        assert_eq!(f.item().available(), 3);
+        assert_eq!(f.sales_order(order_id).status(), SalesOrderStatus::Cancelled);
+    }
+
+    #[test]
+    fn insufficient_stock_backorders_when_configured() {
+        let f = Fixture::new(3);
+        change_settings(
+            &f.dispatcher,
+            &f.settings,
+            TenantSettingsCommand::SetSetting(SetSetting {
+                tenant_id: f.tenant_id,
+                key: keys::SHORT_STOCK.to_string(),
+                value: serde_json::to_value(ShortStockPolicy::Backorder).unwrap(),
+                occurred_at: Utc::now(),
+            }),
+        )
+        .unwrap();
+        let (order_id, confirmed) = f.confirm(5);
+
+        assert_eq!(
+            f.saga.handle(&confirmed).unwrap(),
+            ReservationOutcome::Backordered { line_nos: vec![1] }
+        );
+        let order = f.sales_order(order_id);
+        assert_eq!(order.status(), SalesOrderStatus::Confirmed);
+        assert_eq!(order.backordered_lines(), &[1]);
+        assert_eq!(f.item().available(), 3);
+    }
+}
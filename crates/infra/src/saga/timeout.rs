@@ -0,0 +1,217 @@
+//! Saga timeouts: deadlines for sagas waiting on an expected event.
+//!
+//! Runners `track` a saga after each reaction. A periodic sweep (`fire_expired`)
+//! rehydrates every saga past its `timeout_at` and, if it is still in the state that
+//! armed the deadline, runs its `Saga::on_timeout` actions through the
+//! `CommandExecutor`. Arming a deadline appends a `saga.timeout_armed` event to the
+//! saga stream; at startup `restore` re-arms the sagas still in the armed state, so
+//! a saga that never reacts again still times out after a restart.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use forgeerp_core::{AggregateId, TenantId};
+use forgeerp_events::{Saga, SagaAction};
+use serde_json::Value as JsonValue;
+
+use crate::event_store::{EventStore, StoredEvent};
+use crate::saga::{CommandExecutor, SagaRepository};
+
+/// How often runners sweep for expired saga deadlines.
+pub const SAGA_TIMEOUT_SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Saga event recording an armed deadline (`correlation`, `state`, `timeout_at`).
+pub const TIMEOUT_ARMED_EVENT: &str = "saga.timeout_armed";
+
+struct Armed<S: Saga> {
+    correlation: S::CorrelationId,
+    /// State that armed the deadline (serialized, as `Saga::State` need not be `PartialEq`).
+    state: JsonValue,
+    timeout_at: DateTime<Utc>,
+}
+
+/// Payload of a `TIMEOUT_ARMED_EVENT`.
+#[derive(serde::Deserialize)]
+#[serde(bound = "")]
+struct Recorded<S: Saga> {
+    correlation: S::CorrelationId,
+    state: JsonValue,
+    timeout_at: DateTime<Utc>,
+}
+
+/// Pending deadlines of one saga type, keyed by (tenant, saga id).
+pub struct SagaTimeouts<S: Saga> {
+    armed: Mutex<HashMap<(TenantId, AggregateId), Armed<S>>>,
+}
+
+impl<S: Saga> Default for SagaTimeouts<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Saga> SagaTimeouts<S> {
+    pub fn new() -> Self {
+        Self {
+            armed: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Arm the deadline of a saga now in `state`, or clear it if `state` has no timeout.
+    ///
+    /// A saga still in the state that armed its deadline keeps the original `timeout_at`.
+    /// A newly armed deadline is recorded in the saga stream (failures are logged; the
+    /// deadline is still armed in memory).
+    pub fn track<E: EventStore>(
+        &self,
+        repo: &SagaRepository<S, E>,
+        tenant_id: TenantId,
+        correlation: &S::CorrelationId,
+        state: &S::State,
+        now: DateTime<Utc>,
+    ) {
+        let saga_id = S::saga_id(tenant_id, correlation);
+        let key = (tenant_id, saga_id);
+        let mut armed = self.armed.lock().unwrap();
+        let Some(timeout_at) = S::timeout_at(state, now) else {
+            armed.remove(&key);
+            return;
+        };
+        let state = serde_json::to_value(state).unwrap_or(JsonValue::Null);
+        if armed.get(&key).is_some_and(|a| a.state == state) {
+            return;
+        }
+        let payload = serde_json::json!({
+            "correlation": correlation,
+            "state": state,
+            "timeout_at": timeout_at,
+        });
+        if let Err(e) = repo.append_emit(tenant_id, saga_id, TIMEOUT_ARMED_EVENT, payload) {
+            tracing::warn!(tenant = %tenant_id, saga_id = %saga_id, "failed to record saga deadline: {e}");
+        }
+        armed.insert(
+            key,
+            Armed {
+                correlation: correlation.clone(),
+                state,
+                timeout_at,
+            },
+        );
+    }
+
+    /// Re-arm, from the recorded deadlines, every saga still in the state that armed it.
+    ///
+    /// Run at startup, before the runner reacts to new events. Returns how many
+    /// deadlines were re-armed (a passed deadline fires on the next sweep).
+    pub fn restore<E: EventStore>(&self, repo: &SagaRepository<S, E>) -> Result<usize, crate::event_store::EventStoreError> {
+        // The last recorded deadline of each saga wins.
+        let mut latest: HashMap<(TenantId, AggregateId), StoredEvent> = HashMap::new();
+        for event in repo.events_named(TIMEOUT_ARMED_EVENT)? {
+            latest.insert((event.tenant_id, event.aggregate_id), event);
+        }
+
+        let mut restored = 0;
+        for ((tenant_id, saga_id), event) in latest {
+            let Ok(recorded) = serde_json::from_value::<Recorded<S>>(event.payload) else {
+                tracing::warn!(tenant = %tenant_id, saga_id = %saga_id, "unreadable saga deadline skipped");
+                continue;
+            };
+            let state = repo.rehydrate(tenant_id, &recorded.correlation)?;
+            if serde_json::to_value(&state).unwrap_or(JsonValue::Null) != recorded.state {
+                continue;
+            }
+            self.armed.lock().unwrap().insert(
+                (tenant_id, saga_id),
+                Armed {
+                    correlation: recorded.correlation,
+                    state: recorded.state,
+                    timeout_at: recorded.timeout_at,
+                },
+            );
+            restored += 1;
+        }
+        Ok(restored)
+    }
+
+    /// Earliest pending deadline, if any.
+    pub fn next_timeout_at(&self) -> Option<DateTime<Utc>> {
+        self.armed.lock().unwrap().values().map(|a| a.timeout_at).min()
+    }
+
+    /// Run the timeout actions of every saga past its deadline that has not moved on.
+    ///
+    /// Returns the (tenant, saga id) of the sagas that timed out. Failed loads and
+    /// actions are logged; the saga is not retried.
+    pub fn fire_expired<E, X>(
+        &self,
+        repo: &SagaRepository<S, E>,
+        executor: &X,
+        now: DateTime<Utc>,
+    ) -> Vec<(TenantId, AggregateId)>
+    where
+        E: EventStore,
+        X: CommandExecutor,
+    {
+        let expired: Vec<_> = {
+            let mut armed = self.armed.lock().unwrap();
+            let keys: Vec<_> = armed
+                .iter()
+                .filter(|(_, a)| a.timeout_at <= now)
+                .map(|(key, _)| *key)
+                .collect();
+            keys.into_iter()
+                .filter_map(|key| armed.remove(&key).map(|a| (key, a)))
+                .collect()
+        };
+
+        let mut fired = Vec::new();
+        for ((tenant_id, saga_id), armed) in expired {
+            let state = match repo.rehydrate(tenant_id, &armed.correlation) {
+                Ok(state) => state,
+                Err(e) => {
+                    tracing::warn!(tenant = %tenant_id, saga_id = %saga_id, "failed to load timed-out saga: {e}");
+                    continue;
+                }
+            };
+            if serde_json::to_value(&state).unwrap_or(JsonValue::Null) != armed.state {
+                continue;
+            }
+
+            tracing::info!(tenant = %tenant_id, saga_id = %saga_id, saga_type = S::saga_type(), "saga timed out");
+            for action in S::on_timeout(&state, tenant_id, &armed.correlation) {
+                run_action(repo, executor, tenant_id, saga_id, action);
+            }
+            if let Ok(state) = repo.rehydrate(tenant_id, &armed.correlation) {
+                self.track(repo, tenant_id, &armed.correlation, &state, now);
+            }
+            fired.push((tenant_id, saga_id));
+        }
+        fired
+    }
+}
+
+fn run_action<S, E, X>(repo: &SagaRepository<S, E>, executor: &X, tenant_id: TenantId, saga_id: AggregateId, action: SagaAction)
+where
+    S: Saga,
+    E: EventStore,
+    X: CommandExecutor,
+{
+    match action {
+        SagaAction::Emit { event_type, payload } => {
+            if let Err(e) = repo.append_emit(tenant_id, saga_id, &event_type, payload) {
+                tracing::warn!(tenant = %tenant_id, saga_id = %saga_id, "failed to append {event_type}: {e}");
+            }
+        }
+        SagaAction::Command { aggregate_type, command_type, payload }
+        | SagaAction::Compensate { aggregate_type, command_type, payload } => {
+            if let Err(e) = executor.execute(tenant_id, &aggregate_type, &command_type, &payload) {
+                tracing::warn!(tenant = %tenant_id, saga_id = %saga_id, "{aggregate_type}.{command_type} failed: {e:?}");
+            }
+        }
+        SagaAction::Complete => {
+            let _ = repo.append_emit(tenant_id, saga_id, "saga.completed", serde_json::json!({}));
+        }
+    }
+}
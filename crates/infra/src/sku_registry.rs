@@ -0,0 +1,303 @@
+//! Tenant-wide SKU uniqueness for live products.
+//!
+//! The product aggregate only sees its own stream, so it cannot tell whether another
+//! product already uses a SKU. [`SkuRegistry`] holds one reservation per
+//! `(tenant, SKU)` for products that are live (draft or active):
+//!
+//! - a create reserves the SKU before dispatching and releases it if the create commits
+//!   nothing (a create whose events were stored but not published keeps it);
+//! - `ProductArchived` / `ProductDeleted` events release it, so the SKU can be reused
+//!   by a new product.
+//!
+//! Reserving is atomic, so of two concurrent creates with the same SKU exactly one wins.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use forgeerp_core::{DomainError, TenantId};
+use forgeerp_events::EventEnvelope;
+use forgeerp_products::{ProductEvent, ProductId};
+use serde_json::Value as JsonValue;
+
+use crate::command_dispatcher::{is_dry_run, DispatchError};
+
+#[derive(Debug, Default)]
+struct Reservations {
+    by_sku: HashMap<(TenantId, String), ProductId>,
+    by_product: HashMap<(TenantId, ProductId), String>,
+}
+
+impl Reservations {
+    fn release(&mut self, tenant_id: TenantId, product_id: ProductId) {
+        if let Some(sku) = self.by_product.remove(&(tenant_id, product_id)) {
+            self.by_sku.remove(&(tenant_id, sku));
+        }
+    }
+}
+
+/// SKU reservations of live products, per tenant.
+#[derive(Debug, Default)]
+pub struct SkuRegistry {
+    reservations: Mutex<Reservations>,
+}
+
+impl SkuRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn arc() -> Arc<Self> {
+        Arc::new(Self::new())
+    }
+
+    /// Reserve `sku` for `product_id` (idempotent for the same product).
+    pub fn reserve(&self, tenant_id: TenantId, sku: &str, product_id: ProductId) -> Result<(), DomainError> {
+        let sku = sku.trim().to_string();
+        let mut reservations = self.reservations.lock().expect("sku registry lock poisoned");
+        match reservations.by_sku.get(&(tenant_id, sku.clone())) {
+            Some(holder) if *holder == product_id => Ok(()),
+            Some(holder) => Err(DomainError::conflict(format!(
+                "SKU {sku} is already used by product {holder}"
+            ))),
+            None => {
+                reservations.by_sku.insert((tenant_id, sku.clone()), product_id);
+                reservations.by_product.insert((tenant_id, product_id), sku);
+                Ok(())
+            }
+        }
+    }
+
+    /// Release whatever SKU `product_id` holds.
+    pub fn release(&self, tenant_id: TenantId, product_id: ProductId) {
+        self.reservations
+            .lock()
+            .expect("sku registry lock poisoned")
+            .release(tenant_id, product_id);
+    }
+
+    /// Product currently holding `sku`, if any.
+    pub fn holder(&self, tenant_id: TenantId, sku: &str) -> Option<ProductId> {
+        let reservations = self.reservations.lock().expect("sku registry lock poisoned");
+        reservations.by_sku.get(&(tenant_id, sku.trim().to_string())).copied()
+    }
+
+    /// Reserve `sku`, run `create`, and release the reservation if `create` fails without
+    /// committing anything. After a publish failure ([`DispatchError::committed`]) the
+    /// product exists, so it keeps the SKU.
+    ///
+    /// In a dry run a taken SKU is reported the same way, but nothing is reserved.
+    pub fn create_with<T>(
+        &self,
+        tenant_id: TenantId,
+        sku: &str,
+        product_id: ProductId,
+        create: impl FnOnce() -> Result<T, DispatchError>,
+    ) -> Result<T, DispatchError> {
+        if is_dry_run() {
+            if let Some(holder) = self.holder(tenant_id, sku).filter(|h| *h != product_id) {
+                return Err(DomainError::conflict(format!("SKU {} is already used by product {holder}", sku.trim())).into());
+            }
+            return create();
+        }
+        self.reserve(tenant_id, sku, product_id)?;
+        create().inspect_err(|e| {
+            if e.committed().is_none() {
+                self.release(tenant_id, product_id);
+            }
+        })
+    }
+
+    /// Track product status transitions: created products hold their SKU, archived
+    /// and deleted ones release it. Safe to apply the same event more than once.
+    /// Previewed events (inside a dry run) are ignored.
+    pub fn apply_envelope(&self, envelope: &EventEnvelope<JsonValue>) -> Result<(), DispatchError> {
+        if envelope.aggregate_type() != "products.product" || is_dry_run() {
+            return Ok(());
+        }
+        let event: ProductEvent = serde_json::from_value(envelope.payload().clone())
+            .map_err(|e| DispatchError::Deserialize(e.to_string()))?;
+
+        let tenant_id = envelope.tenant_id();
+        match event {
+            // Events are facts: a SKU another product already holds is left to that product.
+            ProductEvent::ProductCreated(e) => {
+                let _ = self.reserve(tenant_id, &e.sku, e.product_id);
+            }
+            ProductEvent::ProductArchived(e) => self.release(tenant_id, e.product_id),
+            ProductEvent::ProductDeleted(e) => self.release(tenant_id, e.product_id),
+            ProductEvent::ProductActivated(_) | ProductEvent::ProductPricingUpdated(_) => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Barrier;
+
+    use chrono::Utc;
+    use forgeerp_core::AggregateId;
+    use forgeerp_events::InMemoryEventBus;
+    use forgeerp_products::{ArchiveProduct, CreateProduct, Product, ProductCommand};
+
+    use super::*;
+    use crate::command_dispatcher::CommandDispatcher;
+    use crate::event_store::{InMemoryEventStore, StoredEvent};
+
+    type Dispatcher = CommandDispatcher<InMemoryEventStore, Arc<InMemoryEventBus<EventEnvelope<JsonValue>>>>;
+
+    fn dispatcher() -> Dispatcher {
+        CommandDispatcher::new(InMemoryEventStore::new(), Arc::new(InMemoryEventBus::new()))
+    }
+
+    fn send(
+        dispatcher: &Dispatcher,
+        registry: &SkuRegistry,
+        product_id: ProductId,
+        command: ProductCommand,
+        tenant_id: TenantId,
+    ) -> Result<Vec<StoredEvent>, DispatchError> {
+        let committed = dispatcher.dispatch(tenant_id, product_id.0, "products.product", command, |_, id| {
+            Product::empty(ProductId::new(id))
+        })?;
+        for stored in &committed {
+            registry.apply_envelope(&stored.to_envelope())?;
+        }
+        Ok(committed)
+    }
+
+    fn create(
+        dispatcher: &Dispatcher,
+        registry: &SkuRegistry,
+        tenant_id: TenantId,
+        sku: &str,
+    ) -> Result<ProductId, DispatchError> {
+        let product_id = ProductId::new(AggregateId::new());
+        let command = ProductCommand::CreateProduct(CreateProduct {
+            tenant_id,
+            product_id,
+            sku: sku.to_string(),
+            name: "Widget".to_string(),
+            pricing: None,
+            inventory_item_id: None,
+            occurred_at: Utc::now(),
+        });
+        registry.create_with(tenant_id, sku, product_id, || {
+            send(dispatcher, registry, product_id, command, tenant_id)
+        })?;
+        Ok(product_id)
+    }
+
+    #[test]
+    fn archived_product_releases_its_sku_for_a_new_product() {
+        let dispatcher = dispatcher();
+        let registry = SkuRegistry::new();
+        let tenant_id = TenantId::new();
+
+        let a = create(&dispatcher, &registry, tenant_id, "SKU-1").unwrap();
+        assert!(matches!(
+            create(&dispatcher, &registry, tenant_id, "SKU-1"),
+            Err(DispatchError::Concurrency(_))
+        ));
+        // Other tenants have their own SKU space.
+        create(&dispatcher, &registry, TenantId::new(), "SKU-1").unwrap();
+
+        let archive = ProductCommand::ArchiveProduct(ArchiveProduct {
+            tenant_id,
+            product_id: a,
+            occurred_at: Utc::now(),
+        });
+        send(&dispatcher, &registry, a, archive, tenant_id).unwrap();
+
+        let b = create(&dispatcher, &registry, tenant_id, "SKU-1").unwrap();
+        assert_eq!(registry.holder(tenant_id, "SKU-1"), Some(b));
+    }
+
+    #[test]
+    fn concurrent_creates_of_one_sku_admit_exactly_one() {
+        const WRITERS: usize = 8;
+
+        let dispatcher = Arc::new(dispatcher());
+        let registry = SkuRegistry::arc();
+        let tenant_id = TenantId::new();
+        let barrier = Arc::new(Barrier::new(WRITERS));
+
+        let results: Vec<Result<ProductId, DispatchError>> = (0..WRITERS)
+            .map(|_| {
+                let (dispatcher, registry, barrier) = (dispatcher.clone(), registry.clone(), barrier.clone());
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    create(&dispatcher, &registry, tenant_id, "SKU-1")
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect();
+
+        let winners: Vec<ProductId> = results.iter().filter_map(|r| r.as_ref().ok().copied()).collect();
+        assert_eq!(winners.len(), 1);
+        assert_eq!(registry.holder(tenant_id, "SKU-1"), Some(winners[0]));
+    }
+
+    /// Bus that is down: every publication fails.
+    struct DownBus;
+
+    impl forgeerp_events::EventBus<EventEnvelope<JsonValue>> for DownBus {
+        type Error = String;
+
+        fn publish(&self, _message: EventEnvelope<JsonValue>) -> Result<(), String> {
+            Err("bus unavailable".to_string())
+        }
+
+        fn subscribe(&self) -> forgeerp_events::Subscription<EventEnvelope<JsonValue>> {
+            InMemoryEventBus::new().subscribe()
+        }
+    }
+
+    #[test]
+    fn create_committed_before_a_publish_failure_keeps_its_sku() {
+        let dispatcher = CommandDispatcher::new(InMemoryEventStore::new(), DownBus);
+        let registry = SkuRegistry::new();
+        let tenant_id = TenantId::new();
+        let create = |product_id: ProductId| {
+            let command = ProductCommand::CreateProduct(CreateProduct {
+                tenant_id,
+                product_id,
+                sku: "SKU-1".to_string(),
+                name: "Widget".to_string(),
+                pricing: None,
+                inventory_item_id: None,
+                occurred_at: Utc::now(),
+            });
+            registry.create_with(tenant_id, "SKU-1", product_id, || {
+                dispatcher.dispatch(tenant_id, product_id.0, "products.product", command, |_, id| {
+                    Product::empty(ProductId::new(id))
+                })
+            })
+        };
+
+        let first = ProductId::new(AggregateId::new());
+        let err = create(first).unwrap_err();
+        assert_eq!(err.committed().map(<[_]>::len), Some(1));
+        assert_eq!(registry.holder(tenant_id, "SKU-1"), Some(first));
+        assert!(matches!(
+            create(ProductId::new(AggregateId::new())),
+            Err(DispatchError::Concurrency(_))
+        ));
+    }
+
+    #[test]
+    fn failed_create_releases_the_reservation() {
+        let registry = SkuRegistry::new();
+        let tenant_id = TenantId::new();
+        let product_id = ProductId::new(AggregateId::new());
+
+        let result: Result<(), DispatchError> = registry.create_with(tenant_id, "SKU-1", product_id, || {
+            Err(DispatchError::Validation("name cannot be empty".to_string()))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(registry.holder(tenant_id, "SKU-1"), None);
+    }
+}
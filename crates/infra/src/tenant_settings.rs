@@ -0,0 +1,430 @@
+//! Per-tenant settings consulted by the command mapping layer.
+//!
+//! Currently holds the tenant's money convention: the default currency applied when
+//! a command omits one, and the rounding rule used whenever a decimal amount is
+//! converted to minor units (invoice/order prices, payments, ledger postings,
+//! product prices). It also maps the ledger accounts used for automatic invoice and
+//! payment postings, and decides what a confirmed sales order does when its stock
+//! cannot be reserved. Tenants without explicit settings get [`TenantSettings::default`].
+//!
+//! Settings are event-sourced: each tenant has one `tenant.settings` stream, a
+//! [`SetSetting`] command emits a [`SettingChanged`] event carrying the new and the
+//! previous value, so changes are versioned and audited like business events. Other
+//! subsystems read the current values from the
+//! [`TenantSettingsProjection`](crate::projections::tenant_settings::TenantSettingsProjection).
+
+use chrono::{DateTime, Utc};
+use forgeerp_accounting::{Account, AccountKind};
+use forgeerp_core::{
+    Aggregate, AggregateId, AggregateRoot, CurrencyConvention, DomainError, DomainResult, RoundingMode, TenantId,
+};
+use forgeerp_events::{Event, EventBus, EventEnvelope};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::command_dispatcher::{CommandDispatcher, DispatchError};
+use crate::event_store::EventStore;
+use crate::projections::tenant_settings::TenantSettingsProjection;
+
+/// Ledger accounts used when invoices and payments are posted automatically.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerAccounts {
+    /// Debited on invoice issue, credited on payment.
+    pub receivable: Account,
+    /// Credited on invoice issue.
+    pub revenue: Account,
+    /// Debited on payment.
+    pub cash: Account,
+}
+
+impl Default for LedgerAccounts {
+    fn default() -> Self {
+        let account = |code: &str, name: &str, kind| Account {
+            code: code.to_string(),
+            name: name.to_string(),
+            kind,
+        };
+        Self {
+            receivable: account("1200", "Accounts Receivable", AccountKind::Asset),
+            revenue: account("4000", "Sales Revenue", AccountKind::Revenue),
+            cash: account("1000", "Cash", AccountKind::Asset),
+        }
+    }
+}
+
+/// What happens to a confirmed sales order whose stock cannot be reserved.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortStockPolicy {
+    /// Release what was reserved and cancel the order.
+    #[default]
+    FailConfirmation,
+    /// Keep the order and flag the short lines as backordered.
+    Backorder,
+}
+
+/// Money settings for one tenant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantSettings {
+    /// ISO 4217 code used when a command omits the currency.
+    pub default_currency: String,
+    pub rounding: RoundingMode,
+    #[serde(default)]
+    pub ledger_accounts: LedgerAccounts,
+    #[serde(default)]
+    pub short_stock: ShortStockPolicy,
+}
+
+impl Default for TenantSettings {
+    fn default() -> Self {
+        Self {
+            default_currency: "USD".to_string(),
+            rounding: RoundingMode::HalfEven,
+            ledger_accounts: LedgerAccounts::default(),
+            short_stock: ShortStockPolicy::default(),
+        }
+    }
+}
+
+impl TenantSettings {
+    /// Validated settings (the currency code must be a known ISO 4217 shape).
+    pub fn new(default_currency: &str, rounding: RoundingMode) -> DomainResult<Self> {
+        let convention = CurrencyConvention::for_code(default_currency, rounding)?;
+        Ok(Self {
+            default_currency: convention.code,
+            rounding,
+            ledger_accounts: LedgerAccounts::default(),
+            short_stock: ShortStockPolicy::default(),
+        })
+    }
+
+    /// Currency to use for a command: the explicit one, or the tenant default.
+    pub fn currency_or_default(&self, currency: Option<&str>) -> String {
+        match currency {
+            Some(code) if !code.trim().is_empty() => code.trim().to_ascii_uppercase(),
+            _ => self.default_currency.clone(),
+        }
+    }
+
+    /// Rounding convention for `currency` (or the tenant default currency).
+    pub fn convention(&self, currency: Option<&str>) -> DomainResult<CurrencyConvention> {
+        CurrencyConvention::for_code(&self.currency_or_default(currency), self.rounding)
+    }
+
+    /// JSON value of the setting named `key` (see [`keys`]); `None` for unknown keys.
+    pub fn setting(&self, key: &str) -> Option<JsonValue> {
+        match key {
+            keys::DEFAULT_CURRENCY => serde_json::to_value(&self.default_currency).ok(),
+            keys::ROUNDING => serde_json::to_value(self.rounding).ok(),
+            keys::LEDGER_ACCOUNTS => serde_json::to_value(&self.ledger_accounts).ok(),
+            keys::SHORT_STOCK => serde_json::to_value(self.short_stock).ok(),
+            _ => None,
+        }
+    }
+
+    /// Copy with the setting named `key` set to `value`, validated like [`TenantSettings::new`].
+    pub fn with_setting(&self, key: &str, value: &JsonValue) -> DomainResult<Self> {
+        fn parse<T: serde::de::DeserializeOwned>(key: &str, value: &JsonValue) -> DomainResult<T> {
+            serde_json::from_value(value.clone())
+                .map_err(|e| DomainError::validation(format!("invalid value for setting `{key}`: {e}")))
+        }
+
+        let mut next = self.clone();
+        match key {
+            keys::DEFAULT_CURRENCY => {
+                let code: String = parse(key, value)?;
+                next.default_currency = CurrencyConvention::for_code(&code, self.rounding)?.code;
+            }
+            keys::ROUNDING => next.rounding = parse(key, value)?,
+            keys::LEDGER_ACCOUNTS => next.ledger_accounts = parse(key, value)?,
+            keys::SHORT_STOCK => next.short_stock = parse(key, value)?,
+            _ => return Err(DomainError::validation(format!("unknown setting `{key}`"))),
+        }
+        Ok(next)
+    }
+
+    /// Evolve with a recorded change (changes were validated when they were emitted).
+    pub fn apply_change(&mut self, change: &SettingChanged) {
+        if let Ok(next) = self.with_setting(&change.key, &change.value) {
+            *self = next;
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Aggregate
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Aggregate type of the per-tenant settings stream.
+pub const AGGREGATE_TYPE: &str = "tenant.settings";
+
+/// Setting keys accepted by [`SetSetting`].
+pub mod keys {
+    pub const DEFAULT_CURRENCY: &str = "default_currency";
+    pub const ROUNDING: &str = "rounding";
+    pub const LEDGER_ACCOUNTS: &str = "ledger_accounts";
+    pub const SHORT_STOCK: &str = "short_stock";
+
+    pub const ALL: [&str; 4] = [DEFAULT_CURRENCY, ROUNDING, LEDGER_ACCOUNTS, SHORT_STOCK];
+}
+
+/// Each tenant has exactly one settings stream, keyed by the tenant id.
+pub fn settings_aggregate_id(tenant_id: TenantId) -> AggregateId {
+    AggregateId::from_uuid(*tenant_id.as_uuid())
+}
+
+/// Set one setting to `value` (its JSON form, e.g. `"EUR"` or `"backorder"`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetSetting {
+    pub tenant_id: TenantId,
+    pub key: String,
+    pub value: JsonValue,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Set several settings in one append (applied in order).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetSettings {
+    pub tenant_id: TenantId,
+    pub values: Vec<(String, JsonValue)>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TenantSettingsCommand {
+    SetSetting(SetSetting),
+    SetSettings(SetSettings),
+}
+
+/// A setting took a new value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingChanged {
+    pub tenant_id: TenantId,
+    pub key: String,
+    pub value: JsonValue,
+    /// The value it replaced (the default when it had never been set).
+    pub previous: JsonValue,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TenantSettingsEvent {
+    SettingChanged(SettingChanged),
+}
+
+impl Event for TenantSettingsEvent {
+    fn event_type(&self) -> &'static str {
+        match self {
+            TenantSettingsEvent::SettingChanged(_) => "tenant.settings.changed",
+        }
+    }
+
+    fn version(&self) -> u32 {
+        1
+    }
+
+    fn occurred_at(&self) -> DateTime<Utc> {
+        match self {
+            TenantSettingsEvent::SettingChanged(e) => e.occurred_at,
+        }
+    }
+}
+
+/// Event-sourced settings of one tenant; every change is a [`SettingChanged`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantSettingsAggregate {
+    id: AggregateId,
+    tenant_id: TenantId,
+    version: u64,
+    settings: TenantSettings,
+}
+
+impl TenantSettingsAggregate {
+    pub fn new(tenant_id: TenantId, id: AggregateId) -> Self {
+        Self {
+            id,
+            tenant_id,
+            version: 0,
+            settings: TenantSettings::default(),
+        }
+    }
+
+    pub fn settings(&self) -> &TenantSettings {
+        &self.settings
+    }
+
+    fn changes(
+        &self,
+        tenant_id: TenantId,
+        values: &[(String, JsonValue)],
+        occurred_at: DateTime<Utc>,
+    ) -> DomainResult<Vec<TenantSettingsEvent>> {
+        if tenant_id != self.tenant_id {
+            return Err(DomainError::invariant("settings belong to another tenant"));
+        }
+        let mut current = self.settings.clone();
+        let mut events = Vec::new();
+        for (key, value) in values {
+            let next = current.with_setting(key, value)?;
+            let (previous, value) = (current.setting(key), next.setting(key));
+            if value != previous {
+                events.push(TenantSettingsEvent::SettingChanged(SettingChanged {
+                    tenant_id,
+                    key: key.clone(),
+                    value: value.unwrap_or_default(),
+                    previous: previous.unwrap_or_default(),
+                    occurred_at,
+                }));
+            }
+            current = next;
+        }
+        Ok(events)
+    }
+}
+
+impl AggregateRoot for TenantSettingsAggregate {
+    type Id = AggregateId;
+
+    fn id(&self) -> &Self::Id {
+        &self.id
+    }
+
+    fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+impl Aggregate for TenantSettingsAggregate {
+    type Command = TenantSettingsCommand;
+    type Event = TenantSettingsEvent;
+    type Error = DomainError;
+
+    fn apply(&mut self, event: &Self::Event) {
+        match event {
+            TenantSettingsEvent::SettingChanged(e) => self.settings.apply_change(e),
+        }
+        self.version += 1;
+    }
+
+    /// Setting a key to its current value emits nothing.
+    fn handle(&self, command: &Self::Command) -> Result<Vec<Self::Event>, Self::Error> {
+        match command {
+            TenantSettingsCommand::SetSetting(cmd) => {
+                self.changes(cmd.tenant_id, &[(cmd.key.clone(), cmd.value.clone())], cmd.occurred_at)
+            }
+            TenantSettingsCommand::SetSettings(cmd) => self.changes(cmd.tenant_id, &cmd.values, cmd.occurred_at),
+        }
+    }
+}
+
+/// Dispatch `command` to the tenant's settings stream and apply the committed changes to
+/// `projection` right away, so the caller reads its own write; returns the new settings.
+pub fn change_settings<S, B>(
+    dispatcher: &CommandDispatcher<S, B>,
+    projection: &TenantSettingsProjection,
+    command: TenantSettingsCommand,
+) -> Result<TenantSettings, DispatchError>
+where
+    S: EventStore,
+    B: EventBus<EventEnvelope<JsonValue>>,
+{
+    let tenant_id = match &command {
+        TenantSettingsCommand::SetSetting(cmd) => cmd.tenant_id,
+        TenantSettingsCommand::SetSettings(cmd) => cmd.tenant_id,
+    };
+    let committed = dispatcher.dispatch::<TenantSettingsAggregate>(
+        tenant_id,
+        settings_aggregate_id(tenant_id),
+        AGGREGATE_TYPE,
+        command,
+        TenantSettingsAggregate::new,
+    )?;
+    projection
+        .apply_committed(&committed)
+        .map_err(|e| DispatchError::Deserialize(e.to_string()))?;
+    Ok(projection.get(tenant_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use forgeerp_events::InMemoryEventBus;
+
+    use crate::event_store::InMemoryEventStore;
+
+    type Bus = InMemoryEventBus<EventEnvelope<JsonValue>>;
+
+    fn set(tenant_id: TenantId, key: &str, value: JsonValue) -> TenantSettingsCommand {
+        TenantSettingsCommand::SetSetting(SetSetting {
+            tenant_id,
+            key: key.to_string(),
+            value,
+            occurred_at: Utc::now(),
+        })
+    }
+
+    #[test]
+    fn omitted_currency_uses_tenant_default() {
+        let settings = TenantSettings::default()
+            .with_setting(keys::DEFAULT_CURRENCY, &JsonValue::from("jpy"))
+            .unwrap();
+        assert_eq!(settings.currency_or_default(None), "JPY");
+        assert_eq!(settings.currency_or_default(Some("")), "JPY");
+        assert_eq!(settings.currency_or_default(Some("eur")), "EUR");
+        assert_eq!(TenantSettings::default().currency_or_default(None), "USD");
+    }
+
+    #[test]
+    fn zero_decimal_tenant_rounds_with_its_convention() {
+        let settings = TenantSettings::new("JPY", RoundingMode::HalfEven).unwrap();
+        let jpy = settings.convention(None).unwrap();
+        assert_eq!(jpy.to_minor_units("1234.5").unwrap(), 1234);
+        assert_eq!(jpy.to_minor_units("1235.5").unwrap(), 1236);
+
+        // An explicit currency keeps the tenant rounding but its own precision.
+        let usd = settings.convention(Some("USD")).unwrap();
+        assert_eq!(usd.to_minor_units("10.125").unwrap(), 1012);
+
+        assert!(TenantSettings::new("X1", RoundingMode::Down).is_err());
+    }
+
+    #[test]
+    fn setting_a_value_emits_a_change_with_the_previous_value() {
+        let tenant_id = TenantId::new();
+        let mut aggregate = TenantSettingsAggregate::new(tenant_id, settings_aggregate_id(tenant_id));
+
+        let events = aggregate.handle(&set(tenant_id, keys::SHORT_STOCK, "backorder".into())).unwrap();
+        let [TenantSettingsEvent::SettingChanged(change)] = events.as_slice() else {
+            panic!("expected one change, got {events:?}");
+        };
+        assert_eq!((change.value.as_str(), change.previous.as_str()), (Some("backorder"), Some("fail_confirmation")));
+        assert_eq!(events[0].event_type(), "tenant.settings.changed");
+        aggregate.apply(&events[0]);
+        assert_eq!(aggregate.settings().short_stock, ShortStockPolicy::Backorder);
+
+        // Re-setting the current value is a no-op; bad values and keys are rejected.
+        assert!(aggregate.handle(&set(tenant_id, keys::SHORT_STOCK, "backorder".into())).unwrap().is_empty());
+        assert!(aggregate.handle(&set(tenant_id, keys::DEFAULT_CURRENCY, "X1".into())).is_err());
+        assert!(aggregate.handle(&set(tenant_id, "aging_buckets", JsonValue::Null)).is_err());
+    }
+
+    #[test]
+    fn changes_are_stored_in_the_tenant_settings_stream() {
+        let store = Arc::new(InMemoryEventStore::new());
+        let dispatcher = CommandDispatcher::new(store.clone(), Arc::new(Bus::new()));
+        let projection = TenantSettingsProjection::new();
+        let tenant_id = TenantId::new();
+
+        change_settings(&dispatcher, &projection, set(tenant_id, keys::DEFAULT_CURRENCY, "eur".into())).unwrap();
+        let settings = change_settings(&dispatcher, &projection, set(tenant_id, keys::DEFAULT_CURRENCY, "gbp".into()))
+            .unwrap();
+        assert_eq!(settings.default_currency, "GBP");
+
+        let stream = store.load_stream(tenant_id, settings_aggregate_id(tenant_id)).unwrap();
+        assert_eq!(stream.len(), 2);
+        assert!(stream.iter().all(|e| e.aggregate_type == AGGREGATE_TYPE));
+        assert_eq!(stream[1].payload["SettingChanged"]["previous"], "EUR");
+    }
+}
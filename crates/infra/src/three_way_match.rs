@@ -0,0 +1,331 @@
+//! Purchase-to-pay 3-way match: purchase order ↔ goods receipt ↔ supplier invoice.
+//!
+//! Before a supplier invoice is paid, each product on it is compared against what
+//! was ordered and what was actually received:
+//!
+//! - received vs ordered quantity (short/over delivery)
+//! - invoiced vs received quantity (billing for goods not received)
+//! - invoiced vs expected unit price (price drift)
+//!
+//! Differences within the configured [`MatchTolerances`] are accepted. Lines are
+//! matched by product; several PO lines for the same product are summed.
+
+use std::collections::HashMap;
+
+use forgeerp_products::ProductId;
+use forgeerp_purchasing::{LineItem, PurchaseOrderId};
+use serde::{Deserialize, Serialize};
+
+use crate::projections::purchasing::PurchaseOrderReadModel;
+
+/// Accepted variances before a line is flagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchTolerances {
+    /// Absolute quantity difference accepted (units).
+    pub quantity: u64,
+    /// Relative unit price difference accepted, in basis points (100 = 1%).
+    pub price_bps: u32,
+}
+
+impl Default for MatchTolerances {
+    fn default() -> Self {
+        Self {
+            quantity: 0,
+            price_bps: 100,
+        }
+    }
+}
+
+/// One line of a supplier invoice (amounts in minor units).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SupplierInvoiceLine {
+    pub product_id: ProductId,
+    pub quantity: i64,
+    pub unit_price: u64,
+}
+
+/// Supplier invoice as presented for payment.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SupplierInvoice {
+    pub lines: Vec<SupplierInvoiceLine>,
+}
+
+/// A variance beyond tolerance on one line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Discrepancy {
+    /// Received quantity differs from the ordered quantity.
+    ReceivedQuantity { ordered: i64, received: i64 },
+    /// Invoiced quantity differs from the received quantity.
+    InvoicedQuantity { received: i64, invoiced: i64 },
+    /// Invoiced unit price differs from the expected price.
+    Price { expected: u64, invoiced: u64, variance_bps: u64 },
+    /// Invoiced product does not appear on the purchase order.
+    NotOrdered,
+}
+
+/// Match result for one product.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineMatch {
+    pub product_id: ProductId,
+    pub ordered_quantity: i64,
+    pub received_quantity: i64,
+    pub invoiced_quantity: i64,
+    pub expected_unit_price: Option<u64>,
+    pub invoiced_unit_price: Option<u64>,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl LineMatch {
+    pub fn is_match(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+/// Per-line 3-way match report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchReport {
+    pub order_id: PurchaseOrderId,
+    /// `true` when no line has a discrepancy.
+    pub matched: bool,
+    pub tolerances: MatchTolerances,
+    pub lines: Vec<LineMatch>,
+}
+
+#[derive(Default)]
+struct Totals {
+    ordered: i64,
+    received: i64,
+    invoiced: i64,
+    on_order: bool,
+    /// Quantity-weighted invoice amount, to derive an average unit price.
+    invoiced_amount: u128,
+}
+
+/// Totals per product, in first-seen order.
+#[derive(Default)]
+struct ProductTotals {
+    lines: Vec<(ProductId, Totals)>,
+    index: HashMap<ProductId, usize>,
+}
+
+impl ProductTotals {
+    fn entry(&mut self, product_id: ProductId) -> &mut Totals {
+        let i = *self.index.entry(product_id).or_insert_with(|| {
+            self.lines.push((product_id, Totals::default()));
+            self.lines.len() - 1
+        });
+        &mut self.lines[i].1
+    }
+}
+
+/// 3-way match service.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreeWayMatch {
+    tolerances: MatchTolerances,
+}
+
+impl ThreeWayMatch {
+    pub fn new(tolerances: MatchTolerances) -> Self {
+        Self { tolerances }
+    }
+
+    /// Compare an order, its received lines and a supplier invoice.
+    ///
+    /// `expected_prices` holds the agreed unit price per product; products without
+    /// one are not price-checked.
+    pub fn check(
+        &self,
+        order: &PurchaseOrderReadModel,
+        received: &[LineItem],
+        invoice: &SupplierInvoice,
+        expected_prices: &HashMap<ProductId, u64>,
+    ) -> MatchReport {
+        // Keep PO line order in the report; unordered invoice lines go last.
+        let mut totals = ProductTotals::default();
+        for l in &order.lines {
+            let t = totals.entry(l.product_id);
+            t.ordered += l.quantity;
+            t.on_order = true;
+        }
+        for l in received {
+            totals.entry(l.product_id).received += l.quantity;
+        }
+        for l in &invoice.lines {
+            let t = totals.entry(l.product_id);
+            t.invoiced += l.quantity;
+            t.invoiced_amount += l.quantity.max(0) as u128 * l.unit_price as u128;
+        }
+
+        let lines: Vec<LineMatch> = totals
+            .lines
+            .iter()
+            .map(|(product_id, t)| self.line(*product_id, t, expected_prices.get(product_id).copied()))
+            .collect();
+
+        MatchReport {
+            order_id: order.order_id,
+            matched: lines.iter().all(LineMatch::is_match),
+            tolerances: self.tolerances,
+            lines,
+        }
+    }
+
+    fn line(&self, product_id: ProductId, t: &Totals, expected: Option<u64>) -> LineMatch {
+        let mut discrepancies = Vec::new();
+        let within = |a: i64, b: i64| a.abs_diff(b) <= self.tolerances.quantity;
+
+        if !t.on_order {
+            discrepancies.push(Discrepancy::NotOrdered);
+        } else if !within(t.ordered, t.received) {
+            discrepancies.push(Discrepancy::ReceivedQuantity {
+                ordered: t.ordered,
+                received: t.received,
+            });
+        }
+        if !within(t.received, t.invoiced) {
+            discrepancies.push(Discrepancy::InvoicedQuantity {
+                received: t.received,
+                invoiced: t.invoiced,
+            });
+        }
+
+        let invoiced_price = (t.invoiced > 0).then(|| (t.invoiced_amount / t.invoiced as u128) as u64);
+        if let (Some(expected), Some(invoiced)) = (expected, invoiced_price) {
+            let variance_bps = if expected == 0 {
+                if invoiced == 0 { 0 } else { u64::MAX }
+            } else {
+                (expected.abs_diff(invoiced) as u128 * 10_000 / expected as u128) as u64
+            };
+            if variance_bps > self.tolerances.price_bps as u64 {
+                discrepancies.push(Discrepancy::Price {
+                    expected,
+                    invoiced,
+                    variance_bps,
+                });
+            }
+        }
+
+        LineMatch {
+            product_id,
+            ordered_quantity: t.ordered,
+            received_quantity: t.received,
+            invoiced_quantity: t.invoiced,
+            expected_unit_price: expected,
+            invoiced_unit_price: invoiced_price,
+            discrepancies,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forgeerp_core::AggregateId;
+    use forgeerp_parties::PartyId;
+    use forgeerp_purchasing::PurchaseOrderStatus;
+
+    use super::*;
+
+    struct Fixture {
+        order: PurchaseOrderReadModel,
+        bolts: ProductId,
+        nuts: ProductId,
+        prices: HashMap<ProductId, u64>,
+    }
+
+    fn fixture() -> Fixture {
+        let bolts = ProductId::new(AggregateId::new());
+        let nuts = ProductId::new(AggregateId::new());
+        let order = PurchaseOrderReadModel {
+            status: PurchaseOrderStatus::Received,
+            lines: vec![
+                LineItem { line_no: 1, product_id: bolts, quantity: 100 },
+                LineItem { line_no: 2, product_id: nuts, quantity: 50 },
+            ],
+            ..PurchaseOrderReadModel::new(PurchaseOrderId::new(AggregateId::new()), PartyId::new(AggregateId::new()))
+        };
+        let prices = HashMap::from([(bolts, 200), (nuts, 50)]);
+        Fixture { order, bolts, nuts, prices }
+    }
+
+    fn invoice(lines: &[(ProductId, i64, u64)]) -> SupplierInvoice {
+        SupplierInvoice {
+            lines: lines
+                .iter()
+                .map(|&(product_id, quantity, unit_price)| SupplierInvoiceLine {
+                    product_id,
+                    quantity,
+                    unit_price,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn clean_match_has_no_discrepancies() {
+        let f = fixture();
+        // Price within the default 1% tolerance.
+        let inv = invoice(&[(f.bolts, 100, 201), (f.nuts, 50, 50)]);
+
+        let report = ThreeWayMatch::default().check(&f.order, &f.order.lines, &inv, &f.prices);
+
+        assert!(report.matched);
+        assert_eq!(report.lines.len(), 2);
+        assert_eq!(report.lines[0].product_id, f.bolts);
+        assert_eq!(report.lines[0].invoiced_unit_price, Some(201));
+    }
+
+    #[test]
+    fn quantity_variance_flags_only_the_short_line() {
+        let f = fixture();
+        let received = vec![
+            LineItem { line_no: 1, product_id: f.bolts, quantity: 100 },
+            LineItem { line_no: 2, product_id: f.nuts, quantity: 40 },
+        ];
+        let inv = invoice(&[(f.bolts, 100, 200), (f.nuts, 50, 50)]);
+
+        let report = ThreeWayMatch::default().check(&f.order, &received, &inv, &f.prices);
+
+        assert!(!report.matched);
+        assert!(report.lines[0].is_match());
+        assert_eq!(
+            report.lines[1].discrepancies,
+            vec![
+                Discrepancy::ReceivedQuantity { ordered: 50, received: 40 },
+                Discrepancy::InvoicedQuantity { received: 40, invoiced: 50 },
+            ]
+        );
+
+        // A configured quantity tolerance accepts the same shortfall.
+        let lenient = ThreeWayMatch::new(MatchTolerances { quantity: 10, price_bps: 100 });
+        assert!(lenient.check(&f.order, &received, &inv, &f.prices).matched);
+    }
+
+    #[test]
+    fn price_variance_beyond_tolerance_is_flagged() {
+        let f = fixture();
+        let inv = invoice(&[(f.bolts, 100, 200), (f.nuts, 50, 55)]);
+
+        let report = ThreeWayMatch::default().check(&f.order, &f.order.lines, &inv, &f.prices);
+
+        assert!(!report.matched);
+        assert!(report.lines[0].is_match());
+        assert_eq!(
+            report.lines[1].discrepancies,
+            vec![Discrepancy::Price { expected: 50, invoiced: 55, variance_bps: 1000 }]
+        );
+    }
+
+    #[test]
+    fn invoiced_product_not_on_order_is_flagged() {
+        let f = fixture();
+        let washers = ProductId::new(AggregateId::new());
+        let inv = invoice(&[(f.bolts, 100, 200), (f.nuts, 50, 50), (washers, 10, 5)]);
+
+        let report = ThreeWayMatch::default().check(&f.order, &f.order.lines, &inv, &f.prices);
+
+        assert_eq!(report.lines.len(), 3);
+        assert_eq!(report.lines[2].product_id, washers);
+        assert!(report.lines[2].discrepancies.contains(&Discrepancy::NotOrdered));
+    }
+}
@@ -0,0 +1,276 @@
+//! Target policy for outbound webhooks.
+//!
+//! A webhook URL chosen by a tenant must not become a way to reach internal
+//! services (SSRF). [`WebhookTargetPolicy`] decides which URLs may be called:
+//!
+//! - the scheme must be allowed (`https` only by default);
+//! - the host must match an allow pattern (when any are configured) and no deny pattern;
+//! - loopback, private, link-local (cloud metadata) and similar addresses are
+//!   rejected unless `allow_private` is set.
+//!
+//! Check the URL with [`WebhookTargetPolicy::check`] when the target is registered,
+//! and with [`WebhookTargetPolicy::check_resolved`] right before each delivery so a
+//! host name that later resolves to an internal address is still refused.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use reqwest::Url;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum WebhookTargetError {
+    #[error("invalid webhook url: {0}")]
+    InvalidUrl(String),
+
+    #[error("webhook scheme not allowed: {0}")]
+    SchemeNotAllowed(String),
+
+    #[error("webhook host not allowed: {0}")]
+    HostNotAllowed(String),
+
+    #[error("webhook target is an internal address: {0}")]
+    InternalAddress(String),
+}
+
+/// Which webhook URLs may be called.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookTargetPolicy {
+    /// Accepted URL schemes (lowercase).
+    pub allowed_schemes: Vec<String>,
+    /// Host patterns a target must match; empty allows any public host.
+    ///
+    /// Patterns are exact host names or `*.example.com` (any subdomain).
+    pub allowed_hosts: Vec<String>,
+    /// Host patterns that are always rejected.
+    pub denied_hosts: Vec<String>,
+    /// Permit loopback/private/link-local targets (local development only).
+    pub allow_private: bool,
+}
+
+impl Default for WebhookTargetPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_schemes: vec!["https".to_string()],
+            allowed_hosts: Vec::new(),
+            denied_hosts: Vec::new(),
+            allow_private: false,
+        }
+    }
+}
+
+impl WebhookTargetPolicy {
+    /// Read the policy from `WEBHOOK_*` variables (comma-separated lists).
+    ///
+    /// - `WEBHOOK_ALLOWED_SCHEMES` (default `https`)
+    /// - `WEBHOOK_ALLOWED_HOSTS` / `WEBHOOK_DENIED_HOSTS`
+    /// - `WEBHOOK_ALLOW_PRIVATE_TARGETS=true`
+    pub fn from_env() -> Self {
+        let list = |name: &str| std::env::var(name).ok().map(|v| parse_list(&v));
+        let default = Self::default();
+
+        Self {
+            allowed_schemes: list("WEBHOOK_ALLOWED_SCHEMES")
+                .filter(|s| !s.is_empty())
+                .unwrap_or(default.allowed_schemes),
+            allowed_hosts: list("WEBHOOK_ALLOWED_HOSTS").unwrap_or_default(),
+            denied_hosts: list("WEBHOOK_DENIED_HOSTS").unwrap_or_default(),
+            allow_private: std::env::var("WEBHOOK_ALLOW_PRIVATE_TARGETS")
+                .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+                .unwrap_or(false),
+        }
+    }
+
+    /// Validate a target URL without resolving its host (use when the target is registered).
+    pub fn check(&self, url: &str) -> Result<Url, WebhookTargetError> {
+        let url = Url::parse(url.trim()).map_err(|e| WebhookTargetError::InvalidUrl(e.to_string()))?;
+
+        if !self.allowed_schemes.iter().any(|s| s == url.scheme()) {
+            return Err(WebhookTargetError::SchemeNotAllowed(url.scheme().to_string()));
+        }
+
+        let name = url
+            .host_str()
+            .ok_or_else(|| WebhookTargetError::InvalidUrl("url has no host".to_string()))?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_ascii_lowercase();
+
+        if self.denied_hosts.iter().any(|p| host_matches(p, &name))
+            || (!self.allowed_hosts.is_empty() && !self.allowed_hosts.iter().any(|p| host_matches(p, &name)))
+        {
+            return Err(WebhookTargetError::HostNotAllowed(name));
+        }
+
+        if !self.allow_private {
+            // IP hosts are already normalized by the parser (e.g. `2130706433` → `127.0.0.1`).
+            let internal = match name.parse::<IpAddr>() {
+                Ok(ip) => is_internal_ip(ip),
+                Err(_) => is_internal_name(&name),
+            };
+            if internal {
+                return Err(WebhookTargetError::InternalAddress(name));
+            }
+        }
+
+        Ok(url)
+    }
+
+    /// Validate a target URL and every address its host resolves to (use before each delivery).
+    ///
+    /// Returns the checked addresses; connect to one of these rather than resolving again.
+    pub fn check_resolved(&self, url: &str) -> Result<Vec<SocketAddr>, WebhookTargetError> {
+        let url = self.check(url)?;
+        let addrs: Vec<SocketAddr> = url
+            .socket_addrs(|| None)
+            .map_err(|e| WebhookTargetError::InvalidUrl(e.to_string()))?;
+
+        if !self.allow_private
+            && let Some(internal) = addrs.iter().find(|a| is_internal_ip(a.ip()))
+        {
+            return Err(WebhookTargetError::InternalAddress(internal.ip().to_string()));
+        }
+        Ok(addrs)
+    }
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_ascii_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// `example.com` matches only itself; `*.example.com` matches its subdomains.
+fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host.len() > suffix.len() && host.ends_with(&format!(".{suffix}")),
+        None => host == pattern,
+    }
+}
+
+fn is_internal_name(host: &str) -> bool {
+    let host = host.trim_end_matches('.');
+    host == "localhost" || host.ends_with(".localhost") || host.ends_with(".internal")
+}
+
+fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_internal_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_internal_v4(v4),
+            None => is_internal_v6(ip),
+        },
+    }
+}
+
+fn is_internal_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        // 0.0.0.0/8 and carrier-grade NAT (100.64.0.0/10).
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+}
+
+fn is_internal_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // Unique local (fc00::/7) and link-local (fe80::/10).
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowing_http() -> WebhookTargetPolicy {
+        WebhookTargetPolicy {
+            allowed_schemes: vec!["http".to_string(), "https".to_string()],
+            ..WebhookTargetPolicy::default()
+        }
+    }
+
+    #[test]
+    fn public_https_url_is_allowed() {
+        let url = WebhookTargetPolicy::default()
+            .check("https://hooks.example.com/forgeerp?token=abc")
+            .unwrap();
+        assert_eq!(url.host_str(), Some("hooks.example.com"));
+    }
+
+    #[test]
+    fn metadata_endpoint_is_blocked() {
+        let policy = allowing_http();
+        for url in [
+            "http://169.254.169.254/latest/meta-data/",
+            "http://169.254.1.1/",
+            "http://[::ffff:169.254.169.254]/",
+            "http://metadata.google.internal/",
+        ] {
+            assert!(
+                matches!(policy.check(url), Err(WebhookTargetError::InternalAddress(_))),
+                "{url} was not blocked"
+            );
+        }
+        // Plain http is already refused by the default scheme list.
+        assert!(matches!(
+            WebhookTargetPolicy::default().check("http://169.254.169.254/"),
+            Err(WebhookTargetError::SchemeNotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn loopback_url_is_blocked() {
+        let policy = allowing_http();
+        for url in [
+            "https://127.0.0.1/hook",
+            "http://localhost:8080/hook",
+            "https://api.localhost/hook",
+            "https://[::1]/hook",
+            "http://2130706433/hook",
+            "https://10.0.0.5/hook",
+        ] {
+            assert!(
+                matches!(policy.check(url), Err(WebhookTargetError::InternalAddress(_))),
+                "{url} was not blocked"
+            );
+        }
+        assert!(matches!(
+            policy.check_resolved("http://127.0.0.1:9/hook"),
+            Err(WebhookTargetError::InternalAddress(_))
+        ));
+
+        let local_dev = WebhookTargetPolicy {
+            allow_private: true,
+            ..allowing_http()
+        };
+        assert!(local_dev.check("http://localhost:8080/hook").is_ok());
+    }
+
+    #[test]
+    fn host_patterns_restrict_targets() {
+        let policy = WebhookTargetPolicy {
+            allowed_hosts: vec!["*.example.com".to_string()],
+            denied_hosts: vec!["legacy.example.com".to_string()],
+            ..WebhookTargetPolicy::default()
+        };
+
+        assert!(policy.check("https://hooks.example.com/").is_ok());
+        for url in ["https://example.com/", "https://legacy.example.com/", "https://evil-example.com/"] {
+            assert!(
+                matches!(policy.check(url), Err(WebhookTargetError::HostNotAllowed(_))),
+                "{url} was not rejected"
+            );
+        }
+        assert!(matches!(policy.check("not a url"), Err(WebhookTargetError::InvalidUrl(_))));
+    }
+}
@@ -0,0 +1,306 @@
+//! Liveness of background tasks (subscribers, runners).
+//!
+//! Background tasks run detached, so a panic or a closed bus channel used to stop them
+//! silently while read models froze. Each task now registers in a [`TaskRegistry`] and
+//! reports through its [`TaskBeat`]:
+//!
+//! - a heartbeat at least every [`HEARTBEAT_INTERVAL`] while it is alive (idle or busy),
+//! - when it processed a message (and whether that failed),
+//! - how it ended: stopped on request, or exited unexpectedly (bus closed, panic).
+//!
+//! [`TaskRegistry::report`] turns that into a [`LivenessReport`]: a task that exited
+//! unexpectedly or has not beaten for `stall_after` makes the instance unhealthy.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::error;
+
+use forgeerp_events::Subscription;
+
+/// Longest a live task goes without a heartbeat.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+enum RunState {
+    Running,
+    Stopped,
+    Exited(String),
+}
+
+#[derive(Debug, Clone)]
+struct TaskState {
+    run: RunState,
+    started_at: DateTime<Utc>,
+    last_heartbeat: DateTime<Utc>,
+    last_processed: Option<DateTime<Utc>>,
+    processed: u64,
+    failures: u64,
+}
+
+/// Registry of background tasks for one instance (cheap to clone).
+#[derive(Debug, Clone, Default)]
+pub struct TaskRegistry {
+    tasks: Arc<Mutex<BTreeMap<String, TaskState>>>,
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register (or restart) the task `name` as running.
+    pub fn register(&self, name: impl Into<String>) -> TaskBeat {
+        let name: Arc<str> = Arc::from(name.into());
+        let now = Utc::now();
+        self.update_all(|tasks| {
+            tasks.insert(
+                name.to_string(),
+                TaskState {
+                    run: RunState::Running,
+                    started_at: now,
+                    last_heartbeat: now,
+                    last_processed: None,
+                    processed: 0,
+                    failures: 0,
+                },
+            );
+        });
+        TaskBeat {
+            name,
+            registry: Some(self.clone()),
+        }
+    }
+
+    /// Status of every task as of `now`; running tasks silent for `stall_after` are stalled.
+    pub fn report(&self, now: DateTime<Utc>, stall_after: Duration) -> LivenessReport {
+        let stall_after = chrono::Duration::from_std(stall_after).unwrap_or(chrono::Duration::MAX);
+        let tasks = self.update_all(|tasks| {
+            tasks
+                .iter()
+                .map(|(name, s)| {
+                    let (health, exit_reason) = match &s.run {
+                        RunState::Running if now - s.last_heartbeat > stall_after => (TaskHealth::Stalled, None),
+                        RunState::Running => (TaskHealth::Running, None),
+                        RunState::Stopped => (TaskHealth::Stopped, None),
+                        RunState::Exited(reason) => (TaskHealth::Exited, Some(reason.clone())),
+                    };
+                    TaskStatus {
+                        name: name.clone(),
+                        health,
+                        exit_reason,
+                        started_at: s.started_at,
+                        last_heartbeat: s.last_heartbeat,
+                        last_processed: s.last_processed,
+                        processed: s.processed,
+                        failures: s.failures,
+                    }
+                })
+                .collect()
+        });
+        LivenessReport { tasks }
+    }
+
+    fn update_all<T>(&self, f: impl FnOnce(&mut BTreeMap<String, TaskState>) -> T) -> T {
+        f(&mut self.tasks.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn update(&self, name: &str, f: impl FnOnce(&mut TaskState)) {
+        self.update_all(|tasks| {
+            if let Some(state) = tasks.get_mut(name) {
+                f(state);
+            }
+        })
+    }
+}
+
+/// A task's handle for reporting liveness (clones report for the same task).
+#[derive(Debug, Clone)]
+pub struct TaskBeat {
+    name: Arc<str>,
+    registry: Option<TaskRegistry>,
+}
+
+impl TaskBeat {
+    /// Beat that reports nowhere (tasks spawned without a registry).
+    pub fn detached(name: &str) -> Self {
+        Self {
+            name: Arc::from(name),
+            registry: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn update(&self, f: impl FnOnce(&mut TaskState)) {
+        if let Some(registry) = &self.registry {
+            registry.update(&self.name, f);
+        }
+    }
+
+    /// Still alive.
+    pub fn beat(&self) {
+        self.update(|s| s.last_heartbeat = Utc::now());
+    }
+
+    /// Processed one message; `ok = false` counts a failure.
+    pub fn processed(&self, ok: bool) {
+        let now = Utc::now();
+        self.update(|s| {
+            s.last_heartbeat = now;
+            s.last_processed = Some(now);
+            s.processed += 1;
+            if !ok {
+                s.failures += 1;
+            }
+        });
+    }
+
+    /// Stopped on request (shutdown); not a health failure.
+    pub fn stopped(&self) {
+        self.update(|s| s.run = RunState::Stopped);
+    }
+
+    /// Ended without being asked to; logged as an error and reported unhealthy.
+    pub fn exited(&self, reason: &str) {
+        error!(task = %self.name, reason, "background task exited unexpectedly");
+        self.update(|s| s.run = RunState::Exited(reason.to_string()));
+    }
+
+    /// Guard that reports a panic of the current thread as an unexpected exit.
+    pub fn panic_guard(&self) -> PanicGuard<'_> {
+        PanicGuard(self)
+    }
+}
+
+/// See [`TaskBeat::panic_guard`].
+pub struct PanicGuard<'a>(&'a TaskBeat);
+
+impl Drop for PanicGuard<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0.exited("panicked");
+        }
+    }
+}
+
+/// Health of one task in a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskHealth {
+    Running,
+    /// Running but silent for longer than the stall threshold.
+    Stalled,
+    /// Stopped on request.
+    Stopped,
+    /// Ended unexpectedly (bus closed, panic).
+    Exited,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskStatus {
+    pub name: String,
+    pub health: TaskHealth,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_reason: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub last_heartbeat: DateTime<Utc>,
+    pub last_processed: Option<DateTime<Utc>>,
+    pub processed: u64,
+    pub failures: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LivenessReport {
+    pub tasks: Vec<TaskStatus>,
+}
+
+impl LivenessReport {
+    /// No task stalled or exited unexpectedly.
+    pub fn is_healthy(&self) -> bool {
+        self.tasks
+            .iter()
+            .all(|t| matches!(t.health, TaskHealth::Running | TaskHealth::Stopped))
+    }
+}
+
+/// Consume `sub` until the bus closes, calling `handle` for every message and beating
+/// while idle. A closed bus is never expected for a subscriber, so returning reports an
+/// unexpected exit (as does a panic in `handle`).
+pub fn supervise<M, E>(beat: &TaskBeat, sub: Subscription<M>, mut handle: impl FnMut(M) -> Result<(), E>) {
+    let _guard = beat.panic_guard();
+    loop {
+        match sub.recv_timeout(HEARTBEAT_INTERVAL) {
+            Ok(message) => beat.processed(handle(message).is_ok()),
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => beat.beat(),
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    beat.exited("subscription closed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forgeerp_events::{EventBus, InMemoryEventBus};
+
+    fn health(registry: &TaskRegistry, name: &str, now: DateTime<Utc>) -> TaskHealth {
+        let report = registry.report(now, Duration::from_secs(30));
+        report.tasks.iter().find(|t| t.name == name).unwrap().health
+    }
+
+    #[test]
+    fn subscriber_whose_bus_closed_reports_unhealthy() {
+        let registry = TaskRegistry::new();
+        let bus = InMemoryEventBus::<u32>::new();
+        let beat = registry.register("test.subscriber");
+        let sub = bus.subscribe();
+        let task = std::thread::spawn(move || supervise(&beat, sub, |_: u32| Ok::<(), ()>(())));
+
+        bus.publish(1).unwrap();
+        drop(bus);
+        task.join().unwrap();
+
+        let report = registry.report(Utc::now(), Duration::from_secs(30));
+        assert!(!report.is_healthy());
+        let status = &report.tasks[0];
+        assert_eq!(status.health, TaskHealth::Exited);
+        assert_eq!(status.exit_reason.as_deref(), Some("subscription closed"));
+        assert_eq!(status.processed, 1);
+    }
+
+    #[test]
+    fn silent_task_is_stalled_and_stopped_task_is_not_a_failure() {
+        let registry = TaskRegistry::new();
+        let stalled = registry.register("stalled");
+        let stopped = registry.register("stopped");
+        stalled.beat();
+        stopped.stopped();
+
+        assert_eq!(health(&registry, "stalled", Utc::now()), TaskHealth::Running);
+        let later = Utc::now() + chrono::Duration::seconds(31);
+        assert_eq!(health(&registry, "stalled", later), TaskHealth::Stalled);
+        assert_eq!(health(&registry, "stopped", later), TaskHealth::Stopped);
+        assert!(!registry.report(later, Duration::from_secs(30)).is_healthy());
+    }
+
+    #[test]
+    fn panicking_handler_is_reported_as_exited() {
+        let registry = TaskRegistry::new();
+        let bus = InMemoryEventBus::<u32>::new();
+        let beat = registry.register("test.panicky");
+        let sub = bus.subscribe();
+        let task = std::thread::spawn(move || supervise(&beat, sub, |_: u32| -> Result<(), ()> { panic!("boom") }));
+
+        bus.publish(1).unwrap();
+        assert!(task.join().is_err());
+
+        let report = registry.report(Utc::now(), Duration::from_secs(30));
+        assert_eq!(report.tasks[0].exit_reason.as_deref(), Some("panicked"));
+    }
+}
@@ -1,7 +1,11 @@
 //! Background workers (projection runners, etc).
 
+pub mod liveness;
 pub mod projection_worker;
+pub mod sharded_worker;
 
+pub use liveness::{supervise, LivenessReport, TaskBeat, TaskHealth, TaskRegistry, TaskStatus, HEARTBEAT_INTERVAL};
 pub use projection_worker::{ProjectionWorker, WorkerHandle};
+pub use sharded_worker::{ShardKey, ShardedProjectionWorker, ShardingConfig};
 
 
@@ -10,15 +10,16 @@ use forgeerp_events::{EventBus, Subscription, TenantScoped};
 /// Handle to control and join a background worker.
 #[derive(Debug)]
 pub struct WorkerHandle {
-    shutdown: mpsc::Sender<()>,
-    join: Option<thread::JoinHandle<()>>,
+    pub(super) shutdown: mpsc::Sender<()>,
+    /// Worker threads, joined in order on shutdown.
+    pub(super) joins: Vec<thread::JoinHandle<()>>,
 }
 
 impl WorkerHandle {
     /// Request graceful shutdown and wait for the worker to stop.
-    pub fn shutdown(mut self) {
+    pub fn shutdown(self) {
         let _ = self.shutdown.send(());
-        if let Some(j) = self.join.take() {
+        for j in self.joins {
             let _ = j.join();
         }
     }
@@ -60,7 +61,7 @@ impl ProjectionWorker {
 
         WorkerHandle {
             shutdown: shutdown_tx,
-            join: Some(join),
+            joins: vec![join],
         }
     }
 }
@@ -1,8 +1,10 @@
+use std::collections::{HashMap, HashSet};
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use forgeerp_core::{Aggregate, AggregateRoot, AggregateId, DomainError, TenantId};
-use forgeerp_events::Event;
+use forgeerp_core::{Aggregate, AggregateRoot, AggregateId, DomainError, ErrorCode, TenantId};
+use forgeerp_events::{Command, Event};
 
 /// Inventory item identifier (tenant-scoped via `tenant_id` fields in events/commands).
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -22,12 +24,21 @@ impl core::fmt::Display for InventoryItemId {
 }
 
 /// Aggregate root: InventoryItem.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct InventoryItem {
     id: InventoryItemId,
     tenant_id: Option<TenantId>,
     name: String,
-    stock: i64,
+    /// On-hand quantity per location id.
+    locations: HashMap<String, i64>,
+    /// Open reservations by reservation id.
+    reservations: HashMap<AggregateId, i64>,
+    /// Quantity held by open reservations (sum of `reservations`); never exceeds [`Self::stock`].
+    reserved: i64,
+    /// Total stock below which a reorder is due; `None` when not set.
+    reorder_point: Option<i64>,
+    /// Released reservation ids; a reservation id is used at most once.
+    released: HashSet<AggregateId>,
     version: u64,
     created: bool,
 }
@@ -39,7 +50,11 @@ impl InventoryItem {
             id,
             tenant_id: None,
             name: String::new(),
-            stock: 0,
+            locations: HashMap::new(),
+            reservations: HashMap::new(),
+            reserved: 0,
+            reorder_point: None,
+            released: HashSet::new(),
             version: 0,
             created: false,
         }
@@ -53,8 +68,42 @@ impl InventoryItem {
         self.tenant_id
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Total on-hand quantity across all locations.
     pub fn stock(&self) -> i64 {
-        self.stock
+        self.locations.values().sum()
+    }
+
+    /// On-hand quantity at `location_id` (0 for a location never stocked).
+    pub fn stock_at(&self, location_id: &str) -> i64 {
+        self.locations.get(location_id).copied().unwrap_or(0)
+    }
+
+    /// On-hand quantity per location id.
+    pub fn locations(&self) -> &HashMap<String, i64> {
+        &self.locations
+    }
+
+    /// Quantity held by open reservations.
+    pub fn reserved(&self) -> i64 {
+        self.reserved
+    }
+
+    /// Stock that can still be reserved (`stock - reserved`).
+    pub fn available(&self) -> i64 {
+        self.stock() - self.reserved
+    }
+
+    /// Quantity held by the open reservation `reservation_id`, if any.
+    pub fn reservation(&self, reservation_id: AggregateId) -> Option<i64> {
+        self.reservations.get(&reservation_id).copied()
+    }
+
+    pub fn reorder_point(&self) -> Option<i64> {
+        self.reorder_point
     }
 }
 
@@ -79,19 +128,112 @@ pub struct CreateItem {
     pub occurred_at: DateTime<Utc>,
 }
 
+/// Command: RenameItem (corrects the name given at creation).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenameItem {
+    pub tenant_id: TenantId,
+    pub item_id: InventoryItemId,
+    pub name: String,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Location used by adjustments that predate per-location stock (and by callers that
+/// do not track locations).
+pub const DEFAULT_LOCATION: &str = "default";
+
+fn default_location() -> String {
+    DEFAULT_LOCATION.to_string()
+}
+
 /// Command: AdjustStock.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AdjustStock {
     pub tenant_id: TenantId,
     pub item_id: InventoryItemId,
+    /// Location whose quantity changes.
+    #[serde(default = "default_location")]
+    pub location_id: String,
     pub delta: i64,
     pub occurred_at: DateTime<Utc>,
 }
 
+/// Command: TransferStock.
+///
+/// Moves `quantity` from one location to another; emits a negative `StockAdjusted` at
+/// the source and a positive one at the destination, so the total stock is unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferStock {
+    pub tenant_id: TenantId,
+    pub item_id: InventoryItemId,
+    pub from_location: String,
+    pub to_location: String,
+    pub quantity: i64,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Code of the invariant refusing a reservation larger than the available stock.
+pub const INSUFFICIENT_AVAILABLE_STOCK: ErrorCode = ErrorCode::new("inventory.insufficient_available_stock");
+
+/// Command: ReserveStock.
+///
+/// Holds `quantity` for the caller (e.g. a sales order) under `reservation_id`. Reserving
+/// an id that is already open or was released is a no-op, so redelivery cannot double-book.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReserveStock {
+    pub tenant_id: TenantId,
+    pub item_id: InventoryItemId,
+    pub reservation_id: AggregateId,
+    pub quantity: i64,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Code of the validation refusing a release that names a reservation never made.
+pub const UNKNOWN_RESERVATION: ErrorCode = ErrorCode::new("inventory.unknown_reservation");
+
+/// Command: ReleaseReservation (no-op for a reservation already released).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseReservation {
+    pub tenant_id: TenantId,
+    pub item_id: InventoryItemId,
+    pub reservation_id: AggregateId,
+    /// Release only part of the reservation; `None` releases all of it.
+    #[serde(default)]
+    pub quantity: Option<i64>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Command: SetReorderPoint (`None` clears it).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetReorderPoint {
+    pub tenant_id: TenantId,
+    pub item_id: InventoryItemId,
+    pub reorder_point: Option<i64>,
+    pub occurred_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InventoryCommand {
     CreateItem(CreateItem),
+    RenameItem(RenameItem),
     AdjustStock(AdjustStock),
+    TransferStock(TransferStock),
+    ReserveStock(ReserveStock),
+    ReleaseReservation(ReleaseReservation),
+    SetReorderPoint(SetReorderPoint),
+}
+
+impl Command for InventoryCommand {
+    fn target_aggregate_id(&self) -> AggregateId {
+        match self {
+            InventoryCommand::CreateItem(c) => c.item_id.0,
+            InventoryCommand::RenameItem(c) => c.item_id.0,
+            InventoryCommand::AdjustStock(c) => c.item_id.0,
+            InventoryCommand::TransferStock(c) => c.item_id.0,
+            InventoryCommand::ReserveStock(c) => c.item_id.0,
+            InventoryCommand::ReleaseReservation(c) => c.item_id.0,
+            InventoryCommand::SetReorderPoint(c) => c.item_id.0,
+        }
+    }
 }
 
 /// Event: ItemCreated.
@@ -103,26 +245,92 @@ pub struct ItemCreated {
     pub occurred_at: DateTime<Utc>,
 }
 
+/// Event: ItemRenamed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemRenamed {
+    pub tenant_id: TenantId,
+    pub item_id: InventoryItemId,
+    pub name: String,
+    pub occurred_at: DateTime<Utc>,
+}
+
 /// Event: StockAdjusted.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StockAdjusted {
     pub tenant_id: TenantId,
     pub item_id: InventoryItemId,
+    /// Location whose quantity changed; events recorded before per-location stock
+    /// replay into [`DEFAULT_LOCATION`].
+    #[serde(default = "default_location")]
+    pub location_id: String,
     pub delta: i64,
     pub occurred_at: DateTime<Utc>,
 }
 
+/// Event: ReorderPointSet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReorderPointSet {
+    pub tenant_id: TenantId,
+    pub item_id: InventoryItemId,
+    pub reorder_point: Option<i64>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Event: StockFellBelowReorderPoint.
+///
+/// Emitted next to the `StockAdjusted` that takes total stock from at-or-above the
+/// reorder point to below it; further adjustments below the point do not repeat it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StockFellBelowReorderPoint {
+    pub tenant_id: TenantId,
+    pub item_id: InventoryItemId,
+    /// Total stock after the adjustment.
+    pub current_stock: i64,
+    pub reorder_point: i64,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Event: StockReserved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StockReserved {
+    pub tenant_id: TenantId,
+    pub item_id: InventoryItemId,
+    pub reservation_id: AggregateId,
+    pub quantity: i64,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Event: ReservationReleased.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReservationReleased {
+    pub tenant_id: TenantId,
+    pub item_id: InventoryItemId,
+    pub reservation_id: AggregateId,
+    pub quantity: i64,
+    pub occurred_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InventoryEvent {
     ItemCreated(ItemCreated),
+    ItemRenamed(ItemRenamed),
     StockAdjusted(StockAdjusted),
+    StockReserved(StockReserved),
+    ReservationReleased(ReservationReleased),
+    ReorderPointSet(ReorderPointSet),
+    StockFellBelowReorderPoint(StockFellBelowReorderPoint),
 }
 
 impl Event for InventoryEvent {
     fn event_type(&self) -> &'static str {
         match self {
             InventoryEvent::ItemCreated(_) => "inventory.item.created",
+            InventoryEvent::ItemRenamed(_) => "inventory.item.renamed",
             InventoryEvent::StockAdjusted(_) => "inventory.item.stock_adjusted",
+            InventoryEvent::StockReserved(_) => "inventory.item.stock_reserved",
+            InventoryEvent::ReservationReleased(_) => "inventory.item.reservation_released",
+            InventoryEvent::ReorderPointSet(_) => "inventory.item.reorder_point_set",
+            InventoryEvent::StockFellBelowReorderPoint(_) => "inventory.item.stock_fell_below_reorder_point",
         }
     }
 
@@ -133,7 +341,12 @@ impl Event for InventoryEvent {
     fn occurred_at(&self) -> DateTime<Utc> {
         match self {
             InventoryEvent::ItemCreated(e) => e.occurred_at,
+            InventoryEvent::ItemRenamed(e) => e.occurred_at,
             InventoryEvent::StockAdjusted(e) => e.occurred_at,
+            InventoryEvent::StockReserved(e) => e.occurred_at,
+            InventoryEvent::ReservationReleased(e) => e.occurred_at,
+            InventoryEvent::ReorderPointSet(e) => e.occurred_at,
+            InventoryEvent::StockFellBelowReorderPoint(e) => e.occurred_at,
         }
     }
 }
@@ -149,12 +362,35 @@ impl Aggregate for InventoryItem {
                 self.id = e.item_id;
                 self.tenant_id = Some(e.tenant_id);
                 self.name = e.name.clone();
-                self.stock = 0;
+                self.locations.clear();
                 self.created = true;
             }
+            InventoryEvent::ItemRenamed(e) => {
+                self.name = e.name.clone();
+            }
             InventoryEvent::StockAdjusted(e) => {
-                self.stock += e.delta;
+                *self.locations.entry(e.location_id.clone()).or_insert(0) += e.delta;
+            }
+            InventoryEvent::StockReserved(e) => {
+                self.reservations.insert(e.reservation_id, e.quantity);
+                self.reserved += e.quantity;
             }
+            InventoryEvent::ReservationReleased(e) => {
+                let held = self.reservations.get(&e.reservation_id).copied().unwrap_or(0);
+                let quantity = e.quantity.min(held);
+                self.reserved -= quantity;
+                if quantity >= held {
+                    self.reservations.remove(&e.reservation_id);
+                    self.released.insert(e.reservation_id);
+                } else {
+                    self.reservations.insert(e.reservation_id, held - quantity);
+                }
+            }
+            InventoryEvent::ReorderPointSet(e) => {
+                self.reorder_point = e.reorder_point;
+            }
+            // A notification; the stock change itself is the accompanying `StockAdjusted`.
+            InventoryEvent::StockFellBelowReorderPoint(_) => {}
         }
 
         // Deterministic version tracking: +1 per applied event.
@@ -164,7 +400,12 @@ impl Aggregate for InventoryItem {
     fn handle(&self, command: &Self::Command) -> Result<Vec<Self::Event>, Self::Error> {
         match command {
             InventoryCommand::CreateItem(cmd) => self.handle_create(cmd),
+            InventoryCommand::RenameItem(cmd) => self.handle_rename(cmd),
             InventoryCommand::AdjustStock(cmd) => self.handle_adjust(cmd),
+            InventoryCommand::TransferStock(cmd) => self.handle_transfer(cmd),
+            InventoryCommand::ReserveStock(cmd) => self.handle_reserve(cmd),
+            InventoryCommand::ReleaseReservation(cmd) => self.handle_release(cmd),
+            InventoryCommand::SetReorderPoint(cmd) => self.handle_set_reorder_point(cmd),
         }
     }
 }
@@ -202,6 +443,24 @@ impl InventoryItem {
         })])
     }
 
+    fn handle_rename(&self, cmd: &RenameItem) -> Result<Vec<InventoryEvent>, DomainError> {
+        if !self.created {
+            return Err(DomainError::not_found());
+        }
+        self.ensure_tenant(cmd.tenant_id)?;
+        self.ensure_item_id(cmd.item_id)?;
+
+        if cmd.name.trim().is_empty() {
+            return Err(DomainError::validation("name cannot be empty"));
+        }
+        Ok(vec![InventoryEvent::ItemRenamed(ItemRenamed {
+            tenant_id: cmd.tenant_id,
+            item_id: cmd.item_id,
+            name: cmd.name.clone(),
+            occurred_at: cmd.occurred_at,
+        })])
+    }
+
     fn handle_adjust(&self, cmd: &AdjustStock) -> Result<Vec<InventoryEvent>, DomainError> {
         if !self.created {
             return Err(DomainError::not_found());
@@ -212,17 +471,160 @@ impl InventoryItem {
         if cmd.delta == 0 {
             return Err(DomainError::validation("delta cannot be zero"));
         }
+        if cmd.location_id.trim().is_empty() {
+            return Err(DomainError::validation("location_id cannot be empty"));
+        }
 
-        let new_stock = self.stock + cmd.delta;
-        if new_stock < 0 {
-            return Err(DomainError::invariant("stock cannot go negative"));
+        if self.stock_at(&cmd.location_id) + cmd.delta < 0 {
+            return Err(DomainError::invariant(format!(
+                "stock cannot go negative at location {}",
+                cmd.location_id
+            )));
+        }
+        // Reserved stock is promised; only unreserved stock can be taken out.
+        if cmd.delta < 0 && self.stock() + cmd.delta < self.reserved {
+            return Err(DomainError::invariant(format!(
+                "stock cannot go below the reserved quantity ({})",
+                self.reserved
+            )));
         }
 
-        Ok(vec![InventoryEvent::StockAdjusted(StockAdjusted {
+        let mut events = vec![InventoryEvent::StockAdjusted(StockAdjusted {
             tenant_id: cmd.tenant_id,
             item_id: cmd.item_id,
+            location_id: cmd.location_id.clone(),
             delta: cmd.delta,
             occurred_at: cmd.occurred_at,
+        })];
+        let (before, after) = (self.stock(), self.stock() + cmd.delta);
+        if let Some(reorder_point) = self.reorder_point
+            && before >= reorder_point
+            && after < reorder_point
+        {
+            events.push(InventoryEvent::StockFellBelowReorderPoint(StockFellBelowReorderPoint {
+                tenant_id: cmd.tenant_id,
+                item_id: cmd.item_id,
+                current_stock: after,
+                reorder_point,
+                occurred_at: cmd.occurred_at,
+            }));
+        }
+        Ok(events)
+    }
+
+    fn handle_set_reorder_point(&self, cmd: &SetReorderPoint) -> Result<Vec<InventoryEvent>, DomainError> {
+        if !self.created {
+            return Err(DomainError::not_found());
+        }
+        self.ensure_tenant(cmd.tenant_id)?;
+        self.ensure_item_id(cmd.item_id)?;
+
+        if cmd.reorder_point.is_some_and(|p| p < 0) {
+            return Err(DomainError::validation("reorder_point cannot be negative"));
+        }
+        if cmd.reorder_point == self.reorder_point {
+            return Ok(vec![]);
+        }
+        Ok(vec![InventoryEvent::ReorderPointSet(ReorderPointSet {
+            tenant_id: cmd.tenant_id,
+            item_id: cmd.item_id,
+            reorder_point: cmd.reorder_point,
+            occurred_at: cmd.occurred_at,
+        })])
+    }
+
+    fn handle_transfer(&self, cmd: &TransferStock) -> Result<Vec<InventoryEvent>, DomainError> {
+        if !self.created {
+            return Err(DomainError::not_found());
+        }
+        self.ensure_tenant(cmd.tenant_id)?;
+        self.ensure_item_id(cmd.item_id)?;
+
+        if cmd.quantity <= 0 {
+            return Err(DomainError::validation("quantity must be positive"));
+        }
+        if cmd.from_location.trim().is_empty() || cmd.to_location.trim().is_empty() {
+            return Err(DomainError::validation("location_id cannot be empty"));
+        }
+        if cmd.from_location == cmd.to_location {
+            return Err(DomainError::validation("cannot transfer stock to the same location"));
+        }
+        let at_source = self.stock_at(&cmd.from_location);
+        if at_source < cmd.quantity {
+            return Err(DomainError::invariant(format!(
+                "stock cannot go negative at location {} (only {at_source} on hand)",
+                cmd.from_location
+            )));
+        }
+
+        // Both legs in one batch: they are committed (or rejected) together.
+        let leg = |location_id: &str, delta: i64| {
+            InventoryEvent::StockAdjusted(StockAdjusted {
+                tenant_id: cmd.tenant_id,
+                item_id: cmd.item_id,
+                location_id: location_id.to_string(),
+                delta,
+                occurred_at: cmd.occurred_at,
+            })
+        };
+        Ok(vec![leg(&cmd.from_location, -cmd.quantity), leg(&cmd.to_location, cmd.quantity)])
+    }
+
+    fn handle_reserve(&self, cmd: &ReserveStock) -> Result<Vec<InventoryEvent>, DomainError> {
+        if !self.created {
+            return Err(DomainError::not_found());
+        }
+        self.ensure_tenant(cmd.tenant_id)?;
+        self.ensure_item_id(cmd.item_id)?;
+
+        if cmd.quantity <= 0 {
+            return Err(DomainError::validation("quantity must be positive"));
+        }
+        if self.reservations.contains_key(&cmd.reservation_id) || self.released.contains(&cmd.reservation_id) {
+            return Ok(vec![]);
+        }
+        if cmd.quantity > self.available() {
+            return Err(DomainError::invariant("insufficient available stock").with_code(INSUFFICIENT_AVAILABLE_STOCK));
+        }
+
+        Ok(vec![InventoryEvent::StockReserved(StockReserved {
+            tenant_id: cmd.tenant_id,
+            item_id: cmd.item_id,
+            reservation_id: cmd.reservation_id,
+            quantity: cmd.quantity,
+            occurred_at: cmd.occurred_at,
+        })])
+    }
+
+    fn handle_release(&self, cmd: &ReleaseReservation) -> Result<Vec<InventoryEvent>, DomainError> {
+        if !self.created {
+            return Err(DomainError::not_found());
+        }
+        self.ensure_tenant(cmd.tenant_id)?;
+        self.ensure_item_id(cmd.item_id)?;
+
+        let Some(&held) = self.reservations.get(&cmd.reservation_id) else {
+            if self.released.contains(&cmd.reservation_id) {
+                return Ok(vec![]);
+            }
+            return Err(
+                DomainError::validation(format!("unknown reservation {}", cmd.reservation_id)).with_code(UNKNOWN_RESERVATION),
+            );
+        };
+        let quantity = match cmd.quantity {
+            None => held,
+            Some(q) if q <= 0 => return Err(DomainError::validation("quantity must be positive")),
+            Some(q) if q > held => {
+                return Err(DomainError::invariant(format!("cannot release {q}: only {held} reserved")));
+            }
+            Some(q) => q,
+        };
+        Ok(vec![InventoryEvent::ReservationReleased(ReservationReleased {
+            tenant_id: cmd.tenant_id,
+            item_id: cmd.item_id,
+            reservation_id: cmd.reservation_id,
+            quantity,
+            occurred_at: cmd.occurred_at,
         })])
     }
 }
@@ -309,6 +711,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn rename_item_updates_the_name_and_validates_like_create() {
+        let (mut item, tenant_id) = item_with_stock(1);
+        let rename = |tenant_id, name: &str| {
+            InventoryCommand::RenameItem(RenameItem {
+                tenant_id,
+                item_id: item.id_typed(),
+                name: name.to_string(),
+                occurred_at: test_time(),
+            })
+        };
+
+        let events = item.handle(&rename(tenant_id, "Widget")).unwrap();
+        assert!(matches!(&events[..], [InventoryEvent::ItemRenamed(e)] if e.name == "Widget"));
+        assert!(matches!(item.handle(&rename(tenant_id, "  ")), Err(DomainError::Validation(_))));
+        assert!(matches!(item.handle(&rename(test_tenant_id(), "Widget")), Err(DomainError::InvariantViolation(_))));
+        item.apply(&events[0]);
+        assert_eq!(item.name(), "Widget");
+
+        let missing = InventoryItem::empty(test_item_id());
+        let err = missing
+            .handle(&InventoryCommand::RenameItem(RenameItem {
+                tenant_id,
+                item_id: missing.id_typed(),
+                name: "Widget".to_string(),
+                occurred_at: test_time(),
+            }))
+            .unwrap_err();
+        assert_eq!(err, DomainError::not_found());
+    }
+
     #[test]
     fn adjust_stock_emits_stock_adjusted_event() {
         let mut item = InventoryItem::empty(test_item_id());
@@ -329,6 +762,7 @@ mod tests {
         let adjust_cmd = AdjustStock {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 10,
             occurred_at: test_time(),
         };
@@ -366,6 +800,7 @@ mod tests {
         let adjust_cmd = AdjustStock {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: -1,
             occurred_at: test_time(),
         };
@@ -397,6 +832,7 @@ mod tests {
         let add_cmd = AdjustStock {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 10,
             occurred_at: test_time(),
         };
@@ -408,6 +844,7 @@ mod tests {
         let remove_cmd = AdjustStock {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: -10,
             occurred_at: test_time(),
         };
@@ -436,6 +873,7 @@ mod tests {
         let adjust_cmd = AdjustStock {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 0,
             occurred_at: test_time(),
         };
@@ -468,6 +906,7 @@ mod tests {
         let adjust_cmd = AdjustStock {
             tenant_id: wrong_tenant,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 10,
             occurred_at: test_time(),
         };
@@ -485,6 +924,7 @@ mod tests {
         let adjust_cmd = AdjustStock {
             tenant_id: test_tenant_id(),
             item_id: test_item_id(),
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 10,
             occurred_at: test_time(),
         };
@@ -496,6 +936,261 @@ mod tests {
         }
     }
 
+    fn item_with_stock(stock: i64) -> (InventoryItem, TenantId) {
+        let item_id = test_item_id();
+        let mut item = InventoryItem::empty(item_id);
+        let tenant_id = test_tenant_id();
+        let commands = [
+            InventoryCommand::CreateItem(CreateItem {
+                tenant_id,
+                item_id,
+                name: "Test Item".to_string(),
+                occurred_at: test_time(),
+            }),
+            InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id,
+                item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta: stock,
+                occurred_at: test_time(),
+            }),
+        ];
+        for cmd in commands {
+            for e in item.handle(&cmd).unwrap() {
+                item.apply(&e);
+            }
+        }
+        (item, tenant_id)
+    }
+
+    fn reserve(item: &InventoryItem, tenant_id: TenantId, reservation_id: AggregateId, quantity: i64) -> InventoryCommand {
+        InventoryCommand::ReserveStock(ReserveStock {
+            tenant_id,
+            item_id: item.id_typed(),
+            reservation_id,
+            quantity,
+            occurred_at: test_time(),
+        })
+    }
+
+    #[test]
+    fn reservations_reduce_available_stock_and_cannot_oversell() {
+        let (mut item, tenant_id) = item_with_stock(10);
+        let first = AggregateId::new();
+
+        for e in item.handle(&reserve(&item, tenant_id, first, 7)).unwrap() {
+            item.apply(&e);
+        }
+        assert_eq!(item.stock(), 10);
+        assert_eq!(item.available(), 3);
+
+        let err = item.handle(&reserve(&item, tenant_id, AggregateId::new(), 4)).unwrap_err();
+        assert_eq!(err.code(), Some(INSUFFICIENT_AVAILABLE_STOCK));
+        assert!(matches!(err, DomainError::Coded { error, .. } if matches!(*error, DomainError::InvariantViolation(_))));
+        // Redelivery of the same reservation is a no-op.
+        assert!(item.handle(&reserve(&item, tenant_id, first, 7)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn released_reservation_frees_stock_and_is_not_reserved_again() {
+        let (mut item, tenant_id) = item_with_stock(5);
+        let reservation_id = AggregateId::new();
+        for e in item.handle(&reserve(&item, tenant_id, reservation_id, 5)).unwrap() {
+            item.apply(&e);
+        }
+
+        let release = InventoryCommand::ReleaseReservation(ReleaseReservation {
+            tenant_id,
+            item_id: item.id_typed(),
+            reservation_id,
+            quantity: None,
+            occurred_at: test_time(),
+        });
+        let events = item.handle(&release).unwrap();
+        assert!(matches!(&events[..], [InventoryEvent::ReservationReleased(e)] if e.quantity == 5));
+        item.apply(&events[0]);
+
+        assert_eq!(item.available(), 5);
+        assert!(item.handle(&release).unwrap().is_empty());
+        assert!(item.handle(&reserve(&item, tenant_id, reservation_id, 5)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn releasing_an_unknown_reservation_is_rejected() {
+        let (item, tenant_id) = item_with_stock(5);
+        let release = InventoryCommand::ReleaseReservation(ReleaseReservation {
+            tenant_id,
+            item_id: item.id_typed(),
+            reservation_id: AggregateId::new(),
+            quantity: None,
+            occurred_at: test_time(),
+        });
+        assert_eq!(item.handle(&release).unwrap_err().code(), Some(UNKNOWN_RESERVATION));
+    }
+
+    #[test]
+    fn negative_adjustments_stop_at_the_reserved_floor_and_releases_can_be_partial() {
+        let (mut item, tenant_id) = item_with_stock(10);
+        let reservation_id = AggregateId::new();
+        for e in item.handle(&reserve(&item, tenant_id, reservation_id, 6)).unwrap() {
+            item.apply(&e);
+        }
+        assert_eq!((item.reserved(), item.available()), (6, 4));
+
+        let item_id = item.id_typed();
+        let adjust = |delta| {
+            InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id,
+                item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta,
+                occurred_at: test_time(),
+            })
+        };
+        assert!(item.handle(&adjust(-4)).is_ok());
+        let err = item.handle(&adjust(-5)).unwrap_err();
+        assert!(matches!(err, DomainError::InvariantViolation(msg) if msg.contains("reserved quantity")));
+
+        let release = |quantity| {
+            InventoryCommand::ReleaseReservation(ReleaseReservation {
+                tenant_id,
+                item_id,
+                reservation_id,
+                quantity: Some(quantity),
+                occurred_at: test_time(),
+            })
+        };
+        let err = item.handle(&release(7)).unwrap_err();
+        assert!(matches!(err, DomainError::InvariantViolation(msg) if msg.contains("only 6 reserved")));
+
+        let events = item.handle(&release(2)).unwrap();
+        item.apply(&events[0]);
+        assert_eq!((item.reserved(), item.reservation(reservation_id)), (4, Some(4)));
+        let events = item.handle(&release(4)).unwrap();
+        item.apply(&events[0]);
+        assert_eq!((item.reserved(), item.reservation(reservation_id)), (0, None));
+        // Fully released: the id is spent.
+        assert!(item.handle(&reserve(&item, tenant_id, reservation_id, 1)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn stock_is_tracked_per_location_and_transfers_move_it_in_one_batch() {
+        let (mut item, tenant_id) = item_with_stock(10);
+        let item_id = item.id_typed();
+        let adjust = |location_id: &str, delta| {
+            InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id,
+                item_id,
+                location_id: location_id.to_string(),
+                delta,
+                occurred_at: test_time(),
+            })
+        };
+        for e in item.handle(&adjust("warehouse-b", 3)).unwrap() {
+            item.apply(&e);
+        }
+        assert_eq!((item.stock(), item.stock_at(DEFAULT_LOCATION), item.stock_at("warehouse-b")), (13, 10, 3));
+
+        // The negative-stock check is per location, even with stock elsewhere.
+        let err = item.handle(&adjust("warehouse-b", -4)).unwrap_err();
+        assert!(matches!(err, DomainError::InvariantViolation(msg) if msg.contains("cannot go negative at location warehouse-b")));
+
+        let transfer = |from: &str, to: &str, quantity| {
+            InventoryCommand::TransferStock(TransferStock {
+                tenant_id,
+                item_id,
+                from_location: from.to_string(),
+                to_location: to.to_string(),
+                quantity,
+                occurred_at: test_time(),
+            })
+        };
+        let events = item.handle(&transfer(DEFAULT_LOCATION, "warehouse-b", 6)).unwrap();
+        assert!(matches!(
+            &events[..],
+            [InventoryEvent::StockAdjusted(out), InventoryEvent::StockAdjusted(into)]
+                if out.location_id == DEFAULT_LOCATION && out.delta == -6 && into.location_id == "warehouse-b" && into.delta == 6
+        ));
+        for e in &events {
+            item.apply(e);
+        }
+        assert_eq!((item.stock(), item.stock_at(DEFAULT_LOCATION), item.stock_at("warehouse-b")), (13, 4, 9));
+
+        assert!(matches!(item.handle(&transfer(DEFAULT_LOCATION, "warehouse-b", 5)), Err(DomainError::InvariantViolation(_))));
+        assert!(matches!(item.handle(&transfer("warehouse-b", "warehouse-b", 1)), Err(DomainError::Validation(_))));
+        assert!(matches!(item.handle(&transfer("warehouse-b", "warehouse-c", 0)), Err(DomainError::Validation(_))));
+    }
+
+    #[test]
+    fn stock_adjusted_events_without_a_location_replay_into_the_default_location() {
+        let (mut item, tenant_id) = item_with_stock(1);
+        let legacy = serde_json::json!({
+            "StockAdjusted": {
+                "tenant_id": tenant_id,
+                "item_id": item.id_typed(),
+                "delta": 7,
+                "occurred_at": test_time(),
+            }
+        });
+        let event: InventoryEvent = serde_json::from_value(legacy).unwrap();
+        item.apply(&event);
+        assert_eq!((item.stock(), item.stock_at(DEFAULT_LOCATION)), (8, 8));
+    }
+
+    #[test]
+    fn stock_fell_below_reorder_point_is_emitted_only_on_the_downward_crossing() {
+        let (mut item, tenant_id) = item_with_stock(10);
+        let item_id = item.id_typed();
+        let run = |item: &mut InventoryItem, cmd: InventoryCommand| {
+            let events = item.handle(&cmd).unwrap();
+            for e in &events {
+                item.apply(e);
+            }
+            events
+        };
+        let adjust = |delta| {
+            InventoryCommand::AdjustStock(AdjustStock {
+                tenant_id,
+                item_id,
+                location_id: DEFAULT_LOCATION.to_string(),
+                delta,
+                occurred_at: test_time(),
+            })
+        };
+        let set = |reorder_point| {
+            InventoryCommand::SetReorderPoint(SetReorderPoint {
+                tenant_id,
+                item_id,
+                reorder_point,
+                occurred_at: test_time(),
+            })
+        };
+        let alerts = |events: &[InventoryEvent]| {
+            events
+                .iter()
+                .filter_map(|e| match e {
+                    InventoryEvent::StockFellBelowReorderPoint(e) => Some((e.current_stock, e.reorder_point)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert!(matches!(item.handle(&set(Some(-1))), Err(DomainError::Validation(_))));
+        assert_eq!(run(&mut item, set(Some(5))).len(), 1);
+        assert_eq!(item.reorder_point(), Some(5));
+        assert!(run(&mut item, set(Some(5))).is_empty());
+
+        assert!(alerts(&run(&mut item, adjust(-5))).is_empty()); // 10 -> 5: still at the point
+        assert_eq!(alerts(&run(&mut item, adjust(-1))), [(4, 5)]); // 5 -> 4: crossing
+        assert!(alerts(&run(&mut item, adjust(-2))).is_empty()); // 4 -> 2: already below
+        assert!(alerts(&run(&mut item, adjust(4))).is_empty()); // back to 6
+        assert_eq!(alerts(&run(&mut item, adjust(-6))), [(0, 5)]); // crosses again
+
+        run(&mut item, set(None));
+        run(&mut item, adjust(10));
+        assert!(alerts(&run(&mut item, adjust(-10))).is_empty());
+    }
+
     #[test]
     fn version_increments_on_apply() {
         let mut item = InventoryItem::empty(test_item_id());
@@ -517,6 +1212,7 @@ mod tests {
         let adjust_cmd = AdjustStock {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 5,
             occurred_at: test_time(),
         };
@@ -547,6 +1243,7 @@ mod tests {
         let adjust_cmd = AdjustStock {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 10,
             occurred_at: test_time(),
         };
@@ -582,12 +1279,14 @@ mod tests {
         let event2 = InventoryEvent::StockAdjusted(StockAdjusted {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: 10,
             occurred_at: test_time(),
         });
         let event3 = InventoryEvent::StockAdjusted(StockAdjusted {
             tenant_id,
             item_id,
+            location_id: DEFAULT_LOCATION.to_string(),
             delta: -5,
             occurred_at: test_time(),
         });
@@ -628,6 +1327,7 @@ mod tests {
                 .prop_map(move |delta| AdjustStock {
                     tenant_id,
                     item_id,
+                    location_id: DEFAULT_LOCATION.to_string(),
                     delta,
                     occurred_at: Utc::now(),
                 })
@@ -679,6 +1379,7 @@ mod tests {
                     let cmd_with_correct_ids = AdjustStock {
                         tenant_id,
                         item_id,
+                        location_id: DEFAULT_LOCATION.to_string(),
                         delta: cmd.delta,
                         occurred_at: Utc::now(),
                     };
@@ -714,6 +1415,67 @@ mod tests {
                 prop_assert!(item.stock() >= 0, "Final stock is negative: {}", item.stock());
             }
 
+            /// Property: reservations never exceed stock, whatever mix of adjustments,
+            /// reservations and (partial) releases is attempted.
+            #[test]
+            fn reserved_never_exceeds_stock(
+                ops in prop::collection::vec((0u8..3, 1i64..=40, 0usize..4), 0..100)
+            ) {
+                let mut item = InventoryItem::empty(test_item_id());
+                let tenant_id = test_tenant_id();
+                let item_id = test_item_id();
+                let events = item
+                    .handle(&InventoryCommand::CreateItem(CreateItem {
+                        tenant_id,
+                        item_id,
+                        name: "Test Item".to_string(),
+                        occurred_at: Utc::now(),
+                    }))
+                    .unwrap();
+                item.apply(&events[0]);
+                let reservation_ids: Vec<AggregateId> = (0..4).map(|_| AggregateId::new()).collect();
+
+                for (kind, quantity, slot) in ops {
+                    let reservation_id = reservation_ids[slot];
+                    let command = match kind {
+                        0 => InventoryCommand::AdjustStock(AdjustStock {
+                            tenant_id,
+                            item_id,
+                            location_id: DEFAULT_LOCATION.to_string(),
+                            delta: if slot % 2 == 0 { quantity } else { -quantity },
+                            occurred_at: Utc::now(),
+                        }),
+                        1 => InventoryCommand::ReserveStock(ReserveStock {
+                            tenant_id,
+                            item_id,
+                            reservation_id,
+                            quantity,
+                            occurred_at: Utc::now(),
+                        }),
+                        _ => InventoryCommand::ReleaseReservation(ReleaseReservation {
+                            tenant_id,
+                            item_id,
+                            reservation_id,
+                            quantity: (slot % 2 == 0).then_some(quantity),
+                            occurred_at: Utc::now(),
+                        }),
+                    };
+                    if let Ok(events) = item.handle(&command) {
+                        for event in events {
+                            item.apply(&event);
+                        }
+                    }
+                    prop_assert!(item.reserved() >= 0, "negative reserved: {}", item.reserved());
+                    prop_assert!(
+                        item.reserved() <= item.stock(),
+                        "reserved {} exceeds stock {}",
+                        item.reserved(),
+                        item.stock()
+                    );
+                    prop_assert_eq!(item.available(), item.stock() - item.reserved());
+                }
+            }
+
             /// Property: Version increments monotonically with each applied event.
             #[test]
             fn version_increments_monotonically(
@@ -740,6 +1502,7 @@ mod tests {
                     let cmd_with_correct_ids = AdjustStock {
                         tenant_id,
                         item_id,
+                        location_id: DEFAULT_LOCATION.to_string(),
                         delta: cmd.delta,
                         occurred_at: Utc::now(),
                     };
@@ -784,6 +1547,7 @@ mod tests {
                     let add_cmd = AdjustStock {
                         tenant_id,
                         item_id,
+                        location_id: DEFAULT_LOCATION.to_string(),
                         delta: delta.abs(),
                         occurred_at: Utc::now(),
                     };
@@ -798,6 +1562,7 @@ mod tests {
                 let adjust_cmd = AdjustStock {
                     tenant_id,
                     item_id,
+                    location_id: DEFAULT_LOCATION.to_string(),
                     delta,
                     occurred_at: Utc::now(),
                 };
@@ -842,6 +1607,7 @@ mod tests {
                             InventoryEvent::StockAdjusted(StockAdjusted {
                                 tenant_id,
                                 item_id,
+                                location_id: DEFAULT_LOCATION.to_string(),
                                 delta,
                                 occurred_at: Utc::now(),
                             })
@@ -6,8 +6,9 @@
 pub mod item;
 
 pub use item::{
-    AdjustStock, CreateItem, InventoryCommand, InventoryEvent, InventoryItem, InventoryItemId,
-    ItemCreated, StockAdjusted,
+    AdjustStock, CreateItem, DEFAULT_LOCATION, INSUFFICIENT_AVAILABLE_STOCK, InventoryCommand, InventoryEvent, InventoryItem,
+    InventoryItemId, ItemCreated, ItemRenamed, ReleaseReservation, RenameItem, ReorderPointSet, ReservationReleased,
+    ReserveStock, SetReorderPoint, StockAdjusted, StockFellBelowReorderPoint, StockReserved, TransferStock, UNKNOWN_RESERVATION,
 };
 
 
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -6,6 +8,13 @@ use forgeerp_events::Event;
 use forgeerp_sales::SalesOrderId;
 use forgeerp_products::ProductId;
 
+/// Payment terms applied when a command or legacy event does not specify any.
+pub const DEFAULT_PAYMENT_TERMS_DAYS: u32 = 30;
+
+fn default_payment_terms_days() -> u32 {
+    DEFAULT_PAYMENT_TERMS_DAYS
+}
+
 /// Invoice identifier (tenant-scoped via `tenant_id` fields in events/commands).
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -23,6 +32,30 @@ impl core::fmt::Display for InvoiceId {
     }
 }
 
+/// Identifier of a payment registered against an invoice.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PaymentId(pub AggregateId);
+
+impl PaymentId {
+    pub fn new(id: AggregateId) -> Self {
+        Self(id)
+    }
+}
+
+impl core::fmt::Display for PaymentId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// A payment recorded on the invoice, kept so it can be reversed later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedPayment {
+    pub amount: u64,
+    pub reversed: bool,
+}
+
 /// Invoice status lifecycle.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -44,15 +77,19 @@ pub struct InvoiceLine {
 }
 
 /// Aggregate root: Invoice.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Invoice {
     id: InvoiceId,
     tenant_id: Option<TenantId>,
     status: InvoiceStatus,
     lines: Vec<InvoiceLine>,
-    due_date: Option<DateTime<Utc>>,
+    payment_terms_days: u32,
+    due_at: Option<DateTime<Utc>>,
+    currency: Option<String>,
     total_amount: u64,
     total_paid: u64,
+    credited_total: u64,
+    payments: HashMap<PaymentId, RecordedPayment>,
     version: u64,
     created: bool,
 }
@@ -65,9 +102,13 @@ impl Invoice {
             tenant_id: None,
             status: InvoiceStatus::Open,
             lines: Vec::new(),
-            due_date: None,
+            payment_terms_days: DEFAULT_PAYMENT_TERMS_DAYS,
+            due_at: None,
+            currency: None,
             total_amount: 0,
             total_paid: 0,
+            credited_total: 0,
+            payments: HashMap::new(),
             version: 0,
             created: false,
         }
@@ -85,8 +126,23 @@ impl Invoice {
         self.status
     }
 
-    pub fn due_date(&self) -> Option<DateTime<Utc>> {
-        self.due_date
+    pub fn payment_terms_days(&self) -> u32 {
+        self.payment_terms_days
+    }
+
+    /// Issue time plus payment terms.
+    pub fn due_at(&self) -> Option<DateTime<Utc>> {
+        self.due_at
+    }
+
+    /// Whether payment is due at `now` (an invoice with 0-day terms is due on issue).
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.due_at.is_some_and(|due| now >= due)
+    }
+
+    /// ISO code the invoice is billed in (`None` on invoices issued before it was recorded).
+    pub fn currency(&self) -> Option<&str> {
+        self.currency.as_deref()
     }
 
     pub fn total_amount(&self) -> u64 {
@@ -97,14 +153,28 @@ impl Invoice {
         self.total_paid
     }
 
+    /// Sum of all credit notes issued against this invoice.
+    pub fn credited_total(&self) -> u64 {
+        self.credited_total
+    }
+
+    /// Invoiced amount after credit notes.
+    pub fn net_total(&self) -> u64 {
+        self.total_amount.saturating_sub(self.credited_total)
+    }
+
     pub fn outstanding_amount(&self) -> u64 {
-        self.total_amount.saturating_sub(self.total_paid)
+        self.net_total().saturating_sub(self.total_paid)
     }
 
     pub fn lines(&self) -> &[InvoiceLine] {
         &self.lines
     }
 
+    pub fn payment(&self, payment_id: &PaymentId) -> Option<RecordedPayment> {
+        self.payments.get(payment_id).copied()
+    }
+
     /// Invariant: cannot pay void invoice.
     pub fn can_accept_payment(&self) -> bool {
         self.status != InvoiceStatus::Void && self.outstanding_amount() > 0
@@ -130,7 +200,12 @@ pub struct IssueInvoice {
     pub invoice_id: InvoiceId,
     pub sales_order_id: SalesOrderId,
     pub lines: Vec<InvoiceLine>,
-    pub due_date: DateTime<Utc>,
+    /// Days after issue until payment is due.
+    #[serde(default = "default_payment_terms_days")]
+    pub payment_terms_days: u32,
+    /// ISO code of the line prices; payments and credit notes must use the same one.
+    #[serde(default)]
+    pub currency: Option<String>,
     pub occurred_at: DateTime<Utc>,
 }
 
@@ -139,8 +214,13 @@ pub struct IssueInvoice {
 pub struct RegisterPayment {
     pub tenant_id: TenantId,
     pub invoice_id: InvoiceId,
+    /// Caller-assigned id, used to reverse the payment later.
+    pub payment_id: PaymentId,
     /// Payment amount in smallest currency unit.
     pub amount: u64,
+    /// Must match the invoice's currency; `None` pays in it.
+    #[serde(default)]
+    pub currency: Option<String>,
     pub occurred_at: DateTime<Utc>,
 }
 
@@ -153,11 +233,39 @@ pub struct VoidInvoice {
     pub occurred_at: DateTime<Utc>,
 }
 
+/// Command: ReversePayment (bounced check, refund).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReversePayment {
+    pub tenant_id: TenantId,
+    pub invoice_id: InvoiceId,
+    pub payment_id: PaymentId,
+    pub reason: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Command: IssueCreditNote.
+///
+/// Credits (part of) an issued invoice. The credited lines are priced the same
+/// way as invoice lines; their total may not exceed the invoice's uncredited amount.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssueCreditNote {
+    pub tenant_id: TenantId,
+    pub invoice_id: InvoiceId,
+    pub lines: Vec<InvoiceLine>,
+    /// Must match the invoice's currency; `None` credits in it.
+    #[serde(default)]
+    pub currency: Option<String>,
+    pub reason: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InvoiceCommand {
     IssueInvoice(IssueInvoice),
     RegisterPayment(RegisterPayment),
     VoidInvoice(VoidInvoice),
+    IssueCreditNote(IssueCreditNote),
+    ReversePayment(ReversePayment),
 }
 
 /// Event: InvoiceIssued.
@@ -167,7 +275,14 @@ pub struct InvoiceIssued {
     pub invoice_id: InvoiceId,
     pub sales_order_id: SalesOrderId,
     pub lines: Vec<InvoiceLine>,
+    /// Absent on invoices issued before terms were modeled; those carry only `due_date`.
+    #[serde(default = "default_payment_terms_days")]
+    pub payment_terms_days: u32,
+    /// `occurred_at + payment_terms_days`, computed when the invoice is issued.
     pub due_date: DateTime<Utc>,
+    /// Absent on invoices issued before the currency was recorded.
+    #[serde(default)]
+    pub currency: Option<String>,
     pub total_amount: u64,
     pub occurred_at: DateTime<Utc>,
 }
@@ -177,7 +292,13 @@ pub struct InvoiceIssued {
 pub struct PaymentRegistered {
     pub tenant_id: TenantId,
     pub invoice_id: InvoiceId,
+    /// Absent on payments recorded before payment ids existed; those cannot be reversed.
+    #[serde(default)]
+    pub payment_id: Option<PaymentId>,
     pub amount: u64,
+    /// The invoice's currency (absent when the invoice has none recorded).
+    #[serde(default)]
+    pub currency: Option<String>,
     pub new_total_paid: u64,
     pub occurred_at: DateTime<Utc>,
 }
@@ -191,11 +312,40 @@ pub struct InvoiceVoided {
     pub occurred_at: DateTime<Utc>,
 }
 
+/// Event: PaymentReversed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentReversed {
+    pub tenant_id: TenantId,
+    pub invoice_id: InvoiceId,
+    pub payment_id: PaymentId,
+    pub amount: u64,
+    pub new_total_paid: u64,
+    pub reason: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Event: CreditNoteIssued.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreditNoteIssued {
+    pub tenant_id: TenantId,
+    pub invoice_id: InvoiceId,
+    pub lines: Vec<InvoiceLine>,
+    pub amount: u64,
+    /// The invoice's currency (absent when the invoice has none recorded).
+    #[serde(default)]
+    pub currency: Option<String>,
+    pub new_credited_total: u64,
+    pub reason: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InvoiceEvent {
     InvoiceIssued(InvoiceIssued),
     PaymentRegistered(PaymentRegistered),
     InvoiceVoided(InvoiceVoided),
+    CreditNoteIssued(CreditNoteIssued),
+    PaymentReversed(PaymentReversed),
 }
 
 impl Event for InvoiceEvent {
@@ -204,6 +354,8 @@ impl Event for InvoiceEvent {
             InvoiceEvent::InvoiceIssued(_) => "invoicing.invoice.issued",
             InvoiceEvent::PaymentRegistered(_) => "invoicing.invoice.payment_registered",
             InvoiceEvent::InvoiceVoided(_) => "invoicing.invoice.voided",
+            InvoiceEvent::CreditNoteIssued(_) => "invoicing.invoice.credit_note_issued",
+            InvoiceEvent::PaymentReversed(_) => "invoicing.invoice.payment_reversed",
         }
     }
 
@@ -216,6 +368,8 @@ impl Event for InvoiceEvent {
             InvoiceEvent::InvoiceIssued(e) => e.occurred_at,
             InvoiceEvent::PaymentRegistered(e) => e.occurred_at,
             InvoiceEvent::InvoiceVoided(e) => e.occurred_at,
+            InvoiceEvent::CreditNoteIssued(e) => e.occurred_at,
+            InvoiceEvent::PaymentReversed(e) => e.occurred_at,
         }
     }
 }
@@ -231,21 +385,49 @@ impl Aggregate for Invoice {
                 self.id = e.invoice_id;
                 self.tenant_id = Some(e.tenant_id);
                 self.lines = e.lines.clone();
-                self.due_date = Some(e.due_date);
+                self.payment_terms_days = e.payment_terms_days;
+                self.due_at = Some(e.due_date);
+                self.currency = e.currency.clone();
                 self.total_amount = e.total_amount;
                 self.total_paid = 0;
+                self.credited_total = 0;
+                self.payments.clear();
                 self.status = InvoiceStatus::Open;
                 self.created = true;
             }
             InvoiceEvent::PaymentRegistered(e) => {
                 self.total_paid = e.new_total_paid;
-                if self.total_paid >= self.total_amount {
+                if let Some(payment_id) = e.payment_id {
+                    self.payments.insert(
+                        payment_id,
+                        RecordedPayment {
+                            amount: e.amount,
+                            reversed: false,
+                        },
+                    );
+                }
+                if self.total_paid >= self.net_total() {
                     self.status = InvoiceStatus::Paid;
                 }
             }
+            InvoiceEvent::PaymentReversed(e) => {
+                self.total_paid = e.new_total_paid;
+                if let Some(payment) = self.payments.get_mut(&e.payment_id) {
+                    payment.reversed = true;
+                }
+                if self.status == InvoiceStatus::Paid && self.total_paid < self.net_total() {
+                    self.status = InvoiceStatus::Open;
+                }
+            }
             InvoiceEvent::InvoiceVoided(_) => {
                 self.status = InvoiceStatus::Void;
             }
+            InvoiceEvent::CreditNoteIssued(e) => {
+                self.credited_total = e.new_credited_total;
+                if self.status == InvoiceStatus::Open && self.total_paid >= self.net_total() {
+                    self.status = InvoiceStatus::Paid;
+                }
+            }
         }
 
         // Deterministic version tracking: +1 per applied event.
@@ -257,6 +439,8 @@ impl Aggregate for Invoice {
             InvoiceCommand::IssueInvoice(cmd) => self.handle_issue(cmd),
             InvoiceCommand::RegisterPayment(cmd) => self.handle_register_payment(cmd),
             InvoiceCommand::VoidInvoice(cmd) => self.handle_void(cmd),
+            InvoiceCommand::IssueCreditNote(cmd) => self.handle_credit_note(cmd),
+            InvoiceCommand::ReversePayment(cmd) => self.handle_reverse_payment(cmd),
         }
     }
 }
@@ -279,6 +463,17 @@ impl Invoice {
         Ok(())
     }
 
+    /// Reject an amount in another currency than the invoice's (mixing them would corrupt
+    /// the totals). Invoices without a recorded currency accept any.
+    fn ensure_currency(&self, currency: Option<&str>) -> Result<(), DomainError> {
+        match (self.currency.as_deref(), currency) {
+            (Some(invoiced), Some(given)) if invoiced != given => Err(DomainError::validation(format!(
+                "currency {given} does not match the invoice currency {invoiced}"
+            ))),
+            _ => Ok(()),
+        }
+    }
+
     fn handle_issue(&self, cmd: &IssueInvoice) -> Result<Vec<InvoiceEvent>, DomainError> {
         if self.created {
             return Err(DomainError::conflict("invoice already exists"));
@@ -290,37 +485,17 @@ impl Invoice {
             ));
         }
 
-        let mut total: u64 = 0;
-        for line in &cmd.lines {
-            if line.quantity <= 0 {
-                return Err(DomainError::validation(
-                    "invoice line quantity must be positive",
-                ));
-            }
-            if line.unit_price == 0 {
-                return Err(DomainError::validation(
-                    "invoice line unit_price must be positive",
-                ));
-            }
-            let line_total = (line.quantity as i128)
-                .checked_mul(line.unit_price as i128)
-                .ok_or_else(|| DomainError::invariant("invoice line amount overflow"))?;
-            if line_total <= 0 {
-                return Err(DomainError::invariant(
-                    "invoice line total must be positive",
-                ));
-            }
-            total = total
-                .checked_add(line_total as u64)
-                .ok_or_else(|| DomainError::invariant("invoice total overflow"))?;
-        }
+        let total = lines_total(&cmd.lines)?;
+        let due_at = cmd.occurred_at + chrono::Duration::days(i64::from(cmd.payment_terms_days));
 
         Ok(vec![InvoiceEvent::InvoiceIssued(InvoiceIssued {
             tenant_id: cmd.tenant_id,
             invoice_id: cmd.invoice_id,
             sales_order_id: cmd.sales_order_id,
             lines: cmd.lines.clone(),
-            due_date: cmd.due_date,
+            payment_terms_days: cmd.payment_terms_days,
+            due_date: due_at,
+            currency: cmd.currency.clone(),
             total_amount: total,
             occurred_at: cmd.occurred_at,
         })])
@@ -336,6 +511,8 @@ impl Invoice {
         self.ensure_tenant(cmd.tenant_id)?;
         self.ensure_invoice_id(cmd.invoice_id)?;
 
+        self.ensure_currency(cmd.currency.as_deref())?;
+
         if !self.can_accept_payment() {
             return Err(DomainError::invariant(
                 "cannot register payment on void or fully paid invoice",
@@ -348,19 +525,25 @@ impl Invoice {
             ));
         }
 
+        if self.payments.contains_key(&cmd.payment_id) {
+            return Err(DomainError::conflict("payment already registered"));
+        }
+
         let new_total_paid = self
             .total_paid
             .checked_add(cmd.amount)
             .ok_or_else(|| DomainError::invariant("payment total overflow"))?;
 
-        if new_total_paid > self.total_amount {
+        if new_total_paid > self.net_total() {
             return Err(DomainError::invariant("cannot overpay invoice"));
         }
 
         Ok(vec![InvoiceEvent::PaymentRegistered(PaymentRegistered {
             tenant_id: cmd.tenant_id,
             invoice_id: cmd.invoice_id,
+            payment_id: Some(cmd.payment_id),
             amount: cmd.amount,
+            currency: self.currency.clone(),
             new_total_paid,
             occurred_at: cmd.occurred_at,
         })])
@@ -384,6 +567,121 @@ impl Invoice {
             occurred_at: cmd.occurred_at,
         })])
     }
+
+    fn handle_reverse_payment(
+        &self,
+        cmd: &ReversePayment,
+    ) -> Result<Vec<InvoiceEvent>, DomainError> {
+        if !self.created {
+            return Err(DomainError::not_found());
+        }
+        self.ensure_tenant(cmd.tenant_id)?;
+        self.ensure_invoice_id(cmd.invoice_id)?;
+
+        if self.status == InvoiceStatus::Void {
+            return Err(DomainError::invariant(
+                "cannot reverse payment on void invoice",
+            ));
+        }
+
+        let payment = self
+            .payments
+            .get(&cmd.payment_id)
+            .ok_or_else(DomainError::not_found)?;
+        if payment.reversed {
+            return Err(DomainError::conflict("payment is already reversed"));
+        }
+
+        Ok(vec![InvoiceEvent::PaymentReversed(PaymentReversed {
+            tenant_id: cmd.tenant_id,
+            invoice_id: cmd.invoice_id,
+            payment_id: cmd.payment_id,
+            amount: payment.amount,
+            new_total_paid: self.total_paid.saturating_sub(payment.amount),
+            reason: cmd.reason.clone(),
+            occurred_at: cmd.occurred_at,
+        })])
+    }
+
+    fn handle_credit_note(
+        &self,
+        cmd: &IssueCreditNote,
+    ) -> Result<Vec<InvoiceEvent>, DomainError> {
+        if !self.created {
+            return Err(DomainError::not_found());
+        }
+        self.ensure_tenant(cmd.tenant_id)?;
+        self.ensure_invoice_id(cmd.invoice_id)?;
+
+        if self.status == InvoiceStatus::Void {
+            return Err(DomainError::invariant(
+                "cannot issue credit note on void invoice",
+            ));
+        }
+        self.ensure_currency(cmd.currency.as_deref())?;
+
+        if cmd.lines.is_empty() {
+            return Err(DomainError::validation(
+                "cannot issue credit note without lines",
+            ));
+        }
+
+        for line in &cmd.lines {
+            if !self.lines.iter().any(|l| l.line_no == line.line_no) {
+                return Err(DomainError::validation(format!(
+                    "credit note references unknown invoice line {}",
+                    line.line_no
+                )));
+            }
+        }
+
+        let amount = lines_total(&cmd.lines)?;
+        if amount > self.net_total() {
+            return Err(DomainError::invariant(
+                "credited amount exceeds invoice total minus already-credited amounts",
+            ));
+        }
+
+        Ok(vec![InvoiceEvent::CreditNoteIssued(CreditNoteIssued {
+            tenant_id: cmd.tenant_id,
+            invoice_id: cmd.invoice_id,
+            lines: cmd.lines.clone(),
+            amount,
+            currency: self.currency.clone(),
+            new_credited_total: self.credited_total + amount,
+            reason: cmd.reason.clone(),
+            occurred_at: cmd.occurred_at,
+        })])
+    }
+}
+
+/// Sum of `quantity * unit_price` over the given lines, rejecting non-positive lines.
+fn lines_total(lines: &[InvoiceLine]) -> Result<u64, DomainError> {
+    let mut total: u64 = 0;
+    for line in lines {
+        if line.quantity <= 0 {
+            return Err(DomainError::validation(
+                "invoice line quantity must be positive",
+            ));
+        }
+        if line.unit_price == 0 {
+            return Err(DomainError::validation(
+                "invoice line unit_price must be positive",
+            ));
+        }
+        let line_total = (line.quantity as i128)
+            .checked_mul(line.unit_price as i128)
+            .ok_or_else(|| DomainError::invariant("invoice line amount overflow"))?;
+        if line_total <= 0 {
+            return Err(DomainError::invariant(
+                "invoice line total must be positive",
+            ));
+        }
+        total = total
+            .checked_add(line_total as u64)
+            .ok_or_else(|| DomainError::invariant("invoice total overflow"))?;
+    }
+    Ok(total)
 }
 
 #[cfg(test)]
@@ -429,14 +727,15 @@ mod tests {
         let order_id = test_sales_order_id();
 
         let line = single_line(order_id);
-        let due = test_time();
+        let issued_at = test_time();
         let cmd = IssueInvoice {
             tenant_id,
             invoice_id,
             sales_order_id: order_id,
             lines: vec![line.clone()],
-            due_date: due,
-            occurred_at: test_time(),
+            payment_terms_days: 14,
+            currency: None,
+            occurred_at: issued_at,
         };
 
         let events = invoice
@@ -450,7 +749,8 @@ mod tests {
                 assert_eq!(e.invoice_id, invoice_id);
                 assert_eq!(e.sales_order_id, order_id);
                 assert_eq!(e.lines.len(), 1);
-                assert_eq!(e.due_date, due);
+                assert_eq!(e.payment_terms_days, 14);
+                assert_eq!(e.due_date, issued_at + chrono::Duration::days(14));
                 assert_eq!(e.total_amount, 2 * 100);
             }
             _ => panic!("Expected InvoiceIssued event"),
@@ -470,7 +770,8 @@ mod tests {
             invoice_id,
             sales_order_id: order_id,
             lines: vec![line],
-            due_date: test_time(),
+            payment_terms_days: DEFAULT_PAYMENT_TERMS_DAYS,
+            currency: None,
             occurred_at: test_time(),
         };
         let events = invoice
@@ -496,7 +797,9 @@ mod tests {
         let cmd_pay = RegisterPayment {
             tenant_id,
             invoice_id,
+            payment_id: PaymentId::new(AggregateId::new()),
             amount: 50,
+            currency: None,
             occurred_at: test_time(),
         };
         let err = invoice
@@ -509,6 +812,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn payment_or_credit_in_another_currency_is_rejected() {
+        let mut invoice = Invoice::empty(test_invoice_id());
+        let tenant_id = test_tenant_id();
+        let invoice_id = test_invoice_id();
+        let order_id = test_sales_order_id();
+
+        let events = invoice
+            .handle(&InvoiceCommand::IssueInvoice(IssueInvoice {
+                tenant_id,
+                invoice_id,
+                sales_order_id: order_id,
+                lines: vec![single_line(order_id)],
+                payment_terms_days: DEFAULT_PAYMENT_TERMS_DAYS,
+                currency: Some("JPY".to_string()),
+                occurred_at: test_time(),
+            }))
+            .unwrap();
+        invoice.apply(&events[0]);
+        assert_eq!(invoice.currency(), Some("JPY"));
+
+        let pay = |currency: Option<&str>| {
+            InvoiceCommand::RegisterPayment(RegisterPayment {
+                tenant_id,
+                invoice_id,
+                payment_id: PaymentId::new(AggregateId::new()),
+                amount: 50,
+                currency: currency.map(str::to_string),
+                occurred_at: test_time(),
+            })
+        };
+        let err = invoice.handle(&pay(Some("USD"))).unwrap_err();
+        assert!(matches!(err, DomainError::Validation(_)));
+
+        let credit = InvoiceCommand::IssueCreditNote(IssueCreditNote {
+            tenant_id,
+            invoice_id,
+            lines: vec![single_line(order_id)],
+            currency: Some("USD".to_string()),
+            reason: None,
+            occurred_at: test_time(),
+        });
+        let err = invoice.handle(&credit).unwrap_err();
+        assert!(matches!(err, DomainError::Validation(_)));
+
+        // Omitting the currency pays in the invoice's, and the event records it.
+        match &invoice.handle(&pay(None)).unwrap()[0] {
+            InvoiceEvent::PaymentRegistered(e) => assert_eq!(e.currency.as_deref(), Some("JPY")),
+            other => panic!("unexpected event {other:?}"),
+        }
+        assert!(invoice.handle(&pay(Some("JPY"))).is_ok());
+    }
+
     #[test]
     fn cannot_overpay_invoice() {
         let mut invoice = Invoice::empty(test_invoice_id());
@@ -522,7 +878,8 @@ mod tests {
             invoice_id,
             sales_order_id: order_id,
             lines: vec![line],
-            due_date: test_time(),
+            payment_terms_days: DEFAULT_PAYMENT_TERMS_DAYS,
+            currency: None,
             occurred_at: test_time(),
         };
         let events = invoice
@@ -535,7 +892,9 @@ mod tests {
         let cmd_pay = RegisterPayment {
             tenant_id,
             invoice_id,
+            payment_id: PaymentId::new(AggregateId::new()),
             amount: 201,
+            currency: None,
             occurred_at: test_time(),
         };
         let err = invoice
@@ -560,7 +919,8 @@ mod tests {
             invoice_id,
             sales_order_id: order_id,
             lines: vec![line],
-            due_date: test_time(),
+            payment_terms_days: DEFAULT_PAYMENT_TERMS_DAYS,
+            currency: None,
             occurred_at: test_time(),
         };
         let events = invoice
@@ -575,7 +935,9 @@ mod tests {
         let cmd_pay1 = RegisterPayment {
             tenant_id,
             invoice_id,
+            payment_id: PaymentId::new(AggregateId::new()),
             amount: 50,
+            currency: None,
             occurred_at: test_time(),
         };
         let events = invoice
@@ -589,7 +951,9 @@ mod tests {
         let cmd_pay2 = RegisterPayment {
             tenant_id,
             invoice_id,
+            payment_id: PaymentId::new(AggregateId::new()),
             amount: 150,
+            currency: None,
             occurred_at: test_time(),
         };
         let events = invoice
@@ -599,6 +963,272 @@ mod tests {
         assert_eq!(invoice.total_paid(), 200);
         assert_eq!(invoice.status(), InvoiceStatus::Paid);
     }
-}
 
+    #[test]
+    fn credit_notes_reduce_net_total_and_cannot_exceed_uncredited_amount() {
+        let mut invoice = Invoice::empty(test_invoice_id());
+        let tenant_id = test_tenant_id();
+        let invoice_id = test_invoice_id();
+        let order_id = test_sales_order_id();
+
+        let line = single_line(order_id);
+        let cmd_issue = IssueInvoice {
+            tenant_id,
+            invoice_id,
+            sales_order_id: order_id,
+            lines: vec![line.clone()],
+            payment_terms_days: DEFAULT_PAYMENT_TERMS_DAYS,
+            currency: None,
+            occurred_at: test_time(),
+        };
+        let events = invoice
+            .handle(&InvoiceCommand::IssueInvoice(cmd_issue))
+            .unwrap();
+        invoice.apply(&events[0]);
+
+        // Credit one of the two units.
+        let credit = IssueCreditNote {
+            tenant_id,
+            invoice_id,
+            lines: vec![InvoiceLine { quantity: 1, ..line.clone() }],
+            currency: None,
+            reason: Some("Damaged unit".to_string()),
+            occurred_at: test_time(),
+        };
+        let events = invoice
+            .handle(&InvoiceCommand::IssueCreditNote(credit))
+            .unwrap();
+        match &events[0] {
+            InvoiceEvent::CreditNoteIssued(e) => {
+                assert_eq!(e.amount, 100);
+                assert_eq!(e.new_credited_total, 100);
+            }
+            _ => panic!("Expected CreditNoteIssued event"),
+        }
+        invoice.apply(&events[0]);
+        assert_eq!(invoice.credited_total(), 100);
+        assert_eq!(invoice.net_total(), 100);
+        assert_eq!(invoice.outstanding_amount(), 100);
+
+        // Crediting both units again would exceed what is left.
+        let too_much = IssueCreditNote {
+            tenant_id,
+            invoice_id,
+            lines: vec![line.clone()],
+            currency: None,
+            reason: None,
+            occurred_at: test_time(),
+        };
+        let err = invoice
+            .handle(&InvoiceCommand::IssueCreditNote(too_much))
+            .unwrap_err();
+        assert!(matches!(err, DomainError::InvariantViolation(_)));
+
+        // Payment is capped at the net total and settles the invoice.
+        let pay = RegisterPayment {
+            tenant_id,
+            invoice_id,
+            payment_id: PaymentId::new(AggregateId::new()),
+            amount: 100,
+            currency: None,
+            occurred_at: test_time(),
+        };
+        let events = invoice
+            .handle(&InvoiceCommand::RegisterPayment(pay))
+            .unwrap();
+        invoice.apply(&events[0]);
+        assert_eq!(invoice.status(), InvoiceStatus::Paid);
+        assert_eq!(invoice.outstanding_amount(), 0);
+    }
+
+    #[test]
+    fn reversing_a_payment_reopens_the_invoice_once() {
+        let mut invoice = Invoice::empty(test_invoice_id());
+        let tenant_id = test_tenant_id();
+        let invoice_id = test_invoice_id();
+        let order_id = test_sales_order_id();
+
+        let cmd_issue = IssueInvoice {
+            tenant_id,
+            invoice_id,
+            sales_order_id: order_id,
+            lines: vec![single_line(order_id)],
+            payment_terms_days: DEFAULT_PAYMENT_TERMS_DAYS,
+            currency: None,
+            occurred_at: test_time(),
+        };
+        let events = invoice
+            .handle(&InvoiceCommand::IssueInvoice(cmd_issue))
+            .unwrap();
+        invoice.apply(&events[0]);
+
+        let payment_id = PaymentId::new(AggregateId::new());
+        let cmd_pay = RegisterPayment {
+            tenant_id,
+            invoice_id,
+            payment_id,
+            amount: 200,
+            currency: None,
+            occurred_at: test_time(),
+        };
+        let events = invoice
+            .handle(&InvoiceCommand::RegisterPayment(cmd_pay))
+            .unwrap();
+        invoice.apply(&events[0]);
+        assert_eq!(invoice.status(), InvoiceStatus::Paid);
+
+        let cmd_reverse = ReversePayment {
+            tenant_id,
+            invoice_id,
+            payment_id,
+            reason: Some("Check bounced".to_string()),
+            occurred_at: test_time(),
+        };
+        let events = invoice
+            .handle(&InvoiceCommand::ReversePayment(cmd_reverse.clone()))
+            .unwrap();
+        match &events[0] {
+            InvoiceEvent::PaymentReversed(e) => {
+                assert_eq!(e.amount, 200);
+                assert_eq!(e.new_total_paid, 0);
+            }
+            _ => panic!("Expected PaymentReversed event"),
+        }
+        invoice.apply(&events[0]);
+        assert_eq!(invoice.status(), InvoiceStatus::Open);
+        assert_eq!(invoice.total_paid(), 0);
+        assert_eq!(invoice.outstanding_amount(), 200);
+
+        let err = invoice
+            .handle(&InvoiceCommand::ReversePayment(cmd_reverse))
+            .unwrap_err();
+        assert!(matches!(err, DomainError::Conflict(_)));
+
+        let unknown = ReversePayment {
+            tenant_id,
+            invoice_id,
+            payment_id: PaymentId::new(AggregateId::new()),
+            reason: None,
+            occurred_at: test_time(),
+        };
+        let err = invoice
+            .handle(&InvoiceCommand::ReversePayment(unknown))
+            .unwrap_err();
+        assert!(matches!(err, DomainError::NotFound));
+    }
+
+    #[test]
+    fn cannot_reverse_payment_on_void_invoice() {
+        let mut invoice = Invoice::empty(test_invoice_id());
+        let tenant_id = test_tenant_id();
+        let invoice_id = test_invoice_id();
+        let order_id = test_sales_order_id();
+
+        let cmd_issue = IssueInvoice {
+            tenant_id,
+            invoice_id,
+            sales_order_id: order_id,
+            lines: vec![single_line(order_id)],
+            payment_terms_days: DEFAULT_PAYMENT_TERMS_DAYS,
+            currency: None,
+            occurred_at: test_time(),
+        };
+        let events = invoice
+            .handle(&InvoiceCommand::IssueInvoice(cmd_issue))
+            .unwrap();
+        invoice.apply(&events[0]);
+
+        let payment_id = PaymentId::new(AggregateId::new());
+        let cmd_pay = RegisterPayment {
+            tenant_id,
+            invoice_id,
+            payment_id,
+            amount: 50,
+            currency: None,
+            occurred_at: test_time(),
+        };
+        let events = invoice
+            .handle(&InvoiceCommand::RegisterPayment(cmd_pay))
+            .unwrap();
+        invoice.apply(&events[0]);
 
+        let cmd_void = VoidInvoice {
+            tenant_id,
+            invoice_id,
+            reason: None,
+            occurred_at: test_time(),
+        };
+        let events = invoice
+            .handle(&InvoiceCommand::VoidInvoice(cmd_void))
+            .unwrap();
+        invoice.apply(&events[0]);
+
+        let cmd_reverse = ReversePayment {
+            tenant_id,
+            invoice_id,
+            payment_id,
+            reason: None,
+            occurred_at: test_time(),
+        };
+        let err = invoice
+            .handle(&InvoiceCommand::ReversePayment(cmd_reverse))
+            .unwrap_err();
+        match err {
+            DomainError::InvariantViolation(msg)
+                if msg.contains("cannot reverse payment on void invoice") => {}
+            _ => panic!("Expected InvariantViolation for reversing on void invoice"),
+        }
+    }
+
+    #[test]
+    fn zero_day_terms_make_invoice_immediately_due() {
+        let mut invoice = Invoice::empty(test_invoice_id());
+        let order_id = test_sales_order_id();
+        let issued_at = test_time();
+
+        let cmd_issue = IssueInvoice {
+            tenant_id: test_tenant_id(),
+            invoice_id: test_invoice_id(),
+            sales_order_id: order_id,
+            lines: vec![single_line(order_id)],
+            payment_terms_days: 0,
+            currency: None,
+            occurred_at: issued_at,
+        };
+        let events = invoice
+            .handle(&InvoiceCommand::IssueInvoice(cmd_issue))
+            .unwrap();
+        invoice.apply(&events[0]);
+
+        assert_eq!(invoice.due_at(), Some(issued_at));
+        assert!(invoice.is_due(issued_at));
+    }
+
+    #[test]
+    fn legacy_issued_event_without_terms_replays_with_default() {
+        let order_id = test_sales_order_id();
+        let due = test_time();
+        let event = InvoiceEvent::InvoiceIssued(InvoiceIssued {
+            tenant_id: test_tenant_id(),
+            invoice_id: test_invoice_id(),
+            sales_order_id: order_id,
+            lines: vec![single_line(order_id)],
+            payment_terms_days: 0,
+            due_date: due,
+            total_amount: 200,
+            currency: None,
+            occurred_at: test_time(),
+        });
+        let mut json = serde_json::to_value(&event).unwrap();
+        json["InvoiceIssued"]
+            .as_object_mut()
+            .unwrap()
+            .remove("payment_terms_days");
+        let event: InvoiceEvent = serde_json::from_value(json).unwrap();
+
+        let mut invoice = Invoice::empty(test_invoice_id());
+        invoice.apply(&event);
+        assert_eq!(invoice.payment_terms_days(), DEFAULT_PAYMENT_TERMS_DAYS);
+        assert_eq!(invoice.due_at(), Some(due));
+    }
+}
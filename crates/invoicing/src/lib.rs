@@ -6,8 +6,9 @@
 pub mod invoice;
 
 pub use invoice::{
-    Invoice, InvoiceCommand, InvoiceEvent, InvoiceId, InvoiceIssued, InvoiceLine, InvoiceStatus,
-    InvoiceVoided, IssueInvoice, PaymentRegistered, RegisterPayment, VoidInvoice,
+    CreditNoteIssued, DEFAULT_PAYMENT_TERMS_DAYS, Invoice, InvoiceCommand, InvoiceEvent, InvoiceId, InvoiceIssued, InvoiceLine, InvoiceStatus,
+    InvoiceVoided, IssueCreditNote, IssueInvoice, PaymentId, PaymentRegistered, PaymentReversed,
+    RecordedPayment, RegisterPayment, ReversePayment, VoidInvoice,
 };
 
 
@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use forgeerp_core::{Aggregate, AggregateRoot, AggregateId, DomainError, TenantId};
+use forgeerp_core::{Aggregate, AggregateRoot, AggregateId, DomainError, ExpectedVersion, TenantId, VersionedCommand};
 use forgeerp_events::Event;
 
 /// Party identifier (tenant-scoped via `tenant_id` fields in events/commands).
@@ -56,7 +56,7 @@ impl Default for ContactInfo {
 }
 
 /// Aggregate root: Party (customer or supplier).
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Party {
     id: PartyId,
     tenant_id: Option<TenantId>,
@@ -147,6 +147,9 @@ pub struct UpdateDetails {
     pub name: Option<String>,
     /// Optional new contact info (if None, keep existing).
     pub contact: Option<ContactInfo>,
+    /// Party version the edit is based on (if None, edit the latest state).
+    #[serde(default)]
+    pub expected_version: Option<u64>,
     pub occurred_at: DateTime<Utc>,
 }
 
@@ -167,6 +170,18 @@ pub enum PartyCommand {
     SuspendParty(SuspendParty),
 }
 
+impl VersionedCommand for PartyCommand {
+    fn expected_version(&self) -> ExpectedVersion {
+        match self {
+            PartyCommand::UpdateDetails(UpdateDetails {
+                expected_version: Some(v),
+                ..
+            }) => ExpectedVersion::Exact(*v),
+            _ => ExpectedVersion::Any,
+        }
+    }
+}
+
 /// Event: PartyRegistered.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PartyRegistered {
@@ -469,6 +484,7 @@ mod tests {
             party_id,
             name: Some("New Name".to_string()),
             contact: Some(new_contact.clone()),
+            expected_version: None,
             occurred_at: test_time(),
         };
 
@@ -6,7 +6,8 @@
 pub mod product;
 
 pub use product::{
-    ActivateProduct, ArchiveProduct, CreateProduct, Product, ProductArchived, ProductActivated,
-    PricingMetadata, ProductCommand, ProductCreated, ProductEvent, ProductId, ProductStatus,
+    ActivateProduct, ArchiveProduct, CreateProduct, DeleteProduct, Product, ProductArchived,
+    ProductActivated, PricingMetadata, ProductCommand, ProductCreated, ProductDeleted, ProductEvent,
+    ProductId, ProductPricingUpdated, ProductStatus, UpdatePricing,
 };
 
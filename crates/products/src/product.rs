@@ -1,7 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use forgeerp_core::{Aggregate, AggregateRoot, AggregateId, DomainError, TenantId};
+use forgeerp_core::tombstone::ensure_not_deleted;
+use forgeerp_core::{Aggregate, AggregateRoot, AggregateId, DeletionGuard, DomainError, TenantId};
 use forgeerp_events::Event;
 
 /// Product identifier (tenant-scoped via `tenant_id` fields in events/commands).
@@ -28,6 +29,8 @@ pub enum ProductStatus {
     Draft,
     Active,
     Archived,
+    /// Terminal: removed from the catalog, kept as a tombstone.
+    Deleted,
 }
 
 /// Optional pricing metadata (no accounting yet).
@@ -37,6 +40,24 @@ pub struct PricingMetadata {
     pub currency: Option<String>, // ISO currency code (e.g., "USD", "EUR")
 }
 
+impl PricingMetadata {
+    /// A currency needs a base price, and must be a 3-letter uppercase ISO code.
+    pub fn validate(&self) -> Result<(), DomainError> {
+        let Some(currency) = &self.currency else {
+            return Ok(());
+        };
+        if self.base_price.is_none() {
+            return Err(DomainError::validation("base_price is required when currency is set"));
+        }
+        if currency.len() != 3 || !currency.bytes().all(|b| b.is_ascii_uppercase()) {
+            return Err(DomainError::validation(format!(
+                "currency must be a 3-letter uppercase code, got '{currency}'"
+            )));
+        }
+        Ok(())
+    }
+}
+
 impl Default for PricingMetadata {
     fn default() -> Self {
         Self {
@@ -47,7 +68,7 @@ impl Default for PricingMetadata {
 }
 
 /// Aggregate root: Product.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Product {
     id: ProductId,
     tenant_id: Option<TenantId>,
@@ -55,6 +76,7 @@ pub struct Product {
     name: String,
     status: ProductStatus,
     pricing: PricingMetadata,
+    inventory_item_id: Option<AggregateId>,
     version: u64,
     created: bool,
 }
@@ -69,6 +91,7 @@ impl Product {
             name: String::new(),
             status: ProductStatus::Draft,
             pricing: PricingMetadata::default(),
+            inventory_item_id: None,
             version: 0,
             created: false,
         }
@@ -98,6 +121,11 @@ impl Product {
         &self.pricing
     }
 
+    /// Inventory item that holds this product's stock, if it is stocked.
+    pub fn inventory_item_id(&self) -> Option<AggregateId> {
+        self.inventory_item_id
+    }
+
     /// Check if product can be sold (must be Active, not Archived).
     pub fn can_be_sold(&self) -> bool {
         self.status == ProductStatus::Active
@@ -124,6 +152,9 @@ pub struct CreateProduct {
     pub sku: String,
     pub name: String,
     pub pricing: Option<PricingMetadata>,
+    /// Inventory item that holds this product's stock, if it is stocked.
+    #[serde(default)]
+    pub inventory_item_id: Option<AggregateId>,
     pub occurred_at: DateTime<Utc>,
 }
 
@@ -143,11 +174,33 @@ pub struct ArchiveProduct {
     pub occurred_at: DateTime<Utc>,
 }
 
+/// Command: UpdatePricing (replaces the product's pricing).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdatePricing {
+    pub tenant_id: TenantId,
+    pub product_id: ProductId,
+    pub pricing: PricingMetadata,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Command: DeleteProduct.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeleteProduct {
+    pub tenant_id: TenantId,
+    pub product_id: ProductId,
+    pub reason: Option<String>,
+    /// References gathered by the caller (e.g. order and invoice lines).
+    pub guard: DeletionGuard,
+    pub occurred_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProductCommand {
     CreateProduct(CreateProduct),
     ActivateProduct(ActivateProduct),
     ArchiveProduct(ArchiveProduct),
+    UpdatePricing(UpdatePricing),
+    DeleteProduct(DeleteProduct),
 }
 
 /// Event: ProductCreated.
@@ -158,6 +211,8 @@ pub struct ProductCreated {
     pub sku: String,
     pub name: String,
     pub pricing: PricingMetadata,
+    #[serde(default)]
+    pub inventory_item_id: Option<AggregateId>,
     pub occurred_at: DateTime<Utc>,
 }
 
@@ -177,11 +232,31 @@ pub struct ProductArchived {
     pub occurred_at: DateTime<Utc>,
 }
 
+/// Event: ProductPricingUpdated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProductPricingUpdated {
+    pub tenant_id: TenantId,
+    pub product_id: ProductId,
+    pub pricing: PricingMetadata,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Event: ProductDeleted (tombstone).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProductDeleted {
+    pub tenant_id: TenantId,
+    pub product_id: ProductId,
+    pub reason: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProductEvent {
     ProductCreated(ProductCreated),
     ProductActivated(ProductActivated),
     ProductArchived(ProductArchived),
+    ProductPricingUpdated(ProductPricingUpdated),
+    ProductDeleted(ProductDeleted),
 }
 
 impl Event for ProductEvent {
@@ -190,6 +265,8 @@ impl Event for ProductEvent {
             ProductEvent::ProductCreated(_) => "products.product.created",
             ProductEvent::ProductActivated(_) => "products.product.activated",
             ProductEvent::ProductArchived(_) => "products.product.archived",
+            ProductEvent::ProductPricingUpdated(_) => "products.product.pricing_updated",
+            ProductEvent::ProductDeleted(_) => "products.product.deleted",
         }
     }
 
@@ -202,6 +279,8 @@ impl Event for ProductEvent {
             ProductEvent::ProductCreated(e) => e.occurred_at,
             ProductEvent::ProductActivated(e) => e.occurred_at,
             ProductEvent::ProductArchived(e) => e.occurred_at,
+            ProductEvent::ProductPricingUpdated(e) => e.occurred_at,
+            ProductEvent::ProductDeleted(e) => e.occurred_at,
         }
     }
 }
@@ -220,6 +299,7 @@ impl Aggregate for Product {
                 self.name = e.name.clone();
                 self.status = ProductStatus::Draft;
                 self.pricing = e.pricing.clone();
+                self.inventory_item_id = e.inventory_item_id;
                 self.created = true;
             }
             ProductEvent::ProductActivated(_) => {
@@ -228,6 +308,12 @@ impl Aggregate for Product {
             ProductEvent::ProductArchived(_) => {
                 self.status = ProductStatus::Archived;
             }
+            ProductEvent::ProductPricingUpdated(e) => {
+                self.pricing = e.pricing.clone();
+            }
+            ProductEvent::ProductDeleted(_) => {
+                self.status = ProductStatus::Deleted;
+            }
         }
 
         // Deterministic version tracking: +1 per applied event.
@@ -235,10 +321,14 @@ impl Aggregate for Product {
     }
 
     fn handle(&self, command: &Self::Command) -> Result<Vec<Self::Event>, Self::Error> {
+        ensure_not_deleted(self.status == ProductStatus::Deleted)?;
+
         match command {
             ProductCommand::CreateProduct(cmd) => self.handle_create(cmd),
             ProductCommand::ActivateProduct(cmd) => self.handle_activate(cmd),
             ProductCommand::ArchiveProduct(cmd) => self.handle_archive(cmd),
+            ProductCommand::UpdatePricing(cmd) => self.handle_update_pricing(cmd),
+            ProductCommand::DeleteProduct(cmd) => self.handle_delete(cmd),
         }
     }
 }
@@ -274,10 +364,9 @@ impl Product {
             return Err(DomainError::validation("SKU cannot be empty"));
         }
         
-        // Note: True SKU uniqueness per tenant requires infrastructure support
-        // (checking event store or read model). At the aggregate level, we can only
-        // enforce that SKU is non-empty. Infrastructure layer should validate uniqueness
-        // before dispatching the command.
+        // Note: SKU uniqueness per tenant spans streams, so the aggregate only checks
+        // that the SKU is non-empty. Uniqueness among live products is enforced by the
+        // infra `SkuRegistry`, which reserves the SKU before this command is dispatched.
 
         Ok(vec![ProductEvent::ProductCreated(ProductCreated {
             tenant_id: cmd.tenant_id,
@@ -285,6 +374,7 @@ impl Product {
             sku: cmd.sku.clone(),
             name: cmd.name.clone(),
             pricing: cmd.pricing.clone().unwrap_or_default(),
+            inventory_item_id: cmd.inventory_item_id,
             occurred_at: cmd.occurred_at,
         })])
     }
@@ -328,6 +418,42 @@ impl Product {
             occurred_at: cmd.occurred_at,
         })])
     }
+
+    fn handle_update_pricing(&self, cmd: &UpdatePricing) -> Result<Vec<ProductEvent>, DomainError> {
+        if !self.created {
+            return Err(DomainError::not_found());
+        }
+        self.ensure_tenant(cmd.tenant_id)?;
+        self.ensure_product_id(cmd.product_id)?;
+
+        if self.status == ProductStatus::Archived {
+            return Err(DomainError::invariant("archived products cannot be repriced"));
+        }
+        cmd.pricing.validate()?;
+
+        Ok(vec![ProductEvent::ProductPricingUpdated(ProductPricingUpdated {
+            tenant_id: cmd.tenant_id,
+            product_id: cmd.product_id,
+            pricing: cmd.pricing.clone(),
+            occurred_at: cmd.occurred_at,
+        })])
+    }
+
+    fn handle_delete(&self, cmd: &DeleteProduct) -> Result<Vec<ProductEvent>, DomainError> {
+        if !self.created {
+            return Err(DomainError::not_found());
+        }
+        self.ensure_tenant(cmd.tenant_id)?;
+        self.ensure_product_id(cmd.product_id)?;
+        cmd.guard.check()?;
+
+        Ok(vec![ProductEvent::ProductDeleted(ProductDeleted {
+            tenant_id: cmd.tenant_id,
+            product_id: cmd.product_id,
+            reason: cmd.reason.clone(),
+            occurred_at: cmd.occurred_at,
+        })])
+    }
 }
 
 #[cfg(test)]
@@ -358,6 +484,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -384,6 +511,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "   ".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -403,6 +531,7 @@ mod tests {
             sku: "   ".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -424,6 +553,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -452,6 +582,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: Some(pricing.clone()),
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -476,6 +607,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -514,6 +646,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -544,6 +677,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -577,6 +711,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -617,6 +752,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -652,6 +788,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -692,6 +829,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -725,6 +863,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -782,6 +921,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -816,6 +956,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -853,6 +994,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: None,
+            inventory_item_id: None,
             occurred_at: test_time(),
         };
 
@@ -893,6 +1035,7 @@ mod tests {
             sku: "SKU-001".to_string(),
             name: "Test Product".to_string(),
             pricing: PricingMetadata::default(),
+            inventory_item_id: None,
             occurred_at: test_time(),
         });
         let event2 = ProductEvent::ProductActivated(ProductActivated {
@@ -925,6 +1068,102 @@ mod tests {
         assert_eq!(product1.version(), 3);
     }
 
+    fn created_product(tenant_id: TenantId, product_id: ProductId) -> Product {
+        let mut product = Product::empty(product_id);
+        let events = product
+            .handle(&ProductCommand::CreateProduct(CreateProduct {
+                tenant_id,
+                product_id,
+                sku: "SKU-001".to_string(),
+                name: "Created by mistake".to_string(),
+                pricing: None,
+                inventory_item_id: None,
+                occurred_at: test_time(),
+            }))
+            .unwrap();
+        product.apply(&events[0]);
+        product
+    }
+
+    fn delete_cmd(tenant_id: TenantId, product_id: ProductId, references: u64) -> ProductCommand {
+        ProductCommand::DeleteProduct(DeleteProduct {
+            tenant_id,
+            product_id,
+            reason: Some("duplicate".to_string()),
+            guard: DeletionGuard { references },
+            occurred_at: test_time(),
+        })
+    }
+
+    #[test]
+    fn deleted_product_rejects_further_commands() {
+        let tenant_id = test_tenant_id();
+        let product_id = test_product_id();
+        let mut product = created_product(tenant_id, product_id);
+
+        let events = product.handle(&delete_cmd(tenant_id, product_id, 0)).unwrap();
+        product.apply(&events[0]);
+        assert_eq!(product.status(), ProductStatus::Deleted);
+        assert!(!product.can_be_sold());
+
+        let activate = ProductCommand::ActivateProduct(ActivateProduct {
+            tenant_id,
+            product_id,
+            occurred_at: test_time(),
+        });
+        for cmd in [activate, delete_cmd(tenant_id, product_id, 0)] {
+            assert!(matches!(product.handle(&cmd), Err(DomainError::InvariantViolation(_))));
+        }
+    }
+
+    #[test]
+    fn referenced_product_cannot_be_deleted() {
+        let tenant_id = test_tenant_id();
+        let product_id = test_product_id();
+        let product = created_product(tenant_id, product_id);
+
+        let err = product.handle(&delete_cmd(tenant_id, product_id, 3)).unwrap_err();
+        assert!(matches!(err, DomainError::InvariantViolation(_)));
+    }
+
+    #[test]
+    fn update_pricing_replaces_pricing_and_is_validated() {
+        let tenant_id = test_tenant_id();
+        let product_id = test_product_id();
+        let mut product = created_product(tenant_id, product_id);
+        let update = |base_price: Option<u64>, currency: Option<&str>| {
+            ProductCommand::UpdatePricing(UpdatePricing {
+                tenant_id,
+                product_id,
+                pricing: PricingMetadata {
+                    base_price,
+                    currency: currency.map(str::to_string),
+                },
+                occurred_at: test_time(),
+            })
+        };
+
+        let events = product.handle(&update(Some(1_250), Some("EUR"))).unwrap();
+        product.apply(&events[0]);
+        assert_eq!(product.pricing().base_price, Some(1_250));
+        assert_eq!(product.pricing().currency.as_deref(), Some("EUR"));
+
+        for invalid in [update(None, Some("EUR")), update(Some(1), Some("eur")), update(Some(1), Some("EURO"))] {
+            assert!(matches!(product.handle(&invalid), Err(DomainError::Validation(_))));
+        }
+
+        let archived = product
+            .handle(&ProductCommand::ArchiveProduct(ArchiveProduct {
+                tenant_id,
+                product_id,
+                occurred_at: test_time(),
+            }))
+            .unwrap();
+        product.apply(&archived[0]);
+        let err = product.handle(&update(Some(900), Some("EUR"))).unwrap_err();
+        assert!(matches!(err, DomainError::InvariantViolation(_)));
+    }
+
     #[cfg(test)]
     mod proptest_tests {
         use super::*;
@@ -954,6 +1193,7 @@ mod tests {
                     sku: sku.clone(),
                     name: name.clone(),
                     pricing: None,
+                    inventory_item_id: None,
                     occurred_at: Utc::now(),
                 };
                 let events = product.handle(&ProductCommand::CreateProduct(create_cmd)).unwrap();
@@ -999,6 +1239,7 @@ mod tests {
                         sku: sku.clone(),
                         name: name.clone(),
                         pricing: PricingMetadata::default(),
+                        inventory_item_id: None,
                         occurred_at: Utc::now(),
                     }),
                     ProductEvent::ProductActivated(ProductActivated {
@@ -1051,6 +1292,7 @@ mod tests {
                     sku,
                     name,
                     pricing: None,
+                    inventory_item_id: None,
                     occurred_at: Utc::now(),
                 };
                 let events = product.handle(&ProductCommand::CreateProduct(create_cmd)).unwrap();
@@ -1098,6 +1340,7 @@ mod tests {
                     sku,
                     name,
                     pricing: None,
+                    inventory_item_id: None,
                     occurred_at: Utc::now(),
                 };
                 let events = product.handle(&ProductCommand::CreateProduct(create_cmd)).unwrap();
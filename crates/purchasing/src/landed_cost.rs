@@ -0,0 +1,183 @@
+//! Landed cost: order-level freight and discount spread across purchase order lines.
+//!
+//! Each amount is split proportionally to a per-line weight (line value or quantity)
+//! using the largest-remainder method, so the line shares always add up to the
+//! order-level amount exactly. Minor units left over after integer division go one
+//! each to the lines with the largest remainders; ties go to the lowest `line_no`.
+
+use serde::{Deserialize, Serialize};
+
+/// How order-level amounts are weighted across lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AllocationBasis {
+    /// By line value (`quantity × unit_cost`); falls back to quantity when no line is priced.
+    #[default]
+    Value,
+    /// By ordered quantity.
+    Quantity,
+}
+
+/// Input line for [`allocate_landed_costs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostLine {
+    pub line_no: u32,
+    pub quantity: i64,
+    /// Agreed price per unit in minor units, if known.
+    pub unit_cost: Option<u64>,
+}
+
+impl CostLine {
+    fn quantity(&self) -> u64 {
+        self.quantity.max(0) as u64
+    }
+
+    /// `quantity × unit_cost` (0 when unpriced).
+    pub fn value(&self) -> u64 {
+        self.unit_cost.unwrap_or(0).saturating_mul(self.quantity())
+    }
+}
+
+/// Landed cost of one line (all amounts in minor units).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineLandedCost {
+    pub line_no: u32,
+    pub quantity: i64,
+    /// `quantity × unit_cost` before freight and discount.
+    pub value: u64,
+    pub freight: u64,
+    pub discount: u64,
+    /// `value + freight - discount`, floored at zero.
+    pub landed_cost: u64,
+}
+
+impl LineLandedCost {
+    /// Landed cost per unit, rounded half up; `None` for a zero quantity.
+    pub fn unit_landed_cost(&self) -> Option<u64> {
+        let quantity = u64::try_from(self.quantity).ok().filter(|q| *q > 0)?;
+        Some((self.landed_cost + quantity / 2) / quantity)
+    }
+}
+
+/// Split `amount` proportionally to `weights`; the result sums to `amount` exactly.
+///
+/// `weights` are `(line_no, weight)` pairs. When every weight is zero the amount is split
+/// evenly. Returns shares in input order; empty input allocates nothing.
+pub fn allocate_proportionally(amount: u64, weights: &[(u32, u64)]) -> Vec<u64> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+    let total: u128 = weights.iter().map(|(_, w)| u128::from(*w)).sum();
+    let even = total == 0;
+    let total = if even { weights.len() as u128 } else { total };
+
+    let mut shares = Vec::with_capacity(weights.len());
+    let mut remainders = Vec::with_capacity(weights.len());
+    for (index, (line_no, weight)) in weights.iter().enumerate() {
+        let weight = if even { 1 } else { u128::from(*weight) };
+        let scaled = u128::from(amount) * weight;
+        shares.push((scaled / total) as u64);
+        remainders.push((scaled % total, *line_no, index));
+    }
+
+    let leftover = amount - shares.iter().sum::<u64>();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, _, index) in remainders.into_iter().take(leftover as usize) {
+        shares[index] += 1;
+    }
+    shares
+}
+
+/// Allocate order-level `freight` and `discount` across `lines` by `basis`.
+pub fn allocate_landed_costs(
+    lines: &[CostLine],
+    freight: u64,
+    discount: u64,
+    basis: AllocationBasis,
+) -> Vec<LineLandedCost> {
+    let by_quantity: Vec<(u32, u64)> = lines.iter().map(|l| (l.line_no, l.quantity())).collect();
+    let weights = match basis {
+        AllocationBasis::Quantity => by_quantity,
+        AllocationBasis::Value => {
+            let by_value: Vec<(u32, u64)> = lines.iter().map(|l| (l.line_no, l.value())).collect();
+            if by_value.iter().all(|(_, w)| *w == 0) {
+                by_quantity
+            } else {
+                by_value
+            }
+        }
+    };
+    let freight_shares = allocate_proportionally(freight, &weights);
+    let discount_shares = allocate_proportionally(discount, &weights);
+
+    lines
+        .iter()
+        .zip(freight_shares.into_iter().zip(discount_shares))
+        .map(|(line, (freight, discount))| {
+            let value = line.value();
+            LineLandedCost {
+                line_no: line.line_no,
+                quantity: line.quantity,
+                value,
+                freight,
+                discount,
+                landed_cost: value.saturating_add(freight).saturating_sub(discount),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(line_no: u32, quantity: i64, unit_cost: u64) -> CostLine {
+        CostLine {
+            line_no,
+            quantity,
+            unit_cost: Some(unit_cost),
+        }
+    }
+
+    #[test]
+    fn shares_sum_exactly_and_remainders_go_to_largest_then_lowest_line() {
+        // Three equal lines: 100 / 3 = 33 r1 each; the leftover 1 goes to line 1.
+        assert_eq!(allocate_proportionally(100, &[(1, 5), (2, 5), (3, 5)]), [34, 33, 33]);
+        // Same weights listed out of order: still line 1, wherever it sits.
+        assert_eq!(allocate_proportionally(100, &[(3, 5), (1, 5), (2, 5)]), [33, 34, 33]);
+        // 10 by 1:2:4 → 1.43, 2.86, 5.71: floors 1+2+5 = 8, leftovers to 0.86 then 0.71.
+        assert_eq!(allocate_proportionally(10, &[(1, 1), (2, 2), (3, 4)]), [1, 3, 6]);
+        // All-zero weights split evenly.
+        assert_eq!(allocate_proportionally(5, &[(1, 0), (2, 0)]), [3, 2]);
+        assert!(allocate_proportionally(5, &[]).is_empty());
+
+        for amount in [0, 1, 7, 999, 1_000_003] {
+            let shares = allocate_proportionally(amount, &[(1, 3), (2, 7), (3, 11), (4, 0)]);
+            assert_eq!(shares.iter().sum::<u64>(), amount);
+        }
+    }
+
+    #[test]
+    fn freight_and_discount_allocate_by_value_or_quantity() {
+        let lines = [line(1, 10, 100), line(2, 5, 600), line(3, 1, 50)];
+
+        // Values 1000 / 3000 / 50 (total 4050).
+        let by_value = allocate_landed_costs(&lines, 1_000, 101, AllocationBasis::Value);
+        assert_eq!(by_value.iter().map(|l| l.freight).collect::<Vec<_>>(), [247, 741, 12]);
+        assert_eq!(by_value.iter().map(|l| l.discount).collect::<Vec<_>>(), [25, 75, 1]);
+        assert_eq!(by_value.iter().map(|l| l.freight).sum::<u64>(), 1_000);
+        assert_eq!(by_value.iter().map(|l| l.discount).sum::<u64>(), 101);
+        assert_eq!(by_value[0].landed_cost, 1_000 + 247 - 25);
+        assert_eq!(by_value[0].unit_landed_cost(), Some(122));
+
+        // Quantities 10 / 5 / 1 (total 16); lines 2 and 3 tie on the remainder, line 2 wins.
+        let by_quantity = allocate_landed_costs(&lines, 1_000, 101, AllocationBasis::Quantity);
+        assert_eq!(by_quantity.iter().map(|l| l.freight).collect::<Vec<_>>(), [625, 313, 62]);
+        assert_eq!(by_quantity.iter().map(|l| l.discount).sum::<u64>(), 101);
+
+        // Unpriced lines fall back to quantity under the value basis.
+        let unpriced = [CostLine { unit_cost: None, ..lines[0] }, CostLine { unit_cost: None, ..lines[1] }];
+        let fallback = allocate_landed_costs(&unpriced, 30, 0, AllocationBasis::Value);
+        assert_eq!(fallback.iter().map(|l| l.freight).collect::<Vec<_>>(), [20, 10]);
+    }
+}
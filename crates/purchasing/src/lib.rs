@@ -3,8 +3,12 @@
 //! This crate contains business rules for purchase orders, implemented purely as
 //! deterministic domain logic (no IO, no HTTP, no storage).
 
+pub mod landed_cost;
 pub mod order;
 
+pub use landed_cost::{
+    allocate_landed_costs, allocate_proportionally, AllocationBasis, CostLine, LineLandedCost,
+};
 pub use order::{
     AddLine, Approve, CreatePurchaseOrder, GoodsReceived, LineItem, PurchaseOrder,
     PurchaseOrderApproved, PurchaseOrderCommand, PurchaseOrderCreated, PurchaseOrderEvent,
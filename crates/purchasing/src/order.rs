@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -6,6 +8,8 @@ use forgeerp_events::Event;
 use forgeerp_parties::PartyId;
 use forgeerp_products::ProductId;
 
+use crate::landed_cost::{allocate_landed_costs, AllocationBasis, CostLine, LineLandedCost};
+
 /// Purchase order identifier (tenant-scoped via `tenant_id` fields in events/commands).
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -42,13 +46,18 @@ pub struct LineItem {
 }
 
 /// Aggregate root: PurchaseOrder.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PurchaseOrder {
     id: PurchaseOrderId,
     tenant_id: Option<TenantId>,
     supplier_id: Option<PartyId>,
     status: PurchaseOrderStatus,
     lines: Vec<LineItem>,
+    /// Agreed unit cost per line number, for priced lines.
+    unit_costs: BTreeMap<u32, u64>,
+    freight: u64,
+    discount: u64,
+    allocation_basis: AllocationBasis,
     version: u64,
     created: bool,
 }
@@ -62,6 +71,10 @@ impl PurchaseOrder {
             supplier_id: None,
             status: PurchaseOrderStatus::Draft,
             lines: Vec::new(),
+            unit_costs: BTreeMap::new(),
+            freight: 0,
+            discount: 0,
+            allocation_basis: AllocationBasis::default(),
             version: 0,
             created: false,
         }
@@ -86,6 +99,42 @@ impl PurchaseOrder {
     pub fn lines(&self) -> &[LineItem] {
         &self.lines
     }
+
+    pub fn freight(&self) -> u64 {
+        self.freight
+    }
+
+    pub fn discount(&self) -> u64 {
+        self.discount
+    }
+
+    pub fn allocation_basis(&self) -> AllocationBasis {
+        self.allocation_basis
+    }
+
+    /// Per-line landed cost with the order-level freight and discount allocated.
+    pub fn landed_costs(&self) -> Vec<LineLandedCost> {
+        landed_costs(&self.lines, &self.unit_costs, self.freight, self.discount, self.allocation_basis)
+    }
+}
+
+/// Landed cost of `lines` priced from `unit_costs` (keyed by line number).
+pub fn landed_costs(
+    lines: &[LineItem],
+    unit_costs: &BTreeMap<u32, u64>,
+    freight: u64,
+    discount: u64,
+    basis: AllocationBasis,
+) -> Vec<LineLandedCost> {
+    let cost_lines: Vec<CostLine> = lines
+        .iter()
+        .map(|l| CostLine {
+            line_no: l.line_no,
+            quantity: l.quantity,
+            unit_cost: unit_costs.get(&l.line_no).copied(),
+        })
+        .collect();
+    allocate_landed_costs(&cost_lines, freight, discount, basis)
 }
 
 impl AggregateRoot for PurchaseOrder {
@@ -106,6 +155,18 @@ pub struct CreatePurchaseOrder {
     pub tenant_id: TenantId,
     pub order_id: PurchaseOrderId,
     pub supplier_id: PartyId,
+    /// Delivery date promised by the supplier, if agreed.
+    #[serde(default)]
+    pub expected_at: Option<DateTime<Utc>>,
+    /// Order-level freight in minor units, allocated across lines.
+    #[serde(default)]
+    pub freight: u64,
+    /// Order-level discount in minor units, allocated across lines.
+    #[serde(default)]
+    pub discount: u64,
+    /// How freight and discount are spread across lines.
+    #[serde(default)]
+    pub allocation_basis: AllocationBasis,
     pub occurred_at: DateTime<Utc>,
 }
 
@@ -124,6 +185,9 @@ pub struct AddLine {
     pub order_id: PurchaseOrderId,
     pub product_id: ProductId,
     pub quantity: i64,
+    /// Agreed price per unit in minor units, if known.
+    #[serde(default)]
+    pub unit_cost: Option<u64>,
     pub occurred_at: DateTime<Utc>,
 }
 
@@ -149,6 +213,18 @@ pub struct PurchaseOrderCreated {
     pub tenant_id: TenantId,
     pub order_id: PurchaseOrderId,
     pub supplier_id: PartyId,
+    /// Delivery date promised by the supplier, if agreed.
+    #[serde(default)]
+    pub expected_at: Option<DateTime<Utc>>,
+    /// Order-level freight in minor units, allocated across lines.
+    #[serde(default)]
+    pub freight: u64,
+    /// Order-level discount in minor units, allocated across lines.
+    #[serde(default)]
+    pub discount: u64,
+    /// How freight and discount are spread across lines.
+    #[serde(default)]
+    pub allocation_basis: AllocationBasis,
     pub occurred_at: DateTime<Utc>,
 }
 
@@ -168,6 +244,9 @@ pub struct PurchaseOrderLineAdded {
     pub line_no: u32,
     pub product_id: ProductId,
     pub quantity: i64,
+    /// Agreed price per unit in minor units, if known.
+    #[serde(default)]
+    pub unit_cost: Option<u64>,
     pub occurred_at: DateTime<Utc>,
 }
 
@@ -230,6 +309,10 @@ impl Aggregate for PurchaseOrder {
                 self.supplier_id = Some(e.supplier_id);
                 self.status = PurchaseOrderStatus::Draft;
                 self.lines.clear();
+                self.unit_costs.clear();
+                self.freight = e.freight;
+                self.discount = e.discount;
+                self.allocation_basis = e.allocation_basis;
                 self.created = true;
             }
             PurchaseOrderEvent::PurchaseOrderLineAdded(e) => {
@@ -238,6 +321,9 @@ impl Aggregate for PurchaseOrder {
                     product_id: e.product_id,
                     quantity: e.quantity,
                 });
+                if let Some(cost) = e.unit_cost {
+                    self.unit_costs.insert(e.line_no, cost);
+                }
             }
             PurchaseOrderEvent::PurchaseOrderApproved(_) => {
                 self.status = PurchaseOrderStatus::Approved;
@@ -294,6 +380,10 @@ impl PurchaseOrder {
                 tenant_id: cmd.tenant_id,
                 order_id: cmd.order_id,
                 supplier_id: cmd.supplier_id,
+                expected_at: cmd.expected_at,
+                freight: cmd.freight,
+                discount: cmd.discount,
+                allocation_basis: cmd.allocation_basis,
                 occurred_at: cmd.occurred_at,
             },
         )])
@@ -352,6 +442,7 @@ impl PurchaseOrder {
                 line_no: next_line_no,
                 product_id: cmd.product_id,
                 quantity: cmd.quantity,
+                unit_cost: cmd.unit_cost,
                 occurred_at: cmd.occurred_at,
             },
         )])
@@ -430,6 +521,10 @@ mod tests {
             tenant_id,
             order_id,
             supplier_id,
+            expected_at: None,
+            freight: 0,
+            discount: 0,
+            allocation_basis: AllocationBasis::Value,
             occurred_at: test_time(),
         };
 
@@ -459,6 +554,10 @@ mod tests {
             tenant_id,
             order_id,
             supplier_id,
+            expected_at: None,
+            freight: 0,
+            discount: 0,
+            allocation_basis: AllocationBasis::Value,
             occurred_at: test_time(),
         };
         let events = order
@@ -473,6 +572,7 @@ mod tests {
             order_id,
             product_id: test_product_id(),
             quantity: 10,
+            unit_cost: None,
             occurred_at: test_time(),
         };
         let events = order
@@ -503,6 +603,10 @@ mod tests {
             tenant_id,
             order_id,
             supplier_id,
+            expected_at: None,
+            freight: 0,
+            discount: 0,
+            allocation_basis: AllocationBasis::Value,
             occurred_at: test_time(),
         };
         let events = order
@@ -516,6 +620,7 @@ mod tests {
             order_id,
             product_id: test_product_id(),
             quantity: 10,
+            unit_cost: None,
             occurred_at: test_time(),
         };
         let events = order
@@ -550,6 +655,10 @@ mod tests {
             tenant_id,
             order_id,
             supplier_id,
+            expected_at: None,
+            freight: 0,
+            discount: 0,
+            allocation_basis: AllocationBasis::Value,
             occurred_at: test_time(),
         };
         let events = order
@@ -562,6 +671,7 @@ mod tests {
             order_id,
             product_id,
             quantity: 10,
+            unit_cost: None,
             occurred_at: test_time(),
         };
         let events = order
@@ -602,6 +712,50 @@ mod tests {
             _ => panic!("Expected GoodsReceived event"),
         }
     }
-}
 
+    #[test]
+    fn order_level_freight_and_discount_allocate_to_lines() {
+        let order_id = test_order_id();
+        let tenant_id = test_tenant_id();
+        let mut order = PurchaseOrder::empty(order_id);
+        let run = |order: &mut PurchaseOrder, cmd: PurchaseOrderCommand| {
+            for event in order.handle(&cmd).unwrap() {
+                order.apply(&event);
+            }
+        };
+
+        run(
+            &mut order,
+            PurchaseOrderCommand::CreatePurchaseOrder(CreatePurchaseOrder {
+                tenant_id,
+                order_id,
+                supplier_id: test_supplier_id(),
+                expected_at: None,
+                freight: 1_000,
+                discount: 99,
+                allocation_basis: AllocationBasis::Value,
+                occurred_at: test_time(),
+            }),
+        );
+        for (quantity, unit_cost) in [(3, 100), (3, 100), (3, 100)] {
+            run(
+                &mut order,
+                PurchaseOrderCommand::AddLine(AddLine {
+                    tenant_id,
+                    order_id,
+                    product_id: test_product_id(),
+                    quantity,
+                    unit_cost: Some(unit_cost),
+                    occurred_at: test_time(),
+                }),
+            );
+        }
 
+        let landed = order.landed_costs();
+        // 1000 / 3 leaves one unit over, which goes to the first line.
+        assert_eq!(landed.iter().map(|l| l.freight).collect::<Vec<_>>(), [334, 333, 333]);
+        assert_eq!(landed.iter().map(|l| l.discount).collect::<Vec<_>>(), [33, 33, 33]);
+        assert_eq!(landed.iter().map(|l| l.landed_cost).sum::<u64>(), 900 + 1_000 - 99);
+        assert_eq!(landed[0].landed_cost, 300 + 334 - 33);
+    }
+}
@@ -6,7 +6,9 @@
 pub mod order;
 
 pub use order::{
-    AddLine, ConfirmOrder, CreateSalesOrder, LineAdded, MarkInvoiced, OrderConfirmed, SalesOrder,
+    AddLine, CancelOrder, ConfirmOrder, CreateSalesOrder, FlagOrder, LineAdded, LineRemoved, MarkBackordered,
+    MarkInvoiced, ORDER_ALREADY_CANCELLED, OrderBackordered, OrderCancelled, OrderConfirmed, OrderFlagged, OrderLine,
+    RemoveLine, SalesOrder,
     SalesOrderCommand, SalesOrderCreated, SalesOrderEvent, SalesOrderId, SalesOrderStatus,
 };
 
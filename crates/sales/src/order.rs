@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use forgeerp_core::{Aggregate, AggregateRoot, AggregateId, DomainError, TenantId};
+use forgeerp_core::{Aggregate, AggregateRoot, AggregateId, DomainError, ErrorCode, TenantId};
 use forgeerp_events::Event;
 use forgeerp_products::ProductId;
 
@@ -30,6 +30,8 @@ pub enum SalesOrderStatus {
     Confirmed,
     Invoiced,
     Closed,
+    /// Cancelled before invoicing (by a user, or because stock could not be reserved).
+    Cancelled,
 }
 
 /// Order line: product, quantity, unit price.
@@ -43,12 +45,16 @@ pub struct OrderLine {
 }
 
 /// Aggregate root: SalesOrder.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SalesOrder {
     id: SalesOrderId,
     tenant_id: Option<TenantId>,
     status: SalesOrderStatus,
     lines: Vec<OrderLine>,
+    /// Number of the next added line; line numbers are never reused after a removal.
+    next_line_no: u32,
+    backordered_lines: Vec<u32>,
+    flag_reason: Option<String>,
     version: u64,
     created: bool,
 }
@@ -61,6 +67,9 @@ impl SalesOrder {
             tenant_id: None,
             status: SalesOrderStatus::Draft,
             lines: Vec::new(),
+            next_line_no: 1,
+            backordered_lines: Vec::new(),
+            flag_reason: None,
             version: 0,
             created: false,
         }
@@ -82,6 +91,16 @@ impl SalesOrder {
         &self.lines
     }
 
+    /// Lines confirmed without enough stock to reserve.
+    pub fn backordered_lines(&self) -> &[u32] {
+        &self.backordered_lines
+    }
+
+    /// Why the order was flagged for attention, if it was.
+    pub fn flag_reason(&self) -> Option<&str> {
+        self.flag_reason.as_deref()
+    }
+
     pub fn is_modifiable(&self) -> bool {
         matches!(self.status, SalesOrderStatus::Draft)
     }
@@ -122,6 +141,15 @@ pub struct AddLine {
     pub occurred_at: DateTime<Utc>,
 }
 
+/// Command: RemoveLine (draft orders only; `line_no` as assigned by `LineAdded`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoveLine {
+    pub tenant_id: TenantId,
+    pub order_id: SalesOrderId,
+    pub line_no: u32,
+    pub occurred_at: DateTime<Utc>,
+}
+
 /// Command: ConfirmOrder.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ConfirmOrder {
@@ -138,12 +166,46 @@ pub struct MarkInvoiced {
     pub occurred_at: DateTime<Utc>,
 }
 
+/// Code of the conflict refusing a `CancelOrder` on an order that is already cancelled.
+pub const ORDER_ALREADY_CANCELLED: ErrorCode = ErrorCode::new("sales.order_already_cancelled");
+
+/// Command: CancelOrder (draft or confirmed orders; cancelling twice is a conflict).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CancelOrder {
+    pub tenant_id: TenantId,
+    pub order_id: SalesOrderId,
+    pub reason: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Command: MarkBackordered (confirmed order lines that could not be reserved).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarkBackordered {
+    pub tenant_id: TenantId,
+    pub order_id: SalesOrderId,
+    pub line_nos: Vec<u32>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Command: FlagOrder (confirmed orders needing attention, e.g. an invoice that never arrived).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlagOrder {
+    pub tenant_id: TenantId,
+    pub order_id: SalesOrderId,
+    pub reason: String,
+    pub occurred_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SalesOrderCommand {
     CreateSalesOrder(CreateSalesOrder),
     AddLine(AddLine),
+    RemoveLine(RemoveLine),
     ConfirmOrder(ConfirmOrder),
     MarkInvoiced(MarkInvoiced),
+    CancelOrder(CancelOrder),
+    MarkBackordered(MarkBackordered),
+    FlagOrder(FlagOrder),
 }
 
 /// Event: SalesOrderCreated.
@@ -159,6 +221,7 @@ pub struct SalesOrderCreated {
 pub struct LineAdded {
     pub tenant_id: TenantId,
     pub order_id: SalesOrderId,
+    /// Stable identifier of the line within the order.
     pub line_no: u32,
     pub product_id: ProductId,
     pub quantity: i64,
@@ -171,6 +234,9 @@ pub struct LineAdded {
 pub struct OrderConfirmed {
     pub tenant_id: TenantId,
     pub order_id: SalesOrderId,
+    /// Lines as confirmed, so downstream handlers (stock reservation) need no lookup.
+    #[serde(default)]
+    pub lines: Vec<OrderLine>,
     pub occurred_at: DateTime<Utc>,
 }
 
@@ -182,12 +248,55 @@ pub struct OrderInvoiced {
     pub occurred_at: DateTime<Utc>,
 }
 
+/// Event: OrderCancelled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderCancelled {
+    pub tenant_id: TenantId,
+    pub order_id: SalesOrderId,
+    pub reason: Option<String>,
+    /// Whether the order had been confirmed (and may hold stock reservations).
+    pub was_confirmed: bool,
+    pub lines: Vec<OrderLine>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Event: LineRemoved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineRemoved {
+    pub tenant_id: TenantId,
+    pub order_id: SalesOrderId,
+    pub line_no: u32,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Event: OrderBackordered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderBackordered {
+    pub tenant_id: TenantId,
+    pub order_id: SalesOrderId,
+    pub line_nos: Vec<u32>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Event: OrderFlagged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderFlagged {
+    pub tenant_id: TenantId,
+    pub order_id: SalesOrderId,
+    pub reason: String,
+    pub occurred_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SalesOrderEvent {
     SalesOrderCreated(SalesOrderCreated),
     LineAdded(LineAdded),
+    LineRemoved(LineRemoved),
     OrderConfirmed(OrderConfirmed),
     OrderInvoiced(OrderInvoiced),
+    OrderCancelled(OrderCancelled),
+    OrderBackordered(OrderBackordered),
+    OrderFlagged(OrderFlagged),
 }
 
 impl Event for SalesOrderEvent {
@@ -195,8 +304,12 @@ impl Event for SalesOrderEvent {
         match self {
             SalesOrderEvent::SalesOrderCreated(_) => "sales.order.created",
             SalesOrderEvent::LineAdded(_) => "sales.order.line_added",
+            SalesOrderEvent::LineRemoved(_) => "sales.order.line_removed",
             SalesOrderEvent::OrderConfirmed(_) => "sales.order.confirmed",
             SalesOrderEvent::OrderInvoiced(_) => "sales.order.invoiced",
+            SalesOrderEvent::OrderCancelled(_) => "sales.order.cancelled",
+            SalesOrderEvent::OrderBackordered(_) => "sales.order.backordered",
+            SalesOrderEvent::OrderFlagged(_) => "sales.order.flagged",
         }
     }
 
@@ -208,8 +321,12 @@ impl Event for SalesOrderEvent {
         match self {
             SalesOrderEvent::SalesOrderCreated(e) => e.occurred_at,
             SalesOrderEvent::LineAdded(e) => e.occurred_at,
+            SalesOrderEvent::LineRemoved(e) => e.occurred_at,
             SalesOrderEvent::OrderConfirmed(e) => e.occurred_at,
             SalesOrderEvent::OrderInvoiced(e) => e.occurred_at,
+            SalesOrderEvent::OrderCancelled(e) => e.occurred_at,
+            SalesOrderEvent::OrderBackordered(e) => e.occurred_at,
+            SalesOrderEvent::OrderFlagged(e) => e.occurred_at,
         }
     }
 }
@@ -226,6 +343,7 @@ impl Aggregate for SalesOrder {
                 self.tenant_id = Some(e.tenant_id);
                 self.status = SalesOrderStatus::Draft;
                 self.lines.clear();
+                self.next_line_no = 1;
                 self.created = true;
             }
             SalesOrderEvent::LineAdded(e) => {
@@ -236,6 +354,10 @@ impl Aggregate for SalesOrder {
                     unit_price: e.unit_price,
                 };
                 self.lines.push(line);
+                self.next_line_no = self.next_line_no.max(e.line_no + 1);
+            }
+            SalesOrderEvent::LineRemoved(e) => {
+                self.lines.retain(|l| l.line_no != e.line_no);
             }
             SalesOrderEvent::OrderConfirmed(_) => {
                 self.status = SalesOrderStatus::Confirmed;
@@ -243,6 +365,19 @@ impl Aggregate for SalesOrder {
             SalesOrderEvent::OrderInvoiced(_) => {
                 self.status = SalesOrderStatus::Invoiced;
             }
+            SalesOrderEvent::OrderCancelled(_) => {
+                self.status = SalesOrderStatus::Cancelled;
+            }
+            SalesOrderEvent::OrderBackordered(e) => {
+                for line_no in &e.line_nos {
+                    if !self.backordered_lines.contains(line_no) {
+                        self.backordered_lines.push(*line_no);
+                    }
+                }
+            }
+            SalesOrderEvent::OrderFlagged(e) => {
+                self.flag_reason = Some(e.reason.clone());
+            }
         }
 
         // Deterministic version tracking: +1 per applied event.
@@ -253,8 +388,12 @@ impl Aggregate for SalesOrder {
         match command {
             SalesOrderCommand::CreateSalesOrder(cmd) => self.handle_create(cmd),
             SalesOrderCommand::AddLine(cmd) => self.handle_add_line(cmd),
+            SalesOrderCommand::RemoveLine(cmd) => self.handle_remove_line(cmd),
             SalesOrderCommand::ConfirmOrder(cmd) => self.handle_confirm(cmd),
             SalesOrderCommand::MarkInvoiced(cmd) => self.handle_mark_invoiced(cmd),
+            SalesOrderCommand::CancelOrder(cmd) => self.handle_cancel(cmd),
+            SalesOrderCommand::MarkBackordered(cmd) => self.handle_mark_backordered(cmd),
+            SalesOrderCommand::FlagOrder(cmd) => self.handle_flag(cmd),
         }
     }
 }
@@ -313,12 +452,10 @@ impl SalesOrder {
             return Err(DomainError::validation("unit_price must be positive"));
         }
 
-        let next_line_no = (self.lines.len() as u32) + 1;
-
         Ok(vec![SalesOrderEvent::LineAdded(LineAdded {
             tenant_id: cmd.tenant_id,
             order_id: cmd.order_id,
-            line_no: next_line_no,
+            line_no: self.next_line_no,
             product_id: cmd.product_id,
             quantity: cmd.quantity,
             unit_price: cmd.unit_price,
@@ -326,6 +463,30 @@ impl SalesOrder {
         })])
     }
 
+    fn handle_remove_line(&self, cmd: &RemoveLine) -> Result<Vec<SalesOrderEvent>, DomainError> {
+        if !self.created {
+            return Err(DomainError::not_found());
+        }
+        self.ensure_tenant(cmd.tenant_id)?;
+        self.ensure_order_id(cmd.order_id)?;
+
+        if !self.is_modifiable() {
+            return Err(DomainError::invariant(
+                "cannot modify order once it is confirmed or invoiced",
+            ));
+        }
+        if !self.lines.iter().any(|l| l.line_no == cmd.line_no) {
+            return Err(DomainError::not_found());
+        }
+
+        Ok(vec![SalesOrderEvent::LineRemoved(LineRemoved {
+            tenant_id: cmd.tenant_id,
+            order_id: cmd.order_id,
+            line_no: cmd.line_no,
+            occurred_at: cmd.occurred_at,
+        })])
+    }
+
     fn handle_confirm(
         &self,
         cmd: &ConfirmOrder,
@@ -351,6 +512,7 @@ impl SalesOrder {
         Ok(vec![SalesOrderEvent::OrderConfirmed(OrderConfirmed {
             tenant_id: cmd.tenant_id,
             order_id: cmd.order_id,
+            lines: self.lines.clone(),
             occurred_at: cmd.occurred_at,
         })])
     }
@@ -377,6 +539,85 @@ impl SalesOrder {
             occurred_at: cmd.occurred_at,
         })])
     }
+
+    fn handle_cancel(&self, cmd: &CancelOrder) -> Result<Vec<SalesOrderEvent>, DomainError> {
+        if !self.created {
+            return Err(DomainError::not_found());
+        }
+        self.ensure_tenant(cmd.tenant_id)?;
+        self.ensure_order_id(cmd.order_id)?;
+
+        match self.status {
+            SalesOrderStatus::Cancelled => {
+                return Err(DomainError::conflict("order is already cancelled").with_code(ORDER_ALREADY_CANCELLED));
+            }
+            SalesOrderStatus::Draft | SalesOrderStatus::Confirmed => {}
+            SalesOrderStatus::Invoiced | SalesOrderStatus::Closed => {
+                return Err(DomainError::invariant("cannot cancel an invoiced order"));
+            }
+        }
+
+        Ok(vec![SalesOrderEvent::OrderCancelled(OrderCancelled {
+            tenant_id: cmd.tenant_id,
+            order_id: cmd.order_id,
+            reason: cmd.reason.clone(),
+            was_confirmed: self.status == SalesOrderStatus::Confirmed,
+            lines: self.lines.clone(),
+            occurred_at: cmd.occurred_at,
+        })])
+    }
+
+    fn handle_mark_backordered(
+        &self,
+        cmd: &MarkBackordered,
+    ) -> Result<Vec<SalesOrderEvent>, DomainError> {
+        if !self.created {
+            return Err(DomainError::not_found());
+        }
+        self.ensure_tenant(cmd.tenant_id)?;
+        self.ensure_order_id(cmd.order_id)?;
+
+        if self.status != SalesOrderStatus::Confirmed {
+            return Err(DomainError::invariant(
+                "only confirmed orders can be backordered",
+            ));
+        }
+        if cmd.line_nos.is_empty() {
+            return Err(DomainError::validation("line_nos cannot be empty"));
+        }
+        if let Some(n) = cmd.line_nos.iter().find(|n| !self.lines.iter().any(|l| l.line_no == **n)) {
+            return Err(DomainError::validation(format!("unknown line {n}")));
+        }
+
+        Ok(vec![SalesOrderEvent::OrderBackordered(OrderBackordered {
+            tenant_id: cmd.tenant_id,
+            order_id: cmd.order_id,
+            line_nos: cmd.line_nos.clone(),
+            occurred_at: cmd.occurred_at,
+        })])
+    }
+
+    fn handle_flag(&self, cmd: &FlagOrder) -> Result<Vec<SalesOrderEvent>, DomainError> {
+        if !self.created {
+            return Err(DomainError::not_found());
+        }
+        self.ensure_tenant(cmd.tenant_id)?;
+        self.ensure_order_id(cmd.order_id)?;
+
+        if self.status != SalesOrderStatus::Confirmed {
+            return Err(DomainError::invariant("only confirmed orders can be flagged"));
+        }
+        if cmd.reason.trim().is_empty() {
+            return Err(DomainError::validation("reason cannot be empty"));
+        }
+
+        Ok(vec![SalesOrderEvent::OrderFlagged(OrderFlagged {
+            tenant_id: cmd.tenant_id,
+            order_id: cmd.order_id,
+            reason: cmd.reason.clone(),
+            occurred_at: cmd.occurred_at,
+        })])
+    }
 }
 
 #[cfg(test)]
@@ -729,6 +970,7 @@ mod tests {
         let event3 = SalesOrderEvent::OrderConfirmed(OrderConfirmed {
             tenant_id,
             order_id,
+            lines: vec![],
             occurred_at: test_time(),
         });
 
@@ -748,6 +990,150 @@ mod tests {
         assert_eq!(order1.tenant_id(), order2.tenant_id());
         assert_eq!(order1.status(), SalesOrderStatus::Confirmed);
     }
-}
 
+    fn confirmed_order(tenant_id: TenantId, order_id: SalesOrderId) -> SalesOrder {
+        let mut order = SalesOrder::empty(order_id);
+        let commands = [
+            SalesOrderCommand::CreateSalesOrder(CreateSalesOrder {
+                tenant_id,
+                order_id,
+                occurred_at: test_time(),
+            }),
+            SalesOrderCommand::AddLine(AddLine {
+                tenant_id,
+                order_id,
+                product_id: test_product_id(),
+                quantity: 3,
+                unit_price: 100,
+                occurred_at: test_time(),
+            }),
+            SalesOrderCommand::ConfirmOrder(ConfirmOrder {
+                tenant_id,
+                order_id,
+                occurred_at: test_time(),
+            }),
+        ];
+        for cmd in commands {
+            for e in order.handle(&cmd).unwrap() {
+                order.apply(&e);
+            }
+        }
+        order
+    }
 
+    #[test]
+    fn removed_lines_leave_the_order_and_their_numbers_are_not_reused() {
+        let tenant_id = test_tenant_id();
+        let order_id = test_order_id();
+        let mut order = SalesOrder::empty(order_id);
+        let add = |quantity| {
+            SalesOrderCommand::AddLine(AddLine {
+                tenant_id,
+                order_id,
+                product_id: test_product_id(),
+                quantity,
+                unit_price: 100,
+                occurred_at: test_time(),
+            })
+        };
+        let remove = |line_no| {
+            SalesOrderCommand::RemoveLine(RemoveLine {
+                tenant_id,
+                order_id,
+                line_no,
+                occurred_at: test_time(),
+            })
+        };
+        let create = SalesOrderCommand::CreateSalesOrder(CreateSalesOrder {
+            tenant_id,
+            order_id,
+            occurred_at: test_time(),
+        });
+        for cmd in [create, add(1), add(2), remove(2), add(3)] {
+            for e in order.handle(&cmd).unwrap() {
+                order.apply(&e);
+            }
+        }
+
+        let line_nos: Vec<u32> = order.lines().iter().map(|l| l.line_no).collect();
+        assert_eq!(line_nos, [1, 3]);
+        assert_eq!(order.handle(&remove(2)).unwrap_err(), DomainError::not_found());
+
+        let confirmed = confirmed_order(tenant_id, order_id);
+        assert!(matches!(confirmed.handle(&remove(1)), Err(DomainError::InvariantViolation(_))));
+    }
+
+    #[test]
+    fn cancelling_confirmed_order_carries_its_lines_and_cannot_be_repeated() {
+        let tenant_id = test_tenant_id();
+        let order_id = test_order_id();
+        let mut order = confirmed_order(tenant_id, order_id);
+        let cancel = SalesOrderCommand::CancelOrder(CancelOrder {
+            tenant_id,
+            order_id,
+            reason: Some("customer request".to_string()),
+            occurred_at: test_time(),
+        });
+
+        let events = order.handle(&cancel).unwrap();
+        match &events[..] {
+            [SalesOrderEvent::OrderCancelled(e)] => {
+                assert!(e.was_confirmed);
+                assert_eq!(e.lines.len(), 1);
+                assert_eq!(e.lines[0].quantity, 3);
+            }
+            other => panic!("Expected OrderCancelled event, got {other:?}"),
+        }
+        order.apply(&events[0]);
+
+        assert_eq!(order.status(), SalesOrderStatus::Cancelled);
+        assert_eq!(order.handle(&cancel).unwrap_err().code(), Some(ORDER_ALREADY_CANCELLED));
+    }
+
+    #[test]
+    fn invoiced_order_cannot_be_cancelled() {
+        let tenant_id = test_tenant_id();
+        let order_id = test_order_id();
+        let mut order = confirmed_order(tenant_id, order_id);
+        let invoiced = order
+            .handle(&SalesOrderCommand::MarkInvoiced(MarkInvoiced {
+                tenant_id,
+                order_id,
+                occurred_at: test_time(),
+            }))
+            .unwrap();
+        order.apply(&invoiced[0]);
+
+        let err = order
+            .handle(&SalesOrderCommand::CancelOrder(CancelOrder {
+                tenant_id,
+                order_id,
+                reason: None,
+                occurred_at: test_time(),
+            }))
+            .unwrap_err();
+        assert!(matches!(err, DomainError::InvariantViolation(_)));
+    }
+
+    #[test]
+    fn only_confirmed_orders_can_be_flagged() {
+        let tenant_id = test_tenant_id();
+        let order_id = test_order_id();
+        let flag = SalesOrderCommand::FlagOrder(FlagOrder {
+            tenant_id,
+            order_id,
+            reason: "invoice not issued".to_string(),
+            occurred_at: test_time(),
+        });
+
+        let mut order = confirmed_order(tenant_id, order_id);
+        for e in order.handle(&flag).unwrap() {
+            order.apply(&e);
+        }
+        assert_eq!(order.flag_reason(), Some("invoice not issued"));
+        assert_eq!(order.status(), SalesOrderStatus::Confirmed);
+
+        let draft = SalesOrder::empty(order_id);
+        assert_eq!(draft.handle(&flag).unwrap_err(), DomainError::not_found());
+    }
+}
@@ -0,0 +1,8 @@
+-- Event Metadata
+--
+-- Adds request-level metadata to stored events (e.g. the platform admin and home
+-- tenant when a command was issued via a cross-tenant override). Domain payloads are
+-- unchanged; existing events get an empty object.
+
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS metadata JSONB NOT NULL DEFAULT '{}'::jsonb;
@@ -0,0 +1,9 @@
+-- Read Model Watermarks
+--
+-- Records the sequence number of the last event applied to each inventory_stock
+-- row. Together with updated_at (now the storage time of that event rather than
+-- the time of the write) it lets polling clients detect changes without diffing.
+-- Existing rows start at 0 until their next update or a rebuild.
+
+ALTER TABLE inventory_stock
+    ADD COLUMN IF NOT EXISTS last_sequence BIGINT NOT NULL DEFAULT 0;
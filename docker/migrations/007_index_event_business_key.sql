@@ -0,0 +1,10 @@
+-- Business Key Index
+--
+-- Events dispatched under an `X-Business-Key` record it as metadata->>'business_key'.
+-- `GET /admin/events?business_key=` returns every event of that business process
+-- across aggregates (a sales order, its invoice, its ledger postings); this partial
+-- expression index serves that lookup without scanning the tenant's events.
+
+CREATE INDEX IF NOT EXISTS idx_events_business_key
+    ON events (tenant_id, (metadata->>'business_key'), occurred_at DESC)
+    WHERE metadata ? 'business_key';
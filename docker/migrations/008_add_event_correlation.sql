@@ -0,0 +1,13 @@
+-- Event Correlation
+--
+-- Records which flow an event belongs to (`correlation_id`, shared by a request's
+-- command and every saga follow-up) and which command produced it (`causation_id`).
+-- Existing events keep NULL in both columns.
+
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS correlation_id UUID,
+    ADD COLUMN IF NOT EXISTS causation_id UUID;
+
+CREATE INDEX IF NOT EXISTS idx_events_correlation
+    ON events (tenant_id, correlation_id, occurred_at)
+    WHERE correlation_id IS NOT NULL;
@@ -0,0 +1,17 @@
+-- Global Event Order
+--
+-- `global_seq` gives every event a position in one store-wide order, so a projection
+-- can be rebuilt by reading a tenant's events in order (`EventStore::read_all`)
+-- instead of replaying through the bus.
+--
+-- Positions are allocated when the row is inserted, not at commit. Appends therefore
+-- take a per-tenant advisory lock (`pg_advisory_xact_lock`) before inserting and hold
+-- it until commit, so a tenant's positions become visible in order and a reader paging
+-- by `global_seq` never skips a row that commits late.
+-- Existing rows are numbered in table scan order when the column is added.
+
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS global_seq BIGSERIAL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_events_tenant_global_seq
+    ON events (tenant_id, global_seq);
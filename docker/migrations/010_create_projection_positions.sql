@@ -0,0 +1,16 @@
+-- Projection Subscriber Positions
+--
+-- The global position (`events.global_seq`) of the last event a projection subscriber
+-- handled per tenant. At startup the subscriber reads the tenant's events after it
+-- (`EventStore::read_all`) instead of re-scanning the whole history.
+--
+-- Positions only move forward; clearing a projection's offsets for a rebuild also
+-- deletes its position.
+
+CREATE TABLE IF NOT EXISTS projection_positions (
+    tenant_id UUID NOT NULL,
+    projection_name TEXT NOT NULL,
+    global_position BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, projection_name)
+);
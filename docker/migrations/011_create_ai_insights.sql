@@ -0,0 +1,38 @@
+-- AI Insights
+--
+-- Results of the AI runners (anomalies, forecasts, reorder suggestions) per tenant.
+-- They are insights, not domain events: nothing rebuilds them, so they are kept here
+-- to survive restarts and are dropped after the configured retention.
+--
+-- A result with an idempotency key replaces the earlier result with the same key;
+-- results without one are always appended.
+
+CREATE TABLE IF NOT EXISTS ai_insights (
+    insight_id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+
+    -- Insight kind from metadata.kind (e.g. "inventory.anomaly_detection")
+    kind TEXT NOT NULL,
+    idempotency_key TEXT,
+
+    score DOUBLE PRECISION NOT NULL,
+    confidence DOUBLE PRECISION NOT NULL,
+    explanation TEXT,
+    metadata JSONB NOT NULL DEFAULT '{}'::jsonb,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Reads: one kind of insight for a tenant
+CREATE INDEX IF NOT EXISTS idx_ai_insights_tenant_kind
+    ON ai_insights (tenant_id, kind);
+
+-- Replacement of keyed insights
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ai_insights_idempotency
+    ON ai_insights (tenant_id, idempotency_key)
+    WHERE idempotency_key IS NOT NULL;
+
+-- Retention purge
+CREATE INDEX IF NOT EXISTS idx_ai_insights_updated_at
+    ON ai_insights (tenant_id, updated_at);